@@ -103,6 +103,8 @@ pub enum ResourceKind {
     ReplicaSet,
     Endpoint,
     Job,
+    DaemonSet,
+    Node,
     #[default]
     Unknown,
 }
@@ -116,6 +118,8 @@ impl fmt::Display for ResourceKind {
             ResourceKind::ReplicaSet => "ReplicaSet",
             ResourceKind::Endpoint => "Endpoint",
             ResourceKind::Job => "Job",
+            ResourceKind::DaemonSet => "DaemonSet",
+            ResourceKind::Node => "Node",
             ResourceKind::Unknown => "Unknown",
         };
         write!(f, "{}", kind)
@@ -130,6 +134,8 @@ impl From<&str> for ResourceKind {
             "ReplicaSet" => ResourceKind::ReplicaSet,
             "Endpoint" => ResourceKind::Endpoint,
             "Job" => ResourceKind::Job,
+            "DaemonSet" => ResourceKind::DaemonSet,
+            "Node" => ResourceKind::Node,
             _ => ResourceKind::Unknown, // Default to Unknown for unknown kinds
         }
     }
@@ -1847,6 +1853,43 @@ pub struct ReplicaSet {
     pub status: ReplicaSetStatus,
 }
 
+/// Runs one pod from `template` on every node whose labels match `node_selector`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DaemonSetSpec {
+    pub selector: LabelSelector,
+    pub template: PodTemplateSpec,
+    /// Node labels a node must carry to run the pod; empty selects every node.
+    #[serde(rename = "nodeSelector", default)]
+    pub node_selector: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct DaemonSetStatus {
+    /// Nodes that should run the pod.
+    #[serde(rename = "desiredNumberScheduled", default)]
+    pub desired_number_scheduled: i32,
+    /// Nodes that run the pod and should.
+    #[serde(rename = "currentNumberScheduled", default)]
+    pub current_number_scheduled: i32,
+    /// Nodes that run the pod but should not.
+    #[serde(rename = "numberMisscheduled", default)]
+    pub number_misscheduled: i32,
+    #[serde(rename = "numberReady", default)]
+    pub number_ready: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DaemonSet {
+    #[serde(rename = "apiVersion")]
+    pub api_version: String,
+    #[serde(rename = "kind")]
+    pub kind: String,
+    pub metadata: ObjectMeta,
+    pub spec: DaemonSetSpec,
+    #[serde(default)]
+    pub status: DaemonSetStatus,
+}
+
 /// Endpoint related types (similar to Kubernetes Endpoints)
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct EndpointPort {
@@ -143,10 +143,12 @@ async fn handle_pod_update(
                         }
                     }
                     EventType::Delete => {
-                        let name = String::from_utf8_lossy(kv.key()).to_string();
-                        let node_name = name.split('/').rfind(|s| !s.is_empty());
-                        if let Some(n) = node_name {
-                            scheduler.remove_cache_pod(n).await;
+                        let key = String::from_utf8_lossy(kv.key()).to_string();
+                        let pod_key = key.trim_start_matches("/registry/pods/");
+                        // default-namespace pods are cached under their bare name
+                        let name = pod_key.strip_prefix("default/").unwrap_or(pod_key);
+                        if !name.is_empty() {
+                            scheduler.remove_cache_pod(name).await;
                         }
                     }
                 }
@@ -104,8 +104,15 @@ pub fn convert_pod_task_to_pod_info(pod_task: PodTask) -> PodInfo {
         affinity: pod_task.spec.affinity.map(crate::models::Affinity::from),
     };
 
+    // Pods outside the default namespace are identified by their `<namespace>/<name>`
+    // key so that same-named pods in different namespaces stay distinct.
+    let name = match pod_task.metadata.namespace.as_str() {
+        "" | "default" => pod_task.metadata.name,
+        namespace => format!("{namespace}/{}", pod_task.metadata.name),
+    };
+
     PodInfo {
-        name: pod_task.metadata.name,
+        name,
         labels: pod_task.metadata.labels,
         spec,
         queued_info: QueuedInfo::default(),
@@ -43,6 +43,13 @@ pub struct Config {
     #[serde(default = "default_mounts_monitor_interval")]
     #[default(5)]
     pub mounts_monitor_interval: u64,
+    /// Seconds a request may run before it fails with a timeout, 0 for no limit.
+    #[serde(default)]
+    pub request_timeout: u64,
+    /// Token for the transit key of an auto-unseal seal config. `VAULT_TRANSIT_SEAL_TOKEN`
+    /// overrides it.
+    #[serde(default)]
+    pub transit_seal_token: String,
 }
 
 /// Helper enum to control mount entry HMAC verification level.
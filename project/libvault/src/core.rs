@@ -11,27 +11,33 @@
 
 use anyhow::anyhow;
 use arc_swap::{ArcSwap, ArcSwapOption};
+use base64::{Engine, engine::general_purpose::STANDARD};
 use go_defer::defer;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
 use std::collections::VecDeque;
 use std::{
+    future,
     ops::{Deref, DerefMut},
     sync::{Arc, Weak},
+    time::Duration,
 };
 use zeroize::{Zeroize, Zeroizing};
 
 use crate::{
     config::MountEntryHMACLevel,
     errors::RvError,
-    handler::{AuthHandler, HandlePhase, Handler},
-    logical::{Backend, Request, Response},
+    handler::{AuthHandler, HandlePhase, Handler, RequestHook},
+    logical::{Backend, Operation, Request, Response},
     module_manager::ModuleManager,
-    modules::auth::AuthModule,
+    modules::{auth::AuthModule, system::audit::AuditBroker},
     mount::{
         CORE_MOUNT_CONFIG_PATH, LOGICAL_BARRIER_PREFIX, MountTable, MountsMonitor, MountsRouter,
         SYSTEM_BARRIER_PREFIX,
     },
+    namespace::{self, NamespaceStore},
     router::Router,
+    seal::{SealCipher, TransitSealConfig},
     shamir::{SHAMIR_OVERHEAD, ShamirSecret},
     storage::{
         Backend as PhysicalBackend, BackendEntry as PhysicalBackendEntry, barrier::SecurityBarrier,
@@ -45,15 +51,39 @@ pub type LogicalBackendNewFunc =
 
 const SEAL_CONFIG_PATH: &str = "core/seal-config";
 const DEPRECATED_UNSEAL_KEY_SET_PATH: &str = "core/used-unseal-keys-set";
+const TRANSIT_WRAPPED_KEY_PATH: &str = "core/transit-wrapped-key";
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Length of the one-time password a generate-root attempt is started with. Root tokens are
+/// UUIDs, and the OTP is XORed over the token byte for byte.
+pub const GENERATE_ROOT_OTP_LENGTH: usize = 36;
+
+/// How the KEK is protected while the vault is sealed.
+///
+/// By default the KEK is split into `secret_shares` Shamir shares. Setting `transit` instead wraps
+/// the KEK with a transit key of another vault so that `auto_unseal` can unseal without operators;
+/// the share counts must then be left at zero.
+///
+/// `cipher` picks the AEAD the master key is wrapped with. It only matters at init: unseal reads
+/// the cipher back from the sealed blob.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct SealConfig {
     pub secret_shares: u8,
     pub secret_threshold: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transit: Option<TransitSealConfig>,
+    #[serde(default)]
+    pub cipher: SealCipher,
 }
 
 impl SealConfig {
     pub fn validate(&self) -> Result<(), RvError> {
+        if let Some(transit) = &self.transit {
+            if self.secret_shares != 0 || self.secret_threshold != 0 {
+                return Err(RvError::ErrCoreSealConfigConflict);
+            }
+            return transit.validate();
+        }
+
         if self.secret_threshold > self.secret_shares {
             return Err(RvError::ErrCoreSealConfigInvalid);
         }
@@ -84,6 +114,13 @@ pub struct CoreState {
     pub hmac_key: Vec<u8>,
     unseal_key_shares: Vec<Vec<u8>>,
     kek: Vec<u8>,
+    /// Share layout requested by `rekey_init`, set while a rekey is in progress.
+    #[zeroize(skip)]
+    rekey_config: Option<SealConfig>,
+    rekey_key_shares: Vec<Vec<u8>>,
+    /// One-time password of the generate-root attempt in progress.
+    generate_root_otp: Option<String>,
+    generate_root_key_shares: Vec<Vec<u8>>,
 }
 
 pub struct Core {
@@ -94,10 +131,17 @@ pub struct Core {
     pub router: Arc<Router>,
     pub handlers: ArcSwap<Vec<Arc<dyn Handler>>>,
     pub auth_handlers: ArcSwap<Vec<Arc<dyn AuthHandler>>>,
+    pub request_hooks: ArcSwap<Vec<Arc<dyn RequestHook>>>,
     pub module_manager: ModuleManager,
     pub mount_entry_hmac_level: MountEntryHMACLevel,
+    pub audit_broker: AuditBroker,
     pub mounts_monitor: ArcSwapOption<MountsMonitor>,
     pub mounts_monitor_interval: u64,
+    /// Time limit of requests that do not set their own `Request::timeout`. `None` waits forever.
+    pub request_timeout: Option<Duration>,
+    /// Token for `SealConfig::transit`, from the server config. It is never persisted.
+    pub transit_seal_token: String,
+    pub namespaces: Arc<NamespaceStore>,
     pub state: ArcSwap<CoreState>,
 }
 
@@ -109,6 +153,10 @@ impl Default for CoreState {
             unseal_key_shares: Vec::new(),
             hmac_key: Vec::new(),
             kek: Vec::new(),
+            rekey_config: None,
+            rekey_key_shares: Vec::new(),
+            generate_root_otp: None,
+            generate_root_key_shares: Vec::new(),
         }
     }
 }
@@ -133,10 +181,15 @@ impl Default for Core {
             )),
             handlers: ArcSwap::from_pointee(vec![router]),
             auth_handlers: ArcSwap::from_pointee(Vec::new()),
+            request_hooks: ArcSwap::from_pointee(Vec::new()),
             module_manager: ModuleManager::new(),
             mount_entry_hmac_level: MountEntryHMACLevel::None,
+            audit_broker: AuditBroker::default(),
             mounts_monitor: ArcSwapOption::empty(),
             mounts_monitor_interval: 0,
+            request_timeout: None,
+            transit_seal_token: String::new(),
+            namespaces: Arc::new(NamespaceStore::default()),
             state: ArcSwap::from_pointee(CoreState::default()),
         }
     }
@@ -234,8 +287,24 @@ impl Core {
         // Generate a key encryption key, will be zeroized on drop.
         let kek = barrier.generate_key()?;
 
+        // Wrap the KEK before the barrier exists, so a failing transit engine leaves the vault
+        // uninitialized instead of initialized with a KEK nobody can recover.
+        if let Some(transit) = &seal_config.transit {
+            let pe = PhysicalBackendEntry {
+                key: TRANSIT_WRAPPED_KEY_PATH.to_string(),
+                value: self
+                    .transit_seal(transit)
+                    .wrap(kek.as_slice())
+                    .await?
+                    .into_bytes(),
+            };
+            self.physical.put(&pe).await?;
+        }
+
         // Initialize the barrier
-        barrier.init(kek.deref().as_slice()).await?;
+        barrier
+            .init(kek.deref().as_slice(), seal_config.cipher)
+            .await?;
 
         let mut init_result = InitResult {
             secret_shares: Zeroizing::new(Vec::new()),
@@ -257,7 +326,9 @@ impl Core {
         state.kek = kek.deref().clone();
         self.state.store(Arc::new(state));
 
-        if seal_config.secret_shares == 1 {
+        if seal_config.transit.is_some() {
+            // Auto-unseal: there are no shares to hand out.
+        } else if seal_config.secret_shares == 1 {
             init_result
                 .secret_shares
                 .deref_mut()
@@ -338,6 +409,26 @@ impl Core {
         Ok(())
     }
 
+    pub fn add_request_hook(&self, hook: Arc<dyn RequestHook>) -> Result<(), RvError> {
+        let hooks = self.request_hooks.load();
+        if hooks.iter().any(|h| h.name() == hook.name()) {
+            return Err(RvError::ErrCoreHandlerExist);
+        }
+
+        let mut hooks = (*self.request_hooks.load_full()).clone();
+
+        hooks.push(hook);
+        self.request_hooks.store(Arc::new(hooks));
+        Ok(())
+    }
+
+    pub fn delete_request_hook(&self, hook: Arc<dyn RequestHook>) -> Result<(), RvError> {
+        let mut hooks = (*self.request_hooks.load_full()).clone();
+        hooks.retain(|h| h.name() != hook.name());
+        self.request_hooks.store(Arc::new(hooks));
+        Ok(())
+    }
+
     pub fn add_auth_handler(&self, auth_handler: Arc<dyn AuthHandler>) -> Result<(), RvError> {
         let auth_handlers = self.auth_handlers.load();
         if auth_handlers
@@ -399,10 +490,51 @@ impl Core {
         self.state.load().sealed
     }
 
+    /// Liveness summary served at `sys/health`.
+    pub async fn health(&self) -> Result<Map<String, Value>, RvError> {
+        let data = json!({
+            "initialized": self.inited().await?,
+            "sealed": self.sealed(),
+            "version": crate::VERSION,
+        });
+
+        Ok(data.as_object().cloned().unwrap_or_default())
+    }
+
+    /// Seal state served at `sys/seal-status`. Only counts are reported, never key material.
+    pub async fn seal_status(&self) -> Result<Map<String, Value>, RvError> {
+        let initialized = self.inited().await?;
+        let config = if initialized {
+            self.seal_config().await?
+        } else {
+            SealConfig::default()
+        };
+
+        let data = json!({
+            "type": if config.transit.is_some() { "transit" } else { "shamir" },
+            "initialized": initialized,
+            "sealed": self.sealed(),
+            "t": config.secret_threshold,
+            "n": config.secret_shares,
+            "progress": self.unseal_progress(),
+            "version": crate::VERSION,
+        });
+
+        Ok(data.as_object().cloned().unwrap_or_default())
+    }
+
     pub fn unseal_progress(&self) -> usize {
         self.state.load().unseal_key_shares.len()
     }
 
+    /// Discards the unseal keys supplied so far, so the next key starts a fresh attempt.
+    pub fn unseal_reset(&self) -> Result<(), RvError> {
+        let mut state = (*self.state.load_full()).clone();
+        state.unseal_key_shares.clear();
+        self.state.store(Arc::new(state));
+        Ok(())
+    }
+
     pub async fn do_unseal(&self, key: &[u8], once: bool) -> Result<bool, RvError> {
         let inited = self.barrier.inited().await?;
         if !inited {
@@ -422,6 +554,9 @@ impl Core {
 
         let mut state = (*self.state.load_full()).clone();
         let config = self.seal_config().await?;
+        if config.transit.is_some() {
+            return Err(RvError::ErrCoreSealAutoUnseal);
+        }
         if state.unseal_key_shares.iter().any(|v| *v == key) {
             return Ok(false);
         }
@@ -451,6 +586,33 @@ impl Core {
             return Err(RvError::ErrBarrierKeyInvalid);
         }
 
+        let unseal_key_shares = Zeroizing::new(state.unseal_key_shares.clone());
+        self.unseal_with_kek(state, kek).await?;
+
+        if once && let Ok(deprecated_key_set) = &mut deprecated_key_set {
+            for key in unseal_key_shares.iter() {
+                deprecated_key_set.insert(key);
+            }
+
+            let pe = PhysicalBackendEntry {
+                key: DEPRECATED_UNSEAL_KEY_SET_PATH.to_string(),
+                value: serde_json::to_string(deprecated_key_set)?
+                    .as_bytes()
+                    .to_vec(),
+            };
+            self.physical.put(&pe).await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Unseal the barrier with the fully recovered KEK and bring the core up. `state` is the
+    /// working copy of the core state; pending unseal shares in it are dropped either way.
+    async fn unseal_with_kek(
+        &self,
+        mut state: CoreState,
+        kek: Zeroizing<Vec<u8>>,
+    ) -> Result<(), RvError> {
         // Unseal the barrier
         if let Err(e) = self.barrier.unseal(kek.as_slice()).await {
             state.unseal_key_shares.clear();
@@ -458,7 +620,6 @@ impl Core {
             return Err(e);
         }
 
-        let unseal_key_shares = Zeroizing::new(state.unseal_key_shares.clone());
         state.unseal_key_shares.clear();
         state.hmac_key = self.barrier.derive_hmac_key()?;
         state.system_view = Some(Arc::new(BarrierView::new(
@@ -481,25 +642,55 @@ impl Core {
             return Err(e);
         }
 
-        if once && let Ok(deprecated_key_set) = &mut deprecated_key_set {
-            for key in unseal_key_shares.iter() {
-                deprecated_key_set.insert(key);
-            }
+        Ok(())
+    }
 
-            let pe = PhysicalBackendEntry {
-                key: DEPRECATED_UNSEAL_KEY_SET_PATH.to_string(),
-                value: serde_json::to_string(deprecated_key_set)?
-                    .as_bytes()
-                    .to_vec(),
-            };
-            self.physical.put(&pe).await?;
+    pub async fn unseal(&self, key: &[u8]) -> Result<bool, RvError> {
+        self.do_unseal(key, false).await
+    }
+
+    /// Unseal using the transit key configured in the seal config, without any key shares.
+    ///
+    /// The KEK wrapped at init time is read back from physical storage and sent to the remote
+    /// transit engine for decryption.
+    ///
+    /// # Errors
+    /// - Returns `RvError::ErrCoreSealNotAutoUnseal` if the vault was initialized with Shamir shares
+    /// - Returns `RvError::ErrCoreSealTransitKeyNotFound` if the wrapped KEK is missing
+    /// - Returns errors from the remote transit engine if it refuses to decrypt
+    pub async fn auto_unseal(&self) -> Result<(), RvError> {
+        let inited = self.barrier.inited().await?;
+        if !inited {
+            return Err(RvError::ErrBarrierNotInit);
         }
 
-        Ok(true)
+        let sealed = self.barrier.sealed()?;
+        if !sealed {
+            return Err(RvError::ErrBarrierUnsealed);
+        }
+
+        let config = self.seal_config().await?;
+        let transit = config.transit.ok_or(RvError::ErrCoreSealNotAutoUnseal)?;
+
+        let pe = self
+            .physical
+            .get(TRANSIT_WRAPPED_KEY_PATH)
+            .await?
+            .ok_or(RvError::ErrCoreSealTransitKeyNotFound)?;
+        let ciphertext =
+            String::from_utf8(pe.value).map_err(|_| RvError::ErrCoreSealTransitKeyNotFound)?;
+        let kek = self.transit_seal(&transit).unwrap(&ciphertext).await?;
+
+        let state = (*self.state.load_full()).clone();
+        self.unseal_with_kek(state, kek).await
     }
 
-    pub async fn unseal(&self, key: &[u8]) -> Result<bool, RvError> {
-        self.do_unseal(key, false).await
+    /// `transit` with the token from the server config, whatever the caller put in it.
+    fn transit_seal(&self, transit: &TransitSealConfig) -> TransitSealConfig {
+        TransitSealConfig {
+            token: self.transit_seal_token.clone(),
+            ..transit.clone()
+        }
     }
 
     /// Unseals the libvault once and immediately generates new unseal keys.
@@ -558,6 +749,10 @@ impl Core {
         state.unseal_key_shares.clear();
         state.hmac_key.clear();
         state.kek.clear();
+        state.rekey_config = None;
+        state.rekey_key_shares.clear();
+        state.generate_root_otp.zeroize();
+        state.generate_root_key_shares.clear();
         self.state.store(Arc::new(state));
 
         self.barrier.seal()
@@ -598,6 +793,9 @@ impl Core {
         }
 
         let config = self.seal_config().await?;
+        if config.transit.is_some() {
+            return Err(RvError::ErrCoreSealAutoUnseal);
+        }
         ShamirSecret::split(
             kek.as_slice(),
             config.secret_shares,
@@ -605,9 +803,323 @@ impl Core {
         )
     }
 
+    /// Starts rekeying an unsealed vault: the KEK will be re-split into `new_shares` shares, any
+    /// `new_threshold` of which unseal it. The current unseal keys are then supplied one at a time
+    /// through `rekey_update`.
+    ///
+    /// # Errors
+    /// - Returns `RvError::ErrBarrierSealed` if the vault is sealed
+    /// - Returns `RvError::ErrCoreSealAutoUnseal` if the vault uses transit auto-unseal
+    /// - Returns `RvError::ErrCoreSealConfigInvalid` if the new share layout can't be split
+    /// - Returns `RvError::ErrCoreRekeyInProgress` if a rekey was already started
+    pub async fn rekey_init(&self, new_shares: u8, new_threshold: u8) -> Result<(), RvError> {
+        if self.sealed() {
+            return Err(RvError::ErrBarrierSealed);
+        }
+
+        let config = self.seal_config().await?;
+        if config.transit.is_some() {
+            return Err(RvError::ErrCoreSealAutoUnseal);
+        }
+
+        // A single share is the KEK itself; anything else has to be a valid Shamir split.
+        if !(new_shares == 1 && new_threshold == 1)
+            && (new_threshold < 2 || new_shares < new_threshold || new_shares == 255)
+        {
+            return Err(RvError::ErrCoreSealConfigInvalid);
+        }
+
+        let mut state = (*self.state.load_full()).clone();
+        if state.rekey_config.is_some() {
+            return Err(RvError::ErrCoreRekeyInProgress);
+        }
+
+        state.rekey_config = Some(SealConfig {
+            secret_shares: new_shares,
+            secret_threshold: new_threshold,
+            ..Default::default()
+        });
+        state.rekey_key_shares.clear();
+        self.state.store(Arc::new(state));
+
+        Ok(())
+    }
+
+    /// Supplies one current unseal key to a rekey started by `rekey_init`.
+    ///
+    /// Returns `Ok(None)` until the current threshold is reached. Once it is, the recovered KEK is
+    /// checked against the one the vault is running with, and a new KEK is generated, split into
+    /// the new share set, and wrapped around the barrier keyring, so the old shares no longer
+    /// unseal the vault. The keys presented are also retired through the deprecated unseal key set,
+    /// as `unseal_once` does, and the new shares are returned.
+    ///
+    /// # Errors
+    /// - Returns `RvError::ErrCoreRekeyNotStarted` if no rekey is in progress
+    /// - Returns `RvError::ErrBarrierKeyDeprecated` if the key was already retired
+    /// - Returns `RvError::ErrBarrierKeyInvalid` if the keys don't recover the current KEK; the
+    ///   keys supplied so far are discarded but the rekey stays in progress
+    pub async fn rekey_update(
+        &self,
+        key: &[u8],
+    ) -> Result<Option<Zeroizing<Vec<Vec<u8>>>>, RvError> {
+        let mut state = (*self.state.load_full()).clone();
+        if state.sealed {
+            return Err(RvError::ErrBarrierSealed);
+        }
+
+        let new_config = state
+            .rekey_config
+            .clone()
+            .ok_or(RvError::ErrCoreRekeyNotStarted)?;
+
+        let (min, mut max) = self.barrier.key_length_range();
+        max += SHAMIR_OVERHEAD;
+        if key.len() < min || key.len() > max {
+            return Err(RvError::ErrBarrierKeyInvalid);
+        }
+
+        if state.rekey_key_shares.iter().any(|v| *v == key) {
+            return Ok(None);
+        }
+
+        let mut deprecated_key_set = self.deprecated_unseal_keys_set().await;
+        if let Ok(deprecated_key_set) = &deprecated_key_set
+            && deprecated_key_set.contains(key)
+        {
+            return Err(RvError::ErrBarrierKeyDeprecated);
+        }
+
+        let config = self.seal_config().await?;
+        state.rekey_key_shares.push(key.to_vec());
+        if state.rekey_key_shares.len() < config.secret_threshold as usize {
+            self.state.store(Arc::new(state));
+            return Ok(None);
+        }
+
+        let presented = Zeroizing::new(std::mem::take(&mut state.rekey_key_shares));
+        let kek = if config.secret_threshold == 1 {
+            Some(Zeroizing::new(presented[0].clone()))
+        } else {
+            ShamirSecret::combine(presented.to_vec()).map(Zeroizing::new)
+        };
+
+        if kek.is_none_or(|kek| kek.as_slice() != state.kek.as_slice()) {
+            self.state.store(Arc::new(state));
+            return Err(RvError::ErrBarrierKeyInvalid);
+        }
+
+        let new_kek = self.barrier.generate_key()?;
+        let new_shares = if new_config.secret_shares == 1 {
+            Zeroizing::new(vec![new_kek.to_vec()])
+        } else {
+            ShamirSecret::split(
+                new_kek.as_slice(),
+                new_config.secret_shares,
+                new_config.secret_threshold,
+            )?
+        };
+
+        // The keyring is rewrapped with the cipher it was sealed with.
+        let new_config = SealConfig {
+            cipher: config.cipher,
+            ..new_config
+        };
+        let pe = PhysicalBackendEntry {
+            key: SEAL_CONFIG_PATH.to_string(),
+            value: serde_json::to_string(&new_config)?.as_bytes().to_vec(),
+        };
+        self.physical.put(&pe).await?;
+
+        // Rewrapping the keyring is what switches the vault over to the new shares. Should it fail,
+        // the old shares still unseal, so put their seal config back.
+        if let Err(e) = self.barrier.rekey(new_kek.as_slice()).await {
+            let pe = PhysicalBackendEntry {
+                key: SEAL_CONFIG_PATH.to_string(),
+                value: serde_json::to_string(&config)?.as_bytes().to_vec(),
+            };
+            self.physical.put(&pe).await?;
+            return Err(e);
+        }
+        state.kek = new_kek.to_vec();
+
+        if let Ok(deprecated_key_set) = &mut deprecated_key_set {
+            for key in presented.iter() {
+                deprecated_key_set.insert(key);
+            }
+
+            let pe = PhysicalBackendEntry {
+                key: DEPRECATED_UNSEAL_KEY_SET_PATH.to_string(),
+                value: serde_json::to_string(deprecated_key_set)?
+                    .as_bytes()
+                    .to_vec(),
+            };
+            self.physical.put(&pe).await?;
+        }
+
+        state.rekey_config = None;
+        self.state.store(Arc::new(state));
+
+        Ok(Some(new_shares))
+    }
+
+    /// Abandons a rekey in progress, wiping the unseal keys supplied so far.
+    pub fn rekey_cancel(&self) -> Result<(), RvError> {
+        let mut state = (*self.state.load_full()).clone();
+        state.rekey_config = None;
+        state.rekey_key_shares.clear();
+        self.state.store(Arc::new(state));
+        Ok(())
+    }
+
+    /// Number of unseal keys supplied to the rekey in progress.
+    pub fn rekey_progress(&self) -> usize {
+        self.state.load().rekey_key_shares.len()
+    }
+
+    /// Starts a generate-root attempt, the recovery path for when every root token is lost. `otp`
+    /// must be `GENERATE_ROOT_OTP_LENGTH` bytes long; the new root token is only ever handed out
+    /// XORed with it.
+    ///
+    /// # Errors
+    /// - Returns `RvError::ErrBarrierSealed` if the vault is sealed
+    /// - Returns `RvError::ErrCoreSealAutoUnseal` if the vault uses transit auto-unseal
+    /// - Returns `RvError::ErrCoreGenerateRootOtpInvalid` if `otp` has the wrong length
+    /// - Returns `RvError::ErrCoreGenerateRootInProgress` if an attempt was already started
+    pub async fn generate_root_init(&self, otp: &str) -> Result<(), RvError> {
+        if self.sealed() {
+            return Err(RvError::ErrBarrierSealed);
+        }
+
+        let config = self.seal_config().await?;
+        if config.transit.is_some() {
+            return Err(RvError::ErrCoreSealAutoUnseal);
+        }
+
+        if otp.len() != GENERATE_ROOT_OTP_LENGTH {
+            return Err(RvError::ErrCoreGenerateRootOtpInvalid);
+        }
+
+        let mut state = (*self.state.load_full()).clone();
+        if state.generate_root_otp.is_some() {
+            return Err(RvError::ErrCoreGenerateRootInProgress);
+        }
+
+        state.generate_root_otp = Some(otp.to_string());
+        state.generate_root_key_shares.clear();
+        self.state.store(Arc::new(state));
+
+        Ok(())
+    }
+
+    /// Supplies one unseal key to the generate-root attempt in progress.
+    ///
+    /// Returns `Ok(None)` until the unseal threshold is reached. Once the keys recover the KEK the
+    /// vault is running with, a new root token is created and returned base64-encoded after being
+    /// XORed with the attempt's OTP; `decode_generated_root` reverses it.
+    ///
+    /// # Errors
+    /// - Returns `RvError::ErrCoreGenerateRootNotStarted` if no attempt is in progress
+    /// - Returns `RvError::ErrBarrierKeyDeprecated` if the key was already retired
+    /// - Returns `RvError::ErrBarrierKeyInvalid` if the keys don't recover the current KEK; the
+    ///   keys supplied so far are discarded but the attempt stays in progress
+    pub async fn generate_root_update(&self, key: &[u8]) -> Result<Option<String>, RvError> {
+        let mut state = (*self.state.load_full()).clone();
+        if state.sealed {
+            return Err(RvError::ErrBarrierSealed);
+        }
+
+        if state.generate_root_otp.is_none() {
+            return Err(RvError::ErrCoreGenerateRootNotStarted);
+        }
+
+        let (min, mut max) = self.barrier.key_length_range();
+        max += SHAMIR_OVERHEAD;
+        if key.len() < min || key.len() > max {
+            return Err(RvError::ErrBarrierKeyInvalid);
+        }
+
+        if state.generate_root_key_shares.iter().any(|v| *v == key) {
+            return Ok(None);
+        }
+
+        if let Ok(deprecated_key_set) = self.deprecated_unseal_keys_set().await
+            && deprecated_key_set.contains(key)
+        {
+            return Err(RvError::ErrBarrierKeyDeprecated);
+        }
+
+        let config = self.seal_config().await?;
+        state.generate_root_key_shares.push(key.to_vec());
+        if state.generate_root_key_shares.len() < config.secret_threshold as usize {
+            self.state.store(Arc::new(state));
+            return Ok(None);
+        }
+
+        let presented = Zeroizing::new(std::mem::take(&mut state.generate_root_key_shares));
+        let kek = if config.secret_threshold == 1 {
+            Some(Zeroizing::new(presented[0].clone()))
+        } else {
+            ShamirSecret::combine(presented.to_vec()).map(Zeroizing::new)
+        };
+
+        if !matches!(kek, Some(kek) if kek.as_slice() == state.kek.as_slice()) {
+            self.state.store(Arc::new(state));
+            return Err(RvError::ErrBarrierKeyInvalid);
+        }
+
+        let otp = Zeroizing::new(state.generate_root_otp.take().unwrap_or_default());
+        self.state.store(Arc::new(state));
+
+        let Some(auth_module) = self.module_manager.get_module::<AuthModule>("auth") else {
+            return Err(RvError::ErrModuleNotFound);
+        };
+        let Some(token_store) = auth_module.token_store.load_full() else {
+            return Err(RvError::ErrBarrierSealed);
+        };
+        let te = token_store.root_token().await?;
+
+        let encoded: Vec<u8> = te
+            .id
+            .as_bytes()
+            .iter()
+            .zip(otp.as_bytes())
+            .map(|(t, o)| t ^ o)
+            .collect();
+
+        Ok(Some(STANDARD.encode(encoded)))
+    }
+
+    /// Abandons the generate-root attempt in progress, wiping its OTP and the unseal keys supplied
+    /// so far.
+    pub fn generate_root_cancel(&self) -> Result<(), RvError> {
+        let mut state = (*self.state.load_full()).clone();
+        state.generate_root_otp.zeroize();
+        state.generate_root_key_shares.clear();
+        self.state.store(Arc::new(state));
+        Ok(())
+    }
+
+    /// Progress of the generate-root attempt, shaped like the unseal progress in `seal_status`.
+    pub async fn generate_root_status(&self) -> Result<Map<String, Value>, RvError> {
+        let config = self.seal_config().await?;
+        let state = self.state.load();
+
+        let data = json!({
+            "started": state.generate_root_otp.is_some(),
+            "progress": state.generate_root_key_shares.len(),
+            "required": config.secret_threshold,
+            "complete": false,
+            "otp_length": GENERATE_ROOT_OTP_LENGTH,
+        });
+
+        Ok(data.as_object().cloned().unwrap_or_default())
+    }
+
     async fn post_unseal(&self) -> Result<(), RvError> {
         self.module_manager.setup(self)?;
 
+        self.namespaces.load(self.barrier.as_storage()).await?;
+
         // Perform initial setup
         self.mounts_router
             .load_or_default(
@@ -620,6 +1132,8 @@ impl Core {
         self.mounts_router
             .setup(self.self_ptr.upgrade().unwrap().clone())?;
 
+        self.audit_broker.load(self.barrier.as_storage()).await?;
+
         self.module_manager.init(self).await?;
 
         if let Some(mounts_monitor) = self.mounts_monitor.load().as_ref() {
@@ -630,6 +1144,21 @@ impl Core {
         Ok(())
     }
 
+    /// Stops the background tasks started on unseal, the mounts monitor and the expired lease
+    /// checker, and waits for them to exit. Sealing stops them too; this is for tearing down an
+    /// instance that stays unsealed. Calling it more than once is harmless.
+    pub fn shutdown(&self) {
+        if let Some(mounts_monitor) = self.mounts_monitor.load().as_ref() {
+            mounts_monitor.stop();
+        }
+        if let Some(auth_module) = self.module_manager.get_module::<AuthModule>("auth")
+            && let Some(expiration) = auth_module.expiration.load().as_ref()
+            && let Err(err) = expiration.stop_check_expired_lease_entries()
+        {
+            log::warn!("stop expired lease checker failed: {err:?}");
+        }
+    }
+
     fn pre_seal(&self) -> Result<(), RvError> {
         if let Some(mounts_monitor) = self.mounts_monitor.load().as_ref() {
             mounts_monitor.remove_mounts_router(self.mounts_router.clone());
@@ -637,21 +1166,101 @@ impl Core {
         }
         self.module_manager.cleanup(self)?;
         self.unload_mounts()?;
+        self.namespaces.clear();
+        self.audit_broker.unload();
         Ok(())
     }
 
+    /// Run `req` through the handler chain.
+    ///
+    /// The request fails with `ErrRequestTimeout` once `req.timeout` (or the core's
+    /// `request_timeout`) elapses, and with `ErrRequestCancelled` when `req.cancel` fires. The
+    /// handler is abandoned at its next await point; storage writes it already made are not
+    /// rolled back, so an aborted write request may have been partially applied. Only writes
+    /// made inside a backend `Transaction` are undone, as the transaction is dropped uncommitted.
     pub async fn handle_request(&self, req: &mut Request) -> Result<Option<Response>, RvError> {
+        #[cfg(feature = "metrics")]
+        let (start, path, operation) = (std::time::Instant::now(), req.path.clone(), req.operation);
+
+        let timeout = req.timeout.or(self.request_timeout);
+        let cancel = req.cancel.clone();
+        let expired = async {
+            match timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => future::pending().await,
+            }
+        };
+        let cancelled = async {
+            match cancel {
+                Some(token) => token.cancelled().await,
+                None => future::pending().await,
+            }
+        };
+
+        let ret = tokio::select! {
+            ret = self.do_handle_request(req) => ret,
+            _ = expired => Err(RvError::ErrRequestTimeout),
+            _ = cancelled => Err(RvError::ErrRequestCancelled),
+        };
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::observe_request(&path, operation, start.elapsed(), ret.as_ref().err());
+
+        ret
+    }
+
+    async fn do_handle_request(&self, req: &mut Request) -> Result<Option<Response>, RvError> {
         let mut resp = None;
         let mut err: Option<RvError> = None;
         let handlers = self.handlers.load();
 
+        // These must answer while sealed, when the system backend is not even mounted, and they
+        // never need a token.
+        if req.operation == Operation::Read {
+            match req.path.trim_end_matches('/') {
+                "sys/health" => {
+                    return Ok(Some(Response::data_response(Some(self.health().await?))));
+                }
+                "sys/seal-status" => {
+                    return Ok(Some(Response::data_response(Some(
+                        self.seal_status().await?,
+                    ))));
+                }
+                _ => {}
+            }
+        }
+
         if self.state.load().sealed {
             return Err(RvError::ErrBarrierSealed);
         }
 
-        match self.handle_pre_route_phase(&handlers, req).await {
-            Ok(ret) => resp = ret,
-            Err(e) => err = Some(e),
+        if !req.namespace.is_empty() {
+            let ns = self
+                .namespaces
+                .get(&req.namespace)
+                .ok_or(RvError::ErrNamespaceNotFound)?;
+            req.path = namespace::scope_path(&ns.path, &req.path);
+            req.namespace = ns.path;
+        }
+
+        let audit_req = self
+            .audit_broker
+            .log_request(req, self.mount_entry_hmac_level)
+            .await?;
+
+        let hooks = self.request_hooks.load();
+        for hook in hooks.iter() {
+            if let Err(e) = hook.pre_request(req).await {
+                err = Some(e);
+                break;
+            }
+        }
+
+        if err.is_none() {
+            match self.handle_pre_route_phase(&handlers, req).await {
+                Ok(ret) => resp = ret,
+                Err(e) => err = Some(e),
+            }
         }
 
         if resp.is_none() && err.is_none() {
@@ -669,8 +1278,31 @@ impl Core {
             }
         }
 
+        if err.is_none()
+            && let Err(e) = self.handle_log_phase(&handlers, req, &mut resp).await
+        {
+            err = Some(e)
+        }
+
         if err.is_none() {
-            self.handle_log_phase(&handlers, req, &mut resp).await?;
+            for hook in hooks.iter() {
+                if let Err(e) = hook.post_request(req, &mut resp).await {
+                    err = Some(e);
+                    break;
+                }
+            }
+        }
+
+        // The response is audited whether or not the request succeeded.
+        if let Some(audit_req) = audit_req {
+            self.audit_broker
+                .log_response(
+                    &audit_req,
+                    resp.as_ref(),
+                    err.as_ref(),
+                    self.mount_entry_hmac_level,
+                )
+                .await?;
         }
 
         if let Some(e) = err {
@@ -772,3 +1404,22 @@ impl Core {
         Ok(())
     }
 }
+
+/// Recover the root token from the `encoded_token` a generate-root attempt returned, using the OTP
+/// the attempt was started with.
+pub fn decode_generated_root(encoded_token: &str, otp: &str) -> Result<String, RvError> {
+    let encoded = STANDARD
+        .decode(encoded_token)
+        .map_err(|_| RvError::ErrRequestFieldInvalid)?;
+    if encoded.len() != otp.len() {
+        return Err(RvError::ErrCoreGenerateRootOtpInvalid);
+    }
+
+    let token: Vec<u8> = encoded
+        .iter()
+        .zip(otp.as_bytes())
+        .map(|(e, o)| e ^ o)
+        .collect();
+
+    String::from_utf8(token).map_err(|_| RvError::ErrCoreGenerateRootOtpInvalid)
+}
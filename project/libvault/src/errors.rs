@@ -54,8 +54,28 @@ pub enum RvError {
     ErrCoreSealConfigInvalid,
     #[error("Core seal config not found.")]
     ErrCoreSealConfigNotFound,
+    #[error("Core seal config cannot use both Shamir shares and transit auto-unseal.")]
+    ErrCoreSealConfigConflict,
+    #[error("Core seal is not configured for auto-unseal.")]
+    ErrCoreSealNotAutoUnseal,
+    #[error("Core seal is configured for auto-unseal, Shamir unseal keys are not available.")]
+    ErrCoreSealAutoUnseal,
+    #[error("Core seal transit wrapped key not found.")]
+    ErrCoreSealTransitKeyNotFound,
+    #[error("Core seal transit response is invalid.")]
+    ErrCoreSealTransitResponseInvalid,
     #[error("Core unseal key set not found.")]
     ErrCoreDeprecatedUnsealKeySetNotFound,
+    #[error("Core rekey is already in progress.")]
+    ErrCoreRekeyInProgress,
+    #[error("Core rekey has not been started.")]
+    ErrCoreRekeyNotStarted,
+    #[error("Core root token generation is already in progress.")]
+    ErrCoreGenerateRootInProgress,
+    #[error("Core root token generation has not been started.")]
+    ErrCoreGenerateRootNotStarted,
+    #[error("Core root token generation one-time password is invalid.")]
+    ErrCoreGenerateRootOtpInvalid,
     #[error("Physical configuration item is missing.")]
     ErrPhysicalConfigItemMissing,
     #[error("Physical type is invalid.")]
@@ -104,6 +124,36 @@ pub enum RvError {
     ErrMountTableNotReady,
     #[error("Mount not match.")]
     ErrMountNotMatch,
+    #[error("Namespace not found.")]
+    ErrNamespaceNotFound,
+    #[error("Namespace path is invalid.")]
+    ErrNamespacePathInvalid,
+    #[error("Namespace path already in use.")]
+    ErrNamespaceExist,
+    #[error("Namespace still has mounts enabled.")]
+    ErrNamespaceNotEmpty,
+    #[error("Operation is only allowed in the root namespace.")]
+    ErrNamespaceRootOnly,
+    #[error("Identity entity not found.")]
+    ErrIdentityEntityNotFound,
+    #[error("Identity entity name already in use.")]
+    ErrIdentityEntityNameExist,
+    #[error("Identity entity alias not found.")]
+    ErrIdentityAliasNotFound,
+    #[error("Identity entity alias already exists for this mount accessor and name.")]
+    ErrIdentityAliasExist,
+    #[error("No auth mount with this accessor.")]
+    ErrIdentityMountAccessorInvalid,
+    #[error("Audit device path already in use.")]
+    ErrAuditPathExist,
+    #[error("No audit device is enabled at this path.")]
+    ErrAuditPathNotFound,
+    #[error("Audit device type is not supported.")]
+    ErrAuditTypeUnsupported,
+    #[error("Audit device options are invalid.")]
+    ErrAuditOptionsInvalid,
+    #[error("Audit log could not be written to any audit device.")]
+    ErrAuditLogFailed,
     #[error("Logical backend path not supported.")]
     ErrLogicalPathUnsupported,
     #[error("Logical backend operation not supported.")]
@@ -122,16 +172,28 @@ pub enum RvError {
     ErrRequestFieldNotFound,
     #[error("Request field is invalid.")]
     ErrRequestFieldInvalid,
+    #[error("Request timed out.")]
+    ErrRequestTimeout,
+    #[error("Request was cancelled.")]
+    ErrRequestCancelled,
     #[error("Response data is invalid.")]
     ErrResponseDataInvalid,
     #[error("Handler is default.")]
     ErrHandlerDefault,
     #[error("Module kv data field is missing.")]
     ErrModuleKvDataFieldMissing,
+    #[error("Module kv check-and-set parameter did not match the current version.")]
+    ErrModuleKvCheckAndSetMismatch,
+    #[error("Module kv check-and-set parameter is required for this path.")]
+    ErrModuleKvCheckAndSetRequired,
     #[error("Rust downcast failed.")]
     ErrRustDowncastFailed,
     #[error("Shamir share count invalid.")]
     ErrShamirShareCountInvalid,
+    #[error("Shamir secret is empty or too long.")]
+    ErrShamirSecretInvalid,
+    #[error("Shamir shares are malformed or do not belong together.")]
+    ErrShamirSharesInvalid,
     #[error("Module conflict.")]
     ErrModuleConflict,
     #[error("Module is not init.")]
@@ -144,6 +206,14 @@ pub enum RvError {
     ErrAuthTokenNotFound,
     #[error("Auth token id is invalid.")]
     ErrAuthTokenIdInvalid,
+    #[error("Auth token has expired.")]
+    ErrAuthTokenExpired,
+    #[error("Auth token has no uses left.")]
+    ErrAuthTokenUsesExhausted,
+    #[error("Auth token accessor is not found.")]
+    ErrAuthTokenAccessorNotFound,
+    #[error("Login is locked after too many failed attempts.")]
+    ErrAuthLocked,
     #[error("Lease is not found.")]
     ErrLeaseNotFound,
     #[error("Lease is not renewable.")]
@@ -196,6 +266,12 @@ pub enum RvError {
     ErrPkiPgpKeyNameAlreadyExist,
     #[error("PKI PGP key generation failed.")]
     ErrPkiPgpKeyGenerationFailed,
+    #[error("PKI name \"{0}\" is not allowed by role.")]
+    ErrPkiNameNotAllowed(String),
+    #[error("PKI intermediate CSR has not been generated.")]
+    ErrPkiIntermediateNotGenerated,
+    #[error("PKI signed intermediate certificate does not match the generated private key.")]
+    ErrPkiIntermediateKeyMismatch,
     #[error("Credential is invalid.")]
     ErrCredentialInvalid,
     #[error("Credential is not config.")]
@@ -206,12 +282,19 @@ pub enum RvError {
     ErrStorageBackendLockFailed,
     #[error("Storage backend unlock failed.")]
     ErrStorageBackendUnlockFailed,
-    #[cfg(feature = "storage_sqlite")]
-    #[error("SQLite backend does not support absolute paths yet.")]
-    ErrSqliteBackendNotSupportAbsolute,
+    #[error("Storage backend is unavailable: {0}")]
+    ErrStorageUnavailable(String),
+    #[error("Storage backend timed out: {0}")]
+    ErrStorageTimeout(String),
     #[cfg(feature = "storage_sqlite")]
     #[error("Sqlite disallowed fields: {}", .0)]
     ErrSqliteDisallowedFields(String),
+    #[cfg(feature = "storage_mysql")]
+    #[error("MySQL disallowed fields: {}", .0)]
+    ErrMysqlDisallowedFields(String),
+    #[cfg(feature = "storage_pg")]
+    #[error("PostgreSQL disallowed fields: {}", .0)]
+    ErrPostgresDisallowedFields(String),
     #[error("Some IO error happened, {:?}", .source)]
     IO {
         #[from]
@@ -277,6 +360,8 @@ pub enum RvError {
         #[from]
         source: chrono::ParseError,
     },
+    #[error("Password hash error: {0}")]
+    ErrPasswordHash(String),
     #[error("Some bcrypt error happened, {:?}", .source)]
     BcryptError {
         #[from]
@@ -349,11 +434,24 @@ pub enum RvError {
         source: etcd_client::Error,
     },
 
-    #[cfg(feature = "storage_sqlite")]
+    #[cfg(any(
+        feature = "storage_sqlite",
+        feature = "storage_mysql",
+        feature = "storage_pg",
+        feature = "secrets_database"
+    ))]
     #[error("Some sqlite client error happened, {:?}", .source)]
-    SqliteClientError {
+    SqliteClientError { source: sqlx::Error },
+
+    #[cfg(feature = "storage_mysql")]
+    #[error("Some mysql client error happened, {:?}", .source)]
+    MysqlClientError { source: sqlx::Error },
+
+    #[cfg(feature = "storage_redis")]
+    #[error("Some redis client error happened, {:?}", .source)]
+    RedisClientError {
         #[from]
-        source: sqlx::Error,
+        source: redis::RedisError,
     },
 
     #[error(transparent)]
@@ -383,13 +481,55 @@ impl RvError {
             | RvError::ErrRequestFieldNotFound
             | RvError::ErrRequestFieldInvalid
             | RvError::ErrPkiSshCertTypeInvalid
-            | RvError::ErrPkiSshPublicKeyInvalid => 400,
-            RvError::ErrBarrierSealed => 503,
-            RvError::ErrPermissionDenied => 403,
-            RvError::ErrRouterMountNotFound => 404,
+            | RvError::ErrPkiSshPublicKeyInvalid
+            | RvError::ErrPkiNameNotAllowed(_)
+            | RvError::ErrPkiIntermediateNotGenerated
+            | RvError::ErrPkiIntermediateKeyMismatch
+            | RvError::ErrModuleKvCheckAndSetMismatch
+            | RvError::ErrModuleKvCheckAndSetRequired
+            | RvError::ErrAuditPathExist
+            | RvError::ErrAuditPathNotFound
+            | RvError::ErrAuditTypeUnsupported
+            | RvError::ErrAuditOptionsInvalid
+            | RvError::ErrLeaseNotFound
+            | RvError::ErrLeaseNotRenewable
+            | RvError::ErrAuthTokenAccessorNotFound
+            | RvError::ErrCoreRekeyInProgress
+            | RvError::ErrCoreRekeyNotStarted
+            | RvError::ErrCoreGenerateRootInProgress
+            | RvError::ErrCoreGenerateRootNotStarted
+            | RvError::ErrCoreGenerateRootOtpInvalid
+            | RvError::ErrNamespacePathInvalid
+            | RvError::ErrNamespaceExist
+            | RvError::ErrNamespaceNotEmpty
+            | RvError::ErrNamespaceRootOnly
+            | RvError::ErrIdentityEntityNameExist
+            | RvError::ErrIdentityAliasExist
+            | RvError::ErrIdentityMountAccessorInvalid => 400,
+            RvError::ErrBarrierSealed
+            | RvError::ErrStorageUnavailable(_)
+            | RvError::ErrStorageTimeout(_) => 503,
+            RvError::ErrRequestTimeout => 504,
+            RvError::ErrPermissionDenied
+            | RvError::ErrAuthLocked
+            | RvError::ErrAuthTokenExpired
+            | RvError::ErrAuthTokenUsesExhausted => 403,
+            RvError::ErrRouterMountNotFound
+            | RvError::ErrNamespaceNotFound
+            | RvError::ErrIdentityEntityNotFound
+            | RvError::ErrIdentityAliasNotFound => 404,
             _ => 500,
         }
     }
+
+    /// Whether the storage backend failed transiently, so the same request may succeed when
+    /// retried later.
+    pub fn is_storage_transient(&self) -> bool {
+        matches!(
+            self,
+            RvError::ErrStorageUnavailable(_) | RvError::ErrStorageTimeout(_)
+        )
+    }
 }
 
 /// PartialEq is implemented to allow simple equality checks between
@@ -409,6 +549,19 @@ impl PartialEq for RvError {
             | (RvError::ErrCoreLogicalBackendNoExist, RvError::ErrCoreLogicalBackendNoExist)
             | (RvError::ErrCoreSealConfigInvalid, RvError::ErrCoreSealConfigInvalid)
             | (RvError::ErrCoreSealConfigNotFound, RvError::ErrCoreSealConfigNotFound)
+            | (RvError::ErrCoreSealConfigConflict, RvError::ErrCoreSealConfigConflict)
+            | (RvError::ErrCoreSealNotAutoUnseal, RvError::ErrCoreSealNotAutoUnseal)
+            | (RvError::ErrCoreSealAutoUnseal, RvError::ErrCoreSealAutoUnseal)
+            | (RvError::ErrCoreSealTransitKeyNotFound, RvError::ErrCoreSealTransitKeyNotFound)
+            | (
+                RvError::ErrCoreSealTransitResponseInvalid,
+                RvError::ErrCoreSealTransitResponseInvalid,
+            )
+            | (RvError::ErrCoreRekeyInProgress, RvError::ErrCoreRekeyInProgress)
+            | (RvError::ErrCoreRekeyNotStarted, RvError::ErrCoreRekeyNotStarted)
+            | (RvError::ErrCoreGenerateRootInProgress, RvError::ErrCoreGenerateRootInProgress)
+            | (RvError::ErrCoreGenerateRootNotStarted, RvError::ErrCoreGenerateRootNotStarted)
+            | (RvError::ErrCoreGenerateRootOtpInvalid, RvError::ErrCoreGenerateRootOtpInvalid)
             | (RvError::ErrCoreRouterNotHandling, RvError::ErrCoreRouterNotHandling)
             | (RvError::ErrCoreHandlerExist, RvError::ErrCoreHandlerExist)
             | (RvError::ErrPhysicalConfigItemMissing, RvError::ErrPhysicalConfigItemMissing)
@@ -436,6 +589,24 @@ impl PartialEq for RvError {
             | (RvError::ErrMountTableNotFound, RvError::ErrMountTableNotFound)
             | (RvError::ErrMountTableNotReady, RvError::ErrMountTableNotReady)
             | (RvError::ErrMountNotMatch, RvError::ErrMountNotMatch)
+            | (RvError::ErrNamespaceNotFound, RvError::ErrNamespaceNotFound)
+            | (RvError::ErrNamespacePathInvalid, RvError::ErrNamespacePathInvalid)
+            | (RvError::ErrNamespaceExist, RvError::ErrNamespaceExist)
+            | (RvError::ErrNamespaceNotEmpty, RvError::ErrNamespaceNotEmpty)
+            | (RvError::ErrNamespaceRootOnly, RvError::ErrNamespaceRootOnly)
+            | (RvError::ErrIdentityEntityNotFound, RvError::ErrIdentityEntityNotFound)
+            | (RvError::ErrIdentityEntityNameExist, RvError::ErrIdentityEntityNameExist)
+            | (RvError::ErrIdentityAliasNotFound, RvError::ErrIdentityAliasNotFound)
+            | (RvError::ErrIdentityAliasExist, RvError::ErrIdentityAliasExist)
+            | (
+                RvError::ErrIdentityMountAccessorInvalid,
+                RvError::ErrIdentityMountAccessorInvalid,
+            )
+            | (RvError::ErrAuditPathExist, RvError::ErrAuditPathExist)
+            | (RvError::ErrAuditPathNotFound, RvError::ErrAuditPathNotFound)
+            | (RvError::ErrAuditTypeUnsupported, RvError::ErrAuditTypeUnsupported)
+            | (RvError::ErrAuditOptionsInvalid, RvError::ErrAuditOptionsInvalid)
+            | (RvError::ErrAuditLogFailed, RvError::ErrAuditLogFailed)
             | (RvError::ErrLogicalPathUnsupported, RvError::ErrLogicalPathUnsupported)
             | (RvError::ErrLogicalOperationUnsupported, RvError::ErrLogicalOperationUnsupported)
             | (RvError::ErrRequestNotReady, RvError::ErrRequestNotReady)
@@ -445,11 +616,17 @@ impl PartialEq for RvError {
             | (RvError::ErrRequestClientTokenMissing, RvError::ErrRequestClientTokenMissing)
             | (RvError::ErrRequestFieldNotFound, RvError::ErrRequestFieldNotFound)
             | (RvError::ErrRequestFieldInvalid, RvError::ErrRequestFieldInvalid)
+            | (RvError::ErrRequestTimeout, RvError::ErrRequestTimeout)
+            | (RvError::ErrRequestCancelled, RvError::ErrRequestCancelled)
             | (RvError::ErrResponseDataInvalid, RvError::ErrResponseDataInvalid)
             | (RvError::ErrHandlerDefault, RvError::ErrHandlerDefault)
             | (RvError::ErrModuleKvDataFieldMissing, RvError::ErrModuleKvDataFieldMissing)
+            | (RvError::ErrModuleKvCheckAndSetMismatch, RvError::ErrModuleKvCheckAndSetMismatch)
+            | (RvError::ErrModuleKvCheckAndSetRequired, RvError::ErrModuleKvCheckAndSetRequired)
             | (RvError::ErrRustDowncastFailed, RvError::ErrRustDowncastFailed)
             | (RvError::ErrShamirShareCountInvalid, RvError::ErrShamirShareCountInvalid)
+            | (RvError::ErrShamirSecretInvalid, RvError::ErrShamirSecretInvalid)
+            | (RvError::ErrShamirSharesInvalid, RvError::ErrShamirSharesInvalid)
             | (RvError::ErrRwLockReadPoison, RvError::ErrRwLockReadPoison)
             | (RvError::ErrRwLockWritePoison, RvError::ErrRwLockWritePoison)
             | (RvError::ErrConfigPathInvalid, RvError::ErrConfigPathInvalid)
@@ -462,6 +639,10 @@ impl PartialEq for RvError {
             | (RvError::ErrAuthModuleDisabled, RvError::ErrAuthModuleDisabled)
             | (RvError::ErrAuthTokenNotFound, RvError::ErrAuthTokenNotFound)
             | (RvError::ErrAuthTokenIdInvalid, RvError::ErrAuthTokenIdInvalid)
+            | (RvError::ErrAuthTokenExpired, RvError::ErrAuthTokenExpired)
+            | (RvError::ErrAuthTokenUsesExhausted, RvError::ErrAuthTokenUsesExhausted)
+            | (RvError::ErrAuthTokenAccessorNotFound, RvError::ErrAuthTokenAccessorNotFound)
+            | (RvError::ErrAuthLocked, RvError::ErrAuthLocked)
             | (RvError::ErrLeaseNotFound, RvError::ErrLeaseNotFound)
             | (RvError::ErrLeaseNotRenewable, RvError::ErrLeaseNotRenewable)
             | (RvError::ErrPermissionDenied, RvError::ErrPermissionDenied)
@@ -487,6 +668,8 @@ impl PartialEq for RvError {
             | (RvError::ErrPkiPgpKeyNotFound, RvError::ErrPkiPgpKeyNotFound)
             | (RvError::ErrPkiPgpKeyNameAlreadyExist, RvError::ErrPkiPgpKeyNameAlreadyExist)
             | (RvError::ErrPkiPgpKeyGenerationFailed, RvError::ErrPkiPgpKeyGenerationFailed)
+            | (RvError::ErrPkiIntermediateNotGenerated, RvError::ErrPkiIntermediateNotGenerated)
+            | (RvError::ErrPkiIntermediateKeyMismatch, RvError::ErrPkiIntermediateKeyMismatch)
             | (RvError::ErrCredentialInvalid, RvError::ErrCredentialInvalid)
             | (RvError::ErrCredentialNotConfig, RvError::ErrCredentialNotConfig)
             | (RvError::ErrUnknown, RvError::ErrUnknown) => true,
@@ -494,6 +677,10 @@ impl PartialEq for RvError {
             (RvError::ErrResponseStatus(sa, ta), RvError::ErrResponseStatus(sb, tb)) => {
                 sa == sb && ta == tb
             }
+            (RvError::ErrPkiNameNotAllowed(a), RvError::ErrPkiNameNotAllowed(b)) => a == b,
+            (RvError::ErrStorageUnavailable(a), RvError::ErrStorageUnavailable(b)) => a == b,
+            (RvError::ErrStorageTimeout(a), RvError::ErrStorageTimeout(b)) => a == b,
+            (RvError::ErrPasswordHash(a), RvError::ErrPasswordHash(b)) => a == b,
             (RvError::ErrString(a), RvError::ErrString(b)) => a == b,
             _ => false,
         }
@@ -512,6 +699,73 @@ impl<T> From<PoisonError<RwLockReadGuard<'_, T>>> for RvError {
     }
 }
 
+/// Whether `err` means the database could not be reached or dropped the connection: an IO
+/// error, SQLSTATE class 08 ("connection exception", used by PostgreSQL and MySQL), or
+/// 57P01-57P03 (PostgreSQL shutting down or not accepting connections yet).
+#[cfg(any(
+    feature = "storage_sqlite",
+    feature = "storage_mysql",
+    feature = "storage_pg",
+    feature = "secrets_database"
+))]
+pub(crate) fn is_sqlx_connection_error(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::Io(_) => true,
+        sqlx::Error::Database(err) => err
+            .code()
+            .is_some_and(|code| code.starts_with("08") || code.starts_with("57P0")),
+        _ => false,
+    }
+}
+
+/// Whether `err` is a wait that ran out: no pooled connection freed up in time, or SQLite
+/// stayed busy (locked by another writer) past its busy timeout.
+#[cfg(any(
+    feature = "storage_sqlite",
+    feature = "storage_mysql",
+    feature = "storage_pg",
+    feature = "secrets_database"
+))]
+pub(crate) fn is_sqlx_timeout(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::PoolTimedOut => true,
+        sqlx::Error::Database(err) => err.code().is_some_and(|code| code == "5"),
+        _ => false,
+    }
+}
+
+/// Transient failures are routed to `ErrStorageUnavailable` and `ErrStorageTimeout` so callers
+/// can tell them apart from errors the same query would hit again.
+#[cfg(any(
+    feature = "storage_sqlite",
+    feature = "storage_mysql",
+    feature = "storage_pg",
+    feature = "secrets_database"
+))]
+impl From<sqlx::Error> for RvError {
+    fn from(err: sqlx::Error) -> Self {
+        if is_sqlx_timeout(&err) {
+            RvError::ErrStorageTimeout(err.to_string())
+        } else if is_sqlx_connection_error(&err) {
+            RvError::ErrStorageUnavailable(err.to_string())
+        } else {
+            RvError::SqliteClientError { source: err }
+        }
+    }
+}
+
+#[cfg(feature = "storage_mysql")]
+impl RvError {
+    /// Like `From<sqlx::Error>`, but reports failures of the MySQL backend as
+    /// `MysqlClientError`.
+    pub(crate) fn from_mysql(err: sqlx::Error) -> Self {
+        match RvError::from(err) {
+            RvError::SqliteClientError { source } => RvError::MysqlClientError { source },
+            err => err,
+        }
+    }
+}
+
 impl From<rustls_pemfile::Error> for RvError {
     fn from(err: rustls_pemfile::Error) -> Self {
         RvError::RustlsPemFileError(err)
@@ -58,6 +58,34 @@ pub trait AuthHandler: Send + Sync {
     async fn post_auth(&self, _req: &mut Request) -> Result<(), RvError> {
         Err(RvError::ErrHandlerDefault)
     }
+
+    /// Called for a successful login before its token is created, so the handler can amend the
+    /// `auth` the token is issued from.
+    async fn post_login(&self, _req: &Request, _auth: &mut Auth) -> Result<(), RvError> {
+        Err(RvError::ErrHandlerDefault)
+    }
+}
+
+/// A hook run around the whole handler chain of `Core::handle_request`.
+///
+/// Unlike `Handler`, a hook is not consulted for routing; it sees every request before the first
+/// handler and every successful response after the last one. Hooks run in registration order and
+/// the first error returned aborts the request with that error.
+#[async_trait]
+pub trait RequestHook: Send + Sync {
+    fn name(&self) -> String;
+
+    async fn pre_request(&self, _req: &mut Request) -> Result<(), RvError> {
+        Ok(())
+    }
+
+    async fn post_request(
+        &self,
+        _req: &Request,
+        _resp: &mut Option<Response>,
+    ) -> Result<(), RvError> {
+        Ok(())
+    }
 }
 
 #[derive(Display, Copy, Clone, Debug, PartialEq, Eq)]
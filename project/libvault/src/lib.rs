@@ -21,9 +21,10 @@
 //! [Hashicorp Vault]: https://www.hashicorp.com/products/vault
 //! [RESTful API documentation]: https://www.tongsuo.net
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use arc_swap::ArcSwap;
+use serde::{Serialize, de::DeserializeOwned};
 use serde_json::{Map, Value};
 use zeroize::Zeroizing;
 
@@ -33,10 +34,18 @@ use crate::{
     errors::RvError,
     logical::{Request, Response},
     modules::{
-        auth::AuthModule, credential::cert::CertModule, kv::KvModule, pki::PkiModule,
+        auth::AuthModule,
+        credential::{
+            approle::AppRoleModule, cert::CertModule, jwt::JwtModule, userpass::UserPassModule,
+        },
+        crypto::{signing::SigningModule, totp::TotpModule, transit::TransitModule},
+        identity::IdentityModule,
+        kv::{KvModule, v2::KvV2Module},
+        pki::PkiModule,
         policy::PolicyModule,
+        ssh::SshModule,
     },
-    mount::MountsMonitor,
+    mount::{MountInfo, MountsMonitor},
     storage::Backend,
 };
 
@@ -46,10 +55,14 @@ pub mod core;
 pub mod errors;
 pub mod handler;
 pub mod logical;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod module_manager;
 pub mod modules;
 pub mod mount;
+pub mod namespace;
 pub mod router;
+pub mod seal;
 pub mod shamir;
 pub mod storage;
 pub mod utils;
@@ -82,6 +95,9 @@ impl RustyVault {
         if let Some(conf) = config {
             core.mount_entry_hmac_level = conf.mount_entry_hmac_level;
             core.mounts_monitor_interval = conf.mounts_monitor_interval;
+            core.request_timeout =
+                (conf.request_timeout > 0).then(|| Duration::from_secs(conf.request_timeout));
+            core.transit_seal_token = conf.transit_seal_token.clone();
         }
 
         let core = core.wrap();
@@ -103,6 +119,10 @@ impl RustyVault {
         let policy_module = PolicyModule::new(core.clone());
         core.module_manager.add_module(Arc::new(policy_module))?;
 
+        // add identity_module
+        let identity_module = IdentityModule::new(core.clone());
+        core.module_manager.add_module(Arc::new(identity_module))?;
+
         // add pki_module
         let pki_module = PkiModule::new(core.clone());
         core.module_manager.add_module(Arc::new(pki_module))?;
@@ -111,10 +131,49 @@ impl RustyVault {
         let cert_module = CertModule::new(core.clone());
         core.module_manager.add_module(Arc::new(cert_module))?;
 
+        // add credential module: userpass
+        let userpass_module = UserPassModule::new(core.clone());
+        core.module_manager.add_module(Arc::new(userpass_module))?;
+
+        // add credential module: approle
+        let approle_module = AppRoleModule::new(core.clone());
+        core.module_manager.add_module(Arc::new(approle_module))?;
+
+        // add credential module: jwt
+        let jwt_module = JwtModule::new(core.clone());
+        core.module_manager.add_module(Arc::new(jwt_module))?;
+
         // add kv module
         let kv_module = KvModule::new(core.clone());
         core.module_manager.add_module(Arc::new(kv_module))?;
 
+        // add kv-v2 module
+        let kv_v2_module = KvV2Module::new(core.clone());
+        core.module_manager.add_module(Arc::new(kv_v2_module))?;
+
+        // add transit module
+        let transit_module = TransitModule::new(core.clone());
+        core.module_manager.add_module(Arc::new(transit_module))?;
+
+        // add totp module
+        let totp_module = TotpModule::new(core.clone());
+        core.module_manager.add_module(Arc::new(totp_module))?;
+
+        // add crypto signing module
+        let signing_module = SigningModule::new(core.clone());
+        core.module_manager.add_module(Arc::new(signing_module))?;
+
+        // add ssh module
+        let ssh_module = SshModule::new(core.clone());
+        core.module_manager.add_module(Arc::new(ssh_module))?;
+
+        // add database module
+        #[cfg(feature = "secrets_database")]
+        {
+            let database_module = modules::database::DatabaseModule::new(core.clone());
+            core.module_manager.add_module(Arc::new(database_module))?;
+        }
+
         let handlers = core.handlers.load().clone();
         for handler in handlers.iter() {
             match handler.post_config(core.clone(), config) {
@@ -160,6 +219,27 @@ impl RustyVault {
         Ok(false)
     }
 
+    /// Progress of the unseal attempt as `(provided, threshold)`. Supplying a key already counted
+    /// does not advance it.
+    pub async fn unseal_progress(&self) -> Result<(usize, usize), RvError> {
+        let core = self.core.load();
+        let config = core.seal_config().await?;
+        Ok((core.unseal_progress(), config.secret_threshold as usize))
+    }
+
+    /// Discard the unseal keys supplied so far.
+    pub fn unseal_reset(&self) -> Result<(), RvError> {
+        self.core.load().unseal_reset()
+    }
+
+    /// Unseal the vault through the remote transit key configured at init time.
+    ///
+    /// Only valid for vaults initialized with `SealConfig::transit` set; Shamir-sealed vaults
+    /// still go through `unseal`.
+    pub async fn auto_unseal(&self) -> Result<(), RvError> {
+        self.core.load().auto_unseal().await
+    }
+
     /// Attempts to unseal the vault using a list of candidate unseal keys.
     ///
     /// Tries each supplied key in order and returns `Ok(true)` as soon as one
@@ -204,6 +284,37 @@ impl RustyVault {
         self.core.load().generate_unseal_keys().await
     }
 
+    /// Start rekeying the unsealed vault into `new_shares` shares with a threshold of
+    /// `new_threshold`. See `Core::rekey_init`.
+    pub async fn rekey_init(&self, new_shares: u8, new_threshold: u8) -> Result<(), RvError> {
+        self.core.load().rekey_init(new_shares, new_threshold).await
+    }
+
+    /// Supply current unseal keys to the rekey in progress. Returns the new shares once the
+    /// current threshold is met, `Ok(None)` while more keys are needed.
+    pub async fn rekey_update(
+        &self,
+        keys: &[&[u8]],
+    ) -> Result<Option<Zeroizing<Vec<Vec<u8>>>>, RvError> {
+        for key in keys.iter() {
+            if let Some(shares) = self.core.load().rekey_update(key).await? {
+                return Ok(Some(shares));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Abandon the rekey in progress.
+    pub fn rekey_cancel(&self) -> Result<(), RvError> {
+        self.core.load().rekey_cancel()
+    }
+
+    /// Turn on seal wrapping for the existing mount at `path`. See `Core::enable_seal_wrap`.
+    pub async fn enable_seal_wrap(&self, path: &str) -> Result<(), RvError> {
+        self.core.load().enable_seal_wrap(path).await
+    }
+
     pub async fn seal(&self) -> Result<(), RvError> {
         self.core.load().seal().await
     }
@@ -216,6 +327,15 @@ impl RustyVault {
         self.token.store(Arc::new(token.into()));
     }
 
+    /// Stop the background tasks of this instance (mounts monitor, expired
+    /// lease checker) and wait for them to exit.
+    ///
+    /// Sealing stops them as well; dropping a `RustyVault` calls this, so an
+    /// instance never leaves detached tasks behind.
+    pub fn shutdown(&self) {
+        self.core.load().shutdown();
+    }
+
     /// Set the cached client token used for subsequent requests when an
     /// explicit token is not provided to the API methods.
     pub async fn mount<S: Into<String>>(
@@ -391,4 +511,265 @@ impl RustyVault {
             .unwrap_or_else(|| self.token.load().as_ref().clone());
         self.request(&mut req).await
     }
+
+    /// Prometheus scrape body with the request metrics of this process.
+    #[cfg(feature = "metrics")]
+    pub fn metrics_text(&self) -> Result<String, RvError> {
+        metrics::gather()
+    }
+
+    /// Read the secret at `path` of the KV mount `mount` into a `T`, using the
+    /// cached token.
+    ///
+    /// On a `kv-v2` mount the latest version is read and its `data` wrapper is
+    /// unwrapped. Returns `Ok(None)` if there is no secret (or its latest
+    /// version is deleted).
+    pub async fn kv_get<T: DeserializeOwned>(
+        &self,
+        mount: &str,
+        path: &str,
+    ) -> Result<Option<T>, RvError> {
+        let (kv_path, v2) = self.kv_path(mount, path)?;
+        let Some(mut data) = self
+            .read::<String>(None, &kv_path)
+            .await?
+            .and_then(|resp| resp.data)
+        else {
+            return Ok(None);
+        };
+
+        let data = if v2 {
+            match data.remove("data") {
+                Some(Value::Object(data)) => data,
+                _ => return Ok(None),
+            }
+        } else {
+            data
+        };
+        Ok(Some(serde_json::from_value(Value::Object(data))?))
+    }
+
+    /// Write `value` as the secret at `path` of the KV mount `mount`, using the
+    /// cached token. `value` must serialize to a JSON object; on a `kv-v2`
+    /// mount it is stored as a new version.
+    pub async fn kv_put<T: Serialize>(
+        &self,
+        mount: &str,
+        path: &str,
+        value: &T,
+    ) -> Result<(), RvError> {
+        let Value::Object(data) = serde_json::to_value(value)? else {
+            return Err(rv_error_string!(
+                "KV secret must serialize to a JSON object"
+            ));
+        };
+        self.kv_write(mount, path, data, None).await
+    }
+
+    /// Write `data` as the secret at `path`, as a check-and-set write if `cas` is set.
+    async fn kv_write(
+        &self,
+        mount: &str,
+        path: &str,
+        mut data: Map<String, Value>,
+        cas: Option<u64>,
+    ) -> Result<(), RvError> {
+        let (kv_path, v2) = self.kv_path(mount, path)?;
+        if v2 {
+            let mut wrapper = Map::new();
+            wrapper.insert("data".to_string(), Value::Object(data));
+            data = wrapper;
+        }
+        if let Some(cas) = cas {
+            data.insert("options".to_string(), serde_json::json!({ "cas": cas }));
+        }
+        self.write::<String>(None, kv_path, Some(data)).await?;
+        Ok(())
+    }
+
+    /// Request path of secret `path` on KV mount `mount`, and whether the mount
+    /// is a `kv-v2` one.
+    fn kv_path(&self, mount: &str, path: &str) -> Result<(String, bool), RvError> {
+        let mount = mount.trim_matches('/');
+        let path = path.trim_start_matches('/');
+        if self.kv_is_v2(mount)? {
+            Ok((format!("{mount}/data/{path}"), true))
+        } else {
+            Ok((format!("{mount}/{path}"), false))
+        }
+    }
+
+    fn kv_is_v2(&self, mount: &str) -> Result<bool, RvError> {
+        let entry = self
+            .core
+            .load()
+            .router
+            .matching_mount_entry(&format!("{}/", mount.trim_matches('/')))?
+            .ok_or(RvError::ErrRouterMountNotFound)?;
+        Ok(entry.read()?.logical_type == "kv-v2")
+    }
+
+    /// Keys of the KV folder `folder`; sub-folders end with `/`.
+    async fn kv_list(&self, mount: &str, folder: &str) -> Result<Vec<String>, RvError> {
+        let mount = mount.trim_matches('/');
+        let list_path = if self.kv_is_v2(mount)? {
+            format!("{mount}/metadata/{folder}")
+        } else {
+            format!("{mount}/{folder}")
+        };
+        let keys = self
+            .list::<String>(None, list_path)
+            .await?
+            .and_then(|resp| resp.data)
+            .and_then(|mut data| data.remove("keys"));
+        match keys {
+            Some(keys) => Ok(serde_json::from_value(keys)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// List the mounted secrets engines, sorted by path.
+    ///
+    /// If `token` is `None`, the cached token from `set_token` will be used.
+    pub async fn list_mounts<S: Into<String>>(
+        &self,
+        token: Option<S>,
+    ) -> Result<Vec<MountInfo>, RvError> {
+        self.mount_infos(token.map(Into::into), "sys/mounts").await
+    }
+
+    /// List the enabled auth methods, sorted by path.
+    ///
+    /// If `token` is `None`, the cached token from `set_token` will be used.
+    pub async fn list_auth<S: Into<String>>(
+        &self,
+        token: Option<S>,
+    ) -> Result<Vec<MountInfo>, RvError> {
+        self.mount_infos(token.map(Into::into), "sys/auth").await
+    }
+
+    /// Whether a secrets engine is mounted at `path`, or an auth method when
+    /// `path` starts with `auth/`. Uses the cached token.
+    pub async fn is_mounted(&self, path: &str) -> Result<bool, RvError> {
+        let path = path.trim_matches('/');
+        let (mounts, path) = match path.strip_prefix("auth/") {
+            Some(path) => (self.list_auth::<String>(None).await?, path),
+            None => (self.list_mounts::<String>(None).await?, path),
+        };
+        let path = format!("{path}/");
+        Ok(mounts.iter().any(|mount| mount.path == path))
+    }
+
+    async fn mount_infos(
+        &self,
+        token: Option<String>,
+        table_path: &str,
+    ) -> Result<Vec<MountInfo>, RvError> {
+        let Some(data) = self
+            .read(token, table_path)
+            .await?
+            .and_then(|resp| resp.data)
+        else {
+            return Ok(Vec::new());
+        };
+
+        let mut mounts = data
+            .into_iter()
+            .map(|(path, info)| {
+                let mut info: MountInfo = serde_json::from_value(info)?;
+                info.path = path;
+                Ok(info)
+            })
+            .collect::<Result<Vec<_>, RvError>>()?;
+        mounts.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(mounts)
+    }
+
+    /// Read every secret in the folder `prefix` of the KV mount `mount` and its sub-folders,
+    /// keyed by path relative to the mount, using the cached token. On a `kv-v2` mount the
+    /// latest version of each secret is exported.
+    pub async fn kv_export(
+        &self,
+        mount: &str,
+        prefix: &str,
+    ) -> Result<Map<String, Value>, RvError> {
+        let mut secrets = Map::new();
+        self.kv_export_with(mount, prefix, |path, secret| {
+            secrets.insert(path, Value::Object(secret));
+            Ok(())
+        })
+        .await?;
+        Ok(secrets)
+    }
+
+    /// Like `kv_export`, but hands each secret to `f` as soon as it is read instead of
+    /// collecting them. Folders are listed one at a time, so only the listing of the folders
+    /// still to visit is held in memory.
+    pub async fn kv_export_with<F>(
+        &self,
+        mount: &str,
+        prefix: &str,
+        mut f: F,
+    ) -> Result<(), RvError>
+    where
+        F: FnMut(String, Map<String, Value>) -> Result<(), RvError>,
+    {
+        let prefix = prefix.trim_matches('/');
+        let mut folders = vec![if prefix.is_empty() {
+            String::new()
+        } else {
+            format!("{prefix}/")
+        }];
+
+        while let Some(folder) = folders.pop() {
+            let mut keys = self.kv_list(mount, &folder).await?;
+            keys.sort();
+            let mut subfolders = Vec::new();
+            for key in keys {
+                let path = format!("{folder}{key}");
+                if key.ends_with('/') {
+                    subfolders.push(path);
+                } else if let Some(secret) = self.kv_get::<Map<String, Value>>(mount, &path).await?
+                {
+                    f(path, secret)?;
+                }
+            }
+            folders.extend(subfolders.into_iter().rev());
+        }
+        Ok(())
+    }
+
+    /// Write the secrets of `secrets`, keyed by path relative to the mount as returned by
+    /// `kv_export`, to the KV mount `mount`, using the cached token.
+    ///
+    /// With `check_and_set`, a secret is only written if its path does not hold one yet, so
+    /// values written since the export are never overwritten. Returns the paths skipped that way.
+    pub async fn kv_import(
+        &self,
+        mount: &str,
+        secrets: Map<String, Value>,
+        check_and_set: bool,
+    ) -> Result<Vec<String>, RvError> {
+        let cas = check_and_set.then_some(0);
+        let mut skipped = Vec::new();
+        for (path, secret) in secrets {
+            let Value::Object(data) = secret else {
+                return Err(rv_error_string!(format!(
+                    "KV secret {path} must be a JSON object"
+                )));
+            };
+            match self.kv_write(mount, &path, data, cas).await {
+                Ok(()) => {}
+                Err(RvError::ErrModuleKvCheckAndSetMismatch) => skipped.push(path),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(skipped)
+    }
+}
+
+impl Drop for RustyVault {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
 }
@@ -17,6 +17,26 @@ pub struct Auth {
     // Setting this manually will have no effect.
     pub client_token: String,
 
+    // Accessor is a non-secret handle on the client token. It can be used to look up or revoke
+    // the token without knowing the token itself.
+    #[serde(default)]
+    pub accessor: String,
+
+    // EntityID identifies the identity entity the token belongs to. Templated policies resolve
+    // their `identity.entity.*` parameters against it.
+    #[serde(default)]
+    pub entity_id: String,
+
+    // Namespace is the path of the namespace the token was issued in, empty for the root
+    // namespace. The token's policies are looked up in that namespace.
+    #[serde(default)]
+    pub namespace: String,
+
+    // AliasName is the name the auth method knows the user by, e.g. the username for userpass.
+    // Together with the mount's accessor it identifies the identity entity to attach to the token.
+    #[serde(default)]
+    pub alias_name: String,
+
     // DisplayName is a non-security sensitive identifier that is applicable to this Auth.
     // It is used for logging and prefixing of dynamic secrets. For example,
     // DisplayName may be "armon" for the github credential backend. If the client token
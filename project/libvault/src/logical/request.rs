@@ -1,7 +1,8 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use better_default::Default;
 use serde_json::{Map, Value};
+use tokio_util::sync::CancellationToken;
 
 use super::{Operation, Path};
 use crate::{
@@ -28,6 +29,11 @@ pub struct Request {
     #[default(Operation::Read)]
     pub operation: Operation,
     pub path: String,
+    /// Mount the router matched, e.g. `pki/`. Set while a backend handles the request, when `path`
+    /// is relative to the mount.
+    pub mount_point: String,
+    /// Namespace the request is made in, e.g. `team-a/`. Empty for the root namespace.
+    pub namespace: String,
     pub match_path: Option<Arc<Path>>,
     pub headers: Option<HashMap<String, String>>,
     pub body: Option<Map<String, Value>>,
@@ -42,6 +48,10 @@ pub struct Request {
     pub handle_phase: HandlePhase,
     #[default(Arc::new(Context::new()))]
     pub ctx: Arc<Context>,
+    /// Time limit of this request, overriding `Core::request_timeout`.
+    pub timeout: Option<Duration>,
+    /// Aborts the request once cancelled, e.g. when the client disconnects.
+    pub cancel: Option<CancellationToken>,
 }
 
 impl Request {
@@ -300,6 +310,18 @@ impl Request {
         storage.put(entry).await
     }
 
+    pub async fn storage_compare_and_swap(
+        &self,
+        entry: &StorageEntry,
+        expected: Option<&[u8]>,
+    ) -> Result<bool, RvError> {
+        let Some(storage) = self.storage.as_ref() else {
+            return Err(RvError::ErrRequestNotReady);
+        };
+
+        storage.compare_and_swap(entry, expected).await
+    }
+
     pub async fn storage_delete(&self, key: &str) -> Result<(), RvError> {
         let Some(storage) = self.storage.as_ref() else {
             return Err(RvError::ErrRequestNotReady);
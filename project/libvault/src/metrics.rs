@@ -0,0 +1,116 @@
+//! Prometheus metrics for `Core::handle_request`, compiled in with the `metrics` feature.
+//!
+//! Requests are labelled by the first `PATH_LABEL_SEGMENTS` segments of their path, e.g.
+//! `secret/data` or `auth/userpass`, rather than the full path, which keeps the number of series
+//! bounded no matter how many secrets are stored. Errors are labelled by `RvError` variant.
+
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder,
+};
+
+use crate::{errors::RvError, logical::Operation};
+
+/// Number of leading path segments kept in the `path` label.
+const PATH_LABEL_SEGMENTS: usize = 2;
+
+struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    errors: IntCounterVec,
+    latency: HistogramVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        let requests = IntCounterVec::new(
+            Opts::new("vault_requests_total", "Requests handled by the core."),
+            &["path", "operation"],
+        )
+        .unwrap();
+        let errors = IntCounterVec::new(
+            Opts::new(
+                "vault_request_errors_total",
+                "Requests that failed, by error.",
+            ),
+            &["path", "error"],
+        )
+        .unwrap();
+        let latency = HistogramVec::new(
+            HistogramOpts::new(
+                "vault_request_duration_seconds",
+                "Time spent handling a request.",
+            ),
+            &["path", "operation"],
+        )
+        .unwrap();
+
+        registry.register(Box::new(requests.clone())).unwrap();
+        registry.register(Box::new(errors.clone())).unwrap();
+        registry.register(Box::new(latency.clone())).unwrap();
+
+        Self {
+            registry,
+            requests,
+            errors,
+            latency,
+        }
+    }
+}
+
+lazy_static! {
+    static ref METRICS: Metrics = Metrics::new();
+}
+
+/// Record one handled request.
+pub fn observe_request(path: &str, operation: Operation, elapsed: Duration, err: Option<&RvError>) {
+    let path = path_label(path);
+    let operation = operation.to_string();
+    METRICS
+        .requests
+        .with_label_values(&[&path, &operation])
+        .inc();
+    METRICS
+        .latency
+        .with_label_values(&[&path, &operation])
+        .observe(elapsed.as_secs_f64());
+
+    if let Some(err) = err {
+        METRICS
+            .errors
+            .with_label_values(&[&path, &error_variant(err)])
+            .inc();
+    }
+}
+
+/// Render every metric in the Prometheus text exposition format.
+pub fn gather() -> Result<String, RvError> {
+    let mut buf = Vec::new();
+    TextEncoder::new()
+        .encode(&METRICS.registry.gather(), &mut buf)
+        .map_err(|e| RvError::ErrString(e.to_string()))?;
+    String::from_utf8(buf).map_err(|e| RvError::ErrString(e.to_string()))
+}
+
+/// The first `PATH_LABEL_SEGMENTS` segments of `path`.
+fn path_label(path: &str) -> String {
+    path.trim_matches('/')
+        .split('/')
+        .take(PATH_LABEL_SEGMENTS)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The variant name of `err`, without any payload.
+fn error_variant(err: &RvError) -> String {
+    let debug = format!("{err:?}");
+    debug
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
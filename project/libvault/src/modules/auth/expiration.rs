@@ -6,16 +6,17 @@ use std::{
     cmp::Reverse,
     collections::HashMap,
     hash::{Hash, Hasher},
-    sync::{Arc, RwLock, Weak},
-    thread,
+    sync::{Arc, Mutex, RwLock, Weak},
+    thread::{self, JoinHandle},
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use better_default::Default;
-use crossbeam_channel::{select, tick};
+use chrono::{DateTime, Utc};
+use crossbeam_channel::{Sender, bounded, select, tick};
 use priority_queue::PriorityQueue;
 use serde::{Deserialize, Serialize};
-use serde_json::{Map, Value};
+use serde_json::{Map, Value, json};
 use tokio::runtime::Runtime;
 
 use super::{TokenStore, token_store::TokenEntry};
@@ -99,6 +100,8 @@ pub struct ExpirationManager {
     pub token_view: Arc<BarrierView>,
     pub token_store: RwLock<Weak<TokenStore>>,
     queue: Arc<RwLock<PriorityQueue<Arc<LeaseEntry>, Reverse<u128>>>>,
+    // stop signal and thread of the expired lease checker, while it runs
+    checker: Mutex<Option<(Sender<()>, JoinHandle<()>)>>,
 }
 
 impl Hash for LeaseEntry {
@@ -164,6 +167,7 @@ impl ExpirationManager {
             token_view: Arc::new(token_view),
             token_store: RwLock::new(Weak::new()),
             queue: Arc::new(RwLock::new(PriorityQueue::new())),
+            checker: Mutex::new(None),
         };
 
         Ok(expiration)
@@ -233,9 +237,10 @@ impl ExpirationManager {
         }
 
         if let Some(secret) = resp.secret.as_mut() {
+            let (default_ttl, max_ttl) = self.mount_lease_ttls(&le.path);
             secret.ttl = calculate_ttl(
-                MAX_LEASE_TTL,
-                DEFAULT_LEASE_TTL,
+                max_ttl,
+                default_ttl,
                 increment,
                 Duration::ZERO,
                 secret.ttl,
@@ -256,6 +261,50 @@ impl ExpirationManager {
         Ok(Some(resp))
     }
 
+    /// Looks up the metadata of a lease entry. The secret data itself is never returned.
+    pub async fn lookup(&self, lease_id: &str) -> Result<Option<Map<String, Value>>, RvError> {
+        let Some(le) = self.load_lease_entry(lease_id).await? else {
+            return Ok(None);
+        };
+
+        let (expire_time, ttl) = if le.expire_time == SystemTime::UNIX_EPOCH {
+            (Value::Null, 0)
+        } else {
+            let ttl = le
+                .expire_time
+                .duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO);
+            (
+                Value::String(DateTime::<Utc>::from(le.expire_time).to_rfc3339()),
+                ttl.as_secs(),
+            )
+        };
+
+        let data = json!({
+            "id": le.lease_id.clone(),
+            "issue_time": DateTime::<Utc>::from(le.issue_time).to_rfc3339(),
+            "expire_time": expire_time,
+            "ttl": ttl,
+            "renewable": le.renewable(),
+        })
+        .as_object()
+        .cloned();
+
+        Ok(data)
+    }
+
+    /// The default and maximum lease TTL of the mount serving `path`, as tuned through
+    /// `sys/mounts/<path>/tune`, or the system defaults when no mount matches.
+    pub fn mount_lease_ttls(&self, path: &str) -> (Duration, Duration) {
+        match self.router.matching_mount_entry(path) {
+            Ok(Some(mount_entry)) => match mount_entry.read() {
+                Ok(entry) => (entry.default_lease_ttl(), entry.max_lease_ttl()),
+                Err(_) => (DEFAULT_LEASE_TTL, MAX_LEASE_TTL),
+            },
+            _ => (DEFAULT_LEASE_TTL, MAX_LEASE_TTL),
+        }
+    }
+
     /// Renews a token by the given increment.
     pub async fn renew_token(
         &self,
@@ -293,9 +342,10 @@ impl ExpirationManager {
 
         let mut auth = resp.auth.unwrap();
 
+        let (default_ttl, max_ttl) = self.mount_lease_ttls(&le.path);
         auth.ttl = calculate_ttl(
-            MAX_LEASE_TTL,
-            DEFAULT_LEASE_TTL,
+            max_ttl,
+            default_ttl,
             increment,
             auth.period,
             auth.ttl,
@@ -309,6 +359,7 @@ impl ExpirationManager {
         le.auth = Some(auth.clone());
 
         self.persist_lease_entry(&le).await?;
+        token_store.set_expire_time(&te.id, le.expire_time).await?;
         self.register_lease_entry(Arc::new(le))?;
 
         Ok(Some(Response {
@@ -324,12 +375,13 @@ impl ExpirationManager {
         resp: &mut Response,
     ) -> Result<String, RvError> {
         if let Some(secret) = resp.secret.as_mut() {
+            let (default_ttl, max_ttl) = self.mount_lease_ttls(&req.path);
             if secret.ttl.as_secs() == 0 {
-                secret.ttl = DEFAULT_LEASE_DURATION_SECS;
+                secret.ttl = default_ttl;
             }
 
-            if secret.ttl > MAX_LEASE_DURATION_SECS {
-                secret.ttl = MAX_LEASE_DURATION_SECS;
+            if secret.ttl > max_ttl {
+                secret.ttl = max_ttl;
             }
 
             let now = SystemTime::now();
@@ -478,17 +530,25 @@ impl ExpirationManager {
 
     /// Starts a background task to check for and handle expired lease entries.
     pub fn start_check_expired_lease_entries(&self) {
+        let mut checker = self.checker.lock().unwrap();
+        if checker.is_some() {
+            return;
+        }
+
         let queue = self.queue.clone();
         let expiration = self.self_ptr.upgrade().unwrap().clone();
 
         let ticker = tick(Duration::from_millis(200));
-        thread::spawn(move || {
+        // dropping `stop_tx` disconnects `stop_rx`, which wakes the loop up to exit
+        let (stop_tx, stop_rx) = bounded::<()>(0);
+        let handle = thread::spawn(move || {
             let rt = Runtime::new().unwrap();
             let queue_cloned = queue;
             let expiration_cloned = expiration;
             rt.block_on(async move {
                 loop {
                     select! {
+                        recv(stop_rx) -> _ => break,
                         recv(ticker) -> _ => {
                             let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|t| t.as_millis()).unwrap_or(0);
                             let expired = {
@@ -535,10 +595,17 @@ impl ExpirationManager {
                 }
             });
         });
+        checker.replace((stop_tx, handle));
     }
 
-    /// Stops the background task that checks for expired lease entries.
+    /// Stops the background task that checks for expired lease entries, waiting for it to exit,
+    /// and drops the queued entries.
     pub fn stop_check_expired_lease_entries(&self) -> Result<(), RvError> {
+        if let Some((stop_tx, handle)) = self.checker.lock().unwrap().take() {
+            drop(stop_tx);
+            let _ = handle.join();
+        }
+
         let mut queue_write_locked = self.queue.write()?;
         queue_write_locked.clear();
         Ok(())
@@ -18,15 +18,17 @@ use crate::{
     handler::{AuthHandler, Handler},
     logical::Backend,
     modules::Module,
-    mount::{MountEntry, MountTable, MountsRouter},
+    mount::{MountConfig, MountEntry, MountTable, MountsRouter},
     rv_error_response_status,
     storage::{barrier::SecurityBarrier, barrier_view::BarrierView},
     utils::{generate_uuid, is_protect_path},
 };
 
 pub mod expiration;
+pub mod token_role;
 pub mod token_store;
 pub use expiration::ExpirationManager;
+pub use token_role::TokenRole;
 pub use token_store::TokenStore;
 
 const AUTH_CONFIG_PATH: &str = "core/auth";
@@ -128,9 +130,16 @@ impl AuthModule {
             let backend = backend_new_func(self.core.clone())?;
 
             entry.uuid = generate_uuid();
-
-            let prefix = format!("{}{}/", AUTH_BARRIER_PREFIX, &entry.uuid);
+            entry.namespace_id = self
+                .core
+                .namespaces
+                .owner(&entry.path)
+                .map(|ns| ns.id)
+                .unwrap_or_default();
+
+            let prefix = entry.barrier_path(AUTH_BARRIER_PREFIX);
             let view = BarrierView::new(self.barrier.clone(), &prefix);
+            view.set_seal_wrap(entry.seal_wrap);
 
             let path = format!("{}{}", AUTH_ROUTER_PREFIX, &entry.path);
             let key = entry.path.clone();
@@ -209,6 +218,16 @@ impl AuthModule {
             return Err(RvError::ErrMountPathProtected);
         }
 
+        let namespace_of = |path: &str| {
+            self.core
+                .namespaces
+                .owner(path.trim_start_matches(AUTH_ROUTER_PREFIX))
+                .map(|ns| ns.id)
+        };
+        if namespace_of(&src) != namespace_of(&dst) {
+            return Err(RvError::ErrMountPathProtected);
+        }
+
         let mounts_router = &self.mounts_router;
 
         let dst_match = mounts_router.router.matching_mount(&dst)?;
@@ -259,6 +278,39 @@ impl AuthModule {
         Ok(())
     }
 
+    /// Update the tunable settings of the auth method mounted at `path`, the auth table
+    /// counterpart of `Core::tune_mount`.
+    pub async fn tune_auth(
+        &self,
+        path: &str,
+        config: MountConfig,
+        description: Option<String>,
+    ) -> Result<(), RvError> {
+        let mut path = path.to_string();
+        if !path.ends_with('/') {
+            path += "/";
+        }
+
+        if !config.max_lease_ttl.is_zero() && config.default_lease_ttl > config.max_lease_ttl {
+            return Err(RvError::ErrRequestFieldInvalid);
+        }
+
+        let Some(mount_entry) = self.mounts_router.get(&path)? else {
+            return Err(RvError::ErrMountNotMatch);
+        };
+
+        {
+            let mut entry = mount_entry.write()?;
+            entry.config = config;
+            if let Some(description) = description {
+                entry.description = description;
+            }
+            entry.calc_hmac(&self.core.state.load().hmac_key)?;
+        }
+
+        self.mounts_router.persist(self.barrier.as_storage()).await
+    }
+
     pub async fn remove_auth_entry(&self, path: &str) -> Result<(), RvError> {
         if self.mounts_router.delete(path) {
             self.mounts_router
@@ -420,6 +472,9 @@ impl Module for AuthModule {
         if let Some(mounts_monitor) = core.mounts_monitor.load().as_ref() {
             mounts_monitor.remove_mounts_router(self.mounts_router.clone());
         }
+        if let Some(expiration) = self.expiration.load().as_ref() {
+            expiration.stop_check_expired_lease_entries()?;
+        }
         core.delete_handler(self.token_store.load().as_ref().unwrap().clone() as Arc<dyn Handler>)?;
         self.delete_auth_backend("token")?;
         self.teardown_auth()?;
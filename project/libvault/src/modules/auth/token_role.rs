@@ -0,0 +1,285 @@
+//! Token roles constrain the tokens created through `auth/token/create/<role>`.
+//!
+//! A role fixes the policies a token may be given, its TTLs, and whether it is renewable or an
+//! orphan. A token created against a role is not limited by the policies of the token that
+//! created it, only by the role, so a service can be handed a token that does nothing but create
+//! tokens within the role's bounds.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::TokenStore;
+use crate::{
+    errors::RvError,
+    logical::{
+        Backend, FieldBuilder, FieldType, Operation, Path, PathBuilder, Request, Response,
+        field::FieldTrait,
+    },
+    rv_error_response,
+    storage::StorageEntry,
+    utils::{deserialize_duration, policy::sanitize_policies, serialize_duration},
+};
+
+const TOKEN_ROLE_PREFIX: &str = "roles/";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenRole {
+    pub name: String,
+    /// Policies a token created against the role may ask for. If empty, the policies of the
+    /// creating token apply as usual.
+    #[serde(default)]
+    pub allowed_policies: Vec<String>,
+    #[serde(
+        default,
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub token_ttl: Duration,
+    #[serde(
+        default,
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub token_max_ttl: Duration,
+    #[serde(default)]
+    pub orphan: bool,
+    #[serde(default)]
+    pub renewable: bool,
+}
+
+impl TokenRole {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            allowed_policies: Vec::new(),
+            token_ttl: Duration::ZERO,
+            token_max_ttl: Duration::ZERO,
+            orphan: false,
+            renewable: true,
+        }
+    }
+
+    /// Check the policies a token asks for against the role. The `default` policy is always
+    /// allowed.
+    pub fn check_policies(&self, policies: &[String]) -> Result<(), RvError> {
+        if let Some(policy) = policies
+            .iter()
+            .find(|p| p.as_str() != "default" && !self.allowed_policies.contains(p))
+        {
+            return Err(rv_error_response!(&format!(
+                "token policies ({policy}) must be subset of the role's allowed policies"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl TokenStore {
+    pub fn roles_list_path(&self) -> Path {
+        let store = self
+            .self_ptr
+            .upgrade()
+            .expect("token store weak reference should be valid");
+
+        PathBuilder::new()
+            .pattern("roles/?$")
+            .operation(Operation::List, {
+                let handler = store.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.handle_role_list(backend, req).await })
+                }
+            })
+            .help("This endpoint lists the token roles.")
+            .build()
+    }
+
+    pub fn roles_path(&self) -> Path {
+        let store = self
+            .self_ptr
+            .upgrade()
+            .expect("token store weak reference should be valid");
+
+        PathBuilder::new()
+            .pattern(r"roles/(?P<role_name>\w(([\w.-]+)?\w)?)$")
+            .field(
+                "role_name",
+                FieldBuilder::new()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the role"),
+            )
+            .field(
+                "allowed_policies",
+                FieldBuilder::new()
+                    .field_type(FieldType::CommaStringSlice)
+                    .description("Policies tokens created against the role may be given"),
+            )
+            .field(
+                "token_ttl",
+                FieldBuilder::new()
+                    .field_type(FieldType::DurationSecond)
+                    .description("Default TTL of the tokens created against the role"),
+            )
+            .field(
+                "token_max_ttl",
+                FieldBuilder::new()
+                    .field_type(FieldType::DurationSecond)
+                    .description(
+                        "Maximum TTL of the tokens created against the role, renewals included",
+                    ),
+            )
+            .field(
+                "orphan",
+                FieldBuilder::new()
+                    .field_type(FieldType::Bool)
+                    .description("Whether tokens created against the role are orphans"),
+            )
+            .field(
+                "renewable",
+                FieldBuilder::new()
+                    .field_type(FieldType::Bool)
+                    .description("Whether tokens created against the role can be renewed"),
+            )
+            .operation(Operation::Read, {
+                let handler = store.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.handle_role_read(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = store.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.handle_role_write(backend, req).await })
+                }
+            })
+            .operation(Operation::Delete, {
+                let handler = store.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.handle_role_delete(backend, req).await })
+                }
+            })
+            .help("This endpoint manages the token roles that constrain auth/token/create/<role>.")
+            .build()
+    }
+
+    pub async fn get_role(&self, name: &str) -> Result<Option<TokenRole>, RvError> {
+        let Some(view) = self.view.as_ref() else {
+            return Err(RvError::ErrModuleNotInit);
+        };
+
+        match view.get(&format!("{TOKEN_ROLE_PREFIX}{name}")).await? {
+            Some(entry) => Ok(Some(serde_json::from_slice(&entry.value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn handle_role_list(
+        &self,
+        _backend: &dyn Backend,
+        _req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let Some(view) = self.view.as_ref() else {
+            return Err(RvError::ErrModuleNotInit);
+        };
+
+        let roles = view.list(TOKEN_ROLE_PREFIX).await?;
+        Ok(Some(Response::list_response(&roles)))
+    }
+
+    pub async fn handle_role_read(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("role_name")?;
+        let Some(role) = self.get_role(&name).await? else {
+            return Ok(None);
+        };
+
+        let data = json!({
+            "name": role.name,
+            "allowed_policies": role.allowed_policies,
+            "token_ttl": role.token_ttl.as_secs(),
+            "token_max_ttl": role.token_max_ttl.as_secs(),
+            "orphan": role.orphan,
+            "renewable": role.renewable,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn handle_role_write(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let Some(view) = self.view.as_ref() else {
+            return Err(RvError::ErrModuleNotInit);
+        };
+
+        let name = req.get_data_as_str("role_name")?;
+        let mut role = self
+            .get_role(&name)
+            .await?
+            .unwrap_or_else(|| TokenRole::new(&name));
+
+        if let Ok(value) = req.get_data("allowed_policies") {
+            let mut policies = value
+                .as_comma_string_slice()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+            sanitize_policies(&mut policies, false);
+            role.allowed_policies = policies;
+        }
+
+        if let Ok(value) = req.get_data("token_ttl") {
+            role.token_ttl = value.as_duration().ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        if let Ok(value) = req.get_data("token_max_ttl") {
+            role.token_max_ttl = value.as_duration().ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        if let Ok(value) = req.get_data("orphan") {
+            role.orphan = value.as_bool().ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        if let Ok(value) = req.get_data("renewable") {
+            role.renewable = value.as_bool().ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        if role.token_max_ttl > Duration::ZERO && role.token_ttl > role.token_max_ttl {
+            return Err(rv_error_response!(
+                "token_ttl cannot be greater than token_max_ttl"
+            ));
+        }
+
+        let entry = StorageEntry::new(&format!("{TOKEN_ROLE_PREFIX}{name}"), &role)?;
+        view.put(&entry).await?;
+
+        Ok(None)
+    }
+
+    pub async fn handle_role_delete(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let Some(view) = self.view.as_ref() else {
+            return Err(RvError::ErrModuleNotInit);
+        };
+
+        let name = req.get_data_as_str("role_name")?;
+        view.delete(&format!("{TOKEN_ROLE_PREFIX}{name}")).await?;
+
+        Ok(None)
+    }
+}
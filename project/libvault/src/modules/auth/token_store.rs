@@ -15,16 +15,13 @@ use std::{
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
 use better_default::Default;
-use humantime::parse_duration;
+use humantime::{format_rfc3339, parse_duration};
 use lazy_static::lazy_static;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use serde_json::{Value, json};
+use serde_json::{Map, Value, json};
 
-use super::{
-    AUTH_ROUTER_PREFIX,
-    expiration::{DEFAULT_LEASE_DURATION_SECS, ExpirationManager, MAX_LEASE_DURATION_SECS},
-};
+use super::{AUTH_ROUTER_PREFIX, TokenRole, expiration::ExpirationManager};
 use crate::{
     core::Core,
     errors::RvError,
@@ -47,6 +44,7 @@ use crate::{
 };
 
 const TOKEN_LOOKUP_PREFIX: &str = "id/";
+const TOKEN_ACCESSOR_PREFIX: &str = "accessor/";
 const TOKEN_PARENT_PREFIX: &str = "parent/";
 const TOKEN_SALT_LOCATION: &str = "salt";
 const TOKEN_SUB_PATH: &str = "token/";
@@ -116,6 +114,34 @@ pub struct TokenEntry {
         deserialize_with = "deserialize_duration"
     )]
     pub explicit_max_ttl: Duration,
+    #[serde(default)]
+    pub accessor: String,
+    /// When the token stops being accepted, moved forward on every renewal. `UNIX_EPOCH` means the
+    /// token never expires.
+    #[default(SystemTime::UNIX_EPOCH)]
+    #[serde(
+        default = "default_system_time",
+        serialize_with = "serialize_system_time",
+        deserialize_with = "deserialize_system_time"
+    )]
+    pub expire_time: SystemTime,
+    /// Set once the last of `num_uses` has been consumed. The entry is kept so that later requests
+    /// can be told why they are rejected; the token's own lease removes it.
+    #[serde(default)]
+    pub uses_exhausted: bool,
+    /// Identity entity the token was issued for; child tokens inherit it from their parent.
+    #[serde(default)]
+    pub entity_id: String,
+    /// Namespace the token was issued in; child tokens inherit it from their parent.
+    #[serde(default)]
+    pub namespace: String,
+}
+
+/// Index entry mapping a token accessor to the token it stands for.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct AccessorEntry {
+    accessor: String,
+    salted_token_id: String,
 }
 
 /// Manages the storage and handling of tokens.
@@ -234,6 +260,25 @@ impl TokenStore {
                 .help("The token create path is used to create new tokens.")
                 .build();
 
+            let create_role_path = PathBuilder::new()
+                .pattern(r"create/(?P<role_name>\w(([\w.-]+)?\w)?)$")
+                .field(
+                    "role_name",
+                    FieldBuilder::new()
+                        .field_type(FieldType::Str)
+                        .required(true)
+                        .description("Name of the role to create the token against"),
+                )
+                .operation(Operation::Write, {
+                    let handler = store.clone();
+                    move |backend, req| {
+                        let handler = handler.clone();
+                        Box::pin(async move { handler.handle_create_role(backend, req).await })
+                    }
+                })
+                .help("This path creates a token constrained by a token role.")
+                .build();
+
             let lookup_path = PathBuilder::new()
                 .pattern("lookup/(?P<token>.+)")
                 .field(
@@ -271,7 +316,7 @@ impl TokenStore {
                 .build();
 
             let revoke_path = PathBuilder::new()
-                .pattern("revoke/(?P<token>.+)")
+                .pattern("revoke(/(?P<token>.+))?$")
                 .field(
                     "token",
                     FieldBuilder::new()
@@ -330,15 +375,91 @@ impl TokenStore {
                 .help("This endpoint will renew the token and prevent expiration.")
                 .build();
 
+            let renew_self_path = PathBuilder::new()
+                .pattern("renew-self$")
+                .field(
+                    "increment",
+                    FieldBuilder::new()
+                        .field_type(FieldType::Int)
+                        .default_value(0)
+                        .description("The desired increment in seconds to the token expiration"),
+                )
+                .operation(Operation::Write, {
+                    let handler = store.clone();
+                    move |backend, req| {
+                        let handler = handler.clone();
+                        Box::pin(async move { handler.handle_renew_self(backend, req).await })
+                    }
+                })
+                .help("This endpoint will renew the token used to call it and prevent expiration.")
+                .build();
+
+            let accessors_path = PathBuilder::new()
+                .pattern("accessors/?$")
+                .operation(Operation::List, {
+                    let handler = store.clone();
+                    move |backend, req| {
+                        let handler = handler.clone();
+                        Box::pin(async move { handler.handle_list_accessors(backend, req).await })
+                    }
+                })
+                .help("This endpoint will list the accessors of all tokens.")
+                .build();
+
+            let lookup_accessor_path = PathBuilder::new()
+                .pattern("lookup-accessor(/(?P<accessor>.+))?$")
+                .field(
+                    "accessor",
+                    FieldBuilder::new()
+                        .field_type(FieldType::Str)
+                        .description("Accessor of the token to lookup"),
+                )
+                .operation(Operation::Write, {
+                    let handler = store.clone();
+                    move |backend, req| {
+                        let handler = handler.clone();
+                        Box::pin(async move { handler.handle_lookup_accessor(backend, req).await })
+                    }
+                })
+                .help("This endpoint will lookup a token by its accessor.")
+                .build();
+
+            let revoke_accessor_path = PathBuilder::new()
+                .pattern("revoke-accessor(/(?P<accessor>.+))?$")
+                .field(
+                    "accessor",
+                    FieldBuilder::new()
+                        .field_type(FieldType::Str)
+                        .description("Accessor of the token to revoke"),
+                )
+                .operation(Operation::Write, {
+                    let handler = store.clone();
+                    move |backend, req| {
+                        let handler = handler.clone();
+                        Box::pin(async move { handler.handle_revoke_accessor(backend, req).await })
+                    }
+                })
+                .help(
+                    "This endpoint will delete the token behind an accessor and its child tokens.",
+                )
+                .build();
+
             LogicalBackend::builder()
                 .help(AUTH_TOKEN_HELP)
                 .paths(vec![
+                    create_role_path,
                     create_path,
                     lookup_path,
                     lookup_self_path,
                     revoke_path,
                     revoke_orphan_path,
                     renew_path,
+                    renew_self_path,
+                    accessors_path,
+                    lookup_accessor_path,
+                    revoke_accessor_path,
+                    self.roles_list_path(),
+                    self.roles_path(),
                 ])
                 .auth_renew_handler({
                     let handler = store.clone();
@@ -347,7 +468,7 @@ impl TokenStore {
                         Box::pin(async move { handler.auth_renew(backend, req).await })
                     }
                 })
-                .root_paths(vec!["revoke-orphan/*"])
+                .root_paths(vec!["revoke-orphan/*", "accessors*"])
                 .build()
         };
 
@@ -384,10 +505,28 @@ impl TokenStore {
             entry.id = generate_uuid();
         }
 
+        if entry.accessor.is_empty() {
+            entry.accessor = generate_uuid();
+        }
+
+        if entry.ttl > 0 && entry.expire_time == UNIX_EPOCH {
+            entry.expire_time = entry.creation_time + Duration::from_secs(entry.ttl);
+        }
+
         let salted_id = self.salt_id(&entry.id);
 
         let value = serde_json::to_string(&entry)?;
 
+        let accessor_entry = AccessorEntry {
+            accessor: entry.accessor.clone(),
+            salted_token_id: salted_id.clone(),
+        };
+        view.put(&StorageEntry::new(
+            &format!("{TOKEN_ACCESSOR_PREFIX}{}", self.salt_id(&entry.accessor)),
+            &accessor_entry,
+        )?)
+        .await?;
+
         if !entry.parent.is_empty() {
             let parent = self.lookup(&entry.parent).await?;
             if parent.is_none() {
@@ -428,9 +567,30 @@ impl TokenStore {
         entry.num_uses -= 1;
 
         if entry.num_uses == 0 {
-            return self.revoke(&entry.id).await;
+            entry.uses_exhausted = true;
+            //Revoke all secrets under this token
+            self.expiration.revoke_by_token(entry).await?;
         }
 
+        self.update(view.as_ref(), entry).await
+    }
+
+    /// Moves the expiry of a token, after its lease has been renewed.
+    pub async fn set_expire_time(&self, id: &str, expire_time: SystemTime) -> Result<(), RvError> {
+        let Some(view) = self.view.as_ref() else {
+            return Err(RvError::ErrModuleNotInit);
+        };
+
+        let Some(mut entry) = self.lookup(id).await? else {
+            return Err(RvError::ErrAuthTokenNotFound);
+        };
+
+        entry.expire_time = expire_time;
+        self.update(view.as_ref(), &entry).await
+    }
+
+    /// Overwrites the stored entry of an existing token.
+    async fn update(&self, view: &dyn Storage, entry: &TokenEntry) -> Result<(), RvError> {
         let salted_id = self.salt_id(&entry.id);
         let value = serde_json::to_string(&entry)?;
 
@@ -443,6 +603,22 @@ impl TokenStore {
         view.put(&entry).await
     }
 
+    /// Resolves an accessor to the salted id of its token.
+    async fn lookup_accessor(&self, accessor: &str) -> Result<String, RvError> {
+        let Some(view) = self.view.as_ref() else {
+            return Err(RvError::ErrModuleNotInit);
+        };
+
+        let path = format!("{TOKEN_ACCESSOR_PREFIX}{}", self.salt_id(accessor));
+        let raw = view
+            .get(&path)
+            .await?
+            .ok_or(RvError::ErrAuthTokenAccessorNotFound)?;
+        let entry: AccessorEntry = serde_json::from_slice(&raw.value)?;
+
+        Ok(entry.salted_token_id)
+    }
+
     /// Checks the validity of a token and returns the associated authentication data.
     pub async fn check_token(&self, _path: &str, token: &str) -> Result<Option<Auth>, RvError> {
         if token.is_empty() {
@@ -457,10 +633,21 @@ impl TokenStore {
 
         let mut entry = te.unwrap();
 
+        if entry.uses_exhausted {
+            return Err(RvError::ErrAuthTokenUsesExhausted);
+        }
+
+        if entry.expire_time != UNIX_EPOCH && entry.expire_time <= SystemTime::now() {
+            return Err(RvError::ErrAuthTokenExpired);
+        }
+
         self.use_token(&mut entry).await?;
 
         let mut auth = Auth {
             client_token: token.to_string(),
+            accessor: entry.accessor.clone(),
+            entity_id: entry.entity_id,
+            namespace: entry.namespace,
             display_name: entry.display_name,
             token_policies: entry.policies.clone(),
             policies: entry.policies.clone(),
@@ -518,6 +705,10 @@ impl TokenStore {
         view.delete(&path).await?;
 
         if let Some(entry) = entry {
+            if !entry.accessor.is_empty() {
+                let path = format!("{TOKEN_ACCESSOR_PREFIX}{}", self.salt_id(&entry.accessor));
+                view.delete(&path).await?;
+            }
             if entry.parent.as_str() != "" {
                 let path = format!(
                     "{}{}/{}",
@@ -575,6 +766,27 @@ impl TokenStore {
         &self,
         _backend: &dyn Backend,
         req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        self.create_token(req, None).await
+    }
+
+    pub async fn handle_create_role(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("role_name")?;
+        let Some(role) = self.get_role(&name).await? else {
+            return Err(rv_error_response!(&format!("unknown role {name}")));
+        };
+
+        self.create_token(req, Some(role)).await
+    }
+
+    async fn create_token(
+        &self,
+        req: &mut Request,
+        role: Option<TokenRole>,
     ) -> Result<Option<Response>, RvError> {
         if req.body.is_none() {
             return Err(RvError::ErrRequestInvalid);
@@ -595,16 +807,28 @@ impl TokenStore {
         let mut data: TokenReqData =
             serde_json::from_value(Value::Object(req.body.as_ref().unwrap().clone()))?;
 
+        let path = match role.as_ref() {
+            Some(role) => format!("auth/token/create/{}", role.name),
+            None => "auth/token/create".into(),
+        };
+
         let mut te = TokenEntry {
             parent: req.client_token.clone(),
-            path: "auth/token/create".into(),
+            path,
             meta: data.meta.clone(),
             display_name: "token".into(),
             num_uses: data.num_uses,
+            entity_id: parent.entity_id.clone(),
+            namespace: parent.namespace.clone(),
             ..TokenEntry::default()
         };
 
         let mut renewable = data.renewable;
+        if let Some(role) = role.as_ref()
+            && !role.renewable
+        {
+            renewable = false;
+        }
 
         if !data.display_name.is_empty() {
             let mut full = format!("token-{}", data.display_name);
@@ -620,13 +844,28 @@ impl TokenStore {
             te.id.clone_from(&data.id);
         }
 
-        if data.policies.is_empty() {
-            data.policies.clone_from(&parent.policies);
-            sanitize_policies(&mut data.policies, false);
-        }
+        // A role with allowed policies replaces the creating token's policies as the bound.
+        match role
+            .as_ref()
+            .filter(|role| !role.allowed_policies.is_empty())
+        {
+            Some(role) => {
+                if data.policies.is_empty() {
+                    data.policies.clone_from(&role.allowed_policies);
+                }
+                sanitize_policies(&mut data.policies, false);
+                role.check_policies(&data.policies)?;
+            }
+            None => {
+                if data.policies.is_empty() {
+                    data.policies.clone_from(&parent.policies);
+                    sanitize_policies(&mut data.policies, false);
+                }
 
-        if !is_root && !is_str_subset(&data.policies, &parent.policies) {
-            return Err(RvError::ErrRequestInvalid);
+                if !is_root && !is_str_subset(&data.policies, &parent.policies) {
+                    return Err(RvError::ErrRequestInvalid);
+                }
+            }
         }
 
         te.policies.clone_from(&data.policies);
@@ -658,11 +897,27 @@ impl TokenStore {
         } else if !data.lease.is_empty() {
             let dur = parse_duration(&data.lease)?;
             te.ttl = dur.as_secs();
+        } else if let Some(role) = role.as_ref() {
+            te.ttl = role.token_ttl.as_secs();
         }
 
         te.period = data.period;
         te.explicit_max_ttl = data.explicit_max_ttl;
 
+        if let Some(role) = role.as_ref() {
+            // The role's max TTL also bounds renewals, so it is carried as the explicit max TTL.
+            let max_ttl = role.token_max_ttl;
+            if max_ttl > Duration::ZERO
+                && (te.explicit_max_ttl.is_zero() || te.explicit_max_ttl > max_ttl)
+            {
+                te.explicit_max_ttl = max_ttl;
+            }
+
+            if role.orphan {
+                te.parent.clear();
+            }
+        }
+
         if te.period.as_secs() > 0
             || te.ttl > 0
             || (te.ttl == 0 && !te.policies.contains(&"root".to_string()))
@@ -707,6 +962,7 @@ impl TokenStore {
                 ..Lease::default()
             },
             client_token: te.id.clone(),
+            accessor: te.accessor.clone(),
             display_name: te.display_name.clone(),
             policies: te.policies.clone(),
             period: te.period,
@@ -792,31 +1048,86 @@ impl TokenStore {
 
         let te = te.unwrap();
 
-        let meta = serde_json::to_value(&te.meta)?;
-
-        let mut data = serde_json::json!({
-            "id": te.id.clone(),
-            "policies": te.policies.clone(),
-            "path": te.path.clone(),
-            "meta": meta,
-            "display_name": te.display_name.clone(),
-            "num_uses": te.num_uses,
-            "ttl": 0,
-            "creation_time": te.creation_time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
-            "creation_ttl": te.ttl,
-            "explicit_max_ttl": te.explicit_max_ttl.as_secs(),
-        })
-        .as_object()
-        .unwrap()
-        .clone();
+        Ok(Some(Response::data_response(Some(token_info(&te)?))))
+    }
 
-        if te.period.as_secs() > 0 {
-            data.insert("period".to_string(), json!(te.period.as_secs()));
-        }
+    pub async fn handle_lookup_accessor(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let accessor = req.get_data_as_str("accessor")?;
+        let salted_id = self.lookup_accessor(&accessor).await?;
+
+        let te = self
+            .lookup_salted(&salted_id)
+            .await?
+            .ok_or(RvError::ErrAuthTokenNotFound)?;
+
+        let mut data = token_info(&te)?;
+        // The accessor must never be a way to learn the token itself.
+        data.insert("id".to_string(), Value::String(String::new()));
 
         Ok(Some(Response::data_response(Some(data))))
     }
 
+    pub async fn handle_list_accessors(
+        &self,
+        _backend: &dyn Backend,
+        _req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let Some(view) = self.view.as_ref() else {
+            return Err(RvError::ErrModuleNotInit);
+        };
+
+        let mut accessors = Vec::new();
+        for key in view.list(TOKEN_ACCESSOR_PREFIX).await? {
+            let path = format!("{TOKEN_ACCESSOR_PREFIX}{key}");
+            if let Some(raw) = view.get(&path).await? {
+                let entry: AccessorEntry = serde_json::from_slice(&raw.value)?;
+                accessors.push(entry.accessor);
+            }
+        }
+
+        Ok(Some(Response::list_response(&accessors)))
+    }
+
+    pub async fn handle_revoke_accessor(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let accessor = req.get_data_as_str("accessor")?;
+        let salted_id = self.lookup_accessor(&accessor).await?;
+
+        self.revoke_tree_salted(&salted_id).await?;
+
+        Ok(None)
+    }
+
+    pub async fn handle_renew_self(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let te = self
+            .lookup(&req.client_token)
+            .await?
+            .ok_or(RvError::ErrAuthTokenNotFound)?;
+
+        let increment = req
+            .get_data_or_default("increment")?
+            .as_i64()
+            .ok_or(RvError::ErrRequestFieldInvalid)?;
+        if increment < 0 {
+            return Err(RvError::ErrRequestFieldInvalid);
+        }
+
+        self.expiration
+            .renew_token(req, &te, Duration::from_secs(increment as u64))
+            .await
+    }
+
     pub async fn handle_renew(
         &self,
         _backend: &dyn Backend,
@@ -976,17 +1287,45 @@ impl Handler for TokenStore {
                 return Err(RvError::ErrPermissionDenied);
             }
 
+            if is_unauth_path {
+                for auth_handler in self.auth_handlers.load().iter() {
+                    match auth_handler.post_login(req, auth).await {
+                        Ok(()) | Err(RvError::ErrHandlerDefault) => continue,
+                        Err(e) => return Err(e),
+                    }
+                }
+
+                // sanitize_policies below deduplicates the union
+                if let Some(entry) = self.router.matching_mount_entry(&req.path)? {
+                    let entry = entry.read()?;
+                    auth.policies
+                        .extend(entry.config.default_policies.iter().cloned());
+                }
+            }
+
+            // The token store mints its own tokens (create) or extends existing ones (renew), so
+            // those only need their lease registered rather than a second token entry.
+            if req.path.starts_with("auth/token/") && !auth.client_token.is_empty() {
+                if req.path.starts_with("auth/token/create")
+                    && let Some(te) = self.lookup(&auth.client_token).await?
+                {
+                    self.expiration.register_auth(&te, auth).await?;
+                }
+                return Ok(());
+            }
+
+            let (default_ttl, max_ttl) = self.expiration.mount_lease_ttls(&req.path);
             if auth.ttl.as_secs() == 0 {
-                auth.ttl = DEFAULT_LEASE_DURATION_SECS;
+                auth.ttl = default_ttl;
             }
 
-            if auth.ttl > MAX_LEASE_DURATION_SECS {
-                auth.ttl = MAX_LEASE_DURATION_SECS;
+            if auth.ttl > max_ttl {
+                auth.ttl = max_ttl;
             }
 
             let token_ttl = calculate_ttl(
-                MAX_LEASE_TTL,
-                DEFAULT_LEASE_TTL,
+                max_ttl,
+                default_ttl,
                 Duration::ZERO,
                 auth.period,
                 auth.ttl,
@@ -1010,6 +1349,8 @@ impl Handler for TokenStore {
                 path: req.path.clone(),
                 meta: auth.metadata.clone(),
                 display_name: auth.display_name.clone(),
+                entity_id: auth.entity_id.clone(),
+                namespace: req.namespace.clone(),
                 ttl: token_ttl.as_secs(),
                 policies: auth.token_policies.clone(),
                 explicit_max_ttl: auth.explicit_max_ttl,
@@ -1020,6 +1361,7 @@ impl Handler for TokenStore {
             self.create(&mut te).await?;
 
             auth.client_token.clone_from(&te.id);
+            auth.accessor.clone_from(&te.accessor);
             auth.ttl = Duration::from_secs(te.ttl);
 
             self.expiration.register_auth(&te, auth).await?;
@@ -1030,3 +1372,45 @@ impl Handler for TokenStore {
         Ok(())
     }
 }
+
+/// Token properties returned by the lookup endpoints.
+fn token_info(te: &TokenEntry) -> Result<Map<String, Value>, RvError> {
+    let meta = serde_json::to_value(&te.meta)?;
+
+    let (expire_time, ttl) = if te.expire_time == UNIX_EPOCH {
+        (Value::Null, 0)
+    } else {
+        let ttl = te
+            .expire_time
+            .duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO);
+        (
+            Value::String(format_rfc3339(te.expire_time).to_string()),
+            ttl.as_secs(),
+        )
+    };
+
+    let mut data = serde_json::json!({
+        "id": te.id.clone(),
+        "accessor": te.accessor.clone(),
+        "policies": te.policies.clone(),
+        "path": te.path.clone(),
+        "meta": meta,
+        "display_name": te.display_name.clone(),
+        "num_uses": te.num_uses,
+        "ttl": ttl,
+        "expire_time": expire_time,
+        "creation_time": te.creation_time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        "creation_ttl": te.ttl,
+        "explicit_max_ttl": te.explicit_max_ttl.as_secs(),
+    })
+    .as_object()
+    .unwrap()
+    .clone();
+
+    if te.period.as_secs() > 0 {
+        data.insert("period".to_string(), json!(te.period.as_secs()));
+    }
+
+    Ok(data)
+}
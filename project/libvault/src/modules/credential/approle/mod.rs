@@ -0,0 +1,132 @@
+//! The `approle` auth method allows machines or apps to authenticate with RustyVault-defined
+//! roles.
+//!
+//! A role is configured under `role/<name>` and is identified by a `role_id`, which is not
+//! secret. Credentials are completed by a `secret_id` generated from `role/<name>/secret-id`.
+//! Only hashes of every secret id are persisted (a SHA-256 one naming its storage key and one made
+//! with the mount's KDF, see `utils::password_hash`), together with its remaining number of uses
+//! and its expiration time. Logging in at `login` with both values returns a client token carrying
+//! the role's token policies.
+
+use std::{any::Any, sync::Arc};
+
+use async_trait::async_trait;
+use derive_more::Deref;
+
+use crate::{
+    core::Core,
+    errors::RvError,
+    logical::{Backend, LogicalBackend},
+    modules::{Module, auth::AuthModule},
+    utils::locks::Locks,
+};
+
+pub mod path_login;
+pub mod path_role;
+
+pub use path_role::{RoleEntry, SecretIdEntry};
+
+static APPROLE_BACKEND_HELP: &str = r#"
+Any registered Role can authenticate itself with RustyVault. The credentials
+depends on the constraints that are set on the Role. One common required
+credential is the 'role_id' which is a unique identifier of the Role.
+It can be retrieved from the 'role/<role_name>/role-id' endpoint.
+
+The default constraint configuration is 'bind_secret_id', which requires
+the credential 'secret_id' to be presented during login. Refer to the
+documentation for other types of constraints.
+"#;
+
+pub struct AppRoleModule {
+    pub name: String,
+    pub backend: Arc<AppRoleBackend>,
+}
+
+pub struct AppRoleBackendInner {
+    pub core: Arc<Core>,
+    // Serializes the read-modify-write of a secret id's remaining uses.
+    pub secret_id_locks: Locks,
+}
+
+#[derive(Deref)]
+pub struct AppRoleBackend {
+    #[deref]
+    pub inner: Arc<AppRoleBackendInner>,
+}
+
+impl AppRoleBackend {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            inner: Arc::new(AppRoleBackendInner {
+                core,
+                secret_id_locks: Locks::new(),
+            }),
+        }
+    }
+
+    pub fn new_backend(&self) -> LogicalBackend {
+        LogicalBackend::builder()
+            .help(APPROLE_BACKEND_HELP)
+            .unauth_paths(["login"])
+            .path(self.role_path())
+            .path(self.role_list_path())
+            .path(self.role_id_path())
+            .path(self.secret_id_path())
+            .path(self.login_path())
+            .auth_renew_handler({
+                let handler = self.inner.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.login_renew(backend, req).await })
+                }
+            })
+            .build()
+    }
+}
+
+impl AppRoleModule {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            name: "approle".to_string(),
+            backend: Arc::new(AppRoleBackend::new(core)),
+        }
+    }
+}
+
+#[async_trait]
+impl Module for AppRoleModule {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn as_any_arc(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn setup(&self, core: &Core) -> Result<(), RvError> {
+        let approle = self.backend.clone();
+        let approle_backend_new_func = move |_c: Arc<Core>| -> Result<Arc<dyn Backend>, RvError> {
+            let mut approle_backend = approle.new_backend();
+            approle_backend.init()?;
+            Ok(Arc::new(approle_backend))
+        };
+
+        if let Some(auth_module) = core.module_manager.get_module::<AuthModule>("auth") {
+            return auth_module.add_auth_backend("approle", Arc::new(approle_backend_new_func));
+        } else {
+            log::error!("get auth module failed!");
+        }
+
+        Ok(())
+    }
+
+    fn cleanup(&self, core: &Core) -> Result<(), RvError> {
+        if let Some(auth_module) = core.module_manager.get_module::<AuthModule>("auth") {
+            return auth_module.delete_auth_backend("approle");
+        } else {
+            log::error!("get auth module failed!");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use super::{AppRoleBackend, AppRoleBackendInner};
+use crate::{
+    errors::RvError,
+    logical::{Auth, Backend, Field, FieldType, Operation, Path, Request, Response},
+    rv_error_response, rv_error_string,
+    storage::StorageEntry,
+    utils::{password_hash::verify_password, policy::equivalent_policies},
+};
+
+impl AppRoleBackend {
+    pub fn login_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"login$")
+            .field(
+                "role_id",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Unique identifier of the Role. Required to be supplied when the 'bind_secret_id' constraint is set."),
+            )
+            .field(
+                "secret_id",
+                Field::builder()
+                    .field_type(FieldType::SecretStr)
+                    .required(true)
+                    .description("SecretID belong to the App role"),
+            )
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.login(backend, req).await })
+                }
+            })
+            .help("Issue a token based on the credentials supplied.")
+            .build()
+    }
+}
+
+impl AppRoleBackendInner {
+    pub async fn login(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let role_id = req.get_data_as_str("role_id")?;
+        let secret_id = req.get_data_as_str("secret_id")?;
+
+        let Some(role) = self.get_role_by_role_id(req, &role_id).await? else {
+            return Err(rv_error_response!("invalid role_id or secret_id"));
+        };
+
+        if role.bind_secret_id {
+            let key = Self::secret_id_storage_key(&role.name, &secret_id);
+            let lock_entry = self.secret_id_locks.get_lock(&key);
+            let _locked = lock_entry.lock.write().await;
+
+            let Some(mut entry) = self.get_secret_id(req, &key).await? else {
+                return Err(rv_error_response!("invalid role_id or secret_id"));
+            };
+
+            if !entry.secret_id_hash.is_empty()
+                && !verify_password(&secret_id, &entry.secret_id_hash)?
+            {
+                return Err(rv_error_response!("invalid role_id or secret_id"));
+            }
+
+            if entry.is_expired() {
+                req.storage_delete(&key).await?;
+                return Err(rv_error_response!("invalid role_id or secret_id"));
+            }
+
+            match entry.secret_id_num_uses {
+                0 => {}
+                1 => req.storage_delete(&key).await?,
+                _ => {
+                    entry.secret_id_num_uses -= 1;
+                    req.storage_put(&StorageEntry::new(&key, &entry)?).await?;
+                }
+            }
+        }
+
+        let mut auth = Auth {
+            display_name: role.name.clone(),
+            alias_name: role_id,
+            ..Default::default()
+        };
+        auth.metadata.insert("role_name".into(), role.name.clone());
+
+        role.populate_token_auth(&mut auth);
+        auth.period = role.token_period;
+
+        let resp = Response {
+            auth: Some(auth),
+            ..Response::default()
+        };
+
+        Ok(Some(resp))
+    }
+
+    pub async fn login_renew(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        if req.auth.is_none() {
+            return Err(rv_error_response!("invalid request"));
+        }
+        let mut auth = req.auth.clone().unwrap();
+
+        let role_name = auth
+            .metadata
+            .get("role_name")
+            .ok_or(rv_error_response!("invalid request, not found role_name"))?;
+
+        let role = self.get_role(req, role_name).await?;
+        if role.is_none() {
+            return Err(rv_error_response!(format!(
+                "role {role_name} does not exist during renewal"
+            )));
+        }
+
+        let role = role.unwrap();
+
+        if !equivalent_policies(&role.token_policies, &auth.policies) {
+            return Err(rv_error_string!("policies have changed, not renewing"));
+        }
+
+        auth.period = role.token_period;
+        auth.ttl = role.token_ttl;
+        auth.max_ttl = role.token_max_ttl;
+
+        Ok(Some(Response {
+            auth: Some(auth),
+            ..Response::default()
+        }))
+    }
+}
@@ -0,0 +1,472 @@
+use std::time::{Duration, SystemTime};
+
+use derive_more::{Deref, DerefMut};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use super::{AppRoleBackend, AppRoleBackendInner};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response, field::FieldTrait},
+    rv_error_response,
+    storage::StorageEntry,
+    utils::{
+        default_system_time, deserialize_duration, deserialize_system_time, generate_uuid,
+        password_hash::mount_password_hasher,
+        serialize_duration, serialize_system_time, sha256,
+        token_util::{TokenParams, token_fields},
+    },
+};
+
+const ROLE_NAME_PATTERN: &str = r"(?P<role_name>\w(([\w.-]+)?\w)?)";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Deref, DerefMut)]
+pub struct RoleEntry {
+    pub name: String,
+    pub role_id: String,
+    pub bind_secret_id: bool,
+    #[serde(
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub secret_id_ttl: Duration,
+    pub secret_id_num_uses: u64,
+    #[serde(flatten)]
+    #[deref]
+    #[deref_mut]
+    pub token_params: TokenParams,
+}
+
+impl Default for RoleEntry {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            role_id: String::new(),
+            bind_secret_id: true,
+            secret_id_ttl: Duration::from_secs(0),
+            secret_id_num_uses: 0,
+            token_params: TokenParams::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleIdEntry {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretIdEntry {
+    // A value of zero means the secret id may be used an unlimited number of times.
+    pub secret_id_num_uses: u64,
+    #[serde(
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub secret_id_ttl: Duration,
+    #[serde(
+        serialize_with = "serialize_system_time",
+        deserialize_with = "deserialize_system_time"
+    )]
+    pub creation_time: SystemTime,
+    #[serde(
+        serialize_with = "serialize_system_time",
+        deserialize_with = "deserialize_system_time"
+    )]
+    pub expiration_time: SystemTime,
+    pub metadata: Map<String, Value>,
+    /// Hash of the secret id with the mount's KDF, checked at login. Empty for secret ids
+    /// generated before it was recorded; those are only matched by their storage key.
+    #[serde(default)]
+    pub secret_id_hash: String,
+}
+
+impl SecretIdEntry {
+    pub fn is_expired(&self) -> bool {
+        self.secret_id_ttl.as_secs() > 0 && SystemTime::now() >= self.expiration_time
+    }
+}
+
+impl AppRoleBackend {
+    pub fn role_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+        let backend_delete = self.inner.clone();
+
+        let mut path = Path::builder()
+            .pattern(format!(r"role/{ROLE_NAME_PATTERN}"))
+            .field(
+                "role_name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the role."),
+            )
+            .field(
+                "bind_secret_id",
+                Field::builder()
+                    .field_type(FieldType::Bool)
+                    .default_value(true)
+                    .description("Impose secret_id to be presented when logging in using this role."),
+            )
+            .field(
+                "secret_id_num_uses",
+                Field::builder()
+                    .field_type(FieldType::Int)
+                    .description(
+                        "Number of times a SecretID can access the role, after which the SecretID will expire. Defaults to 0 meaning that the secret_id is of unlimited use.",
+                    ),
+            )
+            .field(
+                "secret_id_ttl",
+                Field::builder()
+                    .field_type(FieldType::DurationSecond)
+                    .description(
+                        "Duration in seconds after which the issued SecretID should expire. Defaults to 0, meaning no expiration.",
+                    ),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_role(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.write_role(backend, req).await })
+                }
+            })
+            .operation(Operation::Delete, {
+                let handler = backend_delete.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.delete_role(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+A role can represent a service, a machine or anything that can be IDed.
+The set of policies on the role defines access to the role, meaning, any
+RustyVault token with a policy set that is a superset of the policies on the
+role registered here will have access to the role. If a SecretID is desired
+to be generated against only this specific role, it can be done via
+'role/<role_name>/secret-id' endpoint.
+                "#,
+            )
+            .build();
+
+        path.fields.extend(token_fields());
+
+        path
+    }
+
+    pub fn role_list_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"role/?")
+            .operation(Operation::List, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.list_role(backend, req).await })
+                }
+            })
+            .help("Lists all the roles registered with the backend.")
+            .build()
+    }
+
+    pub fn role_id_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(format!(r"role/{ROLE_NAME_PATTERN}/role-id"))
+            .field(
+                "role_name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the role."),
+            )
+            .operation(Operation::Read, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_role_id(backend, req).await })
+                }
+            })
+            .help("Returns the 'role_id' of the role.")
+            .build()
+    }
+
+    pub fn secret_id_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(format!(r"role/{ROLE_NAME_PATTERN}/secret-id/?"))
+            .field(
+                "role_name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the role."),
+            )
+            .field(
+                "metadata",
+                Field::builder()
+                    .field_type(FieldType::Map)
+                    .description(
+                        "Metadata to be tied to the SecretID. This should be a JSON formatted string containing the metadata in key value pairs.",
+                    ),
+            )
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.generate_secret_id(backend, req).await })
+                }
+            })
+            .help("Generate a SecretID against this role.")
+            .build()
+    }
+}
+
+impl AppRoleBackendInner {
+    pub async fn get_role(&self, req: &Request, name: &str) -> Result<Option<RoleEntry>, RvError> {
+        let key = format!("role/{}", name.to_lowercase());
+        let storage_entry = req.storage_get(&key).await?;
+        if storage_entry.is_none() {
+            return Ok(None);
+        }
+
+        let entry = storage_entry.unwrap();
+        let role_entry: RoleEntry = serde_json::from_slice(entry.value.as_slice())?;
+        Ok(Some(role_entry))
+    }
+
+    pub async fn get_role_by_role_id(
+        &self,
+        req: &Request,
+        role_id: &str,
+    ) -> Result<Option<RoleEntry>, RvError> {
+        let storage_entry = req.storage_get(&format!("role_id/{role_id}")).await?;
+        if storage_entry.is_none() {
+            return Ok(None);
+        }
+
+        let entry = storage_entry.unwrap();
+        let role_id_entry: RoleIdEntry = serde_json::from_slice(entry.value.as_slice())?;
+        self.get_role(req, &role_id_entry.name).await
+    }
+
+    pub async fn get_secret_id(
+        &self,
+        req: &Request,
+        key: &str,
+    ) -> Result<Option<SecretIdEntry>, RvError> {
+        let storage_entry = req.storage_get(key).await?;
+        if storage_entry.is_none() {
+            return Ok(None);
+        }
+
+        let entry = storage_entry.unwrap();
+        let secret_id_entry: SecretIdEntry = serde_json::from_slice(entry.value.as_slice())?;
+        Ok(Some(secret_id_entry))
+    }
+
+    pub fn secret_id_storage_key(role_name: &str, secret_id: &str) -> String {
+        format!("secret_id/{}/{}", role_name, sha256(secret_id.as_bytes()))
+    }
+
+    pub async fn read_role(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("role_name")?.to_lowercase();
+
+        let entry = self.get_role(req, &name).await?;
+        if entry.is_none() {
+            return Ok(None);
+        }
+
+        let role_entry = entry.unwrap();
+        let mut data = Map::new();
+        data.insert("bind_secret_id".into(), json!(role_entry.bind_secret_id));
+        data.insert(
+            "secret_id_ttl".into(),
+            json!(role_entry.secret_id_ttl.as_secs()),
+        );
+        data.insert(
+            "secret_id_num_uses".into(),
+            json!(role_entry.secret_id_num_uses),
+        );
+        role_entry.populate_token_data(&mut data);
+
+        Ok(Some(Response::data_response(Some(data))))
+    }
+
+    pub async fn write_role(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("role_name")?.to_lowercase();
+
+        let mut role_entry = match self.get_role(req, &name).await? {
+            Some(entry) => entry,
+            None => RoleEntry {
+                name: name.clone(),
+                role_id: generate_uuid(),
+                ..Default::default()
+            },
+        };
+
+        if let Ok(bind_secret_id_raw) = req.get_data("bind_secret_id") {
+            role_entry.bind_secret_id = bind_secret_id_raw
+                .as_bool_ex()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        if let Ok(secret_id_num_uses_raw) = req.get_data("secret_id_num_uses") {
+            let secret_id_num_uses = secret_id_num_uses_raw
+                .as_int()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+            if secret_id_num_uses < 0 {
+                return Err(rv_error_response!("secret_id_num_uses cannot be negative"));
+            }
+            role_entry.secret_id_num_uses = secret_id_num_uses as u64;
+        }
+
+        if let Ok(secret_id_ttl_raw) = req.get_data("secret_id_ttl") {
+            role_entry.secret_id_ttl = secret_id_ttl_raw
+                .as_duration()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        role_entry.token_params.parse_token_fields(req)?;
+
+        if !role_entry.bind_secret_id {
+            return Err(rv_error_response!(
+                "at least one constraint should be enabled on the role"
+            ));
+        }
+
+        let role_id_entry = StorageEntry::new(
+            format!("role_id/{}", role_entry.role_id).as_str(),
+            &RoleIdEntry { name: name.clone() },
+        )?;
+        req.storage_put(&role_id_entry).await?;
+
+        let entry = StorageEntry::new(format!("role/{name}").as_str(), &role_entry)?;
+        req.storage_put(&entry).await?;
+
+        Ok(None)
+    }
+
+    pub async fn delete_role(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("role_name")?.to_lowercase();
+
+        let Some(role_entry) = self.get_role(req, &name).await? else {
+            return Ok(None);
+        };
+
+        let prefix = format!("secret_id/{name}/");
+        for hashed in req.storage_list(&prefix).await? {
+            req.storage_delete(&format!("{prefix}{hashed}")).await?;
+        }
+
+        req.storage_delete(&format!("role_id/{}", role_entry.role_id))
+            .await?;
+        req.storage_delete(&format!("role/{name}")).await?;
+        Ok(None)
+    }
+
+    pub async fn list_role(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let roles = req.storage_list("role/").await?;
+        let resp = Response::list_response(&roles);
+        Ok(Some(resp))
+    }
+
+    pub async fn read_role_id(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("role_name")?.to_lowercase();
+
+        let Some(role_entry) = self.get_role(req, &name).await? else {
+            return Ok(None);
+        };
+
+        let data = json!({
+            "role_id": role_entry.role_id,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn generate_secret_id(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("role_name")?.to_lowercase();
+
+        let Some(role_entry) = self.get_role(req, &name).await? else {
+            return Err(rv_error_response!(format!("role {name} does not exist")));
+        };
+
+        let metadata = match req.get_data("metadata") {
+            Ok(metadata) => metadata
+                .as_object()
+                .cloned()
+                .ok_or(RvError::ErrRequestFieldInvalid)?,
+            Err(_) => Map::new(),
+        };
+
+        let secret_id = generate_uuid();
+        let now = SystemTime::now();
+        let secret_id_entry = SecretIdEntry {
+            secret_id_num_uses: role_entry.secret_id_num_uses,
+            secret_id_ttl: role_entry.secret_id_ttl,
+            creation_time: now,
+            expiration_time: if role_entry.secret_id_ttl.as_secs() > 0 {
+                now + role_entry.secret_id_ttl
+            } else {
+                default_system_time()
+            },
+            metadata,
+            secret_id_hash: mount_password_hasher(&self.core.router, req)?.hash(&secret_id)?,
+        };
+
+        let entry = StorageEntry::new(
+            Self::secret_id_storage_key(&name, &secret_id).as_str(),
+            &secret_id_entry,
+        )?;
+        req.storage_put(&entry).await?;
+
+        let data = json!({
+            "secret_id": secret_id,
+            "secret_id_ttl": secret_id_entry.secret_id_ttl.as_secs(),
+            "secret_id_num_uses": secret_id_entry.secret_id_num_uses,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+}
@@ -22,7 +22,7 @@ use crate::{
     core::Core,
     errors::RvError,
     logical::{Backend, LogicalBackend},
-    modules::{Module, auth::AuthModule},
+    modules::{Module, auth::AuthModule, credential::lockout::LoginLockout},
 };
 
 pub mod path_certs;
@@ -52,6 +52,7 @@ pub struct CertModule {
 pub struct CertBackendInner {
     pub core: Arc<Core>,
     pub crls: DashMap<String, CRLInfo>,
+    pub lockout: LoginLockout,
 }
 
 #[derive(Deref)]
@@ -65,6 +66,7 @@ impl CertBackend {
         let inner = CertBackendInner {
             core,
             crls: DashMap::new(),
+            lockout: LoginLockout::new(),
         };
         Self {
             inner: Arc::new(inner),
@@ -49,6 +49,14 @@ pub struct ParsedCert {
     pub certs: Vec<X509>,
 }
 
+/// Outcome of checking a login certificate that chains to a configured CA or matches a trusted
+/// certificate. Certificates that do neither fail `check_credentials` outright.
+enum Verification {
+    Matched(ParsedCert),
+    /// The certificate is trusted but meets the constraints of none of the configured certs.
+    Rejected(RvError),
+}
+
 impl CertBackend {
     pub fn login_path(&self) -> Path {
         let backend = self.inner.clone();
@@ -125,7 +133,20 @@ impl CertBackendInner {
         let skid_hex = utils::hex_encode_with_colon(subject_key_id);
         let akid_hex = utils::hex_encode_with_colon(authority_key_id);
 
-        let matched = self.verify_credentials(req).await?;
+        // failures are counted per common name, the alias of the logins it would produce; only
+        // for trusted certificates, as anyone can present an untrusted one with any common name
+        let router = &self.core.router;
+        self.lockout.check(router, req, &common_name).await?;
+        let matched = match self.check_credentials(req).await? {
+            Verification::Matched(matched) => matched,
+            Verification::Rejected(err) => {
+                self.lockout
+                    .record_failure(router, req, &common_name)
+                    .await?;
+                return Err(err);
+            }
+        };
+        self.lockout.record_success(req, &common_name).await?;
 
         if !matched.entry.token_bound_cidrs.is_empty() {
             let token_bound_cidrs: Vec<Box<dyn SockAddr>> = matched
@@ -141,6 +162,7 @@ impl CertBackendInner {
 
         let mut auth = Auth {
             display_name: matched.entry.display_name.clone(),
+            alias_name: common_name.clone(),
             ..Default::default()
         };
 
@@ -261,6 +283,13 @@ impl CertBackendInner {
     }
 
     async fn verify_credentials(&self, req: &Request) -> Result<ParsedCert, RvError> {
+        match self.check_credentials(req).await? {
+            Verification::Matched(matched) => Ok(matched),
+            Verification::Rejected(err) => Err(err),
+        }
+    }
+
+    async fn check_credentials(&self, req: &Request) -> Result<Verification, RvError> {
         let peer_tls_cert = req
             .connection
             .as_ref()
@@ -287,6 +316,7 @@ impl CertBackendInner {
         let trusted_chains = self.validate_cert(&roots, peer_tls_cert)?;
 
         let mut ret_err = Vec::new();
+        let mut matches_trusted = false;
 
         for trust in trusted_non_ca.iter() {
             let crt = &trust.certs[0];
@@ -299,8 +329,9 @@ impl CertBackendInner {
             if crt.serial_number() == client_cert.serial_number()
                 && crt_key_id.unwrap().as_slice() == client_key_id.unwrap().as_slice()
             {
+                matches_trusted = true;
                 match self.matches_constraints(client_cert, &trust.certs, trust, &ocsp_config) {
-                    Ok(true) => return Ok(trust.clone()),
+                    Ok(true) => return Ok(Verification::Matched(trust.clone())),
                     Err(e) => ret_err.push(e),
                     _ => {}
                 }
@@ -308,21 +339,25 @@ impl CertBackendInner {
         }
 
         if trusted_chains.is_empty() {
-            if !ret_err.is_empty() {
-                return Err(rv_error_response!(&format!(
+            let err = if !ret_err.is_empty() {
+                rv_error_response!(&format!(
                     "invalid certificate or no client certificate supplied; additionally got errors during \
                      verification:: {ret_err:?}"
-                )));
-            }
-            return Err(rv_error_response!(
-                "invalid certificate or no client certificate supplied"
-            ));
+                ))
+            } else {
+                rv_error_response!("invalid certificate or no client certificate supplied")
+            };
+            return if matches_trusted {
+                Ok(Verification::Rejected(err))
+            } else {
+                Err(err)
+            };
         }
 
         for trust in trusted.iter() {
             if trust.certs.iter().any(|crt| trusted_chains.contains(crt)) {
                 match self.matches_constraints(client_cert, &trusted_chains, trust, &ocsp_config) {
-                    Ok(true) => return Ok(trust.clone()),
+                    Ok(true) => return Ok(Verification::Matched(trust.clone())),
                     Err(e) => ret_err.push(e),
                     _ => {}
                 }
@@ -330,15 +365,15 @@ impl CertBackendInner {
         }
 
         if !ret_err.is_empty() {
-            return Err(rv_error_response!(&format!(
+            return Ok(Verification::Rejected(rv_error_response!(&format!(
                 "no chain matching all constraints could be found for this login certificate; additionally got errors \
                  during verification: {ret_err:?}"
-            )));
+            ))));
         }
 
-        Err(rv_error_response!(
+        Ok(Verification::Rejected(rv_error_response!(
             "no chain matching all constraints could be found for this login certificate"
-        ))
+        )))
     }
 
     async fn load_trusted_certs(
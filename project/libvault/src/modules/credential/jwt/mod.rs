@@ -0,0 +1,138 @@
+//! The `jwt` auth method authenticates workloads with JSON Web Tokens issued by an external
+//! identity provider, such as an OIDC provider.
+//!
+//! Tokens are verified either against a set of statically configured PEM public keys or against
+//! the keys published at a JWKS URL. Fetched key sets are cached per URL and refreshed once the
+//! configured `jwks_refresh_interval` has elapsed, or earlier when a token is signed with a key
+//! id that is not in the cache, which covers key rotation at the provider.
+
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Instant,
+};
+
+use async_trait::async_trait;
+use derive_more::Deref;
+use jsonwebtoken::jwk::JwkSet;
+
+use crate::{
+    core::Core,
+    errors::RvError,
+    logical::{Backend, LogicalBackend},
+    modules::{Module, auth::AuthModule},
+};
+
+pub mod path_config;
+pub mod path_login;
+pub mod path_role;
+
+pub use path_config::JwtConfig;
+pub use path_role::JwtRoleEntry;
+
+static JWT_BACKEND_HELP: &str = r#"
+The JWT backend plugin allows authentication using JWTs (including OIDC ID tokens).
+
+A configured role binds claims of the presented token to a set of token
+policies. Tokens are verified against the public keys or the JWKS URL set
+at 'config'.
+"#;
+
+pub struct JwtModule {
+    pub name: String,
+    pub backend: Arc<JwtBackend>,
+}
+
+pub struct JwksCacheEntry {
+    pub keys: JwkSet,
+    pub fetched_at: Instant,
+}
+
+pub struct JwtBackendInner {
+    pub core: Arc<Core>,
+    // Key sets fetched from JWKS URLs, keyed by URL.
+    pub jwks_cache: RwLock<HashMap<String, JwksCacheEntry>>,
+}
+
+#[derive(Deref)]
+pub struct JwtBackend {
+    #[deref]
+    pub inner: Arc<JwtBackendInner>,
+}
+
+impl JwtBackend {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            inner: Arc::new(JwtBackendInner {
+                core,
+                jwks_cache: RwLock::new(HashMap::new()),
+            }),
+        }
+    }
+
+    pub fn new_backend(&self) -> LogicalBackend {
+        LogicalBackend::builder()
+            .help(JWT_BACKEND_HELP)
+            .unauth_paths(["login"])
+            .path(self.config_path())
+            .path(self.role_path())
+            .path(self.role_list_path())
+            .path(self.login_path())
+            .auth_renew_handler({
+                let handler = self.inner.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.login_renew(backend, req).await })
+                }
+            })
+            .build()
+    }
+}
+
+impl JwtModule {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            name: "jwt".to_string(),
+            backend: Arc::new(JwtBackend::new(core)),
+        }
+    }
+}
+
+#[async_trait]
+impl Module for JwtModule {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn as_any_arc(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn setup(&self, core: &Core) -> Result<(), RvError> {
+        let jwt = self.backend.clone();
+        let jwt_backend_new_func = move |_c: Arc<Core>| -> Result<Arc<dyn Backend>, RvError> {
+            let mut jwt_backend = jwt.new_backend();
+            jwt_backend.init()?;
+            Ok(Arc::new(jwt_backend))
+        };
+
+        if let Some(auth_module) = core.module_manager.get_module::<AuthModule>("auth") {
+            return auth_module.add_auth_backend("jwt", Arc::new(jwt_backend_new_func));
+        } else {
+            log::error!("get auth module failed!");
+        }
+
+        Ok(())
+    }
+
+    fn cleanup(&self, core: &Core) -> Result<(), RvError> {
+        if let Some(auth_module) = core.module_manager.get_module::<AuthModule>("auth") {
+            return auth_module.delete_auth_backend("jwt");
+        } else {
+            log::error!("get auth module failed!");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,304 @@
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::{Algorithm, DecodingKey, jwk::JwkSet};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{JwksCacheEntry, JwtBackend, JwtBackendInner};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response, field::FieldTrait},
+    rv_error_response,
+    storage::StorageEntry,
+    utils::{deserialize_duration, serialize_duration},
+};
+
+const DEFAULT_JWKS_REFRESH_INTERVAL: u64 = 3600;
+
+// A token signed with an unknown key id forces a JWKS refetch, but never more often than this,
+// so that a stream of forged tokens cannot be used to hammer the identity provider.
+const JWKS_MIN_REFETCH_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtConfig {
+    pub jwks_url: String,
+    pub jwt_validation_pubkeys: Vec<String>,
+    pub bound_issuer: String,
+    pub bound_audiences: Vec<String>,
+    #[serde(
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub jwks_refresh_interval: Duration,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            jwks_url: String::new(),
+            jwt_validation_pubkeys: Vec::new(),
+            bound_issuer: String::new(),
+            bound_audiences: Vec::new(),
+            jwks_refresh_interval: Duration::from_secs(DEFAULT_JWKS_REFRESH_INTERVAL),
+        }
+    }
+}
+
+/// Parse a PEM encoded public key for use with `alg`. Returns `None` if the key does not belong
+/// to the algorithm's key family.
+pub fn decoding_key_from_pem(pem: &str, alg: Algorithm) -> Option<DecodingKey> {
+    let pem = pem.as_bytes();
+    match alg {
+        Algorithm::RS256
+        | Algorithm::RS384
+        | Algorithm::RS512
+        | Algorithm::PS256
+        | Algorithm::PS384
+        | Algorithm::PS512 => DecodingKey::from_rsa_pem(pem).ok(),
+        Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(pem).ok(),
+        Algorithm::EdDSA => DecodingKey::from_ed_pem(pem).ok(),
+        // HMAC algorithms need a shared secret, which this backend never holds.
+        Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => None,
+    }
+}
+
+impl JwtBackend {
+    pub fn config_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"config$")
+            .field(
+                "jwks_url",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value("")
+                    .description(
+                        "JWKS URL to use to authenticate signatures. Cannot be used with \"jwt_validation_pubkeys\".",
+                    ),
+            )
+            .field(
+                "jwt_validation_pubkeys",
+                Field::builder()
+                    .field_type(FieldType::CommaStringSlice)
+                    .description(
+                        "A list of PEM-encoded public keys to use to authenticate signatures locally. Cannot be used with \"jwks_url\".",
+                    ),
+            )
+            .field(
+                "bound_issuer",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value("")
+                    .description("The value against which to match the 'iss' claim in a JWT. Optional."),
+            )
+            .field(
+                "bound_audiences",
+                Field::builder()
+                    .field_type(FieldType::CommaStringSlice)
+                    .description(
+                        "Comma-separated list of 'aud' claims that are valid for login; any match is sufficient.",
+                    ),
+            )
+            .field(
+                "jwks_refresh_interval",
+                Field::builder()
+                    .field_type(FieldType::DurationSecond)
+                    .default_value(DEFAULT_JWKS_REFRESH_INTERVAL)
+                    .description("Duration after which keys fetched from 'jwks_url' are refreshed."),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_config(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.write_config(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+The JWT backend validates JWTs using either the keys published at a JWKS URL
+or a set of locally configured PEM public keys. Exactly one of these sources
+must be configured.
+                "#,
+            )
+            .build()
+    }
+}
+
+impl JwtBackendInner {
+    pub async fn get_config(&self, req: &Request) -> Result<Option<JwtConfig>, RvError> {
+        let storage_entry = req.storage_get("config").await?;
+        if storage_entry.is_none() {
+            return Ok(None);
+        }
+
+        let entry = storage_entry.unwrap();
+        let config: JwtConfig = serde_json::from_slice(entry.value.as_slice())?;
+        Ok(Some(config))
+    }
+
+    pub async fn read_config(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let Some(config) = self.get_config(req).await? else {
+            return Ok(None);
+        };
+
+        let data = json!({
+            "jwks_url": config.jwks_url,
+            "jwt_validation_pubkeys": config.jwt_validation_pubkeys,
+            "bound_issuer": config.bound_issuer,
+            "bound_audiences": config.bound_audiences,
+            "jwks_refresh_interval": config.jwks_refresh_interval.as_secs(),
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn write_config(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let mut config = self.get_config(req).await?.unwrap_or_default();
+        let previous_jwks_url = config.jwks_url.clone();
+
+        if let Ok(jwks_url) = req.get_data("jwks_url") {
+            config.jwks_url = jwks_url
+                .as_str()
+                .ok_or(RvError::ErrRequestFieldInvalid)?
+                .trim()
+                .to_string();
+        }
+
+        if let Ok(pubkeys) = req.get_data("jwt_validation_pubkeys") {
+            config.jwt_validation_pubkeys = pubkeys
+                .as_comma_string_slice()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        if let Ok(bound_issuer) = req.get_data("bound_issuer") {
+            config.bound_issuer = bound_issuer
+                .as_str()
+                .ok_or(RvError::ErrRequestFieldInvalid)?
+                .trim()
+                .to_string();
+        }
+
+        if let Ok(bound_audiences) = req.get_data("bound_audiences") {
+            config.bound_audiences = bound_audiences
+                .as_comma_string_slice()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        if let Ok(refresh_interval) = req.get_data("jwks_refresh_interval") {
+            config.jwks_refresh_interval = refresh_interval
+                .as_duration()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        match (
+            config.jwks_url.is_empty(),
+            config.jwt_validation_pubkeys.is_empty(),
+        ) {
+            (true, true) => {
+                return Err(rv_error_response!(
+                    "exactly one of 'jwks_url' and 'jwt_validation_pubkeys' must be set"
+                ));
+            }
+            (false, false) => {
+                return Err(rv_error_response!(
+                    "'jwks_url' and 'jwt_validation_pubkeys' are mutually exclusive"
+                ));
+            }
+            _ => {}
+        }
+
+        for pem in config.jwt_validation_pubkeys.iter() {
+            let valid = [
+                Algorithm::RS256,
+                Algorithm::ES256,
+                Algorithm::ES384,
+                Algorithm::EdDSA,
+            ]
+            .into_iter()
+            .any(|alg| decoding_key_from_pem(pem, alg).is_some());
+            if !valid {
+                return Err(rv_error_response!("error parsing public key"));
+            }
+        }
+
+        let entry = StorageEntry::new("config", &config)?;
+        req.storage_put(&entry).await?;
+
+        // Whatever was cached for the old URL may no longer be trusted by this mount.
+        self.jwks_cache.write()?.remove(&previous_jwks_url);
+
+        Ok(None)
+    }
+
+    /// Return the key set published at `config.jwks_url`, fetching it if it is not cached, the
+    /// cached copy is older than `jwks_refresh_interval`, or `force` is set. A forced refetch is
+    /// skipped if the cached copy is very recent. If fetching fails, a stale cached copy is used.
+    pub async fn get_jwks(&self, config: &JwtConfig, force: bool) -> Result<JwkSet, RvError> {
+        let url = config.jwks_url.as_str();
+        let cached = {
+            let cache = self.jwks_cache.read()?;
+            cache.get(url).map(|entry| {
+                let age = entry.fetched_at.elapsed();
+                let fresh = if force {
+                    age < JWKS_MIN_REFETCH_INTERVAL
+                } else {
+                    age < config.jwks_refresh_interval
+                };
+                (entry.keys.clone(), fresh)
+            })
+        };
+
+        if let Some((keys, true)) = cached.as_ref() {
+            return Ok(keys.clone());
+        }
+
+        match Self::fetch_jwks(url).await {
+            Ok(keys) => {
+                let mut cache = self.jwks_cache.write()?;
+                cache.insert(
+                    url.to_string(),
+                    JwksCacheEntry {
+                        keys: keys.clone(),
+                        fetched_at: Instant::now(),
+                    },
+                );
+                Ok(keys)
+            }
+            Err(err) => match cached {
+                Some((keys, _)) => {
+                    log::warn!("failed to refresh JWKS from {url}, using cached keys: {err}");
+                    Ok(keys)
+                }
+                None => Err(err),
+            },
+        }
+    }
+
+    async fn fetch_jwks(url: &str) -> Result<JwkSet, RvError> {
+        let keys = reqwest::get(url)
+            .await?
+            .error_for_status()?
+            .json::<JwkSet>()
+            .await?;
+        Ok(keys)
+    }
+}
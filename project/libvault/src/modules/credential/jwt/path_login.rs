@@ -0,0 +1,251 @@
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header, errors::ErrorKind};
+use serde_json::{Map, Value};
+
+use super::{
+    JwtBackend, JwtBackendInner,
+    path_config::{JwtConfig, decoding_key_from_pem},
+};
+use crate::{
+    errors::RvError,
+    logical::{Auth, Backend, Field, FieldType, Operation, Path, Request, Response},
+    rv_error_response, rv_error_string,
+    utils::policy::equivalent_policies,
+};
+
+impl JwtBackend {
+    pub fn login_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"login$")
+            .field(
+                "role",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("The role to log in against."),
+            )
+            .field(
+                "jwt",
+                Field::builder()
+                    .field_type(FieldType::SecretStr)
+                    .required(true)
+                    .description("The signed JWT to validate."),
+            )
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.login(backend, req).await })
+                }
+            })
+            .help("Authenticates to RustyVault using a JWT (or OIDC) token.")
+            .build()
+    }
+}
+
+impl JwtBackendInner {
+    pub async fn login(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let role_name = req.get_data_as_str("role")?.to_lowercase();
+        let jwt = req.get_data_as_str("jwt")?;
+
+        let Some(config) = self.get_config(req).await? else {
+            return Err(rv_error_response!("could not load configuration"));
+        };
+
+        let Some(role) = self.get_role(req, &role_name).await? else {
+            return Err(rv_error_response!(format!(
+                "role {role_name} could not be found"
+            )));
+        };
+
+        let claims = self.verify_jwt(&config, &jwt).await?;
+        role.validate_claims(&claims)?;
+
+        let user_name = match claims.get(&role.user_claim) {
+            Some(Value::String(value)) => value.clone(),
+            Some(Value::Number(value)) => value.to_string(),
+            _ => {
+                return Err(rv_error_response!(format!(
+                    "claim {} not found in token",
+                    role.user_claim
+                )));
+            }
+        };
+
+        let mut auth = Auth {
+            display_name: user_name.clone(),
+            alias_name: user_name.clone(),
+            ..Default::default()
+        };
+        auth.metadata.insert("role".into(), role_name);
+        auth.metadata.insert(role.user_claim.clone(), user_name);
+
+        role.populate_token_auth(&mut auth);
+        auth.period = role.token_period;
+
+        let resp = Response {
+            auth: Some(auth),
+            ..Response::default()
+        };
+
+        Ok(Some(resp))
+    }
+
+    /// Verify the signature and the registered claims (`exp`, `nbf`, `iss`, `aud`) of `jwt` and
+    /// return its claims.
+    pub async fn verify_jwt(
+        &self,
+        config: &JwtConfig,
+        jwt: &str,
+    ) -> Result<Map<String, Value>, RvError> {
+        let header = decode_header(jwt)
+            .map_err(|err| rv_error_response!(format!("error parsing token: {err}")))?;
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(rv_error_response!(
+                "token is signed with an unsupported algorithm"
+            ));
+        }
+
+        let mut validation = Validation::new(header.alg);
+        validation.validate_nbf = true;
+        if config.bound_issuer.is_empty() {
+            validation.iss = None;
+        } else {
+            validation.set_issuer(&[config.bound_issuer.as_str()]);
+        }
+        if config.bound_audiences.is_empty() {
+            validation.validate_aud = false;
+        } else {
+            validation.set_audience(&config.bound_audiences);
+        }
+
+        if !config.jwks_url.is_empty() {
+            let kid = header.kid.as_deref();
+            match self
+                .verify_with_jwks(config, jwt, kid, &validation, false)
+                .await?
+            {
+                Some(claims) => return Ok(claims),
+                // The signing key may have been rotated in since the key set was last fetched.
+                None => {
+                    if let Some(claims) = self
+                        .verify_with_jwks(config, jwt, kid, &validation, true)
+                        .await?
+                    {
+                        return Ok(claims);
+                    }
+                }
+            }
+            return Err(rv_error_response!(
+                "no known key successfully validated the token signature"
+            ));
+        }
+
+        let keys: Vec<DecodingKey> = config
+            .jwt_validation_pubkeys
+            .iter()
+            .filter_map(|pem| decoding_key_from_pem(pem, header.alg))
+            .collect();
+        for key in keys.iter() {
+            if let Some(claims) = Self::verify_with_key(jwt, key, &validation)? {
+                return Ok(claims);
+            }
+        }
+
+        Err(rv_error_response!(
+            "no known key successfully validated the token signature"
+        ))
+    }
+
+    async fn verify_with_jwks(
+        &self,
+        config: &JwtConfig,
+        jwt: &str,
+        kid: Option<&str>,
+        validation: &Validation,
+        force_refresh: bool,
+    ) -> Result<Option<Map<String, Value>>, RvError> {
+        let jwks = self.get_jwks(config, force_refresh).await?;
+        for jwk in jwks.keys.iter() {
+            if kid.is_some() && jwk.common.key_id.as_deref() != kid {
+                continue;
+            }
+
+            let Ok(key) = DecodingKey::from_jwk(jwk) else {
+                continue;
+            };
+
+            if let Some(claims) = Self::verify_with_key(jwt, &key, validation)? {
+                return Ok(Some(claims));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns `Ok(None)` if the signature does not verify with `key`, and an error if the
+    /// signature verifies but the token is otherwise invalid.
+    fn verify_with_key(
+        jwt: &str,
+        key: &DecodingKey,
+        validation: &Validation,
+    ) -> Result<Option<Map<String, Value>>, RvError> {
+        match decode::<Map<String, Value>>(jwt, key, validation) {
+            Ok(data) => Ok(Some(data.claims)),
+            Err(err) => match err.kind() {
+                ErrorKind::InvalidSignature
+                | ErrorKind::InvalidKeyFormat
+                | ErrorKind::InvalidAlgorithm
+                | ErrorKind::InvalidRsaKey(_)
+                | ErrorKind::InvalidEcdsaKey => Ok(None),
+                _ => Err(rv_error_response!(format!("error validating token: {err}"))),
+            },
+        }
+    }
+
+    pub async fn login_renew(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        if req.auth.is_none() {
+            return Err(rv_error_response!("invalid request"));
+        }
+        let mut auth = req.auth.clone().unwrap();
+
+        let role_name = auth
+            .metadata
+            .get("role")
+            .ok_or(rv_error_response!("invalid request, not found role"))?;
+
+        let role = self.get_role(req, role_name).await?;
+        if role.is_none() {
+            return Err(rv_error_response!(format!(
+                "role {role_name} does not exist during renewal"
+            )));
+        }
+
+        let role = role.unwrap();
+
+        if !equivalent_policies(&role.token_policies, &auth.policies) {
+            return Err(rv_error_string!("policies have changed, not renewing"));
+        }
+
+        auth.period = role.token_period;
+        auth.ttl = role.token_ttl;
+        auth.max_ttl = role.token_max_ttl;
+
+        Ok(Some(Response {
+            auth: Some(auth),
+            ..Response::default()
+        }))
+    }
+}
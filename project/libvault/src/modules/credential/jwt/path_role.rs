@@ -0,0 +1,272 @@
+use derive_more::{Deref, DerefMut};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{JwtBackend, JwtBackendInner};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response},
+    rv_error_response,
+    storage::StorageEntry,
+    utils::token_util::{TokenParams, token_fields},
+};
+
+const DEFAULT_USER_CLAIM: &str = "sub";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Deref, DerefMut)]
+pub struct JwtRoleEntry {
+    pub user_claim: String,
+    pub bound_subject: String,
+    // Maps a claim name to the value it must have. A list means that any of its values is
+    // accepted.
+    pub bound_claims: Map<String, Value>,
+    #[serde(flatten)]
+    #[deref]
+    #[deref_mut]
+    pub token_params: TokenParams,
+}
+
+impl Default for JwtRoleEntry {
+    fn default() -> Self {
+        Self {
+            user_claim: DEFAULT_USER_CLAIM.to_string(),
+            bound_subject: String::new(),
+            bound_claims: Map::new(),
+            token_params: TokenParams::default(),
+        }
+    }
+}
+
+impl JwtRoleEntry {
+    /// Check the `bound_subject` and `bound_claims` constraints of the role against the claims
+    /// of a verified token.
+    pub fn validate_claims(&self, claims: &Map<String, Value>) -> Result<(), RvError> {
+        if !self.bound_subject.is_empty()
+            && claims.get("sub").and_then(|sub| sub.as_str()) != Some(self.bound_subject.as_str())
+        {
+            return Err(rv_error_response!("sub claim does not match bound subject"));
+        }
+
+        for (name, expected) in self.bound_claims.iter() {
+            let Some(actual) = claims.get(name) else {
+                return Err(rv_error_response!(format!("claim {name} is missing")));
+            };
+
+            let expected = match expected {
+                Value::Array(values) => values.clone(),
+                value => vec![value.clone()],
+            };
+            let matched = match actual {
+                Value::Array(values) => values.iter().any(|value| expected.contains(value)),
+                value => expected.contains(value),
+            };
+            if !matched {
+                return Err(rv_error_response!(format!(
+                    "claim {name} does not match any associated bound claim values"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl JwtBackend {
+    pub fn role_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+        let backend_delete = self.inner.clone();
+
+        let mut path = Path::builder()
+            .pattern(r"role/(?P<name>\w(([\w.-]+)?\w)?)")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the role."),
+            )
+            .field(
+                "user_claim",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value(DEFAULT_USER_CLAIM)
+                    .description("The claim to use for the display name of the issued token."),
+            )
+            .field(
+                "bound_subject",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value("")
+                    .description("The 'sub' claim that is valid for login. Optional."),
+            )
+            .field(
+                "bound_claims",
+                Field::builder().field_type(FieldType::Map).description(
+                    "Map of claims and values that are required to be present in the token.",
+                ),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_role(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.write_role(backend, req).await })
+                }
+            })
+            .operation(Operation::Delete, {
+                let handler = backend_delete.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.delete_role(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+A role binds the claims of a JWT to a set of token policies. A token is
+accepted for the role only if it satisfies the role's 'bound_subject' and
+'bound_claims' constraints.
+                "#,
+            )
+            .build();
+
+        path.fields.extend(token_fields());
+
+        path
+    }
+
+    pub fn role_list_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"role/?")
+            .operation(Operation::List, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.list_role(backend, req).await })
+                }
+            })
+            .help("Lists all the roles registered with the backend.")
+            .build()
+    }
+}
+
+impl JwtBackendInner {
+    pub async fn get_role(
+        &self,
+        req: &Request,
+        name: &str,
+    ) -> Result<Option<JwtRoleEntry>, RvError> {
+        let key = format!("role/{}", name.to_lowercase());
+        let storage_entry = req.storage_get(&key).await?;
+        if storage_entry.is_none() {
+            return Ok(None);
+        }
+
+        let entry = storage_entry.unwrap();
+        let role_entry: JwtRoleEntry = serde_json::from_slice(entry.value.as_slice())?;
+        Ok(Some(role_entry))
+    }
+
+    pub async fn read_role(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+
+        let Some(role_entry) = self.get_role(req, &name).await? else {
+            return Ok(None);
+        };
+
+        let mut data = Map::new();
+        data.insert(
+            "user_claim".into(),
+            Value::String(role_entry.user_claim.clone()),
+        );
+        data.insert(
+            "bound_subject".into(),
+            Value::String(role_entry.bound_subject.clone()),
+        );
+        data.insert(
+            "bound_claims".into(),
+            Value::Object(role_entry.bound_claims.clone()),
+        );
+        role_entry.populate_token_data(&mut data);
+
+        Ok(Some(Response::data_response(Some(data))))
+    }
+
+    pub async fn write_role(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?.to_lowercase();
+
+        let mut role_entry = self.get_role(req, &name).await?.unwrap_or_default();
+
+        if let Ok(user_claim) = req.get_data("user_claim") {
+            let user_claim = user_claim
+                .as_str()
+                .ok_or(RvError::ErrRequestFieldInvalid)?
+                .trim();
+            if user_claim.is_empty() {
+                return Err(rv_error_response!(
+                    "a user claim must be defined on the role"
+                ));
+            }
+            role_entry.user_claim = user_claim.to_string();
+        }
+
+        if let Ok(bound_subject) = req.get_data("bound_subject") {
+            role_entry.bound_subject = bound_subject
+                .as_str()
+                .ok_or(RvError::ErrRequestFieldInvalid)?
+                .trim()
+                .to_string();
+        }
+
+        if let Ok(bound_claims) = req.get_data("bound_claims") {
+            role_entry.bound_claims = bound_claims
+                .as_object()
+                .cloned()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        role_entry.token_params.parse_token_fields(req)?;
+
+        let entry = StorageEntry::new(format!("role/{name}").as_str(), &role_entry)?;
+        req.storage_put(&entry).await?;
+
+        Ok(None)
+    }
+
+    pub async fn delete_role(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        req.storage_delete(&format!("role/{}", name.to_lowercase()))
+            .await?;
+        Ok(None)
+    }
+
+    pub async fn list_role(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let roles = req.storage_list("role/").await?;
+        let resp = Response::list_response(&roles);
+        Ok(Some(resp))
+    }
+}
@@ -0,0 +1,148 @@
+//! Lockout of login identities after repeated failed logins, shared by the credential backends.
+//!
+//! Failures are counted per identity (a username, a certificate alias) in the storage of the auth
+//! mount. Once `lockout_threshold` failures land within `lockout_window` of the first one, every
+//! login of that identity fails with `RvError::ErrAuthLocked` for `lockout_duration`, even with the
+//! right credentials. A successful login clears the record. The thresholds are tuned per mount
+//! through `auth/<path>/tune`; a threshold of 0 disables lockout, a window of 0 never forgets
+//! failures.
+//!
+//! A failure updates the record with a compare-and-swap on its storage entry, retried until it
+//! lands, so concurrent attempts of the same identity are all counted, also by vault instances
+//! that share the storage.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::RvError, logical::Request, mount::MountConfig, router::Router, storage::StorageEntry,
+};
+
+const LOCKOUT_PREFIX: &str = "lockout/";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LockoutEntry {
+    failures: u32,
+    /// Unix seconds of the first failure of the current window.
+    window_start: u64,
+    /// Unix seconds until which logins are refused, 0 if not locked.
+    locked_until: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct LoginLockout;
+
+impl LoginLockout {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Fail with `ErrAuthLocked` if `identity` is locked out. Call before checking credentials.
+    pub async fn check(
+        &self,
+        router: &Router,
+        req: &Request,
+        identity: &str,
+    ) -> Result<(), RvError> {
+        if mount_config(router, req)?.lockout_threshold == 0 {
+            return Ok(());
+        }
+
+        match load(req, identity).await? {
+            Some(entry) if entry.locked_until > now() => Err(RvError::ErrAuthLocked),
+            _ => Ok(()),
+        }
+    }
+
+    /// Count a failed login of `identity`, locking it once the mount's threshold is reached.
+    pub async fn record_failure(
+        &self,
+        router: &Router,
+        req: &Request,
+        identity: &str,
+    ) -> Result<(), RvError> {
+        let config = mount_config(router, req)?;
+        if config.lockout_threshold == 0 {
+            return Ok(());
+        }
+
+        let key = key(identity);
+        loop {
+            let current = req.storage_get(&key).await?;
+            let mut entry: LockoutEntry = match current.as_ref() {
+                Some(current) => serde_json::from_slice(&current.value)?,
+                None => LockoutEntry::default(),
+            };
+
+            let now = now();
+            let window = config.lockout_window.as_secs();
+            if entry.failures == 0
+                || (window > 0 && now >= entry.window_start.saturating_add(window))
+            {
+                entry.failures = 0;
+                entry.window_start = now;
+            }
+
+            entry.failures += 1;
+            let failures = entry.failures;
+            if failures >= config.lockout_threshold {
+                entry.failures = 0;
+                entry.locked_until = now.saturating_add(config.lockout_duration.as_secs());
+            }
+
+            let expected = current.as_ref().map(|current| current.value.as_slice());
+            if req
+                .storage_compare_and_swap(&StorageEntry::new(&key, &entry)?, expected)
+                .await?
+            {
+                if failures >= config.lockout_threshold {
+                    log::warn!("locking out {identity} after {failures} failed logins");
+                }
+                return Ok(());
+            }
+            // Another attempt updated the record since it was read; count on top of that one.
+        }
+    }
+
+    /// Forget the failures of `identity` after a successful login.
+    pub async fn record_success(&self, req: &Request, identity: &str) -> Result<(), RvError> {
+        if load(req, identity).await?.is_some() {
+            req.storage_delete(&key(identity)).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Validate the lockout settings of a tuned mount.
+pub fn validate_lockout_config(config: &MountConfig) -> Result<(), String> {
+    if config.lockout_threshold > 0 && config.lockout_duration == Duration::ZERO {
+        return Err("lockout_duration must be set when lockout_threshold is".to_string());
+    }
+    Ok(())
+}
+
+fn mount_config(router: &Router, req: &Request) -> Result<MountConfig, RvError> {
+    match router.matching_mount_entry(&req.mount_point)? {
+        Some(entry) => Ok(entry.read()?.config.clone()),
+        None => Ok(MountConfig::default()),
+    }
+}
+
+async fn load(req: &Request, identity: &str) -> Result<Option<LockoutEntry>, RvError> {
+    match req.storage_get(&key(identity)).await? {
+        Some(entry) => Ok(Some(serde_json::from_slice(&entry.value)?)),
+        None => Ok(None),
+    }
+}
+
+fn key(identity: &str) -> String {
+    format!("{LOCKOUT_PREFIX}{identity}")
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
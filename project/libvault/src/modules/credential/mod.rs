@@ -2,4 +2,8 @@
 //! , etc.
 //!
 
+pub mod approle;
 pub mod cert;
+pub mod jwt;
+pub mod lockout;
+pub mod userpass;
@@ -0,0 +1,125 @@
+//! The `userpass` auth method allows users to authenticate with a username and password.
+//!
+//! Users are managed under the `users/` path by an operator. Passwords are never stored in
+//! plaintext; only a hash is persisted alongside the per-user token parameters, computed with the
+//! KDF tuned on the mount (bcrypt unless set otherwise, see `utils::password_hash`). A successful
+//! write to `login/<username>` returns a client token carrying the user's policies. Repeated
+//! failed logins of a user can lock it out for a while, see `credential::lockout`.
+
+use std::{any::Any, sync::Arc};
+
+use async_trait::async_trait;
+use derive_more::Deref;
+
+use crate::{
+    core::Core,
+    errors::RvError,
+    logical::{Backend, LogicalBackend},
+    modules::{Module, auth::AuthModule, credential::lockout::LoginLockout},
+};
+
+pub mod path_login;
+pub mod path_users;
+
+pub use path_users::UserEntry;
+
+static USERPASS_BACKEND_HELP: &str = r#"
+The "userpass" credential provider allows authentication using
+a combination of a username and password. No additional factors
+are supported.
+
+The username/password combination is configured using the "users/"
+endpoints by a user with root access. Authentication is then done
+by supplying the two fields for "login".
+"#;
+
+pub struct UserPassModule {
+    pub name: String,
+    pub backend: Arc<UserPassBackend>,
+}
+
+pub struct UserPassBackendInner {
+    pub core: Arc<Core>,
+    pub lockout: LoginLockout,
+}
+
+#[derive(Deref)]
+pub struct UserPassBackend {
+    #[deref]
+    pub inner: Arc<UserPassBackendInner>,
+}
+
+impl UserPassBackend {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            inner: Arc::new(UserPassBackendInner {
+                core,
+                lockout: LoginLockout::new(),
+            }),
+        }
+    }
+
+    pub fn new_backend(&self) -> LogicalBackend {
+        LogicalBackend::builder()
+            .help(USERPASS_BACKEND_HELP)
+            .unauth_paths(["login/*"])
+            .path(self.users_path())
+            .path(self.users_list_path())
+            .path(self.login_path())
+            .auth_renew_handler({
+                let handler = self.inner.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.login_renew(backend, req).await })
+                }
+            })
+            .build()
+    }
+}
+
+impl UserPassModule {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            name: "userpass".to_string(),
+            backend: Arc::new(UserPassBackend::new(core)),
+        }
+    }
+}
+
+#[async_trait]
+impl Module for UserPassModule {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn as_any_arc(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn setup(&self, core: &Core) -> Result<(), RvError> {
+        let userpass = self.backend.clone();
+        let userpass_backend_new_func = move |_c: Arc<Core>| -> Result<Arc<dyn Backend>, RvError> {
+            let mut userpass_backend = userpass.new_backend();
+            userpass_backend.init()?;
+            Ok(Arc::new(userpass_backend))
+        };
+
+        if let Some(auth_module) = core.module_manager.get_module::<AuthModule>("auth") {
+            return auth_module.add_auth_backend("userpass", Arc::new(userpass_backend_new_func));
+        } else {
+            log::error!("get auth module failed!");
+        }
+
+        Ok(())
+    }
+
+    fn cleanup(&self, core: &Core) -> Result<(), RvError> {
+        if let Some(auth_module) = core.module_manager.get_module::<AuthModule>("auth") {
+            return auth_module.delete_auth_backend("userpass");
+        } else {
+            log::error!("get auth module failed!");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use super::{UserPassBackend, UserPassBackendInner};
+use crate::{
+    errors::RvError,
+    logical::{Auth, Backend, Field, FieldType, Operation, Path, Request, Response},
+    rv_error_response, rv_error_string,
+    utils::{
+        password_hash::{mount_password_hasher, verify_password},
+        policy::equivalent_policies,
+    },
+};
+
+impl UserPassBackend {
+    pub fn login_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"login/(?P<username>\w(([\w.-]+)?\w)?)")
+            .field(
+                "username",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Username of the user."),
+            )
+            .field(
+                "password",
+                Field::builder()
+                    .field_type(FieldType::SecretStr)
+                    .required(true)
+                    .description("Password for this user."),
+            )
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.login(backend, req).await })
+                }
+            })
+            .help("Log in with a username and password.")
+            .build()
+    }
+}
+
+impl UserPassBackendInner {
+    pub async fn login(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let username = req.get_data_as_str("username")?.to_lowercase();
+        let password = req.get_data("password")?;
+        let password = password.as_str().ok_or(RvError::ErrRequestFieldInvalid)?;
+
+        let router = &self.core.router;
+        self.lockout.check(router, req, &username).await?;
+
+        let user = self.get_user(req, &username).await?;
+        let verified = match user.as_ref() {
+            Some(user) => verify_password(password, &user.password_hash)?,
+            None => {
+                // Hash the password anyway so unknown usernames answer as slowly as known ones
+                // and cannot be enumerated by timing.
+                let _ = mount_password_hasher(router, req)?.hash(password);
+                false
+            }
+        };
+        if !verified {
+            // only existing users are tracked, so made-up names cannot fill the storage
+            if user.is_some() {
+                self.lockout.record_failure(router, req, &username).await?;
+            }
+            return Err(rv_error_response!("invalid username or password"));
+        }
+        self.lockout.record_success(req, &username).await?;
+
+        let user = user.unwrap();
+
+        let mut auth = Auth {
+            display_name: username.clone(),
+            alias_name: username.clone(),
+            ..Default::default()
+        };
+        auth.metadata.insert("username".into(), username);
+
+        user.populate_token_auth(&mut auth);
+        auth.period = user.token_period;
+
+        let resp = Response {
+            auth: Some(auth),
+            ..Response::default()
+        };
+
+        Ok(Some(resp))
+    }
+
+    pub async fn login_renew(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        if req.auth.is_none() {
+            return Err(rv_error_response!("invalid request"));
+        }
+        let mut auth = req.auth.clone().unwrap();
+
+        let username = auth
+            .metadata
+            .get("username")
+            .ok_or(rv_error_response!("invalid request, not found username"))?;
+
+        let user = self.get_user(req, username).await?;
+        if user.is_none() {
+            return Ok(None);
+        }
+
+        let user = user.unwrap();
+
+        if !equivalent_policies(&user.token_policies, &auth.policies) {
+            return Err(rv_error_string!("policies have changed, not renewing"));
+        }
+
+        auth.period = user.token_period;
+        auth.ttl = user.token_ttl;
+        auth.max_ttl = user.token_max_ttl;
+
+        Ok(Some(Response {
+            auth: Some(auth),
+            ..Response::default()
+        }))
+    }
+}
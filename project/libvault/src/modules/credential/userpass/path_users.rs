@@ -0,0 +1,202 @@
+use derive_more::{Deref, DerefMut};
+use serde::{Deserialize, Serialize};
+
+use super::{UserPassBackend, UserPassBackendInner};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response},
+    rv_error_response,
+    storage::StorageEntry,
+    utils::{
+        password_hash::mount_password_hasher,
+        token_util::{TokenParams, token_fields},
+    },
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Deref, DerefMut)]
+pub struct UserEntry {
+    pub username: String,
+    pub password_hash: String,
+    #[serde(flatten)]
+    #[deref]
+    #[deref_mut]
+    pub token_params: TokenParams,
+}
+
+impl UserPassBackend {
+    pub fn users_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+        let backend_delete = self.inner.clone();
+
+        let mut path = Path::builder()
+            .pattern(r"users/(?P<username>\w(([\w.-]+)?\w)?)")
+            .field(
+                "username",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Username for this user."),
+            )
+            .field(
+                "password",
+                Field::builder()
+                    .field_type(FieldType::SecretStr)
+                    .description("Password for this user."),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_user(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.write_user(backend, req).await })
+                }
+            })
+            .operation(Operation::Delete, {
+                let handler = backend_delete.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.delete_user(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+This endpoint allows you to create, read, update, and delete users
+that are allowed to authenticate.
+
+Deleting a user will not revoke auth for prior authenticated users
+with that name. To do this, do a revoke on "login/<username>" for
+the username you want revoked.
+                "#,
+            )
+            .build();
+
+        path.fields.extend(token_fields());
+
+        path
+    }
+
+    pub fn users_list_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"users/?")
+            .operation(Operation::List, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.list_user(backend, req).await })
+                }
+            })
+            .help("This endpoint allows you to list users")
+            .build()
+    }
+}
+
+impl UserPassBackendInner {
+    pub async fn get_user(&self, req: &Request, name: &str) -> Result<Option<UserEntry>, RvError> {
+        let key = format!("user/{}", name.to_lowercase());
+        let storage_entry = req.storage_get(&key).await?;
+        if storage_entry.is_none() {
+            return Ok(None);
+        }
+
+        let entry = storage_entry.unwrap();
+        let user_entry: UserEntry = serde_json::from_slice(entry.value.as_slice())?;
+        Ok(Some(user_entry))
+    }
+
+    pub async fn set_user(
+        &self,
+        req: &Request,
+        name: &str,
+        user_entry: &UserEntry,
+    ) -> Result<(), RvError> {
+        let entry = StorageEntry::new(format!("user/{name}").as_str(), user_entry)?;
+
+        req.storage_put(&entry).await
+    }
+
+    pub async fn read_user(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("username")?.to_lowercase();
+
+        let entry = self.get_user(req, &name).await?;
+        if entry.is_none() {
+            return Ok(None);
+        }
+
+        let user_entry = entry.unwrap();
+        let mut data = serde_json::Map::new();
+        user_entry.populate_token_data(&mut data);
+
+        Ok(Some(Response::data_response(Some(data))))
+    }
+
+    pub async fn write_user(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("username")?.to_lowercase();
+
+        let mut user_entry = match self.get_user(req, &name).await? {
+            Some(entry) => entry,
+            None => UserEntry {
+                username: name.clone(),
+                ..Default::default()
+            },
+        };
+
+        if let Ok(password_raw) = req.get_data("password") {
+            let password = password_raw
+                .as_str()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+            if password.is_empty() {
+                return Err(rv_error_response!("password cannot be empty"));
+            }
+            user_entry.password_hash =
+                mount_password_hasher(&self.core.router, req)?.hash(password)?;
+        }
+
+        if user_entry.password_hash.is_empty() {
+            return Err(rv_error_response!("missing password"));
+        }
+
+        user_entry.token_params.parse_token_fields(req)?;
+
+        self.set_user(req, &name, &user_entry).await?;
+
+        Ok(None)
+    }
+
+    pub async fn delete_user(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("username")?.to_lowercase();
+
+        req.storage_delete(format!("user/{name}").as_str()).await?;
+        Ok(None)
+    }
+
+    pub async fn list_user(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let users = req.storage_list("user/").await?;
+        let resp = Response::list_response(&users);
+        Ok(Some(resp))
+    }
+}
@@ -63,6 +63,9 @@ use crypto_adaptors::tongsuo_adaptor::AdaptorCTX;
 use crate::errors::RvError;
 
 pub mod crypto_adaptors;
+pub mod signing;
+pub mod totp;
+pub mod transit;
 
 /// This defines common modes for block ciphers.
 #[derive(PartialEq)]
@@ -0,0 +1,289 @@
+use std::time::SystemTime;
+
+use openssl::{
+    ec::{EcGroup, EcKey},
+    hash::MessageDigest,
+    nid::Nid,
+    pkey::{Id, PKey, Private, Public},
+    rsa::{Padding, Rsa},
+    sign::{RsaPssSaltlen, Signer, Verifier},
+};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::{
+    errors::RvError,
+    rv_error_response,
+    utils::{deserialize_system_time, serialize_system_time},
+};
+
+pub const KEY_TYPE_RSA: &str = "rsa";
+pub const KEY_TYPE_ECDSA: &str = "ecdsa";
+pub const KEY_TYPE_ED25519: &str = "ed25519";
+
+/// A named asymmetric keypair. The private key is kept as PKCS#8 PEM and never returned.
+#[derive(Debug, Clone, Serialize, Deserialize, Zeroize)]
+#[zeroize(drop)]
+pub struct SigningKey {
+    pub name: String,
+    pub key_type: String,
+    pub key_bits: u32,
+    pub private_key: String,
+    pub public_key: String,
+    pub imported: bool,
+    #[zeroize(skip)]
+    #[serde(
+        serialize_with = "serialize_system_time",
+        deserialize_with = "deserialize_system_time"
+    )]
+    pub creation_time: SystemTime,
+}
+
+impl SigningKey {
+    /// Generate a keypair. `key_bits` selects the RSA modulus (2048, 3072 or 4096) or the ECDSA
+    /// curve (256, 384 or 521); 0 picks 2048 and 256 respectively, and it is ignored for ed25519.
+    pub fn generate(name: &str, key_type: &str, key_bits: u32) -> Result<Self, RvError> {
+        let pkey = match key_type {
+            KEY_TYPE_RSA => match key_bits {
+                0 => PKey::from_rsa(Rsa::generate(2048)?)?,
+                2048 | 3072 | 4096 => PKey::from_rsa(Rsa::generate(key_bits)?)?,
+                _ => {
+                    return Err(rv_error_response!(
+                        "rsa key_bits must be 2048, 3072 or 4096"
+                    ));
+                }
+            },
+            KEY_TYPE_ECDSA => {
+                let nid = match key_bits {
+                    0 | 256 => Nid::X9_62_PRIME256V1,
+                    384 => Nid::SECP384R1,
+                    521 => Nid::SECP521R1,
+                    _ => return Err(rv_error_response!("ecdsa key_bits must be 256, 384 or 521")),
+                };
+                let group = EcGroup::from_curve_name(nid)?;
+                PKey::from_ec_key(EcKey::generate(&group)?)?
+            }
+            KEY_TYPE_ED25519 => PKey::generate_ed25519()?,
+            _ => {
+                return Err(rv_error_response!("key_type must be rsa, ecdsa or ed25519"));
+            }
+        };
+        Self::from_pkey(name, &pkey, false)
+    }
+
+    /// Import a PEM encoded private key. The key type and size are taken from the key.
+    pub fn import(name: &str, pem: &str) -> Result<Self, RvError> {
+        let pkey = PKey::private_key_from_pem(pem.as_bytes())
+            .map_err(|_| rv_error_response!("private_key is not a valid PEM private key"))?;
+        Self::from_pkey(name, &pkey, true)
+    }
+
+    fn from_pkey(name: &str, pkey: &PKey<Private>, imported: bool) -> Result<Self, RvError> {
+        let key_type = match pkey.id() {
+            Id::RSA if pkey.bits() >= 2048 => KEY_TYPE_RSA,
+            Id::RSA => return Err(rv_error_response!("rsa keys must be at least 2048 bits")),
+            Id::EC => {
+                let nid = pkey.ec_key()?.group().curve_name();
+                if !matches!(
+                    nid,
+                    Some(Nid::X9_62_PRIME256V1 | Nid::SECP384R1 | Nid::SECP521R1)
+                ) {
+                    return Err(rv_error_response!(
+                        "ecdsa keys must use the P-256, P-384 or P-521 curve"
+                    ));
+                }
+                KEY_TYPE_ECDSA
+            }
+            Id::ED25519 => KEY_TYPE_ED25519,
+            _ => return Err(rv_error_response!("unsupported private key type")),
+        };
+        let key_bits = if key_type == KEY_TYPE_ED25519 {
+            0
+        } else {
+            pkey.bits()
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            key_type: key_type.to_string(),
+            key_bits,
+            private_key: String::from_utf8(pkey.private_key_to_pem_pkcs8()?)?,
+            public_key: String::from_utf8(pkey.public_key_to_pem()?)?,
+            imported,
+            creation_time: SystemTime::now(),
+        })
+    }
+
+    /// Resolve the digest and RSA padding for a request. Empty values take the key type's
+    /// defaults: sha2-256 with PSS for rsa, the curve's digest for ecdsa, none for ed25519.
+    fn algorithms(
+        &self,
+        hash_algorithm: &str,
+        signature_algorithm: &str,
+    ) -> Result<(Option<MessageDigest>, Option<Padding>), RvError> {
+        let digest = |name: &str| match name {
+            "sha2-256" => Ok(MessageDigest::sha256()),
+            "sha2-384" => Ok(MessageDigest::sha384()),
+            "sha2-512" => Ok(MessageDigest::sha512()),
+            _ => Err(rv_error_response!(
+                "hash_algorithm must be sha2-256, sha2-384 or sha2-512"
+            )),
+        };
+
+        match self.key_type.as_str() {
+            KEY_TYPE_RSA => {
+                let padding = match signature_algorithm {
+                    "" | "pss" => Padding::PKCS1_PSS,
+                    "pkcs1v15" => Padding::PKCS1,
+                    _ => {
+                        return Err(rv_error_response!(
+                            "signature_algorithm must be pss or pkcs1v15 for rsa keys"
+                        ));
+                    }
+                };
+                let hash_algorithm = if hash_algorithm.is_empty() {
+                    "sha2-256"
+                } else {
+                    hash_algorithm
+                };
+                Ok((Some(digest(hash_algorithm)?), Some(padding)))
+            }
+            KEY_TYPE_ECDSA => {
+                if !signature_algorithm.is_empty() {
+                    return Err(rv_error_response!(
+                        "signature_algorithm is only supported for rsa keys"
+                    ));
+                }
+                let hash_algorithm = match (hash_algorithm, self.key_bits) {
+                    ("", 384) => "sha2-384",
+                    ("", 521) => "sha2-512",
+                    ("", _) => "sha2-256",
+                    (hash_algorithm, _) => hash_algorithm,
+                };
+                Ok((Some(digest(hash_algorithm)?), None))
+            }
+            KEY_TYPE_ED25519 => {
+                if !hash_algorithm.is_empty() || !signature_algorithm.is_empty() {
+                    return Err(rv_error_response!(
+                        "ed25519 keys do not support choosing the hash or signature algorithm"
+                    ));
+                }
+                Ok((None, None))
+            }
+            _ => Err(rv_error_response!("unsupported key type")),
+        }
+    }
+
+    /// Sign `data` with the private key. ECDSA signatures are DER encoded.
+    pub fn sign(
+        &self,
+        data: &[u8],
+        hash_algorithm: &str,
+        signature_algorithm: &str,
+    ) -> Result<Vec<u8>, RvError> {
+        let (digest, padding) = self.algorithms(hash_algorithm, signature_algorithm)?;
+        let pkey = PKey::private_key_from_pem(self.private_key.as_bytes())?;
+
+        let mut signer = match digest {
+            Some(digest) => Signer::new(digest, &pkey)?,
+            None => Signer::new_without_digest(&pkey)?,
+        };
+        if let Some(padding) = padding {
+            signer.set_rsa_padding(padding)?;
+            if padding == Padding::PKCS1_PSS {
+                signer.set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)?;
+            }
+        }
+        Ok(signer.sign_oneshot_to_vec(data)?)
+    }
+
+    /// Check `signature` over `data` against the public key, with the same algorithm choices
+    /// that were used to sign. Malformed signatures are reported as invalid.
+    pub fn verify(
+        &self,
+        data: &[u8],
+        signature: &[u8],
+        hash_algorithm: &str,
+        signature_algorithm: &str,
+    ) -> Result<bool, RvError> {
+        let (digest, padding) = self.algorithms(hash_algorithm, signature_algorithm)?;
+        let pkey: PKey<Public> = PKey::public_key_from_pem(self.public_key.as_bytes())?;
+
+        let mut verifier = match digest {
+            Some(digest) => Verifier::new(digest, &pkey)?,
+            None => Verifier::new_without_digest(&pkey)?,
+        };
+        if let Some(padding) = padding {
+            verifier.set_rsa_padding(padding)?;
+            if padding == Padding::PKCS1_PSS {
+                verifier.set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)?;
+            }
+        }
+        Ok(verifier.verify_oneshot(signature, data).unwrap_or(false))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DATA: &[u8] = b"the quick brown fox";
+
+    fn round_trip(key: &SigningKey, hash_algorithm: &str, signature_algorithm: &str) {
+        let mut signature = key.sign(DATA, hash_algorithm, signature_algorithm).unwrap();
+        assert!(
+            key.verify(DATA, &signature, hash_algorithm, signature_algorithm)
+                .unwrap()
+        );
+        assert!(
+            !key.verify(
+                b"the quick brown fix",
+                &signature,
+                hash_algorithm,
+                signature_algorithm
+            )
+            .unwrap()
+        );
+
+        let last = signature.len() - 1;
+        signature[last] ^= 0x01;
+        assert!(
+            !key.verify(DATA, &signature, hash_algorithm, signature_algorithm)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rsa_sign_verify() {
+        let key = SigningKey::generate("rsa", KEY_TYPE_RSA, 2048).unwrap();
+        assert_eq!(key.key_bits, 2048);
+        round_trip(&key, "", "");
+        round_trip(&key, "sha2-512", "pkcs1v15");
+    }
+
+    #[test]
+    fn test_ecdsa_sign_verify() {
+        for key_bits in [256, 384, 521] {
+            let key = SigningKey::generate("ecdsa", KEY_TYPE_ECDSA, key_bits).unwrap();
+            assert_eq!(key.key_bits, key_bits);
+            round_trip(&key, "", "");
+        }
+        let key = SigningKey::generate("ecdsa", KEY_TYPE_ECDSA, 0).unwrap();
+        assert!(key.sign(DATA, "", "pss").is_err());
+    }
+
+    #[test]
+    fn test_ed25519_sign_verify() {
+        let key = SigningKey::generate("ed25519", KEY_TYPE_ED25519, 0).unwrap();
+        round_trip(&key, "", "");
+        assert!(key.sign(DATA, "sha2-256", "").is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_other_key() {
+        let key = SigningKey::generate("a", KEY_TYPE_ED25519, 0).unwrap();
+        let other = SigningKey::generate("b", KEY_TYPE_ED25519, 0).unwrap();
+        let signature = key.sign(DATA, "", "").unwrap();
+        assert!(!other.verify(DATA, &signature, "", "").unwrap());
+    }
+}
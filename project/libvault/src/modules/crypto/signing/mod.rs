@@ -0,0 +1,108 @@
+//! The `crypto` secrets engine signs and verifies data with named asymmetric keys whose private
+//! halves never leave RustyVault.
+//!
+//! `keys/<name>` generates a keypair (rsa, ecdsa or ed25519) or imports a PEM private key. Keys
+//! are kept in the mount's storage, so they are encrypted by the barrier like any other secret;
+//! only the public key can be read back, through `keys/<name>/public`. `sign/<name>` and
+//! `verify/<name>` take base64 data and, where the key type allows it, let each request pick the
+//! hash (`hash_algorithm`) and the RSA padding (`signature_algorithm`).
+
+use std::{any::Any, sync::Arc};
+
+use async_trait::async_trait;
+use derive_more::Deref;
+
+use crate::{
+    core::Core,
+    errors::RvError,
+    logical::{Backend, LogicalBackend},
+    modules::Module,
+    utils::locks::Locks,
+};
+
+pub mod key;
+pub mod path_keys;
+pub mod path_sign;
+
+pub use key::SigningKey;
+
+static CRYPTO_BACKEND_HELP: &str = r#"
+The crypto backend signs and verifies data with named asymmetric keys. The
+private keys are generated by RustyVault or imported once, and never leave
+it, so clients can have data signed without handling any key material.
+
+Only the public key of a named key can be read back.
+"#;
+
+pub struct SigningModule {
+    pub name: String,
+    pub backend: Arc<SigningBackend>,
+}
+
+pub struct SigningBackendInner {
+    pub core: Arc<Core>,
+    // Serializes creation and deletion of a named key.
+    pub key_locks: Locks,
+}
+
+#[derive(Deref)]
+pub struct SigningBackend {
+    #[deref]
+    pub inner: Arc<SigningBackendInner>,
+}
+
+impl SigningBackend {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            inner: Arc::new(SigningBackendInner {
+                core,
+                key_locks: Locks::new(),
+            }),
+        }
+    }
+
+    pub fn new_backend(&self) -> LogicalBackend {
+        LogicalBackend::builder()
+            .help(CRYPTO_BACKEND_HELP)
+            .path(self.keys_path())
+            .path(self.keys_list_path())
+            .path(self.keys_public_path())
+            .path(self.sign_path())
+            .path(self.verify_path())
+            .build()
+    }
+}
+
+impl SigningModule {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            name: "crypto".to_string(),
+            backend: Arc::new(SigningBackend::new(core)),
+        }
+    }
+}
+
+#[async_trait]
+impl Module for SigningModule {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn as_any_arc(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn setup(&self, core: &Core) -> Result<(), RvError> {
+        let signing = self.backend.clone();
+        let signing_backend_new_func = move |_c: Arc<Core>| -> Result<Arc<dyn Backend>, RvError> {
+            let mut signing_backend = signing.new_backend();
+            signing_backend.init()?;
+            Ok(Arc::new(signing_backend))
+        };
+        core.add_logical_backend("crypto", Arc::new(signing_backend_new_func))
+    }
+
+    fn cleanup(&self, core: &Core) -> Result<(), RvError> {
+        core.delete_logical_backend("crypto")
+    }
+}
@@ -0,0 +1,250 @@
+use humantime::format_rfc3339;
+use serde_json::json;
+
+use super::{
+    SigningBackend, SigningBackendInner,
+    key::{KEY_TYPE_ED25519, SigningKey},
+};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response, field::FieldTrait},
+    rv_error_response,
+    storage::StorageEntry,
+};
+
+impl SigningBackend {
+    pub fn keys_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+        let backend_delete = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"keys/(?P<name>\w(([\w.-]+)?\w)?)$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the key"),
+            )
+            .field(
+                "type",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value(KEY_TYPE_ED25519)
+                    .description("Type of the generated key: rsa, ecdsa or ed25519. Ignored when a key is imported."),
+            )
+            .field(
+                "key_bits",
+                Field::builder()
+                    .field_type(FieldType::Int)
+                    .default_value(0)
+                    .description("RSA modulus size (2048, 3072 or 4096) or ECDSA curve size (256, 384 or 521) of the generated key. 0 picks 2048 and 256."),
+            )
+            .field(
+                "private_key",
+                Field::builder()
+                    .field_type(FieldType::SecretStr)
+                    .default_value("")
+                    .description("PEM encoded private key to import instead of generating one."),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_key(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.create_key(backend, req).await })
+                }
+            })
+            .operation(Operation::Delete, {
+                let handler = backend_delete.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.delete_key(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+This path is used to manage the named signing keys. A write against a new
+name generates a keypair of the given type, or imports private_key when it
+is set. Existing keys cannot be overwritten.
+                "#,
+            )
+            .build()
+    }
+
+    pub fn keys_list_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"keys/?$")
+            .operation(Operation::List, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.list_keys(backend, req).await })
+                }
+            })
+            .help("List the named keys available.")
+            .build()
+    }
+
+    pub fn keys_public_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"keys/(?P<name>\w(([\w.-]+)?\w)?)/public$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the key"),
+            )
+            .operation(Operation::Read, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_public_key(backend, req).await })
+                }
+            })
+            .help("Return the PEM encoded public key of the named key.")
+            .build()
+    }
+}
+
+impl SigningBackendInner {
+    pub async fn get_key(&self, req: &Request, name: &str) -> Result<Option<SigningKey>, RvError> {
+        let Some(entry) = req.storage_get(&format!("key/{name}")).await? else {
+            return Ok(None);
+        };
+        let key: SigningKey = serde_json::from_slice(entry.value.as_slice())?;
+        Ok(Some(key))
+    }
+
+    pub async fn read_key(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+
+        let Some(key) = self.get_key(req, &name).await? else {
+            return Ok(None);
+        };
+
+        let data = json!({
+            "name": key.name,
+            "type": key.key_type,
+            "key_bits": key.key_bits,
+            "public_key": key.public_key,
+            "imported": key.imported,
+            "creation_time": format_rfc3339(key.creation_time).to_string(),
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn read_public_key(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+
+        let Some(key) = self.get_key(req, &name).await? else {
+            return Ok(None);
+        };
+
+        let data = json!({
+            "type": key.key_type,
+            "public_key": key.public_key,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn create_key(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        let key_type = req
+            .get_data_or_default("type")?
+            .as_str()
+            .ok_or(RvError::ErrRequestFieldInvalid)?
+            .to_lowercase();
+        let key_bits = req
+            .get_data_or_default("key_bits")?
+            .as_int()
+            .ok_or(RvError::ErrRequestFieldInvalid)?;
+        let private_key = req
+            .get_data_or_default("private_key")?
+            .as_str()
+            .ok_or(RvError::ErrRequestFieldInvalid)?
+            .to_string();
+
+        let lock_entry = self.key_locks.get_lock(&name);
+        let _locked = lock_entry.lock.write().await;
+
+        if self.get_key(req, &name).await?.is_some() {
+            return Err(rv_error_response!(format!("key {name} already exists")));
+        }
+
+        let key = if private_key.is_empty() {
+            let key_bits = u32::try_from(key_bits)
+                .map_err(|_| rv_error_response!("key_bits must not be negative"))?;
+            SigningKey::generate(&name, &key_type, key_bits)?
+        } else {
+            SigningKey::import(&name, &private_key)?
+        };
+
+        let entry = StorageEntry::new(format!("key/{name}").as_str(), &key)?;
+        req.storage_put(&entry).await?;
+
+        let data = json!({
+            "type": key.key_type,
+            "key_bits": key.key_bits,
+            "public_key": key.public_key,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn delete_key(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+
+        let lock_entry = self.key_locks.get_lock(&name);
+        let _locked = lock_entry.lock.write().await;
+
+        req.storage_delete(&format!("key/{name}")).await?;
+        Ok(None)
+    }
+
+    pub async fn list_keys(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let keys = req.storage_list("key/").await?;
+        let resp = Response::list_response(&keys);
+        Ok(Some(resp))
+    }
+}
@@ -0,0 +1,178 @@
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde_json::json;
+
+use super::{SigningBackend, SigningBackendInner};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response},
+    rv_error_response,
+};
+
+impl SigningBackend {
+    pub fn sign_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"sign/(?P<name>\w(([\w.-]+)?\w)?)$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the key"),
+            )
+            .field(
+                "input",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Base64 encoded data to sign"),
+            )
+            .field(
+                "hash_algorithm",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value("")
+                    .description("Hash applied to the input: sha2-256, sha2-384 or sha2-512. Defaults to sha2-256 for rsa and to the curve's hash for ecdsa; not supported for ed25519."),
+            )
+            .field(
+                "signature_algorithm",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value("")
+                    .description("RSA padding scheme: pss (default) or pkcs1v15. Only supported for rsa keys."),
+            )
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.sign(backend, req).await })
+                }
+            })
+            .help("Sign base64 encoded data with the private half of a named key.")
+            .build()
+    }
+
+    pub fn verify_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"verify/(?P<name>\w(([\w.-]+)?\w)?)$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the key"),
+            )
+            .field(
+                "input",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Base64 encoded data that was signed"),
+            )
+            .field(
+                "signature",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Base64 encoded signature to check"),
+            )
+            .field(
+                "hash_algorithm",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value("")
+                    .description("Hash the signature was made with, see sign."),
+            )
+            .field(
+                "signature_algorithm",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value("")
+                    .description("RSA padding scheme the signature was made with, see sign."),
+            )
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.verify(backend, req).await })
+                }
+            })
+            .help("Check a signature over base64 encoded data against the public half of a named key.")
+            .build()
+    }
+}
+
+impl SigningBackendInner {
+    pub async fn sign(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        let input = decode_field(req, "input")?;
+        let (hash_algorithm, signature_algorithm) = algorithm_fields(req)?;
+
+        let Some(key) = self.get_key(req, &name).await? else {
+            return Err(rv_error_response!("signing key not found"));
+        };
+
+        let signature = key.sign(&input, &hash_algorithm, &signature_algorithm)?;
+
+        let data = json!({
+            "signature": STANDARD.encode(signature),
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn verify(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        let input = decode_field(req, "input")?;
+        let signature = decode_field(req, "signature")?;
+        let (hash_algorithm, signature_algorithm) = algorithm_fields(req)?;
+
+        let Some(key) = self.get_key(req, &name).await? else {
+            return Err(rv_error_response!("signing key not found"));
+        };
+
+        let valid = key.verify(&input, &signature, &hash_algorithm, &signature_algorithm)?;
+
+        let data = json!({
+            "valid": valid,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+}
+
+fn decode_field(req: &Request, field: &str) -> Result<Vec<u8>, RvError> {
+    let value = req.get_data_as_str(field)?;
+    STANDARD
+        .decode(value)
+        .map_err(|_| rv_error_response!(format!("failed to base64-decode {field}")))
+}
+
+fn algorithm_fields(req: &Request) -> Result<(String, String), RvError> {
+    let hash_algorithm = req
+        .get_data_or_default("hash_algorithm")?
+        .as_str()
+        .ok_or(RvError::ErrRequestFieldInvalid)?
+        .to_lowercase();
+    let signature_algorithm = req
+        .get_data_or_default("signature_algorithm")?
+        .as_str()
+        .ok_or(RvError::ErrRequestFieldInvalid)?
+        .to_lowercase();
+    Ok((hash_algorithm, signature_algorithm))
+}
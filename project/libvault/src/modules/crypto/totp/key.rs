@@ -0,0 +1,129 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use openssl::{hash::MessageDigest, pkey::PKey, sign::Signer};
+use serde::{Deserialize, Serialize};
+use url::Url;
+use zeroize::Zeroize;
+
+use crate::{errors::RvError, rv_error_response};
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Zeroize)]
+#[zeroize(drop)]
+pub struct TotpKey {
+    pub key: Vec<u8>,
+    pub issuer: String,
+    pub account_name: String,
+    pub algorithm: String,
+    pub digits: u32,
+    pub period: u64,
+    pub skew: u64,
+}
+
+/// Encode `data` as unpadded RFC 4648 base32, the encoding authenticator apps expect.
+pub fn base32_encode(data: &[u8]) -> String {
+    let mut ret = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+    for byte in data {
+        buffer = (buffer << 8) | *byte as u64;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            ret.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        ret.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    ret
+}
+
+/// Decode RFC 4648 base32. Padding, whitespace and lowercase letters are accepted.
+pub fn base32_decode(data: &str) -> Option<Vec<u8>> {
+    let mut ret = Vec::with_capacity(data.len() * 5 / 8);
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+    for c in data.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let c = c.to_ascii_uppercase() as u8;
+        let value = BASE32_ALPHABET.iter().position(|a| *a == c)? as u64;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            ret.push((buffer >> bits) as u8);
+        }
+    }
+    Some(ret)
+}
+
+impl TotpKey {
+    fn message_digest(&self) -> Result<MessageDigest, RvError> {
+        match self.algorithm.as_str() {
+            "SHA1" => Ok(MessageDigest::sha1()),
+            "SHA256" => Ok(MessageDigest::sha256()),
+            "SHA512" => Ok(MessageDigest::sha512()),
+            _ => Err(rv_error_response!("unknown algorithm")),
+        }
+    }
+
+    /// The time step that `time` falls into.
+    pub fn counter_at(&self, time: SystemTime) -> Result<u64, RvError> {
+        Ok(time.duration_since(UNIX_EPOCH)?.as_secs() / self.period)
+    }
+
+    /// Compute the HOTP value (RFC 4226) for `counter`.
+    pub fn code_for_counter(&self, counter: u64) -> Result<String, RvError> {
+        let pkey = PKey::hmac(&self.key)?;
+        let mut signer = Signer::new(self.message_digest()?, &pkey)?;
+        signer.update(&counter.to_be_bytes())?;
+        let hmac = signer.sign_to_vec()?;
+
+        let offset = (hmac[hmac.len() - 1] & 0x0f) as usize;
+        let binary = ((hmac[offset] as u32 & 0x7f) << 24)
+            | ((hmac[offset + 1] as u32) << 16)
+            | ((hmac[offset + 2] as u32) << 8)
+            | (hmac[offset + 3] as u32);
+        let code = binary % 10u32.pow(self.digits);
+
+        Ok(format!("{:0width$}", code, width = self.digits as usize))
+    }
+
+    /// Return the time step, within the skew window around `time`, whose code equals `code`.
+    pub fn matching_counter(&self, code: &str, time: SystemTime) -> Result<Option<u64>, RvError> {
+        if code.len() != self.digits as usize {
+            return Ok(None);
+        }
+
+        let current = self.counter_at(time)?;
+        let first = current.saturating_sub(self.skew);
+        for counter in first..=current + self.skew {
+            if self.code_for_counter(counter)? == code {
+                return Ok(Some(counter));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Build the `otpauth://` URL that authenticator apps use to import the key.
+    pub fn url(&self) -> Result<String, RvError> {
+        let mut url = Url::parse("otpauth://totp/")?;
+        let label = if self.issuer.is_empty() {
+            self.account_name.clone()
+        } else {
+            format!("{}:{}", self.issuer, self.account_name)
+        };
+        url.set_path(&format!("/{label}"));
+        url.query_pairs_mut()
+            .append_pair("secret", &base32_encode(&self.key))
+            .append_pair("algorithm", &self.algorithm)
+            .append_pair("digits", &self.digits.to_string())
+            .append_pair("period", &self.period.to_string());
+        if !self.issuer.is_empty() {
+            url.query_pairs_mut().append_pair("issuer", &self.issuer);
+        }
+        Ok(url.to_string())
+    }
+}
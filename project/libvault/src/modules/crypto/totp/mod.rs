@@ -0,0 +1,103 @@
+//! The `totp` secrets engine generates and validates time-based one-time passwords as defined
+//! in RFC 6238.
+//!
+//! A key is created at `keys/<name>`, either from a generated shared secret or from one
+//! supplied by an external provider. `code/<name>` returns the current code on read and
+//! validates a supplied code on write. The codes accepted for a key within its skew window are
+//! remembered, so an accepted code cannot be replayed.
+
+use std::{any::Any, sync::Arc};
+
+use async_trait::async_trait;
+use derive_more::Deref;
+
+use crate::{
+    core::Core,
+    errors::RvError,
+    logical::{Backend, LogicalBackend},
+    modules::Module,
+    utils::locks::Locks,
+};
+
+pub mod key;
+pub mod path_code;
+pub mod path_keys;
+
+pub use key::TotpKey;
+
+static TOTP_BACKEND_HELP: &str = r#"
+The TOTP backend dynamically generates time-based one-time use passwords.
+It can act both as a generator, sharing the key with an authenticator app,
+and as a provider, validating codes produced from a key it was given.
+"#;
+
+pub struct TotpModule {
+    pub name: String,
+    pub backend: Arc<TotpBackend>,
+}
+
+pub struct TotpBackendInner {
+    pub core: Arc<Core>,
+    // Serializes validations of a key, so that a code cannot be accepted twice.
+    pub key_locks: Locks,
+}
+
+#[derive(Deref)]
+pub struct TotpBackend {
+    #[deref]
+    pub inner: Arc<TotpBackendInner>,
+}
+
+impl TotpBackend {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            inner: Arc::new(TotpBackendInner {
+                core,
+                key_locks: Locks::new(),
+            }),
+        }
+    }
+
+    pub fn new_backend(&self) -> LogicalBackend {
+        LogicalBackend::builder()
+            .help(TOTP_BACKEND_HELP)
+            .path(self.keys_path())
+            .path(self.keys_list_path())
+            .path(self.code_path())
+            .build()
+    }
+}
+
+impl TotpModule {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            name: "totp".to_string(),
+            backend: Arc::new(TotpBackend::new(core)),
+        }
+    }
+}
+
+#[async_trait]
+impl Module for TotpModule {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn as_any_arc(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn setup(&self, core: &Core) -> Result<(), RvError> {
+        let totp = self.backend.clone();
+        let totp_backend_new_func = move |_c: Arc<Core>| -> Result<Arc<dyn Backend>, RvError> {
+            let mut totp_backend = totp.new_backend();
+            totp_backend.init()?;
+            Ok(Arc::new(totp_backend))
+        };
+        core.add_logical_backend("totp", Arc::new(totp_backend_new_func))
+    }
+
+    fn cleanup(&self, core: &Core) -> Result<(), RvError> {
+        core.delete_logical_backend("totp")
+    }
+}
@@ -0,0 +1,137 @@
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{TotpBackend, TotpBackendInner};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response},
+    rv_error_response,
+    storage::StorageEntry,
+};
+
+/// The time steps whose codes were already accepted for a key. Only steps that are still inside
+/// the skew window are kept.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsedCodes {
+    pub counters: Vec<u64>,
+}
+
+impl TotpBackend {
+    pub fn code_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"code/(?P<name>\w(([\w.-]+)?\w)?)$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the key."),
+            )
+            .field(
+                "code",
+                Field::builder()
+                    .field_type(FieldType::SecretStr)
+                    .description("TOTP code to be validated."),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_code(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.validate_code(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+Request a time-based one-time use password or validate a password for a
+certain key. A code is accepted at most once.
+                "#,
+            )
+            .build()
+    }
+}
+
+impl TotpBackendInner {
+    pub async fn read_code(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+
+        let Some(key) = self.get_key(req, &name).await? else {
+            return Err(rv_error_response!(format!("unknown key: {name}")));
+        };
+
+        let code = key.code_for_counter(key.counter_at(SystemTime::now())?)?;
+
+        let data = json!({
+            "code": code,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn validate_code(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        let code = req.get_data_as_str("code")?;
+
+        let lock_entry = self.key_locks.get_lock(&name);
+        let _locked = lock_entry.lock.write().await;
+
+        let Some(key) = self.get_key(req, &name).await? else {
+            return Err(rv_error_response!(format!("unknown key: {name}")));
+        };
+
+        let now = SystemTime::now();
+        let valid = match key.matching_counter(&code, now)? {
+            Some(counter) => {
+                let used_key = format!("used/{name}");
+                let mut used: UsedCodes = match req.storage_get(&used_key).await? {
+                    Some(entry) => serde_json::from_slice(entry.value.as_slice())?,
+                    None => UsedCodes::default(),
+                };
+
+                if used.counters.contains(&counter) {
+                    return Err(rv_error_response!(
+                        "code already used; wait until the next time period"
+                    ));
+                }
+
+                let oldest = key.counter_at(now)?.saturating_sub(key.skew);
+                used.counters.retain(|used_counter| *used_counter >= oldest);
+                used.counters.push(counter);
+                req.storage_put(&StorageEntry::new(&used_key, &used)?)
+                    .await?;
+
+                true
+            }
+            None => false,
+        };
+
+        let data = json!({
+            "valid": valid,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+}
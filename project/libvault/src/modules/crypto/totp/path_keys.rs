@@ -0,0 +1,312 @@
+use openssl::rand::rand_priv_bytes;
+use serde_json::json;
+
+use super::{
+    TotpBackend, TotpBackendInner,
+    key::{TotpKey, base32_decode, base32_encode},
+};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response, field::FieldTrait},
+    rv_error_response,
+    storage::StorageEntry,
+};
+
+const DEFAULT_KEY_SIZE: u64 = 20;
+const DEFAULT_DIGITS: u64 = 6;
+const DEFAULT_PERIOD: u64 = 30;
+const DEFAULT_SKEW: u64 = 1;
+
+impl TotpBackend {
+    pub fn keys_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+        let backend_delete = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"keys/(?P<name>\w(([\w.-]+)?\w)?)$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the key."),
+            )
+            .field(
+                "generate",
+                Field::builder()
+                    .field_type(FieldType::Bool)
+                    .default_value(false)
+                    .description("Determines if a key should be generated by RustyVault or if a key is being passed from another service."),
+            )
+            .field(
+                "key",
+                Field::builder()
+                    .field_type(FieldType::SecretStr)
+                    .description("The shared master key used to generate a TOTP token, base32 encoded. Only used if generate is false."),
+            )
+            .field(
+                "key_size",
+                Field::builder()
+                    .field_type(FieldType::Int)
+                    .default_value(DEFAULT_KEY_SIZE)
+                    .description("Determines the size in bytes of the generated key. Only used if generate is true."),
+            )
+            .field(
+                "issuer",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value("")
+                    .description("The name of the key's issuing organization."),
+            )
+            .field(
+                "account_name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value("")
+                    .description("The name of the account associated with the key."),
+            )
+            .field(
+                "algorithm",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value("SHA1")
+                    .description("The hashing algorithm used to generate the TOTP token. Options include SHA1, SHA256 and SHA512."),
+            )
+            .field(
+                "digits",
+                Field::builder()
+                    .field_type(FieldType::Int)
+                    .default_value(DEFAULT_DIGITS)
+                    .description("The number of digits in the generated TOTP token. This value can either be 6 or 8."),
+            )
+            .field(
+                "period",
+                Field::builder()
+                    .field_type(FieldType::DurationSecond)
+                    .default_value(DEFAULT_PERIOD)
+                    .description("The length of time used to generate a counter for the TOTP token calculation."),
+            )
+            .field(
+                "skew",
+                Field::builder()
+                    .field_type(FieldType::Int)
+                    .default_value(DEFAULT_SKEW)
+                    .description("The number of delay periods that are allowed when validating a TOTP token. This value can either be 0 or 1."),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_key(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.write_key(backend, req).await })
+                }
+            })
+            .operation(Operation::Delete, {
+                let handler = backend_delete.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.delete_key(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+This path lets you manage the keys that can be created with this backend.
+When 'generate' is true, the generated key is returned once, as a base32
+string and as an otpauth URL that can be imported into authenticator apps.
+                "#,
+            )
+            .build()
+    }
+
+    pub fn keys_list_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"keys/?$")
+            .operation(Operation::List, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.list_keys(backend, req).await })
+                }
+            })
+            .help("Lists all the keys registered with the backend.")
+            .build()
+    }
+}
+
+impl TotpBackendInner {
+    pub async fn get_key(&self, req: &Request, name: &str) -> Result<Option<TotpKey>, RvError> {
+        let storage_entry = req.storage_get(&format!("key/{name}")).await?;
+        if storage_entry.is_none() {
+            return Ok(None);
+        }
+
+        let entry = storage_entry.unwrap();
+        let key: TotpKey = serde_json::from_slice(entry.value.as_slice())?;
+        Ok(Some(key))
+    }
+
+    pub async fn read_key(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+
+        let Some(key) = self.get_key(req, &name).await? else {
+            return Ok(None);
+        };
+
+        let data = json!({
+            "issuer": key.issuer,
+            "account_name": key.account_name,
+            "algorithm": key.algorithm,
+            "digits": key.digits,
+            "period": key.period,
+            "skew": key.skew,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn write_key(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        let generate = req
+            .get_data_or_default("generate")?
+            .as_bool()
+            .ok_or(RvError::ErrRequestFieldInvalid)?;
+        let issuer = req
+            .get_data_or_default("issuer")?
+            .as_str()
+            .ok_or(RvError::ErrRequestFieldInvalid)?
+            .to_string();
+        let account_name = req
+            .get_data_or_default("account_name")?
+            .as_str()
+            .ok_or(RvError::ErrRequestFieldInvalid)?
+            .to_string();
+        let algorithm = req
+            .get_data_or_default("algorithm")?
+            .as_str()
+            .ok_or(RvError::ErrRequestFieldInvalid)?
+            .to_uppercase();
+        let digits = req
+            .get_data_or_default("digits")?
+            .as_int()
+            .ok_or(RvError::ErrRequestFieldInvalid)?;
+        let period = req
+            .get_data_or_default("period")?
+            .as_duration()
+            .ok_or(RvError::ErrRequestFieldInvalid)?
+            .as_secs();
+        let skew = req
+            .get_data_or_default("skew")?
+            .as_int()
+            .ok_or(RvError::ErrRequestFieldInvalid)?;
+
+        if !matches!(algorithm.as_str(), "SHA1" | "SHA256" | "SHA512") {
+            return Err(rv_error_response!("the algorithm value is not valid"));
+        }
+        if digits != 6 && digits != 8 {
+            return Err(rv_error_response!("the digits value can only be 6 or 8"));
+        }
+        if period == 0 {
+            return Err(rv_error_response!(
+                "the period value must be greater than zero"
+            ));
+        }
+        if skew != 0 && skew != 1 {
+            return Err(rv_error_response!("the skew value must be 0 or 1"));
+        }
+
+        let secret = if generate {
+            if account_name.is_empty() {
+                return Err(rv_error_response!(
+                    "the account_name value is required for generated keys"
+                ));
+            }
+
+            let key_size = req
+                .get_data_or_default("key_size")?
+                .as_int()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+            if key_size <= 0 {
+                return Err(rv_error_response!(
+                    "the key_size value must be greater than zero"
+                ));
+            }
+
+            let mut secret = vec![0u8; key_size as usize];
+            rand_priv_bytes(&mut secret)?;
+            secret
+        } else {
+            let key = req.get_data_as_str("key")?;
+            match base32_decode(&key) {
+                Some(secret) if !secret.is_empty() => secret,
+                _ => return Err(rv_error_response!("invalid key value")),
+            }
+        };
+
+        let key = TotpKey {
+            key: secret,
+            issuer,
+            account_name,
+            algorithm,
+            digits: digits as u32,
+            period,
+            skew: skew as u64,
+        };
+
+        let entry = StorageEntry::new(format!("key/{name}").as_str(), &key)?;
+        req.storage_put(&entry).await?;
+        req.storage_delete(&format!("used/{name}")).await?;
+
+        if !generate {
+            return Ok(None);
+        }
+
+        let data = json!({
+            "key": base32_encode(&key.key),
+            "url": key.url()?,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn delete_key(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        req.storage_delete(&format!("key/{name}")).await?;
+        req.storage_delete(&format!("used/{name}")).await?;
+        Ok(None)
+    }
+
+    pub async fn list_keys(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let keys = req.storage_list("key/").await?;
+        let resp = Response::list_response(&keys);
+        Ok(Some(resp))
+    }
+}
@@ -0,0 +1,147 @@
+use std::{collections::BTreeMap, time::SystemTime};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use openssl::rand::rand_priv_bytes;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::{
+    errors::RvError,
+    modules::crypto::{AEADCipher, AES, AESKeySize, BlockCipher, CipherMode},
+    rv_error_response,
+    utils::{deserialize_system_time, serialize_system_time},
+};
+
+pub const TRANSIT_KEY_TYPE: &str = "aes256-gcm96";
+const CIPHERTEXT_PREFIX: &str = "vault:v";
+const KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+const TAG_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Zeroize)]
+#[zeroize(drop)]
+pub struct TransitKeyVersion {
+    pub key: Vec<u8>,
+    #[zeroize(skip)]
+    #[serde(
+        serialize_with = "serialize_system_time",
+        deserialize_with = "deserialize_system_time"
+    )]
+    pub creation_time: SystemTime,
+}
+
+/// A named transit key together with all of its versions. Versions start at 1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransitKey {
+    pub name: String,
+    pub key_type: String,
+    pub latest_version: u32,
+    pub versions: BTreeMap<u32, TransitKeyVersion>,
+}
+
+impl TransitKeyVersion {
+    fn generate() -> Result<Self, RvError> {
+        let mut key = vec![0u8; KEY_SIZE];
+        rand_priv_bytes(&mut key)?;
+        Ok(Self {
+            key,
+            creation_time: SystemTime::now(),
+        })
+    }
+}
+
+impl TransitKey {
+    pub fn new(name: &str) -> Result<Self, RvError> {
+        let mut key = Self {
+            name: name.to_string(),
+            key_type: TRANSIT_KEY_TYPE.to_string(),
+            latest_version: 0,
+            versions: BTreeMap::new(),
+        };
+        key.rotate()?;
+        Ok(key)
+    }
+
+    /// Add a new key version and make it the one used for encryption.
+    pub fn rotate(&mut self) -> Result<(), RvError> {
+        self.latest_version += 1;
+        self.versions
+            .insert(self.latest_version, TransitKeyVersion::generate()?);
+        Ok(())
+    }
+
+    /// Encrypt `plaintext` with the latest key version and return the versioned ciphertext.
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<String, RvError> {
+        let version = self
+            .versions
+            .get(&self.latest_version)
+            .ok_or(RvError::ErrCryptoCipherInitFailed)?;
+
+        let mut nonce = vec![0u8; NONCE_SIZE];
+        rand_priv_bytes(&mut nonce)?;
+
+        let mut aes_encrypter = AES::new(
+            false,
+            Some(AESKeySize::AES256),
+            Some(CipherMode::GCM),
+            Some(version.key.clone()),
+            Some(nonce.clone()),
+        )?;
+        aes_encrypter.set_aad(Vec::new())?;
+
+        let ciphertext = aes_encrypter.encrypt(&plaintext.to_vec())?;
+        let tag = aes_encrypter.get_tag()?;
+
+        let mut blob = nonce;
+        blob.extend_from_slice(&ciphertext);
+        blob.extend_from_slice(&tag);
+
+        Ok(format!(
+            "{}{}:{}",
+            CIPHERTEXT_PREFIX,
+            self.latest_version,
+            STANDARD.encode(blob)
+        ))
+    }
+
+    /// Decrypt a ciphertext produced by `encrypt`, using the key version embedded in it.
+    pub fn decrypt(&self, ciphertext: &str) -> Result<Vec<u8>, RvError> {
+        let Some((version, data)) = ciphertext
+            .strip_prefix(CIPHERTEXT_PREFIX)
+            .and_then(|rest| rest.split_once(':'))
+        else {
+            return Err(rv_error_response!("invalid ciphertext: no prefix"));
+        };
+
+        let version: u32 = version.parse().map_err(|_| {
+            rv_error_response!("invalid ciphertext: version number could not be decoded")
+        })?;
+        let Some(key_version) = self.versions.get(&version) else {
+            return Err(rv_error_response!("invalid key version"));
+        };
+
+        let blob = STANDARD
+            .decode(data)
+            .map_err(|_| rv_error_response!("invalid ciphertext: could not decode base64"))?;
+        if blob.len() < NONCE_SIZE + TAG_SIZE {
+            return Err(rv_error_response!("invalid ciphertext: too short"));
+        }
+
+        let (nonce, rest) = blob.split_at(NONCE_SIZE);
+        let (ciphertext, tag) = rest.split_at(rest.len() - TAG_SIZE);
+
+        let mut aes_decrypter = AES::new(
+            false,
+            Some(AESKeySize::AES256),
+            Some(CipherMode::GCM),
+            Some(key_version.key.clone()),
+            Some(nonce.to_vec()),
+        )?;
+        aes_decrypter.set_aad(Vec::new())?;
+        aes_decrypter.set_tag(tag.to_vec())?;
+
+        aes_decrypter
+            .decrypt(&ciphertext.to_vec())
+            .map_err(|_| rv_error_response!("cipher: message authentication failed"))
+    }
+}
@@ -0,0 +1,108 @@
+//! The `transit` secrets engine provides encryption as a service. Data sent to it is encrypted
+//! or decrypted with named keys that never leave RustyVault.
+//!
+//! Every named key keeps all of its versions. `keys/<name>/rotate` adds a new version, which is
+//! then used for all subsequent encryptions, while older versions remain available to decrypt
+//! data encrypted with them. Ciphertexts carry the version of the key that produced them, in the
+//! form `vault:v<version>:<base64 data>`.
+
+use std::{any::Any, sync::Arc};
+
+use async_trait::async_trait;
+use derive_more::Deref;
+
+use crate::{
+    core::Core,
+    errors::RvError,
+    logical::{Backend, LogicalBackend},
+    modules::Module,
+    utils::locks::Locks,
+};
+
+pub mod key;
+pub mod path_encrypt;
+pub mod path_keys;
+
+pub use key::TransitKey;
+
+static TRANSIT_BACKEND_HELP: &str = r#"
+The transit backend is used to encrypt and decrypt data in-transit. It
+manages named encryption keys that never leave RustyVault, so clients can
+have data encrypted without handling any key material themselves.
+
+Keys can be rotated. Data encrypted with an old key version can still be
+decrypted, while new data is always encrypted with the latest version.
+"#;
+
+pub struct TransitModule {
+    pub name: String,
+    pub backend: Arc<TransitBackend>,
+}
+
+pub struct TransitBackendInner {
+    pub core: Arc<Core>,
+    // Serializes creation and rotation of a named key.
+    pub key_locks: Locks,
+}
+
+#[derive(Deref)]
+pub struct TransitBackend {
+    #[deref]
+    pub inner: Arc<TransitBackendInner>,
+}
+
+impl TransitBackend {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            inner: Arc::new(TransitBackendInner {
+                core,
+                key_locks: Locks::new(),
+            }),
+        }
+    }
+
+    pub fn new_backend(&self) -> LogicalBackend {
+        LogicalBackend::builder()
+            .help(TRANSIT_BACKEND_HELP)
+            .path(self.keys_path())
+            .path(self.keys_list_path())
+            .path(self.keys_rotate_path())
+            .path(self.encrypt_path())
+            .path(self.decrypt_path())
+            .build()
+    }
+}
+
+impl TransitModule {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            name: "transit".to_string(),
+            backend: Arc::new(TransitBackend::new(core)),
+        }
+    }
+}
+
+#[async_trait]
+impl Module for TransitModule {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn as_any_arc(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn setup(&self, core: &Core) -> Result<(), RvError> {
+        let transit = self.backend.clone();
+        let transit_backend_new_func = move |_c: Arc<Core>| -> Result<Arc<dyn Backend>, RvError> {
+            let mut transit_backend = transit.new_backend();
+            transit_backend.init()?;
+            Ok(Arc::new(transit_backend))
+        };
+        core.add_logical_backend("transit", Arc::new(transit_backend_new_func))
+    }
+
+    fn cleanup(&self, core: &Core) -> Result<(), RvError> {
+        core.delete_logical_backend("transit")
+    }
+}
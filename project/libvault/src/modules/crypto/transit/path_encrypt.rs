@@ -0,0 +1,126 @@
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde_json::json;
+
+use super::{TransitBackend, TransitBackendInner};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response},
+    rv_error_response,
+};
+
+impl TransitBackend {
+    pub fn encrypt_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"encrypt/(?P<name>\w(([\w.-]+)?\w)?)$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the key"),
+            )
+            .field(
+                "plaintext",
+                Field::builder()
+                    .field_type(FieldType::SecretStr)
+                    .required(true)
+                    .description("Base64 encoded plaintext value to be encrypted"),
+            )
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.encrypt(backend, req).await })
+                }
+            })
+            .help("Encrypt a plaintext value using a named key. The latest version of the key is always used.")
+            .build()
+    }
+
+    pub fn decrypt_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"decrypt/(?P<name>\w(([\w.-]+)?\w)?)$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the key"),
+            )
+            .field(
+                "ciphertext",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Ciphertext value to decrypt"),
+            )
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.decrypt(backend, req).await })
+                }
+            })
+            .help("Decrypt a ciphertext value using a named key. The key version embedded in the ciphertext is used.")
+            .build()
+    }
+}
+
+impl TransitBackendInner {
+    pub async fn encrypt(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        let plaintext = req.get_data_as_str("plaintext")?;
+        let plaintext = STANDARD
+            .decode(plaintext)
+            .map_err(|_| rv_error_response!("failed to base64-decode plaintext"))?;
+
+        let lock_entry = self.key_locks.get_lock(&name);
+        let _locked = lock_entry.lock.read().await;
+
+        let Some(key) = self.get_key(req, &name).await? else {
+            return Err(rv_error_response!("encryption key not found"));
+        };
+
+        let ciphertext = key.encrypt(&plaintext)?;
+
+        let data = json!({
+            "ciphertext": ciphertext,
+            "key_version": key.latest_version,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn decrypt(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        let ciphertext = req.get_data_as_str("ciphertext")?;
+
+        let Some(key) = self.get_key(req, &name).await? else {
+            return Err(rv_error_response!("encryption key not found"));
+        };
+
+        let plaintext = key.decrypt(&ciphertext)?;
+
+        let data = json!({
+            "plaintext": STANDARD.encode(plaintext),
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+}
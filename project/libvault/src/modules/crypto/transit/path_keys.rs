@@ -0,0 +1,191 @@
+use humantime::format_rfc3339;
+use serde_json::{Map, Value, json};
+
+use super::{TransitBackend, TransitBackendInner, key::TransitKey};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response},
+    rv_error_response,
+    storage::StorageEntry,
+};
+
+impl TransitBackend {
+    pub fn keys_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"keys/(?P<name>\w(([\w.-]+)?\w)?)$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the key"),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_key(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.create_key(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+This path is used to manage the named keys that are available. Doing a
+write with no value against a new named key will create it using a
+randomly generated key.
+                "#,
+            )
+            .build()
+    }
+
+    pub fn keys_list_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"keys/?$")
+            .operation(Operation::List, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.list_keys(backend, req).await })
+                }
+            })
+            .help("List the named keys available.")
+            .build()
+    }
+
+    pub fn keys_rotate_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"keys/(?P<name>\w(([\w.-]+)?\w)?)/rotate$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the key"),
+            )
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.rotate_key(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+This path is used to rotate the named key. After rotation, new encryption
+requests using this name will use the new key, but decryption will still
+be supported for older versions.
+                "#,
+            )
+            .build()
+    }
+}
+
+impl TransitBackendInner {
+    pub async fn get_key(&self, req: &Request, name: &str) -> Result<Option<TransitKey>, RvError> {
+        let storage_entry = req.storage_get(&format!("policy/{name}")).await?;
+        if storage_entry.is_none() {
+            return Ok(None);
+        }
+
+        let entry = storage_entry.unwrap();
+        let key: TransitKey = serde_json::from_slice(entry.value.as_slice())?;
+        Ok(Some(key))
+    }
+
+    pub async fn put_key(&self, req: &Request, key: &TransitKey) -> Result<(), RvError> {
+        let entry = StorageEntry::new(format!("policy/{}", key.name).as_str(), key)?;
+        req.storage_put(&entry).await
+    }
+
+    pub async fn read_key(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+
+        let Some(key) = self.get_key(req, &name).await? else {
+            return Ok(None);
+        };
+
+        let mut versions = Map::new();
+        for (version, key_version) in key.versions.iter() {
+            let creation_time = format_rfc3339(key_version.creation_time).to_string();
+            versions.insert(version.to_string(), Value::String(creation_time));
+        }
+
+        let data = json!({
+            "name": key.name,
+            "type": key.key_type,
+            "latest_version": key.latest_version,
+            "keys": versions,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn create_key(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+
+        let lock_entry = self.key_locks.get_lock(&name);
+        let _locked = lock_entry.lock.write().await;
+
+        if self.get_key(req, &name).await?.is_some() {
+            return Ok(None);
+        }
+
+        let key = TransitKey::new(&name)?;
+        self.put_key(req, &key).await?;
+
+        Ok(None)
+    }
+
+    pub async fn list_keys(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let keys = req.storage_list("policy/").await?;
+        let resp = Response::list_response(&keys);
+        Ok(Some(resp))
+    }
+
+    pub async fn rotate_key(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+
+        let lock_entry = self.key_locks.get_lock(&name);
+        let _locked = lock_entry.lock.write().await;
+
+        let Some(mut key) = self.get_key(req, &name).await? else {
+            return Err(rv_error_response!("key not found"));
+        };
+
+        key.rotate()?;
+        self.put_key(req, &key).await?;
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,122 @@
+//! The `database` secrets engine creates short-lived PostgreSQL users on demand.
+//!
+//! `config/<name>` holds how to reach a database and which roles may use it. A role at
+//! `roles/<name>` holds the SQL that creates a user, with `{{name}}`, `{{password}}` and
+//! `{{expiration}}` substituted, and the SQL that drops it again. Reading `creds/<role>` creates a
+//! fresh user and returns its credentials under a lease; when the lease is revoked, by hand or
+//! because it expired, the revocation SQL is run.
+
+use std::{any::Any, sync::Arc};
+
+use async_trait::async_trait;
+use derive_more::Deref;
+
+use crate::{
+    core::Core,
+    errors::RvError,
+    logical::{Backend, LogicalBackend, SecretBuilder},
+    modules::Module,
+};
+
+pub mod path_config;
+pub mod path_creds;
+pub mod path_roles;
+
+pub use path_config::DatabaseConfig;
+pub use path_roles::DatabaseRole;
+
+pub const DATABASE_CREDS_SECRET_TYPE: &str = "creds";
+
+static DATABASE_BACKEND_HELP: &str = r#"
+The database backend generates PostgreSQL credentials dynamically, based
+on configured roles. The credentials are bound to a lease and the user is
+dropped when the lease is revoked.
+"#;
+
+pub struct DatabaseModule {
+    pub name: String,
+    pub backend: Arc<DatabaseBackend>,
+}
+
+pub struct DatabaseBackendInner {
+    pub core: Arc<Core>,
+}
+
+#[derive(Deref)]
+pub struct DatabaseBackend {
+    #[deref]
+    pub inner: Arc<DatabaseBackendInner>,
+}
+
+impl DatabaseBackend {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            inner: Arc::new(DatabaseBackendInner { core }),
+        }
+    }
+
+    pub fn new_backend(&self) -> LogicalBackend {
+        let secret = SecretBuilder::new()
+            .secret_type(DATABASE_CREDS_SECRET_TYPE)
+            .renew_handler({
+                let handler = self.inner.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.renew_creds(backend, req).await })
+                }
+            })
+            .revoke_handler({
+                let handler = self.inner.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.revoke_creds(backend, req).await })
+                }
+            })
+            .build();
+
+        LogicalBackend::builder()
+            .help(DATABASE_BACKEND_HELP)
+            .root_paths(["config/*"])
+            .path(self.config_path())
+            .path(self.config_list_path())
+            .path(self.roles_path())
+            .path(self.roles_list_path())
+            .path(self.creds_path())
+            .secret(secret)
+            .build()
+    }
+}
+
+impl DatabaseModule {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            name: "database".to_string(),
+            backend: Arc::new(DatabaseBackend::new(core)),
+        }
+    }
+}
+
+#[async_trait]
+impl Module for DatabaseModule {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn as_any_arc(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn setup(&self, core: &Core) -> Result<(), RvError> {
+        let database = self.backend.clone();
+        let database_backend_new_func = move |_c: Arc<Core>| -> Result<Arc<dyn Backend>, RvError> {
+            let mut database_backend = database.new_backend();
+            database_backend.init()?;
+            Ok(Arc::new(database_backend))
+        };
+        core.add_logical_backend("database", Arc::new(database_backend_new_func))
+    }
+
+    fn cleanup(&self, core: &Core) -> Result<(), RvError> {
+        core.delete_logical_backend("database")
+    }
+}
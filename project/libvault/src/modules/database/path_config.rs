@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{PgPool, postgres::PgPoolOptions};
+
+use super::{DatabaseBackend, DatabaseBackendInner};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response, field::FieldTrait},
+    rv_error_response,
+    storage::StorageEntry,
+};
+
+/// How to reach a database, and which roles may create users in it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseConfig {
+    pub connection_url: String,
+    /// Roles allowed to use this database. `*` allows any.
+    #[serde(default)]
+    pub allowed_roles: Vec<String>,
+}
+
+impl DatabaseConfig {
+    pub fn is_role_allowed(&self, role: &str) -> bool {
+        self.allowed_roles.iter().any(|r| r == "*" || r == role)
+    }
+
+    /// The connection URL with its password masked, safe to return on read.
+    pub fn redacted_url(&self) -> String {
+        match url::Url::parse(&self.connection_url) {
+            Ok(mut url) if url.password().is_some() => {
+                let _ = url.set_password(Some("*****"));
+                url.to_string()
+            }
+            Ok(url) => url.to_string(),
+            Err(_) => String::new(),
+        }
+    }
+
+    /// A single-connection pool to the database. Statements run on the pool rather than on a
+    /// borrowed `&mut PgConnection`, whose executor futures aren't `Send` for every lifetime, as
+    /// the logical backend handlers require.
+    pub async fn connect(&self) -> Result<PgPool, RvError> {
+        Ok(PgPoolOptions::new()
+            .max_connections(1)
+            .connect(&self.connection_url)
+            .await?)
+    }
+}
+
+impl DatabaseBackend {
+    pub fn config_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+        let backend_delete = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"config/(?P<name>\w(([\w.-]+)?\w)?)$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of this database connection."),
+            )
+            .field(
+                "connection_url",
+                Field::builder()
+                    .field_type(FieldType::SecretStr)
+                    .required(true)
+                    .description(
+                        "PostgreSQL connection URL, e.g. postgres://vault:secret@db:5432/app.",
+                    ),
+            )
+            .field(
+                "allowed_roles",
+                Field::builder()
+                    .field_type(FieldType::CommaStringSlice)
+                    .description("Roles allowed to use this connection. '*' allows any."),
+            )
+            .field(
+                "verify_connection",
+                Field::builder()
+                    .field_type(FieldType::Bool)
+                    .default_value(true)
+                    .description("Connect to the database before saving the configuration."),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_config(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.write_config(backend, req).await })
+                }
+            })
+            .operation(Operation::Delete, {
+                let handler = backend_delete.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.delete_config(backend, req).await })
+                }
+            })
+            .help("This path configures the connection to a database.")
+            .build()
+    }
+
+    pub fn config_list_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"config/?$")
+            .operation(Operation::List, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.list_config(backend, req).await })
+                }
+            })
+            .help("Lists the configured database connections.")
+            .build()
+    }
+}
+
+impl DatabaseBackendInner {
+    pub async fn get_config(
+        &self,
+        req: &Request,
+        name: &str,
+    ) -> Result<Option<DatabaseConfig>, RvError> {
+        match req.storage_get(&format!("config/{name}")).await? {
+            Some(entry) => Ok(Some(serde_json::from_slice(&entry.value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn read_config(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        let Some(config) = self.get_config(req, &name).await? else {
+            return Ok(None);
+        };
+
+        let data = json!({
+            "connection_url": config.redacted_url(),
+            "allowed_roles": config.allowed_roles,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn write_config(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        let connection_url = req.get_data_as_str("connection_url")?;
+        let allowed_roles = match req.get_data("allowed_roles") {
+            Ok(value) => value
+                .as_comma_string_slice()
+                .ok_or(RvError::ErrRequestFieldInvalid)?,
+            Err(_) => Vec::new(),
+        };
+
+        let config = DatabaseConfig {
+            connection_url,
+            allowed_roles,
+        };
+
+        let verify = req
+            .get_data_or_default("verify_connection")?
+            .as_bool()
+            .unwrap_or(true);
+        if verify {
+            let pool = config
+                .connect()
+                .await
+                .map_err(|err| rv_error_response!(&format!("error verifying connection: {err}")))?;
+            pool.close().await;
+        }
+
+        let entry = StorageEntry::new(&format!("config/{name}"), &config)?;
+        req.storage_put(&entry).await?;
+
+        Ok(None)
+    }
+
+    pub async fn delete_config(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        req.storage_delete(&format!("config/{name}")).await?;
+        Ok(None)
+    }
+
+    pub async fn list_config(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let names = req.storage_list("config/").await?;
+        Ok(Some(Response::list_response(&names)))
+    }
+}
@@ -0,0 +1,244 @@
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Utc};
+use rand::{Rng, distr::Alphanumeric};
+use serde_json::{Map, Value, json};
+use sqlx::PgPool;
+
+use super::{
+    DATABASE_CREDS_SECRET_TYPE, DatabaseBackend, DatabaseBackendInner, DatabaseRole,
+    path_roles::render_statements,
+};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response},
+    rv_error_response,
+};
+
+// PostgreSQL truncates identifiers longer than this.
+const MAX_USERNAME_LEN: usize = 63;
+const PASSWORD_LEN: usize = 32;
+
+impl DatabaseBackend {
+    pub fn creds_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"creds/(?P<name>\w(([\w.-]+)?\w)?)$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the role."),
+            )
+            .operation(Operation::Read, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_creds(backend, req).await })
+                }
+            })
+            .help("This path creates a database user for a role and returns its credentials.")
+            .build()
+    }
+}
+
+fn random_string(len: usize) -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+fn generate_username(display_name: &str, role: &str) -> String {
+    let sanitize = |s: &str| -> String {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect()
+    };
+
+    let suffix = random_string(8).to_ascii_lowercase();
+    let prefix = format!("v-{}-{}", sanitize(display_name), sanitize(role));
+    let keep = MAX_USERNAME_LEN - suffix.len() - 1;
+    let prefix: String = prefix.chars().take(keep).collect();
+    format!("{prefix}-{suffix}")
+}
+
+fn format_expiration(expiration: SystemTime) -> String {
+    DateTime::<Utc>::from(expiration)
+        .format("%Y-%m-%d %H:%M:%S%z")
+        .to_string()
+}
+
+/// Run `statements` as one transaction, so a failed statement leaves nothing half done. They are
+/// sent as a single simple query, which PostgreSQL runs in one implicit transaction.
+async fn execute_statements(pool: PgPool, statements: String) -> Result<(), RvError> {
+    sqlx::raw_sql(&statements).execute(&pool).await?;
+    pool.close().await;
+    Ok(())
+}
+
+impl DatabaseBackendInner {
+    /// The role and connection behind a credentials lease, from the lease's internal data.
+    async fn lease_role(&self, req: &Request) -> Result<(String, DatabaseRole), RvError> {
+        let Some(secret) = req.secret.as_ref() else {
+            return Err(RvError::ErrRequestInvalid);
+        };
+        let internal = |key: &str| {
+            secret
+                .internal_data
+                .get(key)
+                .and_then(|value| value.as_str())
+                .map(|value| value.to_string())
+                .ok_or(RvError::ErrRequestInvalid)
+        };
+
+        let username = internal("username")?;
+        let role_name = internal("role")?;
+        let db_name = internal("db_name")?;
+
+        // The role may have been deleted since; its user still has to be dropped.
+        let role = match self.get_role(req, &role_name).await? {
+            Some(role) => role,
+            None => DatabaseRole {
+                db_name,
+                creation_statements: String::new(),
+                revocation_statements: String::new(),
+                renew_statements: String::new(),
+                default_ttl: Duration::ZERO,
+                max_ttl: Duration::ZERO,
+            },
+        };
+
+        Ok((username, role))
+    }
+
+    pub async fn read_creds(
+        &self,
+        backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let role_name = req.get_data_as_str("name")?;
+        let Some(role) = self.get_role(req, &role_name).await? else {
+            return Err(rv_error_response!(&format!("unknown role: {role_name}")));
+        };
+        let Some(config) = self.get_config(req, &role.db_name).await? else {
+            return Err(rv_error_response!(&format!(
+                "unknown database: {}",
+                role.db_name
+            )));
+        };
+        if !config.is_role_allowed(&role_name) {
+            return Err(rv_error_response!(&format!(
+                "role {role_name} is not allowed by database {}",
+                role.db_name
+            )));
+        }
+
+        let display_name = req
+            .auth
+            .as_ref()
+            .map(|auth| auth.display_name.clone())
+            .unwrap_or_default();
+        let username = generate_username(&display_name, &role_name);
+        let password = random_string(PASSWORD_LEN);
+        let expiration = format_expiration(SystemTime::now() + role.default_ttl);
+
+        let statements =
+            render_statements(&role.creation_statements, &username, &password, &expiration);
+        execute_statements(config.connect().await?, statements).await?;
+
+        let mut data = Map::new();
+        data.insert("username".into(), Value::String(username.clone()));
+        data.insert("password".into(), Value::String(password));
+
+        let internal = json!({
+            "username": username,
+            "role": role_name,
+            "db_name": role.db_name,
+        })
+        .as_object()
+        .cloned();
+
+        let Some(secret) = backend.secret(DATABASE_CREDS_SECRET_TYPE) else {
+            return Err(RvError::ErrLogicalOperationUnsupported);
+        };
+        let mut resp = secret.response(Some(data), internal);
+        if let Some(secret) = resp.secret.as_mut() {
+            secret.lease.ttl = role.default_ttl;
+            secret.lease.max_ttl = role.max_ttl;
+        }
+
+        Ok(Some(resp))
+    }
+
+    pub async fn renew_creds(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let (username, role) = self.lease_role(req).await?;
+        let Some(config) = self.get_config(req, &role.db_name).await? else {
+            return Err(rv_error_response!(&format!(
+                "unknown database: {}",
+                role.db_name
+            )));
+        };
+
+        let Some(mut secret) = req.secret.clone() else {
+            return Err(RvError::ErrRequestInvalid);
+        };
+
+        let mut ttl = if secret.lease.increment.is_zero() {
+            role.default_ttl
+        } else {
+            secret.lease.increment
+        };
+        // Renewals never carry the user past the role's max TTL, counted from issue.
+        if !role.max_ttl.is_zero()
+            && let Some(issue_time) = secret.lease.issue_time
+        {
+            let elapsed = SystemTime::now()
+                .duration_since(issue_time)
+                .unwrap_or_default();
+            ttl = ttl.min(role.max_ttl.saturating_sub(elapsed));
+        }
+
+        let expiration = format_expiration(SystemTime::now() + ttl);
+        let statements = render_statements(role.renew_statements(), &username, "", &expiration);
+        execute_statements(config.connect().await?, statements).await?;
+
+        secret.lease.ttl = ttl;
+        Ok(Some(Response {
+            secret: Some(secret),
+            ..Response::default()
+        }))
+    }
+
+    pub async fn revoke_creds(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let (username, role) = self.lease_role(req).await?;
+        let Some(config) = self.get_config(req, &role.db_name).await? else {
+            return Err(rv_error_response!(&format!(
+                "unknown database: {}",
+                role.db_name
+            )));
+        };
+
+        let statements = render_statements(role.revocation_statements(), &username, "", "");
+        execute_statements(config.connect().await?, statements).await?;
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,255 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{DatabaseBackend, DatabaseBackendInner};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response, field::FieldTrait},
+    rv_error_response,
+    storage::StorageEntry,
+    utils::{deserialize_duration, serialize_duration},
+};
+
+const DEFAULT_REVOCATION_STATEMENTS: &str = r#"DROP ROLE IF EXISTS "{{name}}";"#;
+const DEFAULT_RENEW_STATEMENTS: &str = r#"ALTER ROLE "{{name}}" VALID UNTIL '{{expiration}}';"#;
+const DEFAULT_TTL: Duration = Duration::from_secs(3600);
+
+/// How users are created in, and dropped from, a database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseRole {
+    pub db_name: String,
+    pub creation_statements: String,
+    #[serde(default)]
+    pub revocation_statements: String,
+    #[serde(default)]
+    pub renew_statements: String,
+    #[serde(
+        default,
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub default_ttl: Duration,
+    #[serde(
+        default,
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub max_ttl: Duration,
+}
+
+impl DatabaseRole {
+    pub fn revocation_statements(&self) -> &str {
+        if self.revocation_statements.is_empty() {
+            DEFAULT_REVOCATION_STATEMENTS
+        } else {
+            &self.revocation_statements
+        }
+    }
+
+    pub fn renew_statements(&self) -> &str {
+        if self.renew_statements.is_empty() {
+            DEFAULT_RENEW_STATEMENTS
+        } else {
+            &self.renew_statements
+        }
+    }
+}
+
+/// Substitute `{{name}}`, `{{password}}` and `{{expiration}}` in `statements`.
+pub fn render_statements(statements: &str, name: &str, password: &str, expiration: &str) -> String {
+    statements
+        .replace("{{name}}", name)
+        .replace("{{password}}", password)
+        .replace("{{expiration}}", expiration)
+}
+
+impl DatabaseBackend {
+    pub fn roles_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+        let backend_delete = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"roles/(?P<name>\w(([\w.-]+)?\w)?)$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the role."),
+            )
+            .field(
+                "db_name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the database connection the role creates users in."),
+            )
+            .field(
+                "creation_statements",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("SQL creating the user, with {{name}}, {{password}} and {{expiration}} substituted."),
+            )
+            .field(
+                "revocation_statements",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .description("SQL dropping the user. Defaults to DROP ROLE IF EXISTS."),
+            )
+            .field(
+                "renew_statements",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .description("SQL run on lease renewal. Defaults to ALTER ROLE ... VALID UNTIL."),
+            )
+            .field(
+                "default_ttl",
+                Field::builder()
+                    .field_type(FieldType::DurationSecond)
+                    .description("Lease duration of the generated credentials."),
+            )
+            .field(
+                "max_ttl",
+                Field::builder()
+                    .field_type(FieldType::DurationSecond)
+                    .description("Maximum lifetime of the credentials, renewals included."),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_role(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.write_role(backend, req).await })
+                }
+            })
+            .operation(Operation::Delete, {
+                let handler = backend_delete.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.delete_role(backend, req).await })
+                }
+            })
+            .help("This path manages the roles credentials are generated from.")
+            .build()
+    }
+
+    pub fn roles_list_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"roles/?$")
+            .operation(Operation::List, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.list_roles(backend, req).await })
+                }
+            })
+            .help("Lists all the roles registered with the backend.")
+            .build()
+    }
+}
+
+impl DatabaseBackendInner {
+    pub async fn get_role(
+        &self,
+        req: &Request,
+        name: &str,
+    ) -> Result<Option<DatabaseRole>, RvError> {
+        match req.storage_get(&format!("role/{name}")).await? {
+            Some(entry) => Ok(Some(serde_json::from_slice(&entry.value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn read_role(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        let Some(role) = self.get_role(req, &name).await? else {
+            return Ok(None);
+        };
+
+        let data = json!({
+            "db_name": role.db_name,
+            "creation_statements": role.creation_statements,
+            "revocation_statements": role.revocation_statements(),
+            "renew_statements": role.renew_statements(),
+            "default_ttl": role.default_ttl.as_secs(),
+            "max_ttl": role.max_ttl.as_secs(),
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn write_role(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        let optional_str = |key: &str| {
+            req.get_data(key)
+                .ok()
+                .and_then(|value| value.as_str().map(|s| s.trim().to_string()))
+                .unwrap_or_default()
+        };
+        let optional_duration = |key: &str, default: Duration| match req.get_data(key) {
+            Ok(value) => value.as_duration().ok_or(RvError::ErrRequestFieldInvalid),
+            Err(_) => Ok(default),
+        };
+
+        let role = DatabaseRole {
+            db_name: req.get_data_as_str("db_name")?,
+            creation_statements: req.get_data_as_str("creation_statements")?,
+            revocation_statements: optional_str("revocation_statements"),
+            renew_statements: optional_str("renew_statements"),
+            default_ttl: optional_duration("default_ttl", DEFAULT_TTL)?,
+            max_ttl: optional_duration("max_ttl", Duration::ZERO)?,
+        };
+
+        if !role.max_ttl.is_zero() && role.default_ttl > role.max_ttl {
+            return Err(rv_error_response!(
+                "default_ttl cannot be greater than max_ttl"
+            ));
+        }
+
+        let entry = StorageEntry::new(&format!("role/{name}"), &role)?;
+        req.storage_put(&entry).await?;
+
+        Ok(None)
+    }
+
+    pub async fn delete_role(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        req.storage_delete(&format!("role/{name}")).await?;
+        Ok(None)
+    }
+
+    pub async fn list_roles(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let roles = req.storage_list("role/").await?;
+        Ok(Some(Response::list_response(&roles)))
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::IdentityStore;
+use crate::{
+    errors::RvError,
+    modules::auth::AuthModule,
+    storage::{Storage, StorageEntry},
+    utils::generate_uuid,
+};
+
+const ENTITY_PREFIX: &str = "entity/id/";
+const ENTITY_NAME_PREFIX: &str = "entity/name/";
+const ALIAS_PREFIX: &str = "alias/id/";
+const ALIAS_INDEX_PREFIX: &str = "alias/index/";
+
+/// A single person or service, however many auth methods it logs in through.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Entity {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    #[serde(default)]
+    pub alias_ids: Vec<String>,
+    /// Entities that were merged into this one. Tokens issued to them still carry their old id.
+    #[serde(default)]
+    pub merged_entity_ids: Vec<String>,
+}
+
+/// Links the user an auth mount knows as `name` to an entity.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntityAlias {
+    pub id: String,
+    pub canonical_id: String,
+    pub mount_accessor: String,
+    pub name: String,
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+}
+
+impl Entity {
+    pub fn new(name: &str) -> Self {
+        let id = generate_uuid();
+        let name = if name.is_empty() {
+            format!("entity_{}", id.split('-').next().unwrap_or_default())
+        } else {
+            name.to_string()
+        };
+
+        Self {
+            id,
+            name,
+            ..Default::default()
+        }
+    }
+}
+
+// Names end up in storage keys, so the indexes key them by their hex encoding.
+fn entity_name_key(name: &str) -> String {
+    format!("{ENTITY_NAME_PREFIX}{}", hex::encode(name))
+}
+
+fn alias_index_key(mount_accessor: &str, name: &str) -> String {
+    format!("{ALIAS_INDEX_PREFIX}{mount_accessor}/{}", hex::encode(name))
+}
+
+impl IdentityStore {
+    async fn get_json<T: serde::de::DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<Option<T>, RvError> {
+        match self.storage()?.get(key).await? {
+            Some(entry) => Ok(Some(serde_json::from_slice(&entry.value)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn put_json<T: Serialize>(&self, key: &str, value: &T) -> Result<(), RvError> {
+        self.storage()?.put(&StorageEntry::new(key, value)?).await
+    }
+
+    pub async fn get_entity(&self, id: &str) -> Result<Option<Entity>, RvError> {
+        self.get_json(&format!("{ENTITY_PREFIX}{id}")).await
+    }
+
+    pub async fn get_entity_by_name(&self, name: &str) -> Result<Option<Entity>, RvError> {
+        let Some(id) = self.get_json::<String>(&entity_name_key(name)).await? else {
+            return Ok(None);
+        };
+        self.get_entity(&id).await
+    }
+
+    pub async fn list_entity_ids(&self) -> Result<Vec<String>, RvError> {
+        self.storage()?.list(ENTITY_PREFIX).await
+    }
+
+    pub async fn list_entity_names(&self) -> Result<Vec<String>, RvError> {
+        let keys = self.storage()?.list(ENTITY_NAME_PREFIX).await?;
+        Ok(keys
+            .iter()
+            .filter_map(|key| hex::decode(key).ok())
+            .filter_map(|name| String::from_utf8(name).ok())
+            .collect())
+    }
+
+    /// Store `entity`, keeping the name index in step. `old_name` is the name it was stored under
+    /// before, if it's being renamed.
+    pub async fn put_entity(&self, entity: &Entity, old_name: Option<&str>) -> Result<(), RvError> {
+        if let Some(existing) = self.get_entity_by_name(&entity.name).await?
+            && existing.id != entity.id
+        {
+            return Err(RvError::ErrIdentityEntityNameExist);
+        }
+
+        self.put_json(&format!("{ENTITY_PREFIX}{}", entity.id), entity)
+            .await?;
+        self.put_json(&entity_name_key(&entity.name), &entity.id)
+            .await?;
+
+        if let Some(old_name) = old_name
+            && old_name != entity.name
+        {
+            self.storage()?.delete(&entity_name_key(old_name)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Delete an entity together with its aliases.
+    pub async fn delete_entity(&self, id: &str) -> Result<(), RvError> {
+        let Some(entity) = self.get_entity(id).await? else {
+            return Ok(());
+        };
+
+        for alias_id in entity.alias_ids.iter() {
+            if let Some(alias) = self.get_alias(alias_id).await? {
+                self.remove_alias(&alias).await?;
+            }
+        }
+
+        let storage = self.storage()?;
+        storage.delete(&entity_name_key(&entity.name)).await?;
+        storage.delete(&format!("{ENTITY_PREFIX}{id}")).await
+    }
+
+    pub async fn get_alias(&self, id: &str) -> Result<Option<EntityAlias>, RvError> {
+        self.get_json(&format!("{ALIAS_PREFIX}{id}")).await
+    }
+
+    pub async fn get_alias_by_name(
+        &self,
+        mount_accessor: &str,
+        name: &str,
+    ) -> Result<Option<EntityAlias>, RvError> {
+        let key = alias_index_key(mount_accessor, name);
+        let Some(id) = self.get_json::<String>(&key).await? else {
+            return Ok(None);
+        };
+        self.get_alias(&id).await
+    }
+
+    pub async fn put_alias(&self, alias: &EntityAlias) -> Result<(), RvError> {
+        self.put_json(&format!("{ALIAS_PREFIX}{}", alias.id), alias)
+            .await
+    }
+
+    pub async fn list_alias_ids(&self) -> Result<Vec<String>, RvError> {
+        self.storage()?.list(ALIAS_PREFIX).await
+    }
+
+    /// Link `(mount_accessor, name)` to the entity `canonical_id`.
+    pub async fn create_alias(
+        &self,
+        canonical_id: &str,
+        mount_accessor: &str,
+        name: &str,
+        metadata: HashMap<String, String>,
+    ) -> Result<EntityAlias, RvError> {
+        if !self.mount_accessor_exists(mount_accessor)? {
+            return Err(RvError::ErrIdentityMountAccessorInvalid);
+        }
+
+        if self
+            .get_alias_by_name(mount_accessor, name)
+            .await?
+            .is_some()
+        {
+            return Err(RvError::ErrIdentityAliasExist);
+        }
+
+        let mut entity = self
+            .get_entity(canonical_id)
+            .await?
+            .ok_or(RvError::ErrIdentityEntityNotFound)?;
+
+        let alias = EntityAlias {
+            id: generate_uuid(),
+            canonical_id: entity.id.clone(),
+            mount_accessor: mount_accessor.to_string(),
+            name: name.to_string(),
+            metadata,
+        };
+
+        self.put_alias(&alias).await?;
+        self.put_json(&alias_index_key(mount_accessor, name), &alias.id)
+            .await?;
+
+        entity.alias_ids.push(alias.id.clone());
+        self.put_entity(&entity, None).await?;
+
+        Ok(alias)
+    }
+
+    /// Move an alias to another entity.
+    pub async fn move_alias(
+        &self,
+        alias: &mut EntityAlias,
+        canonical_id: &str,
+    ) -> Result<(), RvError> {
+        if alias.canonical_id == canonical_id {
+            return Ok(());
+        }
+
+        let mut target = self
+            .get_entity(canonical_id)
+            .await?
+            .ok_or(RvError::ErrIdentityEntityNotFound)?;
+
+        if let Some(mut source) = self.get_entity(&alias.canonical_id).await? {
+            source.alias_ids.retain(|id| *id != alias.id);
+            self.put_entity(&source, None).await?;
+        }
+
+        alias.canonical_id = target.id.clone();
+        self.put_alias(alias).await?;
+
+        target.alias_ids.push(alias.id.clone());
+        self.put_entity(&target, None).await
+    }
+
+    /// Delete an alias and unlink it from its entity.
+    pub async fn delete_alias(&self, id: &str) -> Result<(), RvError> {
+        let Some(alias) = self.get_alias(id).await? else {
+            return Ok(());
+        };
+
+        if let Some(mut entity) = self.get_entity(&alias.canonical_id).await? {
+            entity.alias_ids.retain(|alias_id| alias_id != id);
+            self.put_entity(&entity, None).await?;
+        }
+
+        self.remove_alias(&alias).await
+    }
+
+    async fn remove_alias(&self, alias: &EntityAlias) -> Result<(), RvError> {
+        let storage = self.storage()?;
+        storage
+            .delete(&alias_index_key(&alias.mount_accessor, &alias.name))
+            .await?;
+        storage.delete(&format!("{ALIAS_PREFIX}{}", alias.id)).await
+    }
+
+    /// Merge the entities `from_ids` into `to_id`: their aliases move over, and their metadata is
+    /// added wherever `to_id` doesn't already have the key. The merged entities are deleted.
+    pub async fn merge_entities(
+        &self,
+        to_id: &str,
+        from_ids: &[String],
+    ) -> Result<Entity, RvError> {
+        let mut target = self
+            .get_entity(to_id)
+            .await?
+            .ok_or(RvError::ErrIdentityEntityNotFound)?;
+
+        for from_id in from_ids.iter().filter(|id| *id != to_id) {
+            let source = self
+                .get_entity(from_id)
+                .await?
+                .ok_or(RvError::ErrIdentityEntityNotFound)?;
+
+            for alias_id in source.alias_ids.iter() {
+                if let Some(mut alias) = self.get_alias(alias_id).await? {
+                    alias.canonical_id = target.id.clone();
+                    self.put_alias(&alias).await?;
+                    target.alias_ids.push(alias.id);
+                }
+            }
+
+            for (key, value) in source.metadata {
+                target.metadata.entry(key).or_insert(value);
+            }
+
+            target.merged_entity_ids.push(source.id.clone());
+            target.merged_entity_ids.extend(source.merged_entity_ids);
+
+            let storage = self.storage()?;
+            storage.delete(&entity_name_key(&source.name)).await?;
+            storage
+                .delete(&format!("{ENTITY_PREFIX}{}", source.id))
+                .await?;
+        }
+
+        self.put_entity(&target, None).await?;
+
+        Ok(target)
+    }
+
+    /// The entity `(mount_accessor, name)` logs in as, created along with its alias on first use.
+    pub async fn resolve_login(
+        &self,
+        mount_accessor: &str,
+        name: &str,
+        metadata: &HashMap<String, String>,
+    ) -> Result<Entity, RvError> {
+        let _guard = self.lock.lock().await;
+
+        if let Some(alias) = self.get_alias_by_name(mount_accessor, name).await?
+            && let Some(entity) = self.get_entity(&alias.canonical_id).await?
+        {
+            return Ok(entity);
+        }
+
+        let entity = Entity::new("");
+        self.put_entity(&entity, None).await?;
+        self.create_alias(&entity.id, mount_accessor, name, metadata.clone())
+            .await?;
+
+        self.get_entity(&entity.id)
+            .await?
+            .ok_or(RvError::ErrIdentityEntityNotFound)
+    }
+
+    fn mount_accessor_exists(&self, mount_accessor: &str) -> Result<bool, RvError> {
+        let Some(auth_module) = self.core.module_manager.get_module::<AuthModule>("auth") else {
+            return Err(RvError::ErrModuleNotFound);
+        };
+
+        let mounts = auth_module.mounts_router.entries.read()?;
+        for mount_entry in mounts.values() {
+            if mount_entry.read()?.accessor() == mount_accessor {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
@@ -0,0 +1,170 @@
+//! The identity store maps the users of different auth methods onto one entity.
+//!
+//! An entity is linked to each auth method it logs in through by an alias, keyed by the mount's
+//! accessor and the name that auth method knows the user as. Logging in through an auth method
+//! that sets `Auth::alias_name` resolves the alias to its entity, creating both on first login, and
+//! attaches the entity id to the issued token, where policies and policy templates can refer to it.
+//!
+//! Entities and aliases are managed under the `identity/` mount, which is set up automatically.
+
+use std::{any::Any, sync::Arc};
+
+use arc_swap::ArcSwapOption;
+use async_trait::async_trait;
+use derive_more::Deref;
+use tokio::sync::Mutex;
+
+use crate::{
+    core::Core,
+    errors::RvError,
+    handler::AuthHandler,
+    logical::{Auth, Backend, LogicalBackend, Request},
+    modules::Module,
+    mount::{MOUNT_TABLE_TYPE, MountEntry},
+    storage::barrier_view::BarrierView,
+};
+
+pub mod entity;
+pub mod path_entity;
+pub mod path_entity_alias;
+
+pub use entity::{Entity, EntityAlias};
+
+pub const IDENTITY_MOUNT_PATH: &str = "identity/";
+
+static IDENTITY_BACKEND_HELP: &str = r#"
+The identity backend manages entities, which tie together the users of
+different auth methods, and the entity aliases that link an auth method's
+user to an entity.
+"#;
+
+pub struct IdentityModule {
+    pub name: String,
+    pub backend: Arc<IdentityBackend>,
+}
+
+pub struct IdentityStore {
+    pub core: Arc<Core>,
+    pub view: ArcSwapOption<BarrierView>,
+    // Serializes the creation of entities on login, so that concurrent first logins of a user
+    // don't end up with two entities.
+    pub lock: Mutex<()>,
+}
+
+#[derive(Deref)]
+pub struct IdentityBackend {
+    #[deref]
+    pub inner: Arc<IdentityStore>,
+}
+
+impl IdentityStore {
+    fn storage(&self) -> Result<Arc<BarrierView>, RvError> {
+        self.view.load_full().ok_or(RvError::ErrBarrierSealed)
+    }
+}
+
+#[async_trait]
+impl AuthHandler for IdentityStore {
+    fn name(&self) -> String {
+        "identity_store".to_string()
+    }
+
+    async fn post_login(&self, req: &Request, auth: &mut Auth) -> Result<(), RvError> {
+        if auth.alias_name.is_empty() || !auth.entity_id.is_empty() {
+            return Ok(());
+        }
+
+        let Some(mount_entry) = self.core.router.matching_mount_entry(&req.path)? else {
+            return Ok(());
+        };
+        let mount_accessor = mount_entry.read()?.accessor();
+
+        let entity = self
+            .resolve_login(&mount_accessor, &auth.alias_name, &auth.metadata)
+            .await?;
+        auth.entity_id = entity.id;
+
+        Ok(())
+    }
+}
+
+impl IdentityBackend {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            inner: Arc::new(IdentityStore {
+                core,
+                view: ArcSwapOption::empty(),
+                lock: Mutex::new(()),
+            }),
+        }
+    }
+
+    pub fn new_backend(&self) -> LogicalBackend {
+        LogicalBackend::builder()
+            .help(IDENTITY_BACKEND_HELP)
+            .path(self.entity_path())
+            .path(self.entity_id_list_path())
+            .path(self.entity_id_path())
+            .path(self.entity_name_list_path())
+            .path(self.entity_name_path())
+            .path(self.entity_merge_path())
+            .path(self.entity_alias_path())
+            .path(self.entity_alias_id_list_path())
+            .path(self.entity_alias_id_path())
+            .build()
+    }
+}
+
+impl IdentityModule {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            name: "identity".to_string(),
+            backend: Arc::new(IdentityBackend::new(core)),
+        }
+    }
+}
+
+#[async_trait]
+impl Module for IdentityModule {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn as_any_arc(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn setup(&self, core: &Core) -> Result<(), RvError> {
+        let identity = self.backend.clone();
+        let identity_backend_new_func = move |_c: Arc<Core>| -> Result<Arc<dyn Backend>, RvError> {
+            let mut identity_backend = identity.new_backend();
+            identity_backend.init()?;
+            Ok(Arc::new(identity_backend))
+        };
+        core.add_logical_backend("identity", Arc::new(identity_backend_new_func))
+    }
+
+    async fn init(&self, core: &Core) -> Result<(), RvError> {
+        // Vaults initialized before the identity store existed don't have its mount yet.
+        if core.router.matching_mount(IDENTITY_MOUNT_PATH)?.is_empty() {
+            let me = MountEntry::new(
+                MOUNT_TABLE_TYPE,
+                IDENTITY_MOUNT_PATH,
+                "identity",
+                "identity store",
+            );
+            core.mount_internal(&me, true).await?;
+        }
+
+        let view = core.router.matching_view(IDENTITY_MOUNT_PATH)?;
+        self.backend.view.store(view);
+
+        core.add_auth_handler(self.backend.inner.clone() as Arc<dyn AuthHandler>)
+    }
+
+    fn cleanup(&self, core: &Core) -> Result<(), RvError> {
+        core.delete_auth_handler(self.backend.inner.clone() as Arc<dyn AuthHandler>)?;
+        self.backend.view.store(None);
+        core.delete_logical_backend("identity")
+    }
+}
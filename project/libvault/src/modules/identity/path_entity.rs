@@ -0,0 +1,376 @@
+use serde_json::{Map, Value, json};
+
+use super::{Entity, IdentityBackend, IdentityStore};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response, field::FieldTrait},
+};
+
+impl IdentityBackend {
+    pub fn entity_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"entity$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value("")
+                    .description("Name of the entity. One is generated if not given."),
+            )
+            .field(
+                "metadata",
+                Field::builder()
+                    .field_type(FieldType::Map)
+                    .description("Metadata to be associated with the entity."),
+            )
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.create_entity(backend, req).await })
+                }
+            })
+            .help("Create a new entity.")
+            .build()
+    }
+
+    pub fn entity_id_list_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"entity/id/?$")
+            .operation(Operation::List, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.list_entities_by_id(backend, req).await })
+                }
+            })
+            .help("List all the entity ids.")
+            .build()
+    }
+
+    pub fn entity_id_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+        let backend_delete = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"entity/id/(?P<id>.+)")
+            .field(
+                "id",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("ID of the entity."),
+            )
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .description("Name of the entity."),
+            )
+            .field(
+                "metadata",
+                Field::builder()
+                    .field_type(FieldType::Map)
+                    .description("Metadata to be associated with the entity."),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_entity_by_id(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.update_entity_by_id(backend, req).await })
+                }
+            })
+            .operation(Operation::Delete, {
+                let handler = backend_delete.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.delete_entity_by_id(backend, req).await })
+                }
+            })
+            .help("Read, update or delete an entity by its id, deleting its aliases along with it.")
+            .build()
+    }
+
+    pub fn entity_name_list_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"entity/name/?$")
+            .operation(Operation::List, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.list_entities_by_name(backend, req).await })
+                }
+            })
+            .help("List all the entity names.")
+            .build()
+    }
+
+    pub fn entity_name_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+        let backend_delete = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"entity/name/(?P<name>.+)")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the entity."),
+            )
+            .field(
+                "metadata",
+                Field::builder()
+                    .field_type(FieldType::Map)
+                    .description("Metadata to be associated with the entity."),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_entity_by_name(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.write_entity_by_name(backend, req).await })
+                }
+            })
+            .operation(Operation::Delete, {
+                let handler = backend_delete.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.delete_entity_by_name(backend, req).await })
+                }
+            })
+            .help("Read, create or update, or delete an entity by its name.")
+            .build()
+    }
+
+    pub fn entity_merge_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"entity/merge$")
+            .field(
+                "to_entity_id",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("ID of the entity the others are merged into."),
+            )
+            .field(
+                "from_entity_ids",
+                Field::builder()
+                    .field_type(FieldType::CommaStringSlice)
+                    .required(true)
+                    .description("IDs of the entities to merge. They are deleted once merged."),
+            )
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.merge_entity(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+Merge entities into another one. The aliases of the merged entities are moved
+over, and their metadata is added wherever the target doesn't already have the
+key.
+                "#,
+            )
+            .build()
+    }
+}
+
+fn entity_response(entity: &Entity) -> Response {
+    let data = json!({
+        "id": entity.id.clone(),
+        "name": entity.name.clone(),
+        "metadata": entity.metadata.clone(),
+        "alias_ids": entity.alias_ids.clone(),
+        "merged_entity_ids": entity.merged_entity_ids.clone(),
+    })
+    .as_object()
+    .cloned();
+
+    Response::data_response(data)
+}
+
+fn entity_id_response(entity: &Entity) -> Response {
+    let mut data = Map::new();
+    data.insert("id".into(), Value::String(entity.id.clone()));
+    data.insert("name".into(), Value::String(entity.name.clone()));
+    Response::data_response(Some(data))
+}
+
+impl IdentityStore {
+    pub async fn create_entity(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_or_default("name")?;
+        let mut entity = Entity::new(name.as_str().unwrap_or_default());
+        if let Ok(metadata) = req.get_data("metadata") {
+            entity.metadata = metadata.as_map().ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        let _guard = self.lock.lock().await;
+        self.put_entity(&entity, None).await?;
+
+        Ok(Some(entity_id_response(&entity)))
+    }
+
+    pub async fn list_entities_by_id(
+        &self,
+        _backend: &dyn Backend,
+        _req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let ids = self.list_entity_ids().await?;
+        Ok(Some(Response::list_response(&ids)))
+    }
+
+    pub async fn read_entity_by_id(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let id = req.get_data_as_str("id")?;
+        match self.get_entity(&id).await? {
+            Some(entity) => Ok(Some(entity_response(&entity))),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn update_entity_by_id(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let id = req.get_data_as_str("id")?;
+
+        let _guard = self.lock.lock().await;
+        let mut entity = self
+            .get_entity(&id)
+            .await?
+            .ok_or(RvError::ErrIdentityEntityNotFound)?;
+        let old_name = entity.name.clone();
+
+        if let Ok(name) = req.get_data_as_str("name") {
+            entity.name = name;
+        }
+        if let Ok(metadata) = req.get_data("metadata") {
+            entity.metadata = metadata.as_map().ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        self.put_entity(&entity, Some(&old_name)).await?;
+
+        Ok(Some(entity_id_response(&entity)))
+    }
+
+    pub async fn delete_entity_by_id(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let id = req.get_data_as_str("id")?;
+
+        let _guard = self.lock.lock().await;
+        self.delete_entity(&id).await?;
+
+        Ok(None)
+    }
+
+    pub async fn list_entities_by_name(
+        &self,
+        _backend: &dyn Backend,
+        _req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let names = self.list_entity_names().await?;
+        Ok(Some(Response::list_response(&names)))
+    }
+
+    pub async fn read_entity_by_name(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        match self.get_entity_by_name(&name).await? {
+            Some(entity) => Ok(Some(entity_response(&entity))),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn write_entity_by_name(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+
+        let _guard = self.lock.lock().await;
+        let mut entity = match self.get_entity_by_name(&name).await? {
+            Some(entity) => entity,
+            None => Entity::new(&name),
+        };
+        if let Ok(metadata) = req.get_data("metadata") {
+            entity.metadata = metadata.as_map().ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        self.put_entity(&entity, None).await?;
+
+        Ok(Some(entity_id_response(&entity)))
+    }
+
+    pub async fn delete_entity_by_name(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+
+        let _guard = self.lock.lock().await;
+        if let Some(entity) = self.get_entity_by_name(&name).await? {
+            self.delete_entity(&entity.id).await?;
+        }
+
+        Ok(None)
+    }
+
+    pub async fn merge_entity(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let to_id = req.get_data_as_str("to_entity_id")?;
+        let from_ids = req
+            .get_data("from_entity_ids")?
+            .as_comma_string_slice()
+            .ok_or(RvError::ErrRequestFieldInvalid)?;
+
+        let _guard = self.lock.lock().await;
+        let entity = self.merge_entities(&to_id, &from_ids).await?;
+
+        Ok(Some(entity_response(&entity)))
+    }
+}
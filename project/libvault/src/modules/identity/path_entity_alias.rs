@@ -0,0 +1,232 @@
+use serde_json::{Map, Value, json};
+
+use super::{EntityAlias, IdentityBackend, IdentityStore};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response, field::FieldTrait},
+};
+
+impl IdentityBackend {
+    pub fn entity_alias_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"entity-alias$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description(
+                        "Name of the user as the auth method knows it, e.g. a userpass username.",
+                    ),
+            )
+            .field(
+                "mount_accessor",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Accessor of the auth method mount the alias belongs to."),
+            )
+            .field(
+                "canonical_id",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("ID of the entity the alias links to."),
+            )
+            .field(
+                "metadata",
+                Field::builder()
+                    .field_type(FieldType::Map)
+                    .description("Metadata to be associated with the alias."),
+            )
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.create_entity_alias(backend, req).await })
+                }
+            })
+            .help("Create a new alias linking an auth method's user to an entity.")
+            .build()
+    }
+
+    pub fn entity_alias_id_list_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"entity-alias/id/?$")
+            .operation(Operation::List, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.list_entity_aliases(backend, req).await })
+                }
+            })
+            .help("List all the entity alias ids.")
+            .build()
+    }
+
+    pub fn entity_alias_id_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+        let backend_delete = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"entity-alias/id/(?P<id>.+)")
+            .field(
+                "id",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("ID of the alias."),
+            )
+            .field(
+                "canonical_id",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .description("ID of the entity to move the alias to."),
+            )
+            .field(
+                "metadata",
+                Field::builder()
+                    .field_type(FieldType::Map)
+                    .description("Metadata to be associated with the alias."),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_entity_alias(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.update_entity_alias(backend, req).await })
+                }
+            })
+            .operation(Operation::Delete, {
+                let handler = backend_delete.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.delete_entity_alias(backend, req).await })
+                }
+            })
+            .help("Read, update or delete an entity alias by its id.")
+            .build()
+    }
+}
+
+fn alias_response(alias: &EntityAlias) -> Response {
+    let data = json!({
+        "id": alias.id.clone(),
+        "canonical_id": alias.canonical_id.clone(),
+        "mount_accessor": alias.mount_accessor.clone(),
+        "name": alias.name.clone(),
+        "metadata": alias.metadata.clone(),
+    })
+    .as_object()
+    .cloned();
+
+    Response::data_response(data)
+}
+
+fn alias_id_response(alias: &EntityAlias) -> Response {
+    let mut data = Map::new();
+    data.insert("id".into(), Value::String(alias.id.clone()));
+    data.insert(
+        "canonical_id".into(),
+        Value::String(alias.canonical_id.clone()),
+    );
+    Response::data_response(Some(data))
+}
+
+impl IdentityStore {
+    pub async fn create_entity_alias(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        let mount_accessor = req.get_data_as_str("mount_accessor")?;
+        let canonical_id = req.get_data_as_str("canonical_id")?;
+        let metadata = match req.get_data("metadata") {
+            Ok(metadata) => metadata.as_map().ok_or(RvError::ErrRequestFieldInvalid)?,
+            Err(_) => Default::default(),
+        };
+
+        let _guard = self.lock.lock().await;
+        let alias = self
+            .create_alias(&canonical_id, &mount_accessor, &name, metadata)
+            .await?;
+
+        Ok(Some(alias_id_response(&alias)))
+    }
+
+    pub async fn list_entity_aliases(
+        &self,
+        _backend: &dyn Backend,
+        _req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let ids = self.list_alias_ids().await?;
+        Ok(Some(Response::list_response(&ids)))
+    }
+
+    pub async fn read_entity_alias(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let id = req.get_data_as_str("id")?;
+        match self.get_alias(&id).await? {
+            Some(alias) => Ok(Some(alias_response(&alias))),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn update_entity_alias(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let id = req.get_data_as_str("id")?;
+
+        let _guard = self.lock.lock().await;
+        let mut alias = self
+            .get_alias(&id)
+            .await?
+            .ok_or(RvError::ErrIdentityAliasNotFound)?;
+
+        if let Ok(metadata) = req.get_data("metadata") {
+            alias.metadata = metadata.as_map().ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        let canonical_id = match req.get_data_as_str("canonical_id") {
+            Ok(canonical_id) => canonical_id,
+            Err(_) => alias.canonical_id.clone(),
+        };
+        if canonical_id != alias.canonical_id {
+            self.move_alias(&mut alias, &canonical_id).await?;
+        } else {
+            self.put_alias(&alias).await?;
+        }
+
+        Ok(Some(alias_id_response(&alias)))
+    }
+
+    pub async fn delete_entity_alias(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let id = req.get_data_as_str("id")?;
+
+        let _guard = self.lock.lock().await;
+        self.delete_alias(&id).await?;
+
+        Ok(None)
+    }
+}
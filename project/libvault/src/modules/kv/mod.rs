@@ -1,5 +1,9 @@
 //! The secure key-value object storage module. The user can use this module to store arbitrary data
 //! into RustyVault. The data stored in RustyVault is encrypted.
+//!
+//! Every write bumps a revision counter of the written key. A write whose body carries
+//! `options.cas` only succeeds if `cas` equals the current revision, with 0 meaning that the key
+//! must not exist yet. This prevents concurrent writers from silently overwriting each other.
 
 use std::{any::Any, sync::Arc, time::Duration};
 
@@ -16,9 +20,13 @@ use crate::{
         Request, Response, SecretBuilder,
     },
     modules::Module,
+    rv_error_response,
     storage::StorageEntry,
+    utils::locks::Locks,
 };
 
+pub mod v2;
+
 static KV_BACKEND_HELP: &str = r#"
 The generic backend reads and writes arbitrary secrets to the backend.
 The secrets are encrypted/decrypted by RustyVault: they are never stored
@@ -31,6 +39,28 @@ revoke and/or replace the secret at that path.
 "#;
 const DEFAULT_LEASE_TTL: Duration = Duration::from_secs(3600_u64);
 
+// Revision counters are kept next to the secrets, under a prefix that is hidden from clients.
+const KV_REVISION_PREFIX: &str = ".revision/";
+
+/// Extract the check-and-set value from the `options` object of a write request. Returns `None`
+/// if the request does not ask for a check-and-set write.
+pub fn check_and_set_option(options: Option<&Value>) -> Result<Option<u64>, RvError> {
+    let Some(options) = options.and_then(|options| options.as_object()) else {
+        return Ok(None);
+    };
+
+    match options.get("cas") {
+        None | Some(Value::Null) => Ok(None),
+        Some(cas) => {
+            let cas = cas
+                .as_u64()
+                .or_else(|| cas.as_str().and_then(|cas| cas.parse::<u64>().ok()))
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+            Ok(Some(cas))
+        }
+    }
+}
+
 pub struct KvModule {
     pub name: String,
     pub backend: Arc<KvBackend>,
@@ -38,6 +68,8 @@ pub struct KvModule {
 
 pub struct KvBackendInner {
     pub core: Arc<Core>,
+    // Serializes the read-modify-write of a key's revision counter.
+    pub locks: Locks,
 }
 
 #[derive(Deref)]
@@ -49,7 +81,10 @@ pub struct KvBackend {
 impl KvBackend {
     pub fn new(core: Arc<Core>) -> Self {
         Self {
-            inner: Arc::new(KvBackendInner { core }),
+            inner: Arc::new(KvBackendInner {
+                core,
+                locks: Locks::new(),
+            }),
         }
     }
 
@@ -134,11 +169,33 @@ impl KvBackend {
 }
 
 impl KvBackendInner {
+    fn check_path(path: &str) -> Result<(), RvError> {
+        if path.starts_with(KV_REVISION_PREFIX) {
+            return Err(rv_error_response!(format!(
+                "paths under {KV_REVISION_PREFIX} are reserved"
+            )));
+        }
+        Ok(())
+    }
+
+    async fn get_revision(&self, req: &Request, path: &str) -> Result<u64, RvError> {
+        let entry = req
+            .storage_get(&format!("{KV_REVISION_PREFIX}{path}"))
+            .await?;
+        match entry {
+            Some(entry) => Ok(serde_json::from_slice(entry.value.as_slice())?),
+            // Keys written before revisions were tracked count as their first revision.
+            None => Ok(req.storage_get(path).await?.map_or(0, |_| 1)),
+        }
+    }
+
     pub async fn handle_read(
         &self,
         backend: &dyn Backend,
         req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
+        Self::check_path(&req.path)?;
+
         let entry = req.storage_get(&req.path).await?;
         if entry.is_none() {
             return Ok(None);
@@ -182,17 +239,41 @@ impl KvBackendInner {
         _backend: &dyn Backend,
         req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
-        if req.body.is_none() {
+        Self::check_path(&req.path)?;
+
+        let Some(mut body) = req.body.clone() else {
             return Err(RvError::ErrModuleKvDataFieldMissing);
+        };
+
+        let cas = check_and_set_option(body.get("options"))?;
+        if cas.is_some() {
+            body.remove("options");
+        }
+
+        let lock_entry = self.locks.get_lock(&req.path);
+        let _locked = lock_entry.lock.write().await;
+
+        let revision = self.get_revision(req, &req.path).await?;
+        if let Some(cas) = cas
+            && cas != revision
+        {
+            return Err(RvError::ErrModuleKvCheckAndSetMismatch);
         }
 
-        let data = serde_json::to_string(req.body.as_ref().unwrap())?;
+        let data = serde_json::to_string(&body)?;
         let entry = StorageEntry {
             key: req.path.clone(),
             value: data.into_bytes(),
         };
 
         req.storage_put(&entry).await?;
+
+        let revision_entry = StorageEntry::new(
+            &format!("{KV_REVISION_PREFIX}{}", req.path),
+            &(revision + 1),
+        )?;
+        req.storage_put(&revision_entry).await?;
+
         Ok(None)
     }
 
@@ -201,7 +282,14 @@ impl KvBackendInner {
         _backend: &dyn Backend,
         req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
+        Self::check_path(&req.path)?;
+
+        let lock_entry = self.locks.get_lock(&req.path);
+        let _locked = lock_entry.lock.write().await;
+
         req.storage_delete(&req.path).await?;
+        req.storage_delete(&format!("{KV_REVISION_PREFIX}{}", req.path))
+            .await?;
         Ok(None)
     }
 
@@ -210,7 +298,12 @@ impl KvBackendInner {
         _backend: &dyn Backend,
         req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
-        let keys = req.storage_list(&req.path).await?;
+        Self::check_path(&req.path)?;
+
+        let mut keys = req.storage_list(&req.path).await?;
+        if req.path.is_empty() {
+            keys.retain(|key| key != KV_REVISION_PREFIX);
+        }
         let resp = Response::list_response(&keys);
         Ok(Some(resp))
     }
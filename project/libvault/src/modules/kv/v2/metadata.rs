@@ -0,0 +1,207 @@
+use std::{collections::BTreeMap, time::SystemTime};
+
+use humantime::format_rfc3339;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use super::KvV2BackendInner;
+use crate::{
+    errors::RvError,
+    logical::Request,
+    storage::StorageEntry,
+    utils::{default_system_time, deserialize_system_time, serialize_system_time, sha256},
+};
+
+pub const DEFAULT_MAX_VERSIONS: u64 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionMetadata {
+    #[serde(
+        serialize_with = "serialize_system_time",
+        deserialize_with = "deserialize_system_time"
+    )]
+    pub created_time: SystemTime,
+    // The UNIX epoch means that the version is not soft-deleted.
+    #[serde(
+        default = "default_system_time",
+        serialize_with = "serialize_system_time",
+        deserialize_with = "deserialize_system_time"
+    )]
+    pub deletion_time: SystemTime,
+    #[serde(default)]
+    pub destroyed: bool,
+}
+
+/// The metadata of a secret. `versions` holds every version that has not been pruned yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KvMetadata {
+    pub current_version: u64,
+    // A value of zero means that the mount's setting applies.
+    pub max_versions: u64,
+    #[serde(default)]
+    pub cas_required: bool,
+    #[serde(
+        serialize_with = "serialize_system_time",
+        deserialize_with = "deserialize_system_time"
+    )]
+    pub created_time: SystemTime,
+    #[serde(
+        serialize_with = "serialize_system_time",
+        deserialize_with = "deserialize_system_time"
+    )]
+    pub updated_time: SystemTime,
+    pub versions: BTreeMap<u64, VersionMetadata>,
+}
+
+impl Default for KvMetadata {
+    fn default() -> Self {
+        let now = SystemTime::now();
+        Self {
+            current_version: 0,
+            max_versions: 0,
+            cas_required: false,
+            created_time: now,
+            updated_time: now,
+            versions: BTreeMap::new(),
+        }
+    }
+}
+
+impl VersionMetadata {
+    pub fn new(created_time: SystemTime) -> Self {
+        Self {
+            created_time,
+            deletion_time: default_system_time(),
+            destroyed: false,
+        }
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.deletion_time != default_system_time()
+    }
+
+    /// Whether the data of this version may no longer be returned: it is destroyed, or its
+    /// deletion time has passed.
+    pub fn is_unreadable(&self) -> bool {
+        self.destroyed || (self.is_deleted() && self.deletion_time <= SystemTime::now())
+    }
+
+    pub fn to_map(&self, version: u64) -> Map<String, Value> {
+        let deletion_time = if self.is_deleted() {
+            format_rfc3339(self.deletion_time).to_string()
+        } else {
+            String::new()
+        };
+
+        json!({
+            "version": version,
+            "created_time": format_rfc3339(self.created_time).to_string(),
+            "deletion_time": deletion_time,
+            "destroyed": self.destroyed,
+        })
+        .as_object()
+        .cloned()
+        .unwrap_or_default()
+    }
+}
+
+impl KvMetadata {
+    /// The number of versions kept: the secret's own limit if set, else the mount's, else
+    /// `DEFAULT_MAX_VERSIONS`.
+    pub fn effective_max_versions(&self, mount_max_versions: u64) -> u64 {
+        if self.max_versions != 0 {
+            self.max_versions
+        } else if mount_max_versions != 0 {
+            mount_max_versions
+        } else {
+            DEFAULT_MAX_VERSIONS
+        }
+    }
+
+    pub fn oldest_version(&self) -> u64 {
+        self.versions.keys().next().copied().unwrap_or(0)
+    }
+
+    /// Drop the oldest versions until at most `max_versions` remain, returning the dropped
+    /// version numbers so that their data can be removed.
+    pub fn prune(&mut self, mount_max_versions: u64) -> Vec<u64> {
+        let max_versions = self.effective_max_versions(mount_max_versions) as usize;
+        let mut pruned = Vec::new();
+        while self.versions.len() > max_versions {
+            let Some((version, _)) = self.versions.pop_first() else {
+                break;
+            };
+            pruned.push(version);
+        }
+        pruned
+    }
+
+    pub fn to_map(&self) -> Map<String, Value> {
+        let versions: Map<String, Value> = self
+            .versions
+            .iter()
+            .map(|(version, meta)| (version.to_string(), Value::Object(meta.to_map(*version))))
+            .collect();
+
+        json!({
+            "current_version": self.current_version,
+            "oldest_version": self.oldest_version(),
+            "max_versions": self.max_versions,
+            "cas_required": self.cas_required,
+            "created_time": format_rfc3339(self.created_time).to_string(),
+            "updated_time": format_rfc3339(self.updated_time).to_string(),
+            "versions": versions,
+        })
+        .as_object()
+        .cloned()
+        .unwrap_or_default()
+    }
+}
+
+pub fn metadata_key(path: &str) -> String {
+    format!("metadata/{path}")
+}
+
+pub fn version_key(path: &str, version: u64) -> String {
+    format!("versions/{}/{}", sha256(path.as_bytes()), version)
+}
+
+impl KvV2BackendInner {
+    pub async fn get_metadata(
+        &self,
+        req: &Request,
+        path: &str,
+    ) -> Result<Option<KvMetadata>, RvError> {
+        let storage_entry = req.storage_get(&metadata_key(path)).await?;
+        if storage_entry.is_none() {
+            return Ok(None);
+        }
+
+        let entry = storage_entry.unwrap();
+        let metadata: KvMetadata = serde_json::from_slice(entry.value.as_slice())?;
+        Ok(Some(metadata))
+    }
+
+    pub async fn put_metadata(
+        &self,
+        req: &Request,
+        path: &str,
+        metadata: &KvMetadata,
+    ) -> Result<(), RvError> {
+        let entry = StorageEntry::new(&metadata_key(path), metadata)?;
+        req.storage_put(&entry).await
+    }
+
+    /// Remove the stored data of the given versions of `path`.
+    pub async fn delete_versions_data(
+        &self,
+        req: &Request,
+        path: &str,
+        versions: &[u64],
+    ) -> Result<(), RvError> {
+        for version in versions {
+            req.storage_delete(&version_key(path, *version)).await?;
+        }
+        Ok(())
+    }
+}
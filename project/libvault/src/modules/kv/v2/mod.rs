@@ -0,0 +1,119 @@
+//! Version 2 of the key-value secrets engine, mounted with the `kv-v2` type.
+//!
+//! Unlike the v1 engine, which overwrites a secret in place, every write to `data/<path>`
+//! creates a new numbered version. The per-secret metadata at `metadata/<path>` records all the
+//! versions that are kept. Once a secret has more than `max_versions` versions, the oldest ones
+//! are pruned. `config` holds the mount-wide defaults for `max_versions` and `cas_required`;
+//! when `cas_required` is set, every write must carry a check-and-set version in `options.cas`.
+//!
+//! Versions can be soft-deleted at `delete/<path>`, which hides them from reads but keeps their
+//! data so that `undelete/<path>` can restore them. `destroy/<path>` removes the data of a
+//! version permanently.
+//!
+//! Secret values are stored under a hash of their path, one storage entry per version. The
+//! metadata entries are stored under the plain path, so listing `metadata/` walks the secret
+//! hierarchy.
+
+use std::{any::Any, sync::Arc};
+
+use async_trait::async_trait;
+use derive_more::Deref;
+
+use crate::{
+    core::Core,
+    errors::RvError,
+    logical::{Backend, LogicalBackend},
+    modules::Module,
+    utils::locks::Locks,
+};
+
+pub mod metadata;
+pub mod path_config;
+pub mod path_data;
+pub mod path_delete;
+pub mod path_metadata;
+
+pub use metadata::{KvMetadata, VersionMetadata};
+
+static KV_V2_BACKEND_HELP: &str = r#"
+This backend provides a versioned key-value store. The kv backend reads and
+writes arbitrary secrets to the storage backend. The secrets are
+encrypted/decrypted by RustyVault: they are never stored unencrypted in the
+backend and the backend never has an opportunity to see the unencrypted
+value. Each key can have a configured number of versions, and versions can
+be retrieved based on their version numbers.
+"#;
+
+pub struct KvV2Module {
+    pub name: String,
+    pub backend: Arc<KvV2Backend>,
+}
+
+pub struct KvV2BackendInner {
+    pub core: Arc<Core>,
+    // Serializes the read-modify-write of a secret's metadata.
+    pub locks: Locks,
+}
+
+#[derive(Deref)]
+pub struct KvV2Backend {
+    #[deref]
+    pub inner: Arc<KvV2BackendInner>,
+}
+
+impl KvV2Backend {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            inner: Arc::new(KvV2BackendInner {
+                core,
+                locks: Locks::new(),
+            }),
+        }
+    }
+
+    pub fn new_backend(&self) -> LogicalBackend {
+        LogicalBackend::builder()
+            .help(KV_V2_BACKEND_HELP)
+            .path(self.config_path())
+            .path(self.data_path())
+            .path(self.metadata_path())
+            .path(self.delete_path())
+            .path(self.undelete_path())
+            .path(self.destroy_path())
+            .build()
+    }
+}
+
+impl KvV2Module {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            name: "kv-v2".to_string(),
+            backend: Arc::new(KvV2Backend::new(core)),
+        }
+    }
+}
+
+#[async_trait]
+impl Module for KvV2Module {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn as_any_arc(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn setup(&self, core: &Core) -> Result<(), RvError> {
+        let kv = self.backend.clone();
+        let kv_backend_new_func = move |_c: Arc<Core>| -> Result<Arc<dyn Backend>, RvError> {
+            let mut kv_backend = kv.new_backend();
+            kv_backend.init()?;
+            Ok(Arc::new(kv_backend))
+        };
+        core.add_logical_backend("kv-v2", Arc::new(kv_backend_new_func))
+    }
+
+    fn cleanup(&self, core: &Core) -> Result<(), RvError> {
+        core.delete_logical_backend("kv-v2")
+    }
+}
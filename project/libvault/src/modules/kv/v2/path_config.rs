@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{KvV2Backend, KvV2BackendInner};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response, field::FieldTrait},
+    rv_error_response,
+    storage::StorageEntry,
+};
+
+/// The mount-wide settings of a KV v2 engine. They apply to every secret that does not override
+/// them in its own metadata.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KvV2Config {
+    pub max_versions: u64,
+    pub cas_required: bool,
+}
+
+impl KvV2Backend {
+    pub fn config_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"config$")
+            .field(
+                "max_versions",
+                Field::builder()
+                    .field_type(FieldType::Int)
+                    .description("The number of versions to keep for each key. Defaults to 10."),
+            )
+            .field(
+                "cas_required",
+                Field::builder().field_type(FieldType::Bool).description(
+                    "If true, the backend will require the cas parameter to be set for each write.",
+                ),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_config(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.write_config(backend, req).await })
+                }
+            })
+            .help("Configures settings for the KV store.")
+            .build()
+    }
+}
+
+impl KvV2BackendInner {
+    pub async fn get_config(&self, req: &Request) -> Result<KvV2Config, RvError> {
+        let storage_entry = req.storage_get("config").await?;
+        match storage_entry {
+            Some(entry) => Ok(serde_json::from_slice(entry.value.as_slice())?),
+            None => Ok(KvV2Config::default()),
+        }
+    }
+
+    pub async fn read_config(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let config = self.get_config(req).await?;
+
+        let data = json!({
+            "max_versions": config.max_versions,
+            "cas_required": config.cas_required,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn write_config(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let mut config = self.get_config(req).await?;
+
+        if let Ok(max_versions) = req.get_data("max_versions") {
+            let max_versions = max_versions
+                .as_int()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+            if max_versions < 0 {
+                return Err(rv_error_response!("max_versions cannot be negative"));
+            }
+            config.max_versions = max_versions as u64;
+        }
+
+        if let Ok(cas_required) = req.get_data("cas_required") {
+            config.cas_required = cas_required
+                .as_bool()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        let entry = StorageEntry::new("config", &config)?;
+        req.storage_put(&entry).await?;
+
+        Ok(None)
+    }
+}
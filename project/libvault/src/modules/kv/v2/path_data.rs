@@ -0,0 +1,188 @@
+use std::time::SystemTime;
+
+use serde_json::{Map, Value};
+
+use super::{
+    KvV2Backend, KvV2BackendInner,
+    metadata::{VersionMetadata, version_key},
+};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response, field::FieldTrait},
+    modules::kv::check_and_set_option,
+    rv_error_response,
+    storage::StorageEntry,
+};
+
+impl KvV2Backend {
+    pub fn data_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+        let backend_delete = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"data/(?P<path>.+)")
+            .field(
+                "path",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Location of the secret."),
+            )
+            .field(
+                "version",
+                Field::builder()
+                    .field_type(FieldType::Int)
+                    .default_value(0)
+                    .description("If provided during a read, the value at the version number will be returned."),
+            )
+            .field(
+                "data",
+                Field::builder()
+                    .field_type(FieldType::Map)
+                    .description("The contents of the data map will be stored and returned on read."),
+            )
+            .field(
+                "options",
+                Field::builder()
+                    .field_type(FieldType::Map)
+                    .description("Options for writing a KV entry. Set the \"cas\" value to use a Check-And-Set operation. If not set the write will be allowed. If set to 0 a write will only be allowed if the key doesn't exist. If the index is non-zero the write will only be allowed if the key's current version matches the version specified in the cas parameter."),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_data(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.write_data(backend, req).await })
+                }
+            })
+            .operation(Operation::Delete, {
+                let handler = backend_delete.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.delete_latest_version(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+This path takes a key name and based on the operation stores, retrieves or
+deletes versions of data.
+
+If a write operation is used the endpoint takes a data object and stores it
+as a new version. A read returns the latest version, or the version given by
+the 'version' parameter, together with the metadata of that version. A
+delete soft-deletes the latest version.
+                "#,
+            )
+            .build()
+    }
+}
+
+impl KvV2BackendInner {
+    pub async fn read_data(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let path = req.get_data_as_str("path")?;
+        let version = req
+            .get_data_or_default("version")
+            .ok()
+            .and_then(|version| version.as_int())
+            .unwrap_or(0);
+        if version < 0 {
+            return Err(rv_error_response!("version must be a positive integer"));
+        }
+
+        let lock_entry = self.locks.get_lock(&path);
+        let _locked = lock_entry.lock.read().await;
+
+        let Some(metadata) = self.get_metadata(req, &path).await? else {
+            return Ok(None);
+        };
+
+        let version = if version == 0 {
+            metadata.current_version
+        } else {
+            version as u64
+        };
+        let Some(version_metadata) = metadata.versions.get(&version) else {
+            return Ok(None);
+        };
+
+        // Deleted and destroyed versions only report their metadata, like Vault does.
+        let data = if version_metadata.is_unreadable() {
+            Value::Null
+        } else {
+            let Some(entry) = req.storage_get(&version_key(&path, version)).await? else {
+                return Ok(None);
+            };
+            Value::Object(serde_json::from_slice(entry.value.as_slice())?)
+        };
+
+        let mut resp_data = Map::new();
+        resp_data.insert("data".into(), data);
+        resp_data.insert(
+            "metadata".into(),
+            Value::Object(version_metadata.to_map(version)),
+        );
+
+        Ok(Some(Response::data_response(Some(resp_data))))
+    }
+
+    pub async fn write_data(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let path = req.get_data_as_str("path")?;
+        let data = req
+            .get_data("data")
+            .map_err(|_| RvError::ErrModuleKvDataFieldMissing)?
+            .as_object()
+            .cloned()
+            .ok_or(RvError::ErrRequestFieldInvalid)?;
+
+        let lock_entry = self.locks.get_lock(&path);
+        let _locked = lock_entry.lock.write().await;
+
+        let config = self.get_config(req).await?;
+        let mut metadata = self.get_metadata(req, &path).await?.unwrap_or_default();
+
+        let cas = check_and_set_option(req.get_data("options").ok().as_ref())?;
+        match cas {
+            Some(cas) if cas != metadata.current_version => {
+                return Err(RvError::ErrModuleKvCheckAndSetMismatch);
+            }
+            None if config.cas_required || metadata.cas_required => {
+                return Err(RvError::ErrModuleKvCheckAndSetRequired);
+            }
+            _ => {}
+        }
+
+        let now = SystemTime::now();
+        let version = metadata.current_version + 1;
+
+        let entry = StorageEntry::new(&version_key(&path, version), &data)?;
+        req.storage_put(&entry).await?;
+
+        let version_metadata = VersionMetadata::new(now);
+        let resp_data = version_metadata.to_map(version);
+
+        metadata.current_version = version;
+        metadata.updated_time = now;
+        metadata.versions.insert(version, version_metadata);
+        let pruned = metadata.prune(config.max_versions);
+
+        self.put_metadata(req, &path, &metadata).await?;
+        self.delete_versions_data(req, &path, &pruned).await?;
+
+        Ok(Some(Response::data_response(Some(resp_data))))
+    }
+}
@@ -0,0 +1,232 @@
+use std::time::SystemTime;
+
+use super::{KvV2Backend, KvV2BackendInner, metadata::version_key};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response, field::FieldTrait},
+    rv_error_response,
+    utils::default_system_time,
+};
+
+fn versions_field() -> Field {
+    Field::builder()
+        .field_type(FieldType::CommaStringSlice)
+        .required(true)
+        .description("The versions to be processed.")
+        .build()
+}
+
+impl KvV2Backend {
+    pub fn delete_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"delete/(?P<path>.+)")
+            .field(
+                "path",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Location of the secret."),
+            )
+            .field("versions", versions_field())
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.delete_versions(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+Marks the given versions of the secret as deleted. Their data is not removed
+and can be restored with the undelete endpoint.
+                "#,
+            )
+            .build()
+    }
+
+    pub fn undelete_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"undelete/(?P<path>.+)")
+            .field(
+                "path",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Location of the secret."),
+            )
+            .field("versions", versions_field())
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.undelete_versions(backend, req).await })
+                }
+            })
+            .help("Restores the given soft-deleted versions of the secret.")
+            .build()
+    }
+
+    pub fn destroy_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"destroy/(?P<path>.+)")
+            .field(
+                "path",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Location of the secret."),
+            )
+            .field("versions", versions_field())
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.destroy_versions(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+Permanently removes the data of the given versions of the secret. Their
+metadata is kept and marked as destroyed.
+                "#,
+            )
+            .build()
+    }
+}
+
+fn parse_versions(req: &Request) -> Result<Vec<u64>, RvError> {
+    let versions = req
+        .get_data("versions")?
+        .as_comma_string_slice()
+        .ok_or(RvError::ErrRequestFieldInvalid)?;
+    if versions.is_empty() {
+        return Err(rv_error_response!("no version number provided"));
+    }
+
+    versions
+        .iter()
+        .map(|version| {
+            version
+                .parse::<u64>()
+                .map_err(|_| rv_error_response!(format!("invalid version number: {version}")))
+        })
+        .collect()
+}
+
+impl KvV2BackendInner {
+    pub async fn delete_latest_version(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let path = req.get_data_as_str("path")?;
+
+        let lock_entry = self.locks.get_lock(&path);
+        let _locked = lock_entry.lock.write().await;
+
+        let Some(mut metadata) = self.get_metadata(req, &path).await? else {
+            return Ok(None);
+        };
+
+        let current_version = metadata.current_version;
+        if let Some(version_metadata) = metadata.versions.get_mut(&current_version)
+            && !version_metadata.is_deleted()
+            && !version_metadata.destroyed
+        {
+            version_metadata.deletion_time = SystemTime::now();
+            self.put_metadata(req, &path, &metadata).await?;
+        }
+
+        Ok(None)
+    }
+
+    pub async fn delete_versions(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let path = req.get_data_as_str("path")?;
+        let versions = parse_versions(req)?;
+
+        let lock_entry = self.locks.get_lock(&path);
+        let _locked = lock_entry.lock.write().await;
+
+        let Some(mut metadata) = self.get_metadata(req, &path).await? else {
+            return Ok(None);
+        };
+
+        let now = SystemTime::now();
+        for version in versions {
+            if let Some(version_metadata) = metadata.versions.get_mut(&version)
+                && !version_metadata.is_deleted()
+                && !version_metadata.destroyed
+            {
+                version_metadata.deletion_time = now;
+            }
+        }
+
+        self.put_metadata(req, &path, &metadata).await?;
+        Ok(None)
+    }
+
+    pub async fn undelete_versions(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let path = req.get_data_as_str("path")?;
+        let versions = parse_versions(req)?;
+
+        let lock_entry = self.locks.get_lock(&path);
+        let _locked = lock_entry.lock.write().await;
+
+        let Some(mut metadata) = self.get_metadata(req, &path).await? else {
+            return Ok(None);
+        };
+
+        for version in versions {
+            if let Some(version_metadata) = metadata.versions.get_mut(&version)
+                && !version_metadata.destroyed
+            {
+                version_metadata.deletion_time = default_system_time();
+            }
+        }
+
+        self.put_metadata(req, &path, &metadata).await?;
+        Ok(None)
+    }
+
+    pub async fn destroy_versions(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let path = req.get_data_as_str("path")?;
+        let versions = parse_versions(req)?;
+
+        let lock_entry = self.locks.get_lock(&path);
+        let _locked = lock_entry.lock.write().await;
+
+        let Some(mut metadata) = self.get_metadata(req, &path).await? else {
+            return Ok(None);
+        };
+
+        for version in versions {
+            if let Some(version_metadata) = metadata.versions.get_mut(&version)
+                && !version_metadata.destroyed
+            {
+                req.storage_delete(&version_key(&path, version)).await?;
+                version_metadata.destroyed = true;
+            }
+        }
+
+        self.put_metadata(req, &path, &metadata).await?;
+        Ok(None)
+    }
+}
@@ -0,0 +1,158 @@
+use super::{KvV2Backend, KvV2BackendInner, metadata::metadata_key};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response, field::FieldTrait},
+    rv_error_response,
+};
+
+impl KvV2Backend {
+    pub fn metadata_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+        let backend_delete = self.inner.clone();
+        let backend_list = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"metadata/?(?P<path>.*)")
+            .field(
+                "path",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .description("Location of the secret."),
+            )
+            .field(
+                "max_versions",
+                Field::builder().field_type(FieldType::Int).description(
+                    "The number of versions to keep. If not set, the default of 10 applies.",
+                ),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_metadata(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.write_metadata(backend, req).await })
+                }
+            })
+            .operation(Operation::Delete, {
+                let handler = backend_delete.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.delete_metadata(backend, req).await })
+                }
+            })
+            .operation(Operation::List, {
+                let handler = backend_list.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.list_metadata(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+This path provides access to the metadata of a secret, including the list of
+its versions. Writing to it configures the number of versions kept for the
+secret, and deleting it removes the secret together with all of its versions.
+                "#,
+            )
+            .build()
+    }
+}
+
+impl KvV2BackendInner {
+    pub async fn read_metadata(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let path = req.get_data_as_str("path")?;
+
+        let lock_entry = self.locks.get_lock(&path);
+        let _locked = lock_entry.lock.read().await;
+
+        let Some(metadata) = self.get_metadata(req, &path).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(Response::data_response(Some(metadata.to_map()))))
+    }
+
+    pub async fn write_metadata(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let path = req.get_data_as_str("path")?;
+
+        let lock_entry = self.locks.get_lock(&path);
+        let _locked = lock_entry.lock.write().await;
+
+        let mut metadata = self.get_metadata(req, &path).await?.unwrap_or_default();
+
+        if let Ok(max_versions) = req.get_data("max_versions") {
+            let max_versions = max_versions
+                .as_int()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+            if max_versions < 0 {
+                return Err(rv_error_response!("max_versions cannot be negative"));
+            }
+            metadata.max_versions = max_versions as u64;
+        }
+
+        if let Ok(cas_required) = req.get_data("cas_required") {
+            metadata.cas_required = cas_required
+                .as_bool()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        let config = self.get_config(req).await?;
+        let pruned = metadata.prune(config.max_versions);
+        self.put_metadata(req, &path, &metadata).await?;
+        self.delete_versions_data(req, &path, &pruned).await?;
+
+        Ok(None)
+    }
+
+    pub async fn delete_metadata(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let path = req.get_data_as_str("path")?;
+
+        let lock_entry = self.locks.get_lock(&path);
+        let _locked = lock_entry.lock.write().await;
+
+        let Some(metadata) = self.get_metadata(req, &path).await? else {
+            return Ok(None);
+        };
+
+        let versions: Vec<u64> = metadata.versions.keys().copied().collect();
+        self.delete_versions_data(req, &path, &versions).await?;
+        req.storage_delete(&metadata_key(&path)).await?;
+
+        Ok(None)
+    }
+
+    pub async fn list_metadata(
+        &self,
+        _backend: &dyn Backend,
+        req: &Request,
+    ) -> Result<Option<Response>, RvError> {
+        let path = req.get_data_or_default("path").ok();
+        let path = path.as_ref().and_then(|path| path.as_str()).unwrap_or("");
+        if !path.is_empty() && !path.ends_with('/') {
+            return Err(rv_error_response!("path must end with '/' to be listed"));
+        }
+
+        let keys = req.storage_list(&metadata_key(path)).await?;
+        let resp = Response::list_response(&keys);
+        Ok(Some(resp))
+    }
+}
@@ -15,9 +15,13 @@ use std::{any::Any, sync::Arc};
 pub mod auth;
 pub mod credential;
 pub mod crypto;
+#[cfg(feature = "secrets_database")]
+pub mod database;
+pub mod identity;
 pub mod kv;
 pub mod pki;
 pub mod policy;
+pub mod ssh;
 pub mod system;
 
 /// Utilities and helper traits consumed by modules.
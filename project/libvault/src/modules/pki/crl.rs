@@ -0,0 +1,169 @@
+//! Certificate revocation list support for the TLS CA.
+//!
+//! The rust `openssl` crate can parse CRLs but has no builder for them, so the CRL is assembled
+//! through the raw `X509_CRL_*` API, the same way `utils::cert` reaches for `openssl_sys` where the
+//! safe bindings fall short.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use foreign_types::ForeignType;
+use humantime::parse_duration;
+use libc::{c_int, c_long};
+use openssl::{asn1::Asn1Time, bn::BigNum, error::ErrorStack, hash::MessageDigest, x509::X509Crl};
+use openssl_sys::{ASN1_INTEGER, ASN1_TIME, EVP_MD, EVP_PKEY, X509_CRL, X509_NAME, X509_REVOKED};
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::RvError, logical::Request, storage::StorageEntry, utils::cert::CertBundle};
+
+pub const CRL_CONFIG_KEY: &str = "config/crl";
+pub const CRL_KEY: &str = "crl";
+pub const REVOKED_PREFIX: &str = "revoked/";
+pub const DEFAULT_CRL_EXPIRY: &str = "72h";
+
+unsafe extern "C" {
+    fn X509_CRL_new() -> *mut X509_CRL;
+    fn X509_CRL_set_version(crl: *mut X509_CRL, version: c_long) -> c_int;
+    fn X509_CRL_set_issuer_name(crl: *mut X509_CRL, name: *mut X509_NAME) -> c_int;
+    fn X509_CRL_set1_lastUpdate(crl: *mut X509_CRL, tm: *const ASN1_TIME) -> c_int;
+    fn X509_CRL_set1_nextUpdate(crl: *mut X509_CRL, tm: *const ASN1_TIME) -> c_int;
+    fn X509_CRL_add0_revoked(crl: *mut X509_CRL, rev: *mut X509_REVOKED) -> c_int;
+    fn X509_CRL_sort(crl: *mut X509_CRL) -> c_int;
+    fn X509_CRL_sign(crl: *mut X509_CRL, pkey: *mut EVP_PKEY, md: *const EVP_MD) -> c_int;
+    fn X509_REVOKED_new() -> *mut X509_REVOKED;
+    fn X509_REVOKED_free(rev: *mut X509_REVOKED);
+    fn X509_REVOKED_set_serialNumber(rev: *mut X509_REVOKED, serial: *mut ASN1_INTEGER) -> c_int;
+    fn X509_REVOKED_set_revocationDate(rev: *mut X509_REVOKED, tm: *mut ASN1_TIME) -> c_int;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrlConfig {
+    pub expiry: String,
+}
+
+impl Default for CrlConfig {
+    fn default() -> Self {
+        Self {
+            expiry: DEFAULT_CRL_EXPIRY.to_string(),
+        }
+    }
+}
+
+/// A revoked TLS certificate, stored under `revoked/<serial>`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevokedEntry {
+    pub serial_number: String,
+    /// Unix timestamp (seconds) of the first revocation; re-revoking never moves it.
+    pub revocation_time: i64,
+}
+
+fn cvt(r: c_int) -> Result<c_int, ErrorStack> {
+    if r <= 0 {
+        Err(ErrorStack::get())
+    } else {
+        Ok(r)
+    }
+}
+
+fn cvt_p<T>(p: *mut T) -> Result<*mut T, ErrorStack> {
+    if p.is_null() {
+        Err(ErrorStack::get())
+    } else {
+        Ok(p)
+    }
+}
+
+pub async fn read_crl_config(req: &Request) -> Result<CrlConfig, RvError> {
+    match req.storage_get(CRL_CONFIG_KEY).await? {
+        Some(entry) => Ok(serde_json::from_slice(&entry.value)?),
+        None => Ok(CrlConfig::default()),
+    }
+}
+
+pub async fn list_revoked(req: &Request) -> Result<Vec<RevokedEntry>, RvError> {
+    let mut revoked = Vec::new();
+    for serial in req.storage_list(REVOKED_PREFIX).await? {
+        let entry = req
+            .storage_get(&format!("{REVOKED_PREFIX}{serial}"))
+            .await?;
+        if let Some(entry) = entry {
+            revoked.push(serde_json::from_slice(&entry.value)?);
+        }
+    }
+    Ok(revoked)
+}
+
+/// Build a v2 CRL over `revoked`, signed by the CA in `ca_bundle` and valid for `expiry`.
+pub fn build_crl(
+    ca_bundle: &CertBundle,
+    revoked: &[RevokedEntry],
+    expiry: Duration,
+) -> Result<X509Crl, RvError> {
+    let digest = match ca_bundle.private_key_type.as_str() {
+        "rsa" | "ec" => Some(MessageDigest::sha256()),
+        #[cfg(feature = "crypto_adaptor_tongsuo")]
+        "sm2" => Some(MessageDigest::sm3()),
+        // Pure signature schemes take no separate digest.
+        "ed25519" => None,
+        _ => return Err(RvError::ErrPkiKeyTypeInvalid),
+    };
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    let this_update = Asn1Time::from_unix(now.as_secs() as i64)?;
+    let next_update = Asn1Time::from_unix((now + expiry).as_secs() as i64)?;
+    // Take an owned copy of the issuer so only owned `ForeignType` handles cross into FFI.
+    let issuer = ca_bundle.certificate.subject_name().to_owned()?;
+
+    unsafe {
+        let crl = X509Crl::from_ptr(cvt_p(X509_CRL_new())?);
+        cvt(X509_CRL_set_version(crl.as_ptr(), 1))?;
+        cvt(X509_CRL_set_issuer_name(crl.as_ptr(), issuer.as_ptr()))?;
+        cvt(X509_CRL_set1_lastUpdate(crl.as_ptr(), this_update.as_ptr()))?;
+        cvt(X509_CRL_set1_nextUpdate(crl.as_ptr(), next_update.as_ptr()))?;
+
+        for entry in revoked {
+            let serial = BigNum::from_hex_str(&entry.serial_number.replace(['-', ':'], ""))?
+                .to_asn1_integer()?;
+            let revocation_date = Asn1Time::from_unix(entry.revocation_time)?;
+
+            let rev = cvt_p(X509_REVOKED_new())?;
+            let ret = cvt(X509_REVOKED_set_serialNumber(rev, serial.as_ptr()))
+                .and_then(|_| {
+                    cvt(X509_REVOKED_set_revocationDate(
+                        rev,
+                        revocation_date.as_ptr(),
+                    ))
+                })
+                .and_then(|_| cvt(X509_CRL_add0_revoked(crl.as_ptr(), rev)));
+            if let Err(e) = ret {
+                X509_REVOKED_free(rev);
+                return Err(e.into());
+            }
+        }
+
+        cvt(X509_CRL_sort(crl.as_ptr()))?;
+        cvt(X509_CRL_sign(
+            crl.as_ptr(),
+            ca_bundle.private_key.as_ptr(),
+            digest.map_or(std::ptr::null(), |md| md.as_ptr()),
+        ))?;
+
+        Ok(crl)
+    }
+}
+
+/// Regenerate the CRL from every `revoked/` entry and persist it as DER under `crl`.
+pub async fn rebuild_crl(req: &Request, ca_bundle: &CertBundle) -> Result<X509Crl, RvError> {
+    let config = read_crl_config(req).await?;
+    let expiry = parse_duration(&config.expiry)?;
+    let revoked = list_revoked(req).await?;
+
+    let crl = build_crl(ca_bundle, &revoked, expiry)?;
+
+    let entry = StorageEntry {
+        key: CRL_KEY.to_string(),
+        value: crl.to_der()?,
+    };
+    req.storage_put(&entry).await?;
+
+    Ok(crl)
+}
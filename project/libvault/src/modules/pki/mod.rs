@@ -20,6 +20,7 @@ use std::{
     sync::{Arc, atomic::AtomicU64},
     time::Duration,
 };
+use tokio::sync::Mutex;
 use x509_parser::nom::AsBytes;
 use x509_parser::prelude::{FromDer, X509Certificate};
 use x509_parser::time::ASN1Time;
@@ -139,10 +140,12 @@ impl CertBackend for PgpCertBackend {
     }
 }
 
+pub mod crl;
 pub mod field;
 pub mod path_config_ca;
 pub mod path_config_crl;
 pub mod path_fetch;
+pub mod path_intermediate;
 pub mod path_issue;
 pub mod path_keys;
 pub mod path_revoke;
@@ -169,6 +172,7 @@ pub struct PkiBackendInner {
     pub core: Arc<Core>,
     pub cert_count: AtomicU64,
     pub revoked_cert_count: AtomicU64,
+    pub crl_lock: Mutex<()>,
 }
 
 #[derive(Deref)]
@@ -184,6 +188,7 @@ impl PkiBackend {
                 core,
                 cert_count: AtomicU64::new(0),
                 revoked_cert_count: AtomicU64::new(0),
+                crl_lock: Mutex::new(()),
             }),
         }
     }
@@ -193,10 +198,12 @@ impl PkiBackend {
             .help(PKI_BACKEND_HELP)
             .root_paths([
                 "config/*",
+                "revoke",
                 "revoke/*",
                 "crl/rotate",
                 "krl/rotate",
                 "root/*",
+                "intermediate/*",
                 "roles/*",
                 "keys/generate/*",
                 "keys/import",
@@ -207,6 +214,8 @@ impl PkiBackend {
             .path(self.config_crl_path())
             .path(self.root_generate_path())
             .path(self.root_delete_path())
+            .path(self.intermediate_generate_path())
+            .path(self.intermediate_set_signed_path())
             .path(self.fetch_ca_path())
             .path(self.fetch_crl_path())
             .path(self.fetch_cert_path())
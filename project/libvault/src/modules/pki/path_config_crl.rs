@@ -1,7 +1,14 @@
-use super::{PkiBackend, PkiBackendInner};
+use humantime::parse_duration;
+
+use super::{
+    PkiBackend, PkiBackendInner,
+    crl::{CRL_CONFIG_KEY, CrlConfig, read_crl_config},
+};
 use crate::{
     errors::RvError,
     logical::{Backend, Field, FieldType, Operation, Path, Request, Response},
+    modules::ResponseExt,
+    storage::StorageEntry,
 };
 
 impl PkiBackend {
@@ -43,16 +50,31 @@ impl PkiBackendInner {
     pub async fn read_path_crl(
         &self,
         _backend: &dyn Backend,
-        _req: &mut Request,
+        req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
-        Ok(None)
+        let config = read_crl_config(req).await?;
+        Ok(Some(Response::data_response(config.to_map()?)))
     }
 
     pub async fn write_path_crl(
         &self,
         _backend: &dyn Backend,
-        _req: &mut Request,
+        req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
+        let expiry = req.get_data_or_default("expiry")?;
+        let expiry = expiry
+            .as_str()
+            .ok_or(RvError::ErrRequestFieldInvalid)?
+            .trim();
+        // Reject values the CRL builder would not be able to use later on.
+        parse_duration(expiry)?;
+
+        let config = CrlConfig {
+            expiry: expiry.to_string(),
+        };
+        req.storage_put(&StorageEntry::new(CRL_CONFIG_KEY, &config)?)
+            .await?;
+
         Ok(None)
     }
 }
@@ -1,7 +1,11 @@
-use openssl::x509::X509;
+use base64::Engine;
+use openssl::x509::{X509, X509Crl};
+use serde_json::json;
 
 use super::{
-    CertBackend, PgpCertBackend, PkiBackend, PkiBackendInner, SshCertBackend, TlsCertBackend, types,
+    CertBackend, PgpCertBackend, PkiBackend, PkiBackendInner, SshCertBackend, TlsCertBackend,
+    crl::{CRL_KEY, rebuild_crl},
+    types,
 };
 use crate::{
     errors::RvError,
@@ -220,12 +224,40 @@ impl PkiBackendInner {
 
     // ── CRL ──
 
+    /// `crl` returns the DER encoding (base64 in the JSON body), `crl/pem` the PEM encoding.
     pub async fn read_path_fetch_crl(
         &self,
         _backend: &dyn Backend,
-        _req: &mut Request,
+        req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
-        Ok(None)
+        let crl = self.fetch_crl(req).await?;
+
+        let crl_value = if req.path.ends_with("/pem") {
+            String::from_utf8_lossy(&crl.to_pem()?).to_string()
+        } else {
+            base64::engine::general_purpose::STANDARD.encode(crl.to_der()?)
+        };
+
+        let data = json!({
+            "crl": crl_value,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    /// Load the stored CRL, generating the first one on demand once a CA is configured.
+    pub async fn fetch_crl(&self, req: &Request) -> Result<X509Crl, RvError> {
+        if let Some(entry) = req.storage_get(CRL_KEY).await?
+            && !entry.value.is_empty()
+        {
+            return Ok(X509Crl::from_der(&entry.value)?);
+        }
+
+        let ca_bundle = self.fetch_ca_bundle(req).await?;
+        let _guard = self.crl_lock.lock().await;
+        rebuild_crl(req, &ca_bundle).await
     }
 
     // ── TLS cert fetch ──
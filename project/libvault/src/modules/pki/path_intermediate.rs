@@ -0,0 +1,162 @@
+use openssl::{pkey::PKey, x509::X509};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use super::{PkiBackend, PkiBackendInner, field, util};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response},
+    storage::StorageEntry,
+    utils::cert::{self, CertBundle},
+};
+
+/// Private key generated alongside an intermediate CSR, kept until the signed cert comes back.
+const PENDING_INTERMEDIATE_KEY: &str = "config/intermediate/pending";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingIntermediate {
+    private_key: String,
+    private_key_type: String,
+}
+
+impl PkiBackend {
+    pub fn intermediate_generate_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        let mut path = Path::builder()
+            .pattern(r"intermediate/generate/(?P<exported>internal|exported)")
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.generate_intermediate(backend, req).await })
+                }
+            })
+            .help("Generate a new private key and a CSR for an intermediate CA.")
+            .build();
+
+        path.fields.extend(field::ca_common_fields());
+        path.fields.extend(field::ca_key_generation_fields());
+        path.fields.extend(field::ca_issue_fields());
+
+        path
+    }
+
+    pub fn intermediate_set_signed_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"intermediate/set-signed")
+            .field(
+                "certificate",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description(
+                        r#"PEM-format certificate. This must be the signed intermediate CA
+certificate, optionally followed by the rest of its issuing chain."#,
+                    ),
+            )
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.set_signed_intermediate(backend, req).await })
+                }
+            })
+            .help("Provide the signed intermediate CA certificate.")
+            .build()
+    }
+}
+
+impl PkiBackendInner {
+    pub async fn generate_intermediate(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let export_private_key = req
+            .get_data_or_default("exported")?
+            .as_str()
+            .ok_or(RvError::ErrRequestFieldInvalid)?
+            == "exported";
+
+        let role_entry = util::get_role_params(req)?;
+        let cert = util::generate_certificate(&role_entry, req)?;
+
+        let private_key = cert::generate_private_key(&cert.key_type, cert.key_bits)?;
+        let csr = cert.to_csr(&private_key)?;
+
+        let private_key_pem =
+            String::from_utf8_lossy(&private_key.private_key_to_pem_pkcs8()?).to_string();
+        let pending = PendingIntermediate {
+            private_key: private_key_pem.clone(),
+            private_key_type: cert.key_type.clone(),
+        };
+        req.storage_put(&StorageEntry::new(PENDING_INTERMEDIATE_KEY, &pending)?)
+            .await?;
+
+        let mut resp_data = json!({
+            "csr": String::from_utf8_lossy(&csr.to_pem()?),
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+
+        if export_private_key {
+            resp_data.insert("private_key".to_string(), Value::String(private_key_pem));
+            resp_data.insert(
+                "private_key_type".to_string(),
+                Value::String(pending.private_key_type),
+            );
+        }
+
+        Ok(Some(Response::data_response(Some(resp_data))))
+    }
+
+    pub async fn set_signed_intermediate(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let certificate = req.get_data_as_str("certificate")?;
+
+        let entry = req
+            .storage_get(PENDING_INTERMEDIATE_KEY)
+            .await?
+            .ok_or(RvError::ErrPkiIntermediateNotGenerated)?;
+        let pending: PendingIntermediate = serde_json::from_slice(&entry.value)?;
+        let private_key = PKey::private_key_from_pem(pending.private_key.as_bytes())?;
+
+        let mut certs = X509::stack_from_pem(certificate.as_bytes())?.into_iter();
+        let intermediate = certs.next().ok_or(RvError::ErrPkiPemBundleInvalid)?;
+        if !cert::is_ca_cert(&intermediate) {
+            return Err(RvError::ErrPkiCertIsNotCA);
+        }
+        let pub_key = intermediate.public_key()?;
+        if !private_key.public_eq(&pub_key) {
+            return Err(RvError::ErrPkiIntermediateKeyMismatch);
+        }
+
+        let cert_bundle = CertBundle {
+            serial_number: cert::format_serial_number(&intermediate)?,
+            certificate: intermediate,
+            ca_chain: certs.collect(),
+            private_key,
+            private_key_type: pending.private_key_type,
+        };
+        cert_bundle.verify()?;
+
+        self.store_ca_bundle(req, &cert_bundle).await?;
+        req.storage_delete(PENDING_INTERMEDIATE_KEY).await?;
+
+        // The previous CRL was signed by a different issuer.
+        let entry = StorageEntry {
+            key: "crl".to_string(),
+            value: Vec::new(),
+        };
+        req.storage_put(&entry).await?;
+
+        Ok(None)
+    }
+}
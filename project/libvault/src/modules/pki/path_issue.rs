@@ -1,19 +1,36 @@
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{
+    net::IpAddr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use base64::Engine;
 use humantime::parse_duration;
-use openssl::{asn1::Asn1Time, pkey::PKey, x509::X509NameBuilder};
+use openssl::{
+    asn1::Asn1Time,
+    nid::Nid,
+    pkey::{Id, PKey, Public},
+    x509::{X509Name, X509NameBuilder, X509Req},
+};
 use rand::Rng;
-use serde_json::{Map, Value};
+use serde_json::{Map, Value, json};
 use tracing::info;
 
-use super::{PkiBackend, PkiBackendInner, ssh_util, types};
+use x509_parser::{
+    certification_request::X509CertificationRequest,
+    extensions::{GeneralName, ParsedExtension},
+    prelude::FromDer,
+};
+
+use super::{PkiBackend, PkiBackendInner, path_roles::RoleEntry, ssh_util, types};
 use crate::{
     errors::RvError,
     logical::{Backend, Field, FieldType, Operation, Path, Request, Response},
     modules::{RequestExt, ResponseExt},
     utils,
-    utils::cert,
+    utils::{
+        cert::{self, CertBundle},
+        token_util::MAX_LEASE_TTL,
+    },
 };
 
 impl PkiBackend {
@@ -55,6 +72,12 @@ impl PkiBackend {
                     .field_type(FieldType::Str)
                     .description("IP SANs, comma-delimited"),
             )
+            .field(
+                "uri_sans",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .description("URI SANs, comma-delimited"),
+            )
             .field(
                 "ttl",
                 Field::builder()
@@ -124,6 +147,30 @@ impl PkiBackend {
                     .field_type(FieldType::Str)
                     .description("PEM-encoded CSR (TLS)"),
             )
+            .field(
+                "common_name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .description("Common name; taken from the CSR if not set (TLS)"),
+            )
+            .field(
+                "alt_names",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .description("Subject Alternative Names, comma-delimited (TLS)"),
+            )
+            .field(
+                "ip_sans",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .description("IP SANs, comma-delimited (TLS)"),
+            )
+            .field(
+                "uri_sans",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .description("URI SANs, comma-delimited (TLS)"),
+            )
             .operation(Operation::Write, {
                 let handler = backend.clone();
                 move |backend, req| {
@@ -137,6 +184,14 @@ impl PkiBackend {
 }
 
 impl PkiBackendInner {
+    /// Longest validity the mount serving `req` allows, as tuned through `sys/mounts/<path>/tune`.
+    fn mount_max_ttl(&self, req: &Request) -> Result<Duration, RvError> {
+        match self.core.router.matching_mount_entry(&req.mount_point)? {
+            Some(mount_entry) => Ok(mount_entry.read()?.max_lease_ttl()),
+            None => Ok(MAX_LEASE_TTL),
+        }
+    }
+
     // ── Dispatch ──
 
     pub async fn dispatch_issue(
@@ -161,10 +216,7 @@ impl PkiBackendInner {
         let ct = req.get_data("cert_type")?;
         let ct = ct.as_str().ok_or(RvError::ErrRequestFieldInvalid)?;
         match ct {
-            "tls" => {
-                // TLS CSR signing - not yet implemented
-                Err(RvError::ErrPkiKeyOperationInvalid)
-            }
+            "tls" => self.sign_cert(backend, req).await,
             "ssh" => self.ssh_sign_key(backend, req).await,
             _ => Err(RvError::ErrRequestFieldInvalid),
         }
@@ -186,13 +238,7 @@ impl PkiBackendInner {
             common_names.push(common_name.clone());
         }
 
-        if let Some(alt_names) = payload.alt_names
-            && !alt_names.is_empty()
-        {
-            for v in alt_names.split(',') {
-                common_names.push(v.to_string());
-            }
-        }
+        common_names.extend(split_comma(payload.alt_names.as_deref()));
 
         let role = self
             .get_role(
@@ -207,60 +253,24 @@ impl PkiBackendInner {
         }
 
         let role_entry = role.unwrap();
-
-        let mut ip_sans = Vec::new();
-        if let Some(ip_sans_str) = payload.ip_sans
-            && !ip_sans_str.is_empty()
-        {
-            for v in ip_sans_str.split(',') {
-                ip_sans.push(v.to_string());
-            }
+        if role_entry.key_type == "any" {
+            // There is no key type to generate; such roles can only sign CSRs.
+            return Err(RvError::ErrPkiKeyTypeInvalid);
         }
 
-        let ca_bundle = self.fetch_ca_bundle(req).await?;
-        let not_before = SystemTime::now() - Duration::from_secs(10);
-        let mut not_after = not_before + parse_duration("30d").unwrap();
-
-        if let Some(ttl) = payload.ttl {
-            let ttl_dur = parse_duration(ttl.as_str())?;
-            let req_ttl_not_after_dur = SystemTime::now() + ttl_dur;
-            let req_ttl_not_after = Asn1Time::from_unix(
-                req_ttl_not_after_dur.duration_since(UNIX_EPOCH)?.as_secs() as i64,
-            )?;
-            let ca_not_after = ca_bundle.certificate.not_after();
-            match ca_not_after.compare(&req_ttl_not_after) {
-                Ok(ret) => {
-                    if ret == std::cmp::Ordering::Less {
-                        return Err(RvError::ErrRequestInvalid);
-                    }
-                    not_after = req_ttl_not_after_dur;
-                }
-                Err(err) => {
-                    return Err(RvError::OpenSSL { source: err });
-                }
-            }
-        }
+        let ip_sans = split_comma(payload.ip_sans.as_deref());
+        let uri_sans = req.get_data("uri_sans").ok();
+        let uri_sans = split_comma(uri_sans.as_ref().and_then(|value| value.as_str()));
+        role_entry.validate_names(&common_names, &ip_sans, &uri_sans)?;
 
-        let mut subject_name = X509NameBuilder::new().unwrap();
-        if !role_entry.country.is_empty() {
-            subject_name.append_entry_by_text("C", &role_entry.country)?;
-        }
-        if !role_entry.province.is_empty() {
-            subject_name.append_entry_by_text("ST", &role_entry.province)?;
-        }
-        if !role_entry.locality.is_empty() {
-            subject_name.append_entry_by_text("L", &role_entry.locality)?;
-        }
-        if !role_entry.organization.is_empty() {
-            subject_name.append_entry_by_text("O", &role_entry.organization)?;
-        }
-        if !role_entry.ou.is_empty() {
-            subject_name.append_entry_by_text("OU", &role_entry.ou)?;
-        }
-        if !common_name.is_empty() {
-            subject_name.append_entry_by_text("CN", &common_name)?;
-        }
-        let subject = subject_name.build();
+        let ca_bundle = self.fetch_ca_bundle(req).await?;
+        let (not_before, not_after) = validity_period(
+            &role_entry,
+            &ca_bundle,
+            payload.ttl.as_deref(),
+            self.mount_max_ttl(req)?,
+        )?;
+        let subject = subject_name(&role_entry, &common_name)?;
 
         let mut cert_obj = cert::Certificate {
             not_before,
@@ -268,6 +278,7 @@ impl PkiBackendInner {
             subject,
             dns_sans: common_names,
             ip_sans,
+            uri_sans,
             key_type: role_entry.key_type.clone(),
             key_bits: role_entry.key_bits,
             ..cert::Certificate::default()
@@ -284,9 +295,11 @@ impl PkiBackendInner {
 
         let cert_expiration =
             utils::asn1time_to_timestamp(cert_bundle.certificate.not_after().to_string().as_str())?;
-        let ca_chain_pem: String = cert_bundle
-            .ca_chain
-            .iter()
+        // Issuing CA first, followed by whatever chain it was imported with, so that certs
+        // issued by an intermediate chain all the way up to the root.
+        let ca_chain_pem: String = ca_bundle
+            .get_cert_chain()
+            .into_iter()
             .map(|x509| x509.to_pem().unwrap())
             .map(|pem| String::from_utf8_lossy(&pem).to_string())
             .collect::<Vec<String>>()
@@ -329,6 +342,102 @@ impl PkiBackendInner {
         }
     }
 
+    // ── TLS sign ──
+
+    pub async fn sign_cert(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let role_name = req.get_data_as_str("role")?;
+        let role_entry = self
+            .get_role(req, &role_name)
+            .await?
+            .ok_or(RvError::ErrPkiRoleNotFound)?;
+
+        let csr = X509Req::from_pem(req.get_data_as_str("csr")?.as_bytes())?;
+        let public_key = csr.public_key()?;
+        if !csr.verify(&public_key)? {
+            return Err(RvError::ErrPkiDataInvalid);
+        }
+        let key_type = check_key_constraints(&role_entry, &public_key)?;
+
+        let mut common_name = optional_str(req, "common_name");
+        if common_name.is_empty()
+            && role_entry.use_csr_common_name
+            && let Some(entry) = csr.subject_name().entries_by_nid(Nid::COMMONNAME).next()
+        {
+            common_name = entry.data().as_utf8()?.to_string();
+        }
+
+        let mut dns_sans = split_comma(Some(optional_str(req, "alt_names").as_str()));
+        let mut ip_sans = split_comma(Some(optional_str(req, "ip_sans").as_str()));
+        let mut uri_sans = split_comma(Some(optional_str(req, "uri_sans").as_str()));
+        if role_entry.use_csr_sans {
+            csr_sans(&csr, &mut dns_sans, &mut ip_sans, &mut uri_sans)?;
+        }
+
+        let mut names = dns_sans.clone();
+        if !common_name.is_empty() {
+            names.insert(0, common_name.clone());
+        }
+        role_entry.validate_names(&names, &ip_sans, &uri_sans)?;
+
+        let ca_bundle = self.fetch_ca_bundle(req).await?;
+        let ttl = optional_str(req, "ttl");
+        let (not_before, not_after) = validity_period(
+            &role_entry,
+            &ca_bundle,
+            Some(ttl.as_str()),
+            self.mount_max_ttl(req)?,
+        )?;
+
+        let mut cert_obj = cert::Certificate {
+            not_before,
+            not_after,
+            subject: subject_name(&role_entry, &common_name)?,
+            dns_sans: names,
+            ip_sans,
+            uri_sans,
+            key_type: key_type.to_string(),
+            key_bits: public_key.bits(),
+            ..cert::Certificate::default()
+        };
+
+        let certificate = cert_obj.sign_public_key(
+            &ca_bundle.certificate,
+            &ca_bundle.private_key,
+            &public_key,
+        )?;
+        let serial_number = cert::format_serial_number(&certificate)?;
+
+        if !role_entry.no_store {
+            let serial_number_hex = serial_number.replace(':', "-");
+            self.store_cert(req, &serial_number_hex, &certificate)
+                .await?;
+        }
+
+        let ca_chain_pem: String = ca_bundle
+            .get_cert_chain()
+            .into_iter()
+            .map(|x509| x509.to_pem().unwrap())
+            .map(|pem| String::from_utf8_lossy(&pem).to_string())
+            .collect::<Vec<String>>()
+            .join("");
+
+        let data = json!({
+            "certificate": String::from_utf8_lossy(&certificate.to_pem()?),
+            "issuing_ca": String::from_utf8_lossy(&ca_bundle.certificate.to_pem()?),
+            "ca_chain": ca_chain_pem,
+            "serial_number": serial_number,
+            "expiration": utils::asn1time_to_timestamp(certificate.not_after().to_string().as_str())?,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
     // ── SSH issue ──
 
     pub async fn ssh_issue_cert(
@@ -379,7 +488,8 @@ impl PkiBackendInner {
             parse_duration(ttl_str)?
         } else {
             role.ttl
-        };
+        }
+        .min(self.mount_max_ttl(req)?);
 
         let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
         let valid_after = now - 10;
@@ -519,7 +629,8 @@ impl PkiBackendInner {
             parse_duration(ttl_str)?
         } else {
             role.ttl
-        };
+        }
+        .min(self.mount_max_ttl(req)?);
 
         let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
         let valid_after = now - 10;
@@ -580,3 +691,149 @@ impl PkiBackendInner {
         Ok(Some(Response::data_response(response.to_map()?)))
     }
 }
+
+const DEFAULT_ISSUE_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+fn split_comma(value: Option<&str>) -> Vec<String> {
+    value
+        .map(|value| {
+            value
+                .split(',')
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn optional_str(req: &Request, key: &str) -> String {
+    req.get_data(key)
+        .ok()
+        .and_then(|value| value.as_str().map(|v| v.trim().to_string()))
+        .unwrap_or_default()
+}
+
+/// Work out the validity window for a new leaf. A requested TTL above the role's `max_ttl` or the
+/// mount's `max_lease_ttl` is clamped to it, but a certificate is never allowed to outlive its
+/// issuer.
+fn validity_period(
+    role_entry: &RoleEntry,
+    ca_bundle: &CertBundle,
+    ttl: Option<&str>,
+    mount_max_ttl: Duration,
+) -> Result<(SystemTime, SystemTime), RvError> {
+    let not_before = SystemTime::now() - Duration::from_secs(10);
+    let Some(ttl) = ttl.filter(|ttl| !ttl.is_empty()) else {
+        return Ok((
+            not_before,
+            not_before + role_entry.clamp_ttl(DEFAULT_ISSUE_TTL).min(mount_max_ttl),
+        ));
+    };
+
+    let not_after = SystemTime::now()
+        + role_entry
+            .clamp_ttl(parse_duration(ttl)?)
+            .min(mount_max_ttl);
+    let req_ttl_not_after =
+        Asn1Time::from_unix(not_after.duration_since(UNIX_EPOCH)?.as_secs() as i64)?;
+    match ca_bundle
+        .certificate
+        .not_after()
+        .compare(&req_ttl_not_after)
+    {
+        Ok(std::cmp::Ordering::Less) => Err(RvError::ErrRequestInvalid),
+        Ok(_) => Ok((not_before, not_after)),
+        Err(err) => Err(RvError::OpenSSL { source: err }),
+    }
+}
+
+fn subject_name(role_entry: &RoleEntry, common_name: &str) -> Result<X509Name, RvError> {
+    let mut subject_name = X509NameBuilder::new()?;
+    if !role_entry.country.is_empty() {
+        subject_name.append_entry_by_text("C", &role_entry.country)?;
+    }
+    if !role_entry.province.is_empty() {
+        subject_name.append_entry_by_text("ST", &role_entry.province)?;
+    }
+    if !role_entry.locality.is_empty() {
+        subject_name.append_entry_by_text("L", &role_entry.locality)?;
+    }
+    if !role_entry.organization.is_empty() {
+        subject_name.append_entry_by_text("O", &role_entry.organization)?;
+    }
+    if !role_entry.ou.is_empty() {
+        subject_name.append_entry_by_text("OU", &role_entry.ou)?;
+    }
+    if !common_name.is_empty() {
+        subject_name.append_entry_by_text("CN", common_name)?;
+    }
+    Ok(subject_name.build())
+}
+
+/// The CSR's key must be of the role's `key_type`; RSA keys may be larger than `key_bits`.
+/// Returns the CSR's key type, which is all an `any` role has to go on.
+fn check_key_constraints(
+    role_entry: &RoleEntry,
+    public_key: &PKey<Public>,
+) -> Result<&'static str, RvError> {
+    let key_type = match public_key.id() {
+        Id::RSA => "rsa",
+        Id::EC => "ec",
+        Id::SM2 => "sm2",
+        Id::ED25519 => "ed25519",
+        _ => return Err(RvError::ErrPkiKeyTypeInvalid),
+    };
+    if role_entry.key_type != "any" && role_entry.key_type != key_type {
+        return Err(RvError::ErrPkiKeyTypeInvalid);
+    }
+
+    let bits = public_key.bits();
+    let bits_ok = match key_type {
+        "rsa" => bits >= role_entry.key_bits,
+        "ec" => role_entry.key_type == "any" || bits == role_entry.key_bits,
+        _ => true,
+    };
+    if !bits_ok {
+        return Err(RvError::ErrPkiKeyBitsInvalid);
+    }
+
+    Ok(key_type)
+}
+
+/// Append the DNS, IP and URI SANs requested in the CSR's extension request.
+fn csr_sans(
+    csr: &X509Req,
+    dns_sans: &mut Vec<String>,
+    ip_sans: &mut Vec<String>,
+    uri_sans: &mut Vec<String>,
+) -> Result<(), RvError> {
+    let der = csr.to_der()?;
+    let (_, parsed) =
+        X509CertificationRequest::from_der(&der).map_err(|_| RvError::ErrPkiDataInvalid)?;
+    let Some(extensions) = parsed.requested_extensions() else {
+        return Ok(());
+    };
+
+    for extension in extensions {
+        let ParsedExtension::SubjectAlternativeName(san) = extension else {
+            continue;
+        };
+        for name in &san.general_names {
+            match name {
+                GeneralName::DNSName(dns) => dns_sans.push(dns.to_string()),
+                GeneralName::URI(uri) => uri_sans.push(uri.to_string()),
+                GeneralName::IPAddress(bytes) => match bytes.len() {
+                    4 => {
+                        ip_sans.push(IpAddr::from(<[u8; 4]>::try_from(*bytes).unwrap()).to_string())
+                    }
+                    16 => ip_sans
+                        .push(IpAddr::from(<[u8; 16]>::try_from(*bytes).unwrap()).to_string()),
+                    _ => return Err(RvError::ErrPkiDataInvalid),
+                },
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
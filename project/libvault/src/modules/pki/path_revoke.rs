@@ -1,8 +1,20 @@
-use super::{PkiBackend, PkiBackendInner, types};
+use std::{
+    sync::atomic::Ordering,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::json;
+
+use super::{
+    PkiBackend, PkiBackendInner,
+    crl::{REVOKED_PREFIX, RevokedEntry, rebuild_crl},
+    types,
+};
 use crate::{
     errors::RvError,
     logical::{Backend, Field, FieldType, Operation, Path, Request, Response},
     modules::RequestExt,
+    storage::StorageEntry,
 };
 
 impl PkiBackend {
@@ -10,13 +22,13 @@ impl PkiBackend {
         let backend = self.inner.clone();
 
         Path::builder()
-            .pattern(r"revoke/(?P<cert_type>tls|ssh|pgp)")
+            .pattern(r"revoke(/(?P<cert_type>tls|ssh|pgp))?")
             .field(
                 "cert_type",
                 Field::builder()
                     .field_type(FieldType::Str)
-                    .required(true)
-                    .description("Certificate type: tls, ssh, or pgp"),
+                    .default_value("tls")
+                    .description("Certificate type: tls, ssh, or pgp; defaults to tls"),
             )
             .field(
                 "serial_number",
@@ -64,7 +76,7 @@ impl PkiBackendInner {
         backend: &dyn Backend,
         req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
-        let ct = req.get_data("cert_type")?;
+        let ct = req.get_data_or_default("cert_type")?;
         let ct = ct.as_str().ok_or(RvError::ErrRequestFieldInvalid)?;
         match ct {
             "tls" => self.revoke_cert(backend, req).await,
@@ -79,8 +91,49 @@ impl PkiBackendInner {
         _backend: &dyn Backend,
         req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
-        let _payload: types::RevokeCertificateRequest = req.parse_json()?;
-        Ok(None)
+        let payload: types::RevokeCertificateRequest = req.parse_json()?;
+        let serial_number = payload
+            .serial_number
+            .trim()
+            .replace(':', "-")
+            .to_lowercase();
+        if serial_number.is_empty() {
+            return Err(RvError::ErrRequestFieldInvalid);
+        }
+
+        let ca_bundle = self.fetch_ca_bundle(req).await?;
+        if ca_bundle.serial_number.replace(':', "-").to_lowercase() == serial_number {
+            return Err(RvError::ErrPkiDataInvalid);
+        }
+
+        // Make sure the serial belongs to a certificate issued by this mount.
+        self.fetch_cert(req, &serial_number).await?;
+
+        let _guard = self.crl_lock.lock().await;
+
+        let key = format!("{REVOKED_PREFIX}{serial_number}");
+        let revoked = match req.storage_get(&key).await? {
+            Some(entry) => serde_json::from_slice::<RevokedEntry>(&entry.value)?,
+            None => {
+                let revoked = RevokedEntry {
+                    serial_number: serial_number.clone(),
+                    revocation_time: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64,
+                };
+                req.storage_put(&StorageEntry::new(&key, &revoked)?).await?;
+                self.revoked_cert_count.fetch_add(1, Ordering::Relaxed);
+                revoked
+            }
+        };
+
+        rebuild_crl(req, &ca_bundle).await?;
+
+        let data = json!({
+            "revocation_time": revoked.revocation_time,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
     }
 
     pub async fn revoke_ssh_cert(
@@ -105,8 +158,19 @@ impl PkiBackendInner {
     pub async fn read_rotate_crl(
         &self,
         _backend: &dyn Backend,
-        _req: &mut Request,
+        req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
-        Ok(None)
+        let ca_bundle = self.fetch_ca_bundle(req).await?;
+
+        let _guard = self.crl_lock.lock().await;
+        rebuild_crl(req, &ca_bundle).await?;
+
+        let data = json!({
+            "success": true,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
     }
 }
@@ -67,6 +67,81 @@ pub struct RoleEntry {
     pub no_store: bool,
     pub generate_lease: bool,
     pub not_after: String,
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    #[serde(default)]
+    pub allowed_uri_sans: Vec<String>,
+}
+
+impl RoleEntry {
+    /// Check a common name or DNS SAN against `allowed_domains` and the `allow_*` flags.
+    pub fn is_name_allowed(&self, name: &str) -> bool {
+        if self.allow_any_name {
+            return true;
+        }
+
+        let name = name.trim_end_matches('.').to_lowercase();
+        if self.allow_localhost && (name == "localhost" || name == "localdomain") {
+            return true;
+        }
+
+        self.allowed_domains.iter().any(|domain| {
+            let domain = domain.trim_end_matches('.').to_lowercase();
+            (self.allow_bare_domains && name == domain)
+                || (self.allow_subdomains && name.ends_with(&format!(".{domain}")))
+        })
+    }
+
+    /// Check a URI SAN against `allowed_uri_sans`; entries may use `*` as a wildcard.
+    pub fn is_uri_san_allowed(&self, uri: &str) -> bool {
+        self.allowed_uri_sans
+            .iter()
+            .any(|pattern| glob_match(pattern, uri))
+    }
+
+    /// Reject any requested name the role does not allow, before anything gets signed.
+    pub fn validate_names(
+        &self,
+        names: &[String],
+        ip_sans: &[String],
+        uri_sans: &[String],
+    ) -> Result<(), RvError> {
+        if let Some(name) = names.iter().find(|name| !self.is_name_allowed(name)) {
+            return Err(RvError::ErrPkiNameNotAllowed(name.clone()));
+        }
+        if !self.allow_ip_sans
+            && let Some(ip) = ip_sans.first()
+        {
+            return Err(RvError::ErrPkiNameNotAllowed(ip.clone()));
+        }
+        if let Some(uri) = uri_sans.iter().find(|uri| !self.is_uri_san_allowed(uri)) {
+            return Err(RvError::ErrPkiNameNotAllowed(uri.clone()));
+        }
+        Ok(())
+    }
+
+    /// Requests above `max_ttl` are clamped rather than rejected.
+    pub fn clamp_ttl(&self, ttl: Duration) -> Duration {
+        if self.max_ttl.is_zero() {
+            ttl
+        } else {
+            ttl.min(self.max_ttl)
+        }
+    }
+}
+
+fn glob_match(pattern: &str, value: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == value,
+        Some((prefix, rest)) => {
+            let Some(value) = value.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=value.len())
+                .filter(|i| value.is_char_boundary(*i))
+                .any(|i| glob_match(rest, &value[i..]))
+        }
+    }
 }
 
 impl PkiBackend {
@@ -150,7 +225,7 @@ independent of allowed_domains value."#,
             .field(
                 "allowed_domains",
                 Field::builder()
-                    .field_type(FieldType::Str)
+                    .field_type(FieldType::CommaStringSlice)
                     .description(
                         r#"
 Specifies the domains this role is allowed to issue certificates for.
@@ -160,6 +235,16 @@ SAN entries of certificates. See the documentation for more information.
 This parameter accepts a comma-separated string or list of domains."#,
                     ),
             )
+            .field(
+                "allowed_uri_sans",
+                Field::builder()
+                    .field_type(FieldType::CommaStringSlice)
+                    .description(
+                        r#"
+If set, an array of allowed URIs for URI Subject Alternative Names.
+Any valid URI is accepted, these values support globbing."#,
+                    ),
+            )
             .field(
                 "allow_bare_domains",
                 Field::builder()
@@ -603,6 +688,8 @@ impl PkiBackendInner {
                     return Err(RvError::ErrPkiKeyBitsInvalid);
                 }
             }
+            // Only valid for signing; the CSR's key decides the type and size.
+            "any" => {}
             _ => {
                 return Err(RvError::ErrPkiKeyTypeInvalid);
             }
@@ -709,6 +796,18 @@ impl PkiBackendInner {
             .as_u64()
             .ok_or(RvError::ErrRequestFieldInvalid)?;
         let not_before_duration = Duration::from_secs(not_before_duration_u64);
+        let allowed_domains = match req.get_data("allowed_domains") {
+            Ok(value) => value
+                .as_comma_string_slice()
+                .ok_or(RvError::ErrRequestFieldInvalid)?,
+            Err(_) => Vec::new(),
+        };
+        let allowed_uri_sans = match req.get_data("allowed_uri_sans") {
+            Ok(value) => value
+                .as_comma_string_slice()
+                .ok_or(RvError::ErrRequestFieldInvalid)?,
+            Err(_) => Vec::new(),
+        };
 
         let role_entry = RoleEntry {
             ttl,
@@ -738,6 +837,8 @@ impl PkiBackendInner {
             not_before_duration,
             street_address,
             postal_code,
+            allowed_domains,
+            allowed_uri_sans,
             ..Default::default()
         };
 
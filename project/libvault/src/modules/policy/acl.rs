@@ -66,12 +66,82 @@ pub struct SentinelResults {
 pub struct ACL {
     pub exact_rules: Trie<String, Permissions>,
     pub prefix_rules: Trie<String, Permissions>,
-    pub segment_wildcard_paths: DashMap<String, Permissions>,
+    pub segment_wildcard_paths: DashMap<String, SegmentWildcardPath>,
     pub rgp_policies: Vec<Arc<Policy>>,
     #[default(false)]
     pub root: bool,
 }
 
+/// A policy path containing `+` segment wildcards, split into segments once when the ACL is built.
+///
+/// `+` stands for exactly one path segment. A trailing `*` makes the rule a prefix: its last segment
+/// only has to start the corresponding request segment, and any number of segments may follow.
+#[derive(Debug, Clone, Default)]
+pub struct SegmentWildcardPath {
+    pub segments: Vec<String>,
+    pub is_prefix: bool,
+    pub permissions: Permissions,
+}
+
+impl SegmentWildcardPath {
+    pub fn new(path: &str, permissions: Permissions) -> Self {
+        let (path, is_prefix) = match path.strip_suffix('*') {
+            Some(path) => (path, true),
+            None => (path, false),
+        };
+
+        Self {
+            segments: path.split('/').map(str::to_string).collect(),
+            is_prefix,
+            permissions,
+        }
+    }
+
+    /// Whether the rule applies to a request path split into `path_parts`.
+    pub fn matches(&self, path_parts: &[&str]) -> bool {
+        if path_parts.len() < self.segments.len()
+            || (!self.is_prefix && path_parts.len() != self.segments.len())
+        {
+            return false;
+        }
+
+        let last = self.segments.len() - 1;
+        self.segments
+            .iter()
+            .zip(path_parts)
+            .enumerate()
+            .all(|(i, (segment, part))| {
+                segment == "+"
+                    || segment == part
+                    || (self.is_prefix && i == last && part.starts_with(segment.as_str()))
+            })
+    }
+
+    /// Whether the rule could apply to some path below the mount split into `mount_parts`.
+    fn matches_below(&self, mount_parts: &[&str]) -> bool {
+        self.segments.len() > mount_parts.len()
+            && self
+                .segments
+                .iter()
+                .zip(mount_parts)
+                .all(|(segment, part)| segment == "+" || segment == part)
+    }
+
+    fn descr(&self) -> WcPathDescr {
+        let wc_path = self.segments.join("/");
+        WcPathDescr {
+            first_wc_or_glob: wc_path.find('+').map(|i| i as isize).unwrap_or(-1),
+            is_prefix: self.is_prefix,
+            wildcards: self.segments.iter().filter(|s| *s == "+").count(),
+            perms: Some(self.permissions.clone()),
+            wc_path,
+        }
+    }
+}
+
+/// Ordering key used to pick the most specific of several matching non-exact rules: the later the
+/// first wildcard, the more specific; then non-prefix over prefix, fewer `+` over more, and the
+/// longer path over the shorter one.
 #[derive(Debug, Clone, Default)]
 struct WcPathDescr {
     first_wc_or_glob: isize,
@@ -177,8 +247,8 @@ impl ACL {
     /// * `Result<Option<Permissions>, RvError>` - Returns the permissions if found, otherwise `None`.
     pub fn get_permissions(&self, pr: &PolicyPathRules) -> Result<Option<Permissions>, RvError> {
         if pr.has_segment_wildcards {
-            if let Some(existing_perms) = self.segment_wildcard_paths.get(&pr.path) {
-                return Ok(Some(existing_perms.value().clone()));
+            if let Some(existing) = self.segment_wildcard_paths.get(&pr.path) {
+                return Ok(Some(existing.value().permissions.clone()));
             }
         } else {
             let tree = if pr.is_prefix {
@@ -214,7 +284,8 @@ impl ACL {
         perm: Permissions,
     ) -> Result<(), RvError> {
         if pr.has_segment_wildcards {
-            self.segment_wildcard_paths.insert(pr.path.clone(), perm);
+            self.segment_wildcard_paths
+                .insert(pr.path.clone(), SegmentWildcardPath::new(&pr.path, perm));
         } else {
             let tree = if pr.is_prefix {
                 &mut self.prefix_rules
@@ -321,95 +392,29 @@ impl ACL {
             return None;
         }
 
-        if self.segment_wildcard_paths.is_empty() {
-            return None;
-        }
-
-        let path_parts: Vec<&str> = path.split('/').collect();
-
-        for item in self.segment_wildcard_paths.iter() {
-            let (full_wc_path, permissions) = (item.key(), item.value());
-
-            if full_wc_path.is_empty() {
-                continue;
-            }
-
-            let mut pd = WcPathDescr {
-                first_wc_or_glob: full_wc_path.find('+').map(|i| i as isize).unwrap_or(-1),
-                ..Default::default()
-            };
-
-            let mut curr_wc_path = full_wc_path.as_str();
-            if curr_wc_path.ends_with('*') {
-                pd.is_prefix = true;
-                curr_wc_path = &curr_wc_path[..curr_wc_path.len() - 1];
-            }
-            pd.wc_path = curr_wc_path.to_string();
-
-            let split_curr_wc_path: Vec<&str> = curr_wc_path.split('/').collect();
-
-            if !bare_mount && path_parts.len() < split_curr_wc_path.len() {
-                continue;
-            }
-
-            if !bare_mount && !pd.is_prefix && split_curr_wc_path.len() != path_parts.len() {
-                continue;
-            }
-
-            let mut skip = false;
-            let mut segments = Vec::with_capacity(split_curr_wc_path.len());
-
-            for (i, acl_part) in split_curr_wc_path.iter().enumerate() {
-                match *acl_part {
-                    "+" => {
-                        pd.wildcards += 1;
-                        segments.push(path_parts[i]);
-                    }
-                    _ if *acl_part == path_parts[i] => {
-                        segments.push(path_parts[i]);
-                    }
-                    _ if pd.is_prefix
-                        && i == split_curr_wc_path.len() - 1
-                        && path_parts[i].starts_with(acl_part) =>
-                    {
-                        segments.extend_from_slice(&path_parts[i..]);
-                    }
-                    _ if !bare_mount => {
-                        skip = true;
-                        break;
-                    }
-                    _ => {}
-                }
-
-                if bare_mount && i == path_parts.len() - 2 {
-                    let joined_path = segments.join("/") + "/";
-                    if joined_path.starts_with(path)
+        if bare_mount {
+            let mount_parts: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+            return self
+                .segment_wildcard_paths
+                .iter()
+                .find(|item| {
+                    let permissions = &item.value().permissions;
+                    item.value().matches_below(&mount_parts)
                         && permissions.capabilities_bitmap & Capability::Deny.to_bits() == 0
                         && permissions.capabilities_bitmap > 0
-                    {
-                        return Some(permissions.clone());
-                    }
-                    skip = true;
-                    break;
-                }
-            }
-
-            if !skip {
-                pd.perms = Some(permissions.clone());
-                wc_path_descrs.push(pd);
-            }
-        }
-
-        if bare_mount || wc_path_descrs.is_empty() {
-            return None;
+                })
+                .map(|item| item.value().permissions.clone());
         }
 
-        wc_path_descrs.sort();
-
-        wc_path_descrs
-            .into_iter()
-            .next_back()
-            .and_then(|pd| pd.perms)
+        let path_parts: Vec<&str> = path.split('/').collect();
+        wc_path_descrs.extend(
+            self.segment_wildcard_paths
+                .iter()
+                .filter(|item| item.value().matches(&path_parts))
+                .map(|item| item.value().descr()),
+        );
+
+        wc_path_descrs.into_iter().max().and_then(|pd| pd.perms)
     }
 
     pub fn capabilities<S: Into<String>>(&self, path: S) -> Vec<String> {
@@ -480,3 +485,129 @@ fn check_path_capability(rules: &Trie<String, Permissions>, path: &str) -> bool
                     > 0
             })
 }
+
+#[cfg(test)]
+mod test {
+    use std::{str::FromStr, sync::Arc};
+
+    use super::*;
+
+    fn new_acl(raw: &str) -> ACL {
+        let mut policy = Policy::from_str(raw).unwrap();
+        policy.name = "test".into();
+        ACL::new(&[Arc::new(policy)]).unwrap()
+    }
+
+    #[test]
+    fn test_acl_plus_matches_single_segment() {
+        let acl = new_acl(
+            r#"
+            path "secret/+/config" {
+                capabilities = ["read"]
+            }
+            "#,
+        );
+
+        assert_eq!(acl.capabilities("secret/app1/config"), vec!["read"]);
+        assert_eq!(acl.capabilities("secret/app2/config"), vec!["read"]);
+        assert_eq!(acl.capabilities("secret/app1/sub/config"), vec!["deny"]);
+        assert_eq!(acl.capabilities("secret/config"), vec!["deny"]);
+        assert_eq!(acl.capabilities("secret/app1/config/x"), vec!["deny"]);
+    }
+
+    #[test]
+    fn test_acl_glob_matches_any_suffix() {
+        let acl = new_acl(
+            r#"
+            path "secret/*" {
+                capabilities = ["read"]
+            }
+            path "kv/+/db-*" {
+                capabilities = ["update"]
+            }
+            "#,
+        );
+
+        assert_eq!(acl.capabilities("secret/a"), vec!["read"]);
+        assert_eq!(acl.capabilities("secret/a/b/c"), vec!["read"]);
+        assert_eq!(acl.capabilities("kv/app1/db-main"), vec!["update"]);
+        assert_eq!(acl.capabilities("kv/app1/db-main/creds"), vec!["update"]);
+        assert_eq!(acl.capabilities("kv/app1/cache"), vec!["deny"]);
+        assert_eq!(acl.capabilities("kv/db-main"), vec!["deny"]);
+        assert_eq!(acl.capabilities("other/a"), vec!["deny"]);
+    }
+
+    #[test]
+    fn test_acl_overlapping_wildcards_most_specific_wins() {
+        let acl = new_acl(
+            r#"
+            path "secret/*" {
+                capabilities = ["read"]
+            }
+            path "secret/+/*" {
+                capabilities = ["list"]
+            }
+            path "secret/+/config" {
+                capabilities = ["update"]
+            }
+            path "secret/+/+" {
+                capabilities = ["delete"]
+            }
+            path "secret/app1/+" {
+                capabilities = ["create"]
+            }
+            "#,
+        );
+
+        // A `+` rule beats a `*` prefix whose glob starts at the same position.
+        assert_eq!(acl.capabilities("secret/app2/config"), vec!["update"]);
+        // Fewer `+` segments beat more.
+        assert_eq!(acl.capabilities("secret/app2/other"), vec!["delete"]);
+        // A later first wildcard beats an earlier one.
+        assert_eq!(acl.capabilities("secret/app1/config"), vec!["create"]);
+        // Between two prefixes starting their wildcards at the same position, fewer `+` wins.
+        assert_eq!(acl.capabilities("secret/app2/a/b"), vec!["read"]);
+        assert_eq!(acl.capabilities("secret/top"), vec!["read"]);
+    }
+
+    #[test]
+    fn test_acl_literal_path_beats_wildcards() {
+        let acl = new_acl(
+            r#"
+            path "secret/*" {
+                capabilities = ["read", "update"]
+            }
+            path "secret/+/config" {
+                capabilities = ["read", "update"]
+            }
+            path "secret/app1/config" {
+                capabilities = ["deny"]
+            }
+            path "secret/app2/config" {
+                capabilities = ["list"]
+            }
+            "#,
+        );
+
+        assert_eq!(acl.capabilities("secret/app1/config"), vec!["deny"]);
+        assert_eq!(acl.capabilities("secret/app2/config"), vec!["list"]);
+        assert_eq!(
+            acl.capabilities("secret/app3/config"),
+            vec!["read", "update"]
+        );
+    }
+
+    #[test]
+    fn test_acl_mount_access_through_wildcards() {
+        let acl = new_acl(
+            r#"
+            path "secret/+/config" {
+                capabilities = ["read"]
+            }
+            "#,
+        );
+
+        assert!(acl.has_mount_access("secret/"));
+        assert!(!acl.has_mount_access("other/"));
+    }
+}
@@ -4,7 +4,7 @@ use arc_swap::ArcSwap;
 use async_trait::async_trait;
 use base64::{Engine as _, engine::general_purpose::STANDARD};
 use better_default::Default;
-use serde_json::{Map, Value};
+use serde_json::{Map, Value, json};
 
 use super::Module;
 use crate::{
@@ -12,6 +12,7 @@ use crate::{
     errors::RvError,
     handler::AuthHandler,
     logical::{Backend, Request, Response},
+    namespace::scope_path,
     rv_error_response_status,
 };
 
@@ -24,6 +25,9 @@ pub use policy_store::PolicyStore;
 
 pub mod acl;
 
+pub mod template;
+pub use template::TemplateContext;
+
 #[derive(Default)]
 pub struct PolicyModule {
     #[default("policy".into())]
@@ -45,6 +49,35 @@ impl PolicyModule {
         self.policy_store.load().load_default_acl_policy().await
     }
 
+    /// Effective capabilities of `policies` on each of `paths`, resolved through the same ACL that
+    /// authorizes requests. Paths no rule grants anything on come back as `["deny"]`.
+    /// `namespace` is the one the token was issued in, and `paths` are as seen from inside it.
+    pub async fn capabilities(
+        &self,
+        namespace: &str,
+        policies: &[String],
+        paths: &[String],
+    ) -> Result<Map<String, Value>, RvError> {
+        let acl = self
+            .policy_store
+            .load()
+            .new_namespace_acl(namespace, policies)
+            .await?;
+
+        let mut data = Map::new();
+        for path in paths.iter() {
+            let scoped = scope_path(namespace, path);
+            data.insert(path.clone(), json!(acl.capabilities(scoped.as_str())));
+        }
+
+        // Single-path callers expect the result under a fixed key as well.
+        if paths.len() == 1 {
+            data.insert("capabilities".into(), data[&paths[0]].clone());
+        }
+
+        Ok(data)
+    }
+
     pub async fn handle_policy_list(
         &self,
         _backend: &dyn Backend,
@@ -53,12 +86,13 @@ impl PolicyModule {
         let mut policies = self
             .policy_store
             .load()
-            .list_policy(PolicyType::Acl)
+            .list_namespace_policy(&req.namespace, PolicyType::Acl)
             .await?;
 
-        // TODO: After the "namespace" feature is added here, it is necessary to determine whether it is the root
-        // namespace before the root can be added.
-        policies.push("root".into());
+        // The root policy only exists in the root namespace.
+        if req.namespace.is_empty() {
+            policies.push("root".into());
+        }
 
         let mut resp = Response::list_response(&policies);
 
@@ -78,7 +112,7 @@ impl PolicyModule {
         if let Some(policy) = self
             .policy_store
             .load()
-            .get_policy(&name, PolicyType::Acl)
+            .get_policy(&format!("{}{name}", req.namespace), PolicyType::Acl)
             .await?
         {
             let mut resp_data = Map::new();
@@ -122,7 +156,7 @@ impl PolicyModule {
         };
 
         let mut policy = Policy::from_str(&policy_raw)?;
-        policy.name = name;
+        policy.name = format!("{}{name}", req.namespace);
 
         if policy.policy_type == PolicyType::Egp || policy.policy_type == PolicyType::Rgp {
             policy.input_sentinel_policy_data(req)?;
@@ -141,7 +175,7 @@ impl PolicyModule {
         let name = req.get_data_as_str("name")?;
         self.policy_store
             .load()
-            .delete_policy(&name, PolicyType::Acl)
+            .delete_policy(&format!("{}{name}", req.namespace), PolicyType::Acl)
             .await?;
         Ok(None)
     }
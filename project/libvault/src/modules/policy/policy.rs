@@ -25,10 +25,14 @@ use serde_json::Value;
 use strum::IntoEnumIterator;
 use strum_macros::{Display as StrumDisplay, EnumIter, EnumString};
 
-use super::acl::ACLResults;
+use super::{
+    acl::ACLResults,
+    template::{TemplateContext, is_templated},
+};
 use crate::{
     errors::RvError,
     logical::{Operation, Request, Response, auth::PolicyInfo},
+    namespace::scope_path,
     rv_error_string,
     utils::{
         deserialize_duration,
@@ -61,6 +65,8 @@ pub struct Policy {
     #[default(PolicyType::Acl)]
     pub policy_type: PolicyType,
     pub templated: bool,
+    /// Path of the namespace the policy was written in. Its rules are scoped to that namespace.
+    pub namespace: String,
 }
 
 /// Describes rules associated with specific paths in a policy.
@@ -205,8 +211,9 @@ impl FromStr for Policy {
         let mut policy = Policy::default();
         policy.raw = s.to_string();
         policy.name.clone_from(&policy_config.name);
+        policy.templated = policy_config.path.keys().any(|path| is_templated(path));
 
-        policy.init(&policy_config)?;
+        policy.init(&policy_config, None)?;
 
         Ok(policy)
     }
@@ -223,6 +230,41 @@ impl Policy {
         Ok(())
     }
 
+    /// Render a templated policy for one request. Rules whose parameters `template` can't resolve
+    /// are left out.
+    pub fn render(&self, template: &TemplateContext) -> Result<Policy, RvError> {
+        let policy_config = Policy::parse(&self.raw)?;
+
+        let mut policy = Policy {
+            name: self.name.clone(),
+            raw: self.raw.clone(),
+            policy_type: self.policy_type,
+            templated: self.templated,
+            namespace: self.namespace.clone(),
+            ..Policy::default()
+        };
+        policy.init(&policy_config, Some(template))?;
+
+        Ok(policy)
+    }
+
+    /// Re-read the policy as written in `namespace`, so each rule only covers paths inside it.
+    pub fn scoped(&self, namespace: &str) -> Result<Policy, RvError> {
+        let policy_config = Policy::parse(&self.raw)?;
+
+        let mut policy = Policy {
+            name: self.name.clone(),
+            raw: self.raw.clone(),
+            policy_type: self.policy_type,
+            templated: self.templated,
+            namespace: namespace.to_string(),
+            ..Policy::default()
+        };
+        policy.init(&policy_config, None)?;
+
+        Ok(policy)
+    }
+
     fn parse(s: &str) -> Result<PolicyConfig, RvError> {
         let body: Body = hcl::from_str(s)?;
 
@@ -311,10 +353,24 @@ impl Policy {
         Ok(policy_config)
     }
 
-    fn init(&mut self, policy_config: &PolicyConfig) -> Result<(), RvError> {
+    fn init(
+        &mut self,
+        policy_config: &PolicyConfig,
+        template: Option<&TemplateContext>,
+    ) -> Result<(), RvError> {
         for (path, pc) in policy_config.path.iter() {
+            // Without a request to render against, a templated rule grants nothing.
+            let path = if is_templated(path) {
+                match template.and_then(|template| template.render(path)) {
+                    Some(path) => path,
+                    None => continue,
+                }
+            } else {
+                path.clone()
+            };
+
             let mut rules = PolicyPathRules::default();
-            rules.path = ensure_no_leading_slash(path);
+            rules.path = scope_path(&self.namespace, &ensure_no_leading_slash(&path));
             rules.capabilities.clone_from(&pc.capabilities);
             rules.min_wrapping_ttl = pc.min_wrapping_ttl;
             rules.max_wrapping_ttl = pc.max_wrapping_ttl;
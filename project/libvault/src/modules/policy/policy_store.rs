@@ -33,12 +33,15 @@ use super::{
     Policy, PolicyType,
     acl::{ACL, ACLResults},
     policy::SentinelPolicy,
+    template::TemplateContext,
 };
 use crate::{
     core::Core,
     errors::RvError,
     handler::AuthHandler,
-    logical::{Operation, Request, auth::PolicyResults},
+    logical::{Auth, Operation, Request, auth::PolicyResults},
+    modules::identity::{Entity, IdentityModule},
+    namespace::NamespaceStore,
     router::Router,
     rv_error_response_status, rv_error_string,
     storage::{Storage, StorageEntry, barrier_view::BarrierView},
@@ -206,6 +209,9 @@ pub struct PolicyStore {
     pub egp_lru: Option<Cache<String, Arc<Policy>>>,
     // Stores whether a token policy is ACL or RGP
     pub policy_type_map: DashMap<String, PolicyType>,
+    pub namespaces: Arc<NamespaceStore>,
+    // Used to reach the identity store, which is set up after the policy store.
+    pub core: Weak<Core>,
     pub self_ptr: Weak<PolicyStore>,
 }
 
@@ -237,6 +243,8 @@ impl PolicyStore {
             acl_view: Some(Arc::new(acl_view)),
             rgp_view: Some(Arc::new(rgp_view)),
             egp_view: Some(Arc::new(egp_view)),
+            namespaces: core.namespaces.clone(),
+            core: core.self_ptr.clone(),
             self_ptr: Weak::default(),
             ..Default::default()
         };
@@ -292,11 +300,11 @@ impl PolicyStore {
             return Err(rv_error_string!(format!("cannot update {} policy", name)));
         }
 
-        if name != policy.name {
-            let mut p = policy.clone();
-            p.name = name;
-            return self.set_policy_internal(Arc::new(p)).await;
-        }
+        let mut policy = match self.namespaces.owner(&name) {
+            Some(ns) => policy.scoped(&ns.path)?,
+            None => policy,
+        };
+        policy.name = name;
 
         self.set_policy_internal(Arc::new(policy)).await
     }
@@ -373,7 +381,10 @@ impl PolicyStore {
 
         let mut policy = match policy_type {
             PolicyType::Acl => {
-                let p = Policy::from_str(&policy_entry.raw)?;
+                let mut p = Policy::from_str(&policy_entry.raw)?;
+                if let Some(ns) = self.namespaces.owner(&name) {
+                    p = p.scoped(&ns.path)?;
+                }
                 self.policy_type_map.insert(index.clone(), PolicyType::Acl);
                 p
             }
@@ -406,6 +417,45 @@ impl PolicyStore {
         Ok(Some(p))
     }
 
+    /// List the policies of a specific type written in `namespace`, without the namespace prefix.
+    pub async fn list_namespace_policy(
+        &self,
+        namespace: &str,
+        policy_type: PolicyType,
+    ) -> Result<Vec<String>, RvError> {
+        let keys = self.list_policy(policy_type).await?;
+        if namespace.is_empty() {
+            return Ok(keys
+                .into_iter()
+                .filter(|key| self.namespaces.owner(key).is_none())
+                .collect());
+        }
+
+        Ok(keys
+            .iter()
+            .filter_map(|key| key.strip_prefix(namespace))
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Delete every policy written in `namespace`.
+    pub async fn delete_namespace_policies(&self, namespace: &str) -> Result<(), RvError> {
+        for policy_type in [PolicyType::Acl, PolicyType::Rgp, PolicyType::Egp] {
+            let view = self.get_barrier_view(policy_type)?;
+            for name in self.list_namespace_policy(namespace, policy_type).await? {
+                let name = format!("{namespace}{name}");
+                view.delete(&name).await?;
+
+                let index = self.cache_key(&name);
+                self.remove_token_policy_cache(&index)?;
+                self.remove_egp_cache(&index)?;
+                self.policy_type_map.remove(&index);
+            }
+        }
+
+        Ok(())
+    }
+
     /// List policies of a specific type in the policy store.
     /// This function retrieves the keys from the appropriate view and filters out non-assignable policies for ACLs.
     pub async fn list_policy(&self, policy_type: PolicyType) -> Result<Vec<String>, RvError> {
@@ -503,14 +553,74 @@ impl PolicyStore {
         &self,
         policy_names: &[String],
         additional_policies: Option<Vec<Arc<Policy>>>,
+    ) -> Result<ACL, RvError> {
+        self.new_templated_acl("", policy_names, additional_policies, None)
+            .await
+    }
+
+    /// Create an ACL from policy names as seen from inside `namespace`.
+    pub async fn new_namespace_acl(
+        &self,
+        namespace: &str,
+        policy_names: &[String],
+    ) -> Result<ACL, RvError> {
+        self.new_templated_acl(namespace, policy_names, None, None)
+            .await
+    }
+
+    /// Create the ACL that authorizes requests made with `auth`, rendering templated policies
+    /// against the token's entity. Tokens without an entity get no templated rules at all.
+    pub async fn new_acl_for_auth(&self, auth: &Auth) -> Result<ACL, RvError> {
+        let template = self
+            .lookup_entity(&auth.entity_id)
+            .await?
+            .map(|entity| TemplateContext::from_entity(&entity));
+        self.new_templated_acl(&auth.namespace, &auth.policies, None, template.as_ref())
+            .await
+    }
+
+    /// The identity store's entity `entity_id`, if there is one.
+    async fn lookup_entity(&self, entity_id: &str) -> Result<Option<Entity>, RvError> {
+        if entity_id.is_empty() {
+            return Ok(None);
+        }
+        let Some(core) = self.core.upgrade() else {
+            return Ok(None);
+        };
+        let Some(identity_module) = core.module_manager.get_module::<IdentityModule>("identity")
+        else {
+            return Ok(None);
+        };
+
+        identity_module.backend.get_entity(entity_id).await
+    }
+
+    async fn new_templated_acl(
+        &self,
+        namespace: &str,
+        policy_names: &[String],
+        additional_policies: Option<Vec<Arc<Policy>>>,
+        template: Option<&TemplateContext>,
     ) -> Result<ACL, RvError> {
         let mut all_policies: Vec<Arc<Policy>> = vec![];
         for policy_name in policy_names.iter() {
+            // Policy names resolve inside the token's namespace; only `root` is global.
+            let policy_name = if namespace.is_empty() || policy_name == "root" {
+                policy_name.clone()
+            } else {
+                format!("{namespace}{policy_name}")
+            };
+
             if let Some(policy) = self
                 .get_policy(policy_name.as_str(), PolicyType::Token)
                 .await?
             {
-                all_policies.push(policy);
+                match template {
+                    Some(template) if policy.templated => {
+                        all_policies.push(Arc::new(policy.render(template)?));
+                    }
+                    _ => all_policies.push(policy),
+                }
             }
         }
 
@@ -701,7 +811,7 @@ impl AuthHandler for PolicyStore {
                 return Ok(());
             }
 
-            let acl = self.new_acl(&auth.policies, None).await?;
+            let acl = self.new_acl_for_auth(auth).await?;
             acl_result = acl.allow_operation(req, false)?;
         }
 
@@ -0,0 +1,71 @@
+//! Templated policy paths.
+//!
+//! A path rule such as `secret/data/{{identity.entity.id}}/*` is not matched as written: it is
+//! rendered per request against the requesting token's entity, as stored in the identity store.
+//! Supported parameters are `identity.entity.id`, `identity.entity.name` and
+//! `identity.entity.metadata.<key>`. A rule whose parameters can't all be resolved, including for
+//! tokens without an entity, is dropped, so it never matches anything.
+
+use std::collections::HashMap;
+
+use crate::modules::identity::Entity;
+
+/// Values available to templated path rules for one request.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateContext {
+    pub entity_id: String,
+    pub entity_name: String,
+    pub entity_metadata: HashMap<String, String>,
+}
+
+impl TemplateContext {
+    /// Describe `entity`. Only the identity store's record is trusted: the display name and
+    /// metadata on a token are chosen by its auth method, or by whoever created the token.
+    pub fn from_entity(entity: &Entity) -> Self {
+        Self {
+            entity_id: entity.id.clone(),
+            entity_name: entity.name.clone(),
+            entity_metadata: entity.metadata.clone(),
+        }
+    }
+
+    fn lookup(&self, key: &str) -> Option<&str> {
+        let value = match key {
+            "identity.entity.id" => self.entity_id.as_str(),
+            "identity.entity.name" => self.entity_name.as_str(),
+            _ => self
+                .entity_metadata
+                .get(key.strip_prefix("identity.entity.metadata.")?)?
+                .as_str(),
+        };
+
+        // An empty value would collapse the path, and a glob character would widen it.
+        if value.is_empty() || value.contains(['*', '+']) {
+            return None;
+        }
+
+        Some(value)
+    }
+
+    /// Substitute every `{{ ... }}` parameter in `template`, or `None` if any of them is unknown or
+    /// has no value for this request.
+    pub fn render(&self, template: &str) -> Option<String> {
+        let mut rendered = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find("{{") {
+            let end = rest[start..].find("}}")? + start;
+            rendered.push_str(&rest[..start]);
+            rendered.push_str(self.lookup(rest[start + 2..end].trim())?);
+            rest = &rest[end + 2..];
+        }
+
+        rendered.push_str(rest);
+        Some(rendered)
+    }
+}
+
+/// Whether `path` contains template parameters.
+pub fn is_templated(path: &str) -> bool {
+    path.contains("{{")
+}
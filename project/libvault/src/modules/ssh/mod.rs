@@ -0,0 +1,99 @@
+//! The `ssh` secrets engine signs OpenSSH user and host certificates.
+//!
+//! `config/ca` holds the signing key, either generated or imported. A role at `roles/<name>`
+//! bounds what may be signed against it: the principals, the extensions and the TTL.
+//! `sign/<role>` takes an OpenSSH public key and returns a certificate in the standard
+//! `*-cert-v01@openssh.com` format, usable as is by `ssh -i`. Each certificate gets a random serial
+//! and a key id naming the requester and that serial, which sshd logs on every login.
+
+use std::{any::Any, sync::Arc};
+
+use async_trait::async_trait;
+use derive_more::Deref;
+
+use crate::{
+    core::Core,
+    errors::RvError,
+    logical::{Backend, LogicalBackend},
+    modules::Module,
+};
+
+pub mod path_config_ca;
+pub mod path_roles;
+pub mod path_sign;
+
+pub use path_config_ca::SshCaConfig;
+pub use path_roles::SshRole;
+
+static SSH_BACKEND_HELP: &str = r#"
+The SSH backend signs SSH public keys with a CA key, producing OpenSSH
+certificates for users or hosts. Roles restrict the principals, extensions
+and lifetime of the certificates signed through them.
+"#;
+
+pub struct SshModule {
+    pub name: String,
+    pub backend: Arc<SshBackend>,
+}
+
+pub struct SshBackendInner {
+    pub core: Arc<Core>,
+}
+
+#[derive(Deref)]
+pub struct SshBackend {
+    #[deref]
+    pub inner: Arc<SshBackendInner>,
+}
+
+impl SshBackend {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            inner: Arc::new(SshBackendInner { core }),
+        }
+    }
+
+    pub fn new_backend(&self) -> LogicalBackend {
+        LogicalBackend::builder()
+            .help(SSH_BACKEND_HELP)
+            .path(self.config_ca_path())
+            .path(self.roles_path())
+            .path(self.roles_list_path())
+            .path(self.sign_path())
+            .build()
+    }
+}
+
+impl SshModule {
+    pub fn new(core: Arc<Core>) -> Self {
+        Self {
+            name: "ssh".to_string(),
+            backend: Arc::new(SshBackend::new(core)),
+        }
+    }
+}
+
+#[async_trait]
+impl Module for SshModule {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn as_any_arc(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn setup(&self, core: &Core) -> Result<(), RvError> {
+        let ssh = self.backend.clone();
+        let ssh_backend_new_func = move |_c: Arc<Core>| -> Result<Arc<dyn Backend>, RvError> {
+            let mut ssh_backend = ssh.new_backend();
+            ssh_backend.init()?;
+            Ok(Arc::new(ssh_backend))
+        };
+        core.add_logical_backend("ssh", Arc::new(ssh_backend_new_func))
+    }
+
+    fn cleanup(&self, core: &Core) -> Result<(), RvError> {
+        core.delete_logical_backend("ssh")
+    }
+}
@@ -0,0 +1,170 @@
+use openssl::{nid::Nid, pkey::PKey};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{SshBackend, SshBackendInner};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response},
+    modules::pki::ssh_util,
+    rv_error_response,
+    storage::StorageEntry,
+};
+
+const SSH_CA_CONFIG_KEY: &str = "config/ca";
+
+/// The CA key certificates are signed with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshCaConfig {
+    pub private_key: String,
+    pub public_key: String,
+}
+
+impl SshBackend {
+    pub fn config_ca_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+        let backend_delete = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"config/ca$")
+            .field(
+                "generate_signing_key",
+                Field::builder()
+                    .field_type(FieldType::Bool)
+                    .default_value(true)
+                    .description("Generate the signing key rather than import one. Ignored if private_key is given."),
+            )
+            .field(
+                "key_type",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value("ed25519")
+                    .description("Type of the generated signing key: ed25519, rsa or ec."),
+            )
+            .field(
+                "key_bits",
+                Field::builder()
+                    .field_type(FieldType::Int)
+                    .default_value(0)
+                    .description("Size of the generated signing key. 0 picks the default for the key type."),
+            )
+            .field(
+                "private_key",
+                Field::builder()
+                    .field_type(FieldType::SecretStr)
+                    .default_value("")
+                    .description("PEM encoded private key to import as the signing key."),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_config_ca(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.write_config_ca(backend, req).await })
+                }
+            })
+            .operation(Operation::Delete, {
+                let handler = backend_delete.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.delete_config_ca(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+This path sets the CA key certificates are signed with, either generated or
+imported. Reading it returns the CA public key, to be added to sshd's
+TrustedUserCAKeys or to known_hosts as a @cert-authority.
+                "#,
+            )
+            .build()
+    }
+}
+
+/// The curve of an EC key, which the SSH key type depends on.
+pub fn key_nid(pkey: &PKey<openssl::pkey::Private>) -> Result<Option<Nid>, RvError> {
+    if pkey.id() == openssl::pkey::Id::EC {
+        return Ok(pkey.ec_key()?.group().curve_name());
+    }
+    Ok(None)
+}
+
+impl SshBackendInner {
+    pub async fn get_ca_config(&self, req: &Request) -> Result<Option<SshCaConfig>, RvError> {
+        match req.storage_get(SSH_CA_CONFIG_KEY).await? {
+            Some(entry) => Ok(Some(serde_json::from_slice(&entry.value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn read_config_ca(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let Some(config) = self.get_ca_config(req).await? else {
+            return Ok(None);
+        };
+
+        let data = json!({ "public_key": config.public_key })
+            .as_object()
+            .cloned();
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn write_config_ca(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let private_key = req.get_data_or_default("private_key")?;
+        let private_key = private_key.as_str().unwrap_or_default().trim();
+        let generate = req
+            .get_data_or_default("generate_signing_key")?
+            .as_bool()
+            .unwrap_or(true);
+
+        let (pkey, nid) = if !private_key.is_empty() {
+            let pkey = PKey::private_key_from_pem(private_key.as_bytes())?;
+            let nid = key_nid(&pkey)?;
+            (pkey, nid)
+        } else if generate {
+            let key_type = req.get_data_or_default("key_type")?;
+            let key_bits = req.get_data_or_default("key_bits")?.as_u64().unwrap_or(0);
+            ssh_util::generate_ssh_keypair(key_type.as_str().unwrap_or("ed25519"), key_bits as u32)?
+        } else {
+            return Err(rv_error_response!(
+                "private_key must be given if generate_signing_key is false"
+            ));
+        };
+
+        let config = SshCaConfig {
+            private_key: String::from_utf8_lossy(&pkey.private_key_to_pem_pkcs8()?).to_string(),
+            public_key: ssh_util::format_openssh_pubkey(&pkey, nid)?,
+        };
+
+        let entry = StorageEntry::new(SSH_CA_CONFIG_KEY, &config)?;
+        req.storage_put(&entry).await?;
+
+        let data = json!({ "public_key": config.public_key })
+            .as_object()
+            .cloned();
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn delete_config_ca(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        req.storage_delete(SSH_CA_CONFIG_KEY).await?;
+        Ok(None)
+    }
+}
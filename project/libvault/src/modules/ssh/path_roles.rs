@@ -0,0 +1,305 @@
+use std::{collections::HashMap, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{SshBackend, SshBackendInner};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response, field::FieldTrait},
+    rv_error_response,
+    storage::StorageEntry,
+    utils::{deserialize_duration, serialize_duration},
+};
+
+const DEFAULT_TTL: Duration = Duration::from_secs(3600);
+
+/// Bounds on the certificates signed against a role.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshRole {
+    /// Only `ca` is supported: keys are signed rather than provisioned.
+    pub key_type: String,
+    /// `user` or `host`.
+    pub cert_type: String,
+    /// Principals a certificate may be signed for. `*` allows any.
+    #[serde(default)]
+    pub allowed_users: Vec<String>,
+    /// Principal used when a sign request doesn't name any.
+    #[serde(default)]
+    pub default_user: String,
+    /// Extensions a sign request may ask for. `*` allows any.
+    #[serde(default)]
+    pub allowed_extensions: Vec<String>,
+    /// Extensions used when a sign request doesn't ask for any.
+    #[serde(default)]
+    pub default_extensions: HashMap<String, String>,
+    #[serde(
+        default,
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub ttl: Duration,
+    #[serde(
+        default,
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub max_ttl: Duration,
+}
+
+impl Default for SshRole {
+    fn default() -> Self {
+        Self {
+            key_type: "ca".to_string(),
+            cert_type: "user".to_string(),
+            allowed_users: Vec::new(),
+            default_user: String::new(),
+            allowed_extensions: Vec::new(),
+            default_extensions: HashMap::new(),
+            ttl: DEFAULT_TTL,
+            max_ttl: Duration::ZERO,
+        }
+    }
+}
+
+impl SshRole {
+    pub fn is_user_allowed(&self, user: &str) -> bool {
+        self.allowed_users.iter().any(|u| u == "*" || u == user)
+    }
+
+    pub fn is_extension_allowed(&self, extension: &str) -> bool {
+        self.allowed_extensions
+            .iter()
+            .any(|e| e == "*" || e == extension)
+    }
+
+    /// The longest TTL a certificate may be signed for.
+    pub fn ttl_limit(&self) -> Duration {
+        if self.max_ttl.is_zero() {
+            self.ttl
+        } else {
+            self.max_ttl
+        }
+    }
+}
+
+impl SshBackend {
+    pub fn roles_path(&self) -> Path {
+        let backend_read = self.inner.clone();
+        let backend_write = self.inner.clone();
+        let backend_delete = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"roles/(?P<name>\w(([\w.-]+)?\w)?)$")
+            .field(
+                "name",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("Name of the role."),
+            )
+            .field(
+                "key_type",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value("ca")
+                    .description("Type of credentials the role issues. Only 'ca' is supported."),
+            )
+            .field(
+                "cert_type",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .default_value("user")
+                    .description("Type of certificates signed against the role: 'user' or 'host'."),
+            )
+            .field(
+                "allowed_users",
+                Field::builder()
+                    .field_type(FieldType::CommaStringSlice)
+                    .description("Principals certificates may be signed for. '*' allows any."),
+            )
+            .field(
+                "default_user",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .description("Principal used when the sign request names none."),
+            )
+            .field(
+                "allowed_extensions",
+                Field::builder()
+                    .field_type(FieldType::CommaStringSlice)
+                    .description("Extensions a sign request may ask for. '*' allows any."),
+            )
+            .field(
+                "default_extensions",
+                Field::builder()
+                    .field_type(FieldType::Map)
+                    .description("Extensions used when the sign request asks for none."),
+            )
+            .field(
+                "ttl",
+                Field::builder()
+                    .field_type(FieldType::DurationSecond)
+                    .description("Default lifetime of the signed certificates."),
+            )
+            .field(
+                "max_ttl",
+                Field::builder()
+                    .field_type(FieldType::DurationSecond)
+                    .description("Maximum lifetime a sign request may ask for. Defaults to ttl."),
+            )
+            .operation(Operation::Read, {
+                let handler = backend_read.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.read_role(backend, req).await })
+                }
+            })
+            .operation(Operation::Write, {
+                let handler = backend_write.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.write_role(backend, req).await })
+                }
+            })
+            .operation(Operation::Delete, {
+                let handler = backend_delete.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.delete_role(backend, req).await })
+                }
+            })
+            .help("This path manages the roles certificates are signed against.")
+            .build()
+    }
+
+    pub fn roles_list_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"roles/?$")
+            .operation(Operation::List, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.list_roles(backend, req).await })
+                }
+            })
+            .help("Lists all the roles registered with the backend.")
+            .build()
+    }
+}
+
+impl SshBackendInner {
+    pub async fn get_role(&self, req: &Request, name: &str) -> Result<Option<SshRole>, RvError> {
+        match req.storage_get(&format!("role/{name}")).await? {
+            Some(entry) => Ok(Some(serde_json::from_slice(&entry.value)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn read_role(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        let Some(role) = self.get_role(req, &name).await? else {
+            return Ok(None);
+        };
+
+        let data = json!({
+            "key_type": role.key_type,
+            "cert_type": role.cert_type,
+            "allowed_users": role.allowed_users,
+            "default_user": role.default_user,
+            "allowed_extensions": role.allowed_extensions,
+            "default_extensions": role.default_extensions,
+            "ttl": role.ttl.as_secs(),
+            "max_ttl": role.max_ttl.as_secs(),
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+
+    pub async fn write_role(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        let mut role = self.get_role(req, &name).await?.unwrap_or_default();
+
+        if let Ok(key_type) = req.get_data_as_str("key_type") {
+            role.key_type = key_type;
+        }
+        if role.key_type != "ca" {
+            return Err(rv_error_response!("key_type must be 'ca'"));
+        }
+
+        if let Ok(cert_type) = req.get_data_as_str("cert_type") {
+            role.cert_type = cert_type;
+        }
+        if role.cert_type != "user" && role.cert_type != "host" {
+            return Err(RvError::ErrPkiSshCertTypeInvalid);
+        }
+
+        if let Ok(value) = req.get_data("allowed_users") {
+            role.allowed_users = value
+                .as_comma_string_slice()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+        if let Ok(value) = req.get_data("default_user") {
+            role.default_user = value.as_str().unwrap_or_default().trim().to_string();
+        }
+        if let Ok(value) = req.get_data("allowed_extensions") {
+            role.allowed_extensions = value
+                .as_comma_string_slice()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+        if let Ok(value) = req.get_data("default_extensions") {
+            role.default_extensions = value.as_map().ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+        if let Ok(value) = req.get_data("ttl") {
+            role.ttl = value.as_duration().ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+        if let Ok(value) = req.get_data("max_ttl") {
+            role.max_ttl = value.as_duration().ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+
+        if !role.default_user.is_empty() && !role.is_user_allowed(&role.default_user) {
+            return Err(rv_error_response!(
+                "default_user must be one of allowed_users"
+            ));
+        }
+        if !role.max_ttl.is_zero() && role.ttl > role.max_ttl {
+            return Err(rv_error_response!("ttl cannot be greater than max_ttl"));
+        }
+
+        let entry = StorageEntry::new(&format!("role/{name}"), &role)?;
+        req.storage_put(&entry).await?;
+
+        Ok(None)
+    }
+
+    pub async fn delete_role(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let name = req.get_data_as_str("name")?;
+        req.storage_delete(&format!("role/{name}")).await?;
+        Ok(None)
+    }
+
+    pub async fn list_roles(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let roles = req.storage_list("role/").await?;
+        Ok(Some(Response::list_response(&roles)))
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use openssl::pkey::PKey;
+use rand::Rng;
+use serde_json::json;
+use tracing::info;
+
+use super::{SshBackend, SshBackendInner, path_config_ca::key_nid};
+use crate::{
+    errors::RvError,
+    logical::{Backend, Field, FieldType, Operation, Path, Request, Response, field::FieldTrait},
+    modules::pki::ssh_util,
+    rv_error_response,
+};
+
+impl SshBackend {
+    pub fn sign_path(&self) -> Path {
+        let backend = self.inner.clone();
+
+        Path::builder()
+            .pattern(r"sign/(?P<role>\w(([\w.-]+)?\w)?)$")
+            .field(
+                "role",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("The desired role with configuration for this request."),
+            )
+            .field(
+                "public_key",
+                Field::builder()
+                    .field_type(FieldType::Str)
+                    .required(true)
+                    .description("SSH public key to sign, in authorized_keys format."),
+            )
+            .field(
+                "valid_principals",
+                Field::builder()
+                    .field_type(FieldType::CommaStringSlice)
+                    .description("Users or hosts the certificate is valid for. Defaults to the role's default_user."),
+            )
+            .field(
+                "ttl",
+                Field::builder()
+                    .field_type(FieldType::DurationSecond)
+                    .description("Requested lifetime of the certificate. Defaults to the role's ttl."),
+            )
+            .field(
+                "extensions",
+                Field::builder()
+                    .field_type(FieldType::Map)
+                    .description("Extensions to include in the certificate. Defaults to the role's default_extensions."),
+            )
+            .operation(Operation::Write, {
+                let handler = backend.clone();
+                move |backend, req| {
+                    let handler = handler.clone();
+                    Box::pin(async move { handler.sign_key(backend, req).await })
+                }
+            })
+            .help(
+                r#"
+This path signs an SSH public key against a role and returns the OpenSSH
+certificate, to be saved next to the private key as <key>-cert.pub.
+                "#,
+            )
+            .build()
+    }
+}
+
+/// Split an authorized_keys line into its key type and the key fields that follow the type in its
+/// wire encoding, which is what a certificate embeds.
+fn parse_public_key(public_key: &str) -> Result<(String, Vec<u8>), RvError> {
+    let mut parts = public_key.split_whitespace();
+    let (Some(key_type), Some(encoded)) = (parts.next(), parts.next()) else {
+        return Err(RvError::ErrPkiSshPublicKeyInvalid);
+    };
+
+    let blob = STANDARD
+        .decode(encoded)
+        .map_err(|_| RvError::ErrPkiSshPublicKeyInvalid)?;
+    if blob.len() < 4 {
+        return Err(RvError::ErrPkiSshPublicKeyInvalid);
+    }
+
+    let type_len = u32::from_be_bytes([blob[0], blob[1], blob[2], blob[3]]) as usize;
+    if blob.len() < 4 + type_len || &blob[4..4 + type_len] != key_type.as_bytes() {
+        return Err(RvError::ErrPkiSshPublicKeyInvalid);
+    }
+
+    Ok((key_type.to_string(), blob[4 + type_len..].to_vec()))
+}
+
+fn cert_key_type(key_type: &str) -> Result<String, RvError> {
+    match key_type {
+        "ssh-rsa"
+        | "ssh-ed25519"
+        | "ecdsa-sha2-nistp256"
+        | "ecdsa-sha2-nistp384"
+        | "ecdsa-sha2-nistp521" => Ok(format!("{key_type}-cert-v01@openssh.com")),
+        _ => Err(RvError::ErrPkiSshPublicKeyInvalid),
+    }
+}
+
+impl SshBackendInner {
+    pub async fn sign_key(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let role_name = req.get_data_as_str("role")?;
+        let role = self
+            .get_role(req, &role_name)
+            .await?
+            .ok_or(RvError::ErrPkiSshRoleNotFound)?;
+        let ca = self
+            .get_ca_config(req)
+            .await?
+            .ok_or(RvError::ErrPkiSshCaNotConfig)?;
+
+        let (key_type, key_data) = parse_public_key(&req.get_data_as_str("public_key")?)?;
+        let cert_key_type = cert_key_type(&key_type)?;
+
+        let mut principals = match req.get_data("valid_principals") {
+            Ok(value) => value
+                .as_comma_string_slice()
+                .ok_or(RvError::ErrRequestFieldInvalid)?,
+            Err(_) => Vec::new(),
+        };
+        if principals.is_empty() && !role.default_user.is_empty() {
+            principals.push(role.default_user.clone());
+        }
+        if principals.is_empty() {
+            return Err(rv_error_response!(
+                "no valid_principals given and the role has no default_user"
+            ));
+        }
+        if let Some(principal) = principals.iter().find(|p| !role.is_user_allowed(p)) {
+            return Err(rv_error_response!(&format!(
+                "{principal} is not a valid principal for role {role_name}"
+            )));
+        }
+
+        let ttl = match req.get_data("ttl") {
+            Ok(value) => value.as_duration().ok_or(RvError::ErrRequestFieldInvalid)?,
+            Err(_) => role.ttl,
+        };
+        if ttl.is_zero() || ttl > role.ttl_limit() {
+            return Err(rv_error_response!(&format!(
+                "ttl must be between 1s and {}s for role {role_name}",
+                role.ttl_limit().as_secs()
+            )));
+        }
+
+        let (cert_type, extensions) = if role.cert_type == "host" {
+            (ssh_util::SSH_CERT_TYPE_HOST, HashMap::new())
+        } else {
+            let extensions = match req.get_data("extensions") {
+                Ok(value) => value.as_map().ok_or(RvError::ErrRequestFieldInvalid)?,
+                Err(_) => HashMap::new(),
+            };
+            if let Some(extension) = extensions.keys().find(|e| !role.is_extension_allowed(e)) {
+                return Err(rv_error_response!(&format!(
+                    "extension {extension} is not allowed for role {role_name}"
+                )));
+            }
+
+            if extensions.is_empty() {
+                (
+                    ssh_util::SSH_CERT_TYPE_USER,
+                    role.default_extensions.clone(),
+                )
+            } else {
+                (ssh_util::SSH_CERT_TYPE_USER, extensions)
+            }
+        };
+
+        let serial: u64 = rand::rng().random();
+        let requester = req
+            .auth
+            .as_ref()
+            .map(|auth| auth.display_name.clone())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "token".to_string());
+        let key_id = format!("vault-{requester}-{role_name}-{serial:016x}");
+
+        // Backdated a little to absorb clock skew between here and the server.
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let valid_after = now.saturating_sub(30);
+        let valid_before = now + ttl.as_secs();
+
+        let ca_key = PKey::private_key_from_pem(ca.private_key.as_bytes())?;
+        let cert = ssh_util::build_ssh_certificate(
+            &cert_key_type,
+            &key_data,
+            serial,
+            &key_id,
+            &principals,
+            valid_after,
+            valid_before,
+            cert_type,
+            &extensions,
+            &ca_key,
+            key_nid(&ca_key)?,
+        )?;
+
+        info!(
+            role = %role_name,
+            key_id = %key_id,
+            principals = ?principals,
+            valid_before = valid_before,
+            "SSH certificate signed"
+        );
+
+        let data = json!({
+            "signed_key": ssh_util::format_openssh_cert(&cert_key_type, &cert),
+            "serial_number": format!("{serial:016x}"),
+            "key_id": key_id,
+            "valid_principals": principals,
+            "expiration": valid_before,
+        })
+        .as_object()
+        .cloned();
+
+        Ok(Some(Response::data_response(data)))
+    }
+}
@@ -0,0 +1,67 @@
+//! The `file` audit device appends one JSON object per line to a local file.
+//!
+//! Options:
+//! - `file_path` (required): the log file, created if missing and always opened for append.
+//! - `mode` (optional, unix only): octal permission bits for a newly created file, default `0600`.
+
+use std::{collections::HashMap, fs::OpenOptions, path::PathBuf};
+
+use async_trait::async_trait;
+use tokio::{fs::File, io::AsyncWriteExt, sync::Mutex};
+
+use super::{AuditBackend, AuditRecord};
+use crate::errors::RvError;
+
+const DEFAULT_FILE_MODE: u32 = 0o600;
+
+pub struct FileAuditBackend {
+    pub path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl FileAuditBackend {
+    pub fn new(options: &HashMap<String, String>) -> Result<Self, RvError> {
+        let path = options
+            .get("file_path")
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+            .ok_or(RvError::ErrAuditOptionsInvalid)?;
+
+        let mode = match options.get("mode") {
+            Some(mode) => u32::from_str_radix(mode.trim_start_matches("0o"), 8)
+                .map_err(|_| RvError::ErrAuditOptionsInvalid)?,
+            None => DEFAULT_FILE_MODE,
+        };
+
+        let mut open_options = OpenOptions::new();
+        open_options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            open_options.mode(mode);
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+
+        let file = open_options.open(&path)?;
+
+        Ok(Self {
+            path,
+            file: Mutex::new(File::from_std(file)),
+        })
+    }
+}
+
+#[async_trait]
+impl AuditBackend for FileAuditBackend {
+    async fn log(&self, record: &AuditRecord) -> Result<(), RvError> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        // Hold the lock across the whole write so concurrent records never interleave.
+        let mut file = self.file.lock().await;
+        file.write_all(&line).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,448 @@
+//! Audit devices record every request the core handles together with the response or error it
+//! produced.
+//!
+//! Devices are enabled through `sys/audit/<path>` and kept in the audit table inside the barrier,
+//! so they come back on every unseal. Each device owns a random HMAC key: client tokens are never
+//! written in the clear, and depending on `mount_entry_hmac_level` the sensitive (`compat`) or all
+//! (`high`) string values of request and response bodies are hashed as well. An operator holding a
+//! raw value can still find it in the log by hashing it with the same key.
+
+use std::{collections::HashMap, sync::Arc};
+
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+use openssl::{hash::MessageDigest, pkey::PKey, rand::rand_bytes, sign::Signer};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    config::MountEntryHMACLevel,
+    errors::RvError,
+    logical::{Operation, Request, Response},
+    storage::{Storage, StorageEntry},
+};
+
+pub mod file;
+
+pub use file::FileAuditBackend;
+
+/// Barrier key holding the audit table.
+pub const AUDIT_TABLE_PATH: &str = "core/audit";
+
+/// Body keys hashed at `MountEntryHMACLevel::Compat`.
+const SENSITIVE_KEYS: &[&str] = &[
+    "password",
+    "secret",
+    "secret_id",
+    "secret_key",
+    "private_key",
+    "token",
+    "client_token",
+    "hmac_key",
+    "key",
+    "keys",
+    "plaintext",
+];
+
+/// A sink for audit records.
+#[async_trait]
+pub trait AuditBackend: Send + Sync {
+    /// Persist one record. Records handed to the backend are already hashed.
+    async fn log(&self, record: &AuditRecord) -> Result<(), RvError>;
+}
+
+/// Audit table entry describing one enabled audit device.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub path: String,
+    #[serde(rename = "type")]
+    pub logical_type: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub options: HashMap<String, String>,
+    /// Hex-encoded per-device HMAC key.
+    #[serde(default)]
+    pub hmac_key: String,
+}
+
+impl AuditEntry {
+    pub fn new(path: &str, logical_type: &str, description: &str) -> Self {
+        Self {
+            path: sanitize_path(path),
+            logical_type: logical_type.to_string(),
+            description: description.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Snapshot of a request, taken before routing rewrites its path and token.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRequest {
+    pub id: String,
+    pub operation: Operation,
+    pub path: String,
+    pub client_token: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub remote_address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Map<String, Value>>,
+}
+
+impl AuditRequest {
+    pub fn new(req: &Request) -> Self {
+        Self {
+            id: req.id.clone(),
+            operation: req.operation,
+            path: req.path.clone(),
+            client_token: req.client_token.clone(),
+            remote_address: req
+                .connection
+                .as_ref()
+                .map(|conn| conn.peer_addr.clone())
+                .unwrap_or_default(),
+            data: req.body.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditAuth {
+    pub client_token: String,
+    pub accessor: String,
+    pub display_name: String,
+    pub policies: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<AuditAuth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Map<String, Value>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// One line of the audit log.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    /// Either `request` or `response`.
+    #[serde(rename = "type")]
+    pub record_type: String,
+    pub time: String,
+    pub request: AuditRequest,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<AuditResponse>,
+    /// Only set on response records.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+pub struct AuditDevice {
+    pub entry: AuditEntry,
+    hmac_key: Vec<u8>,
+    backend: Arc<dyn AuditBackend>,
+}
+
+impl AuditDevice {
+    fn new(entry: AuditEntry) -> Result<Self, RvError> {
+        let hmac_key = hex::decode(&entry.hmac_key)?;
+        let backend = new_audit_backend(&entry)?;
+        Ok(Self {
+            entry,
+            hmac_key,
+            backend,
+        })
+    }
+
+    /// Hash `value` with this device's key, in the `hmac-sha256:<hex>` form written to the log.
+    pub fn hash(&self, value: &str) -> Result<String, RvError> {
+        let pkey = PKey::hmac(&self.hmac_key)?;
+        let mut signer = Signer::new(MessageDigest::sha256(), &pkey)?;
+        signer.update(value.as_bytes())?;
+        Ok(format!(
+            "hmac-sha256:{}",
+            hex::encode(signer.sign_to_vec()?)
+        ))
+    }
+
+    fn hash_token(&self, token: &str) -> Result<String, RvError> {
+        if token.is_empty() {
+            return Ok(String::new());
+        }
+        self.hash(token)
+    }
+
+    fn hash_map(
+        &self,
+        map: &Map<String, Value>,
+        level: MountEntryHMACLevel,
+    ) -> Result<Map<String, Value>, RvError> {
+        let mut ret = Map::new();
+        for (key, value) in map.iter() {
+            let hash_all = level == MountEntryHMACLevel::High
+                || (level == MountEntryHMACLevel::Compat && SENSITIVE_KEYS.contains(&key.as_str()));
+            ret.insert(key.clone(), self.hash_value(value, hash_all, level)?);
+        }
+        Ok(ret)
+    }
+
+    fn hash_value(
+        &self,
+        value: &Value,
+        hash_all: bool,
+        level: MountEntryHMACLevel,
+    ) -> Result<Value, RvError> {
+        Ok(match value {
+            Value::String(s) if hash_all => Value::String(self.hash(s)?),
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.hash_value(item, hash_all, level))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(map) if hash_all => Value::Object(
+                map.iter()
+                    .map(|(k, v)| Ok((k.clone(), self.hash_value(v, true, level)?)))
+                    .collect::<Result<_, RvError>>()?,
+            ),
+            Value::Object(map) => Value::Object(self.hash_map(map, level)?),
+            _ => value.clone(),
+        })
+    }
+
+    fn hash_request(
+        &self,
+        req: &AuditRequest,
+        level: MountEntryHMACLevel,
+    ) -> Result<AuditRequest, RvError> {
+        let mut req = req.clone();
+        req.client_token = self.hash_token(&req.client_token)?;
+        if let Some(data) = req.data.as_ref() {
+            req.data = Some(self.hash_map(data, level)?);
+        }
+        Ok(req)
+    }
+
+    fn hash_response(
+        &self,
+        resp: &Response,
+        level: MountEntryHMACLevel,
+    ) -> Result<AuditResponse, RvError> {
+        let auth = match resp.auth.as_ref() {
+            Some(auth) => Some(AuditAuth {
+                client_token: self.hash_token(&auth.client_token)?,
+                accessor: auth.accessor.clone(),
+                display_name: auth.display_name.clone(),
+                policies: auth.policies.clone(),
+            }),
+            None => None,
+        };
+        let data = match resp.data.as_ref() {
+            Some(data) => Some(self.hash_map(data, level)?),
+            None => None,
+        };
+
+        Ok(AuditResponse {
+            auth,
+            data,
+            warnings: resp.warnings.clone(),
+        })
+    }
+}
+
+/// Fans audit records out to every enabled device.
+pub struct AuditBroker {
+    devices: ArcSwap<Vec<Arc<AuditDevice>>>,
+}
+
+impl Default for AuditBroker {
+    fn default() -> Self {
+        Self {
+            devices: ArcSwap::from_pointee(Vec::new()),
+        }
+    }
+}
+
+impl AuditBroker {
+    pub fn is_enabled(&self) -> bool {
+        !self.devices.load().is_empty()
+    }
+
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.devices
+            .load()
+            .iter()
+            .map(|device| device.entry.clone())
+            .collect()
+    }
+
+    /// Read the audit table from `storage` and open every device in it.
+    pub async fn load(&self, storage: &dyn Storage) -> Result<(), RvError> {
+        let mut devices = Vec::new();
+        for entry in read_audit_table(storage).await? {
+            devices.push(Arc::new(AuditDevice::new(entry)?));
+        }
+        self.devices.store(Arc::new(devices));
+        Ok(())
+    }
+
+    pub fn unload(&self) {
+        self.devices.store(Arc::new(Vec::new()));
+    }
+
+    pub async fn enable(
+        &self,
+        storage: &dyn Storage,
+        mut entry: AuditEntry,
+    ) -> Result<(), RvError> {
+        let mut table = read_audit_table(storage).await?;
+        if table.iter().any(|e| e.path == entry.path) {
+            return Err(RvError::ErrAuditPathExist);
+        }
+
+        let mut key = vec![0u8; 32];
+        rand_bytes(&mut key)?;
+        entry.hmac_key = hex::encode(&key);
+
+        // Open the device before persisting it, so a bad file path fails the enable call.
+        let device = Arc::new(AuditDevice::new(entry.clone())?);
+
+        table.push(entry);
+        write_audit_table(storage, &table).await?;
+
+        let mut devices = (*self.devices.load_full()).clone();
+        devices.push(device);
+        self.devices.store(Arc::new(devices));
+        Ok(())
+    }
+
+    pub async fn disable(&self, storage: &dyn Storage, path: &str) -> Result<(), RvError> {
+        let path = sanitize_path(path);
+        let mut table = read_audit_table(storage).await?;
+        let len = table.len();
+        table.retain(|e| e.path != path);
+        if table.len() == len {
+            return Err(RvError::ErrAuditPathNotFound);
+        }
+        write_audit_table(storage, &table).await?;
+
+        let mut devices = (*self.devices.load_full()).clone();
+        devices.retain(|d| d.entry.path != path);
+        self.devices.store(Arc::new(devices));
+        Ok(())
+    }
+
+    /// Log `req` to every device. Returns the snapshot to hand back to `log_response`, or `None`
+    /// when no device is enabled.
+    pub async fn log_request(
+        &self,
+        req: &Request,
+        level: MountEntryHMACLevel,
+    ) -> Result<Option<AuditRequest>, RvError> {
+        if !self.is_enabled() {
+            return Ok(None);
+        }
+
+        let audit_req = AuditRequest::new(req);
+        self.broadcast(|device| {
+            Ok(AuditRecord {
+                record_type: "request".to_string(),
+                time: now(),
+                request: device.hash_request(&audit_req, level)?,
+                response: None,
+                success: None,
+                error: None,
+            })
+        })
+        .await?;
+
+        Ok(Some(audit_req))
+    }
+
+    pub async fn log_response(
+        &self,
+        req: &AuditRequest,
+        resp: Option<&Response>,
+        err: Option<&RvError>,
+        level: MountEntryHMACLevel,
+    ) -> Result<(), RvError> {
+        self.broadcast(|device| {
+            Ok(AuditRecord {
+                record_type: "response".to_string(),
+                time: now(),
+                request: device.hash_request(req, level)?,
+                response: match resp {
+                    Some(resp) => Some(device.hash_response(resp, level)?),
+                    None => None,
+                },
+                success: Some(err.is_none()),
+                error: err.map(|e| e.to_string()),
+            })
+        })
+        .await
+    }
+
+    /// Write a record built per device. The request is only failed if no device accepted it, so
+    /// one broken sink does not take the vault down while another still records everything.
+    async fn broadcast<F>(&self, build: F) -> Result<(), RvError>
+    where
+        F: Fn(&AuditDevice) -> Result<AuditRecord, RvError>,
+    {
+        let devices = self.devices.load_full();
+        let mut logged = false;
+        for device in devices.iter() {
+            let ret = match build(device) {
+                Ok(record) => device.backend.log(&record).await,
+                Err(e) => Err(e),
+            };
+            match ret {
+                Ok(()) => logged = true,
+                Err(e) => log::error!("audit device {} failed to log: {}", device.entry.path, e),
+            }
+        }
+
+        if !logged && !devices.is_empty() {
+            return Err(RvError::ErrAuditLogFailed);
+        }
+
+        Ok(())
+    }
+}
+
+fn new_audit_backend(entry: &AuditEntry) -> Result<Arc<dyn AuditBackend>, RvError> {
+    match entry.logical_type.as_str() {
+        "file" => Ok(Arc::new(FileAuditBackend::new(&entry.options)?)),
+        _ => Err(RvError::ErrAuditTypeUnsupported),
+    }
+}
+
+async fn read_audit_table(storage: &dyn Storage) -> Result<Vec<AuditEntry>, RvError> {
+    match storage.get(AUDIT_TABLE_PATH).await? {
+        Some(entry) => Ok(serde_json::from_slice(&entry.value)?),
+        None => Ok(Vec::new()),
+    }
+}
+
+async fn write_audit_table(storage: &dyn Storage, table: &[AuditEntry]) -> Result<(), RvError> {
+    storage
+        .put(&StorageEntry::new(AUDIT_TABLE_PATH, &table)?)
+        .await
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+fn sanitize_path(path: &str) -> String {
+    let path = path.trim_start_matches('/');
+    if path.ends_with('/') {
+        path.to_string()
+    } else {
+        format!("{path}/")
+    }
+}
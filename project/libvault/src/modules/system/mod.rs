@@ -3,11 +3,12 @@
 
 use std::{
     any::Any,
-    sync::{Arc, Weak},
+    sync::{Arc, RwLock, Weak},
+    time::Duration,
 };
 
 use async_trait::async_trait;
-use serde_json::{Map, Value, from_value, json};
+use serde_json::{Map, Value, json};
 
 use crate::{
     core::Core,
@@ -18,14 +19,23 @@ use crate::{
     },
     modules::{
         Module,
-        auth::{AUTH_TABLE_TYPE, AuthModule},
+        auth::{AUTH_TABLE_TYPE, AuthModule, ExpirationManager},
+        credential::lockout::validate_lockout_config,
         policy::{PolicyModule, acl::ACL},
     },
     mount::{MOUNT_TABLE_TYPE, MountEntry},
+    namespace::{Namespace, scope_path},
     rv_error_response_status,
     storage::StorageEntry,
 };
 
+pub mod audit;
+
+use audit::AuditEntry;
+
+/// The auth module and, if mounted, the entry a `sys/auth/<path>/tune` request names.
+type TunedAuthEntry = (Arc<AuthModule>, Option<Arc<RwLock<MountEntry>>>);
+
 static SYSTEM_BACKEND_HELP: &str = r#"
 The system backend is built-in to RustyVault and cannot be remounted or
 unmounted. It contains the paths that are used to configure RustyVault itself
@@ -78,13 +88,17 @@ impl SystemBackend {
                 "seal",
                 "raw/*",
                 "revoke-prefix/*",
+                "namespaces",
+                "namespaces/*",
             ])
             .unauth_paths([
                 "internal/ui/mounts",
                 "internal/ui/mounts/*",
                 "init",
+                "health",
                 "seal-status",
                 "unseal",
+                "generate-root/*",
             ]);
 
         {
@@ -138,8 +152,71 @@ impl SystemBackend {
                             "The options to pass into the backend. Should be a json object with string keys and values.",
                         ),
                 )
+                .field(
+                    "seal_wrap",
+                    FieldBuilder::new()
+                        .field_type(FieldType::Bool)
+                        .default_value(false)
+                        .description("Encrypt the mount's values a second time before they are stored."),
+                )
                 .build();
 
+            paths.push(
+                PathBuilder::new()
+                    .pattern("mounts/(?P<path>.+)/tune$")
+                    .field(
+                        "path",
+                        FieldBuilder::new()
+                            .field_type(FieldType::Str)
+                            .description(r#"The path of the mount to tune. Example: "aws/east""#),
+                    )
+                    .field(
+                        "default_lease_ttl",
+                        FieldBuilder::new()
+                            .field_type(FieldType::DurationSecond)
+                            .description(
+                                "Default lease TTL of the mount. 0 uses the system default.",
+                            ),
+                    )
+                    .field(
+                        "max_lease_ttl",
+                        FieldBuilder::new()
+                            .field_type(FieldType::DurationSecond)
+                            .description(
+                                "Maximum lease TTL of the mount. 0 uses the system default.",
+                            ),
+                    )
+                    .field(
+                        "description",
+                        FieldBuilder::new()
+                            .field_type(FieldType::Str)
+                            .description("User-friendly description for this mount."),
+                    )
+                    .operation(Operation::Read, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(
+                                async move { handler.handle_mount_tune_read(backend, req).await },
+                            )
+                        }
+                    })
+                    .operation(Operation::Write, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(
+                                async move { handler.handle_mount_tune_write(backend, req).await },
+                            )
+                        }
+                    })
+                    .build(),
+            );
+
             paths.push(
                 PathBuilder::new()
                     .pattern("mounts/(?P<path>.+)")
@@ -197,6 +274,7 @@ impl SystemBackend {
                     "increment",
                     FieldBuilder::new()
                         .field_type(FieldType::Int)
+                        .default_value(0)
                         .description("The desired increment in seconds to the lease"),
                 )
                 .build();
@@ -204,7 +282,7 @@ impl SystemBackend {
             paths.push(
                 PathBuilder::new()
                     .pattern("renew/(?P<lease_id>.+)")
-                    .fields(renew_fields)
+                    .fields(renew_fields.clone())
                     .operation(Operation::Write, {
                         let handler = backend.clone();
 
@@ -261,6 +339,64 @@ impl SystemBackend {
                     .build(),
             );
 
+            paths.push(
+                PathBuilder::new()
+                    .pattern("leases/renew/?$")
+                    .fields(renew_fields)
+                    .operation(Operation::Write, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(async move { handler.handle_renew(backend, req).await })
+                        }
+                    })
+                    .build(),
+            );
+
+            paths.push(
+                PathBuilder::new()
+                    .pattern("leases/revoke/?$")
+                    .field(
+                        "lease_id",
+                        FieldBuilder::new()
+                            .field_type(FieldType::Str)
+                            .description("The lease identifier to revoke."),
+                    )
+                    .operation(Operation::Write, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(async move { handler.handle_revoke(backend, req).await })
+                        }
+                    })
+                    .build(),
+            );
+
+            paths.push(
+                PathBuilder::new()
+                    .pattern("leases/lookup/?$")
+                    .field(
+                        "lease_id",
+                        FieldBuilder::new()
+                            .field_type(FieldType::Str)
+                            .description("The lease identifier to look up."),
+                    )
+                    .operation(Operation::Write, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(async move { handler.handle_lease_lookup(backend, req).await })
+                        }
+                    })
+                    .build(),
+            );
+
             paths.push(
                 PathBuilder::new()
                     .pattern("auth$")
@@ -312,6 +448,94 @@ impl SystemBackend {
                 )
                 .build();
 
+            paths.push(
+                PathBuilder::new()
+                    .pattern("auth/(?P<path>.+)/tune$")
+                    .field(
+                        "path",
+                        FieldBuilder::new()
+                            .field_type(FieldType::Str)
+                            .description(r#"The path of the auth method to tune. Example: "user""#),
+                    )
+                    .field(
+                        "default_lease_ttl",
+                        FieldBuilder::new()
+                            .field_type(FieldType::DurationSecond)
+                            .description(
+                                "Default lease TTL of the mount. 0 uses the system default.",
+                            ),
+                    )
+                    .field(
+                        "max_lease_ttl",
+                        FieldBuilder::new()
+                            .field_type(FieldType::DurationSecond)
+                            .description(
+                                "Maximum lease TTL of the mount. 0 uses the system default.",
+                            ),
+                    )
+                    .field(
+                        "description",
+                        FieldBuilder::new()
+                            .field_type(FieldType::Str)
+                            .description("User-friendly description for this auth method."),
+                    )
+                    .field(
+                        "password_hash",
+                        FieldBuilder::new()
+                            .field_type(FieldType::Map)
+                            .description(
+                                r#"KDF used to hash stored secrets. Example: {"algorithm": "argon2id"}"#,
+                            ),
+                    )
+                    .field(
+                        "default_policies",
+                        FieldBuilder::new()
+                            .field_type(FieldType::CommaStringSlice)
+                            .description("Policies added to every token issued by this mount."),
+                    )
+                    .field(
+                        "lockout_threshold",
+                        FieldBuilder::new()
+                            .field_type(FieldType::Int)
+                            .description("Failed logins that lock an identity out. 0 disables."),
+                    )
+                    .field(
+                        "lockout_window",
+                        FieldBuilder::new()
+                            .field_type(FieldType::DurationSecond)
+                            .description("Span in which failed logins are counted."),
+                    )
+                    .field(
+                        "lockout_duration",
+                        FieldBuilder::new()
+                            .field_type(FieldType::DurationSecond)
+                            .description("How long a locked out identity is refused."),
+                    )
+                    .operation(Operation::Read, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(
+                                async move { handler.handle_auth_tune_read(backend, req).await },
+                            )
+                        }
+                    })
+                    .operation(Operation::Write, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(
+                                async move { handler.handle_auth_tune_write(backend, req).await },
+                            )
+                        }
+                    })
+                    .build(),
+            );
+
             paths.push(
                 PathBuilder::new()
                     .pattern("auth/(?P<path>.+)")
@@ -472,6 +696,130 @@ impl SystemBackend {
                     .build(),
             );
 
+            let capabilities_fields = FieldsBuilder::new()
+                .field(
+                    "token",
+                    FieldBuilder::new()
+                        .field_type(FieldType::Str)
+                        .description("Token for which capabilities are being queried."),
+                )
+                .field(
+                    "path",
+                    FieldBuilder::new()
+                        .field_type(FieldType::CommaStringSlice)
+                        .description("Use 'paths' instead."),
+                )
+                .field(
+                    "paths",
+                    FieldBuilder::new()
+                        .field_type(FieldType::CommaStringSlice)
+                        .description("Paths on which capabilities are being queried."),
+                )
+                .build();
+
+            paths.push(
+                PathBuilder::new()
+                    .pattern("capabilities$")
+                    .fields(capabilities_fields.clone())
+                    .operation(Operation::Write, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(async move { handler.handle_capabilities(backend, req).await })
+                        }
+                    })
+                    .build(),
+            );
+
+            paths.push(
+                PathBuilder::new()
+                    .pattern("capabilities-self$")
+                    .fields(capabilities_fields)
+                    .operation(Operation::Write, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(
+                                async move { handler.handle_capabilities_self(backend, req).await },
+                            )
+                        }
+                    })
+                    .build(),
+            );
+
+            paths.push(
+                PathBuilder::new()
+                    .pattern("generate-root/attempt$")
+                    .field(
+                        "otp",
+                        FieldBuilder::new()
+                            .field_type(FieldType::Str)
+                            .description("One-time password the new root token is XORed with."),
+                    )
+                    .operation(Operation::Read, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(async move {
+                                handler.handle_generate_root_status(backend, req).await
+                            })
+                        }
+                    })
+                    .operation(Operation::Write, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(async move {
+                                handler.handle_generate_root_init(backend, req).await
+                            })
+                        }
+                    })
+                    .operation(Operation::Delete, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(async move {
+                                handler.handle_generate_root_cancel(backend, req).await
+                            })
+                        }
+                    })
+                    .build(),
+            );
+
+            paths.push(
+                PathBuilder::new()
+                    .pattern("generate-root/update$")
+                    .field(
+                        "key",
+                        FieldBuilder::new()
+                            .field_type(FieldType::Str)
+                            .required(true)
+                            .description("Hex-encoded unseal key."),
+                    )
+                    .operation(Operation::Write, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(async move {
+                                handler.handle_generate_root_update(backend, req).await
+                            })
+                        }
+                    })
+                    .build(),
+            );
+
             paths.push(
                 PathBuilder::new()
                     .pattern("audit$")
@@ -548,15 +896,73 @@ impl SystemBackend {
 
             paths.push(
                 PathBuilder::new()
-                    .pattern("raw/(?P<path>.+)")
-                    .fields(raw_fields)
+                    .pattern("raw/(?P<path>.+)")
+                    .fields(raw_fields)
+                    .operation(Operation::Read, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(async move { handler.handle_raw_read(backend, req).await })
+                        }
+                    })
+                    .operation(Operation::Write, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(async move { handler.handle_raw_write(backend, req).await })
+                        }
+                    })
+                    .operation(Operation::Delete, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(async move { handler.handle_raw_delete(backend, req).await })
+                        }
+                    })
+                    .build(),
+            );
+
+            paths.push(
+                PathBuilder::new()
+                    .pattern("namespaces/?$")
+                    .operation(Operation::List, {
+                        let handler = backend.clone();
+
+                        move |backend, req| {
+                            let handler = handler.clone();
+
+                            Box::pin(
+                                async move { handler.handle_namespace_list(backend, req).await },
+                            )
+                        }
+                    })
+                    .build(),
+            );
+
+            paths.push(
+                PathBuilder::new()
+                    .pattern("namespaces/(?P<path>.+)")
+                    .field(
+                        "path",
+                        FieldBuilder::new()
+                            .field_type(FieldType::Str)
+                            .description(r#"The namespace path. Example: "team-a""#),
+                    )
                     .operation(Operation::Read, {
                         let handler = backend.clone();
 
                         move |backend, req| {
                             let handler = handler.clone();
 
-                            Box::pin(async move { handler.handle_raw_read(backend, req).await })
+                            Box::pin(
+                                async move { handler.handle_namespace_read(backend, req).await },
+                            )
                         }
                     })
                     .operation(Operation::Write, {
@@ -565,7 +971,9 @@ impl SystemBackend {
                         move |backend, req| {
                             let handler = handler.clone();
 
-                            Box::pin(async move { handler.handle_raw_write(backend, req).await })
+                            Box::pin(
+                                async move { handler.handle_namespace_create(backend, req).await },
+                            )
                         }
                     })
                     .operation(Operation::Delete, {
@@ -574,7 +982,9 @@ impl SystemBackend {
                         move |backend, req| {
                             let handler = handler.clone();
 
-                            Box::pin(async move { handler.handle_raw_delete(backend, req).await })
+                            Box::pin(
+                                async move { handler.handle_namespace_delete(backend, req).await },
+                            )
                         }
                     })
                     .build(),
@@ -627,7 +1037,7 @@ impl SystemBackend {
     pub async fn handle_mount_table(
         &self,
         _backend: &dyn Backend,
-        _req: &mut Request,
+        req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
         let mut data: Map<String, Value> = Map::new();
 
@@ -635,11 +1045,16 @@ impl SystemBackend {
 
         for mount_entry in mounts.values() {
             let entry = mount_entry.read()?;
+            let Some(path) = self.namespace_mount_path(req, &entry) else {
+                continue;
+            };
             let info: Value = json!({
                 "type": entry.logical_type.clone(),
                 "description": entry.description.clone(),
+                "accessor": entry.accessor(),
+                "seal_wrap": entry.seal_wrap,
             });
-            data.insert(entry.path.clone(), info);
+            data.insert(path, info);
         }
 
         Ok(Some(Response::data_response(Some(data))))
@@ -654,6 +1069,7 @@ impl SystemBackend {
         let logical_type = req.get_data("type")?;
         let description = req.get_data_or_default("description")?;
         let options = req.get_data_or_default("options")?;
+        let seal_wrap = req.get_data_or_default("seal_wrap")?;
 
         let path = path.as_str().unwrap();
         let logical_type = logical_type.as_str().unwrap();
@@ -663,8 +1079,10 @@ impl SystemBackend {
             return Err(RvError::ErrRequestInvalid);
         }
 
-        let mut me = MountEntry::new(MOUNT_TABLE_TYPE, path, logical_type, description);
+        let path = format!("{}{path}", req.namespace);
+        let mut me = MountEntry::new(MOUNT_TABLE_TYPE, &path, logical_type, description);
         me.options = options.as_map();
+        me.seal_wrap = seal_wrap.as_bool().unwrap_or(false);
 
         self.core.mount(&me).await?;
         Ok(None)
@@ -680,7 +1098,69 @@ impl SystemBackend {
             return Err(RvError::ErrRequestInvalid);
         }
 
-        self.core.unmount(suffix).await?;
+        self.core
+            .unmount(&format!("{}{suffix}", req.namespace))
+            .await?;
+        Ok(None)
+    }
+
+    fn tuned_mount_entry(&self, req: &Request) -> Result<Option<Arc<RwLock<MountEntry>>>, RvError> {
+        let mut path = format!("{}{}", req.namespace, req.get_data_as_str("path")?);
+        if !path.ends_with('/') {
+            path += "/";
+        }
+
+        self.core.mounts_router.get(&path)
+    }
+
+    pub async fn handle_mount_tune_read(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let Some(mount_entry) = self.tuned_mount_entry(req)? else {
+            return Err(RvError::ErrMountNotMatch);
+        };
+
+        let entry = mount_entry.read()?;
+        let data = json!({
+            "default_lease_ttl": entry.default_lease_ttl().as_secs(),
+            "max_lease_ttl": entry.max_lease_ttl().as_secs(),
+            "description": entry.description.clone(),
+            "seal_wrap": entry.seal_wrap,
+        });
+
+        Ok(Some(Response::data_response(data.as_object().cloned())))
+    }
+
+    pub async fn handle_mount_tune_write(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let Some(mount_entry) = self.tuned_mount_entry(req)? else {
+            return Err(RvError::ErrMountNotMatch);
+        };
+
+        let (mount_path, mut config) = {
+            let entry = mount_entry.read()?;
+            (entry.path.clone(), entry.config.clone())
+        };
+
+        if let Ok(ttl) = req.get_data("default_lease_ttl") {
+            config.default_lease_ttl = ttl.as_duration().ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+        if let Ok(ttl) = req.get_data("max_lease_ttl") {
+            config.max_lease_ttl = ttl.as_duration().ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+        let description = req
+            .get_data("description")
+            .ok()
+            .and_then(|description| description.as_str().map(str::to_string));
+
+        self.core
+            .tune_mount(&mount_path, config, description)
+            .await?;
         Ok(None)
     }
 
@@ -698,15 +1178,15 @@ impl SystemBackend {
             return Err(RvError::ErrRequestInvalid);
         }
 
-        let from_path = sanitize_path(from);
-        let to_path = sanitize_path(to);
+        let from_path = scope_path(&req.namespace, &sanitize_path(from));
+        let to_path = scope_path(&req.namespace, &sanitize_path(to));
 
         if let Some(me) = self.core.router.matching_mount_entry(&from_path)? {
             let mount_entry_table_type;
             {
                 let mount_entry = me.read()?;
 
-                let dst_path_match = self.core.router.matching_mount(to)?;
+                let dst_path_match = self.core.router.matching_mount(&to_path)?;
                 if !dst_path_match.is_empty() {
                     return Err(rv_error_response_status!(
                         409,
@@ -746,10 +1226,17 @@ impl SystemBackend {
         _backend: &dyn Backend,
         req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
-        let _lease_id = req.get_data("lease_id")?;
-        let _increment: i32 = from_value(req.get_data("increment")?)?;
-        //TODO
-        Ok(None)
+        let lease_id = req.get_data_as_str("lease_id")?;
+        self.check_namespace_path(req, &lease_id)?;
+        let increment = req
+            .get_data_or_default("increment")?
+            .as_int()
+            .filter(|i| *i >= 0)
+            .ok_or(RvError::ErrRequestFieldInvalid)?;
+
+        self.expiration()?
+            .renew(&lease_id, Duration::from_secs(increment as u64))
+            .await
     }
 
     pub async fn handle_revoke(
@@ -757,8 +1244,10 @@ impl SystemBackend {
         _backend: &dyn Backend,
         req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
-        let _lease_id = req.get_data("lease_id")?;
-        //TODO
+        let lease_id = req.get_data_as_str("lease_id")?;
+        self.check_namespace_path(req, &lease_id)?;
+
+        self.expiration()?.revoke_lease_id(&lease_id, true).await?;
         Ok(None)
     }
 
@@ -767,14 +1256,32 @@ impl SystemBackend {
         _backend: &dyn Backend,
         req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
-        let _prefix = req.get_data("prefix")?;
+        let prefix = req.get_data_as_str("prefix")?;
+        self.check_namespace_path(req, &prefix)?;
+
+        self.expiration()?.revoke_prefix(&prefix).await?;
         Ok(None)
     }
 
+    pub async fn handle_lease_lookup(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let lease_id = req.get_data_as_str("lease_id")?;
+        self.check_namespace_path(req, &lease_id)?;
+
+        let Some(data) = self.expiration()?.lookup(&lease_id).await? else {
+            return Err(RvError::ErrLeaseNotFound);
+        };
+
+        Ok(Some(Response::data_response(Some(data))))
+    }
+
     pub async fn handle_auth_table(
         &self,
         _backend: &dyn Backend,
-        _req: &mut Request,
+        req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
         let mut data: Map<String, Value> = Map::new();
 
@@ -784,11 +1291,21 @@ impl SystemBackend {
 
         for mount_entry in mounts.values() {
             let entry = mount_entry.read()?;
+            // The shared token store is listed in every namespace.
+            let path = if entry.logical_type == "token" {
+                entry.path.clone()
+            } else {
+                let Some(path) = self.namespace_mount_path(req, &entry) else {
+                    continue;
+                };
+                path
+            };
             let info: Value = json!({
                 "type": entry.logical_type.clone(),
                 "description": entry.description.clone(),
+                "accessor": entry.accessor(),
             });
-            data.insert(entry.path.clone(), info);
+            data.insert(path, info);
         }
 
         Ok(Some(Response::data_response(Some(data))))
@@ -804,7 +1321,7 @@ impl SystemBackend {
         let description = req.get_data_or_default("description")?;
         let options = req.get_data_or_default("options")?;
 
-        let path = sanitize_path(path.as_str().unwrap());
+        let path = format!("{}{}", req.namespace, sanitize_path(path.as_str().unwrap()));
         let logical_type = logical_type.as_str().unwrap();
         let description = description.as_str().unwrap();
 
@@ -823,6 +1340,115 @@ impl SystemBackend {
         Ok(None)
     }
 
+    fn tuned_auth_entry(&self, req: &Request) -> Result<TunedAuthEntry, RvError> {
+        let path = format!(
+            "{}{}",
+            req.namespace,
+            sanitize_path(&req.get_data_as_str("path")?)
+        );
+        let auth_module = self.get_module::<AuthModule>("auth")?;
+        let entry = auth_module.mounts_router.get(&path)?;
+        Ok((auth_module, entry))
+    }
+
+    pub async fn handle_auth_tune_read(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let (_, Some(mount_entry)) = self.tuned_auth_entry(req)? else {
+            return Err(RvError::ErrMountNotMatch);
+        };
+
+        let entry = mount_entry.read()?;
+        let data = json!({
+            "default_lease_ttl": entry.default_lease_ttl().as_secs(),
+            "max_lease_ttl": entry.max_lease_ttl().as_secs(),
+            "description": entry.description.clone(),
+            "password_hash": entry.config.password_hash,
+            "default_policies": entry.config.default_policies,
+            "lockout_threshold": entry.config.lockout_threshold,
+            "lockout_window": entry.config.lockout_window.as_secs(),
+            "lockout_duration": entry.config.lockout_duration.as_secs(),
+        });
+
+        Ok(Some(Response::data_response(data.as_object().cloned())))
+    }
+
+    pub async fn handle_auth_tune_write(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let (auth_module, Some(mount_entry)) = self.tuned_auth_entry(req)? else {
+            return Err(RvError::ErrMountNotMatch);
+        };
+
+        let (mount_path, mut config) = {
+            let entry = mount_entry.read()?;
+            (entry.path.clone(), entry.config.clone())
+        };
+
+        if let Ok(ttl) = req.get_data("default_lease_ttl") {
+            config.default_lease_ttl = ttl.as_duration().ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+        if let Ok(ttl) = req.get_data("max_lease_ttl") {
+            config.max_lease_ttl = ttl.as_duration().ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+        if let Ok(password_hash) = req.get_data("password_hash") {
+            config.password_hash = serde_json::from_value(password_hash).map_err(|err| {
+                rv_error_response_status!(400, &format!("invalid password_hash: {err}"))
+            })?;
+            // reject unusable parameters now rather than on the next write
+            config
+                .password_hash
+                .hasher()
+                .map_err(|err| rv_error_response_status!(400, err))?;
+        }
+        if let Ok(policies) = req.get_data("default_policies") {
+            let mut policies = policies
+                .as_comma_string_slice()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+            policies
+                .iter_mut()
+                .for_each(|p| *p = p.trim().to_lowercase());
+            policies.retain(|p| !p.is_empty());
+            if policies.iter().any(|p| p == "root") {
+                return Err(rv_error_response_status!(
+                    400,
+                    "root cannot be a default policy of an auth mount"
+                ));
+            }
+            config.default_policies = policies;
+        }
+        if let Ok(threshold) = req.get_data("lockout_threshold") {
+            config.lockout_threshold = threshold
+                .as_int()
+                .and_then(|threshold| u32::try_from(threshold).ok())
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+        if let Ok(window) = req.get_data("lockout_window") {
+            config.lockout_window = window
+                .as_duration()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+        if let Ok(duration) = req.get_data("lockout_duration") {
+            config.lockout_duration = duration
+                .as_duration()
+                .ok_or(RvError::ErrRequestFieldInvalid)?;
+        }
+        validate_lockout_config(&config).map_err(|err| rv_error_response_status!(400, err))?;
+        let description = req
+            .get_data("description")
+            .ok()
+            .and_then(|description| description.as_str().map(str::to_string));
+
+        auth_module
+            .tune_auth(&mount_path, config, description)
+            .await?;
+        Ok(None)
+    }
+
     pub async fn handle_auth_disable(
         &self,
         _backend: &dyn Backend,
@@ -835,7 +1461,9 @@ impl SystemBackend {
 
         let auth_module = self.get_module::<AuthModule>("auth")?;
 
-        auth_module.disable_auth(&path).await?;
+        auth_module
+            .disable_auth(&format!("{}{path}", req.namespace))
+            .await?;
 
         Ok(None)
     }
@@ -880,27 +1508,187 @@ impl SystemBackend {
         policy_module.handle_policy_delete(backend, req).await
     }
 
-    pub async fn handle_audit_table(
+    pub async fn handle_capabilities(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let token = req.get_data_as_str("token")?;
+        let auth_module = self.get_module::<AuthModule>("auth")?;
+        let Some(token_store) = auth_module.token_store.load_full() else {
+            return Err(RvError::ErrBarrierSealed);
+        };
+
+        // A lookup rather than `check_token`: asking about a token must not spend one of its uses.
+        let te = match token_store.lookup(&token).await? {
+            Some(te) => te,
+            None => return Err(RvError::ErrAuthTokenNotFound),
+        };
+
+        self.capabilities(req, &te.namespace, &te.policies).await
+    }
+
+    pub async fn handle_capabilities_self(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        let (namespace, policies) = match req.auth.as_ref() {
+            Some(auth) => (auth.namespace.clone(), auth.policies.clone()),
+            None => return Err(RvError::ErrPermissionDenied),
+        };
+
+        self.capabilities(req, &namespace, &policies).await
+    }
+
+    pub async fn handle_generate_root_status(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        root_namespace_only(req)?;
+        let data = self.core.generate_root_status().await?;
+        Ok(Some(Response::data_response(Some(data))))
+    }
+
+    pub async fn handle_generate_root_init(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        root_namespace_only(req)?;
+        let otp = req.get_data_as_str("otp")?;
+        self.core.generate_root_init(&otp).await?;
+
+        let data = self.core.generate_root_status().await?;
+        Ok(Some(Response::data_response(Some(data))))
+    }
+
+    pub async fn handle_generate_root_cancel(
         &self,
         _backend: &dyn Backend,
-        _req: &mut Request,
+        req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
+        root_namespace_only(req)?;
+        self.core.generate_root_cancel()?;
         Ok(None)
     }
 
+    pub async fn handle_generate_root_update(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        root_namespace_only(req)?;
+        let key = req.get_data_as_str("key")?;
+        let key = hex::decode(key).map_err(|_| RvError::ErrRequestFieldInvalid)?;
+
+        let encoded_token = self.core.generate_root_update(&key).await?;
+
+        let mut data = self.core.generate_root_status().await?;
+        if let Some(encoded_token) = encoded_token {
+            data.insert("complete".into(), Value::Bool(true));
+            data.insert("encoded_token".into(), Value::String(encoded_token));
+        }
+
+        Ok(Some(Response::data_response(Some(data))))
+    }
+
+    async fn capabilities(
+        &self,
+        req: &Request,
+        namespace: &str,
+        policies: &[String],
+    ) -> Result<Option<Response>, RvError> {
+        let mut paths = req
+            .get_data_or_default("paths")?
+            .as_comma_string_slice()
+            .unwrap_or_default();
+        paths.extend(
+            req.get_data_or_default("path")?
+                .as_comma_string_slice()
+                .unwrap_or_default(),
+        );
+        if paths.is_empty() {
+            return Err(rv_error_response_status!(400, "missing paths"));
+        }
+
+        let policy_module = self.get_module::<PolicyModule>("policy")?;
+        let data = policy_module
+            .capabilities(namespace, policies, &paths)
+            .await?;
+
+        Ok(Some(Response::data_response(Some(data))))
+    }
+
+    pub async fn handle_audit_table(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        root_namespace_only(req)?;
+        let mut data: Map<String, Value> = Map::new();
+
+        for entry in self.core.audit_broker.entries() {
+            let info: Value = json!({
+                "path": entry.path.clone(),
+                "type": entry.logical_type.clone(),
+                "description": entry.description.clone(),
+                "options": entry.options.clone(),
+            });
+            data.insert(entry.path.clone(), info);
+        }
+
+        Ok(Some(Response::data_response(Some(data))))
+    }
+
     pub async fn handle_audit_enable(
         &self,
         _backend: &dyn Backend,
-        _req: &mut Request,
+        req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
+        root_namespace_only(req)?;
+        let path = req.get_data("path")?;
+        let logical_type = req.get_data("type")?;
+        let description = req.get_data_or_default("description")?;
+        let options = req.get_data_or_default("options")?;
+
+        let path = path.as_str().ok_or(RvError::ErrRequestFieldInvalid)?;
+        let logical_type = logical_type
+            .as_str()
+            .ok_or(RvError::ErrRequestFieldInvalid)?;
+        let description = description.as_str().unwrap_or_default();
+
+        if path.is_empty() || logical_type.is_empty() {
+            return Err(RvError::ErrRequestInvalid);
+        }
+
+        let mut entry = AuditEntry::new(path, logical_type, description);
+        entry.options = options.as_map().unwrap_or_default();
+
+        self.core
+            .audit_broker
+            .enable(self.core.barrier.as_storage(), entry)
+            .await?;
         Ok(None)
     }
 
     pub async fn handle_audit_disable(
         &self,
         _backend: &dyn Backend,
-        _req: &mut Request,
+        req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
+        root_namespace_only(req)?;
+        let path = req.get_data("path")?;
+        let path = path.as_str().ok_or(RvError::ErrRequestFieldInvalid)?;
+        if path.is_empty() {
+            return Err(RvError::ErrRequestInvalid);
+        }
+
+        self.core
+            .audit_broker
+            .disable(self.core.barrier.as_storage(), path)
+            .await?;
         Ok(None)
     }
 
@@ -909,6 +1697,7 @@ impl SystemBackend {
         _backend: &dyn Backend,
         req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
+        root_namespace_only(req)?;
         let path = req.get_data("path")?;
 
         let path = path.as_str().unwrap();
@@ -932,6 +1721,7 @@ impl SystemBackend {
         _backend: &dyn Backend,
         req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
+        root_namespace_only(req)?;
         let path = req.get_data("path")?;
         let value = req.get_data("value")?;
 
@@ -953,6 +1743,7 @@ impl SystemBackend {
         _backend: &dyn Backend,
         req: &mut Request,
     ) -> Result<Option<Response>, RvError> {
+        root_namespace_only(req)?;
         let path = req.get_data("path")?;
 
         let path = path.as_str().unwrap();
@@ -991,7 +1782,7 @@ impl SystemBackend {
                     policy_module
                         .policy_store
                         .load()
-                        .new_acl(&auth.policies, None)
+                        .new_acl_for_auth(&auth)
                         .await?,
                 )
             }
@@ -1016,14 +1807,17 @@ impl SystemBackend {
         };
 
         let entries = self.core.mounts_router.entries.read()?;
-        for (path, entry) in entries.iter() {
+        for entry in entries.values() {
             let me = entry.read()?;
+            let Some(path) = self.namespace_mount_path(req, &me) else {
+                continue;
+            };
             if has_access(&me) {
                 if is_authed {
-                    secret_mounts.insert(path.clone(), Value::Object(self.mount_info(&me)));
+                    secret_mounts.insert(path, Value::Object(self.mount_info(&me)));
                 } else {
                     secret_mounts.insert(
-                        path.clone(),
+                        path,
                         json!({
                             "type": me.logical_type.clone(),
                             "description": me.description.clone(),
@@ -1035,14 +1829,17 @@ impl SystemBackend {
         }
 
         let entries = auth_module.mounts_router.entries.read()?;
-        for (path, entry) in entries.iter() {
+        for entry in entries.values() {
             let me = entry.read()?;
+            let Some(path) = self.namespace_mount_path(req, &me) else {
+                continue;
+            };
             if has_access(&me) {
                 if is_authed {
-                    auth_mounts.insert(path.clone(), Value::Object(self.mount_info(&me)));
+                    auth_mounts.insert(path, Value::Object(self.mount_info(&me)));
                 } else {
                     auth_mounts.insert(
-                        path.clone(),
+                        path,
                         json!({
                             "type": me.logical_type.clone(),
                             "description": me.description.clone(),
@@ -1071,10 +1868,13 @@ impl SystemBackend {
         let policy_module = self.get_module::<PolicyModule>("policy")?;
         let auth_module = self.get_module::<AuthModule>("auth")?;
 
-        let path = sanitize_path(
-            req.get_data("path")?
-                .as_str()
-                .ok_or(RvError::ErrRequestInvalid)?,
+        let path = scope_path(
+            &req.namespace,
+            &sanitize_path(
+                req.get_data("path")?
+                    .as_str()
+                    .ok_or(RvError::ErrRequestInvalid)?,
+            ),
         );
 
         if auth_module.token_store.load().is_none() {
@@ -1095,7 +1895,7 @@ impl SystemBackend {
                 policy_module
                     .policy_store
                     .load()
-                    .new_acl(&auth.policies, None)
+                    .new_acl_for_auth(&auth)
                     .await?
             }
         } else {
@@ -1126,6 +1926,107 @@ impl SystemBackend {
         Ok(Some(Response::data_response(Some(data))))
     }
 
+    pub async fn handle_namespace_list(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        root_namespace_only(req)?;
+
+        let keys: Vec<String> = self
+            .core
+            .namespaces
+            .list()
+            .into_iter()
+            .map(|ns| ns.path)
+            .collect();
+
+        Ok(Some(Response::list_response(&keys)))
+    }
+
+    pub async fn handle_namespace_read(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        root_namespace_only(req)?;
+
+        let path = req.get_data_as_str("path")?;
+        let Some(ns) = self.core.namespaces.get(&path) else {
+            return Ok(None);
+        };
+
+        Ok(Some(Response::data_response(Some(namespace_info(&ns)))))
+    }
+
+    pub async fn handle_namespace_create(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        root_namespace_only(req)?;
+
+        let path = req.get_data_as_str("path")?;
+        let ns = self.core.create_namespace(&path).await?;
+
+        Ok(Some(Response::data_response(Some(namespace_info(&ns)))))
+    }
+
+    pub async fn handle_namespace_delete(
+        &self,
+        _backend: &dyn Backend,
+        req: &mut Request,
+    ) -> Result<Option<Response>, RvError> {
+        root_namespace_only(req)?;
+
+        let path = req.get_data_as_str("path")?;
+        let ns = self.core.delete_namespace(&path).await?;
+
+        let policy_module = self.get_module::<PolicyModule>("policy")?;
+        policy_module
+            .policy_store
+            .load()
+            .delete_namespace_policies(&ns.path)
+            .await?;
+
+        Ok(None)
+    }
+
+    /// The path `entry` is mounted at as seen from the request's namespace, or `None` if it was
+    /// enabled in another namespace.
+    fn namespace_mount_path(&self, req: &Request, entry: &MountEntry) -> Option<String> {
+        if req.namespace.is_empty() {
+            return entry.namespace_id.is_empty().then(|| entry.path.clone());
+        }
+
+        let ns = self.core.namespaces.get(&req.namespace)?;
+        if entry.namespace_id != ns.id {
+            return None;
+        }
+
+        entry.path.strip_prefix(&ns.path).map(str::to_string)
+    }
+
+    /// Reject a lease id or prefix that lies outside the request's namespace.
+    fn check_namespace_path(&self, req: &Request, path: &str) -> Result<(), RvError> {
+        if req.namespace.is_empty() {
+            return Ok(());
+        }
+
+        match self.core.namespaces.get(&req.namespace) {
+            Some(ns) if ns.contains(path) => Ok(()),
+            _ => Err(RvError::ErrPermissionDenied),
+        }
+    }
+
+    fn expiration(&self) -> Result<Arc<ExpirationManager>, RvError> {
+        let auth_module = self.get_module::<AuthModule>("auth")?;
+        auth_module
+            .expiration
+            .load_full()
+            .ok_or(RvError::ErrBarrierSealed)
+    }
+
     fn get_module<T: Any + Send + Sync>(&self, name: &str) -> Result<Arc<T>, RvError> {
         if let Some(module) = self.core.module_manager.get_module::<T>(name) {
             return Ok(module);
@@ -1140,6 +2041,7 @@ impl SystemBackend {
             "description": entry.description.clone(),
             "uuid": entry.uuid.clone(),
             "options": entry.options.clone(),
+            "seal_wrap": entry.seal_wrap,
         })
         .as_object()
         .unwrap()
@@ -1183,6 +2085,25 @@ impl Module for SystemModule {
     }
 }
 
+/// Vault-wide operations can't be reached through a namespace's `sys/` mount.
+fn root_namespace_only(req: &Request) -> Result<(), RvError> {
+    if !req.namespace.is_empty() {
+        return Err(RvError::ErrNamespaceRootOnly);
+    }
+
+    Ok(())
+}
+
+fn namespace_info(ns: &Namespace) -> Map<String, Value> {
+    json!({
+        "id": ns.id.clone(),
+        "path": ns.path.clone(),
+    })
+    .as_object()
+    .unwrap()
+    .clone()
+}
+
 fn sanitize_path(path: &str) -> String {
     let mut new_path = path.to_string();
     if !new_path.ends_with('/') {
@@ -32,7 +32,12 @@ use crate::{
     errors::RvError,
     router::Router,
     storage::{Storage, StorageEntry, barrier::SecurityBarrier, barrier_view::BarrierView},
-    utils::{generate_uuid, is_protect_path},
+    utils::{
+        deserialize_duration, generate_uuid, is_protect_path,
+        password_hash::PasswordHashConfig,
+        serialize_duration,
+        token_util::{DEFAULT_LEASE_TTL, MAX_LEASE_TTL},
+    },
 };
 
 pub const LOGICAL_BARRIER_PREFIX: &str = "logical/";
@@ -41,7 +46,7 @@ pub const SYSTEM_BARRIER_PREFIX: &str = "sys/";
 pub const MOUNT_TABLE_TYPE: &str = "mounts";
 
 lazy_static! {
-    static ref PROTECTED_MOUNTS: Vec<&'static str> = vec!["audit/", "auth/", "sys/",];
+    static ref PROTECTED_MOUNTS: Vec<&'static str> = vec!["audit/", "auth/", "identity/", "sys/",];
     static ref DEFAULT_CORE_MOUNTS: Vec<MountEntry> = vec![
         MountEntry {
             table: MOUNT_TABLE_TYPE.to_string(),
@@ -84,6 +89,49 @@ pub struct MountsRouter {
     pub backends: DashMap<String, Arc<LogicalBackendNewFunc>>,
 }
 
+/// Settings of a mount that can be tuned after it is enabled. A zero TTL falls back to the system
+/// default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MountConfig {
+    #[serde(
+        default,
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub default_lease_ttl: Duration,
+    #[serde(
+        default,
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub max_lease_ttl: Duration,
+    /// KDF credential backends hash the secrets they store with. Only meaningful on auth mounts.
+    #[serde(default)]
+    pub password_hash: PasswordHashConfig,
+    /// Policies every token issued by a login through the mount carries on top of those of the
+    /// role or user. Only meaningful on auth mounts.
+    #[serde(default)]
+    pub default_policies: Vec<String>,
+    /// Failed logins after which an identity is locked out, 0 to disable. Only meaningful on
+    /// credential mounts that support lockout (userpass, cert).
+    #[serde(default)]
+    pub lockout_threshold: u32,
+    /// Span in which failures count towards the threshold, zero to count them until a success.
+    #[serde(
+        default,
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub lockout_window: Duration,
+    /// How long a locked out identity is refused.
+    #[serde(
+        default,
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub lockout_duration: Duration,
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct MountEntry {
     #[serde(default)]
@@ -96,6 +144,27 @@ pub struct MountEntry {
     pub options: Option<HashMap<String, String>>,
     #[serde(default)]
     pub hmac: String,
+    /// Id of the namespace the mount was enabled in, empty for the root namespace.
+    #[serde(default)]
+    pub namespace_id: String,
+    /// Whether values written by the mount get an extra layer of encryption on top of the
+    /// barrier's. See `BarrierView::set_seal_wrap`.
+    #[serde(default)]
+    pub seal_wrap: bool,
+    #[serde(default)]
+    pub config: MountConfig,
+}
+
+/// A mounted secrets engine or auth method, as listed by `sys/mounts` and `sys/auth`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MountInfo {
+    /// Mount path with a trailing `/`, relative to `auth/` for auth methods.
+    #[serde(default)]
+    pub path: String,
+    #[serde(rename = "type")]
+    pub mount_type: String,
+    pub description: String,
+    pub accessor: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -130,12 +199,13 @@ impl MountsRouter {
 
         for mount_entry in mounts.values() {
             let entry = mount_entry.read()?;
-            let barrier_path = format!("{}{}/", self.barrier_prefix, &entry.uuid);
+            let barrier_path = entry.barrier_path(&self.barrier_prefix);
 
             let backend_new_func = self.get_backend(&entry.logical_type)?;
             let backend = backend_new_func(core.clone())?;
 
             let view = BarrierView::new(self.barrier.clone(), &barrier_path);
+            view.set_seal_wrap(entry.seal_wrap);
             let path = format!("{}{}", self.router_prefix, &entry.path);
 
             self.router
@@ -195,6 +265,45 @@ impl MountEntry {
             description: desc.to_string(),
             options: None,
             hmac: String::new(),
+            namespace_id: String::new(),
+            seal_wrap: false,
+            config: MountConfig::default(),
+        }
+    }
+
+    /// TTL given to leases of the mount that don't ask for one.
+    pub fn default_lease_ttl(&self) -> Duration {
+        let ttl = if self.config.default_lease_ttl.is_zero() {
+            DEFAULT_LEASE_TTL
+        } else {
+            self.config.default_lease_ttl
+        };
+        ttl.min(self.max_lease_ttl())
+    }
+
+    /// Longest TTL a lease of the mount may have, renewals included.
+    pub fn max_lease_ttl(&self) -> Duration {
+        if self.config.max_lease_ttl.is_zero() {
+            MAX_LEASE_TTL
+        } else {
+            self.config.max_lease_ttl
+        }
+    }
+
+    /// Non-secret handle on the mount, e.g. `auth_userpass_1c2d3e4f`, used by identity aliases to
+    /// refer to it.
+    pub fn accessor(&self) -> String {
+        let suffix = self.uuid.split('-').next().unwrap_or_default();
+        format!("{}_{}_{}", self.table, self.logical_type, suffix)
+    }
+
+    /// Where the mount keeps its data: `<prefix><uuid>/`, with the namespace id in between for a
+    /// mount that belongs to a namespace.
+    pub fn barrier_path(&self, prefix: &str) -> String {
+        if self.namespace_id.is_empty() {
+            format!("{}{}/", prefix, self.uuid)
+        } else {
+            format!("{}{}/{}/", prefix, self.namespace_id, self.uuid)
         }
     }
 
@@ -233,6 +342,22 @@ impl MountEntry {
             }
         }
 
+        if !self.namespace_id.is_empty() {
+            msg = format!("{msg}-ns:{}", self.namespace_id);
+        }
+
+        if self.seal_wrap {
+            msg = format!("{msg}-seal_wrap");
+        }
+
+        if self.config != MountConfig::default() {
+            msg = format!(
+                "{msg}-ttl:{}/{}",
+                self.config.default_lease_ttl.as_secs(),
+                self.config.max_lease_ttl.as_secs()
+            );
+        }
+
         msg
     }
 }
@@ -447,6 +572,8 @@ impl MountsMonitor {
         }
 
         self.running.store(true, Ordering::Relaxed);
+        // clear the flag a previous `stop` left set
+        *self.stop_condvar.0.lock().unwrap() = false;
         let running_flag = self.running.clone();
         let stop_condvar = self.stop_condvar.clone();
 
@@ -518,6 +645,16 @@ impl MountsMonitor {
 
 impl Core {
     pub async fn mount(&self, me: &MountEntry) -> Result<(), RvError> {
+        self.mount_internal(me, false).await
+    }
+
+    /// Mount `me`. A mount whose path falls under a namespace belongs to that namespace, and
+    /// `allow_protected` is only set for the `sys/` mount a new namespace gets.
+    pub(crate) async fn mount_internal(
+        &self,
+        me: &MountEntry,
+        allow_protected: bool,
+    ) -> Result<(), RvError> {
         {
             let mut table = self.mounts_router.entries.write()?;
             let mut entry = me.clone();
@@ -526,7 +663,7 @@ impl Core {
                 entry.path += "/";
             }
 
-            if is_protect_path(&PROTECTED_MOUNTS, &[&entry.path]) {
+            if !allow_protected && self.is_protected_mount(&entry.path) {
                 return Err(RvError::ErrMountPathProtected);
             }
 
@@ -543,9 +680,15 @@ impl Core {
             let backend = backend_new_func(self.self_ptr.upgrade().unwrap().clone())?;
 
             entry.uuid = generate_uuid();
+            entry.namespace_id = self
+                .namespaces
+                .owner(&entry.path)
+                .map(|ns| ns.id)
+                .unwrap_or_default();
 
-            let prefix = format!("{}{}/", LOGICAL_BARRIER_PREFIX, &entry.uuid);
+            let prefix = entry.barrier_path(LOGICAL_BARRIER_PREFIX);
             let view = BarrierView::new(self.barrier.clone(), &prefix);
+            view.set_seal_wrap(entry.seal_wrap);
 
             let path = entry.path.clone();
 
@@ -567,12 +710,20 @@ impl Core {
     }
 
     pub async fn unmount(&self, path: &str) -> Result<(), RvError> {
+        self.unmount_internal(path, false).await
+    }
+
+    pub(crate) async fn unmount_internal(
+        &self,
+        path: &str,
+        allow_protected: bool,
+    ) -> Result<(), RvError> {
         let mut path = path.to_string();
         if !path.ends_with('/') {
             path += "/";
         }
 
-        if is_protect_path(&PROTECTED_MOUNTS, &[&path]) {
+        if !allow_protected && self.is_protected_mount(&path) {
             return Err(RvError::ErrMountPathProtected);
         }
 
@@ -598,6 +749,67 @@ impl Core {
         Ok(())
     }
 
+    /// Turn on seal wrapping for the existing mount at `path`. Entries already stored stay readable
+    /// and are wrapped as they are next written.
+    pub async fn enable_seal_wrap(&self, path: &str) -> Result<(), RvError> {
+        let mut path = path.to_string();
+        if !path.ends_with('/') {
+            path += "/";
+        }
+
+        let Some(mount_entry) = self.mounts_router.get(&path)? else {
+            return Err(RvError::ErrMountNotMatch);
+        };
+
+        {
+            let mut entry = mount_entry.write()?;
+            if entry.seal_wrap {
+                return Ok(());
+            }
+            entry.seal_wrap = true;
+            entry.calc_hmac(&self.state.load().hmac_key)?;
+        }
+
+        if let Some(view) = self.router.matching_view(&path)? {
+            view.set_seal_wrap(true);
+        }
+
+        self.mounts_router.persist(self.barrier.as_storage()).await
+    }
+
+    /// Replace the tunable settings of the mount at `path`, and its description unless that is
+    /// `None`.
+    pub async fn tune_mount(
+        &self,
+        path: &str,
+        config: MountConfig,
+        description: Option<String>,
+    ) -> Result<(), RvError> {
+        let mut path = path.to_string();
+        if !path.ends_with('/') {
+            path += "/";
+        }
+
+        if !config.max_lease_ttl.is_zero() && config.default_lease_ttl > config.max_lease_ttl {
+            return Err(RvError::ErrRequestFieldInvalid);
+        }
+
+        let Some(mount_entry) = self.mounts_router.get(&path)? else {
+            return Err(RvError::ErrMountNotMatch);
+        };
+
+        {
+            let mut entry = mount_entry.write()?;
+            entry.config = config;
+            if let Some(description) = description {
+                entry.description = description;
+            }
+            entry.calc_hmac(&self.state.load().hmac_key)?;
+        }
+
+        self.mounts_router.persist(self.barrier.as_storage()).await
+    }
+
     pub async fn remount(&self, src: &str, dst: &str) -> Result<(), RvError> {
         let mut src = src.to_string();
         let mut dst = dst.to_string();
@@ -610,7 +822,13 @@ impl Core {
             dst += "/";
         }
 
-        if is_protect_path(&PROTECTED_MOUNTS, &[&src, &dst]) {
+        if self.is_protected_mount(&src) || self.is_protected_mount(&dst) {
+            return Err(RvError::ErrMountPathProtected);
+        }
+
+        // A mount can't be moved between namespaces; its data is stored under the namespace id.
+        let namespace_of = |path: &str| self.namespaces.owner(path).map(|ns| ns.id);
+        if namespace_of(&src) != namespace_of(&dst) {
             return Err(RvError::ErrMountPathProtected);
         }
 
@@ -657,6 +875,16 @@ impl Core {
         Ok(())
     }
 
+    /// Whether `path` is one of the built-in mounts, either at the root or inside a namespace, or
+    /// the namespace prefix itself.
+    fn is_protected_mount(&self, path: &str) -> bool {
+        let relative = match self.namespaces.owner(path) {
+            Some(ns) => &path[ns.path.len()..],
+            None => path,
+        };
+        relative.is_empty() || is_protect_path(&PROTECTED_MOUNTS, &[relative])
+    }
+
     pub fn unload_mounts(&self) -> Result<(), RvError> {
         let _ = self.router.clear();
         let _ = self.mounts_router.clear();
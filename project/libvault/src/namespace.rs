@@ -0,0 +1,202 @@
+//! Namespaces partition one RustyVault between tenants.
+//!
+//! A namespace is a single path segment such as `team-a/`. `Core` rewrites the path of every
+//! request made in a namespace before routing it: `secret/foo` becomes `team-a/secret/foo` and
+//! `auth/userpass/login` becomes `auth/team-a/userpass/login`. Mounts enabled from inside the
+//! namespace therefore live under its prefix, and policies written there are scoped the same way,
+//! so nothing outside `team-a/` can be reached from it. Each namespace gets its own `sys/` mount;
+//! the token store at `auth/token/` is shared by all of them.
+//!
+//! The namespace table itself is managed through `sys/namespaces` in the root namespace.
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::Core,
+    errors::RvError,
+    modules::auth::AUTH_ROUTER_PREFIX,
+    mount::{MOUNT_TABLE_TYPE, MountEntry},
+    storage::{Storage, StorageEntry},
+    utils::generate_uuid,
+};
+
+pub const CORE_NAMESPACE_CONFIG_PATH: &str = "core/namespaces";
+
+/// Top-level paths a namespace can't be named after, as they would shadow root mounts.
+const RESERVED_NAMESPACES: [&str; 4] = ["audit/", "auth/", "identity/", "sys/"];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Namespace {
+    pub id: String,
+    pub path: String,
+}
+
+#[derive(Default)]
+pub struct NamespaceStore {
+    namespaces: DashMap<String, Namespace>,
+}
+
+impl Namespace {
+    pub fn new(path: &str) -> Result<Self, RvError> {
+        Ok(Self {
+            id: generate_uuid(),
+            path: sanitize_path(path)?,
+        })
+    }
+
+    /// Whether the (already scoped) request or lease path `path` belongs to this namespace.
+    pub fn contains(&self, path: &str) -> bool {
+        path.starts_with(&self.path)
+            || path
+                .strip_prefix(AUTH_ROUTER_PREFIX)
+                .is_some_and(|p| p.starts_with(&self.path))
+    }
+}
+
+impl NamespaceStore {
+    pub fn get(&self, path: &str) -> Option<Namespace> {
+        let path = sanitize_path(path).ok()?;
+        self.namespaces.get(&path).map(|ns| ns.clone())
+    }
+
+    /// The namespace whose prefix `path` starts with, if any. `path` is a mount path or policy name
+    /// in scoped form, e.g. `team-a/secret/`.
+    pub fn owner(&self, path: &str) -> Option<Namespace> {
+        let (segment, _) = path.split_once('/')?;
+        self.namespaces
+            .get(&format!("{segment}/"))
+            .map(|ns| ns.clone())
+    }
+
+    pub fn list(&self) -> Vec<Namespace> {
+        let mut namespaces: Vec<Namespace> = self.namespaces.iter().map(|ns| ns.clone()).collect();
+        namespaces.sort_by(|a, b| a.path.cmp(&b.path));
+        namespaces
+    }
+
+    pub fn clear(&self) {
+        self.namespaces.clear();
+    }
+
+    pub async fn load(&self, storage: &dyn Storage) -> Result<(), RvError> {
+        self.namespaces.clear();
+
+        let Some(entry) = storage.get(CORE_NAMESPACE_CONFIG_PATH).await? else {
+            return Ok(());
+        };
+
+        let namespaces: Vec<Namespace> = serde_json::from_slice(&entry.value)?;
+        for ns in namespaces {
+            self.namespaces.insert(ns.path.clone(), ns);
+        }
+
+        Ok(())
+    }
+
+    pub async fn persist(&self, storage: &dyn Storage) -> Result<(), RvError> {
+        let entry = StorageEntry::new(CORE_NAMESPACE_CONFIG_PATH, &self.list())?;
+        storage.put(&entry).await
+    }
+}
+
+impl Core {
+    /// Create the namespace `path` along with its `sys/` mount.
+    pub async fn create_namespace(&self, path: &str) -> Result<Namespace, RvError> {
+        let ns = Namespace::new(path)?;
+        if self.namespaces.get(&ns.path).is_some() {
+            return Err(RvError::ErrNamespaceExist);
+        }
+
+        // A root mount already at or below the namespace prefix would become unreachable.
+        let auth_path = format!("{AUTH_ROUTER_PREFIX}{}", ns.path);
+        if !self.router.matching_mount(&ns.path)?.is_empty()
+            || !self.router.mounts_under(&ns.path)?.is_empty()
+            || !self.router.mounts_under(&auth_path)?.is_empty()
+        {
+            return Err(RvError::ErrNamespaceExist);
+        }
+
+        self.namespaces
+            .namespaces
+            .insert(ns.path.clone(), ns.clone());
+
+        let sys = MountEntry::new(
+            MOUNT_TABLE_TYPE,
+            &format!("{}sys/", ns.path),
+            "system",
+            "system endpoints used for control, policy and debugging",
+        );
+        let ret = match self.mount_internal(&sys, true).await {
+            Ok(()) => self.namespaces.persist(self.barrier.as_storage()).await,
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = ret {
+            self.namespaces.namespaces.remove(&ns.path);
+            return Err(e);
+        }
+
+        Ok(ns)
+    }
+
+    /// Delete the namespace `path`. Every secret engine and auth method enabled in it must have
+    /// been disabled first.
+    pub async fn delete_namespace(&self, path: &str) -> Result<Namespace, RvError> {
+        let ns = self
+            .namespaces
+            .get(path)
+            .ok_or(RvError::ErrNamespaceNotFound)?;
+
+        let sys_path = format!("{}sys/", ns.path);
+        let auth_path = format!("{AUTH_ROUTER_PREFIX}{}", ns.path);
+        let mounts = self.router.mounts_under(&ns.path)?;
+        if mounts.iter().any(|m| *m != sys_path)
+            || !self.router.mounts_under(&auth_path)?.is_empty()
+        {
+            return Err(RvError::ErrNamespaceNotEmpty);
+        }
+
+        if !mounts.is_empty() {
+            self.unmount_internal(&sys_path, true).await?;
+        }
+
+        self.namespaces.namespaces.remove(&ns.path);
+        self.namespaces.persist(self.barrier.as_storage()).await?;
+
+        Ok(ns)
+    }
+}
+
+/// Normalize a namespace path to `name/`. Namespaces are a single lower-case segment.
+pub fn sanitize_path(path: &str) -> Result<String, RvError> {
+    let name = path.trim_matches('/');
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err(RvError::ErrNamespacePathInvalid);
+    }
+
+    let path = format!("{name}/");
+    if RESERVED_NAMESPACES.contains(&path.as_str()) {
+        return Err(RvError::ErrNamespacePathInvalid);
+    }
+
+    Ok(path)
+}
+
+/// Map `path`, as seen from inside `namespace`, to the path it has in the router.
+pub fn scope_path(namespace: &str, path: &str) -> String {
+    if namespace.is_empty() {
+        return path.to_string();
+    }
+
+    match path.strip_prefix(AUTH_ROUTER_PREFIX) {
+        // The token store is shared by every namespace.
+        Some(rest) if rest == "token" || rest.starts_with("token/") => path.to_string(),
+        Some(rest) => format!("{AUTH_ROUTER_PREFIX}{namespace}{rest}"),
+        None => format!("{namespace}{path}"),
+    }
+}
@@ -123,6 +123,15 @@ impl Router {
         }
     }
 
+    /// Every mount point at or below `prefix`.
+    pub fn mounts_under(&self, prefix: &str) -> Result<Vec<String>, RvError> {
+        let root = self.root.read()?;
+        Ok(root
+            .get_raw_descendant(prefix)
+            .map(|sub| sub.keys().cloned().collect())
+            .unwrap_or_default())
+    }
+
     pub fn matching_mount_entry(
         &self,
         path: &str,
@@ -228,6 +237,7 @@ impl Router {
             if req.path == "/" {
                 req.path = String::new();
             }
+            req.mount_point = mount.to_string();
 
             req.storage = Some(me.view.clone());
 
@@ -241,6 +251,7 @@ impl Router {
         let response = backend.handle_request(req).await?;
 
         req.path = original;
+        req.mount_point = String::new();
         req.connection = original_conn;
         req.storage = None;
         req.client_token = client_token;
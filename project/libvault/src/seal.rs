@@ -0,0 +1,116 @@
+//! Auto-unseal support.
+//!
+//! Instead of splitting the key encryption key (KEK) into Shamir shares, the KEK can be wrapped by
+//! a transit key living in another vault. The wrapped blob is kept in physical storage, and on
+//! startup `Core::auto_unseal` asks the remote transit engine to unwrap it.
+//!
+//! `SealCipher` selects the AEAD the barrier wraps the master key with.
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use zeroize::Zeroizing;
+
+use crate::errors::RvError;
+
+/// AEAD used by the barrier, chosen once at init.
+///
+/// The chosen cipher is recorded in the header of every sealed blob, so unsealing never depends on
+/// the current default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SealCipher {
+    #[default]
+    Aes256Gcm,
+    ChaCha20Poly1305,
+}
+
+fn default_mount_path() -> String {
+    "transit".to_string()
+}
+
+/// Where to find the transit key that wraps the KEK.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransitSealConfig {
+    /// Address of the remote vault, e.g. `https://vault.example.com:8200`.
+    pub endpoint: String,
+    /// Token allowed to call `<mount_path>/encrypt/<key_name>` and `<mount_path>/decrypt/<key_name>`.
+    /// Never stored with the seal config: the core fills it in from the server config's
+    /// `transit_seal_token`, and `VAULT_TRANSIT_SEAL_TOKEN`, when set, takes precedence.
+    #[serde(skip)]
+    pub token: String,
+    pub key_name: String,
+    #[serde(default = "default_mount_path")]
+    pub mount_path: String,
+}
+
+impl TransitSealConfig {
+    pub fn validate(&self) -> Result<(), RvError> {
+        if self.endpoint.is_empty() || self.key_name.is_empty() || self.mount_path.is_empty() {
+            return Err(RvError::ErrCoreSealConfigInvalid);
+        }
+
+        Ok(())
+    }
+
+    fn url(&self, op: &str) -> String {
+        format!(
+            "{}/v1/{}/{}/{}",
+            self.endpoint.trim_end_matches('/'),
+            self.mount_path.trim_matches('/'),
+            op,
+            self.key_name
+        )
+    }
+
+    async fn call(&self, op: &str, body: Value) -> Result<Value, RvError> {
+        let token = std::env::var("VAULT_TRANSIT_SEAL_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty())
+            .unwrap_or_else(|| self.token.clone());
+        if token.is_empty() {
+            return Err(RvError::ErrCoreSealConfigInvalid);
+        }
+        let resp = reqwest::Client::new()
+            .post(self.url(op))
+            .header("X-Vault-Token", token)
+            .json(&body)
+            .send()
+            .await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return Err(RvError::ErrResponseStatus(
+                status.as_u16(),
+                resp.text().await.unwrap_or_default(),
+            ));
+        }
+
+        let mut body: Value = resp.json().await?;
+        Ok(body["data"].take())
+    }
+
+    /// Encrypt the KEK with the remote transit key, returning the `vault:v<N>:...` ciphertext.
+    pub async fn wrap(&self, kek: &[u8]) -> Result<String, RvError> {
+        let data = self
+            .call("encrypt", json!({ "plaintext": STANDARD.encode(kek) }))
+            .await?;
+        data["ciphertext"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or(RvError::ErrCoreSealTransitResponseInvalid)
+    }
+
+    /// Ask the remote transit key to decrypt a KEK previously produced by `wrap`.
+    pub async fn unwrap(&self, ciphertext: &str) -> Result<Zeroizing<Vec<u8>>, RvError> {
+        let data = self
+            .call("decrypt", json!({ "ciphertext": ciphertext }))
+            .await?;
+        let plaintext = data["plaintext"]
+            .as_str()
+            .ok_or(RvError::ErrCoreSealTransitResponseInvalid)?;
+        let kek = STANDARD
+            .decode(plaintext)
+            .map_err(|_| RvError::ErrCoreSealTransitResponseInvalid)?;
+        Ok(Zeroizing::new(kek))
+    }
+}
@@ -1,5 +1,10 @@
 //! A Shamir threshold algorithm implementation which is used to derive the RustyVault master key.
 //!
+//! Besides the `ShamirSecret` primitive used by unseal, the module offers `split` and `combine`
+//! for splitting arbitrary secrets. Their shares carry the threshold and an integrity tag of the
+//! secret, so combining too few, mismatched or tampered shares fails instead of returning
+//! garbage.
+//!
 //! This code is originated from Chris MacNaughton, we modified it to be compatible with Vault's
 //! Shamir algorithm.
 //!
@@ -29,6 +34,7 @@
 
 use std::ops::DerefMut;
 
+use blake2b_simd::Params;
 use rand::{RngCore, rng};
 use zeroize::Zeroizing;
 
@@ -74,6 +80,85 @@ static GF256_LOG: [u8; 256] = [
 
 pub const SHAMIR_OVERHEAD: usize = 1;
 
+/// Largest secret accepted by `split`.
+pub const MAX_SECRET_LEN: usize = 64 * 1024;
+
+/// Length of the integrity tag appended to the secret before splitting.
+const TAG_LEN: usize = 16;
+
+/// Split `secret` into `shares` shares, any `threshold` of which recover it with `combine`.
+///
+/// Each share is laid out as `threshold || share of (secret || tag) || id`, with a tag of 16
+/// bytes, so shares are 18 bytes longer than the secret. `threshold` must be at least 2 and at
+/// most `shares`, `shares` at most 254, and the secret non-empty and at most `MAX_SECRET_LEN`
+/// bytes.
+pub fn split(secret: &[u8], shares: u8, threshold: u8) -> Result<Vec<Vec<u8>>, RvError> {
+    if secret.is_empty() || secret.len() > MAX_SECRET_LEN {
+        return Err(RvError::ErrShamirSecretInvalid);
+    }
+    if threshold < 2 || shares < threshold || shares == 255 {
+        return Err(RvError::ErrShamirShareCountInvalid);
+    }
+
+    let mut payload = Zeroizing::new(Vec::with_capacity(secret.len() + TAG_LEN));
+    payload.extend_from_slice(secret);
+    payload.extend_from_slice(secret_tag(secret).as_bytes());
+
+    let parts = ShamirSecret::split(&payload, shares, threshold)?;
+    Ok(parts
+        .iter()
+        .map(|part| {
+            let mut share = Vec::with_capacity(part.len() + 1);
+            share.push(threshold);
+            share.extend_from_slice(part);
+            share
+        })
+        .collect())
+}
+
+/// Recover the secret from shares produced by `split`.
+///
+/// Fails with `ErrShamirShareCountInvalid` when fewer shares than their threshold are given, and
+/// with `ErrShamirSharesInvalid` when the shares are malformed, come from different splits or do
+/// not reproduce the integrity tag.
+pub fn combine(shares: &[Vec<u8>]) -> Result<Zeroizing<Vec<u8>>, RvError> {
+    let first = shares.first().ok_or(RvError::ErrShamirShareCountInvalid)?;
+    // threshold, at least one secret byte, the tag and the id
+    if first.len() < TAG_LEN + 3 {
+        return Err(RvError::ErrShamirSharesInvalid);
+    }
+
+    let threshold = first[0];
+    if threshold < 2 {
+        return Err(RvError::ErrShamirSharesInvalid);
+    }
+    if shares.iter().any(|share| {
+        share.len() != first.len() || share[0] != threshold || share[share.len() - 1] == 0
+    }) {
+        return Err(RvError::ErrShamirSharesInvalid);
+    }
+    if shares.len() < threshold as usize {
+        return Err(RvError::ErrShamirShareCountInvalid);
+    }
+
+    let parts = shares.iter().map(|share| share[1..].to_vec()).collect();
+    let payload =
+        Zeroizing::new(ShamirSecret::combine(parts).ok_or(RvError::ErrShamirSharesInvalid)?);
+    let (secret, tag) = payload.split_at(payload.len() - TAG_LEN);
+    // Hash compares in constant time
+    if secret_tag(secret) != *tag {
+        return Err(RvError::ErrShamirSharesInvalid);
+    }
+    Ok(Zeroizing::new(secret.to_vec()))
+}
+
+fn secret_tag(secret: &[u8]) -> blake2b_simd::Hash {
+    Params::new()
+        .hash_length(TAG_LEN)
+        .personal(b"rv-shamir-tag")
+        .hash(secret)
+}
+
 pub struct ShamirSecret {
     pub coefficients: Vec<Vec<u8>>,
 }
@@ -314,3 +399,98 @@ impl ShamirSecret {
         results
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SECRET: &[u8] = b"correct horse battery staple";
+
+    #[test]
+    fn test_split_combine_round_trip() {
+        for (shares, threshold) in [(2, 2), (3, 2), (5, 3), (10, 10), (20, 7)] {
+            let parts = split(SECRET, shares, threshold).unwrap();
+            assert_eq!(parts.len(), shares as usize);
+            assert!(parts.iter().all(|p| p.len() == SECRET.len() + TAG_LEN + 2));
+
+            let threshold = threshold as usize;
+            assert_eq!(combine(&parts).unwrap().as_slice(), SECRET);
+            assert_eq!(combine(&parts[..threshold]).unwrap().as_slice(), SECRET);
+            let tail = &parts[parts.len() - threshold..];
+            assert_eq!(combine(tail).unwrap().as_slice(), SECRET);
+        }
+
+        let secret: Vec<u8> = (0..=255).cycle().take(1024).collect();
+        let parts = split(&secret, 3, 2).unwrap();
+        assert_eq!(*combine(&parts[1..]).unwrap(), secret);
+        assert!(split(&vec![0xa5; MAX_SECRET_LEN], 3, 2).is_ok());
+    }
+
+    #[test]
+    fn test_split_rejects_invalid_input() {
+        assert_eq!(split(b"", 3, 2), Err(RvError::ErrShamirSecretInvalid));
+        let too_long = vec![0; MAX_SECRET_LEN + 1];
+        assert_eq!(split(&too_long, 3, 2), Err(RvError::ErrShamirSecretInvalid));
+
+        for (shares, threshold) in [(3, 0), (3, 1), (2, 3), (0, 0), (255, 2)] {
+            assert_eq!(
+                split(SECRET, shares, threshold),
+                Err(RvError::ErrShamirShareCountInvalid)
+            );
+        }
+    }
+
+    #[test]
+    fn test_combine_rejects_too_few_shares() {
+        let parts = split(SECRET, 5, 3).unwrap();
+        assert_eq!(combine(&[]), Err(RvError::ErrShamirShareCountInvalid));
+        assert_eq!(
+            combine(&parts[..2]),
+            Err(RvError::ErrShamirShareCountInvalid)
+        );
+
+        // lowering the recorded threshold does not make two shares enough
+        let forged: Vec<Vec<u8>> = parts[..2]
+            .iter()
+            .map(|p| {
+                let mut p = p.clone();
+                p[0] = 2;
+                p
+            })
+            .collect();
+        assert_eq!(combine(&forged), Err(RvError::ErrShamirSharesInvalid));
+    }
+
+    #[test]
+    fn test_combine_rejects_tampered_shares() {
+        let parts = split(SECRET, 3, 2).unwrap();
+
+        let mut tampered = parts[..2].to_vec();
+        tampered[1][3] ^= 0x01;
+        assert_eq!(combine(&tampered), Err(RvError::ErrShamirSharesInvalid));
+
+        // a corrupted extra share is detected too
+        let mut tampered = parts.clone();
+        tampered[2][1] ^= 0x80;
+        assert_eq!(combine(&tampered), Err(RvError::ErrShamirSharesInvalid));
+
+        let duplicated = vec![parts[0].clone(), parts[0].clone()];
+        assert_eq!(combine(&duplicated), Err(RvError::ErrShamirSharesInvalid));
+
+        let mut zero_id = parts[..2].to_vec();
+        *zero_id[0].last_mut().unwrap() = 0;
+        assert_eq!(combine(&zero_id), Err(RvError::ErrShamirSharesInvalid));
+
+        let mut truncated = parts[..2].to_vec();
+        truncated[1].pop();
+        assert_eq!(combine(&truncated), Err(RvError::ErrShamirSharesInvalid));
+        assert_eq!(
+            combine(&[vec![2, 1], vec![2, 2]]),
+            Err(RvError::ErrShamirSharesInvalid)
+        );
+
+        let other = split(SECRET, 3, 2).unwrap();
+        let mixed = vec![parts[0].clone(), other[1].clone()];
+        assert_eq!(combine(&mixed), Err(RvError::ErrShamirSharesInvalid));
+    }
+}
@@ -9,19 +9,27 @@ use async_trait::async_trait;
 use zeroize::Zeroizing;
 
 use super::Storage;
-use crate::errors::RvError;
+use crate::{errors::RvError, seal::SealCipher};
 
 pub const BARRIER_INIT_PATH: &str = "barrier/init";
 
 #[async_trait]
 pub trait SecurityBarrier: Storage + Send + Sync {
     async fn inited(&self) -> Result<bool, RvError>;
-    async fn init(&self, key: &[u8]) -> Result<(), RvError>;
+    async fn init(&self, key: &[u8], cipher: SealCipher) -> Result<(), RvError>;
     fn generate_key(&self) -> Result<Zeroizing<Vec<u8>>, RvError>;
     fn key_length_range(&self) -> (usize, usize);
     fn sealed(&self) -> Result<bool, RvError>;
     async fn unseal(&self, key: &[u8]) -> Result<(), RvError>;
+    /// Re-encrypt the keyring of an unsealed barrier under a new key encryption key. The data
+    /// encryption key is kept, so nothing else has to be rewritten.
+    async fn rekey(&self, key: &[u8]) -> Result<(), RvError>;
     fn seal(&self) -> Result<(), RvError>;
     fn derive_hmac_key(&self) -> Result<Vec<u8>, RvError>;
+    /// Encrypt `plaintext` a second time, under a key derived from the encryption key, before it
+    /// is handed to `put`. Used for the values of seal-wrapped mounts.
+    fn seal_wrap(&self, path: &str, plaintext: &[u8]) -> Result<Vec<u8>, RvError>;
+    /// Reverse `seal_wrap`.
+    fn seal_unwrap(&self, path: &str, ciphertext: &[u8]) -> Result<Vec<u8>, RvError>;
     fn as_storage(&self) -> &dyn Storage;
 }
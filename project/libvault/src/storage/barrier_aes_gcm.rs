@@ -1,5 +1,8 @@
 //! This is the implementation of aes-gcm barrier, which uses aes-gcm block cipher to encrypt or
 //! decrypt data before writing or reading data to or from specific storage backend.
+//!
+//! ChaCha20-Poly1305 can be selected instead at init. The version byte of every blob names the
+//! cipher it was written with, so blobs of either kind are always readable.
 
 use std::{
     any::Any,
@@ -11,6 +14,8 @@ use arc_swap::ArcSwap;
 use better_default::Default;
 use openssl::{
     hash::{MessageDigest, hash},
+    pkey::PKey,
+    sign::Signer,
     symm::{Cipher, Crypter, Mode},
 };
 use rand::{Rng, rng};
@@ -21,13 +26,15 @@ use super::{
     Backend, BackendEntry, Storage, StorageEntry,
     barrier::{BARRIER_INIT_PATH, SecurityBarrier},
 };
-use crate::errors::RvError;
+use crate::{errors::RvError, seal::SealCipher};
 
 const EPOCH_SIZE: usize = 4;
 const KEY_EPOCH: u8 = 1;
 const AES_GCM_VERSION1: u8 = 0x1;
 const AES_GCM_VERSION2: u8 = 0x2;
+const CHACHA20_POLY1305_VERSION: u8 = 0x3;
 const AES_BLOCK_SIZE: usize = 16;
+const SEAL_WRAP_KEY_CONTEXT: &[u8] = b"seal-wrap";
 
 // the BarrierInit structure contains the encryption key, so it's zeroized anyway
 // when it's dropped
@@ -46,7 +53,7 @@ struct BarrierInfo {
     sealed: bool,
     key: Option<Vec<u8>>,
     #[default(AES_GCM_VERSION2)]
-    aes_gcm_version_byte: u8,
+    version_byte: u8,
 }
 
 pub struct AESGCMBarrier {
@@ -115,6 +122,35 @@ impl Storage for AESGCMBarrier {
     async fn lock(&self, lock_name: &str) -> Result<Box<dyn Any>, RvError> {
         self.backend.lock(lock_name).await
     }
+
+    async fn compare_and_swap(
+        &self,
+        entry: &StorageEntry,
+        expected: Option<&[u8]>,
+    ) -> Result<bool, RvError> {
+        if self.barrier_info.load().sealed {
+            return Err(RvError::ErrBarrierSealed);
+        }
+
+        // Encryption is randomized, so compare the plaintext and swap against the ciphertext it
+        // was decrypted from.
+        let current = self.backend.get(&entry.key).await?;
+        let plain = current
+            .as_ref()
+            .map(|pe| self.decrypt(&entry.key, pe.value.as_slice()))
+            .transpose()?;
+        if plain.as_deref() != expected {
+            return Ok(false);
+        }
+
+        let be = BackendEntry {
+            key: entry.key.clone(),
+            value: self.encrypt(&entry.key, entry.value.as_slice())?,
+        };
+        self.backend
+            .compare_and_swap(&be, current.as_ref().map(|pe| pe.value.as_slice()))
+            .await
+    }
 }
 
 #[async_trait::async_trait]
@@ -127,7 +163,7 @@ impl SecurityBarrier for AESGCMBarrier {
     // kek stands for key encryption key, it's used to encrypt the actual
     // encryption key, which is generated during the init() process.
     // The kek's zerization is handled in the caller.
-    async fn init(&self, kek: &[u8]) -> Result<(), RvError> {
+    async fn init(&self, kek: &[u8], cipher: SealCipher) -> Result<(), RvError> {
         let (min, max) = self.key_length_range();
         if kek.len() < min || kek.len() > max {
             return Err(RvError::ErrBarrierKeyInvalid);
@@ -150,6 +186,10 @@ impl SecurityBarrier for AESGCMBarrier {
         let serialized_barrier_init = serde_json::to_string(&barrier_init)?;
 
         self.init_cipher(kek)?;
+        self.set_version_byte(match cipher {
+            SealCipher::Aes256Gcm => AES_GCM_VERSION2,
+            SealCipher::ChaCha20Poly1305 => CHACHA20_POLY1305_VERSION,
+        });
 
         let value = self.encrypt(BARRIER_INIT_PATH, serialized_barrier_init.as_bytes())?;
 
@@ -193,9 +233,21 @@ impl SecurityBarrier for AESGCMBarrier {
             return Err(RvError::ErrBarrierNotInit);
         }
 
+        let entry = entry.unwrap();
+        if entry.value.len() <= EPOCH_SIZE {
+            return Err(RvError::ErrBarrierUnsealFailed);
+        }
+
+        // Keep writing with the cipher the keyring was sealed with.
+        self.set_version_byte(match entry.value[EPOCH_SIZE] {
+            AES_GCM_VERSION1 | AES_GCM_VERSION2 => AES_GCM_VERSION2,
+            CHACHA20_POLY1305_VERSION => CHACHA20_POLY1305_VERSION,
+            _ => return Err(RvError::ErrBarrierVersionMismatch),
+        });
+
         self.init_cipher(kek)?;
 
-        let value = self.decrypt(BARRIER_INIT_PATH, entry.unwrap().value.as_slice());
+        let value = self.decrypt(BARRIER_INIT_PATH, entry.value.as_slice());
         if value.is_err() {
             return Err(RvError::ErrBarrierUnsealFailed);
         }
@@ -213,6 +265,44 @@ impl SecurityBarrier for AESGCMBarrier {
         Ok(())
     }
 
+    async fn rekey(&self, kek: &[u8]) -> Result<(), RvError> {
+        let (min, max) = self.key_length_range();
+        if kek.len() < min || kek.len() > max {
+            return Err(RvError::ErrBarrierKeyInvalid);
+        }
+
+        if self.sealed()? {
+            return Err(RvError::ErrBarrierSealed);
+        }
+
+        let barrier_info = self.barrier_info.load_full();
+        let Some(key) = barrier_info.key.as_ref() else {
+            return Err(RvError::ErrBarrierNotInit);
+        };
+
+        let barrier_init = BarrierInit {
+            version: 1,
+            key: key.clone(),
+        };
+        let serialized_barrier_init = Zeroizing::new(serde_json::to_vec(&barrier_init)?);
+
+        // Encrypt with the new kek directly; swapping it in through init_cipher would break
+        // concurrent requests, which keep using the encryption key.
+        let value = aead_encrypt(
+            kek,
+            barrier_info.version_byte,
+            BARRIER_INIT_PATH,
+            &serialized_barrier_init,
+        )?;
+
+        let be = BackendEntry {
+            key: BARRIER_INIT_PATH.to_string(),
+            value,
+        };
+
+        self.backend.put(&be).await
+    }
+
     fn seal(&self) -> Result<(), RvError> {
         self.reset_cipher()?;
         let mut barrier_info = (*self.barrier_info.load_full()).clone();
@@ -237,11 +327,30 @@ impl SecurityBarrier for AESGCMBarrier {
         Ok(ret.to_vec())
     }
 
+    fn seal_wrap(&self, path: &str, plaintext: &[u8]) -> Result<Vec<u8>, RvError> {
+        let key = self.seal_wrap_key()?;
+        let version_byte = self.barrier_info.load().version_byte;
+        aead_encrypt(&key, version_byte, path, plaintext)
+    }
+
+    fn seal_unwrap(&self, path: &str, ciphertext: &[u8]) -> Result<Vec<u8>, RvError> {
+        let key = self.seal_wrap_key()?;
+        aead_decrypt(&key, path, ciphertext)
+    }
+
     fn as_storage(&self) -> &dyn Storage {
         self
     }
 }
 
+fn cipher_for_version(version_byte: u8) -> Result<Cipher, RvError> {
+    match version_byte {
+        AES_GCM_VERSION1 | AES_GCM_VERSION2 => Ok(Cipher::aes_256_gcm()),
+        CHACHA20_POLY1305_VERSION => Ok(Cipher::chacha20_poly1305()),
+        _ => Err(RvError::ErrBarrierVersionMismatch),
+    }
+}
+
 impl AESGCMBarrier {
     pub fn new(physical: Arc<dyn Backend>) -> Self {
         Self {
@@ -257,6 +366,12 @@ impl AESGCMBarrier {
         Ok(())
     }
 
+    fn set_version_byte(&self, version_byte: u8) {
+        let mut barrier_info = (*self.barrier_info.load_full()).clone();
+        barrier_info.version_byte = version_byte;
+        self.barrier_info.store(Arc::new(barrier_info));
+    }
+
     fn reset_cipher(&self) -> Result<(), RvError> {
         let mut barrier_info = (*self.barrier_info.load_full()).clone();
         // Zeroize it explicitly
@@ -272,99 +387,130 @@ impl AESGCMBarrier {
             return Err(RvError::ErrBarrierNotInit);
         }
 
-        let cipher = Cipher::aes_256_gcm();
-        let iv_len = cipher.iv_len().unwrap_or(0);
-        let tag_len = 16;
-        let block_size = cipher.block_size();
-
         // XXX: the cloned variable 'key' will be zeroized automatically on drop
         let key = Zeroizing::new(barrier_info.key.clone().unwrap());
 
-        let size: usize = EPOCH_SIZE + 1 + iv_len + plaintext.len() + tag_len;
-        let mut out = vec![0u8; size + block_size];
-        out[3] = KEY_EPOCH;
-        out[4] = barrier_info.aes_gcm_version_byte;
-
-        // Generate a random nonce
-        let mut nonce = Zeroizing::new(vec![0u8; iv_len]);
-        let iv = match iv_len {
-            0 => None,
-            _ => {
-                rng().fill(nonce.deref_mut().as_mut_slice());
-                out[5..5 + iv_len].copy_from_slice(nonce.deref().as_slice());
-                Some(nonce.deref().as_slice())
-            }
-        };
-
-        let mut encrypter = Crypter::new(cipher, Mode::Encrypt, key.deref().as_slice(), iv)?;
-
-        encrypter.pad(false);
+        aead_encrypt(&key, barrier_info.version_byte, path, plaintext)
+    }
 
-        if barrier_info.aes_gcm_version_byte == AES_GCM_VERSION2 {
-            encrypter.aad_update(path.as_bytes())?;
+    fn decrypt(&self, path: &str, ciphertext: &[u8]) -> Result<Vec<u8>, RvError> {
+        let barrier_info = self.barrier_info.load();
+        if barrier_info.key.is_none() {
+            return Err(RvError::ErrBarrierNotInit);
         }
 
-        let mut count = encrypter.update(plaintext, &mut out[EPOCH_SIZE + 1 + iv_len..])?;
-        count += encrypter.finalize(&mut out[EPOCH_SIZE + 1 + iv_len + count..])?;
-        out.truncate(EPOCH_SIZE + 1 + iv_len + count + tag_len);
-
-        encrypter.get_tag(&mut out[EPOCH_SIZE + 1 + iv_len + count..])?;
+        let key = Zeroizing::new(barrier_info.key.clone().unwrap());
 
-        Ok(out)
+        aead_decrypt(&key, path, ciphertext)
     }
 
-    fn decrypt(&self, path: &str, ciphertext: &[u8]) -> Result<Vec<u8>, RvError> {
+    /// The seal-wrap key, derived from the encryption key so it never has to be stored.
+    fn seal_wrap_key(&self) -> Result<Zeroizing<Vec<u8>>, RvError> {
         let barrier_info = self.barrier_info.load();
-        if barrier_info.key.is_none() {
+        let Some(key) = barrier_info.key.as_ref() else {
             return Err(RvError::ErrBarrierNotInit);
-        }
+        };
 
-        if ciphertext[0] != 0
-            || ciphertext[1] != 0
-            || ciphertext[2] != 0
-            || ciphertext[3] != KEY_EPOCH
-        {
-            return Err(RvError::ErrBarrierEpochMismatch);
-        }
+        let pkey = PKey::hmac(key)?;
+        let mut signer = Signer::new(MessageDigest::sha256(), &pkey)?;
+        signer.update(SEAL_WRAP_KEY_CONTEXT)?;
+        Ok(Zeroizing::new(signer.sign_to_vec()?))
+    }
+}
 
-        let cipher = Cipher::aes_256_gcm();
-        let block_size = cipher.block_size();
-        let iv_len = cipher.iv_len().unwrap_or(0);
-        let tag_len = 16;
+fn aead_encrypt(
+    key: &[u8],
+    version_byte: u8,
+    path: &str,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, RvError> {
+    let cipher = cipher_for_version(version_byte)?;
+    let iv_len = cipher.iv_len().unwrap_or(0);
+    let tag_len = 16;
+    let block_size = cipher.block_size();
+
+    let size: usize = EPOCH_SIZE + 1 + iv_len + plaintext.len() + tag_len;
+    let mut out = vec![0u8; size + block_size];
+    out[3] = KEY_EPOCH;
+    out[4] = version_byte;
+
+    // Generate a random nonce
+    let mut nonce = Zeroizing::new(vec![0u8; iv_len]);
+    let iv = match iv_len {
+        0 => None,
+        _ => {
+            rng().fill(nonce.deref_mut().as_mut_slice());
+            out[5..5 + iv_len].copy_from_slice(nonce.deref().as_slice());
+            Some(nonce.deref().as_slice())
+        }
+    };
 
-        let key = Zeroizing::new(barrier_info.key.clone().unwrap());
+    let mut encrypter = Crypter::new(cipher, Mode::Encrypt, key, iv)?;
 
-        let iv = match iv_len {
-            0 => None,
-            _ => Some(&ciphertext[5..5 + iv_len]),
-        };
+    encrypter.pad(false);
 
-        let mut decrypter = Crypter::new(cipher, Mode::Decrypt, key.deref().as_slice(), iv)?;
+    if version_byte != AES_GCM_VERSION1 {
+        encrypter.aad_update(path.as_bytes())?;
+    }
 
-        decrypter.pad(false);
+    let mut count = encrypter.update(plaintext, &mut out[EPOCH_SIZE + 1 + iv_len..])?;
+    count += encrypter.finalize(&mut out[EPOCH_SIZE + 1 + iv_len + count..])?;
+    out.truncate(EPOCH_SIZE + 1 + iv_len + count + tag_len);
 
-        match ciphertext[4] {
-            AES_GCM_VERSION1 => {}
-            AES_GCM_VERSION2 => {
-                decrypter.aad_update(path.as_bytes())?;
-            }
-            _ => {
-                return Err(RvError::ErrBarrierVersionMismatch);
-            }
-        };
+    encrypter.get_tag(&mut out[EPOCH_SIZE + 1 + iv_len + count..])?;
 
-        let raw = &ciphertext[5 + iv_len..ciphertext.len() - tag_len];
-        let tag = &ciphertext[ciphertext.len() - tag_len..ciphertext.len()];
-        let size = ciphertext.len() - 5 - iv_len - tag_len;
-        let mut out = vec![0u8; size + block_size];
+    Ok(out)
+}
 
-        let mut count = decrypter.update(raw, &mut out)?;
+fn aead_decrypt(key: &[u8], path: &str, ciphertext: &[u8]) -> Result<Vec<u8>, RvError> {
+    if ciphertext.len() <= EPOCH_SIZE {
+        return Err(RvError::ErrBarrierEpochMismatch);
+    }
 
-        decrypter.set_tag(tag)?;
+    if ciphertext[0] != 0 || ciphertext[1] != 0 || ciphertext[2] != 0 || ciphertext[3] != KEY_EPOCH
+    {
+        return Err(RvError::ErrBarrierEpochMismatch);
+    }
 
-        count += decrypter.finalize(&mut out[count..])?;
-        out.truncate(count);
+    let cipher = cipher_for_version(ciphertext[4])?;
+    let block_size = cipher.block_size();
+    let iv_len = cipher.iv_len().unwrap_or(0);
+    let tag_len = 16;
 
-        Ok(out)
+    if ciphertext.len() < 5 + iv_len + tag_len {
+        return Err(RvError::ErrBarrierVersionMismatch);
     }
+
+    let iv = match iv_len {
+        0 => None,
+        _ => Some(&ciphertext[5..5 + iv_len]),
+    };
+
+    let mut decrypter = Crypter::new(cipher, Mode::Decrypt, key, iv)?;
+
+    decrypter.pad(false);
+
+    match ciphertext[4] {
+        AES_GCM_VERSION1 => {}
+        AES_GCM_VERSION2 | CHACHA20_POLY1305_VERSION => {
+            decrypter.aad_update(path.as_bytes())?;
+        }
+        _ => {
+            return Err(RvError::ErrBarrierVersionMismatch);
+        }
+    };
+
+    let raw = &ciphertext[5 + iv_len..ciphertext.len() - tag_len];
+    let tag = &ciphertext[ciphertext.len() - tag_len..ciphertext.len()];
+    let size = ciphertext.len() - 5 - iv_len - tag_len;
+    let mut out = vec![0u8; size + block_size];
+
+    let mut count = decrypter.update(raw, &mut out)?;
+
+    decrypter.set_tag(tag)?;
+
+    count += decrypter.finalize(&mut out[count..])?;
+    out.truncate(count);
+
+    Ok(out)
 }
@@ -1,11 +1,23 @@
-use std::{any::Any, sync::Arc};
+use std::{
+    any::Any,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
 
 use super::{Storage, StorageEntry, barrier::SecurityBarrier};
 use crate::errors::RvError;
 
+/// Header of a seal-wrapped value. Values written before seal wrapping was turned on lack it and
+/// are returned as stored; they get wrapped the next time they are written.
+pub const SEAL_WRAP_HEADER: &[u8] = b"\0sealwrap\x01";
+
 pub struct BarrierView {
     barrier: Arc<dyn SecurityBarrier>,
     prefix: String,
+    /// Shared with sub views, so toggling it on a mount's view covers them too.
+    seal_wrap: Arc<AtomicBool>,
 }
 
 #[async_trait::async_trait]
@@ -19,9 +31,10 @@ impl Storage for BarrierView {
         self.sanity_check(key)?;
         let storage_entry = self.barrier.get(self.expand_key(key).as_str()).await?;
         if let Some(entry) = storage_entry {
+            let value = self.unwrap_value(&entry.key, entry.value)?;
             Ok(Some(StorageEntry {
                 key: self.truncate_key(entry.key.as_str()),
-                value: entry.value,
+                value,
             }))
         } else {
             Ok(None)
@@ -30,10 +43,9 @@ impl Storage for BarrierView {
 
     async fn put(&self, entry: &StorageEntry) -> Result<(), RvError> {
         self.sanity_check(entry.key.as_str())?;
-        let nested = StorageEntry {
-            key: self.expand_key(entry.key.as_str()),
-            value: entry.value.clone(),
-        };
+        let key = self.expand_key(entry.key.as_str());
+        let value = self.wrap_value(&key, &entry.value)?;
+        let nested = StorageEntry { key, value };
         self.barrier.put(&nested).await
     }
 
@@ -45,6 +57,34 @@ impl Storage for BarrierView {
     async fn lock(&self, lock_name: &str) -> Result<Box<dyn Any>, RvError> {
         self.barrier.lock(lock_name).await
     }
+
+    async fn compare_and_swap(
+        &self,
+        entry: &StorageEntry,
+        expected: Option<&[u8]>,
+    ) -> Result<bool, RvError> {
+        self.sanity_check(entry.key.as_str())?;
+        let key = self.expand_key(entry.key.as_str());
+
+        // The barrier compares what it stores, which may be seal wrapped.
+        let current = self.barrier.get(&key).await?;
+        let value = current
+            .as_ref()
+            .map(|current| self.unwrap_value(&key, current.value.clone()))
+            .transpose()?;
+        if value.as_deref() != expected {
+            return Ok(false);
+        }
+
+        let value = self.wrap_value(&key, &entry.value)?;
+        let nested = StorageEntry { key, value };
+        self.barrier
+            .compare_and_swap(
+                &nested,
+                current.as_ref().map(|current| current.value.as_slice()),
+            )
+            .await
+    }
 }
 
 impl BarrierView {
@@ -52,6 +92,7 @@ impl BarrierView {
         Self {
             barrier,
             prefix: prefix.to_string(),
+            seal_wrap: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -59,9 +100,19 @@ impl BarrierView {
         Self {
             barrier: self.barrier.clone(),
             prefix: self.expand_key(prefix),
+            seal_wrap: self.seal_wrap.clone(),
         }
     }
 
+    pub fn seal_wrap(&self) -> bool {
+        self.seal_wrap.load(Ordering::Relaxed)
+    }
+
+    /// Turn seal wrapping of the values written through this view on or off.
+    pub fn set_seal_wrap(&self, enabled: bool) {
+        self.seal_wrap.store(enabled, Ordering::Relaxed);
+    }
+
     pub async fn get_keys(&self) -> Result<Vec<String>, RvError> {
         let mut paths = vec!["".to_string()];
         let mut keys = Vec::new();
@@ -96,6 +147,23 @@ impl BarrierView {
         self
     }
 
+    fn wrap_value(&self, key: &str, value: &[u8]) -> Result<Vec<u8>, RvError> {
+        if self.seal_wrap() {
+            let mut wrapped = SEAL_WRAP_HEADER.to_vec();
+            wrapped.extend(self.barrier.seal_wrap(key, value)?);
+            Ok(wrapped)
+        } else {
+            Ok(value.to_vec())
+        }
+    }
+
+    fn unwrap_value(&self, key: &str, value: Vec<u8>) -> Result<Vec<u8>, RvError> {
+        match value.strip_prefix(SEAL_WRAP_HEADER) {
+            Some(wrapped) if self.seal_wrap() => self.barrier.seal_unwrap(key, wrapped),
+            _ => Ok(value),
+        }
+    }
+
     fn sanity_check(&self, key: &str) -> Result<(), RvError> {
         if key.contains("..") || key.starts_with('/') {
             Err(RvError::ErrBarrierKeySanityCheckFailed)
@@ -0,0 +1,86 @@
+//! An in-memory storage backend.
+//!
+//! `MemoryBackend` keeps all entries in a `BTreeMap` guarded by a `RwLock`. Nothing is persisted,
+//! which makes it a good fit for tests and ephemeral vault instances. Key validation and the
+//! prefix-folding semantics of `list` follow `SqliteBackend` so that the two can be swapped
+//! without behavioral differences.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    errors::RvError,
+    storage::{Backend, BackendEntry},
+};
+
+#[derive(Debug, Default)]
+pub struct MemoryBackend {
+    entries: RwLock<BTreeMap<String, Vec<u8>>>,
+}
+
+impl MemoryBackend {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> Arc<dyn Backend> {
+        Arc::new(Self::default())
+    }
+}
+
+#[async_trait::async_trait]
+impl Backend for MemoryBackend {
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, RvError> {
+        let entries = self.entries.read()?;
+        let mut res = HashSet::new();
+        for key in entries
+            .range(prefix.to_string()..)
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(prefix))
+        {
+            let key = &key[prefix.len()..];
+            match key.find('/') {
+                Some(i) => {
+                    res.insert(key[0..i + 1].to_string());
+                }
+                None => {
+                    res.insert(key.to_string());
+                }
+            }
+        }
+
+        Ok(res.into_iter().collect())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<BackendEntry>, RvError> {
+        let entries = self.entries.read()?;
+        Ok(entries.get(key).map(|value| BackendEntry {
+            key: key.to_string(),
+            value: value.clone(),
+        }))
+    }
+
+    async fn put(&self, entry: &BackendEntry) -> Result<(), RvError> {
+        let mut entries = self.entries.write()?;
+        entries.insert(entry.key.clone(), entry.value.clone());
+        Ok(())
+    }
+
+    async fn compare_and_swap(
+        &self,
+        entry: &BackendEntry,
+        expected: Option<&[u8]>,
+    ) -> Result<bool, RvError> {
+        let mut entries = self.entries.write()?;
+        if entries.get(&entry.key).map(Vec::as_slice) != expected {
+            return Ok(false);
+        }
+        entries.insert(entry.key.clone(), entry.value.clone());
+        Ok(true)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), RvError> {
+        let mut entries = self.entries.write()?;
+        entries.remove(key);
+        Ok(())
+    }
+}
@@ -19,13 +19,18 @@ use std::{any::Any, collections::HashMap, sync::Arc};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use tokio::io::AsyncRead;
 
 use crate::errors::RvError;
 
 pub mod barrier;
 pub mod barrier_aes_gcm;
 pub mod barrier_view;
+#[cfg(feature = "storage_memory")]
+pub mod memory;
 pub mod physical;
+#[cfg(feature = "storage_redis")]
+pub mod redis;
 pub mod sql;
 #[cfg(feature = "storage_xline")]
 pub mod xline;
@@ -39,6 +44,23 @@ pub trait Storage: Send + Sync {
     async fn lock(&self, _lock_name: &str) -> Result<Box<dyn Any>, RvError> {
         Ok(Box::new(true))
     }
+
+    /// Store `entry` only if its key currently holds `expected`, or does not exist if `expected`
+    /// is `None`. Returns whether the entry was stored.
+    ///
+    /// The default implementation is a `get` followed by a `put` and is therefore not atomic.
+    async fn compare_and_swap(
+        &self,
+        entry: &StorageEntry,
+        expected: Option<&[u8]>,
+    ) -> Result<bool, RvError> {
+        let current = self.get(&entry.key).await?;
+        if current.as_ref().map(|current| current.value.as_slice()) != expected {
+            return Ok(false);
+        }
+        self.put(entry).await?;
+        Ok(true)
+    }
 }
 
 /// This struct is used to describe a specific storage entry
@@ -70,6 +92,131 @@ pub trait Backend: Send + Sync {
     async fn lock(&self, _lock_name: &str) -> Result<Box<dyn Any>, RvError> {
         Ok(Box::new(true))
     }
+
+    /// Store `entry` only if its key currently holds `expected`, or does not exist if `expected`
+    /// is `None`. Returns whether the entry was stored.
+    ///
+    /// The default implementation is a `get` followed by a `put` and is therefore not atomic;
+    /// backends that can write conditionally should override it.
+    async fn compare_and_swap(
+        &self,
+        entry: &BackendEntry,
+        expected: Option<&[u8]>,
+    ) -> Result<bool, RvError> {
+        let current = self.get(&entry.key).await?;
+        if current.as_ref().map(|current| current.value.as_slice()) != expected {
+            return Ok(false);
+        }
+        self.put(entry).await?;
+        Ok(true)
+    }
+
+    /// Open a reader over the value stored under `key`, or `None` if the key does not exist.
+    ///
+    /// The default implementation reads the whole entry and wraps it in a cursor; backends that
+    /// can fetch a value piecewise should override it to keep peak memory bounded.
+    async fn get_stream(&self, key: &str) -> Result<Option<BackendValueReader>, RvError> {
+        Ok(self
+            .get(key)
+            .await?
+            .map(|entry| Box::new(std::io::Cursor::new(entry.value)) as BackendValueReader))
+    }
+
+    /// Fetch several keys at once. The result has the same length and order as `keys`.
+    ///
+    /// The default implementation issues one `get` per key; backends that can do better
+    /// (e.g. a single SQL statement) should override it.
+    async fn get_batch(&self, keys: &[&str]) -> Result<Vec<Option<BackendEntry>>, RvError> {
+        let mut entries = Vec::with_capacity(keys.len());
+        for key in keys.iter() {
+            entries.push(self.get(key).await?);
+        }
+        Ok(entries)
+    }
+
+    /// Store several entries at once.
+    ///
+    /// The default implementation issues one `put` per entry and is therefore not atomic;
+    /// backends with transaction support should override it so a partial failure rolls back.
+    async fn put_batch(&self, entries: &[BackendEntry]) -> Result<(), RvError> {
+        for entry in entries.iter() {
+            self.put(entry).await?;
+        }
+        Ok(())
+    }
+
+    /// List at most `limit` children of `prefix`, in ascending order, starting after the child
+    /// `after`. Children are folded the same way `list` folds them (`dir/` for anything below a
+    /// sub-directory). Pass the returned `ListPage::next` as `after` to fetch the next page.
+    ///
+    /// The default implementation pages over a full `list`, so it bounds the result size but not
+    /// the memory used; backends that can seek by key should override it.
+    async fn list_page(
+        &self,
+        prefix: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<ListPage, RvError> {
+        let mut keys = self.list(prefix).await?;
+        keys.sort();
+        Ok(ListPage::from_sorted(keys, after, limit))
+    }
+
+    /// Returns this backend as a `TransactionalBackend` if it supports atomic multi-key
+    /// operations, or `None` otherwise.
+    fn as_transactional(&self) -> Option<&dyn TransactionalBackend> {
+        None
+    }
+}
+
+/// Reader over the value of one entry, returned by `Backend::get_stream`.
+pub type BackendValueReader = Box<dyn AsyncRead + Send + Unpin>;
+
+/// One page of children returned by `Backend::list_page`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListPage {
+    pub keys: Vec<String>,
+    /// Cursor for the following page, `None` once the listing is exhausted.
+    pub next: Option<String>,
+}
+
+impl ListPage {
+    /// Cut one page out of an already sorted, de-duplicated list of children.
+    pub fn from_sorted(sorted: Vec<String>, after: Option<&str>, limit: usize) -> Self {
+        let mut keys: Vec<String> = sorted
+            .into_iter()
+            .filter(|key| after.is_none_or(|after| key.as_str() > after))
+            .take(limit.saturating_add(1))
+            .collect();
+
+        let next = if keys.len() > limit {
+            keys.truncate(limit);
+            keys.last().cloned()
+        } else {
+            None
+        };
+
+        Self { keys, next }
+    }
+}
+
+/// A single in-flight transaction opened by `TransactionalBackend::begin`.
+///
+/// Reads observe the writes made earlier in the same transaction. Nothing becomes visible to
+/// other readers until `commit`; dropping the transaction without committing rolls it back.
+#[async_trait]
+pub trait Transaction: Send {
+    async fn get(&mut self, key: &str) -> Result<Option<BackendEntry>, RvError>;
+    async fn put(&mut self, entry: &BackendEntry) -> Result<(), RvError>;
+    async fn delete(&mut self, key: &str) -> Result<(), RvError>;
+    async fn commit(self: Box<Self>) -> Result<(), RvError>;
+    async fn rollback(self: Box<Self>) -> Result<(), RvError>;
+}
+
+/// A storage backend that can group several operations into one atomic unit.
+#[async_trait]
+pub trait TransactionalBackend: Backend {
+    async fn begin(&self) -> Result<Box<dyn Transaction>, RvError>;
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -79,7 +226,12 @@ pub struct BackendEntry {
     pub value: Vec<u8>,
 }
 
-#[cfg(feature = "storage_sqlite")]
+#[cfg(any(
+    feature = "storage_sqlite",
+    feature = "storage_mysql",
+    feature = "storage_pg",
+    feature = "storage_redis"
+))]
 fn current_handle<'a, F, T>(fut: F) -> T
 where
     F: std::future::Future<Output = T> + Send + 'a,
@@ -118,6 +270,23 @@ pub fn new_backend(t: &str, conf: &HashMap<String, Value>) -> Result<Arc<dyn Bac
             let backend = current_handle(sql::sqlite::SqliteBackend::new(conf))?;
             Ok(Arc::new(backend))
         }
+        #[cfg(feature = "storage_mysql")]
+        "mysql" => {
+            let backend = current_handle(sql::mysql::MysqlBackend::new(conf))?;
+            Ok(Arc::new(backend))
+        }
+        #[cfg(feature = "storage_pg")]
+        "postgresql" => {
+            let backend = current_handle(sql::postgresql::PostgresBackend::new(conf))?;
+            Ok(Arc::new(backend))
+        }
+        #[cfg(feature = "storage_redis")]
+        "redis" => {
+            let backend = current_handle(redis::RedisBackend::new(conf))?;
+            Ok(Arc::new(backend))
+        }
+        #[cfg(feature = "storage_memory")]
+        "memory" => Ok(memory::MemoryBackend::new()),
         "mock" => Ok(Arc::new(physical::mock::MockBackend::new())),
         _ => Err(RvError::ErrPhysicalTypeInvalid),
     }
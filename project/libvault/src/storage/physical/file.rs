@@ -113,16 +113,23 @@ impl Backend for FileBackend {
         Ok(())
     }
 
-    async fn lock(&self, lock_name: &str) -> Result<Box<dyn Any>, RvError> {
-        let (path, key) = self.path_key(lock_name);
-        let file_path = path.join(format!("{key}.lock"));
-        loop {
-            if let Ok(lock) = Lockfile::create_with_parents(&file_path) {
-                return Ok(Box::new(lock));
-            } else {
-                sleep(Duration::from_millis(100));
-            }
+    async fn compare_and_swap(
+        &self,
+        entry: &BackendEntry,
+        expected: Option<&[u8]>,
+    ) -> Result<bool, RvError> {
+        // `put` takes the key's own lock, so swaps serialize on a lock of their own.
+        let _lock = self.lockfile(&format!("{}.cas", entry.key));
+        let current = self.get(&entry.key).await?;
+        if current.as_ref().map(|current| current.value.as_slice()) != expected {
+            return Ok(false);
         }
+        self.put(entry).await?;
+        Ok(true)
+    }
+
+    async fn lock(&self, lock_name: &str) -> Result<Box<dyn Any>, RvError> {
+        Ok(Box::new(self.lockfile(lock_name)))
     }
 }
 
@@ -153,6 +160,18 @@ impl FileBackend {
         })
     }
 
+    fn lockfile(&self, lock_name: &str) -> Lockfile {
+        let (path, key) = self.path_key(lock_name);
+        let file_path = path.join(format!("{key}.lock"));
+        loop {
+            if let Ok(lock) = Lockfile::create_with_parents(&file_path) {
+                return lock;
+            } else {
+                sleep(Duration::from_millis(100));
+            }
+        }
+    }
+
     fn path_key(&self, k: &str) -> (PathBuf, String) {
         let path = self.path.join(k);
         let parent = path.parent().unwrap().to_owned();
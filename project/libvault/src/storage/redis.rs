@@ -0,0 +1,252 @@
+//! A Redis storage backend.
+//!
+//! Every vault key is stored as a plain Redis string under a configurable namespace prefix, so
+//! `get`/`put`/`delete` map directly onto `GET`/`SET`/`DEL`. `list` walks the keyspace with `SCAN`
+//! and folds the results into immediate children the same way `SqliteBackend::list` does. Keys
+//! are not validated, so a key with a leading `/` is stored and listed like any other.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use redis::{AsyncCommands, aio::ConnectionManager};
+use serde::{Deserialize, Deserializer, de::Error as _};
+use serde_json::{Map, Value};
+
+use crate::{
+    errors::RvError,
+    storage::{Backend, BackendEntry},
+};
+
+const DEFAULT_REDIS_URL: &str = "redis://127.0.0.1:6379";
+const DEFAULT_REDIS_PREFIX: &str = "vault:";
+const DEFAULT_REDIS_POOL_SIZE: usize = 1;
+const MAX_REDIS_POOL_SIZE: usize = 64;
+const REDIS_SCAN_COUNT: usize = 1000;
+
+/// `SET KEYS[1] ARGV[3]` if the key holds `ARGV[2]`, or does not exist if `ARGV[1]` is 0. Scripts
+/// run atomically, so nothing can write the key between the check and the `SET`.
+const REDIS_CAS_SCRIPT: &str = r#"
+local current = redis.call('GET', KEYS[1])
+if ARGV[1] == '1' then
+    if current ~= ARGV[2] then
+        return 0
+    end
+elseif current then
+    return 0
+end
+redis.call('SET', KEYS[1], ARGV[3])
+return 1
+"#;
+
+#[derive(Clone, Debug)]
+pub struct RedisBackendConfig {
+    url: String,
+    prefix: String,
+    pool_size: usize,
+}
+
+impl<'de> Deserialize<'de> for RedisBackendConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let default_cfg = Self::default();
+        let deserializer_map: Map<String, Value> = <Map<String, Value>>::deserialize(deserializer)?;
+        Ok(Self {
+            url: std::env::var("VAULT_REDIS_URL").ok().unwrap_or(
+                deserializer_map
+                    .get("url")
+                    .and_then(|url| {
+                        serde_json::from_value::<String>(url.clone())
+                            .map_err(|err| {
+                                log::warn!("Redis Backend: `url` from value failed: {err:?}")
+                            })
+                            .ok()
+                    })
+                    .unwrap_or(default_cfg.url),
+            ),
+            prefix: std::env::var("VAULT_REDIS_PREFIX").ok().unwrap_or(
+                deserializer_map
+                    .get("prefix")
+                    .and_then(|prefix| {
+                        serde_json::from_value::<String>(prefix.clone())
+                            .map_err(|err| {
+                                log::warn!("Redis Backend: `prefix` from value failed: {err:?}")
+                            })
+                            .ok()
+                    })
+                    .unwrap_or(default_cfg.prefix),
+            ),
+            pool_size: {
+                let pool_size = match std::env::var("VAULT_REDIS_POOL_SIZE")
+                    .map(Value::String)
+                    .ok()
+                    .or(deserializer_map.get("pool_size").cloned())
+                {
+                    Some(Value::String(size)) => match size.is_empty() {
+                        true => default_cfg.pool_size,
+                        false => size.trim().parse::<usize>().map_err(D::Error::custom)?,
+                    },
+                    Some(Value::Number(size)) => size
+                        .as_u64()
+                        .map(|size| size as usize)
+                        .unwrap_or(default_cfg.pool_size),
+                    _ => default_cfg.pool_size,
+                };
+                match pool_size > 0 && pool_size <= MAX_REDIS_POOL_SIZE {
+                    true => pool_size,
+                    false => Err(D::Error::custom(format!(
+                        "Redis Backend: pool_size must be between 1 and {}.",
+                        MAX_REDIS_POOL_SIZE
+                    )))?,
+                }
+            },
+        })
+    }
+}
+
+impl Default for RedisBackendConfig {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_REDIS_URL.to_string(),
+            prefix: DEFAULT_REDIS_PREFIX.to_string(),
+            pool_size: DEFAULT_REDIS_POOL_SIZE,
+        }
+    }
+}
+
+pub struct RedisBackend {
+    conns: Vec<ConnectionManager>,
+    next: AtomicUsize,
+    prefix: String,
+}
+
+impl RedisBackend {
+    pub async fn new(conf: &HashMap<String, Value>) -> Result<Self, RvError> {
+        let conf: RedisBackendConfig = serde_json::from_value(serde_json::to_value(conf)?)?;
+        let client = redis::Client::open(conf.url.as_str())?;
+
+        let mut conns = Vec::with_capacity(conf.pool_size);
+        for _ in 0..conf.pool_size {
+            conns.push(ConnectionManager::new(client.clone()).await?);
+        }
+
+        Ok(Self {
+            conns,
+            next: AtomicUsize::new(0),
+            prefix: conf.prefix,
+        })
+    }
+
+    /// Pick a connection in round-robin order. `ConnectionManager` is cheap to clone and
+    /// multiplexes requests, so every call gets its own handle.
+    fn conn(&self) -> ConnectionManager {
+        let idx = self.next.fetch_add(1, Ordering::Relaxed) % self.conns.len();
+        self.conns[idx].clone()
+    }
+
+    fn expand_key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+}
+
+/// Escape the glob characters understood by `SCAN ... MATCH` so that `prefix` is matched literally.
+fn escape_match_pattern(prefix: &str) -> String {
+    let mut escaped = String::with_capacity(prefix.len());
+    for c in prefix.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[async_trait::async_trait]
+impl Backend for RedisBackend {
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, RvError> {
+        let full_prefix = self.expand_key(prefix);
+        let pattern = format!("{}*", escape_match_pattern(&full_prefix));
+        let mut conn = self.conn();
+        let mut cursor: u64 = 0;
+        let mut res = HashSet::new();
+        loop {
+            let (next, keys): (u64, Vec<Vec<u8>>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(REDIS_SCAN_COUNT)
+                .query_async(&mut conn)
+                .await?;
+
+            for key_bytes in keys {
+                let key = String::from_utf8(key_bytes)?;
+                let Some(key) = key.strip_prefix(&full_prefix) else {
+                    continue;
+                };
+
+                match key.find('/') {
+                    Some(i) => {
+                        res.insert(key[0..i + 1].to_string());
+                    }
+                    None => {
+                        res.insert(key.to_string());
+                    }
+                }
+            }
+
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+
+        Ok(res.into_iter().collect())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<BackendEntry>, RvError> {
+        let mut conn = self.conn();
+        let value: Option<Vec<u8>> = conn.get(self.expand_key(key)).await?;
+
+        Ok(value.map(|value| BackendEntry {
+            key: key.to_string(),
+            value,
+        }))
+    }
+
+    async fn put(&self, entry: &BackendEntry) -> Result<(), RvError> {
+        let mut conn = self.conn();
+        let _: () = conn
+            .set(self.expand_key(&entry.key), entry.value.as_slice())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn compare_and_swap(
+        &self,
+        entry: &BackendEntry,
+        expected: Option<&[u8]>,
+    ) -> Result<bool, RvError> {
+        let mut conn = self.conn();
+        let swapped: i64 = redis::Script::new(REDIS_CAS_SCRIPT)
+            .key(self.expand_key(&entry.key))
+            .arg(expected.is_some() as u8)
+            .arg(expected.unwrap_or_default())
+            .arg(entry.value.as_slice())
+            .invoke_async(&mut conn)
+            .await?;
+
+        Ok(swapped == 1)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), RvError> {
+        let mut conn = self.conn();
+        let _: () = conn.del(self.expand_key(key)).await?;
+
+        Ok(())
+    }
+}
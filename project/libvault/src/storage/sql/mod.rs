@@ -1,6 +1,17 @@
-//! This module includes storage backends for all SQL types. Currently supported: SQLite, PostgreSQL.
+//! This module includes storage backends for all SQL types. Currently supported: SQLite, PostgreSQL,
+//! MySQL.
 
+#[cfg(feature = "storage_mysql")]
+pub mod mysql;
 #[cfg(feature = "storage_pg")]
 pub mod postgresql;
 #[cfg(feature = "storage_sqlite")]
 pub mod sqlite;
+
+/// Bytes fetched per query when `get_stream` reads a value out of a SQL backend.
+#[cfg(any(
+    feature = "storage_sqlite",
+    feature = "storage_pg",
+    feature = "storage_mysql"
+))]
+pub(crate) const STREAM_CHUNK_SIZE: i64 = 64 * 1024;
@@ -0,0 +1,494 @@
+use bytes::Bytes;
+use futures::stream;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, de::Error as _};
+use serde_json::{Map, Value};
+use sqlx::mysql::{MySqlConnectOptions, MySqlPoolOptions};
+use sqlx::{MySql, MySqlPool};
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    str::FromStr,
+    time::Duration,
+};
+use tokio_util::io::StreamReader;
+
+use crate::{
+    errors::RvError,
+    storage::{
+        Backend, BackendEntry, BackendValueReader, Transaction, TransactionalBackend,
+        sql::STREAM_CHUNK_SIZE,
+    },
+};
+
+const DEFAULT_MYSQL_HOST: &str = "localhost";
+const DEFAULT_MYSQL_PORT: u16 = 3306;
+const DEFAULT_MYSQL_DATABASE: &str = "vault";
+const DEFAULT_MYSQL_TABLE: &str = "vault";
+const DEFAULT_MYSQL_TIMEOUT: u64 = 7200;
+/// Upper bound of keys bound into a single `IN (...)` clause by `get_batch`.
+const MYSQL_BATCH_SIZE: usize = 500;
+
+#[derive(Clone, Debug)]
+pub struct MysqlBackendConfig {
+    url: Option<String>,
+    host: String,
+    port: u16,
+    user: Option<String>,
+    password: Option<String>,
+    database: String,
+    table: String,
+    timeout: Duration,
+}
+
+fn string_value(deserializer_map: &Map<String, Value>, env_key: &str, key: &str) -> Option<String> {
+    std::env::var(env_key).ok().or_else(|| {
+        deserializer_map.get(key).and_then(|value| {
+            serde_json::from_value::<String>(value.clone())
+                .map_err(|err| log::warn!("MySQL Backend: `{key}` from value failed: {err:?}"))
+                .ok()
+        })
+    })
+}
+
+impl<'de> Deserialize<'de> for MysqlBackendConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let default_cfg = Self::default();
+        let deserializer_map: Map<String, Value> = <Map<String, Value>>::deserialize(deserializer)?;
+        Ok(Self {
+            url: string_value(&deserializer_map, "VAULT_MYSQL_URL", "url"),
+            host: string_value(&deserializer_map, "VAULT_MYSQL_HOST", "host")
+                .unwrap_or(default_cfg.host),
+            port: match std::env::var("VAULT_MYSQL_PORT")
+                .map(Value::String)
+                .ok()
+                .or(deserializer_map.get("port").cloned())
+            {
+                Some(Value::String(port)) => {
+                    port.trim().parse::<u16>().map_err(D::Error::custom)?
+                }
+                Some(Value::Number(port)) => port
+                    .as_u64()
+                    .and_then(|port| u16::try_from(port).ok())
+                    .ok_or_else(|| D::Error::custom("MySQL Backend: `port` is out of range."))?,
+                _ => default_cfg.port,
+            },
+            user: string_value(&deserializer_map, "VAULT_MYSQL_USER", "user"),
+            password: string_value(&deserializer_map, "VAULT_MYSQL_PASSWORD", "password"),
+            database: string_value(&deserializer_map, "VAULT_MYSQL_DATABASE", "database")
+                .unwrap_or(default_cfg.database),
+            table: deserializer_map
+                .get("table")
+                .and_then(|table| {
+                    serde_json::from_value::<String>(table.clone())
+                        .map_err(|err| {
+                            log::warn!("MySQL Backend: `table` from value failed: {err:?}")
+                        })
+                        .ok()
+                })
+                .unwrap_or(default_cfg.table),
+            timeout: {
+                let timeout = match std::env::var("VAULT_MYSQL_TIMEOUT")
+                    .map(Value::String)
+                    .ok()
+                    .or(deserializer_map.get("timeout").cloned())
+                {
+                    Some(Value::String(duration)) => match duration.is_empty() {
+                        true => default_cfg.timeout,
+                        false => {
+                            humantime::parse_duration(duration.trim()).map_err(D::Error::custom)?
+                        }
+                    },
+                    Some(Value::Number(secs)) => {
+                        Duration::from_secs(secs.as_u64().unwrap_or(5_u64))
+                    }
+                    _ => default_cfg.timeout,
+                };
+                match timeout.gt(&Duration::ZERO)
+                    && timeout.lt(&Duration::from_secs(DEFAULT_MYSQL_TIMEOUT))
+                {
+                    true => timeout,
+                    false => Err(D::Error::custom(format!(
+                        "MySQL Backend: Timeout must be greater than 0s and less than {}s.",
+                        DEFAULT_MYSQL_TIMEOUT
+                    )))?,
+                }
+            },
+        })
+    }
+}
+
+impl Default for MysqlBackendConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            host: DEFAULT_MYSQL_HOST.to_string(),
+            port: DEFAULT_MYSQL_PORT,
+            user: None,
+            password: None,
+            database: DEFAULT_MYSQL_DATABASE.to_string(),
+            table: DEFAULT_MYSQL_TABLE.to_string(),
+            timeout: Duration::new(5, 0),
+        }
+    }
+}
+
+impl MysqlBackendConfig {
+    fn connect_options(&self) -> Result<MySqlConnectOptions, RvError> {
+        if let Some(url) = self.url.as_ref() {
+            return MySqlConnectOptions::from_str(url)
+                .map_err(|_| RvError::ErrDatabaseConnectionInfoInvalid);
+        }
+
+        let mut opts = MySqlConnectOptions::new()
+            .host(&self.host)
+            .port(self.port)
+            .database(&self.database);
+        if let Some(user) = self.user.as_ref() {
+            opts = opts.username(user);
+        }
+        if let Some(password) = self.password.as_ref() {
+            opts = opts.password(password);
+        }
+        Ok(opts)
+    }
+}
+
+/// Keys are stored verbatim: a key with a leading `/` reads back unchanged and is listed
+/// under the `/` prefix.
+pub struct MysqlBackend {
+    pool: MySqlPool,
+    table: String,
+}
+
+impl MysqlBackend {
+    pub async fn new(conf: &HashMap<String, Value>) -> Result<Self, RvError> {
+        let conf: MysqlBackendConfig = serde_json::from_value(serde_json::to_value(conf)?)?;
+        let re = Regex::new(r"^(?-u:\w)+$").expect("MySQL regex init failed");
+        if !re.is_match(&conf.table) {
+            let err = RvError::ErrMysqlDisallowedFields(conf.table.clone());
+            log::debug!("{err:?}");
+            Err(err)?;
+        }
+
+        let pool = MySqlPoolOptions::new()
+            .acquire_timeout(conf.timeout)
+            .connect_with(conf.connect_options()?)
+            .await
+            .map_err(RvError::from_mysql)?;
+        sqlx::query(&format!(
+            r#"CREATE TABLE IF NOT EXISTS `{}` (
+    `vault_key` VARBINARY(3072) NOT NULL,
+    `vault_value` LONGBLOB NOT NULL,
+    PRIMARY KEY (`vault_key`)
+);"#,
+            conf.table
+        ))
+        .execute(&pool)
+        .await
+        .map_err(RvError::from_mysql)?;
+
+        Ok(Self {
+            pool,
+            table: conf.table,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Backend for MysqlBackend {
+    async fn get(&self, key: &str) -> Result<Option<BackendEntry>, RvError> {
+        let sql = format!(
+            "SELECT vault_value FROM `{}` WHERE vault_key = ?",
+            &self.table
+        );
+        let ret: Option<Vec<u8>> = sqlx::query_scalar(&sql)
+            .bind(key.as_bytes())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(RvError::from_mysql)?;
+
+        Ok(ret.map(|value| BackendEntry {
+            key: key.to_string(),
+            value,
+        }))
+    }
+
+    async fn put(&self, entry: &BackendEntry) -> Result<(), RvError> {
+        let sql = format!(
+            "INSERT INTO `{}` (vault_key, vault_value) VALUES (?, ?) ON DUPLICATE KEY UPDATE vault_value = VALUES(vault_value)",
+            &self.table
+        );
+        sqlx::query(&sql)
+            .bind(entry.key.as_bytes())
+            .bind(&entry.value)
+            .execute(&self.pool)
+            .await
+            .map_err(RvError::from_mysql)?;
+
+        Ok(())
+    }
+
+    async fn compare_and_swap(
+        &self,
+        entry: &BackendEntry,
+        expected: Option<&[u8]>,
+    ) -> Result<bool, RvError> {
+        let ret = match expected {
+            Some(expected) => {
+                let sql = format!(
+                    "UPDATE `{}` SET vault_value = ? WHERE vault_key = ? AND vault_value = ?",
+                    &self.table
+                );
+                sqlx::query(&sql)
+                    .bind(&entry.value)
+                    .bind(entry.key.as_bytes())
+                    .bind(expected)
+                    .execute(&self.pool)
+                    .await
+            }
+            None => {
+                let sql = format!(
+                    "INSERT IGNORE INTO `{}` (vault_key, vault_value) VALUES (?, ?)",
+                    &self.table
+                );
+                sqlx::query(&sql)
+                    .bind(entry.key.as_bytes())
+                    .bind(&entry.value)
+                    .execute(&self.pool)
+                    .await
+            }
+        }
+        .map_err(RvError::from_mysql)?;
+
+        // MySQL counts only changed rows, so swapping in the expected value itself reports 0.
+        Ok(ret.rows_affected() == 1)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), RvError> {
+        let sql = format!("DELETE FROM `{}` WHERE vault_key = ?", &self.table);
+        sqlx::query(&sql)
+            .bind(key.as_bytes())
+            .execute(&self.pool)
+            .await
+            .map_err(RvError::from_mysql)?;
+
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, RvError> {
+        let sql = format!(
+            "SELECT vault_key FROM `{}` WHERE vault_key LIKE ? ESCAPE '\\\\'",
+            &self.table
+        );
+        // Escape the LIKE wildcard characters (% and _) and the escape character (\)
+        // so that `prefix` is treated as a literal prefix.
+        let escaped_prefix = prefix
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let keys: Vec<Vec<u8>> = sqlx::query_scalar(&sql)
+            .bind(format!("{}%", escaped_prefix).as_bytes())
+            .fetch_all(&self.pool)
+            .await
+            .map_err(RvError::from_mysql)?;
+        let mut res = HashSet::new();
+        for key_bytes in keys {
+            let key = String::from_utf8(key_bytes)?;
+            let key = key.strip_prefix(prefix).unwrap_or(&key);
+
+            match key.find('/') {
+                Some(i) => {
+                    let key = &key[0..i + 1];
+                    res.insert(key.to_string());
+                }
+                None => {
+                    res.insert(key.to_string());
+                }
+            }
+        }
+
+        Ok(res.into_iter().collect())
+    }
+
+    /// Reads the BLOB `STREAM_CHUNK_SIZE` bytes at a time. All chunks come from one consistent
+    /// snapshot transaction, so a concurrent `put` is never seen halfway; the reader holds a
+    /// pooled connection until it is dropped.
+    async fn get_stream(&self, key: &str) -> Result<Option<BackendValueReader>, RvError> {
+        let mut tx = self
+            .pool
+            .begin_with("START TRANSACTION WITH CONSISTENT SNAPSHOT, READ ONLY")
+            .await
+            .map_err(RvError::from_mysql)?;
+        let len: Option<i64> = sqlx::query_scalar(&format!(
+            "SELECT CAST(LENGTH(vault_value) AS SIGNED) FROM `{}` WHERE vault_key = ?",
+            &self.table
+        ))
+        .bind(key.as_bytes())
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(RvError::from_mysql)?;
+        let Some(len) = len else {
+            return Ok(None);
+        };
+
+        // SUBSTRING() counts BLOB offsets in bytes, starting at 1.
+        let sql = format!(
+            "SELECT SUBSTRING(vault_value, ?, ?) FROM `{}` WHERE vault_key = ?",
+            &self.table
+        );
+        let key = key.as_bytes().to_vec();
+        let chunks = stream::try_unfold((tx, 0_i64), move |(mut tx, offset)| {
+            let (sql, key) = (sql.clone(), key.clone());
+            async move {
+                if offset >= len {
+                    return Ok(None);
+                }
+                let chunk: Option<Vec<u8>> = sqlx::query_scalar(&sql)
+                    .bind(offset + 1)
+                    .bind(STREAM_CHUNK_SIZE)
+                    .bind(key)
+                    .fetch_optional(&mut *tx)
+                    .await
+                    .map_err(io::Error::other)?;
+                Ok::<_, io::Error>(chunk.filter(|chunk| !chunk.is_empty()).map(|chunk| {
+                    let next = offset + chunk.len() as i64;
+                    (Bytes::from(chunk), (tx, next))
+                }))
+            }
+        });
+        Ok(Some(Box::new(StreamReader::new(Box::pin(chunks)))))
+    }
+
+    fn as_transactional(&self) -> Option<&dyn TransactionalBackend> {
+        Some(self)
+    }
+
+    async fn get_batch(&self, keys: &[&str]) -> Result<Vec<Option<BackendEntry>>, RvError> {
+        let mut found: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+        for chunk in keys.chunks(MYSQL_BATCH_SIZE) {
+            let sql = format!(
+                "SELECT vault_key, vault_value FROM `{}` WHERE vault_key IN ({})",
+                &self.table,
+                vec!["?"; chunk.len()].join(", ")
+            );
+            let mut query = sqlx::query_as::<_, (Vec<u8>, Vec<u8>)>(&sql);
+            for key in chunk.iter() {
+                query = query.bind(key.as_bytes());
+            }
+            for (key, value) in query
+                .fetch_all(&self.pool)
+                .await
+                .map_err(RvError::from_mysql)?
+            {
+                found.insert(key, value);
+            }
+        }
+
+        Ok(keys
+            .iter()
+            .map(|key| {
+                found.get(key.as_bytes()).map(|value| BackendEntry {
+                    key: key.to_string(),
+                    value: value.clone(),
+                })
+            })
+            .collect())
+    }
+
+    async fn put_batch(&self, entries: &[BackendEntry]) -> Result<(), RvError> {
+        let sql = format!(
+            "INSERT INTO `{}` (vault_key, vault_value) VALUES (?, ?) ON DUPLICATE KEY UPDATE vault_value = VALUES(vault_value)",
+            &self.table
+        );
+        // Dropping `tx` without committing rolls back, so a failure half way through leaves
+        // the table untouched.
+        let mut tx = self.pool.begin().await.map_err(RvError::from_mysql)?;
+        for entry in entries.iter() {
+            sqlx::query(&sql)
+                .bind(entry.key.as_bytes())
+                .bind(&entry.value)
+                .execute(&mut *tx)
+                .await
+                .map_err(RvError::from_mysql)?;
+        }
+        tx.commit().await.map_err(RvError::from_mysql)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl TransactionalBackend for MysqlBackend {
+    async fn begin(&self) -> Result<Box<dyn Transaction>, RvError> {
+        let tx = self.pool.begin().await.map_err(RvError::from_mysql)?;
+        Ok(Box::new(MysqlTransaction {
+            tx,
+            table: self.table.clone(),
+        }))
+    }
+}
+
+pub struct MysqlTransaction {
+    tx: sqlx::Transaction<'static, MySql>,
+    table: String,
+}
+
+#[async_trait::async_trait]
+impl Transaction for MysqlTransaction {
+    async fn get(&mut self, key: &str) -> Result<Option<BackendEntry>, RvError> {
+        let sql = format!(
+            "SELECT vault_value FROM `{}` WHERE vault_key = ?",
+            &self.table
+        );
+        let ret: Option<Vec<u8>> = sqlx::query_scalar(&sql)
+            .bind(key.as_bytes())
+            .fetch_optional(&mut *self.tx)
+            .await
+            .map_err(RvError::from_mysql)?;
+
+        Ok(ret.map(|value| BackendEntry {
+            key: key.to_string(),
+            value,
+        }))
+    }
+
+    async fn put(&mut self, entry: &BackendEntry) -> Result<(), RvError> {
+        let sql = format!(
+            "INSERT INTO `{}` (vault_key, vault_value) VALUES (?, ?) ON DUPLICATE KEY UPDATE vault_value = VALUES(vault_value)",
+            &self.table
+        );
+        sqlx::query(&sql)
+            .bind(entry.key.as_bytes())
+            .bind(&entry.value)
+            .execute(&mut *self.tx)
+            .await
+            .map_err(RvError::from_mysql)?;
+
+        Ok(())
+    }
+
+    async fn delete(&mut self, key: &str) -> Result<(), RvError> {
+        let sql = format!("DELETE FROM `{}` WHERE vault_key = ?", &self.table);
+        sqlx::query(&sql)
+            .bind(key.as_bytes())
+            .execute(&mut *self.tx)
+            .await
+            .map_err(RvError::from_mysql)?;
+
+        Ok(())
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), RvError> {
+        self.tx.commit().await.map_err(RvError::from_mysql)?;
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), RvError> {
+        self.tx.rollback().await.map_err(RvError::from_mysql)?;
+        Ok(())
+    }
+}
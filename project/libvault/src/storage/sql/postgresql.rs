@@ -1 +1,622 @@
+use bytes::Bytes;
+use futures::stream;
+use regex::Regex;
+use serde::{Deserialize, Deserializer, de::Error as _};
+use serde_json::{Map, Value};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::{PgPool, Postgres};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    io,
+    str::FromStr,
+    time::Duration,
+};
+use tokio_util::io::StreamReader;
 
+use crate::{
+    errors::{RvError, is_sqlx_connection_error},
+    storage::{
+        Backend, BackendEntry, BackendValueReader, Transaction, TransactionalBackend,
+        sql::STREAM_CHUNK_SIZE,
+    },
+};
+
+const DEFAULT_POSTGRES_HOST: &str = "localhost";
+const DEFAULT_POSTGRES_PORT: u16 = 5432;
+const DEFAULT_POSTGRES_DATABASE: &str = "vault";
+const DEFAULT_POSTGRES_TABLE: &str = "vault";
+const DEFAULT_POSTGRES_TIMEOUT: u64 = 7200;
+// Pool defaults are the ones `PgPoolOptions::new()` starts from.
+const DEFAULT_POSTGRES_MAX_CONNECTIONS: u32 = 10;
+const DEFAULT_POSTGRES_IDLE_TIMEOUT: u64 = 600;
+const DEFAULT_POSTGRES_MAX_RETRIES: u32 = 3;
+const DEFAULT_POSTGRES_RETRY_BACKOFF: u64 = 100;
+
+#[derive(Clone, Debug)]
+pub struct PostgresBackendConfig {
+    url: Option<String>,
+    host: String,
+    port: u16,
+    user: Option<String>,
+    password: Option<String>,
+    database: String,
+    table: String,
+    max_connections: u32,
+    min_connections: u32,
+    acquire_timeout: Duration,
+    /// `None` keeps idle connections open forever.
+    idle_timeout: Option<Duration>,
+    /// How many times `get` and `put` retry a transient connection error before giving up.
+    max_retries: u32,
+    /// Delay before the first retry, doubled on each further one.
+    retry_backoff: Duration,
+}
+
+fn string_value(deserializer_map: &Map<String, Value>, env_key: &str, key: &str) -> Option<String> {
+    std::env::var(env_key).ok().or_else(|| {
+        deserializer_map.get(key).and_then(|value| {
+            serde_json::from_value::<String>(value.clone())
+                .map_err(|err| log::warn!("PostgreSQL Backend: `{key}` from value failed: {err:?}"))
+                .ok()
+        })
+    })
+}
+
+fn u32_value(
+    deserializer_map: &Map<String, Value>,
+    env_key: &str,
+    key: &str,
+) -> Result<Option<u32>, String> {
+    match std::env::var(env_key)
+        .map(Value::String)
+        .ok()
+        .or(deserializer_map.get(key).cloned())
+    {
+        Some(Value::String(value)) => value
+            .trim()
+            .parse::<u32>()
+            .map(Some)
+            .map_err(|err| format!("PostgreSQL Backend: `{key}` is invalid: {err}")),
+        Some(Value::Number(value)) => value
+            .as_u64()
+            .and_then(|value| u32::try_from(value).ok())
+            .map(Some)
+            .ok_or_else(|| format!("PostgreSQL Backend: `{key}` is out of range.")),
+        _ => Ok(None),
+    }
+}
+
+/// Durations accept a humantime string (`"30s"`) or a number of seconds. An empty string means
+/// "not set".
+fn duration_value(
+    deserializer_map: &Map<String, Value>,
+    env_key: &str,
+    key: &str,
+) -> Result<Option<Duration>, String> {
+    match std::env::var(env_key)
+        .map(Value::String)
+        .ok()
+        .or(deserializer_map.get(key).cloned())
+    {
+        Some(Value::String(duration)) if duration.trim().is_empty() => Ok(None),
+        Some(Value::String(duration)) => humantime::parse_duration(duration.trim())
+            .map(Some)
+            .map_err(|err| format!("PostgreSQL Backend: `{key}` is invalid: {err}")),
+        Some(Value::Number(secs)) => secs
+            .as_u64()
+            .map(|secs| Some(Duration::from_secs(secs)))
+            .ok_or_else(|| format!("PostgreSQL Backend: `{key}` is out of range.")),
+        _ => Ok(None),
+    }
+}
+
+impl<'de> Deserialize<'de> for PostgresBackendConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let default_cfg = Self::default();
+        let deserializer_map: Map<String, Value> = <Map<String, Value>>::deserialize(deserializer)?;
+
+        let max_connections = u32_value(
+            &deserializer_map,
+            "VAULT_POSTGRES_MAX_CONNECTIONS",
+            "max_connections",
+        )
+        .map_err(D::Error::custom)?
+        .unwrap_or(default_cfg.max_connections);
+        let min_connections = u32_value(
+            &deserializer_map,
+            "VAULT_POSTGRES_MIN_CONNECTIONS",
+            "min_connections",
+        )
+        .map_err(D::Error::custom)?
+        .unwrap_or(default_cfg.min_connections);
+        if max_connections == 0 || min_connections > max_connections {
+            return Err(D::Error::custom(
+                "PostgreSQL Backend: `max_connections` must be positive and at least \
+                 `min_connections`.",
+            ));
+        }
+
+        Ok(Self {
+            url: string_value(&deserializer_map, "VAULT_POSTGRES_URL", "url"),
+            host: string_value(&deserializer_map, "VAULT_POSTGRES_HOST", "host")
+                .unwrap_or(default_cfg.host),
+            port: match std::env::var("VAULT_POSTGRES_PORT")
+                .map(Value::String)
+                .ok()
+                .or(deserializer_map.get("port").cloned())
+            {
+                Some(Value::String(port)) => {
+                    port.trim().parse::<u16>().map_err(D::Error::custom)?
+                }
+                Some(Value::Number(port)) => port
+                    .as_u64()
+                    .and_then(|port| u16::try_from(port).ok())
+                    .ok_or_else(|| {
+                        D::Error::custom("PostgreSQL Backend: `port` is out of range.")
+                    })?,
+                _ => default_cfg.port,
+            },
+            user: string_value(&deserializer_map, "VAULT_POSTGRES_USER", "user"),
+            password: string_value(&deserializer_map, "VAULT_POSTGRES_PASSWORD", "password"),
+            database: string_value(&deserializer_map, "VAULT_POSTGRES_DATABASE", "database")
+                .unwrap_or(default_cfg.database),
+            table: deserializer_map
+                .get("table")
+                .and_then(|table| {
+                    serde_json::from_value::<String>(table.clone())
+                        .map_err(|err| {
+                            log::warn!("PostgreSQL Backend: `table` from value failed: {err:?}")
+                        })
+                        .ok()
+                })
+                .unwrap_or(default_cfg.table),
+            max_connections,
+            min_connections,
+            acquire_timeout: {
+                let timeout = duration_value(
+                    &deserializer_map,
+                    "VAULT_POSTGRES_ACQUIRE_TIMEOUT",
+                    "acquire_timeout",
+                )
+                .map_err(D::Error::custom)?
+                .unwrap_or(default_cfg.acquire_timeout);
+                match timeout.gt(&Duration::ZERO)
+                    && timeout.lt(&Duration::from_secs(DEFAULT_POSTGRES_TIMEOUT))
+                {
+                    true => timeout,
+                    false => Err(D::Error::custom(format!(
+                        "PostgreSQL Backend: `acquire_timeout` must be greater than 0s and less \
+                         than {}s.",
+                        DEFAULT_POSTGRES_TIMEOUT
+                    )))?,
+                }
+            },
+            idle_timeout: match deserializer_map.get("idle_timeout") {
+                // An explicit `null` turns the idle timeout off.
+                Some(Value::Null) if std::env::var("VAULT_POSTGRES_IDLE_TIMEOUT").is_err() => None,
+                _ => duration_value(
+                    &deserializer_map,
+                    "VAULT_POSTGRES_IDLE_TIMEOUT",
+                    "idle_timeout",
+                )
+                .map_err(D::Error::custom)?
+                .or(default_cfg.idle_timeout),
+            },
+            max_retries: u32_value(
+                &deserializer_map,
+                "VAULT_POSTGRES_MAX_RETRIES",
+                "max_retries",
+            )
+            .map_err(D::Error::custom)?
+            .unwrap_or(default_cfg.max_retries),
+            retry_backoff: duration_value(
+                &deserializer_map,
+                "VAULT_POSTGRES_RETRY_BACKOFF",
+                "retry_backoff",
+            )
+            .map_err(D::Error::custom)?
+            .unwrap_or(default_cfg.retry_backoff),
+        })
+    }
+}
+
+impl Default for PostgresBackendConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            host: DEFAULT_POSTGRES_HOST.to_string(),
+            port: DEFAULT_POSTGRES_PORT,
+            user: None,
+            password: None,
+            database: DEFAULT_POSTGRES_DATABASE.to_string(),
+            table: DEFAULT_POSTGRES_TABLE.to_string(),
+            max_connections: DEFAULT_POSTGRES_MAX_CONNECTIONS,
+            min_connections: 0,
+            acquire_timeout: Duration::from_secs(30),
+            idle_timeout: Some(Duration::from_secs(DEFAULT_POSTGRES_IDLE_TIMEOUT)),
+            max_retries: DEFAULT_POSTGRES_MAX_RETRIES,
+            retry_backoff: Duration::from_millis(DEFAULT_POSTGRES_RETRY_BACKOFF),
+        }
+    }
+}
+
+impl PostgresBackendConfig {
+    fn connect_options(&self) -> Result<PgConnectOptions, RvError> {
+        if let Some(url) = self.url.as_ref() {
+            return PgConnectOptions::from_str(url)
+                .map_err(|_| RvError::ErrDatabaseConnectionInfoInvalid);
+        }
+
+        let mut opts = PgConnectOptions::new()
+            .host(&self.host)
+            .port(self.port)
+            .database(&self.database);
+        if let Some(user) = self.user.as_ref() {
+            opts = opts.username(user);
+        }
+        if let Some(password) = self.password.as_ref() {
+            opts = opts.password(password);
+        }
+        Ok(opts)
+    }
+
+    fn pool_options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(self.acquire_timeout)
+            .idle_timeout(self.idle_timeout)
+    }
+}
+
+/// Whether `err` is a dropped or unavailable connection that is worth retrying, as opposed to an
+/// error the same query would hit again.
+fn is_transient(err: &sqlx::Error) -> bool {
+    matches!(err, sqlx::Error::PoolTimedOut) || is_sqlx_connection_error(err)
+}
+
+/// Keys are stored verbatim: a key with a leading `/` reads back unchanged and is listed
+/// under the `/` prefix.
+pub struct PostgresBackend {
+    pool: PgPool,
+    table: String,
+    max_retries: u32,
+    retry_backoff: Duration,
+}
+
+impl PostgresBackend {
+    pub async fn new(conf: &HashMap<String, Value>) -> Result<Self, RvError> {
+        let conf: PostgresBackendConfig = serde_json::from_value(serde_json::to_value(conf)?)?;
+        let re = Regex::new(r"^(?-u:\w)+$").expect("PostgreSQL regex init failed");
+        if !re.is_match(&conf.table) {
+            let err = RvError::ErrPostgresDisallowedFields(conf.table.clone());
+            log::debug!("{err:?}");
+            Err(err)?;
+        }
+
+        let pool = conf
+            .pool_options()
+            .connect_with(conf.connect_options()?)
+            .await?;
+        sqlx::query(&format!(
+            r#"CREATE TABLE IF NOT EXISTS "{}" (
+    vault_key TEXT NOT NULL,
+    vault_value BYTEA NOT NULL,
+    PRIMARY KEY (vault_key)
+);"#,
+            conf.table
+        ))
+        .execute(&pool)
+        .await?;
+
+        Ok(Self {
+            pool,
+            table: conf.table,
+            max_retries: conf.max_retries,
+            retry_backoff: conf.retry_backoff,
+        })
+    }
+
+    /// Run `op`, retrying it with exponential backoff while it fails with a transient error.
+    async fn with_retry<T, F, Fut>(&self, mut op: F) -> Result<T, sqlx::Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        let mut backoff = self.retry_backoff;
+        let mut attempt = 0;
+        loop {
+            match op().await {
+                Err(err) if attempt < self.max_retries && is_transient(&err) => {
+                    attempt += 1;
+                    log::warn!(
+                        "PostgreSQL Backend: transient error, retry {attempt}/{} in {backoff:?}: \
+                         {err:?}",
+                        self.max_retries
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = backoff.saturating_mul(2);
+                }
+                ret => return ret,
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Backend for PostgresBackend {
+    async fn get(&self, key: &str) -> Result<Option<BackendEntry>, RvError> {
+        let sql = format!(
+            r#"SELECT vault_value FROM "{}" WHERE vault_key = $1"#,
+            &self.table
+        );
+        let ret: Option<Vec<u8>> = self
+            .with_retry(|| {
+                sqlx::query_scalar(&sql)
+                    .bind(key)
+                    .fetch_optional(&self.pool)
+            })
+            .await?;
+
+        Ok(ret.map(|value| BackendEntry {
+            key: key.to_string(),
+            value,
+        }))
+    }
+
+    async fn put(&self, entry: &BackendEntry) -> Result<(), RvError> {
+        let sql = format!(
+            r#"INSERT INTO "{}" (vault_key, vault_value) VALUES ($1, $2) ON CONFLICT (vault_key) DO UPDATE SET vault_value = EXCLUDED.vault_value"#,
+            &self.table
+        );
+        self.with_retry(|| {
+            sqlx::query(&sql)
+                .bind(&entry.key)
+                .bind(&entry.value)
+                .execute(&self.pool)
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    async fn compare_and_swap(
+        &self,
+        entry: &BackendEntry,
+        expected: Option<&[u8]>,
+    ) -> Result<bool, RvError> {
+        // Not retried: a swap that landed before a transient error would read back as a conflict.
+        let ret = match expected {
+            Some(expected) => {
+                let sql = format!(
+                    r#"UPDATE "{}" SET vault_value = $1 WHERE vault_key = $2 AND vault_value = $3"#,
+                    &self.table
+                );
+                sqlx::query(&sql)
+                    .bind(&entry.value)
+                    .bind(&entry.key)
+                    .bind(expected)
+                    .execute(&self.pool)
+                    .await?
+            }
+            None => {
+                let sql = format!(
+                    r#"INSERT INTO "{}" (vault_key, vault_value) VALUES ($1, $2) ON CONFLICT (vault_key) DO NOTHING"#,
+                    &self.table
+                );
+                sqlx::query(&sql)
+                    .bind(&entry.key)
+                    .bind(&entry.value)
+                    .execute(&self.pool)
+                    .await?
+            }
+        };
+
+        Ok(ret.rows_affected() == 1)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), RvError> {
+        let sql = format!(r#"DELETE FROM "{}" WHERE vault_key = $1"#, &self.table);
+        sqlx::query(&sql).bind(key).execute(&self.pool).await?;
+
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, RvError> {
+        let sql = format!(
+            r#"SELECT vault_key FROM "{}" WHERE vault_key LIKE $1 ESCAPE '\'"#,
+            &self.table
+        );
+        // Escape the LIKE wildcard characters (% and _) and the escape character (\)
+        // so that `prefix` is treated as a literal prefix.
+        let escaped_prefix = prefix
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let keys: Vec<String> = sqlx::query_scalar(&sql)
+            .bind(format!("{}%", escaped_prefix))
+            .fetch_all(&self.pool)
+            .await?;
+        let mut res = HashSet::new();
+        for key in keys {
+            let key = key.strip_prefix(prefix).unwrap_or(&key);
+
+            match key.find('/') {
+                Some(i) => {
+                    let key = &key[0..i + 1];
+                    res.insert(key.to_string());
+                }
+                None => {
+                    res.insert(key.to_string());
+                }
+            }
+        }
+
+        Ok(res.into_iter().collect())
+    }
+
+    /// Reads the bytea `STREAM_CHUNK_SIZE` bytes at a time. All chunks come from one
+    /// REPEATABLE READ transaction, so a concurrent `put` is never seen halfway; the reader
+    /// holds a pooled connection until it is dropped.
+    async fn get_stream(&self, key: &str) -> Result<Option<BackendValueReader>, RvError> {
+        let mut tx = self.with_retry(|| self.pool.begin()).await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ")
+            .execute(&mut *tx)
+            .await?;
+        let len: Option<i64> = sqlx::query_scalar(&format!(
+            r#"SELECT octet_length(vault_value)::bigint FROM "{}" WHERE vault_key = $1"#,
+            &self.table
+        ))
+        .bind(key)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(len) = len else {
+            return Ok(None);
+        };
+
+        // substring() counts bytea offsets in bytes, starting at 1.
+        let sql = format!(
+            r#"SELECT substring(vault_value FROM $1::integer FOR $2::integer) FROM "{}" WHERE vault_key = $3"#,
+            &self.table
+        );
+        let key = key.to_string();
+        let chunks = stream::try_unfold((tx, 0_i64), move |(mut tx, offset)| {
+            let (sql, key) = (sql.clone(), key.clone());
+            async move {
+                if offset >= len {
+                    return Ok(None);
+                }
+                let chunk: Option<Vec<u8>> = sqlx::query_scalar(&sql)
+                    .bind(offset + 1)
+                    .bind(STREAM_CHUNK_SIZE)
+                    .bind(key)
+                    .fetch_optional(&mut *tx)
+                    .await
+                    .map_err(io::Error::other)?;
+                Ok::<_, io::Error>(chunk.filter(|chunk| !chunk.is_empty()).map(|chunk| {
+                    let next = offset + chunk.len() as i64;
+                    (Bytes::from(chunk), (tx, next))
+                }))
+            }
+        });
+        Ok(Some(Box::new(StreamReader::new(Box::pin(chunks)))))
+    }
+
+    fn as_transactional(&self) -> Option<&dyn TransactionalBackend> {
+        Some(self)
+    }
+
+    async fn get_batch(&self, keys: &[&str]) -> Result<Vec<Option<BackendEntry>>, RvError> {
+        // Postgres binds the whole key list as one array parameter, so no chunking is needed.
+        let sql = format!(
+            r#"SELECT vault_key, vault_value FROM "{}" WHERE vault_key = ANY($1)"#,
+            &self.table
+        );
+        let found: HashMap<String, Vec<u8>> = sqlx::query_as::<_, (String, Vec<u8>)>(&sql)
+            .bind(keys)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .collect();
+
+        Ok(keys
+            .iter()
+            .map(|key| {
+                found.get(*key).map(|value| BackendEntry {
+                    key: key.to_string(),
+                    value: value.clone(),
+                })
+            })
+            .collect())
+    }
+
+    async fn put_batch(&self, entries: &[BackendEntry]) -> Result<(), RvError> {
+        let sql = format!(
+            r#"INSERT INTO "{}" (vault_key, vault_value) VALUES ($1, $2) ON CONFLICT (vault_key) DO UPDATE SET vault_value = EXCLUDED.vault_value"#,
+            &self.table
+        );
+        // Dropping `tx` without committing rolls back, so a failure half way through leaves
+        // the table untouched.
+        let mut tx = self.pool.begin().await?;
+        for entry in entries.iter() {
+            sqlx::query(&sql)
+                .bind(&entry.key)
+                .bind(&entry.value)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl TransactionalBackend for PostgresBackend {
+    async fn begin(&self) -> Result<Box<dyn Transaction>, RvError> {
+        let tx = self.pool.begin().await?;
+        Ok(Box::new(PostgresTransaction {
+            tx,
+            table: self.table.clone(),
+        }))
+    }
+}
+
+pub struct PostgresTransaction {
+    tx: sqlx::Transaction<'static, Postgres>,
+    table: String,
+}
+
+#[async_trait::async_trait]
+impl Transaction for PostgresTransaction {
+    async fn get(&mut self, key: &str) -> Result<Option<BackendEntry>, RvError> {
+        let sql = format!(
+            r#"SELECT vault_value FROM "{}" WHERE vault_key = $1"#,
+            &self.table
+        );
+        let ret: Option<Vec<u8>> = sqlx::query_scalar(&sql)
+            .bind(key)
+            .fetch_optional(&mut *self.tx)
+            .await?;
+
+        Ok(ret.map(|value| BackendEntry {
+            key: key.to_string(),
+            value,
+        }))
+    }
+
+    async fn put(&mut self, entry: &BackendEntry) -> Result<(), RvError> {
+        let sql = format!(
+            r#"INSERT INTO "{}" (vault_key, vault_value) VALUES ($1, $2) ON CONFLICT (vault_key) DO UPDATE SET vault_value = EXCLUDED.vault_value"#,
+            &self.table
+        );
+        sqlx::query(&sql)
+            .bind(&entry.key)
+            .bind(&entry.value)
+            .execute(&mut *self.tx)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&mut self, key: &str) -> Result<(), RvError> {
+        let sql = format!(r#"DELETE FROM "{}" WHERE vault_key = $1"#, &self.table);
+        sqlx::query(&sql).bind(key).execute(&mut *self.tx).await?;
+
+        Ok(())
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), RvError> {
+        self.tx.commit().await?;
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), RvError> {
+        self.tx.rollback().await?;
+        Ok(())
+    }
+}
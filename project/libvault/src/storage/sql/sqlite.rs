@@ -1,30 +1,47 @@
+use bytes::Bytes;
+use futures::stream;
 use regex::Regex;
 use serde::{Deserialize, Deserializer, de::Error as _};
 use serde_json::{Map, Value};
-use sqlx::SqlitePool;
-use sqlx::sqlite::SqliteConnectOptions;
-use std::{
-    collections::{HashMap, HashSet},
-    env,
-    path::PathBuf,
-    time::Duration,
-};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqliteSynchronous};
+use sqlx::{Sqlite, SqlitePool};
+use std::{collections::HashMap, env, io, path::PathBuf, str::FromStr, time::Duration};
+use tokio_util::io::StreamReader;
 
 use crate::{
     errors::RvError,
-    storage::{Backend, BackendEntry},
+    storage::{
+        Backend, BackendEntry, BackendValueReader, ListPage, Transaction, TransactionalBackend,
+        sql::STREAM_CHUNK_SIZE,
+    },
 };
 
 const DEFAULT_SQLITE_FILENAME: &str = "vault.db";
 const DEFAULT_SQLITE_TABLE: &str = "vault";
 const DEFAULT_SQLITE_TIMEOUT: u64 = 7200;
+/// Upper bound of keys bound into a single `IN (...)` clause by `get_batch`.
+const SQLITE_BATCH_SIZE: usize = 500;
+/// Page size `list` walks the table with.
+const SQLITE_LIST_PAGE_SIZE: usize = 1000;
 
 #[derive(Clone, Debug)]
 pub struct SqliteBackendConfig {
     filename: PathBuf,
     table: String,
-    timeout: Duration,
+    /// How long a connection waits on a locked database. Also read from the older `timeout` key.
+    busy_timeout: Duration,
     create_if_missing: bool,
+    /// WAL by default, so that readers are not blocked behind the single writer.
+    journal_mode: SqliteJournalMode,
+    synchronous: SqliteSynchronous,
+}
+
+/// Look `key` up in the `VAULT_SQLITE_*` environment variable first, then in the config map.
+fn env_or_value(deserializer_map: &Map<String, Value>, env_key: &str, key: &str) -> Option<Value> {
+    std::env::var(env_key)
+        .map(Value::String)
+        .ok()
+        .or(deserializer_map.get(key).cloned())
 }
 
 impl<'de> Deserialize<'de> for SqliteBackendConfig {
@@ -86,14 +103,19 @@ impl<'de> Deserialize<'de> for SqliteBackendConfig {
                         .ok()
                 })
                 .unwrap_or(default_cfg.table),
-            timeout: {
-                let timeout = match std::env::var("VAULT_SQLITE_TIMEOUT")
-                    .map(Value::String)
-                    .ok()
-                    .or(deserializer_map.get("timeout").cloned())
-                {
+            busy_timeout: {
+                let timeout = match env_or_value(
+                    &deserializer_map,
+                    "VAULT_SQLITE_BUSY_TIMEOUT",
+                    "busy_timeout",
+                )
+                .or(env_or_value(
+                    &deserializer_map,
+                    "VAULT_SQLITE_TIMEOUT",
+                    "timeout",
+                )) {
                     Some(Value::String(duration)) => match duration.is_empty() {
-                        true => default_cfg.timeout,
+                        true => default_cfg.busy_timeout,
                         false => {
                             humantime::parse_duration(duration.trim()).map_err(D::Error::custom)?
                         }
@@ -101,7 +123,7 @@ impl<'de> Deserialize<'de> for SqliteBackendConfig {
                     Some(Value::Number(secs)) => {
                         Duration::from_secs(secs.as_u64().unwrap_or(5_u64))
                     }
-                    _ => default_cfg.timeout,
+                    _ => default_cfg.busy_timeout,
                 };
                 match timeout.gt(&Duration::ZERO)
                     && timeout.lt(&Duration::from_secs(DEFAULT_SQLITE_TIMEOUT))
@@ -113,6 +135,36 @@ impl<'de> Deserialize<'de> for SqliteBackendConfig {
                     )))?,
                 }
             },
+            journal_mode: match env_or_value(
+                &deserializer_map,
+                "VAULT_SQLITE_JOURNAL_MODE",
+                "journal_mode",
+            ) {
+                Some(Value::String(mode)) if !mode.trim().is_empty() => {
+                    SqliteJournalMode::from_str(mode.trim()).map_err(|_| {
+                        D::Error::custom(format!(
+                            "SQLite Backend: `journal_mode` must be one of DELETE, TRUNCATE, \
+                             PERSIST, MEMORY, WAL or OFF, got {mode:?}."
+                        ))
+                    })?
+                }
+                _ => default_cfg.journal_mode,
+            },
+            synchronous: match env_or_value(
+                &deserializer_map,
+                "VAULT_SQLITE_SYNCHRONOUS",
+                "synchronous",
+            ) {
+                Some(Value::String(mode)) if !mode.trim().is_empty() => {
+                    SqliteSynchronous::from_str(mode.trim()).map_err(|_| {
+                        D::Error::custom(format!(
+                            "SQLite Backend: `synchronous` must be one of OFF, NORMAL, FULL or \
+                             EXTRA, got {mode:?}."
+                        ))
+                    })?
+                }
+                _ => default_cfg.synchronous,
+            },
             create_if_missing,
         })
     }
@@ -123,12 +175,16 @@ impl Default for SqliteBackendConfig {
         Self {
             filename: env::temp_dir().join(DEFAULT_SQLITE_FILENAME),
             table: DEFAULT_SQLITE_TABLE.to_string(),
-            timeout: Duration::new(5, 0),
+            busy_timeout: Duration::new(5, 0),
             create_if_missing: true,
+            journal_mode: SqliteJournalMode::Wal,
+            synchronous: SqliteSynchronous::Full,
         }
     }
 }
 
+/// Keys are stored verbatim: a key with a leading `/` reads back unchanged and is listed
+/// under the `/` prefix.
 pub struct SqliteBackend {
     pool: SqlitePool,
     table: String,
@@ -145,7 +201,9 @@ impl SqliteBackend {
         }
         let opts = SqliteConnectOptions::new()
             .filename(conf.filename)
-            .busy_timeout(conf.timeout)
+            .busy_timeout(conf.busy_timeout)
+            .journal_mode(conf.journal_mode)
+            .synchronous(conf.synchronous)
             .create_if_missing(conf.create_if_missing)
             .read_only(false);
         log::debug!("Sqlite connect options: {:?}", opts);
@@ -175,11 +233,6 @@ impl Backend for SqliteBackend {
         #[derive(Debug, sqlx::FromRow)]
         struct SqliteBackendEntry(Vec<u8>);
 
-        // This will change.
-        if key.starts_with("/") {
-            return Err(RvError::ErrSqliteBackendNotSupportAbsolute);
-        }
-
         let sql = format!(
             "SELECT vault_value FROM `{}` WHERE vault_key = ?",
             &self.table
@@ -200,10 +253,6 @@ impl Backend for SqliteBackend {
     }
 
     async fn put(&self, entry: &BackendEntry) -> Result<(), RvError> {
-        if entry.key.starts_with("/") {
-            Err(RvError::ErrSqliteBackendNotSupportAbsolute)?;
-        }
-
         let sql = format!(
             "INSERT INTO `{}` (vault_key, vault_value) VALUES (?, ?) ON CONFLICT(vault_key) DO UPDATE SET vault_value = excluded.vault_value",
             &self.table
@@ -217,11 +266,41 @@ impl Backend for SqliteBackend {
         Ok(())
     }
 
-    async fn delete(&self, key: &str) -> Result<(), RvError> {
-        if key.starts_with("/") {
-            Err(RvError::ErrSqliteBackendNotSupportAbsolute)?;
-        }
+    async fn compare_and_swap(
+        &self,
+        entry: &BackendEntry,
+        expected: Option<&[u8]>,
+    ) -> Result<bool, RvError> {
+        let ret = match expected {
+            Some(expected) => {
+                let sql = format!(
+                    "UPDATE `{}` SET vault_value = ? WHERE vault_key = ? AND vault_value = ?",
+                    &self.table
+                );
+                sqlx::query(&sql)
+                    .bind(&entry.value)
+                    .bind(entry.key.as_bytes())
+                    .bind(expected)
+                    .execute(&self.pool)
+                    .await?
+            }
+            None => {
+                let sql = format!(
+                    "INSERT INTO `{}` (vault_key, vault_value) VALUES (?, ?) ON CONFLICT(vault_key) DO NOTHING",
+                    &self.table
+                );
+                sqlx::query(&sql)
+                    .bind(entry.key.as_bytes())
+                    .bind(&entry.value)
+                    .execute(&self.pool)
+                    .await?
+            }
+        };
 
+        Ok(ret.rows_affected() == 1)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), RvError> {
         let sql = format!("DELETE FROM `{}` WHERE vault_key = ?", &self.table);
         sqlx::query(&sql)
             .bind(key.as_bytes())
@@ -232,12 +311,30 @@ impl Backend for SqliteBackend {
     }
 
     async fn list(&self, prefix: &str) -> Result<Vec<String>, RvError> {
-        if prefix.starts_with("/") {
-            Err(RvError::ErrSqliteBackendNotSupportAbsolute)?;
+        let mut res = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            let page = self
+                .list_page(prefix, after.as_deref(), SQLITE_LIST_PAGE_SIZE)
+                .await?;
+            res.extend(page.keys);
+            match page.next {
+                Some(next) => after = Some(next),
+                None => break,
+            }
         }
 
+        Ok(res)
+    }
+
+    async fn list_page(
+        &self,
+        prefix: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<ListPage, RvError> {
         let sql = format!(
-            "SELECT vault_key FROM `{}` WHERE vault_key LIKE ? ESCAPE '\\\\'",
+            "SELECT vault_key FROM `{}` WHERE vault_key LIKE ? ESCAPE '\\' AND vault_key > ? ORDER BY vault_key LIMIT ?",
             &self.table
         );
         // Escape the LIKE wildcard characters (% and _) and the escape character (\)
@@ -246,26 +343,213 @@ impl Backend for SqliteBackend {
             .replace('\\', "\\\\")
             .replace('%', "\\%")
             .replace('_', "\\_");
-        let keys: Vec<Vec<u8>> = sqlx::query_scalar(&sql)
-            .bind(format!("{}%", escaped_prefix).as_bytes())
-            .fetch_all(&self.pool)
-            .await?;
-        let mut res = HashSet::new();
-        for key_bytes in keys {
-            let key = String::from_utf8(key_bytes)?;
-            let key = key.strip_prefix(prefix).unwrap_or(&key);
-
-            match key.find('/') {
-                Some(i) => {
-                    let key = &key[0..i + 1];
-                    res.insert(key.to_string());
+        let pattern = format!("{}%", escaped_prefix);
+
+        // Keys are compared as bytes. A folded `dir/` child is skipped as a whole by seeking past
+        // every key it covers: 0xFF never occurs in UTF-8, so it sorts after all of them.
+        let seek_past = |child: &str| {
+            let mut bound = format!("{prefix}{child}").into_bytes();
+            if child.ends_with('/') {
+                bound.push(0xFF);
+            }
+            bound
+        };
+
+        let mut children: Vec<String> = Vec::new();
+        let mut lower = after.map(seek_past).unwrap_or_default();
+        'scan: while children.len() <= limit {
+            let wanted = limit + 1 - children.len();
+            let keys: Vec<Vec<u8>> = sqlx::query_scalar(&sql)
+                .bind(pattern.as_bytes())
+                .bind(&lower)
+                .bind(wanted as i64)
+                .fetch_all(&self.pool)
+                .await?;
+            if keys.is_empty() {
+                break;
+            }
+
+            for key_bytes in keys {
+                let key = String::from_utf8(key_bytes)?;
+                let key = key.strip_prefix(prefix).unwrap_or(&key);
+                let child = match key.find('/') {
+                    Some(i) => &key[0..i + 1],
+                    None => key,
+                };
+
+                lower = seek_past(child);
+                children.push(child.to_string());
+                if child.ends_with('/') || children.len() > limit {
+                    continue 'scan;
                 }
-                None => {
-                    res.insert(key.to_string());
+            }
+        }
+
+        Ok(ListPage::from_sorted(children, None, limit))
+    }
+
+    /// Reads the BLOB `STREAM_CHUNK_SIZE` bytes at a time. All chunks come from one read
+    /// transaction, so a concurrent `put` is never seen halfway; the reader holds a pooled
+    /// connection until it is dropped.
+    async fn get_stream(&self, key: &str) -> Result<Option<BackendValueReader>, RvError> {
+        let mut tx = self.pool.begin().await?;
+        let len: Option<i64> = sqlx::query_scalar(&format!(
+            "SELECT length(vault_value) FROM `{}` WHERE vault_key = ?",
+            &self.table
+        ))
+        .bind(key.as_bytes())
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(len) = len else {
+            return Ok(None);
+        };
+
+        // substr() counts BLOB offsets in bytes, starting at 1.
+        let sql = format!(
+            "SELECT substr(vault_value, ?, ?) FROM `{}` WHERE vault_key = ?",
+            &self.table
+        );
+        let key = key.as_bytes().to_vec();
+        let chunks = stream::try_unfold((tx, 0_i64), move |(mut tx, offset)| {
+            let (sql, key) = (sql.clone(), key.clone());
+            async move {
+                if offset >= len {
+                    return Ok(None);
                 }
+                let chunk: Option<Vec<u8>> = sqlx::query_scalar(&sql)
+                    .bind(offset + 1)
+                    .bind(STREAM_CHUNK_SIZE)
+                    .bind(key)
+                    .fetch_optional(&mut *tx)
+                    .await
+                    .map_err(io::Error::other)?;
+                Ok::<_, io::Error>(chunk.filter(|chunk| !chunk.is_empty()).map(|chunk| {
+                    let next = offset + chunk.len() as i64;
+                    (Bytes::from(chunk), (tx, next))
+                }))
             }
+        });
+        Ok(Some(Box::new(StreamReader::new(Box::pin(chunks)))))
+    }
+
+    fn as_transactional(&self) -> Option<&dyn TransactionalBackend> {
+        Some(self)
+    }
+
+    async fn get_batch(&self, keys: &[&str]) -> Result<Vec<Option<BackendEntry>>, RvError> {
+        let mut found: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+        for chunk in keys.chunks(SQLITE_BATCH_SIZE) {
+            let sql = format!(
+                "SELECT vault_key, vault_value FROM `{}` WHERE vault_key IN ({})",
+                &self.table,
+                vec!["?"; chunk.len()].join(", ")
+            );
+            let mut query = sqlx::query_as::<_, (Vec<u8>, Vec<u8>)>(&sql);
+            for key in chunk.iter() {
+                query = query.bind(key.as_bytes());
+            }
+            for (key, value) in query.fetch_all(&self.pool).await? {
+                found.insert(key, value);
+            }
+        }
+
+        Ok(keys
+            .iter()
+            .map(|key| {
+                found.get(key.as_bytes()).map(|value| BackendEntry {
+                    key: key.to_string(),
+                    value: value.clone(),
+                })
+            })
+            .collect())
+    }
+
+    async fn put_batch(&self, entries: &[BackendEntry]) -> Result<(), RvError> {
+        let sql = format!(
+            "INSERT INTO `{}` (vault_key, vault_value) VALUES (?, ?) ON CONFLICT(vault_key) DO UPDATE SET vault_value = excluded.vault_value",
+            &self.table
+        );
+        // Dropping `tx` without committing rolls back, so a failure half way through leaves
+        // the table untouched.
+        let mut tx = self.pool.begin().await?;
+        for entry in entries.iter() {
+            sqlx::query(&sql)
+                .bind(entry.key.as_bytes())
+                .bind(&entry.value)
+                .execute(&mut *tx)
+                .await?;
         }
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl TransactionalBackend for SqliteBackend {
+    async fn begin(&self) -> Result<Box<dyn Transaction>, RvError> {
+        let tx = self.pool.begin().await?;
+        Ok(Box::new(SqliteTransaction {
+            tx,
+            table: self.table.clone(),
+        }))
+    }
+}
+
+pub struct SqliteTransaction {
+    tx: sqlx::Transaction<'static, Sqlite>,
+    table: String,
+}
+
+#[async_trait::async_trait]
+impl Transaction for SqliteTransaction {
+    async fn get(&mut self, key: &str) -> Result<Option<BackendEntry>, RvError> {
+        let sql = format!(
+            "SELECT vault_value FROM `{}` WHERE vault_key = ?",
+            &self.table
+        );
+        let ret: Option<Vec<u8>> = sqlx::query_scalar(&sql)
+            .bind(key.as_bytes())
+            .fetch_optional(&mut *self.tx)
+            .await?;
 
-        Ok(res.into_iter().collect())
+        Ok(ret.map(|value| BackendEntry {
+            key: key.to_string(),
+            value,
+        }))
+    }
+
+    async fn put(&mut self, entry: &BackendEntry) -> Result<(), RvError> {
+        let sql = format!(
+            "INSERT INTO `{}` (vault_key, vault_value) VALUES (?, ?) ON CONFLICT(vault_key) DO UPDATE SET vault_value = excluded.vault_value",
+            &self.table
+        );
+        sqlx::query(&sql)
+            .bind(entry.key.as_bytes())
+            .bind(&entry.value)
+            .execute(&mut *self.tx)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&mut self, key: &str) -> Result<(), RvError> {
+        let sql = format!("DELETE FROM `{}` WHERE vault_key = ?", &self.table);
+        sqlx::query(&sql)
+            .bind(key.as_bytes())
+            .execute(&mut *self.tx)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), RvError> {
+        self.tx.commit().await?;
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), RvError> {
+        self.tx.rollback().await?;
+        Ok(())
     }
 }
@@ -13,10 +13,12 @@ use openssl::{
     ec::{EcGroup, EcKey},
     hash::MessageDigest,
     nid::Nid,
-    pkey::{PKey, Private},
+    pkey::{HasPublic, PKey, PKeyRef, Private},
     rsa::Rsa,
+    stack::Stack,
     x509::{
-        X509, X509Builder, X509Extension, X509Name, X509NameBuilder, X509Ref,
+        X509, X509Builder, X509Extension, X509Name, X509NameBuilder, X509Ref, X509Req,
+        X509ReqBuilder,
         extension::{
             AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage,
             SubjectAlternativeName, SubjectKeyIdentifier,
@@ -135,6 +137,57 @@ pub fn generate_serial_number() -> BigNum {
     sn
 }
 
+pub fn generate_private_key(key_type: &str, key_bits: u32) -> Result<PKey<Private>, RvError> {
+    let priv_key = match key_type {
+        "rsa" => match key_bits {
+            2048 | 3072 | 4096 => {
+                let rsa_key = Rsa::generate(key_bits)?;
+                PKey::from_rsa(rsa_key)?
+            }
+            _ => return Err(RvError::ErrPkiKeyBitsInvalid),
+        },
+        "ec" => {
+            let curve_name = match key_bits {
+                224 => Nid::SECP224R1,
+                256 => Nid::X9_62_PRIME256V1,
+                384 => Nid::SECP384R1,
+                521 => Nid::SECP521R1,
+                _ => return Err(RvError::ErrPkiKeyBitsInvalid),
+            };
+            let ec_group = EcGroup::from_curve_name(curve_name)?;
+            let ec_key = EcKey::generate(ec_group.as_ref())?;
+            PKey::from_ec_key(ec_key)?
+        }
+        #[cfg(feature = "crypto_adaptor_tongsuo")]
+        "sm2" => {
+            if key_bits != 256 {
+                return Err(RvError::ErrPkiKeyBitsInvalid);
+            }
+            let ec_group = EcGroup::from_curve_name(Nid::SM2)?;
+            let ec_key = EcKey::generate(&ec_group)?;
+            PKey::from_ec_key(ec_key)?
+        }
+        _ => return Err(RvError::ErrPkiKeyTypeInvalid),
+    };
+
+    Ok(priv_key)
+}
+
+/// Format a certificate serial number as lowercase, colon-separated hex.
+pub fn format_serial_number(cert: &X509Ref) -> Result<String, RvError> {
+    let serial_number = cert.serial_number().to_bn()?;
+    let serial_number_hex = serial_number.to_hex_str()?;
+    let serial_number_hex = serial_number_hex
+        .chars()
+        .collect::<Vec<char>>()
+        .chunks(2)
+        .map(|chunk| chunk.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join(":");
+
+    Ok(serial_number_hex.to_lowercase())
+}
+
 pub fn has_x509_ext_key_usage(x509: &X509) -> bool {
     unsafe { X509_get_extension_flags(x509.as_ptr()) & EXFLAG_XKUSAGE != 0 }
 }
@@ -234,6 +287,36 @@ impl Certificate {
         ca_key: Option<&PKey<Private>>,
         private_key: &PKey<Private>,
     ) -> Result<X509, RvError> {
+        let mut builder = self.x509_builder(ca_cert, private_key)?;
+
+        let digest = self.digest()?;
+        if let Some(key) = ca_key {
+            builder.sign(key, digest)?;
+        } else {
+            builder.sign(private_key, digest)?;
+        }
+
+        Ok(builder.build())
+    }
+
+    /// Issue a certificate for a public key the caller holds the private half of, e.g. one taken
+    /// from a CSR. Unlike `to_x509` there is no self-signed variant.
+    pub fn sign_public_key<T: HasPublic>(
+        &mut self,
+        ca_cert: &X509Ref,
+        ca_key: &PKey<Private>,
+        public_key: &PKeyRef<T>,
+    ) -> Result<X509, RvError> {
+        let mut builder = self.x509_builder(Some(ca_cert), public_key)?;
+        builder.sign(ca_key, self.digest()?)?;
+        Ok(builder.build())
+    }
+
+    fn x509_builder<T: HasPublic>(
+        &mut self,
+        ca_cert: Option<&X509Ref>,
+        public_key: &PKeyRef<T>,
+    ) -> Result<X509Builder, RvError> {
         let mut builder = X509::builder()?;
         builder.set_version(self.version)?;
         let serial_number = self.serial_number.to_asn1_integer()?;
@@ -244,7 +327,7 @@ impl Certificate {
         } else {
             builder.set_issuer_name(&self.subject)?;
         }
-        builder.set_pubkey(private_key)?;
+        builder.set_pubkey(public_key)?;
 
         let not_before_dur = self.not_before.duration_since(UNIX_EPOCH)?;
         let not_before = Asn1Time::from_unix(not_before_dur.as_secs() as i64)?;
@@ -319,76 +402,69 @@ impl Certificate {
             .build(&builder.x509v3_context(ca_cert, None))?;
         builder.append_extension(authority_key_id)?;
 
-        let digest = match self.key_type.as_str() {
-            "rsa" | "ec" => MessageDigest::sha256(),
-            #[cfg(feature = "crypto_adaptor_tongsuo")]
-            "sm2" => MessageDigest::sm3(),
-            _ => return Err(RvError::ErrPkiKeyTypeInvalid),
-        };
-        if let Some(key) = ca_key {
-            builder.sign(key, digest)?;
-        } else {
-            builder.sign(private_key, digest)?;
+        Ok(builder)
+    }
+
+    /// Build a certificate signing request for `private_key` carrying this certificate's subject
+    /// and SANs, for a parent CA to sign.
+    pub fn to_csr(&self, private_key: &PKey<Private>) -> Result<X509Req, RvError> {
+        let mut builder = X509ReqBuilder::new()?;
+        builder.set_version(0)?;
+        builder.set_subject_name(&self.subject)?;
+        builder.set_pubkey(private_key)?;
+
+        if (self.dns_sans.len() | self.email_sans.len() | self.ip_sans.len() | self.uri_sans.len())
+            > 0
+        {
+            let mut san_ext = SubjectAlternativeName::new();
+            for dns in &self.dns_sans {
+                san_ext.dns(dns.as_str());
+            }
+            for email in &self.email_sans {
+                san_ext.email(email.as_str());
+            }
+            for ip in &self.ip_sans {
+                san_ext.ip(ip.as_str());
+            }
+            for uri in &self.uri_sans {
+                san_ext.uri(uri.as_str());
+            }
+
+            let mut extensions = Stack::new()?;
+            extensions.push(san_ext.build(&builder.x509v3_context(None))?)?;
+            builder.add_extensions(&extensions)?;
         }
 
+        builder.sign(private_key, self.digest()?)?;
+
         Ok(builder.build())
     }
 
+    fn digest(&self) -> Result<MessageDigest, RvError> {
+        match self.key_type.as_str() {
+            "rsa" | "ec" => Ok(MessageDigest::sha256()),
+            #[cfg(feature = "crypto_adaptor_tongsuo")]
+            "sm2" => Ok(MessageDigest::sm3()),
+            _ => Err(RvError::ErrPkiKeyTypeInvalid),
+        }
+    }
+
     pub fn to_cert_bundle(
         &mut self,
         ca_cert: Option<&X509Ref>,
         ca_key: Option<&PKey<Private>>,
     ) -> Result<CertBundle, RvError> {
-        let key_bits = self.key_bits;
-        let priv_key = match self.key_type.as_str() {
-            "rsa" => match key_bits {
-                2048 | 3072 | 4096 => {
-                    let rsa_key = Rsa::generate(key_bits)?;
-                    PKey::from_rsa(rsa_key)?
-                }
-                _ => return Err(RvError::ErrPkiKeyBitsInvalid),
-            },
-            "ec" => {
-                let curve_name = match key_bits {
-                    224 => Nid::SECP224R1,
-                    256 => Nid::X9_62_PRIME256V1,
-                    384 => Nid::SECP384R1,
-                    521 => Nid::SECP521R1,
-                    _ => return Err(RvError::ErrPkiKeyBitsInvalid),
-                };
-                let ec_group = EcGroup::from_curve_name(curve_name)?;
-                let ec_key = EcKey::generate(ec_group.as_ref())?;
-                PKey::from_ec_key(ec_key)?
-            }
-            #[cfg(feature = "crypto_adaptor_tongsuo")]
-            "sm2" => {
-                if key_bits != 256 {
-                    return Err(RvError::ErrPkiKeyBitsInvalid);
-                }
-                let ec_group = EcGroup::from_curve_name(Nid::SM2)?;
-                let ec_key = EcKey::generate(&ec_group)?;
-                PKey::from_ec_key(ec_key)?
-            }
-            _ => return Err(RvError::ErrPkiKeyTypeInvalid),
-        };
+        let priv_key = generate_private_key(&self.key_type, self.key_bits)?;
 
         let cert = self.to_x509(ca_cert, ca_key, &priv_key)?;
-        let serial_number = cert.serial_number().to_bn()?;
-        let serial_number_hex = serial_number.to_hex_str()?;
-        let serial_number_hex = serial_number_hex
-            .chars()
-            .collect::<Vec<char>>()
-            .chunks(2)
-            .map(|chunk| chunk.iter().collect::<String>())
-            .collect::<Vec<String>>()
-            .join(":");
+        let serial_number = format_serial_number(&cert)?;
 
         let mut cert_bundle = CertBundle {
             certificate: cert,
             ca_chain: Vec::new(),
             private_key: priv_key.clone(),
             private_key_type: self.key_type.clone(),
-            serial_number: serial_number_hex.to_lowercase(),
+            serial_number,
         };
 
         if let Some(ca) = ca_cert {
@@ -21,6 +21,7 @@ pub mod key;
 pub mod kv_builder;
 pub mod locks;
 pub mod ocsp;
+pub mod password_hash;
 pub mod policy;
 pub mod salt;
 pub mod seal;
@@ -0,0 +1,183 @@
+//! Password hashing for the credential backends that store user secrets (userpass passwords,
+//! approle secret ids).
+//!
+//! The KDF is chosen per mount through `MountConfig::password_hash` and applies to secrets written
+//! from then on. Hashes are self-describing strings: argon2id hashes use the PHC string format
+//! (`$argon2id$v=19$m=...,t=...,p=...$<salt>$<hash>`) and bcrypt hashes their usual `$2b$<cost>$`
+//! form, so `verify_password` picks the algorithm and parameters from the stored hash and keeps
+//! working after a mount switches to another algorithm.
+
+use argon2::{
+    Algorithm, Argon2, Params, PasswordHash, PasswordHasher as _, PasswordVerifier as _, Version,
+    password_hash::{self, SaltString},
+};
+use rand::{Rng, rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::RvError, logical::Request, router::Router};
+
+// The `bcrypt` crate does not export its cost bounds.
+const BCRYPT_MIN_COST: u32 = 4;
+const BCRYPT_MAX_COST: u32 = 31;
+
+/// A KDF turning secrets into self-describing hashes.
+pub trait PasswordHasher: Send + Sync {
+    /// Hash `secret` with a fresh random salt.
+    fn hash(&self, secret: &str) -> Result<String, RvError>;
+
+    /// Check `secret` against a hash produced by this algorithm. The digests are compared in
+    /// constant time.
+    fn verify(&self, secret: &str, hash: &str) -> Result<bool, RvError>;
+}
+
+/// KDF selection of a mount, e.g. `{"algorithm": "argon2id", "memory_kib": 65536}`. Omitted
+/// parameters take the algorithm's defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "lowercase")]
+pub enum PasswordHashConfig {
+    Bcrypt {
+        #[serde(default = "default_bcrypt_cost")]
+        cost: u32,
+    },
+    Argon2id {
+        #[serde(default = "default_argon2_memory_kib")]
+        memory_kib: u32,
+        #[serde(default = "default_argon2_iterations")]
+        iterations: u32,
+        #[serde(default = "default_argon2_parallelism")]
+        parallelism: u32,
+    },
+}
+
+fn default_bcrypt_cost() -> u32 {
+    bcrypt::DEFAULT_COST
+}
+
+fn default_argon2_memory_kib() -> u32 {
+    Params::DEFAULT_M_COST
+}
+
+fn default_argon2_iterations() -> u32 {
+    Params::DEFAULT_T_COST
+}
+
+fn default_argon2_parallelism() -> u32 {
+    Params::DEFAULT_P_COST
+}
+
+impl Default for PasswordHashConfig {
+    fn default() -> Self {
+        PasswordHashConfig::Bcrypt {
+            cost: default_bcrypt_cost(),
+        }
+    }
+}
+
+impl PasswordHashConfig {
+    /// Build the hasher, rejecting parameters the algorithm does not accept.
+    pub fn hasher(&self) -> Result<Box<dyn PasswordHasher>, RvError> {
+        match *self {
+            PasswordHashConfig::Bcrypt { cost } => {
+                if !(BCRYPT_MIN_COST..=BCRYPT_MAX_COST).contains(&cost) {
+                    return Err(RvError::ErrPasswordHash(format!(
+                        "bcrypt cost must be between {BCRYPT_MIN_COST} and {BCRYPT_MAX_COST}"
+                    )));
+                }
+                Ok(Box::new(BcryptHasher { cost }))
+            }
+            PasswordHashConfig::Argon2id {
+                memory_kib,
+                iterations,
+                parallelism,
+            } => Ok(Box::new(Argon2idHasher::new(
+                memory_kib,
+                iterations,
+                parallelism,
+            )?)),
+        }
+    }
+}
+
+pub struct BcryptHasher {
+    cost: u32,
+}
+
+impl PasswordHasher for BcryptHasher {
+    fn hash(&self, secret: &str) -> Result<String, RvError> {
+        Ok(bcrypt::hash(secret, self.cost)?)
+    }
+
+    fn verify(&self, secret: &str, hash: &str) -> Result<bool, RvError> {
+        // bcrypt::verify compares the digests in constant time.
+        Ok(bcrypt::verify(secret, hash)?)
+    }
+}
+
+pub struct Argon2idHasher {
+    params: Params,
+}
+
+impl Argon2idHasher {
+    pub fn new(memory_kib: u32, iterations: u32, parallelism: u32) -> Result<Self, RvError> {
+        let params = Params::new(memory_kib, iterations, parallelism, None)
+            .map_err(|err| RvError::ErrPasswordHash(err.to_string()))?;
+        Ok(Self { params })
+    }
+}
+
+impl PasswordHasher for Argon2idHasher {
+    fn hash(&self, secret: &str) -> Result<String, RvError> {
+        let mut salt = [0u8; 16];
+        rng().fill(&mut salt);
+        let salt = SaltString::encode_b64(&salt)
+            .map_err(|err| RvError::ErrPasswordHash(err.to_string()))?;
+
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, self.params.clone())
+            .hash_password(secret.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|err| RvError::ErrPasswordHash(err.to_string()))
+    }
+
+    fn verify(&self, secret: &str, hash: &str) -> Result<bool, RvError> {
+        let hash =
+            PasswordHash::new(hash).map_err(|err| RvError::ErrPasswordHash(err.to_string()))?;
+        // The parameters are taken from the hash; the output is compared in constant time.
+        match Argon2::default().verify_password(secret.as_bytes(), &hash) {
+            Ok(()) => Ok(true),
+            Err(password_hash::Error::Password) => Ok(false),
+            Err(err) => Err(RvError::ErrPasswordHash(err.to_string())),
+        }
+    }
+}
+
+/// Check `secret` against a stored hash of any supported algorithm.
+pub fn verify_password(secret: &str, hash: &str) -> Result<bool, RvError> {
+    if hash.starts_with("$argon2id$") {
+        Argon2idHasher::new(
+            default_argon2_memory_kib(),
+            default_argon2_iterations(),
+            default_argon2_parallelism(),
+        )?
+        .verify(secret, hash)
+    } else if hash.starts_with("$2") {
+        BcryptHasher {
+            cost: default_bcrypt_cost(),
+        }
+        .verify(secret, hash)
+    } else {
+        Err(RvError::ErrPasswordHash(
+            "unknown password hash format".to_string(),
+        ))
+    }
+}
+
+/// The hasher configured on the mount serving `req`.
+pub fn mount_password_hasher(
+    router: &Router,
+    req: &Request,
+) -> Result<Box<dyn PasswordHasher>, RvError> {
+    match router.matching_mount_entry(&req.mount_point)? {
+        Some(entry) => entry.read()?.config.password_hash.hasher(),
+        None => PasswordHashConfig::default().hasher(),
+    }
+}
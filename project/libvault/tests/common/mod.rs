@@ -0,0 +1,51 @@
+//! Helpers shared by the integration tests.
+
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use libvault::{
+    RustyVault,
+    core::SealConfig,
+    storage::{Backend, physical::file::FileBackend},
+};
+use serde_json::{Map, Value};
+use tempfile::TempDir;
+
+/// An initialized and unsealed vault over a file backend in a temporary directory, with the
+/// root token cached.
+pub struct TestVault {
+    pub vault: RustyVault,
+    pub unseal_keys: Vec<Vec<u8>>,
+    pub root_token: String,
+    _dir: TempDir,
+}
+
+pub async fn new_unsealed_vault(shares: u8, threshold: u8) -> TestVault {
+    let dir = tempfile::tempdir().unwrap();
+    let backend: Arc<dyn Backend> = Arc::new(FileBackend::with_folder(dir.path()).unwrap());
+    let vault = RustyVault::new(backend, None).unwrap();
+
+    let seal_config = SealConfig {
+        secret_shares: shares,
+        secret_threshold: threshold,
+        ..Default::default()
+    };
+    let init = vault.init(&seal_config).await.unwrap();
+    let unseal_keys = init.secret_shares.to_vec();
+    let keys: Vec<&[u8]> = unseal_keys.iter().map(Vec::as_slice).collect();
+    assert!(vault.unseal(&keys[..threshold as usize]).await.unwrap());
+    vault.set_token(init.root_token.clone());
+
+    TestVault {
+        vault,
+        unseal_keys,
+        root_token: init.root_token.clone(),
+        _dir: dir,
+    }
+}
+
+/// Turn a `json!` object into request data.
+pub fn data(value: Value) -> Option<Map<String, Value>> {
+    value.as_object().cloned()
+}
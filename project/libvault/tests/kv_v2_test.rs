@@ -0,0 +1,118 @@
+mod common;
+
+use common::{data, new_unsealed_vault};
+use libvault::{RustyVault, logical::Request};
+use serde_json::{Map, Value, json};
+
+async fn read(vault: &RustyVault, path: &str) -> Map<String, Value> {
+    vault
+        .read::<String>(None, path)
+        .await
+        .unwrap()
+        .and_then(|resp| resp.data)
+        .unwrap()
+}
+
+/// Read `path` of a kv-v2 mount at the given version, 0 being the latest.
+async fn read_version(vault: &RustyVault, path: &str, version: u64) -> Map<String, Value> {
+    let mut req = Request::new_read_request(path);
+    req.body = data(json!({ "version": version }));
+    req.client_token = vault.token.load().as_ref().clone();
+    vault
+        .request(&mut req)
+        .await
+        .unwrap()
+        .and_then(|resp| resp.data)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_kv_v2_deleted_and_destroyed_versions_hide_data() {
+    let test = new_unsealed_vault(1, 1).await;
+    let vault = &test.vault;
+    vault.mount(None, "kv", "kv-v2").await.unwrap();
+
+    for value in ["one", "two", "three"] {
+        vault
+            .write(
+                None,
+                "kv/data/app",
+                data(json!({"data": {"value": value}})),
+            )
+            .await
+            .unwrap();
+    }
+
+    // Soft-delete the latest version.
+    vault
+        .delete::<String>(None, "kv/data/app".into(), None)
+        .await
+        .unwrap();
+    let latest = read_version(vault, "kv/data/app", 0).await;
+    assert_eq!(latest["data"], Value::Null);
+    assert_eq!(latest["metadata"]["version"], json!(3));
+    assert_ne!(latest["metadata"]["deletion_time"], json!(""));
+    assert_eq!(vault.kv_get::<Value>("kv", "app").await.unwrap(), None);
+
+    // Destroy an older version.
+    vault
+        .write(None, "kv/destroy/app", data(json!({"versions": "1"})))
+        .await
+        .unwrap();
+    let destroyed = read_version(vault, "kv/data/app", 1).await;
+    assert_eq!(destroyed["data"], Value::Null);
+    assert_eq!(destroyed["metadata"]["destroyed"], json!(true));
+
+    // Untouched versions stay readable, and undeleting brings the data back.
+    let kept = read_version(vault, "kv/data/app", 2).await;
+    assert_eq!(kept["data"], json!({"value": "two"}));
+    vault
+        .write(None, "kv/undelete/app", data(json!({"versions": "3"})))
+        .await
+        .unwrap();
+    let latest = read_version(vault, "kv/data/app", 0).await;
+    assert_eq!(latest["data"], json!({"value": "three"}));
+}
+
+#[tokio::test]
+async fn test_kv_v2_mount_max_versions_trims_versions() {
+    let test = new_unsealed_vault(1, 1).await;
+    let vault = &test.vault;
+    vault.mount(None, "kv", "kv-v2").await.unwrap();
+    vault
+        .write(None, "kv/config", data(json!({"max_versions": 2})))
+        .await
+        .unwrap();
+    vault
+        .write(
+            None,
+            "kv/metadata/own",
+            data(json!({"max_versions": 3})),
+        )
+        .await
+        .unwrap();
+
+    for value in 1..=4 {
+        for path in ["kv/data/app", "kv/data/own"] {
+            vault
+                .write(None, path, data(json!({"data": {"value": value}})))
+                .await
+                .unwrap();
+        }
+    }
+
+    // The mount limit applies to secrets without their own.
+    let metadata = read(vault, "kv/metadata/app").await;
+    assert_eq!(metadata["current_version"], json!(4));
+    assert_eq!(metadata["oldest_version"], json!(3));
+    let versions = metadata["versions"].as_object().unwrap();
+    assert_eq!(versions.keys().collect::<Vec<_>>(), ["3", "4"]);
+    let mut req = Request::new_read_request("kv/data/app");
+    req.body = data(json!({ "version": 1 }));
+    req.client_token = test.root_token.clone();
+    assert!(vault.request(&mut req).await.unwrap().is_none());
+
+    // A secret's own limit takes precedence over the mount's.
+    let metadata = read(vault, "kv/metadata/own").await;
+    assert_eq!(metadata["oldest_version"], json!(2));
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::{data, new_unsealed_vault};
+use libvault::errors::RvError;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_userpass_lockout_counts_concurrent_failures() {
+    let test = new_unsealed_vault(1, 1).await;
+    let vault = &test.vault;
+    vault
+        .enable_auth(None, "userpass", "userpass")
+        .await
+        .unwrap();
+    vault
+        .write(
+            None,
+            "sys/auth/userpass/tune",
+            data(json!({"lockout_threshold": 3, "lockout_duration": "1h"})),
+        )
+        .await
+        .unwrap();
+    vault
+        .write(
+            None,
+            "auth/userpass/users/alice",
+            data(json!({"password": "correct horse"})),
+        )
+        .await
+        .unwrap();
+
+    let login = |password: &str| {
+        vault.login(
+            "auth/userpass/login/alice",
+            data(json!({"password": password})),
+        )
+    };
+    let (a, b, c) = tokio::join!(login("wrong"), login("wrong"), login("wrong"));
+    assert!(a.is_err() && b.is_err() && c.is_err());
+
+    let locked = login("correct horse").await.unwrap_err();
+    assert!(matches!(locked, RvError::ErrAuthLocked));
+}
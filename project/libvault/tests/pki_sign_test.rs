@@ -0,0 +1,136 @@
+mod common;
+
+use common::{data, new_unsealed_vault};
+use libvault::RustyVault;
+use openssl::{
+    asn1::Asn1Time,
+    ec::{EcGroup, EcKey},
+    hash::MessageDigest,
+    nid::Nid,
+    pkey::PKey,
+    x509::{X509, X509NameBuilder, X509Req},
+};
+use serde_json::json;
+
+async fn setup_pki(vault: &RustyVault) {
+    vault.mount(None, "pki", "pki").await.unwrap();
+    vault
+        .write(
+            None,
+            "pki/root/tls/generate/internal",
+            data(json!({"common_name": "Test Root CA", "ttl": "87600h"})),
+        )
+        .await
+        .unwrap();
+}
+
+fn ec_csr(common_name: &str) -> (PKey<openssl::pkey::Private>, String) {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_text("CN", common_name).unwrap();
+    let mut builder = X509Req::builder().unwrap();
+    builder.set_subject_name(&name.build()).unwrap();
+    builder.set_pubkey(&key).unwrap();
+    builder.sign(&key, MessageDigest::sha256()).unwrap();
+    let csr = String::from_utf8(builder.build().to_pem().unwrap()).unwrap();
+    (key, csr)
+}
+
+#[tokio::test]
+async fn test_pki_any_role_signs_csr_of_its_own_key_type() {
+    let test = new_unsealed_vault(1, 1).await;
+    let vault = &test.vault;
+    setup_pki(vault).await;
+    for (role, key_type) in [("any-role", "any"), ("rsa-role", "rsa")] {
+        vault
+            .write(
+                None,
+                format!("pki/roles/tls/{role}"),
+                data(json!({
+                    "allowed_domains": "example.com",
+                    "allow_subdomains": true,
+                    "key_type": key_type,
+                })),
+            )
+            .await
+            .unwrap();
+    }
+
+    let (key, csr) = ec_csr("www.example.com");
+    let signed = vault
+        .write(
+            None,
+            "pki/sign/tls/any-role".to_string(),
+            data(json!({"csr": csr, "common_name": "www.example.com"})),
+        )
+        .await
+        .unwrap()
+        .and_then(|resp| resp.data)
+        .unwrap();
+    let certificate = X509::from_pem(signed["certificate"].as_str().unwrap().as_bytes()).unwrap();
+    assert!(certificate.public_key().unwrap().public_eq(&key));
+
+    // A role pinned to another key type still refuses the CSR.
+    let result = vault
+        .write(
+            None,
+            "pki/sign/tls/rsa-role".to_string(),
+            data(json!({"csr": csr, "common_name": "www.example.com"})),
+        )
+        .await;
+    assert!(result.is_err());
+
+    // With no key type to generate, an `any` role cannot issue.
+    let result = vault
+        .write(
+            None,
+            "pki/issue/tls/any-role".to_string(),
+            data(json!({"common_name": "www.example.com"})),
+        )
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_pki_ttl_is_clamped_to_mount_max_lease_ttl() {
+    let test = new_unsealed_vault(1, 1).await;
+    let vault = &test.vault;
+    setup_pki(vault).await;
+    vault
+        .write(
+            None,
+            "pki/roles/tls/example",
+            data(json!({"allowed_domains": "example.com", "allow_subdomains": true})),
+        )
+        .await
+        .unwrap();
+    vault
+        .write(
+            None,
+            "sys/mounts/pki/tune",
+            data(json!({"max_lease_ttl": 3600})),
+        )
+        .await
+        .unwrap();
+
+    let issued = vault
+        .write(
+            None,
+            "pki/issue/tls/example",
+            data(json!({"common_name": "www.example.com", "ttl": "72h"})),
+        )
+        .await
+        .unwrap()
+        .and_then(|resp| resp.data)
+        .unwrap();
+    let certificate = X509::from_pem(issued["certificate"].as_str().unwrap().as_bytes()).unwrap();
+    let limit = Asn1Time::days_from_now(0).unwrap();
+    let diff = limit.diff(certificate.not_after()).unwrap();
+    assert_eq!(diff.days, 0);
+    assert!(
+        diff.secs <= 3600,
+        "certificate outlives the mount's max_lease_ttl"
+    );
+}
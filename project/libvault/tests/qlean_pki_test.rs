@@ -54,6 +54,7 @@ async fn setup_core() -> Result<(Arc<Core>, String)> {
     let seal_config = libvault::core::SealConfig {
         secret_shares: 1,
         secret_threshold: 1,
+        ..Default::default()
     };
     let init_result = core
         .init(&seal_config)
@@ -179,7 +180,7 @@ async fn test_tls_ssh_pgp_generation_and_validation() -> Result<()> {
     println!("[OK] TLS cert issued (serial: {})", tls_serial);
 
     // 1-4. Storage consistency: fetch cert by serial
-    let mut req = Request::new(&format!("pki/cert/tls/{}", tls_serial));
+    let mut req = Request::new(format!("pki/cert/tls/{}", tls_serial));
     req.operation = Operation::Read;
     req.client_token = root_token.clone();
     let resp = core
@@ -322,7 +323,7 @@ async fn test_tls_ssh_pgp_generation_and_validation() -> Result<()> {
     println!("[OK] SSH cert signed (serial: {})", ssh_serial);
 
     // 2-5. Storage consistency: fetch SSH cert by serial
-    let mut req = Request::new(&format!("pki/cert/ssh/{}", ssh_serial));
+    let mut req = Request::new(format!("pki/cert/ssh/{}", ssh_serial));
     req.operation = Operation::Read;
     req.client_token = root_token.clone();
     let resp = core
@@ -0,0 +1,30 @@
+mod common;
+
+use common::new_unsealed_vault;
+use libvault::errors::RvError;
+
+#[tokio::test]
+async fn test_rekey_retires_old_shares() {
+    let test = new_unsealed_vault(5, 2).await;
+    let vault = &test.vault;
+    let old_keys: Vec<&[u8]> = test.unseal_keys.iter().map(Vec::as_slice).collect();
+
+    vault.rekey_init(3, 2).await.unwrap();
+    assert!(vault.rekey_update(&old_keys[..1]).await.unwrap().is_none());
+    let new_shares = vault.rekey_update(&old_keys[1..2]).await.unwrap().unwrap();
+    assert_eq!(new_shares.len(), 3);
+    vault.seal().await.unwrap();
+
+    // The shares presented to the rekey are retired outright.
+    let presented = vault.unseal(&old_keys[..1]).await.unwrap_err();
+    assert!(matches!(presented, RvError::ErrBarrierKeyDeprecated));
+
+    // The others still combine into the old KEK, which no longer opens the barrier.
+    let unpresented = vault.unseal(&old_keys[2..4]).await;
+    assert!(!matches!(unpresented, Ok(true)));
+    assert!(vault.core.load().sealed());
+    vault.unseal_reset().unwrap();
+
+    let new_keys: Vec<&[u8]> = new_shares.iter().map(Vec::as_slice).collect();
+    assert!(vault.unseal(&new_keys[..2]).await.unwrap());
+}
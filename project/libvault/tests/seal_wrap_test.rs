@@ -0,0 +1,79 @@
+mod common;
+
+use common::{data, new_unsealed_vault};
+use libvault::{
+    RustyVault, mount::LOGICAL_BARRIER_PREFIX, storage::barrier_view::SEAL_WRAP_HEADER,
+};
+use serde_json::{Value, json};
+
+async fn read_value(vault: &RustyVault, path: &str) -> Value {
+    vault
+        .read::<String>(None, path)
+        .await
+        .unwrap()
+        .and_then(|resp| resp.data)
+        .unwrap()["value"]
+        .clone()
+}
+
+/// The value of `key` under the mount at `path`, as the barrier stores it.
+async fn raw_value(vault: &RustyVault, path: &str, key: &str) -> Vec<u8> {
+    let core = vault.core.load();
+    let mount_entry = core.mounts_router.get(path).unwrap().unwrap();
+    let prefix = mount_entry
+        .read()
+        .unwrap()
+        .barrier_path(LOGICAL_BARRIER_PREFIX);
+    core.barrier
+        .as_storage()
+        .get(&format!("{prefix}{key}"))
+        .await
+        .unwrap()
+        .unwrap()
+        .value
+}
+
+#[tokio::test]
+async fn test_enable_seal_wrap_wraps_entries_as_they_are_rewritten() {
+    let test = new_unsealed_vault(1, 1).await;
+    let vault = &test.vault;
+    vault.mount(None, "wrapped", "kv").await.unwrap();
+    vault.mount(None, "plain", "kv").await.unwrap();
+    for path in ["wrapped/app", "plain/app"] {
+        vault
+            .write(None, path, data(json!({"value": "one"})))
+            .await
+            .unwrap();
+    }
+
+    vault.enable_seal_wrap("wrapped").await.unwrap();
+
+    // Entries written before seal wrapping stay readable as they are.
+    assert!(
+        !raw_value(vault, "wrapped/", "app")
+            .await
+            .starts_with(SEAL_WRAP_HEADER)
+    );
+    assert_eq!(read_value(vault, "wrapped/app").await, json!("one"));
+
+    // They are wrapped the next time they are written.
+    vault
+        .write(None, "wrapped/app", data(json!({"value": "two"})))
+        .await
+        .unwrap();
+    assert!(
+        raw_value(vault, "wrapped/", "app")
+            .await
+            .starts_with(SEAL_WRAP_HEADER)
+    );
+    assert_eq!(read_value(vault, "wrapped/app").await, json!("two"));
+
+    // Other mounts are left alone.
+    vault
+        .write(None, "plain/app", data(json!({"value": "two"})))
+        .await
+        .unwrap();
+    let raw = raw_value(vault, "plain/", "app").await;
+    assert!(!raw.starts_with(SEAL_WRAP_HEADER));
+    assert_eq!(read_value(vault, "plain/app").await, json!("two"));
+}
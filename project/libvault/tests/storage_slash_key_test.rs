@@ -0,0 +1,45 @@
+//! Backends store keys with a leading `/` verbatim and list them under the `/` prefix.
+#![cfg(any(feature = "storage_memory", feature = "storage_sqlite"))]
+
+use libvault::storage::{Backend, BackendEntry};
+
+async fn assert_slash_key_round_trip(backend: &dyn Backend) {
+    let entry = BackendEntry {
+        key: "/foo".to_string(),
+        value: b"bar".to_vec(),
+    };
+    backend.put(&entry).await.unwrap();
+
+    assert_eq!(backend.get("/foo").await.unwrap(), Some(entry));
+    assert_eq!(backend.get("foo").await.unwrap(), None);
+    assert_eq!(backend.list("/").await.unwrap(), vec!["foo".to_string()]);
+    assert!(backend.list("").await.unwrap().contains(&"/".to_string()));
+
+    backend.delete("/foo").await.unwrap();
+    assert_eq!(backend.get("/foo").await.unwrap(), None);
+}
+
+#[cfg(feature = "storage_memory")]
+#[tokio::test]
+async fn test_memory_slash_key_round_trip() {
+    let backend = libvault::storage::memory::MemoryBackend::new();
+    assert_slash_key_round_trip(backend.as_ref()).await;
+}
+
+#[cfg(feature = "storage_sqlite")]
+#[tokio::test]
+async fn test_sqlite_slash_key_round_trip() {
+    use std::collections::HashMap;
+
+    use libvault::storage::sql::sqlite::SqliteBackend;
+    use serde_json::Value;
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut conf = HashMap::new();
+    conf.insert(
+        "filename".to_string(),
+        Value::String(dir.path().join("vault.db").to_string_lossy().into_owned()),
+    );
+    let backend = SqliteBackend::new(&conf).await.unwrap();
+    assert_slash_key_round_trip(&backend).await;
+}
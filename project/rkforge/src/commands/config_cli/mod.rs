@@ -1,10 +1,124 @@
-use crate::config::auth::RkforgeConfig;
-use crate::config::image::resolve_storage_root_for_current_user;
-use anyhow::{Result, bail};
+use crate::config::auth::{AuthConfig, RegistrySettings, RkforgeConfig};
+use crate::config::image::{
+    resolve_build_dir, resolve_default_registry, resolve_max_concurrency, resolve_metadata_dir,
+    resolve_storage_root_for_current_user,
+};
+use crate::registry::{ProxySettings, parse_registry_host, redact_proxy};
+use anyhow::{Context, Result, bail};
 use clap::{Parser, Subcommand};
 use std::path::Path;
 
 const IMAGE_STORAGE_KEY: &str = "image.storage";
+const IMAGE_MAX_CONCURRENCY_KEY: &str = "image.max_concurrency";
+const BUILD_DIR_KEY: &str = "build.dir";
+const METADATA_DIR_KEY: &str = "metadata.dir";
+const REGISTRY_DEFAULT_KEY: &str = "registry.default";
+const REGISTRY_PROXY_KEY: &str = "registry.proxy";
+const REGISTRY_MIRRORS_KEY: &str = "registry.mirrors";
+const REGISTRY_KEY_PREFIX: &str = "registry.";
+const REGISTRY_FIELDS: [&str; 3] = ["insecure", "mirror", "namespace"];
+
+/// A fixed config key with its validation and effective-value lookup.
+struct ConfigKey {
+    name: &'static str,
+    set: fn(&mut RkforgeConfig, &str) -> Result<()>,
+    get: fn(&RkforgeConfig) -> Result<String>,
+}
+
+const CONFIG_KEYS: &[ConfigKey] = &[
+    ConfigKey {
+        name: IMAGE_STORAGE_KEY,
+        set: |cfg, value| {
+            cfg.image.storage = Some(validate_dir_value(IMAGE_STORAGE_KEY, value)?);
+            Ok(())
+        },
+        get: |cfg| Ok(path_string(&resolve_storage_root_for_current_user(cfg)?)),
+    },
+    ConfigKey {
+        name: IMAGE_MAX_CONCURRENCY_KEY,
+        set: |cfg, value| {
+            let value = value.trim();
+            let limit = value
+                .parse::<usize>()
+                .ok()
+                .filter(|limit| *limit > 0)
+                .with_context(|| {
+                    format!(
+                        "config value for `{IMAGE_MAX_CONCURRENCY_KEY}` must be a positive \
+                         integer, got `{value}`"
+                    )
+                })?;
+            cfg.image.max_concurrency = Some(limit);
+            Ok(())
+        },
+        get: |cfg| Ok(resolve_max_concurrency(cfg).to_string()),
+    },
+    ConfigKey {
+        name: BUILD_DIR_KEY,
+        set: |cfg, value| {
+            cfg.build.dir = Some(validate_dir_value(BUILD_DIR_KEY, value)?);
+            Ok(())
+        },
+        get: |cfg| {
+            let root = resolve_storage_root_for_current_user(cfg)?;
+            Ok(path_string(&resolve_build_dir(cfg, &root)?))
+        },
+    },
+    ConfigKey {
+        name: METADATA_DIR_KEY,
+        set: |cfg, value| {
+            cfg.metadata.dir = Some(validate_dir_value(METADATA_DIR_KEY, value)?);
+            Ok(())
+        },
+        get: |cfg| {
+            let root = resolve_storage_root_for_current_user(cfg)?;
+            Ok(path_string(&resolve_metadata_dir(cfg, &root)?))
+        },
+    },
+    ConfigKey {
+        name: REGISTRY_DEFAULT_KEY,
+        set: |cfg, value| {
+            cfg.registry.default = Some(
+                parse_registry_host(value)
+                    .with_context(|| format!("invalid registry for `{REGISTRY_DEFAULT_KEY}`"))?,
+            );
+            Ok(())
+        },
+        get: |cfg| Ok(resolve_default_registry(cfg)),
+    },
+    ConfigKey {
+        name: REGISTRY_PROXY_KEY,
+        set: |cfg, value| {
+            cfg.registry.proxy = Some(validate_proxy_value(value)?);
+            Ok(())
+        },
+        // Effective proxy for HTTPS registries, including `HTTPS_PROXY`, without credentials.
+        get: |cfg| {
+            Ok(ProxySettings::resolve(cfg.registry_proxy())
+                .https
+                .as_deref()
+                .map(redact_proxy)
+                .unwrap_or_default())
+        },
+    },
+    ConfigKey {
+        name: REGISTRY_MIRRORS_KEY,
+        // Comma-separated, in the order they are tried; an empty value clears the list.
+        set: |cfg, value| {
+            cfg.registry.mirrors = value
+                .split(',')
+                .map(str::trim)
+                .filter(|mirror| !mirror.is_empty())
+                .map(|mirror| {
+                    parse_registry_host(mirror)
+                        .with_context(|| format!("invalid registry in `{REGISTRY_MIRRORS_KEY}`"))
+                })
+                .collect::<Result<_>>()?;
+            Ok(())
+        },
+        get: |cfg| Ok(AuthConfig::from_rkforge_config(cfg)?.mirrors.join(",")),
+    },
+];
 
 #[derive(Parser, Debug)]
 pub struct ConfigArgs {
@@ -16,14 +130,14 @@ pub struct ConfigArgs {
 pub enum ConfigSubCommand {
     /// Set a config key to a value
     Set {
-        /// Config key, currently only supports image.storage
+        /// Config key, e.g. image.storage, build.dir or registry.<host[:port]>.insecure
         key: String,
         /// Config value
         value: String,
     },
     /// Get effective value of a config key
     Get {
-        /// Config key, currently only supports image.storage
+        /// Config key, e.g. image.storage, build.dir or registry.<host[:port]>.insecure
         key: String,
     },
 }
@@ -45,49 +159,158 @@ pub fn config(args: ConfigArgs) -> Result<()> {
 }
 
 fn set_value(cfg: &mut RkforgeConfig, key: &str, value: &str) -> Result<()> {
-    match key {
-        IMAGE_STORAGE_KEY => {
-            let value = value.trim();
-            if value.is_empty() {
-                bail!("config value for `{IMAGE_STORAGE_KEY}` must not be empty");
+    if let Some(entry) = find_key(key) {
+        return (entry.set)(cfg, value);
+    }
+    if let Some((url, field)) = parse_registry_key(key)? {
+        return set_registry_value(cfg, &url, field, value);
+    }
+    bail!(
+        "unsupported config key `{key}`. supported keys: {}",
+        supported_keys()
+    )
+}
+
+fn find_key(key: &str) -> Option<&'static ConfigKey> {
+    CONFIG_KEYS.iter().find(|entry| entry.name == key)
+}
+
+fn supported_keys() -> String {
+    CONFIG_KEYS
+        .iter()
+        .map(|entry| entry.name.to_string())
+        .chain(
+            REGISTRY_FIELDS
+                .iter()
+                .map(|field| format!("{REGISTRY_KEY_PREFIX}<host[:port]>.{field}")),
+        )
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+/// Splits `registry.<host[:port]>.<field>` into the normalized host and field.
+/// Returns `None` for keys outside the registry namespace.
+fn parse_registry_key(key: &str) -> Result<Option<(String, &str)>> {
+    let Some(rest) = key.strip_prefix(REGISTRY_KEY_PREFIX) else {
+        return Ok(None);
+    };
+    let Some((url, field)) = rest.rsplit_once('.') else {
+        bail!("invalid registry key `{key}`: expected registry.<host[:port]>.<field>");
+    };
+    if !REGISTRY_FIELDS.contains(&field) {
+        bail!(
+            "unsupported registry setting `{field}` in `{key}`. supported settings: {}",
+            REGISTRY_FIELDS.join(", ")
+        );
+    }
+    let url = parse_registry_host(url).with_context(|| format!("invalid registry in `{key}`"))?;
+    Ok(Some((url, field)))
+}
+
+fn set_registry_value(cfg: &mut RkforgeConfig, url: &str, field: &str, value: &str) -> Result<()> {
+    let value = value.trim();
+    if value.is_empty() {
+        bail!("config value for `{REGISTRY_KEY_PREFIX}{url}.{field}` must not be empty");
+    }
+    let settings = registry_settings_mut(cfg, url);
+    match field {
+        "insecure" => {
+            let insecure = value
+                .parse::<bool>()
+                .with_context(|| format!("expected `true` or `false`, got `{value}`"))?;
+            settings.insecure = Some(insecure);
+        }
+        "mirror" => settings.mirror = Some(parse_registry_host(value)?),
+        _ => {
+            let namespace = value.trim_matches('/');
+            if namespace.is_empty() {
+                bail!("registry namespace must not be empty");
             }
-            validate_storage_value(value)?;
-            cfg.image.storage = Some(value.to_string());
-            Ok(())
+            settings.namespace = Some(namespace.to_string());
         }
-        _ => bail!("unsupported config key `{key}`. supported keys: {IMAGE_STORAGE_KEY}"),
     }
+    Ok(())
+}
+
+/// Returns the settings table for `url`, re-keying a table written with a
+/// non-normalized host (e.g. `Example.com`) so it is not duplicated.
+fn registry_settings_mut<'a>(cfg: &'a mut RkforgeConfig, url: &str) -> &'a mut RegistrySettings {
+    let existing = cfg
+        .registries
+        .keys()
+        .find(|key| parse_registry_host(key).is_ok_and(|host| host == url))
+        .cloned();
+    let settings = existing
+        .and_then(|key| cfg.registries.remove(&key))
+        .unwrap_or_default();
+    cfg.registries.entry(url.to_string()).or_insert(settings)
+}
+
+fn validate_proxy_value(value: &str) -> Result<String> {
+    let value = value.trim();
+    let url = reqwest::Url::parse(value)
+        .with_context(|| format!("config value for `{REGISTRY_PROXY_KEY}` must be a URL"))?;
+    if !matches!(url.scheme(), "http" | "https" | "socks5" | "socks5h") {
+        bail!(
+            "unsupported proxy scheme `{}` for `{REGISTRY_PROXY_KEY}`: expected http, https, \
+             socks5 or socks5h",
+            url.scheme()
+        );
+    }
+    if url.host_str().is_none() {
+        bail!("config value for `{REGISTRY_PROXY_KEY}` must include a proxy host");
+    }
+    Ok(value.to_string())
 }
 
-fn validate_storage_value(value: &str) -> Result<()> {
+/// Checks a directory value: absolute, `~` or `~/...`. Returns it trimmed.
+fn validate_dir_value(key: &str, value: &str) -> Result<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        bail!("config value for `{key}` must not be empty");
+    }
     if value == "~" || value.starts_with("~/") {
-        return Ok(());
+        return Ok(value.to_string());
     }
 
     if value.starts_with('~') {
-        bail!(
-            "unsupported home path `{value}`: only `~` and `~/...` are supported for {IMAGE_STORAGE_KEY}"
-        );
+        bail!("unsupported home path `{value}`: only `~` and `~/...` are supported for {key}");
     }
 
     if !Path::new(value).is_absolute() {
-        bail!("config value for `{IMAGE_STORAGE_KEY}` must be an absolute path or start with `~/`");
+        bail!("config value for `{key}` must be an absolute path or start with `~/`");
     }
-    Ok(())
+    Ok(value.to_string())
 }
 
 fn get_value(cfg: &RkforgeConfig, key: &str) -> Result<String> {
-    match key {
-        IMAGE_STORAGE_KEY => Ok(resolve_storage_root_for_current_user(cfg)?
-            .to_string_lossy()
-            .to_string()),
-        _ => bail!("unsupported config key `{key}`. supported keys: {IMAGE_STORAGE_KEY}"),
+    if let Some(entry) = find_key(key) {
+        return (entry.get)(cfg);
+    }
+    if let Some((url, field)) = parse_registry_key(key)? {
+        let resolved = AuthConfig::from_rkforge_config(cfg)?.resolve_registry(&url)?;
+        return Ok(match field {
+            "insecure" => resolved.insecure.to_string(),
+            "mirror" => resolved.mirror.unwrap_or_default(),
+            _ => resolved.namespace.unwrap_or_default(),
+        });
     }
+    bail!(
+        "unsupported config key `{key}`. supported keys: {}",
+        supported_keys()
+    )
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{IMAGE_STORAGE_KEY, get_value, set_value};
+    use super::{
+        BUILD_DIR_KEY, IMAGE_MAX_CONCURRENCY_KEY, IMAGE_STORAGE_KEY, METADATA_DIR_KEY,
+        REGISTRY_DEFAULT_KEY, REGISTRY_MIRRORS_KEY, REGISTRY_PROXY_KEY, get_value, set_value,
+    };
     use crate::config::auth::RkforgeConfig;
 
     #[test]
@@ -141,4 +364,152 @@ mod tests {
         let cfg = RkforgeConfig::default();
         assert!(get_value(&cfg, "unknown.key").is_err());
     }
+
+    #[test]
+    fn test_unknown_key_lists_supported_keys() {
+        let mut cfg = RkforgeConfig::default();
+        let msg = set_value(&mut cfg, "unknown.key", "x")
+            .unwrap_err()
+            .to_string();
+        for key in [
+            IMAGE_STORAGE_KEY,
+            BUILD_DIR_KEY,
+            METADATA_DIR_KEY,
+            REGISTRY_DEFAULT_KEY,
+        ] {
+            assert!(msg.contains(key), "missing {key} in: {msg}");
+        }
+    }
+
+    #[test]
+    fn test_set_and_get_dir_keys() {
+        let mut cfg = RkforgeConfig::default();
+        set_value(&mut cfg, BUILD_DIR_KEY, " /scratch/build ").unwrap();
+        set_value(&mut cfg, METADATA_DIR_KEY, "/srv/meta").unwrap();
+        assert_eq!(cfg.build.dir.as_deref(), Some("/scratch/build"));
+        assert_eq!(get_value(&cfg, BUILD_DIR_KEY).unwrap(), "/scratch/build");
+        assert_eq!(get_value(&cfg, METADATA_DIR_KEY).unwrap(), "/srv/meta");
+
+        assert!(set_value(&mut cfg, BUILD_DIR_KEY, "relative").is_err());
+        assert!(set_value(&mut cfg, METADATA_DIR_KEY, "  ").is_err());
+    }
+
+    #[test]
+    fn test_get_build_dir_defaults_under_storage_root() {
+        let mut cfg = RkforgeConfig::default();
+        cfg.image.storage = Some("/data/rkforge".to_string());
+        assert_eq!(
+            get_value(&cfg, BUILD_DIR_KEY).unwrap(),
+            "/data/rkforge/build"
+        );
+        assert_eq!(
+            get_value(&cfg, METADATA_DIR_KEY).unwrap(),
+            "/data/rkforge/metadata"
+        );
+    }
+
+    #[test]
+    fn test_set_and_get_max_concurrency() {
+        let mut cfg = RkforgeConfig::default();
+        set_value(&mut cfg, IMAGE_MAX_CONCURRENCY_KEY, "8").unwrap();
+        assert_eq!(cfg.image.max_concurrency, Some(8));
+        assert_eq!(get_value(&cfg, IMAGE_MAX_CONCURRENCY_KEY).unwrap(), "8");
+        assert!(set_value(&mut cfg, IMAGE_MAX_CONCURRENCY_KEY, "0").is_err());
+        assert!(set_value(&mut cfg, IMAGE_MAX_CONCURRENCY_KEY, "many").is_err());
+    }
+
+    #[test]
+    fn test_set_and_get_registry_proxy() {
+        let mut cfg = RkforgeConfig::default();
+        set_value(
+            &mut cfg,
+            REGISTRY_PROXY_KEY,
+            "http://user:pw@proxy.corp:3128",
+        )
+        .unwrap();
+        assert_eq!(
+            cfg.registry.proxy.as_deref(),
+            Some("http://user:pw@proxy.corp:3128")
+        );
+        assert_eq!(
+            get_value(&cfg, REGISTRY_PROXY_KEY).unwrap(),
+            "http://proxy.corp:3128/"
+        );
+        assert!(set_value(&mut cfg, REGISTRY_PROXY_KEY, "proxy.corp:3128").is_err());
+        assert!(set_value(&mut cfg, REGISTRY_PROXY_KEY, "ftp://proxy.corp").is_err());
+    }
+
+    #[test]
+    fn test_set_and_get_registry_mirrors() {
+        let mut cfg = RkforgeConfig::default();
+        set_value(
+            &mut cfg,
+            REGISTRY_MIRRORS_KEY,
+            "Mirror-a.local, mirror-b.local:5000",
+        )
+        .unwrap();
+        assert_eq!(
+            cfg.registry.mirrors,
+            vec![
+                "mirror-a.local".to_string(),
+                "mirror-b.local:5000".to_string()
+            ]
+        );
+        assert_eq!(
+            get_value(&cfg, REGISTRY_MIRRORS_KEY).unwrap(),
+            "mirror-a.local,mirror-b.local:5000"
+        );
+        assert!(set_value(&mut cfg, REGISTRY_MIRRORS_KEY, "http://mirror.local").is_err());
+
+        set_value(&mut cfg, REGISTRY_MIRRORS_KEY, "").unwrap();
+        assert!(cfg.registry.mirrors.is_empty());
+    }
+
+    #[test]
+    fn test_set_and_get_default_registry() {
+        let mut cfg = RkforgeConfig::default();
+        assert!(!get_value(&cfg, REGISTRY_DEFAULT_KEY).unwrap().is_empty());
+        set_value(&mut cfg, REGISTRY_DEFAULT_KEY, "Registry.Example.com:5000").unwrap();
+        assert_eq!(
+            get_value(&cfg, REGISTRY_DEFAULT_KEY).unwrap(),
+            "registry.example.com:5000"
+        );
+        assert!(set_value(&mut cfg, REGISTRY_DEFAULT_KEY, "").is_err());
+    }
+
+    #[test]
+    fn test_set_and_get_registry_keys() {
+        let mut cfg = RkforgeConfig::default();
+        set_value(&mut cfg, "registry.Example.com:5000.insecure", "true").unwrap();
+        set_value(&mut cfg, "registry.example.com:5000.mirror", "mirror.local").unwrap();
+        set_value(&mut cfg, "registry.example.com:5000.namespace", "/team/").unwrap();
+
+        assert_eq!(cfg.registries.len(), 1);
+        let settings = &cfg.registries["example.com:5000"];
+        assert_eq!(settings.insecure, Some(true));
+        assert_eq!(settings.mirror.as_deref(), Some("mirror.local"));
+        assert_eq!(settings.namespace.as_deref(), Some("team"));
+
+        assert_eq!(
+            get_value(&cfg, "registry.example.com:5000.insecure").unwrap(),
+            "true"
+        );
+        assert_eq!(
+            get_value(&cfg, "registry.example.com:5000.namespace").unwrap(),
+            "team"
+        );
+        assert_eq!(
+            get_value(&cfg, "registry.other.io.insecure").unwrap(),
+            "false"
+        );
+        assert_eq!(get_value(&cfg, "registry.other.io.mirror").unwrap(), "");
+    }
+
+    #[test]
+    fn test_set_registry_rejects_bad_values() {
+        let mut cfg = RkforgeConfig::default();
+        assert!(set_value(&mut cfg, "registry.example.com.insecure", "yes").is_err());
+        assert!(set_value(&mut cfg, "registry.example.com.unknown", "x").is_err());
+        assert!(set_value(&mut cfg, "registry.example.com.namespace", "/").is_err());
+    }
 }
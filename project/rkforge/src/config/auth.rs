@@ -1,21 +1,80 @@
 use crate::config::image::CONFIG;
-use crate::registry::{RegistryScheme, parse_registry_host, scheme_for_registry};
+use crate::registry::{ProxySettings, RegistryScheme, parse_registry_host, scheme_for_registry};
 use crate::utils::cli::original_user_config_path;
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
 use std::pin::Pin;
 
 #[derive(Serialize, Deserialize, Debug, Default, Ord, PartialOrd, Eq, PartialEq)]
 pub struct ImageConfig {
     pub storage: Option<String>,
+    /// Maximum number of layer blobs transferred at once by pull and push.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Ord, PartialOrd, Eq, PartialEq)]
+pub struct BuildConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Ord, PartialOrd, Eq, PartialEq)]
+pub struct MetadataConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Ord, PartialOrd, Eq, PartialEq)]
 pub struct RegistryConfig {
     #[serde(default, rename = "insecure-registries", alias = "insecure_registries")]
     pub insecure_registries: Vec<String>,
+    /// Registry used when an image reference or command names none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// Proxy URL for registry traffic; overrides `HTTP_PROXY`/`HTTPS_PROXY`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Registries tried in order before the canonical one when pulling.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
+}
+
+/// Per-registry settings from the `[registries."<host[:port]>"]` tables.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, Ord, PartialOrd, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RegistrySettings {
+    /// Talk plain HTTP to this registry. Overrides `registry.insecure-registries`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub insecure: Option<bool>,
+    /// Registry host tried before this one when pulling.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror: Option<String>,
+    /// Namespace prepended to repository names without one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+}
+
+/// Everything known about one registry: credentials plus its settings.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResolvedRegistry {
+    pub url: String,
+    pub auth: Option<AuthEntry>,
+    pub insecure: bool,
+    pub mirror: Option<String>,
+    pub namespace: Option<String>,
+}
+
+impl ResolvedRegistry {
+    pub fn scheme(&self) -> RegistryScheme {
+        if self.insecure {
+            RegistryScheme::Http
+        } else {
+            RegistryScheme::Https
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Ord, PartialOrd, Eq, PartialEq)]
@@ -24,6 +83,8 @@ pub struct RkforgeConfig {
     pub entries: Vec<AuthEntry>,
     #[serde(default)]
     pub registry: RegistryConfig,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub registries: BTreeMap<String, RegistrySettings>,
     #[serde(
         default,
         rename = "insecure-registries",
@@ -33,6 +94,29 @@ pub struct RkforgeConfig {
     legacy_insecure_registries: Vec<String>,
     #[serde(default)]
     pub image: ImageConfig,
+    #[serde(default, skip_serializing_if = "BuildConfig::is_empty")]
+    pub build: BuildConfig,
+    #[serde(default, skip_serializing_if = "MetadataConfig::is_empty")]
+    pub metadata: MetadataConfig,
+}
+
+impl BuildConfig {
+    fn is_empty(&self) -> bool {
+        self.dir.is_none()
+    }
+}
+
+impl MetadataConfig {
+    fn is_empty(&self) -> bool {
+        self.dir.is_none()
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
 }
 
 impl RkforgeConfig {
@@ -58,11 +142,29 @@ impl RkforgeConfig {
     }
 
     pub fn storage_root(&self) -> Option<&str> {
-        self.image
-            .storage
-            .as_deref()
-            .map(str::trim)
-            .filter(|value| !value.is_empty())
+        non_empty(&self.image.storage)
+    }
+
+    /// Build directory override; defaults to `<storage>/build` when unset.
+    pub fn build_dir(&self) -> Option<&str> {
+        non_empty(&self.build.dir)
+    }
+
+    /// Metadata directory override; defaults to `<storage>/metadata` when unset.
+    pub fn metadata_dir(&self) -> Option<&str> {
+        non_empty(&self.metadata.dir)
+    }
+
+    pub fn max_concurrency(&self) -> Option<usize> {
+        self.image.max_concurrency.filter(|value| *value > 0)
+    }
+
+    pub fn default_registry(&self) -> Option<&str> {
+        non_empty(&self.registry.default)
+    }
+
+    pub fn registry_proxy(&self) -> Option<&str> {
+        non_empty(&self.registry.proxy)
     }
 }
 
@@ -72,6 +174,12 @@ pub struct AuthConfig {
     pub entries: Vec<AuthEntry>,
     #[serde(default)]
     pub insecure_registries: Vec<String>,
+    #[serde(default)]
+    pub registries: BTreeMap<String, RegistrySettings>,
+    #[serde(default)]
+    pub mirrors: Vec<String>,
+    #[serde(skip)]
+    pub proxy: ProxySettings,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, Default, Ord, PartialOrd, Eq, PartialEq)]
@@ -148,12 +256,12 @@ impl AuthConfig {
 
     pub fn load() -> anyhow::Result<Self> {
         let config = RkforgeConfig::load()?;
-        Self::from_rkforge_config(config)
+        Self::from_rkforge_config(&config)
     }
 
     pub fn load_from(path: impl AsRef<Path>) -> anyhow::Result<Self> {
         let config = RkforgeConfig::load_from(path)?;
-        Self::from_rkforge_config(config)
+        Self::from_rkforge_config(&config)
     }
 
     pub fn insecure_registries(&self) -> &[String] {
@@ -161,21 +269,75 @@ impl AuthConfig {
     }
 
     pub fn registry_scheme(&self, registry: impl AsRef<str>) -> RegistryScheme {
-        scheme_for_registry(registry, &self.insecure_registries)
+        let registry = registry.as_ref();
+        match parse_registry_host(registry)
+            .ok()
+            .and_then(|host| self.registries.get(&host))
+            .and_then(|settings| settings.insecure)
+        {
+            Some(true) => RegistryScheme::Http,
+            Some(false) => RegistryScheme::Https,
+            None => scheme_for_registry(registry, &self.insecure_registries),
+        }
+    }
+
+    /// Merges the credentials and per-registry settings configured for `url`.
+    ///
+    /// Registries without a `[registries]` table fall back to the global
+    /// `insecure-registries` list, so older configs resolve as before.
+    pub fn resolve_registry(&self, url: impl AsRef<str>) -> anyhow::Result<ResolvedRegistry> {
+        let url = parse_registry_host(url.as_ref())?;
+        let settings = self.registries.get(&url).cloned().unwrap_or_default();
+        Ok(ResolvedRegistry {
+            auth: self.entries.iter().find(|entry| entry.url == url).cloned(),
+            insecure: matches!(self.registry_scheme(&url), RegistryScheme::Http),
+            mirror: settings.mirror,
+            namespace: settings.namespace,
+            url,
+        })
     }
 
-    fn from_rkforge_config(config: RkforgeConfig) -> anyhow::Result<Self> {
-        let mut insecure_registries = config.registry.insecure_registries;
-        insecure_registries.extend(config.legacy_insecure_registries);
+    pub fn from_rkforge_config(config: &RkforgeConfig) -> anyhow::Result<Self> {
+        let mut insecure_registries = config.registry.insecure_registries.clone();
+        insecure_registries.extend(config.legacy_insecure_registries.iter().cloned());
         Ok(Self {
-            entries: normalize_entries(config.entries)?,
+            entries: normalize_entries(config.entries.clone())?,
             insecure_registries: normalize_registry_list(
                 insecure_registries,
                 "insecure_registries",
             )?,
+            registries: normalize_registries(config.registries.clone())?,
+            mirrors: normalize_registry_list(config.registry.mirrors.clone(), "mirrors")?,
+            proxy: ProxySettings::resolve(config.registry_proxy()),
         })
     }
 
+    /// Mirrors to try before pulling from `registry`: its own `mirror` setting
+    /// first, then the global `registry.mirrors` list.
+    pub fn mirrors_for(&self, registry: impl AsRef<str>) -> Vec<String> {
+        let Ok(registry) = parse_registry_host(registry.as_ref()) else {
+            return Vec::new();
+        };
+        let own = self
+            .registries
+            .get(&registry)
+            .and_then(|settings| settings.mirror.clone());
+        let mut mirrors = Vec::new();
+        for mirror in own.into_iter().chain(self.mirrors.iter().cloned()) {
+            if mirror != registry && !mirrors.contains(&mirror) {
+                mirrors.push(mirror);
+            }
+        }
+        mirrors
+    }
+
+    /// The proxy requests to `registry` go through, if any.
+    pub fn proxy_for_registry(&self, registry: impl AsRef<str>) -> Option<&str> {
+        let registry = registry.as_ref();
+        self.proxy
+            .proxy_for(self.registry_scheme(registry), registry)
+    }
+
     pub fn is_anonymous(&self, url: impl AsRef<str>) -> bool {
         let Ok(url) = parse_registry_host(url.as_ref()) else {
             return true;
@@ -228,6 +390,26 @@ fn normalize_entries(entries: Vec<AuthEntry>) -> anyhow::Result<Vec<AuthEntry>>
         .collect()
 }
 
+fn normalize_registries(
+    registries: BTreeMap<String, RegistrySettings>,
+) -> anyhow::Result<BTreeMap<String, RegistrySettings>> {
+    let mut normalized = BTreeMap::new();
+    for (url, mut settings) in registries {
+        let host = parse_registry_host(&url)
+            .with_context(|| format!("invalid registry in registries: {url}"))?;
+        if let Some(mirror) = settings.mirror.take() {
+            settings.mirror = Some(
+                parse_registry_host(&mirror)
+                    .with_context(|| format!("invalid mirror for registry {url}: {mirror}"))?,
+            );
+        }
+        if normalized.insert(host.clone(), settings).is_some() {
+            anyhow::bail!("registry {host} is configured more than once in registries");
+        }
+    }
+    Ok(normalized)
+}
+
 fn normalize_registry_list(values: Vec<String>, field: &str) -> anyhow::Result<Vec<String>> {
     let mut seen = HashSet::new();
     let mut normalized = Vec::new();
@@ -258,6 +440,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::{AuthConfig, ImageConfig, RegistryConfig, RkforgeConfig};
+    use crate::registry::RegistryScheme;
     use std::fs;
     use tempfile::tempdir;
 
@@ -266,6 +449,7 @@ mod tests {
         let config = RkforgeConfig {
             image: ImageConfig {
                 storage: Some("   ".to_string()),
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -277,6 +461,7 @@ mod tests {
         let config = RkforgeConfig {
             image: ImageConfig {
                 storage: Some("  /data/rkforge  ".to_string()),
+                ..Default::default()
             },
             ..Default::default()
         };
@@ -390,7 +575,8 @@ insecure-registries = ["47.79.87.161:8968"]
         assert_eq!(
             config.registry,
             RegistryConfig {
-                insecure_registries: vec!["47.79.87.161:8968".to_string()]
+                insecure_registries: vec!["47.79.87.161:8968".to_string()],
+                ..Default::default()
             }
         );
     }
@@ -412,4 +598,112 @@ insecure-registries = ["47.79.87.161:8968"]
 
         assert!(AuthConfig::load_from(&config_path).is_err());
     }
+
+    #[test]
+    fn test_registries_table_merges_with_auth_entry() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("rkforge.toml");
+        fs::write(
+            &config_path,
+            r#"
+[[entries]]
+pat = "token"
+url = "example.com:5000"
+
+[registry]
+insecure-registries = ["example.com:5000", "legacy.io"]
+
+[registries."Example.com:5000"]
+insecure = false
+mirror = "Mirror.local"
+namespace = "team"
+
+[registries."plain.io"]
+insecure = true
+"#,
+        )
+        .unwrap();
+
+        let auth = AuthConfig::load_from(&config_path).unwrap();
+        let resolved = auth.resolve_registry("example.com:5000").unwrap();
+        assert_eq!(resolved.auth.unwrap().pat, "token");
+        assert!(
+            !resolved.insecure,
+            "per-registry setting overrides the list"
+        );
+        assert_eq!(resolved.mirror.as_deref(), Some("mirror.local"));
+        assert_eq!(resolved.namespace.as_deref(), Some("team"));
+
+        assert_eq!(auth.registry_scheme("plain.io"), RegistryScheme::Http);
+        assert_eq!(auth.registry_scheme("legacy.io"), RegistryScheme::Http);
+        let anonymous = auth.resolve_registry("legacy.io").unwrap();
+        assert!(anonymous.auth.is_none());
+        assert!(anonymous.insecure);
+    }
+
+    #[test]
+    fn test_mirrors_for_orders_registry_mirror_before_global_list() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("rkforge.toml");
+        fs::write(
+            &config_path,
+            r#"
+[registry]
+mirrors = ["Mirror-a.local", "mirror-b.local:5000", "mirror-a.local"]
+
+[registries."docker.io"]
+mirror = "mirror-b.local:5000"
+
+[registries."mirror-a.local"]
+insecure = true
+"#,
+        )
+        .unwrap();
+
+        let auth = AuthConfig::load_from(&config_path).unwrap();
+        assert_eq!(
+            auth.mirrors,
+            vec![
+                "mirror-a.local".to_string(),
+                "mirror-b.local:5000".to_string()
+            ]
+        );
+        assert_eq!(
+            auth.mirrors_for("docker.io"),
+            vec![
+                "mirror-b.local:5000".to_string(),
+                "mirror-a.local".to_string()
+            ]
+        );
+        // A mirror is never its own mirror.
+        assert_eq!(
+            auth.mirrors_for("mirror-a.local"),
+            vec!["mirror-b.local:5000".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_config_without_registries_round_trips_unchanged() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("rkforge.toml");
+        fs::write(
+            &config_path,
+            r#"
+[[entries]]
+pat = "token"
+url = "example.com"
+"#,
+        )
+        .unwrap();
+
+        let config = RkforgeConfig::load_from(&config_path).unwrap();
+        assert!(config.registries.is_empty());
+        let stored_path = dir.path().join("stored.toml");
+        confy::store_path(&stored_path, &config).unwrap();
+        assert!(
+            !fs::read_to_string(&stored_path)
+                .unwrap()
+                .contains("registries")
+        );
+    }
 }
@@ -10,6 +10,7 @@ pub static CONFIG: Lazy<Config> =
 
 static REGISTRY: &str = "47.79.87.161:8968";
 static ROOT_PATH: &str = "/var/lib/rkforge";
+const DEFAULT_MAX_CONCURRENCY: usize = 4;
 
 fn current_user_is_root() -> bool {
     nix::unistd::getuid().is_root()
@@ -37,25 +38,50 @@ fn expand_home(path: &str) -> Result<PathBuf> {
     }
 }
 
+/// Expands a configured directory value, which must be absolute or start with `~/`.
+fn resolve_configured_dir(key: &str, path: &str) -> Result<PathBuf> {
+    let expanded =
+        expand_home(path).with_context(|| format!("Failed to resolve {key} path `{path}`"))?;
+    if !expanded.is_absolute() {
+        bail!("Configured {key} `{path}` must be an absolute path or start with `~/`");
+    }
+    Ok(expanded)
+}
+
 fn resolve_storage_root_with_source(
     config: &RkforgeConfig,
     is_root: bool,
 ) -> Result<(PathBuf, bool)> {
     match config.storage_root() {
-        Some(path) => {
-            let expanded = expand_home(path)
-                .with_context(|| format!("Failed to resolve image.storage path `{path}`"))?;
-            if !expanded.is_absolute() {
-                bail!(
-                    "Configured image.storage `{path}` must be an absolute path or start with `~/`"
-                );
-            }
-            Ok((expanded, true))
-        }
+        Some(path) => Ok((resolve_configured_dir("image.storage", path)?, true)),
         None => default_storage_root(is_root).map(|p| (p, false)),
     }
 }
 
+/// Resolves `build.dir`, falling back to `<storage_root>/build`.
+pub(crate) fn resolve_build_dir(config: &RkforgeConfig, storage_root: &Path) -> Result<PathBuf> {
+    match config.build_dir() {
+        Some(path) => resolve_configured_dir("build.dir", path),
+        None => Ok(storage_root.join("build")),
+    }
+}
+
+/// Resolves `metadata.dir`, falling back to `<storage_root>/metadata`.
+pub(crate) fn resolve_metadata_dir(config: &RkforgeConfig, storage_root: &Path) -> Result<PathBuf> {
+    match config.metadata_dir() {
+        Some(path) => resolve_configured_dir("metadata.dir", path),
+        None => Ok(storage_root.join("metadata")),
+    }
+}
+
+pub(crate) fn resolve_default_registry(config: &RkforgeConfig) -> String {
+    config.default_registry().unwrap_or(REGISTRY).to_string()
+}
+
+pub(crate) fn resolve_max_concurrency(config: &RkforgeConfig) -> usize {
+    config.max_concurrency().unwrap_or(DEFAULT_MAX_CONCURRENCY)
+}
+
 pub(crate) fn resolve_storage_root_from_config(
     config: &RkforgeConfig,
     is_root: bool,
@@ -67,33 +93,36 @@ pub(crate) fn resolve_storage_root_for_current_user(config: &RkforgeConfig) -> R
     resolve_storage_root_from_config(config, current_user_is_root())
 }
 
-fn resolve_storage_root(is_root: bool) -> Result<(PathBuf, bool)> {
-    resolve_storage_root_with_loader(is_root, || {
-        let config_path = original_user_config_path("rk8s", Some("rkforge"))
-            .with_context(|| "Failed to resolve rkforge config path")?;
-        RkforgeConfig::load_from(&config_path).with_context(|| {
-            format!(
-                "Failed to load rkforge config from {}",
-                config_path.display()
-            )
-        })
+fn load_user_config() -> Result<RkforgeConfig> {
+    let config_path = original_user_config_path("rk8s", Some("rkforge"))
+        .with_context(|| "Failed to resolve rkforge config path")?;
+    RkforgeConfig::load_from(&config_path).with_context(|| {
+        format!(
+            "Failed to load rkforge config from {}",
+            config_path.display()
+        )
     })
 }
 
+fn load_config_or_default<F>(load_config: F) -> RkforgeConfig
+where
+    F: FnOnce() -> Result<RkforgeConfig>,
+{
+    load_config().unwrap_or_else(|err| {
+        tracing::warn!(
+            error = ?err,
+            "Failed to read rkforge config, falling back to default settings"
+        );
+        RkforgeConfig::default()
+    })
+}
+
+#[cfg(test)]
 fn resolve_storage_root_with_loader<F>(is_root: bool, load_config: F) -> Result<(PathBuf, bool)>
 where
     F: FnOnce() -> Result<RkforgeConfig>,
 {
-    match load_config() {
-        Ok(config) => resolve_storage_root_with_source(&config, is_root),
-        Err(err) => {
-            tracing::warn!(
-                error = ?err,
-                "Failed to read rkforge config for image.storage, falling back to default root"
-            );
-            default_storage_root(is_root).map(|path| (path, false))
-        }
-    }
+    resolve_storage_root_with_source(&load_config_or_default(load_config), is_root)
 }
 
 /// Validates the storage root when the path already exists.
@@ -166,6 +195,8 @@ pub struct Config {
     pub build_dir: PathBuf,
     pub metadata_dir: PathBuf,
     pub default_registry: String,
+    /// Maximum number of layer blobs downloaded or uploaded concurrently.
+    pub max_concurrency: usize,
     pub is_root: bool,
     /// Container rootfs mount mode: true=persistent overlay mount, false=traditional cp mode
     pub use_overlay_rootfs: bool,
@@ -176,17 +207,18 @@ pub struct Config {
 impl Config {
     pub fn new() -> Result<Self> {
         let is_root = current_user_is_root();
+        let user_config = load_config_or_default(load_user_config);
         let (root_dir, from_config) = match std::env::var("RKFORGE_STORAGE_ROOT") {
             Ok(val) if !val.is_empty() => (PathBuf::from(val), true),
-            _ => resolve_storage_root(is_root)?,
+            _ => resolve_storage_root_with_source(&user_config, is_root)?,
         };
         validate_storage_root(&root_dir)?;
         if from_config {
             ensure_storage_root_writable(&root_dir)?;
         }
         let layers_store_root = root_dir.join("layers");
-        let build_dir = root_dir.join("build");
-        let metadata_dir = root_dir.join("metadata");
+        let build_dir = resolve_build_dir(&user_config, &root_dir)?;
+        let metadata_dir = resolve_metadata_dir(&user_config, &root_dir)?;
 
         fs::create_dir_all(&layers_store_root).with_context(|| {
             format!(
@@ -204,7 +236,8 @@ impl Config {
             layers_store_root,
             build_dir,
             metadata_dir,
-            default_registry: String::from(REGISTRY),
+            default_registry: resolve_default_registry(&user_config),
+            max_concurrency: resolve_max_concurrency(&user_config),
             is_root,
             use_overlay_rootfs: std::env::var("RKFORGE_OVERLAY_ROOTFS")
                 .map(|v| v != "0")
@@ -219,14 +252,15 @@ impl Config {
 #[cfg(test)]
 mod tests {
     use super::{
-        default_storage_root, ensure_storage_root_writable, expand_home,
+        REGISTRY, default_storage_root, ensure_storage_root_writable, expand_home,
+        resolve_build_dir, resolve_default_registry, resolve_max_concurrency, resolve_metadata_dir,
         resolve_storage_root_from_config, resolve_storage_root_with_loader, validate_storage_root,
     };
     use crate::config::auth::RkforgeConfig;
     use crate::utils::cli::original_user_home_dir;
     use anyhow::anyhow;
     use std::fs;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
     use tempfile::tempdir;
 
     #[test]
@@ -324,4 +358,71 @@ storage = "/tmp/rkforge-storage-test"
             "unexpected error message: {msg}"
         );
     }
+
+    #[test]
+    fn test_build_and_metadata_dirs_default_under_storage_root() {
+        let config = RkforgeConfig::default();
+        let root = Path::new("/data/rkforge");
+        assert_eq!(
+            resolve_build_dir(&config, root).unwrap(),
+            root.join("build")
+        );
+        assert_eq!(
+            resolve_metadata_dir(&config, root).unwrap(),
+            root.join("metadata")
+        );
+        assert_eq!(resolve_default_registry(&config), REGISTRY);
+        assert_eq!(resolve_max_concurrency(&config), 4);
+    }
+
+    #[test]
+    fn test_max_concurrency_ignores_zero() {
+        let mut config = RkforgeConfig::default();
+        config.image.max_concurrency = Some(0);
+        assert_eq!(resolve_max_concurrency(&config), 4);
+        config.image.max_concurrency = Some(16);
+        assert_eq!(resolve_max_concurrency(&config), 16);
+    }
+
+    #[test]
+    fn test_build_metadata_and_registry_overrides() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("rkforge.toml");
+        fs::write(
+            &config_path,
+            r#"
+[build]
+dir = "/scratch/build"
+
+[metadata]
+dir = "/srv/rkforge-meta"
+
+[registry]
+default = "registry.example.com:5000"
+"#,
+        )
+        .unwrap();
+
+        let config = RkforgeConfig::load_from(&config_path).unwrap();
+        let root = Path::new("/data/rkforge");
+        assert_eq!(
+            resolve_build_dir(&config, root).unwrap(),
+            PathBuf::from("/scratch/build")
+        );
+        assert_eq!(
+            resolve_metadata_dir(&config, root).unwrap(),
+            PathBuf::from("/srv/rkforge-meta")
+        );
+        assert_eq!(
+            resolve_default_registry(&config),
+            "registry.example.com:5000"
+        );
+    }
+
+    #[test]
+    fn test_build_dir_rejects_relative_path() {
+        let mut config = RkforgeConfig::default();
+        config.build.dir = Some("build".to_string());
+        assert!(resolve_build_dir(&config, Path::new("/data")).is_err());
+    }
 }
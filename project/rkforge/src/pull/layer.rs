@@ -1,32 +1,33 @@
+use crate::config::image::CONFIG;
 use crate::pull::media::get_media_type;
+use crate::pull::source::{PullSource, pull_blob_stream};
 use crate::storage::{DigestExt, ultimate_blob_path};
 use anyhow::{Context, bail};
 use futures::StreamExt;
 use indicatif::{ProgressBar, ProgressStyle};
 use iterator_ext::IteratorExt;
-use oci_client::Client;
 use oci_client::client::BlobResponse;
 use oci_client::manifest::{OciDescriptor, OciImageManifest};
-use oci_spec::distribution::Reference;
 use sha2::{Digest, Sha256};
 use std::iter;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 use std::time::Duration;
 use tempfile::TempDir;
 use tokio::io::{AsyncWriteExt, BufWriter};
-use tokio::sync::Semaphore;
-use tokio::task::JoinHandle;
+use tokio::task::JoinSet;
 
-const DEFAULT_PULL_CONCURRENCY: usize = 3;
 const DEFAULT_PULL_RETRIES: usize = 3;
 
+/// Pulls the blobs of `manifest` that are not cached yet.
+///
+/// Each blob is requested from `sources` in order, so mirrors are tried before
+/// the canonical registry, and is checked against the digest in the manifest.
 pub async fn pull_layers(
-    client: &Client,
-    image_ref: &Reference,
+    sources: &[PullSource],
     manifest: &OciImageManifest,
     no_cache: bool,
     quiet: bool,
+    skip_verify: bool,
 ) -> anyhow::Result<Vec<PathBuf>> {
     if !no_cache {
         remove_invalid_cached_layer_blobs(manifest).await?;
@@ -44,26 +45,19 @@ pub async fn pull_layers(
         .map(|descriptor| ultimate_blob_path(&descriptor.digest))
         .collect::<anyhow::Result<Vec<_>>>()?;
 
-    let semaphore = Arc::new(Semaphore::new(pull_concurrency_limit()));
-    let tasks = to_download
+    let jobs = to_download
         .into_iter()
         .map(|descriptor| {
-            let client = client.clone();
-            let image_ref = image_ref.clone();
+            let sources = sources.to_vec();
             let descriptor = descriptor.clone();
-            let semaphore = semaphore.clone();
-
-            tokio::spawn(async move {
-                let _permit = semaphore
-                    .acquire_owned()
-                    .await
-                    .with_context(|| "Failed to acquire layer pull permit")?;
-                pull_and_unpack_layer(&client, &image_ref, &descriptor, no_cache, quiet).await
-            })
+
+            async move {
+                pull_and_unpack_layer(&sources, &descriptor, no_cache, quiet, skip_verify).await
+            }
         })
         .collect::<Vec<_>>();
 
-    if let Err(err) = wait_for_layer_tasks(tasks).await {
+    if let Err(err) = run_layer_jobs(jobs, pull_concurrency_limit()).await {
         if let Err(cleanup_err) = remove_cached_blobs(&new_blob_paths).await {
             tracing::warn!(
                 error = ?cleanup_err,
@@ -100,8 +94,9 @@ async fn remove_invalid_cached_layer_blobs(manifest: &OciImageManifest) -> anyho
     Ok(())
 }
 
+/// `RKFORGE_PULL_CONCURRENCY` overrides the `image.max_concurrency` setting.
 fn pull_concurrency_limit() -> usize {
-    parse_positive_env_usize("RKFORGE_PULL_CONCURRENCY", DEFAULT_PULL_CONCURRENCY)
+    parse_positive_env_usize("RKFORGE_PULL_CONCURRENCY", CONFIG.max_concurrency)
 }
 
 fn pull_retry_attempts() -> usize {
@@ -123,14 +118,58 @@ async fn remove_cached_blobs(paths: &[PathBuf]) -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn wait_for_layer_tasks(tasks: Vec<JoinHandle<anyhow::Result<()>>>) -> anyhow::Result<()> {
-    let results = futures::future::join_all(tasks).await;
-    for result in results {
-        result
-            .with_context(|| "Failed to join layer download task")?
-            .with_context(|| "Failed to pull and unpack layer")?;
+/// Runs layer jobs with at most `limit` of them in flight.
+///
+/// The first failure aborts every other job and stops scheduling new ones; the
+/// failures collected until then are reported as one error.
+async fn run_layer_jobs<F>(jobs: Vec<F>, limit: usize) -> anyhow::Result<()>
+where
+    F: Future<Output = anyhow::Result<()>> + Send + 'static,
+{
+    let mut pending = jobs.into_iter();
+    let mut running = JoinSet::new();
+    for job in pending.by_ref().take(limit.max(1)) {
+        running.spawn(job);
     }
-    Ok(())
+
+    let mut errors = Vec::new();
+    while let Some(result) = running.join_next().await {
+        let err = match result {
+            Ok(Ok(())) => {
+                if errors.is_empty()
+                    && let Some(job) = pending.next()
+                {
+                    running.spawn(job);
+                }
+                continue;
+            }
+            Ok(Err(err)) => err,
+            Err(err) if err.is_cancelled() => continue,
+            Err(err) => anyhow::Error::new(err).context("Failed to join layer download task"),
+        };
+        if errors.is_empty() {
+            running.abort_all();
+        }
+        errors.push(err);
+    }
+
+    aggregate_layer_errors(errors)
+}
+
+fn aggregate_layer_errors(errors: Vec<anyhow::Error>) -> anyhow::Result<()> {
+    let mut errors = errors.into_iter();
+    let Some(first) = errors.next() else {
+        return Ok(());
+    };
+    let others = errors.map(|err| format!("{err:#}")).collect::<Vec<_>>();
+    if others.is_empty() {
+        return Err(first.context("Failed to pull and unpack layer"));
+    }
+    Err(first.context(format!(
+        "Failed to pull and unpack {} layers; other failures: {}",
+        others.len() + 1,
+        others.join("; ")
+    )))
 }
 
 async fn ensure_unpacked_layer_dirs(layers: &[PathBuf]) -> anyhow::Result<()> {
@@ -149,11 +188,11 @@ async fn ensure_unpacked_layer_dirs(layers: &[PathBuf]) -> anyhow::Result<()> {
 }
 
 async fn pull_and_unpack_layer(
-    client: &Client,
-    image_ref: &Reference,
+    sources: &[PullSource],
     descriptor: &OciDescriptor,
     no_cache: bool,
     quiet: bool,
+    skip_verify: bool,
 ) -> anyhow::Result<()> {
     let digest = &descriptor.digest;
 
@@ -161,7 +200,7 @@ async fn pull_and_unpack_layer(
     let packed_blob_path = temp_dir.path().join(digest);
     let ultimate_blob_path = ultimate_blob_path(digest)?;
 
-    pull_layer(client, image_ref, descriptor, &packed_blob_path, quiet).await?;
+    pull_layer(sources, descriptor, &packed_blob_path, quiet, skip_verify).await?;
 
     if no_cache {
         remove_cached_blob(&ultimate_blob_path).await?;
@@ -203,11 +242,11 @@ async fn remove_cached_blob(path: &Path) -> anyhow::Result<()> {
 }
 
 async fn pull_layer(
-    client: &Client,
-    image_ref: &Reference,
+    sources: &[PullSource],
     descriptor: &OciDescriptor,
     dest: impl AsRef<Path>,
     quiet: bool,
+    skip_verify: bool,
 ) -> anyhow::Result<()> {
     let dest = dest.as_ref();
     let digest = descriptor.digest.split_digest()?;
@@ -216,7 +255,7 @@ async fn pull_layer(
     let attempts = pull_retry_attempts();
 
     for attempt in 1..=attempts {
-        match pull_layer_attempt(client, image_ref, descriptor, dest, &progress_bar).await {
+        match pull_layer_attempt(sources, descriptor, dest, &progress_bar, skip_verify).await {
             Ok(()) => {
                 progress_bar.finish_with_message(format!("Downloaded layer {digest}"));
                 return Ok(());
@@ -245,11 +284,11 @@ async fn pull_layer(
 }
 
 async fn pull_layer_attempt(
-    client: &Client,
-    image_ref: &Reference,
+    sources: &[PullSource],
     descriptor: &OciDescriptor,
     dest: &Path,
     progress_bar: &ProgressBar,
+    skip_verify: bool,
 ) -> anyhow::Result<()> {
     let expected_size = descriptor.size as u64;
     let mut offset = downloaded_blob_size(dest).await?;
@@ -259,6 +298,10 @@ async fn pull_layer_attempt(
     }
 
     if offset == expected_size && expected_size != 0 {
+        if skip_verify {
+            progress_bar.set_position(expected_size);
+            return Ok(());
+        }
         match verify_downloaded_blob(dest, &descriptor.digest).await {
             Ok(()) => {
                 progress_bar.set_position(expected_size);
@@ -272,10 +315,9 @@ async fn pull_layer_attempt(
     }
 
     progress_bar.set_position(offset);
-    let response = client
-        .pull_blob_stream_partial(image_ref, descriptor, offset, None)
-        .await
-        .with_context(|| format!("Failed to pull layer blob {}", descriptor.digest))?;
+    let (response, source) = pull_blob_stream(sources, descriptor, offset).await?;
+    // Bytes served by a mirror are always checked against the manifest digest.
+    let skip_verify = skip_verify && !source.is_mirror;
 
     let (stream, append) = match response {
         BlobResponse::Full(stream) => {
@@ -299,11 +341,20 @@ async fn pull_layer_attempt(
             .await
             .with_context(|| format!("Failed to create layer file: {}", dest.display()))?
     };
+    // Hash while streaming; a resumed download first hashes the bytes already on disk.
+    let mut hasher = match (skip_verify, append) {
+        (true, _) => None,
+        (false, true) => Some(hash_existing_blob(dest).await?),
+        (false, false) => Some(Sha256::new()),
+    };
     let mut writer = BufWriter::new(file);
     let mut stream = stream.stream;
 
     while let Some(bytes) = stream.next().await {
         let bytes = bytes.with_context(|| format!("Failed to read layer {}", descriptor.digest))?;
+        if let Some(hasher) = hasher.as_mut() {
+            hasher.update(&bytes);
+        }
         writer
             .write_all(&bytes)
             .await
@@ -325,10 +376,48 @@ async fn pull_layer_attempt(
             metadata.len()
         );
     }
-    if let Err(err) = verify_downloaded_blob(dest, &descriptor.digest).await {
+    if let Some(hasher) = hasher
+        && let Err(err) = check_blob_digest(dest, &descriptor.digest, hasher)
+    {
         let _ = remove_cached_blob(dest).await;
         return Err(err);
     }
+    tracing::info!(
+        layer = %descriptor.digest,
+        source = %source.registry,
+        mirror = source.is_mirror,
+        "downloaded layer blob"
+    );
+    Ok(())
+}
+
+async fn hash_existing_blob(path: &Path) -> anyhow::Result<Sha256> {
+    let path = path.to_owned();
+    tokio::task::spawn_blocking(move || {
+        let mut file = std::fs::File::open(&path)
+            .with_context(|| format!("Failed to open partial layer {}", path.display()))?;
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut file, &mut hasher)
+            .with_context(|| format!("Failed to read partial layer {}", path.display()))?;
+        Ok(hasher)
+    })
+    .await?
+}
+
+/// Compares the hash of a downloaded blob with the manifest digest.
+fn check_blob_digest(path: &Path, digest: &str, hasher: Sha256) -> anyhow::Result<()> {
+    let expected = digest
+        .strip_prefix("sha256:")
+        .with_context(|| format!("Unsupported layer digest algorithm: {digest}"))?;
+    let actual = format!("{:x}", hasher.finalize());
+    if actual != expected {
+        bail!(
+            "Downloaded layer digest mismatch for {}. Expected sha256:{}, got sha256:{}",
+            path.display(),
+            expected,
+            actual
+        );
+    }
     Ok(())
 }
 
@@ -352,9 +441,6 @@ async fn verify_downloaded_blob(path: &Path, digest: &str) -> anyhow::Result<()>
 }
 
 fn verify_downloaded_blob_sync(path: &Path, digest: &str) -> anyhow::Result<()> {
-    let expected = digest
-        .strip_prefix("sha256:")
-        .with_context(|| format!("Unsupported layer digest algorithm: {digest}"))?;
     let mut file = std::fs::File::open(path)
         .with_context(|| format!("Failed to open downloaded layer {}", path.display()))?;
     let mut hasher = Sha256::new();
@@ -367,16 +453,7 @@ fn verify_downloaded_blob_sync(path: &Path, digest: &str) -> anyhow::Result<()>
         }
         hasher.update(&buffer[..read]);
     }
-    let actual = format!("{:x}", hasher.finalize());
-    if actual != expected {
-        bail!(
-            "Downloaded layer digest mismatch for {}. Expected sha256:{}, got sha256:{}",
-            path.display(),
-            expected,
-            actual
-        );
-    }
-    Ok(())
+    check_blob_digest(path, digest, hasher)
 }
 
 fn retry_delay(attempt: usize) -> Duration {
@@ -460,19 +537,83 @@ fn new_progress_bar(total_size: u64, digest: impl AsRef<str>, quiet: bool) -> Pr
 #[cfg(test)]
 mod tests {
     use super::{
-        ensure_unpacked_layer_dirs, parse_positive_env_usize, remove_cached_blobs, retry_delay,
-        wait_for_layer_tasks,
+        aggregate_layer_errors, check_blob_digest, ensure_unpacked_layer_dirs, hash_existing_blob,
+        parse_positive_env_usize, remove_cached_blobs, retry_delay, run_layer_jobs,
     };
+    use sha2::{Digest, Sha256};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
 
     #[tokio::test]
-    async fn wait_for_layer_tasks_propagates_inner_error() {
-        let tasks = vec![tokio::spawn(async { anyhow::bail!("unpack failed") })];
+    async fn run_layer_jobs_propagates_inner_error() {
+        let jobs = vec![async { Err::<(), _>(anyhow::anyhow!("unpack failed")) }];
 
-        let err = wait_for_layer_tasks(tasks).await.unwrap_err();
+        let err = run_layer_jobs(jobs, 3).await.unwrap_err();
 
         assert!(format!("{err:?}").contains("unpack failed"));
     }
 
+    #[tokio::test]
+    async fn run_layer_jobs_respects_concurrency_limit() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let jobs = (0..8)
+            .map(|_| {
+                let running = running.clone();
+                let peak = peak.clone();
+                async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    anyhow::Ok(())
+                }
+            })
+            .collect::<Vec<_>>();
+
+        run_layer_jobs(jobs, 3).await.unwrap();
+
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(peak > 1, "layers should transfer in parallel");
+        assert!(peak <= 3, "at most 3 layers in flight, saw {peak}");
+    }
+
+    #[tokio::test]
+    async fn run_layer_jobs_cancels_remaining_jobs_on_failure() {
+        let finished = Arc::new(AtomicUsize::new(0));
+        let mut jobs: Vec<std::pin::Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>> =
+            vec![Box::pin(async { anyhow::bail!("layer 0 failed") })];
+        for _ in 0..4 {
+            let finished = finished.clone();
+            jobs.push(Box::pin(async move {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                finished.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }));
+        }
+
+        let err = run_layer_jobs(jobs, 2).await.unwrap_err();
+
+        assert!(format!("{err:#}").contains("layer 0 failed"));
+        assert_eq!(finished.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn aggregate_layer_errors_reports_every_failure() {
+        let err = aggregate_layer_errors(vec![
+            anyhow::anyhow!("first broke"),
+            anyhow::anyhow!("second broke"),
+        ])
+        .unwrap_err();
+
+        let message = format!("{err:#}");
+        assert!(message.contains("2 layers"));
+        assert!(message.contains("first broke"));
+        assert!(message.contains("second broke"));
+        assert!(aggregate_layer_errors(Vec::new()).is_ok());
+    }
+
     #[tokio::test]
     async fn ensure_unpacked_layer_dirs_rejects_missing_layer() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -502,6 +643,33 @@ mod tests {
         assert!(!dir.exists());
     }
 
+    #[tokio::test]
+    async fn resumed_stream_hash_matches_full_digest() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let blob = temp_dir.path().join("blob");
+        std::fs::write(&blob, b"hello ").unwrap();
+        let digest = format!("sha256:{:x}", Sha256::digest(b"hello world"));
+
+        let mut hasher = hash_existing_blob(&blob).await.unwrap();
+        hasher.update(b"world");
+
+        check_blob_digest(&blob, &digest, hasher).unwrap();
+    }
+
+    #[test]
+    fn check_blob_digest_rejects_corrupted_blob() {
+        let digest = format!("sha256:{:x}", Sha256::digest(b"expected"));
+        let mut hasher = Sha256::new();
+        hasher.update(b"corrupted");
+
+        let err = check_blob_digest(std::path::Path::new("layer"), &digest, hasher).unwrap_err();
+
+        assert!(err.to_string().contains("digest mismatch"));
+        assert!(
+            check_blob_digest(std::path::Path::new("layer"), "md5:abc", Sha256::new()).is_err()
+        );
+    }
+
     #[test]
     fn retry_delay_is_capped() {
         assert_eq!(retry_delay(1), std::time::Duration::from_millis(500));
@@ -1,18 +1,16 @@
 mod downloader;
 mod layer;
 pub mod media;
+mod source;
 
 use crate::config::auth::AuthConfig;
 use crate::pull::layer::pull_layers;
-use crate::registry::{parse_registry_host_arg, resolve_client_ref_auth as resolve_ref_with_auth};
+use crate::pull::source::{PullSource, canonical_source, pull_manifest, resolve_pull_sources};
+use crate::registry::parse_registry_host_arg;
 use crate::storage::write_manifest;
-use anyhow::Context;
 use anyhow::anyhow;
 use clap::Parser;
-use oci_client::Client;
 use oci_client::manifest::OciManifest;
-use oci_client::secrets::RegistryAuth;
-use oci_spec::distribution::Reference;
 use std::path::PathBuf;
 use std::sync::OnceLock;
 use std::thread;
@@ -92,6 +90,9 @@ pub struct PullArgs {
     /// Skip TLS certificate verification for HTTPS registry.
     #[arg(long)]
     skip_tls_verify: bool,
+    /// Do not check downloaded layers against their manifest digests.
+    #[arg(long)]
+    skip_verify: bool,
 }
 
 pub fn pull(args: PullArgs) -> anyhow::Result<()> {
@@ -101,6 +102,7 @@ pub fn pull(args: PullArgs) -> anyhow::Result<()> {
         false,
         false,
         args.skip_tls_verify,
+        args.skip_verify,
     )?;
     Ok(())
 }
@@ -110,7 +112,9 @@ pub fn sync_pull_or_get_image_with_policy(
     url: Option<impl AsRef<str>>,
     no_cache: bool,
 ) -> anyhow::Result<(PathBuf, Vec<PathBuf>)> {
-    sync_pull_or_get_image_with_policy_and_output_with_tls(image_ref, url, no_cache, false, false)
+    sync_pull_or_get_image_with_policy_and_output_with_tls(
+        image_ref, url, no_cache, false, false, false,
+    )
 }
 
 pub fn sync_pull_or_get_image_with_policy_and_output(
@@ -119,7 +123,9 @@ pub fn sync_pull_or_get_image_with_policy_and_output(
     no_cache: bool,
     quiet: bool,
 ) -> anyhow::Result<(PathBuf, Vec<PathBuf>)> {
-    sync_pull_or_get_image_with_policy_and_output_with_tls(image_ref, url, no_cache, quiet, false)
+    sync_pull_or_get_image_with_policy_and_output_with_tls(
+        image_ref, url, no_cache, quiet, false, false,
+    )
 }
 
 fn sync_pull_or_get_image_with_policy_and_output_with_tls(
@@ -128,25 +134,23 @@ fn sync_pull_or_get_image_with_policy_and_output_with_tls(
     no_cache: bool,
     quiet: bool,
     skip_tls_verify: bool,
+    skip_verify: bool,
 ) -> anyhow::Result<(PathBuf, Vec<PathBuf>)> {
     let image_ref = image_ref.as_ref();
     let url = url.map(|u| u.as_ref().to_string());
-    let (client, image_ref, auth_method) =
-        resolve_client_ref_auth(image_ref, url, skip_tls_verify)?;
+    let sources = resolve_sources(image_ref, url, skip_tls_verify)?;
     let do_pull = async move {
-        let (manifest, digest) = client
-            .pull_manifest(&image_ref, &auth_method)
-            .await
-            .map_err(|e| anyhow!("Failed to pull manifest: {e}"))?;
+        let (manifest, digest) = pull_manifest(&sources).await?;
 
         let layers = match &manifest {
             OciManifest::Image(manifest) => {
-                pull_layers(&client, &image_ref, manifest, no_cache, quiet).await
+                pull_layers(&sources, manifest, no_cache, quiet, skip_verify).await
             }
             OciManifest::ImageIndex(_) => anyhow::bail!("Image indexes are not supported yet"),
         }?;
 
-        let manifest_path = write_manifest(&image_ref, &manifest, &digest).await?;
+        let image_ref = &canonical_source(&sources).image_ref;
+        let manifest_path = write_manifest(image_ref, &manifest, &digest).await?;
         Ok((manifest_path, layers))
     };
     match Handle::try_current() {
@@ -220,21 +224,18 @@ async fn pull_or_get_image_with_policy_and_tls(
 ) -> anyhow::Result<(PathBuf, Vec<PathBuf>)> {
     let image_ref = image_ref.as_ref();
     let url = url.map(|u| u.as_ref().to_string());
-    let (client, image_ref, auth_method) =
-        resolve_client_ref_auth(image_ref, url, skip_tls_verify)?;
-    let (manifest, digest) = client
-        .pull_manifest(&image_ref, &auth_method)
-        .await
-        .with_context(|| "Failed to pull manifest")?;
+    let sources = resolve_sources(image_ref, url, skip_tls_verify)?;
+    let (manifest, digest) = pull_manifest(&sources).await?;
 
     let layers = match &manifest {
         OciManifest::Image(manifest) => {
-            pull_layers(&client, &image_ref, manifest, no_cache, false).await
+            pull_layers(&sources, manifest, no_cache, false, false).await
         }
         OciManifest::ImageIndex(_) => anyhow::bail!("Image indexes are not supported yet"),
     }?;
 
-    let manifest_path = write_manifest(&image_ref, &manifest, &digest).await?;
+    let image_ref = &canonical_source(&sources).image_ref;
+    let manifest_path = write_manifest(image_ref, &manifest, &digest).await?;
     Ok((manifest_path, layers))
 }
 
@@ -250,22 +251,19 @@ pub fn sync_pull_or_get_image_with_config(
     let url = url.map(|u| u.as_ref().to_string());
     let (resolved_url, normalized_image_ref) =
         resolve_registry_and_image_ref(auth_config, image_ref, url)?;
-    let (client, image_ref, auth_method) =
-        resolve_ref_with_auth(auth_config, &resolved_url, &normalized_image_ref, false)?;
+    let sources = resolve_pull_sources(auth_config, &resolved_url, &normalized_image_ref, false)?;
     let do_pull = async move {
-        let (manifest, digest) = client
-            .pull_manifest(&image_ref, &auth_method)
-            .await
-            .map_err(|e| anyhow!("Failed to pull manifest: {e}"))?;
+        let (manifest, digest) = pull_manifest(&sources).await?;
 
         let layers = match &manifest {
             OciManifest::Image(manifest) => {
-                pull_layers(&client, &image_ref, manifest, false, false).await
+                pull_layers(&sources, manifest, false, false, false).await
             }
             OciManifest::ImageIndex(_) => anyhow::bail!("Image indexes are not supported yet"),
         }?;
 
-        let manifest_path = write_manifest(&image_ref, &manifest, &digest).await?;
+        let image_ref = &canonical_source(&sources).image_ref;
+        let manifest_path = write_manifest(image_ref, &manifest, &digest).await?;
         Ok((manifest_path, layers))
     };
     match Handle::try_current() {
@@ -295,33 +293,27 @@ pub async fn pull_or_get_image_with_config(
     let url = url.map(|u| u.as_ref().to_string());
     let (resolved_url, normalized_image_ref) =
         resolve_registry_and_image_ref(auth_config, image_ref, url)?;
-    let (client, image_ref, auth_method) =
-        resolve_ref_with_auth(auth_config, &resolved_url, &normalized_image_ref, false)?;
-
-    let (manifest, digest) = client
-        .pull_manifest(&image_ref, &auth_method)
-        .await
-        .with_context(|| "Failed to pull manifest")?;
+    let sources = resolve_pull_sources(auth_config, &resolved_url, &normalized_image_ref, false)?;
+    let (manifest, digest) = pull_manifest(&sources).await?;
 
     let layers = match &manifest {
-        OciManifest::Image(manifest) => {
-            pull_layers(&client, &image_ref, manifest, false, false).await
-        }
+        OciManifest::Image(manifest) => pull_layers(&sources, manifest, false, false, false).await,
         OciManifest::ImageIndex(_) => anyhow::bail!("Image indexes are not supported yet"),
     }?;
 
-    let manifest_path = write_manifest(&image_ref, &manifest, &digest).await?;
+    let image_ref = &canonical_source(&sources).image_ref;
+    let manifest_path = write_manifest(image_ref, &manifest, &digest).await?;
     Ok((manifest_path, layers))
 }
 
-fn resolve_client_ref_auth(
+fn resolve_sources(
     image_ref: &str,
     url: Option<String>,
     skip_tls_verify: bool,
-) -> anyhow::Result<(Client, Reference, RegistryAuth)> {
+) -> anyhow::Result<Vec<PullSource>> {
     let auth_config = AuthConfig::load()?;
     let (url, normalized_image_ref) = resolve_registry_and_image_ref(&auth_config, image_ref, url)?;
-    resolve_ref_with_auth(&auth_config, &url, &normalized_image_ref, skip_tls_verify)
+    resolve_pull_sources(&auth_config, &url, &normalized_image_ref, skip_tls_verify)
 }
 
 #[cfg(test)]
@@ -0,0 +1,226 @@
+use crate::config::auth::AuthConfig;
+use crate::registry::{explain_connection_error, resolve_client_ref_auth};
+use anyhow::{Context, bail};
+use oci_client::client::BlobResponse;
+use oci_client::manifest::{OciDescriptor, OciManifest};
+use oci_client::secrets::RegistryAuth;
+use oci_client::{Client, RegistryOperation};
+use oci_spec::distribution::Reference;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// A registry an image can be fetched from: one of its mirrors or the
+/// canonical registry itself.
+#[derive(Clone)]
+pub struct PullSource {
+    pub registry: String,
+    pub client: Client,
+    pub image_ref: Reference,
+    pub auth: RegistryAuth,
+    pub proxy: Option<String>,
+    pub is_mirror: bool,
+    authenticated: Arc<OnceCell<()>>,
+}
+
+impl PullSource {
+    fn new(
+        auth_config: &AuthConfig,
+        registry: &str,
+        image_ref: &str,
+        skip_tls_verify: bool,
+        is_mirror: bool,
+    ) -> anyhow::Result<Self> {
+        let (client, image_ref, auth) =
+            resolve_client_ref_auth(auth_config, registry, image_ref, skip_tls_verify)?;
+        Ok(Self {
+            registry: registry.to_string(),
+            client,
+            image_ref,
+            auth,
+            proxy: auth_config.proxy_for_registry(registry).map(str::to_string),
+            is_mirror,
+            authenticated: Arc::default(),
+        })
+    }
+
+    /// Fetches a pull token once; blob requests reuse the one cached in the client.
+    async fn authenticate(&self) -> anyhow::Result<()> {
+        self.authenticated
+            .get_or_try_init(|| async {
+                self.client
+                    .auth(&self.image_ref, &self.auth, RegistryOperation::Pull)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| self.explain(e.into()))
+            })
+            .await
+            .copied()
+    }
+
+    fn explain(&self, err: anyhow::Error) -> anyhow::Error {
+        explain_connection_error(err, &self.registry, self.proxy.as_deref())
+    }
+}
+
+/// Builds the sources for `image_ref`: the mirrors of `registry` in their
+/// configured order, followed by `registry` itself.
+pub fn resolve_pull_sources(
+    auth_config: &AuthConfig,
+    registry: &str,
+    image_ref: &str,
+    skip_tls_verify: bool,
+) -> anyhow::Result<Vec<PullSource>> {
+    let mut sources = auth_config
+        .mirrors_for(registry)
+        .iter()
+        .map(|mirror| PullSource::new(auth_config, mirror, image_ref, skip_tls_verify, true))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    sources.push(PullSource::new(
+        auth_config,
+        registry,
+        image_ref,
+        skip_tls_verify,
+        false,
+    )?);
+    Ok(sources)
+}
+
+/// The canonical registry, which is always the last source.
+pub fn canonical_source(sources: &[PullSource]) -> &PullSource {
+    sources
+        .last()
+        .expect("pull sources always end with the canonical registry")
+}
+
+/// Fetches the manifest from the first source that serves it.
+///
+/// Manifests pinned by digest are checked against that digest. For a tag, a
+/// mirror's manifest must hash to the digest the canonical registry reports for
+/// it, so a mirror cannot substitute different content for the canonical image.
+pub async fn pull_manifest(sources: &[PullSource]) -> anyhow::Result<(OciManifest, String)> {
+    let canonical = canonical_source(sources);
+    let canonical_digest = OnceCell::new();
+    let mut last_err = None;
+    for source in sources {
+        let result = async {
+            let expected = match source.image_ref.digest() {
+                Some(digest) => Some(digest.to_string()),
+                None if source.is_mirror => Some(
+                    canonical_digest
+                        .get_or_try_init(|| fetch_manifest_digest(canonical))
+                        .await?
+                        .clone(),
+                ),
+                None => None,
+            };
+            pull_manifest_from(source, expected.as_deref()).await
+        }
+        .await;
+        match result {
+            Ok(manifest) => {
+                tracing::info!(
+                    image = %canonical.image_ref,
+                    source = %source.registry,
+                    "pulled manifest"
+                );
+                return Ok(manifest);
+            }
+            Err(err) if source.is_mirror => {
+                tracing::warn!(
+                    error = ?err,
+                    mirror = %source.registry,
+                    "mirror failed to serve manifest; trying next source"
+                );
+                last_err = Some(err);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| anyhow::anyhow!("no registry to pull the manifest from")))
+}
+
+/// Asks `source` for the digest of its manifest with a HEAD request.
+async fn fetch_manifest_digest(source: &PullSource) -> anyhow::Result<String> {
+    source.authenticate().await?;
+    source
+        .client
+        .fetch_manifest_digest(&source.image_ref, &source.auth)
+        .await
+        .map_err(|e| source.explain(e.into()))
+        .with_context(|| {
+            format!(
+                "Failed to resolve the manifest digest on {}",
+                source.registry
+            )
+        })
+}
+
+/// Pulls the manifest from `source`. The returned digest is that of the
+/// manifest bytes, so comparing it with `expected` verifies the content.
+async fn pull_manifest_from(
+    source: &PullSource,
+    expected: Option<&str>,
+) -> anyhow::Result<(OciManifest, String)> {
+    let (manifest, digest) = source
+        .client
+        .pull_manifest(&source.image_ref, &source.auth)
+        .await
+        .map_err(|e| source.explain(e.into()).context("Failed to pull manifest"))?;
+    // Pulling the manifest also authenticated the client for later blob requests.
+    let _ = source.authenticated.set(());
+    if let Some(expected) = expected
+        && expected != digest
+    {
+        bail!(
+            "manifest from {} has digest {digest}, expected {expected}",
+            source.registry
+        );
+    }
+    Ok((manifest, digest))
+}
+
+/// Opens a stream for `descriptor` starting at `offset`, trying each source in
+/// order. Returns the response together with the source that served it.
+pub async fn pull_blob_stream<'a>(
+    sources: &'a [PullSource],
+    descriptor: &OciDescriptor,
+    offset: u64,
+) -> anyhow::Result<(BlobResponse, &'a PullSource)> {
+    let mut last_err = None;
+    for source in sources {
+        match pull_blob_stream_from(source, descriptor, offset).await {
+            Ok(response) => return Ok((response, source)),
+            Err(err) => {
+                if source.is_mirror {
+                    tracing::warn!(
+                        error = ?err,
+                        layer = %descriptor.digest,
+                        mirror = %source.registry,
+                        "mirror failed to serve layer blob; trying next source"
+                    );
+                }
+                last_err = Some(err);
+            }
+        }
+    }
+    Err(last_err.unwrap_or_else(|| anyhow::anyhow!("no registry to pull the layer from")))
+}
+
+async fn pull_blob_stream_from(
+    source: &PullSource,
+    descriptor: &OciDescriptor,
+    offset: u64,
+) -> anyhow::Result<BlobResponse> {
+    source.authenticate().await?;
+    source
+        .client
+        .pull_blob_stream_partial(&source.image_ref, descriptor, offset, None)
+        .await
+        .map_err(|e| source.explain(e.into()))
+        .with_context(|| {
+            format!(
+                "Failed to pull layer blob {} from {}",
+                descriptor.digest, source.registry
+            )
+        })
+}
@@ -1,10 +1,11 @@
 mod pusher;
 
 use crate::config::auth::AuthConfig;
+use crate::config::image::CONFIG;
 use crate::push::pusher::{PushTask, Pusher};
 use crate::registry::{
-    RegistryScheme, effective_skip_tls_verify, parse_registry_host, parse_registry_host_arg,
-    resolve_client_ref_auth as resolve_ref_with_auth,
+    ProxySettings, RegistryScheme, effective_skip_tls_verify, explain_connection_error,
+    parse_registry_host, parse_registry_host_arg, resolve_client_ref_auth as resolve_ref_with_auth,
 };
 use crate::rt::block_on;
 use crate::storage::{DigestExt, parse_image_ref};
@@ -90,6 +91,7 @@ fn push_from_layout_with_tls(
         &registry_url,
         auth_method.clone(),
         skip_tls_verify,
+        &auth_config.proxy,
     )?;
 
     block_on(async move {
@@ -113,6 +115,8 @@ struct BlobUploader {
     scheme: RegistryScheme,
     registry: String,
     auth: RegistryAuth,
+    /// Proxy the registry is reached through, for error reporting.
+    proxy: Option<String>,
 }
 
 struct PushImageContext<'a> {
@@ -151,7 +155,6 @@ struct UploadStatus {
 
 const SMALL_BLOB_UPLOAD_THRESHOLD_BYTES: u64 = 16 * 1024 * 1024;
 const CHUNKED_BLOB_UPLOAD_SIZE_BYTES: usize = 32 * 1024 * 1024;
-const MAX_CONCURRENT_BLOB_UPLOADS: usize = 4;
 const STREAM_READ_BUFFER_BYTES: usize = 1024 * 1024;
 const BLOB_UPLOAD_MAX_ATTEMPTS: usize = 5;
 const BLOB_UPLOAD_RETRY_BASE_DELAY_MS: u64 = 300;
@@ -209,24 +212,29 @@ impl BlobUploader {
         registry: impl Into<String>,
         auth: RegistryAuth,
         skip_tls_verify: bool,
+        proxy: &ProxySettings,
     ) -> anyhow::Result<Self> {
         let registry = registry.into();
-        let http = reqwest::Client::builder()
-            .danger_accept_invalid_certs(effective_skip_tls_verify(
-                skip_tls_verify,
-                scheme,
-                &registry,
-            ))
+        let builder = reqwest::Client::builder().danger_accept_invalid_certs(
+            effective_skip_tls_verify(skip_tls_verify, scheme, &registry),
+        );
+        let http = proxy
+            .apply(builder)?
             .build()
             .context("failed to create blob upload HTTP client")?;
         Ok(Self {
             http,
             scheme,
+            proxy: proxy.proxy_for(scheme, &registry).map(str::to_string),
             registry,
             auth,
         })
     }
 
+    fn connection_error(&self, error: reqwest::Error) -> anyhow::Error {
+        explain_connection_error(error.into(), &self.registry, self.proxy.as_deref())
+    }
+
     fn apply_auth(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
         match &self.auth {
             RegistryAuth::Anonymous => builder,
@@ -308,11 +316,13 @@ impl BlobUploader {
                 }
                 Err(error) if should_retry_reqwest_error(&error) => {
                     if attempt == BLOB_UPLOAD_MAX_ATTEMPTS {
-                        return Err(error).with_context(|| format!("failed to {operation}"));
+                        return Err(self.connection_error(error))
+                            .with_context(|| format!("failed to {operation}"));
                     }
                 }
                 Err(error) => {
-                    return Err(error).with_context(|| format!("failed to {operation}"));
+                    return Err(self.connection_error(error))
+                        .with_context(|| format!("failed to {operation}"));
                 }
             }
 
@@ -753,7 +763,8 @@ async fn push_target_ref(
             let blobs_dir = blobs_dir.to_path_buf();
             async move { push_layer_descriptor(blob_uploader, target_ref, blobs_dir, descriptor).await }
         })
-        .buffer_unordered(MAX_CONCURRENT_BLOB_UPLOADS)
+        // Dropping the stream on the first error cancels the uploads still in flight.
+        .buffer_unordered(CONFIG.max_concurrency)
         .try_collect::<Vec<_>>()
         .await?;
 
@@ -3,7 +3,7 @@ mod types;
 use crate::config::auth::AuthConfig;
 use crate::config::auth::AuthEntry;
 use crate::registry::{
-    RegistryScheme, api_url, effective_skip_tls_verify, parse_registry_host_arg,
+    ProxySettings, RegistryScheme, api_url, effective_skip_tls_verify, parse_registry_host_arg,
 };
 use crate::repo::types::{ListRepoResponse, Visibility};
 use crate::rt::block_on;
@@ -44,22 +44,23 @@ pub fn repo(args: RepoArgs) -> anyhow::Result<()> {
     let entry = auth_config.resolve_entry(args.url.as_ref())?.clone();
     let scheme = auth_config.registry_scheme(&entry.url);
     let skip_tls_verify = effective_skip_tls_verify(args.skip_tls_verify, scheme, &entry.url);
+    let proxy = auth_config.proxy.clone();
     block_on(async move {
+        let client = client_with_authentication(&entry.pat, skip_tls_verify, &proxy).await?;
         match args.sub {
-            RepoSubArgs::List => handle_repo_list(&entry, scheme, skip_tls_verify).await,
+            RepoSubArgs::List => handle_repo_list(&client, &entry, scheme).await,
             RepoSubArgs::Vis { name, visibility } => {
-                handle_repo_visibility(&entry, scheme, skip_tls_verify, name, visibility).await
+                handle_repo_visibility(&client, &entry, scheme, name, visibility).await
             }
         }
     })?
 }
 
 async fn handle_repo_list(
+    client: &reqwest::Client,
     entry: &AuthEntry,
     scheme: RegistryScheme,
-    skip_tls_verify: bool,
 ) -> anyhow::Result<()> {
-    let client = client_with_authentication(&entry.pat, skip_tls_verify).await?;
     let url = api_url(scheme, &entry.url, "api/v1/repo");
 
     let res = send_and_handle_unexpected(client.get(&url))
@@ -107,13 +108,12 @@ fn format_local_timestamp(value: Option<DateTime<Utc>>) -> String {
 }
 
 async fn handle_repo_visibility(
+    client: &reqwest::Client,
     entry: &AuthEntry,
     scheme: RegistryScheme,
-    skip_tls_verify: bool,
     name: impl AsRef<str>,
     visibility: Visibility,
 ) -> anyhow::Result<()> {
-    let client = client_with_authentication(&entry.pat, skip_tls_verify).await?;
     let url = api_url(
         scheme,
         &entry.url,
@@ -130,14 +130,15 @@ async fn handle_repo_visibility(
 pub async fn client_with_authentication(
     pat: impl AsRef<str>,
     skip_tls_verify: bool,
+    proxy: &ProxySettings,
 ) -> anyhow::Result<reqwest::Client> {
     let mut headers = HeaderMap::new();
     headers.insert("Authorization", format!("Bearer {}", pat.as_ref()).parse()?);
 
-    Ok(reqwest::Client::builder()
+    let builder = reqwest::Client::builder()
         .default_headers(headers)
-        .danger_accept_invalid_certs(skip_tls_verify)
-        .build()?)
+        .danger_accept_invalid_certs(skip_tls_verify);
+    Ok(proxy.apply(builder)?.build()?)
 }
 
 pub async fn send_and_handle_unexpected(builder: RequestBuilder) -> anyhow::Result<Response> {
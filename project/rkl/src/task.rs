@@ -1308,6 +1308,7 @@ mod tests {
                 AuthEntry::new("other-token", "other.registry"),
             ],
             insecure_registries: vec!["insecure.local:5000".to_string()],
+            ..Default::default()
         };
 
         let merged = merge_auth_config_with_registry_credentials(
@@ -1338,7 +1339,7 @@ mod tests {
     fn merge_auth_config_prefers_central_entry_on_registry_conflict() {
         let local = AuthConfig {
             entries: vec![AuthEntry::new("local-token", "shared.registry")],
-            insecure_registries: Vec::new(),
+            ..Default::default()
         };
 
         let merged = merge_auth_config_with_registry_credentials(
@@ -2,17 +2,103 @@ use crate::protocol::config::NetworkConfig;
 use anyhow::Result;
 use common::*;
 use etcd_client::{
-    Client, Compare, CompareOp, GetOptions, PutOptions, Txn, TxnOp, WatchOptions, WatchStream,
-    Watcher,
+    Client, Compare, CompareOp, GetOptions, LeaseKeepAliveStream, LeaseKeeper, PutOptions, Txn,
+    TxnOp, TxnOpResponse, WatchOptions, WatchStream, Watcher,
 };
+use futures::stream::{self, BoxStream};
 use libvault::storage::xline::XlineOptions;
-use log::error;
+use log::{error, warn};
+use std::collections::VecDeque;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Namespace used for objects that do not name one.
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// Builds the `<namespace>/<name>` key of a namespaced object; an empty namespace
+/// falls back to [`DEFAULT_NAMESPACE`].
+pub fn namespaced_key(namespace: &str, name: &str) -> String {
+    if namespace.is_empty() {
+        format!("{DEFAULT_NAMESPACE}/{name}")
+    } else {
+        format!("{namespace}/{name}")
+    }
+}
+
+/// Splits a `<namespace>/<name>` key. A bare name is taken to live in
+/// [`DEFAULT_NAMESPACE`].
+pub fn split_namespaced_key(key: &str) -> (&str, &str) {
+    match key.split_once('/') {
+        Some((namespace, name)) if !namespace.is_empty() => (namespace, name),
+        Some((_, name)) => (DEFAULT_NAMESPACE, name),
+        None => (DEFAULT_NAMESPACE, key),
+    }
+}
+
+fn pod_key(key: &str) -> String {
+    let (namespace, name) = split_namespaced_key(key);
+    format!("/registry/pods/{namespace}/{name}")
+}
+
+fn replicaset_key(key: &str) -> String {
+    let (namespace, name) = split_namespaced_key(key);
+    format!("/registry/replicasets/{namespace}/{name}")
+}
+
+/// A watch event.
+/// Contains the resource yaml.
+#[derive(Debug, Clone)]
+pub enum WatchEvent {
+    Add { yaml: String },
+    Update { old_yaml: String, new_yaml: String },
+    Delete { yaml: String },
+}
+
+/// A change under a watched prefix, tagged with the revision that produced it.
+#[derive(Debug, Clone)]
+pub struct StoreEvent {
+    /// Key relative to the watched prefix, e.g. `<namespace>/<name>` for pods.
+    pub key: String,
+    /// Revision of the change. A watch resumed from `revision + 1` continues
+    /// right after this event.
+    pub revision: i64,
+    pub event: WatchEvent,
+}
+
+/// Stream of [`StoreEvent`]s in revision order. It ends after the first error.
+pub type StoreEventStream = BoxStream<'static, Result<StoreEvent>>;
+
+/// The revision a watch asked for has been compacted away; callers must take a
+/// fresh snapshot instead of resuming.
+#[derive(Debug, thiserror::Error)]
+#[error("watch revision has been compacted (compact revision {compact_revision})")]
+pub struct WatchCompacted {
+    pub compact_revision: i64,
+}
+
+/// A compare-and-swap write found a different revision than the caller read.
+///
+/// `current_revision` is `None` when the key no longer exists. Callers re-read the
+/// object, re-apply their change and try again.
+#[derive(Debug, thiserror::Error)]
+#[error("revision conflict on {key}: expected {expected_revision}, found {current_revision:?}")]
+pub struct RevisionConflict {
+    pub key: String,
+    pub expected_revision: i64,
+    pub current_revision: Option<i64>,
+}
+
 /// XlineStore provides an etcd-like API for managing pods and nodes.
-/// Keys are stored under `/registry/pods/` and `/registry/nodes/`.
+/// Pods and ReplicaSets are namespaced and stored under `/registry/pods/<ns>/<name>`
+/// and `/registry/replicasets/<ns>/<name>`; nodes live under `/registry/nodes/`.
 /// Values are YAML serialized definitions.
+///
+/// Methods that take a single pod or ReplicaSet name accept either a bare name,
+/// which resolves to [`DEFAULT_NAMESPACE`], or a `<namespace>/<name>` key.
+///
+/// `*_with_revision` reads return the object's mod revision; passing it back to the
+/// matching `update_*_if_revision` write only succeeds if nobody wrote in between,
+/// and fails with a [`RevisionConflict`] otherwise.
 #[derive(Clone)]
 pub struct XlineStore {
     client: Arc<RwLock<Client>>,
@@ -34,7 +120,7 @@ impl XlineStore {
         self.client.read().await
     }
 
-    /// List all pod names (keys only, values are ignored).
+    /// List the `<namespace>/<name>` keys of all pods (values are ignored).
     pub async fn list_pod_names(&self) -> Result<Vec<String>> {
         let key = "/registry/pods/".to_string();
         let mut client = self.client.write().await;
@@ -87,8 +173,25 @@ impl XlineStore {
         Ok(nodes)
     }
 
+    /// List pods across all namespaces. Kept for existing callers; same as
+    /// [`Self::list_all_pods`].
     pub async fn list_pods(&self) -> Result<Vec<PodTask>> {
-        let key = "/registry/pods/".to_string();
+        self.list_all_pods().await
+    }
+
+    /// List pods across all namespaces.
+    pub async fn list_all_pods(&self) -> Result<Vec<PodTask>> {
+        self.list_pods_under("/registry/pods/").await
+    }
+
+    /// List the pods of a single namespace.
+    pub async fn list_pods_in_namespace(&self, namespace: &str) -> Result<Vec<PodTask>> {
+        let prefix = pod_key(&namespaced_key(namespace, ""));
+        self.list_pods_under(&prefix).await
+    }
+
+    async fn list_pods_under(&self, prefix: &str) -> Result<Vec<PodTask>> {
+        let key = prefix.to_string();
         let mut client = self.client.write().await;
         let resp = client
             .get(key.clone(), Some(GetOptions::new().with_prefix()))
@@ -144,17 +247,107 @@ impl XlineStore {
         }
     }
 
+    /// Get a node YAML definition together with its mod revision.
+    pub async fn get_node_yaml_with_revision(
+        &self,
+        node_name: &str,
+    ) -> Result<Option<(String, i64)>> {
+        self.get_yaml_with_revision(&format!("/registry/nodes/{node_name}"))
+            .await
+    }
+
+    /// Write a node only if it is still at `expected_revision`; returns the new revision.
+    pub async fn update_node_if_revision(
+        &self,
+        node_name: &str,
+        node_yaml: &str,
+        expected_revision: i64,
+    ) -> Result<i64> {
+        let key = format!("/registry/nodes/{node_name}");
+        self.update_yaml_if_revision(&key, node_yaml, expected_revision)
+            .await
+    }
+
+    /// Record a heartbeat from a node: store the status it reported, stamp its Ready
+    /// condition with the current time and re-derive its taints. Returns `false` if
+    /// the node is not registered.
+    pub async fn record_node_heartbeat(&self, node_name: &str, status: NodeStatus) -> Result<bool> {
+        let Some(mut node) = self.get_node(node_name).await? else {
+            return Ok(false);
+        };
+        node.status = status;
+        // use the rks clock, worker clocks may drift
+        node.set_last_heartbeat_time(chrono::Utc::now());
+        node.spec.taints = Node::derive_taints_from_conditions(&node.status.conditions);
+        self.insert_node(&node).await?;
+        Ok(true)
+    }
+
     /// Insert a pod YAML definition into xline.
     pub async fn insert_pod_yaml(&self, pod_name: &str, pod_yaml: &str) -> Result<()> {
-        let key = format!("/registry/pods/{pod_name}");
+        let key = pod_key(pod_name);
         let mut client = self.client.write().await;
         client.put(key, pod_yaml, Some(PutOptions::new())).await?;
         Ok(())
     }
 
+    /// Insert a pod YAML definition into `namespace`.
+    pub async fn insert_namespaced_pod_yaml(
+        &self,
+        namespace: &str,
+        pod_name: &str,
+        pod_yaml: &str,
+    ) -> Result<()> {
+        self.insert_pod_yaml(&namespaced_key(namespace, pod_name), pod_yaml)
+            .await
+    }
+
+    /// Get a pod YAML definition together with its mod revision.
+    pub async fn get_pod_yaml_with_revision(
+        &self,
+        pod_name: &str,
+    ) -> Result<Option<(String, i64)>> {
+        self.get_yaml_with_revision(&pod_key(pod_name)).await
+    }
+
+    /// Write a pod only if it is still at `expected_revision`; returns the new revision.
+    pub async fn update_pod_if_revision(
+        &self,
+        pod_name: &str,
+        pod_yaml: &str,
+        expected_revision: i64,
+    ) -> Result<i64> {
+        self.update_yaml_if_revision(&pod_key(pod_name), pod_yaml, expected_revision)
+            .await
+    }
+
+    /// Get a pod YAML definition from `namespace`.
+    pub async fn get_namespaced_pod_yaml(
+        &self,
+        namespace: &str,
+        pod_name: &str,
+    ) -> Result<Option<String>> {
+        self.get_pod_yaml(&namespaced_key(namespace, pod_name))
+            .await
+    }
+
+    /// Get a pod object from `namespace`.
+    pub async fn get_namespaced_pod(
+        &self,
+        namespace: &str,
+        pod_name: &str,
+    ) -> Result<Option<PodTask>> {
+        self.get_pod(&namespaced_key(namespace, pod_name)).await
+    }
+
+    /// Delete a pod from `namespace`.
+    pub async fn delete_namespaced_pod(&self, namespace: &str, pod_name: &str) -> Result<()> {
+        self.delete_pod(&namespaced_key(namespace, pod_name)).await
+    }
+
     /// Get a pod YAML definition from xline.
     pub async fn get_pod_yaml(&self, pod_name: &str) -> Result<Option<String>> {
-        let key = format!("/registry/pods/{pod_name}");
+        let key = pod_key(pod_name);
         let mut client = self.client.write().await;
         let resp = client.get(key, None).await?;
         if let Some(kv) = resp.kvs().first() {
@@ -274,8 +467,14 @@ impl XlineStore {
         Ok((items, rev))
     }
 
-    /// Create a watch on all pods with prefix `/registry/pods/`, starting from a given revision.
-    pub async fn watch_pods(&self, start_rev: i64) -> Result<(Watcher, WatchStream)> {
+    /// Watch pod changes from `from_revision` on. Keys are `<namespace>/<name>`.
+    pub async fn watch_pods(&self, from_revision: i64) -> Result<StoreEventStream> {
+        self.watch_prefix_events("/registry/pods/", from_revision)
+            .await
+    }
+
+    /// Create a raw etcd watch on all pods under `/registry/pods/`, starting from a given revision.
+    pub async fn watch_pods_raw(&self, start_rev: i64) -> Result<(Watcher, WatchStream)> {
         let key_prefix = "/registry/pods/".to_string();
         let opts = WatchOptions::new()
             .with_prefix()
@@ -486,15 +685,42 @@ impl XlineStore {
 
     /// Insert a replicaset YAML definition into xline.
     pub async fn insert_replicaset_yaml(&self, rs_name: &str, rs_yaml: &str) -> Result<()> {
-        let key = format!("/registry/replicasets/{rs_name}");
+        let key = replicaset_key(rs_name);
         let mut client = self.client.write().await;
         client.put(key, rs_yaml, Some(PutOptions::new())).await?;
         Ok(())
     }
 
+    /// Insert a replicaset YAML definition into `namespace`.
+    pub async fn insert_namespaced_replicaset_yaml(
+        &self,
+        namespace: &str,
+        rs_name: &str,
+        rs_yaml: &str,
+    ) -> Result<()> {
+        self.insert_replicaset_yaml(&namespaced_key(namespace, rs_name), rs_yaml)
+            .await
+    }
+
+    /// Get a replicaset YAML definition from `namespace`.
+    pub async fn get_namespaced_replicaset_yaml(
+        &self,
+        namespace: &str,
+        rs_name: &str,
+    ) -> Result<Option<String>> {
+        self.get_replicaset_yaml(&namespaced_key(namespace, rs_name))
+            .await
+    }
+
+    /// Delete a replicaset from `namespace`.
+    pub async fn delete_namespaced_replicaset(&self, namespace: &str, rs_name: &str) -> Result<()> {
+        self.delete_replicaset(&namespaced_key(namespace, rs_name))
+            .await
+    }
+
     /// Get a replicaset YAML definition from xline.
     pub async fn get_replicaset_yaml(&self, rs_name: &str) -> Result<Option<String>> {
-        let key = format!("/registry/replicasets/{rs_name}");
+        let key = replicaset_key(rs_name);
         let mut client = self.client.write().await;
         let resp = client.get(key, None).await?;
         Ok(resp
@@ -503,19 +729,12 @@ impl XlineStore {
             .map(|kv| String::from_utf8_lossy(kv.value()).to_string()))
     }
 
+    /// Get a replicaset YAML definition together with its mod revision.
     pub async fn get_replicaset_yaml_with_revision(
         &self,
         rs_name: &str,
     ) -> Result<Option<(String, i64)>> {
-        let key = format!("/registry/replicasets/{rs_name}");
-        let mut client = self.client.write().await;
-        let resp = client.get(key, None).await?;
-        Ok(resp.kvs().first().map(|kv| {
-            (
-                String::from_utf8_lossy(kv.value()).to_string(),
-                kv.mod_revision(),
-            )
-        }))
+        self.get_yaml_with_revision(&replicaset_key(rs_name)).await
     }
 
     /// Delete a replicaset from xline.
@@ -528,28 +747,37 @@ impl XlineStore {
         .await
     }
 
-    pub async fn compare_and_set_replicaset_yaml(
+    /// Write a replicaset only if it is still at `expected_revision`; returns the new
+    /// revision.
+    pub async fn update_replicaset_if_revision(
         &self,
         rs_name: &str,
-        expected_mod_revision: i64,
         rs_yaml: &str,
-    ) -> Result<bool> {
-        let key = format!("/registry/replicasets/{rs_name}");
-        let cmp = Compare::mod_revision(key.clone(), CompareOp::Equal, expected_mod_revision);
-        let then_ops = vec![TxnOp::put(key.clone(), rs_yaml, None)];
-        let else_ops = vec![TxnOp::get(key, None)];
-        let mut client = self.client.write().await;
-        let txn = Txn::new()
-            .when(vec![cmp])
-            .and_then(then_ops)
-            .or_else(else_ops);
-        let resp = client.txn(txn).await?;
-        Ok(resp.succeeded())
+        expected_revision: i64,
+    ) -> Result<i64> {
+        self.update_yaml_if_revision(&replicaset_key(rs_name), rs_yaml, expected_revision)
+            .await
     }
 
-    /// List all replicaset YAMLs (deserialize values).
+    /// List ReplicaSets across all namespaces. Kept for existing callers; same as
+    /// [`Self::list_all_replicasets`].
     pub async fn list_replicasets(&self) -> Result<Vec<ReplicaSet>> {
-        let key = "/registry/replicasets/".to_string();
+        self.list_all_replicasets().await
+    }
+
+    /// List ReplicaSets across all namespaces.
+    pub async fn list_all_replicasets(&self) -> Result<Vec<ReplicaSet>> {
+        self.list_replicasets_under("/registry/replicasets/").await
+    }
+
+    /// List the ReplicaSets of a single namespace.
+    pub async fn list_replicasets_in_namespace(&self, namespace: &str) -> Result<Vec<ReplicaSet>> {
+        let prefix = replicaset_key(&namespaced_key(namespace, ""));
+        self.list_replicasets_under(&prefix).await
+    }
+
+    async fn list_replicasets_under(&self, prefix: &str) -> Result<Vec<ReplicaSet>> {
+        let key = prefix.to_string();
         let mut client = self.client.write().await;
         let resp = client
             .get(key.clone(), Some(GetOptions::new().with_prefix()))
@@ -588,17 +816,12 @@ impl XlineStore {
         Ok((items, rev))
     }
 
-    /// Create a watch on all replicasets with prefix `/registry/replicasets/`, starting from a given revision.
-    pub async fn watch_replicasets(&self, start_rev: i64) -> Result<(Watcher, WatchStream)> {
-        let key_prefix = "/registry/replicasets/".to_string();
-        let opts = WatchOptions::new()
-            .with_prefix()
-            .with_prev_key()
-            .with_start_revision(start_rev);
-        let mut client = self.client.write().await;
-        let (watcher, stream) = client.watch(key_prefix, Some(opts)).await?;
-        Ok((watcher, stream))
+    /// Watch ReplicaSet changes from `from_revision` on. Keys are `<namespace>/<name>`.
+    pub async fn watch_replicasets(&self, from_revision: i64) -> Result<StoreEventStream> {
+        self.watch_prefix_events("/registry/replicasets/", from_revision)
+            .await
     }
+
     /// Get all deployments as a snapshot with the current revision
     pub async fn deployments_snapshot_with_rev(&self) -> Result<(Vec<(String, String)>, i64)> {
         let prefix = "/registry/deployments/";
@@ -651,6 +874,28 @@ impl XlineStore {
             .map(|kv| String::from_utf8_lossy(kv.value()).to_string()))
     }
 
+    /// Get a deployment YAML definition together with its mod revision.
+    pub async fn get_deployment_yaml_with_revision(
+        &self,
+        deploy_name: &str,
+    ) -> Result<Option<(String, i64)>> {
+        self.get_yaml_with_revision(&format!("/registry/deployments/{deploy_name}"))
+            .await
+    }
+
+    /// Write a deployment only if it is still at `expected_revision`; returns the new
+    /// revision.
+    pub async fn update_deployment_if_revision(
+        &self,
+        deploy_name: &str,
+        deploy_yaml: &str,
+        expected_revision: i64,
+    ) -> Result<i64> {
+        let key = format!("/registry/deployments/{deploy_name}");
+        self.update_yaml_if_revision(&key, deploy_yaml, expected_revision)
+            .await
+    }
+
     /// Get a deployment object from xline.
     pub async fn get_deployment(&self, deploy_name: &str) -> Result<Option<Deployment>> {
         if let Some(yaml) = self.get_deployment_yaml(deploy_name).await? {
@@ -742,6 +987,8 @@ impl XlineStore {
             ResourceKind::ReplicaSet => self.get_replicaset_yaml(name).await,
             ResourceKind::Endpoint => self.get_endpoint_yaml(name).await,
             ResourceKind::Job => self.get_job_yaml(name).await,
+            ResourceKind::DaemonSet => self.get_daemonset_yaml(name).await,
+            ResourceKind::Node => self.get_node_yaml(name).await,
             ResourceKind::Unknown => Ok(None),
         }
     }
@@ -760,6 +1007,8 @@ impl XlineStore {
             ResourceKind::ReplicaSet => self.insert_replicaset_yaml(name, yaml).await,
             ResourceKind::Endpoint => self.insert_endpoint_yaml(name, yaml).await,
             ResourceKind::Job => self.insert_job_yaml(name, yaml).await,
+            ResourceKind::DaemonSet => self.insert_daemonset_yaml(name, yaml).await,
+            ResourceKind::Node => self.insert_node_yaml(name, yaml).await,
             ResourceKind::Unknown => Ok(()),
         }
     }
@@ -771,12 +1020,14 @@ impl XlineStore {
         policy: DeletePropagationPolicy,
     ) -> Result<()> {
         let key = match kind {
-            ResourceKind::Pod => format!("/registry/pods/{name}"),
+            ResourceKind::Pod => pod_key(name),
             ResourceKind::Service => format!("/registry/services/{name}"),
             ResourceKind::Deployment => format!("/registry/deployments/{name}"),
-            ResourceKind::ReplicaSet => format!("/registry/replicasets/{name}"),
+            ResourceKind::ReplicaSet => replicaset_key(name),
             ResourceKind::Endpoint => format!("/registry/endpoints/{name}"),
             ResourceKind::Job => format!("/registry/jobs/{name}"),
+            ResourceKind::DaemonSet => format!("/registry/daemonsets/{name}"),
+            ResourceKind::Node => format!("/registry/nodes/{name}"),
             ResourceKind::Unknown => return Ok(()),
         };
         let yaml = self.get_object_yaml(kind, name).await?;
@@ -843,6 +1094,27 @@ impl XlineStore {
             .map(|kv| String::from_utf8_lossy(kv.value()).to_string()))
     }
 
+    /// Get a Job YAML definition together with its mod revision.
+    pub async fn get_job_yaml_with_revision(
+        &self,
+        job_name: &str,
+    ) -> Result<Option<(String, i64)>> {
+        self.get_yaml_with_revision(&format!("/registry/jobs/{job_name}"))
+            .await
+    }
+
+    /// Write a Job only if it is still at `expected_revision`; returns the new revision.
+    pub async fn update_job_if_revision(
+        &self,
+        job_name: &str,
+        job_yaml: &str,
+        expected_revision: i64,
+    ) -> Result<i64> {
+        let key = format!("/registry/jobs/{job_name}");
+        self.update_yaml_if_revision(&key, job_yaml, expected_revision)
+            .await
+    }
+
     /// Get a Job object from xline.
     pub async fn get_job(&self, job_name: &str) -> Result<Option<common::Job>> {
         match self.get_job_yaml(job_name).await? {
@@ -879,6 +1151,281 @@ impl XlineStore {
         .await
     }
 
+    /// Insert a DaemonSet YAML definition into xline.
+    pub async fn insert_daemonset_yaml(&self, ds_name: &str, ds_yaml: &str) -> Result<()> {
+        let key = format!("/registry/daemonsets/{ds_name}");
+        let mut client = self.client.write().await;
+        client.put(key, ds_yaml, Some(PutOptions::new())).await?;
+        Ok(())
+    }
+
+    /// Get a DaemonSet YAML definition from xline.
+    pub async fn get_daemonset_yaml(&self, ds_name: &str) -> Result<Option<String>> {
+        let key = format!("/registry/daemonsets/{ds_name}");
+        let mut client = self.client.write().await;
+        let resp = client.get(key, None).await?;
+        Ok(resp
+            .kvs()
+            .first()
+            .map(|kv| String::from_utf8_lossy(kv.value()).to_string()))
+    }
+
+    /// Get a DaemonSet YAML definition together with its mod revision.
+    pub async fn get_daemonset_yaml_with_revision(
+        &self,
+        ds_name: &str,
+    ) -> Result<Option<(String, i64)>> {
+        self.get_yaml_with_revision(&format!("/registry/daemonsets/{ds_name}"))
+            .await
+    }
+
+    /// Write a DaemonSet only if it is still at `expected_revision`; returns the new
+    /// revision.
+    pub async fn update_daemonset_if_revision(
+        &self,
+        ds_name: &str,
+        ds_yaml: &str,
+        expected_revision: i64,
+    ) -> Result<i64> {
+        let key = format!("/registry/daemonsets/{ds_name}");
+        self.update_yaml_if_revision(&key, ds_yaml, expected_revision)
+            .await
+    }
+
+    /// List all DaemonSets (deserialize values).
+    pub async fn list_daemonsets(&self) -> Result<Vec<DaemonSet>> {
+        let key = "/registry/daemonsets/".to_string();
+        let mut client = self.client.write().await;
+        let resp = client
+            .get(key.clone(), Some(GetOptions::new().with_prefix()))
+            .await?;
+        let daemonsets: Vec<DaemonSet> = resp
+            .kvs()
+            .iter()
+            .filter_map(|kv| {
+                let yaml_str = String::from_utf8_lossy(kv.value());
+                serde_yaml::from_str::<DaemonSet>(&yaml_str).ok()
+            })
+            .collect();
+        Ok(daemonsets)
+    }
+
+    /// Delete a DaemonSet from xline (Background propagation — GC handles owned Pods).
+    pub async fn delete_daemonset(&self, ds_name: &str) -> Result<()> {
+        self.delete_object(
+            ResourceKind::DaemonSet,
+            ds_name,
+            DeletePropagationPolicy::Background,
+        )
+        .await
+    }
+
+    /// Move pods and ReplicaSets stored under the old un-namespaced layout
+    /// (`/registry/pods/<name>`) to `/registry/pods/<ns>/<name>`, taking the
+    /// namespace from the object's metadata.
+    pub async fn migrate_legacy_namespaced_keys(&self) -> Result<usize> {
+        let mut moved = 0;
+        for prefix in ["/registry/pods/", "/registry/replicasets/"] {
+            for (key, yaml) in self.list_raw(prefix).await? {
+                let name = &key[prefix.len()..];
+                if name.contains('/') {
+                    continue;
+                }
+                let namespace =
+                    serde_yaml::from_str::<serde_yaml::Value>(&yaml)?["metadata"]["namespace"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string();
+                let new_key = format!("{prefix}{}", namespaced_key(&namespace, name));
+                let txn = Txn::new()
+                    .when(vec![Compare::version(new_key.clone(), CompareOp::Equal, 0)])
+                    .and_then(vec![
+                        TxnOp::put(new_key, yaml, None),
+                        TxnOp::delete(key.clone(), None),
+                    ]);
+                if self.client.write().await.txn(txn).await?.succeeded() {
+                    moved += 1;
+                } else {
+                    error!(
+                        "not migrating {key}: a namespaced object with that name already exists"
+                    );
+                }
+            }
+        }
+        Ok(moved)
+    }
+
+    /// Snapshot every key under `prefix`; names are returned with the prefix stripped.
+    pub(crate) async fn snapshot_prefix_with_rev(
+        &self,
+        prefix: &str,
+    ) -> Result<(Vec<(String, String)>, i64)> {
+        let mut client = self.client.write().await;
+        let resp = client
+            .get(prefix, Some(GetOptions::new().with_prefix()))
+            .await?;
+        let rev = resp.header().map(|h| h.revision()).unwrap_or(0);
+        let items = resp
+            .kvs()
+            .iter()
+            .map(|kv| {
+                (
+                    String::from_utf8_lossy(kv.key()).replace(prefix, ""),
+                    String::from_utf8_lossy(kv.value()).to_string(),
+                )
+            })
+            .collect();
+        Ok((items, rev))
+    }
+
+    /// Watch every key under `prefix` from `start_rev`, including previous values.
+    pub(crate) async fn watch_prefix(
+        &self,
+        prefix: &str,
+        start_rev: i64,
+    ) -> Result<(Watcher, WatchStream)> {
+        let opts = WatchOptions::new()
+            .with_prefix()
+            .with_prev_key()
+            .with_start_revision(start_rev);
+        let mut client = self.client.write().await;
+        let (watcher, stream) = client.watch(prefix, Some(opts)).await?;
+        Ok((watcher, stream))
+    }
+
+    /// Watch every key under `prefix` from `from_revision` on as a stream of
+    /// [`StoreEvent`]s. A compacted `from_revision` yields a [`WatchCompacted`] error.
+    pub async fn watch_prefix_events(
+        &self,
+        prefix: &str,
+        from_revision: i64,
+    ) -> Result<StoreEventStream> {
+        let (watcher, watch_stream) = self.watch_prefix(prefix, from_revision).await?;
+        let prefix = prefix.to_string();
+        // the watcher is carried along so the watch stays registered
+        let state = Some((watcher, watch_stream, VecDeque::new()));
+        let events = stream::unfold(state, move |state| {
+            let prefix = prefix.clone();
+            async move {
+                let (watcher, mut watch_stream, mut pending) = state?;
+                loop {
+                    if let Some(event) = pending.pop_front() {
+                        return Some((Ok(event), Some((watcher, watch_stream, pending))));
+                    }
+                    match watch_stream.message().await {
+                        Ok(Some(resp)) if resp.compact_revision() > 0 => {
+                            let err = WatchCompacted {
+                                compact_revision: resp.compact_revision(),
+                            };
+                            return Some((Err(err.into()), None));
+                        }
+                        Ok(Some(resp)) => pending.extend(
+                            resp.events()
+                                .iter()
+                                .filter_map(|ev| store_event_from(&prefix, ev)),
+                        ),
+                        Ok(None) => return None,
+                        Err(e) => return Some((Err(e.into()), None)),
+                    }
+                }
+            }
+        });
+        Ok(Box::pin(events))
+    }
+
+    /// Try to take the lock at `key` for `holder`, bound to a new lease of `ttl_secs`.
+    ///
+    /// Returns the lease id when the lock was free and `None` when it is held by
+    /// someone else. The lock disappears with the lease, so the holder must keep it
+    /// alive with [`XlineStore::lease_keep_alive`].
+    pub async fn try_acquire_lock(
+        &self,
+        key: &str,
+        holder: &str,
+        ttl_secs: i64,
+    ) -> Result<Option<i64>> {
+        let mut client = self.client.write().await;
+        let lease_id = client.lease_grant(ttl_secs, None).await?.id();
+        let cmp = Compare::version(key, CompareOp::Equal, 0);
+        let put_op = TxnOp::put(key, holder, Some(PutOptions::new().with_lease(lease_id)));
+        let txn = Txn::new().when([cmp]).and_then([put_op]);
+
+        let acquired = client.txn(txn).await.map(|resp| resp.succeeded());
+        if !matches!(acquired, Ok(true)) {
+            let _ = client.lease_revoke(lease_id).await;
+        }
+        Ok(acquired?.then_some(lease_id))
+    }
+
+    /// Get the holder written to the lock at `key`, if it is held.
+    pub async fn get_lock_holder(&self, key: &str) -> Result<Option<String>> {
+        let mut client = self.client.write().await;
+        let resp = client.get(key, None).await?;
+        Ok(resp
+            .kvs()
+            .first()
+            .map(|kv| String::from_utf8_lossy(kv.value()).to_string()))
+    }
+
+    /// Open a keep-alive channel for `lease_id`.
+    pub async fn lease_keep_alive(
+        &self,
+        lease_id: i64,
+    ) -> Result<(LeaseKeeper, LeaseKeepAliveStream)> {
+        let mut client = self.client.write().await;
+        Ok(client.lease_keep_alive(lease_id).await?)
+    }
+
+    /// Revoke `lease_id`, deleting every key attached to it.
+    pub async fn revoke_lease(&self, lease_id: i64) -> Result<()> {
+        let mut client = self.client.write().await;
+        client.lease_revoke(lease_id).await?;
+        Ok(())
+    }
+
+    async fn get_yaml_with_revision(&self, key: &str) -> Result<Option<(String, i64)>> {
+        let mut client = self.client.write().await;
+        let resp = client.get(key, None).await?;
+        Ok(resp.kvs().first().map(|kv| {
+            (
+                String::from_utf8_lossy(kv.value()).to_string(),
+                kv.mod_revision(),
+            )
+        }))
+    }
+
+    /// Put `yaml` at `key` if its mod revision is still `expected_revision`, in one
+    /// transaction. On a mismatch the else branch reads the key back so the conflict
+    /// can report the revision it found.
+    async fn update_yaml_if_revision(
+        &self,
+        key: &str,
+        yaml: &str,
+        expected_revision: i64,
+    ) -> Result<i64> {
+        let cmp = Compare::mod_revision(key, CompareOp::Equal, expected_revision);
+        let txn = Txn::new()
+            .when([cmp])
+            .and_then([TxnOp::put(key, yaml, None)])
+            .or_else([TxnOp::get(key, None)]);
+        let mut client = self.client.write().await;
+        let resp = client.txn(txn).await?;
+        if resp.succeeded() {
+            return Ok(resp.header().map(|h| h.revision()).unwrap_or_default());
+        }
+
+        let current_revision = resp.op_responses().into_iter().find_map(|op| match op {
+            TxnOpResponse::Get(get) => get.kvs().first().map(|kv| kv.mod_revision()),
+            _ => None,
+        });
+        Err(RevisionConflict {
+            key: key.to_string(),
+            expected_revision,
+            current_revision,
+        }
+        .into())
+    }
+
     async fn update_meta(
         &self,
         key: &str,
@@ -903,3 +1450,33 @@ impl XlineStore {
         Ok(())
     }
 }
+
+fn store_event_from(prefix: &str, ev: &etcd_client::Event) -> Option<StoreEvent> {
+    let kv = ev.kv()?;
+    let key = String::from_utf8_lossy(kv.key()).replacen(prefix, "", 1);
+    let event = match ev.event_type() {
+        etcd_client::EventType::Put => match ev.prev_kv() {
+            Some(prev_kv) => WatchEvent::Update {
+                old_yaml: String::from_utf8_lossy(prev_kv.value()).to_string(),
+                new_yaml: String::from_utf8_lossy(kv.value()).to_string(),
+            },
+            None => WatchEvent::Add {
+                yaml: String::from_utf8_lossy(kv.value()).to_string(),
+            },
+        },
+        etcd_client::EventType::Delete => match ev.prev_kv() {
+            Some(prev_kv) => WatchEvent::Delete {
+                yaml: String::from_utf8_lossy(prev_kv.value()).to_string(),
+            },
+            None => {
+                warn!("watch delete event missing prev_kv for key {}", key);
+                return None;
+            }
+        },
+    };
+    Some(StoreEvent {
+        key,
+        revision: kv.mod_revision(),
+        event,
+    })
+}
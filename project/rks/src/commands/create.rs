@@ -34,7 +34,11 @@ pub async fn user_create(
     xline_store: &Arc<XlineStore>,
     conn: &Connection,
 ) -> Result<()> {
-    if (xline_store.get_pod_yaml(&pod_task.metadata.name).await?).is_some() {
+    if (xline_store
+        .get_namespaced_pod_yaml(&pod_task.metadata.namespace, &pod_task.metadata.name)
+        .await?)
+        .is_some()
+    {
         error!(
             target: "rks::commands::user_create",
             "Pod {} already exists, creation skipped",
@@ -68,7 +72,11 @@ pub async fn user_create(
     };
 
     xline_store
-        .insert_pod_yaml(&pod_task.metadata.name, &pod_yaml)
+        .insert_namespaced_pod_yaml(
+            &pod_task.metadata.namespace,
+            &pod_task.metadata.name,
+            &pod_yaml,
+        )
         .await?;
 
     info!(
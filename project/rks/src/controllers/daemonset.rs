@@ -0,0 +1,284 @@
+use crate::api::xlinestore::{RevisionConflict, XlineStore};
+use crate::controllers::manager::{Controller, ResourceWatchResponse, WatchEvent};
+use crate::controllers::replicaset::ReplicaSetController;
+use anyhow::Result;
+use async_trait::async_trait;
+use common::{
+    ConditionStatus, DaemonSet, DaemonSetStatus, Node, OwnerReference, PodConditionType, PodTask,
+    ResourceKind,
+};
+use log::{debug, info, warn};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Keeps exactly one pod of each DaemonSet on every node its node selector matches.
+///
+/// Pods are created with `node_name` already set, so they bypass the scheduler.
+/// Pods left on removed or no longer matching nodes are deleted.
+pub struct DaemonSetController {
+    store: Arc<XlineStore>,
+}
+
+impl DaemonSetController {
+    pub fn new(store: Arc<XlineStore>) -> Self {
+        Self { store }
+    }
+
+    /// Whether `node` carries every label in the DaemonSet's node selector.
+    pub fn node_matches(ds: &DaemonSet, node: &Node) -> bool {
+        ds.spec
+            .node_selector
+            .iter()
+            .all(|(k, v)| node.metadata.labels.get(k) == Some(v))
+    }
+
+    fn is_owned_by(ds: &DaemonSet, pod: &PodTask) -> bool {
+        pod.metadata
+            .owner_references
+            .as_ref()
+            .is_some_and(|owners| {
+                owners.iter().any(|owner| {
+                    owner.kind == ResourceKind::DaemonSet && owner.uid == ds.metadata.uid
+                })
+            })
+    }
+
+    fn is_ready(pod: &PodTask) -> bool {
+        pod.status
+            .conditions
+            .as_ref()
+            .and_then(|conds| {
+                conds
+                    .iter()
+                    .find(|c| matches!(c.condition_type, PodConditionType::PodReady))
+            })
+            .is_some_and(|c| matches!(c.status, ConditionStatus::True))
+    }
+
+    /// Reconcile given DaemonSet: create missing pods, delete surplus and misscheduled
+    /// ones, and update status.
+    pub async fn reconcile(&self, ds: &mut DaemonSet) -> Result<()> {
+        let eligible: HashSet<String> = self
+            .store
+            .list_nodes()
+            .await?
+            .into_iter()
+            .filter(|node| Self::node_matches(ds, node))
+            .map(|node| node.metadata.name)
+            .collect();
+
+        let mut pods_by_node: HashMap<String, Vec<PodTask>> = HashMap::new();
+        let mut to_delete = Vec::new();
+        for pod in self.store.list_pods().await? {
+            if !Self::is_owned_by(ds, &pod) {
+                continue;
+            }
+            match pod.spec.node_name.clone() {
+                Some(node) if eligible.contains(&node) => {
+                    pods_by_node.entry(node).or_default().push(pod)
+                }
+                _ => to_delete.push(pod),
+            }
+        }
+        let misscheduled = to_delete.len() as i32;
+
+        let mut ready = 0;
+        for pods in pods_by_node.values_mut() {
+            // keep a ready pod when a node ended up with several
+            pods.sort_by_key(|pod| !Self::is_ready(pod));
+            to_delete.extend(pods.drain(1..));
+            if Self::is_ready(&pods[0]) {
+                ready += 1;
+            }
+        }
+
+        for pod in to_delete {
+            self.store
+                .delete_namespaced_pod(&pod.metadata.namespace, &pod.metadata.name)
+                .await?;
+            info!(
+                "DaemonSet {} deleted pod {} from node {}",
+                ds.metadata.name,
+                pod.metadata.name,
+                pod.spec.node_name.as_deref().unwrap_or("<none>")
+            );
+        }
+
+        for node in eligible
+            .iter()
+            .filter(|node| !pods_by_node.contains_key(*node))
+        {
+            let name = self.create_pod(ds, node).await?;
+            info!(
+                "DaemonSet {} created pod {} on node {}",
+                ds.metadata.name, name, node
+            );
+        }
+
+        ds.status = DaemonSetStatus {
+            desired_number_scheduled: eligible.len() as i32,
+            current_number_scheduled: eligible.len() as i32,
+            number_misscheduled: misscheduled,
+            number_ready: ready,
+        };
+        Ok(())
+    }
+
+    async fn create_pod(&self, ds: &DaemonSet, node: &str) -> Result<String> {
+        let tpl = &ds.spec.template;
+        let mut pod = PodTask {
+            api_version: "v1".to_string(),
+            kind: "Pod".to_string(),
+            metadata: tpl.metadata.clone(),
+            spec: tpl.spec.clone(),
+            status: Default::default(),
+        };
+        let name = ReplicaSetController::generate_unique_name(
+            &ds.metadata.name,
+            &ds.metadata.namespace,
+            self.store.as_ref(),
+        )
+        .await?;
+        pod.metadata.name = name.clone();
+        pod.metadata.namespace = ds.metadata.namespace.clone();
+        pod.metadata.uid = Uuid::new_v4();
+        for (k, v) in ds.spec.selector.match_labels.iter() {
+            pod.metadata.labels.insert(k.clone(), v.clone());
+        }
+        pod.metadata.owner_references = Some(vec![OwnerReference {
+            api_version: ds.api_version.clone(),
+            kind: ResourceKind::DaemonSet,
+            name: ds.metadata.name.clone(),
+            uid: ds.metadata.uid,
+            controller: true,
+            block_owner_deletion: Some(true),
+        }]);
+        pod.spec.node_name = Some(node.to_string());
+
+        let yaml = serde_yaml::to_string(&pod)?;
+        self.store
+            .insert_namespaced_pod_yaml(&ds.metadata.namespace, &name, &yaml)
+            .await?;
+        Ok(name)
+    }
+
+    /// Load a DaemonSet by name, reconcile it and persist its status.
+    pub async fn reconcile_by_name(&self, name: &str) -> Result<()> {
+        let mut attempts = 0;
+        loop {
+            let Some((yaml, revision)) = self.store.get_daemonset_yaml_with_revision(name).await?
+            else {
+                return Ok(());
+            };
+            let mut ds: DaemonSet = serde_yaml::from_str(&yaml)?;
+            if ds.metadata.deletion_timestamp.is_some() {
+                debug!("DaemonSet {} is being deleted, skipping reconcile", name);
+                return Ok(());
+            }
+
+            let old_status = ds.status.clone();
+            self.reconcile(&mut ds).await?;
+            if ds.status == old_status {
+                return Ok(());
+            }
+
+            let new_yaml = serde_yaml::to_string(&ds)?;
+            match self
+                .store
+                .update_daemonset_if_revision(name, &new_yaml, revision)
+                .await
+            {
+                Ok(_) => return Ok(()),
+                Err(e) if e.is::<RevisionConflict>() => {}
+                Err(e) => return Err(e),
+            }
+
+            attempts += 1;
+            if attempts >= 5 {
+                warn!(
+                    "DaemonSetController reconcile_by_name {} failed due to concurrent updates",
+                    name
+                );
+                return Ok(());
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+    }
+
+    async fn reconcile_all(&self) -> Result<()> {
+        for ds in self.store.list_daemonsets().await? {
+            self.reconcile_by_name(&ds.metadata.name).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Controller for DaemonSetController {
+    fn name(&self) -> &'static str {
+        "daemonset"
+    }
+
+    fn watch_resources(&self) -> Vec<ResourceKind> {
+        vec![
+            ResourceKind::DaemonSet,
+            ResourceKind::Node,
+            ResourceKind::Pod,
+        ]
+    }
+
+    async fn handle_watch_response(&mut self, response: &ResourceWatchResponse) -> Result<()> {
+        match response.kind {
+            ResourceKind::DaemonSet => {
+                let should_reconcile = match &response.event {
+                    WatchEvent::Add { .. } => true,
+                    WatchEvent::Update { old_yaml, new_yaml } => {
+                        let old_ds: DaemonSet = serde_yaml::from_str(old_yaml)?;
+                        let new_ds: DaemonSet = serde_yaml::from_str(new_yaml)?;
+                        old_ds.spec != new_ds.spec
+                            || old_ds.metadata.deletion_timestamp
+                                != new_ds.metadata.deletion_timestamp
+                    }
+                    WatchEvent::Delete { .. } => false,
+                };
+                if should_reconcile {
+                    self.reconcile_by_name(&response.key).await?;
+                }
+            }
+            ResourceKind::Node => {
+                // heartbeats update nodes constantly; only membership and labels matter here
+                let should_reconcile = match &response.event {
+                    WatchEvent::Add { .. } | WatchEvent::Delete { .. } => true,
+                    WatchEvent::Update { old_yaml, new_yaml } => {
+                        let old_node: Node = serde_yaml::from_str(old_yaml)?;
+                        let new_node: Node = serde_yaml::from_str(new_yaml)?;
+                        old_node.metadata.labels != new_node.metadata.labels
+                    }
+                };
+                if should_reconcile {
+                    debug!(
+                        "DaemonSetController reconciling all DaemonSets for node {}",
+                        response.key
+                    );
+                    self.reconcile_all().await?;
+                }
+            }
+            ResourceKind::Pod => {
+                // recreate daemon pods that were deleted out from under us
+                if let WatchEvent::Delete { yaml } = &response.event {
+                    let pod: PodTask = serde_yaml::from_str(yaml)?;
+                    let owners = pod.metadata.owner_references.unwrap_or_default();
+                    for owner in owners
+                        .iter()
+                        .filter(|owner| owner.kind == ResourceKind::DaemonSet)
+                    {
+                        self.reconcile_by_name(&owner.name).await?;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
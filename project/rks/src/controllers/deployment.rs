@@ -164,7 +164,7 @@ impl DeploymentController {
 
         let yaml = self
             .store
-            .get_replicaset_yaml(rs_name)
+            .get_namespaced_replicaset_yaml(&rs.metadata.namespace, rs_name)
             .await?
             .ok_or_else(|| anyhow!("ReplicaSet {} not found", rs_name))?;
 
@@ -188,7 +188,7 @@ impl DeploymentController {
 
         let updated_yaml = serde_yaml::to_string(&updated_rs)?;
         self.store
-            .insert_replicaset_yaml(rs_name, &updated_yaml)
+            .insert_namespaced_replicaset_yaml(&rs.metadata.namespace, rs_name, &updated_yaml)
             .await?;
 
         info!(
@@ -298,7 +298,10 @@ impl DeploymentController {
         let template_hash = self.generate_hash(&deployment.spec.template, collision_count);
         let rs_name = format!("{}-{}", deploy_name, template_hash);
 
-        let existing_rs_yaml = self.store.get_replicaset_yaml(&rs_name).await?;
+        let existing_rs_yaml = self
+            .store
+            .get_namespaced_replicaset_yaml(&deployment.metadata.namespace, &rs_name)
+            .await?;
         if let Some(existing_yaml) = existing_rs_yaml {
             let existing_rs: ReplicaSet = serde_yaml::from_str(&existing_yaml)?;
 
@@ -385,7 +388,7 @@ impl DeploymentController {
 
         let rs_yaml = serde_yaml::to_string(&rs)?;
         self.store
-            .insert_replicaset_yaml(&rs_name, &rs_yaml)
+            .insert_namespaced_replicaset_yaml(&deployment.metadata.namespace, &rs_name, &rs_yaml)
             .await?;
 
         // Update Deployment revision
@@ -600,7 +603,7 @@ impl DeploymentController {
 
         let rs_yaml = self
             .store
-            .get_replicaset_yaml(rs_name)
+            .get_namespaced_replicaset_yaml(&rs.metadata.namespace, rs_name)
             .await?
             .ok_or_else(|| anyhow!("ReplicaSet {} not found", rs_name))?;
 
@@ -609,7 +612,7 @@ impl DeploymentController {
 
         let updated_yaml = serde_yaml::to_string(&updated_rs)?;
         self.store
-            .insert_replicaset_yaml(rs_name, &updated_yaml)
+            .insert_namespaced_replicaset_yaml(&rs.metadata.namespace, rs_name, &updated_yaml)
             .await?;
 
         Ok(())
@@ -762,7 +765,9 @@ impl DeploymentController {
                 "Deleting old ReplicaSet {} (revision history cleanup)",
                 rs.metadata.name
             );
-            self.store.delete_replicaset(&rs.metadata.name).await?;
+            self.store
+                .delete_namespaced_replicaset(&rs.metadata.namespace, &rs.metadata.name)
+                .await?;
         }
 
         Ok(())
@@ -865,10 +870,11 @@ impl DeploymentController {
             return Ok(());
         }
 
-        // Update deployment status
-        let yaml = self
+        // Update deployment status. The write is conditional on the revision read here,
+        // so a concurrent spec or status change fails it and the event is retried.
+        let (yaml, revision) = self
             .store
-            .get_deployment_yaml(deploy_name)
+            .get_deployment_yaml_with_revision(deploy_name)
             .await?
             .ok_or_else(|| anyhow!("Deployment {} not found", deploy_name))?;
 
@@ -881,7 +887,7 @@ impl DeploymentController {
 
         let updated_yaml = serde_yaml::to_string(&deploy)?;
         self.store
-            .insert_deployment_yaml(deploy_name, &updated_yaml)
+            .update_deployment_if_revision(deploy_name, &updated_yaml, revision)
             .await?;
 
         info!(
@@ -366,7 +366,10 @@ fn selector_match(sel: &LabelSelector, svc_ns: &str, pod: &PodTask) -> bool {
 
 /// Build an `Endpoint` object for given Service and list of Pods.
 /// Returns `None` when the controller should skip processing (e.g. nil selector).
-fn build_endpoints_from_service_and_pods(svc: &ServiceTask, pods: &[PodTask]) -> Option<Endpoint> {
+pub(crate) fn build_endpoints_from_service_and_pods(
+    svc: &ServiceTask,
+    pods: &[PodTask],
+) -> Option<Endpoint> {
     // nil selector -> skip
     svc.spec.selector.as_ref()?;
 
@@ -207,6 +207,7 @@ impl Controller for GarbageCollector {
             ResourceKind::ReplicaSet,
             ResourceKind::Deployment,
             ResourceKind::Job,
+            ResourceKind::DaemonSet,
         ]
     }
 
@@ -618,7 +619,7 @@ async fn delete_object(
     policy: DeletePropagationPolicy,
 ) -> anyhow::Result<()> {
     xline_store
-        .delete_object(identity.kind, &identity.name, policy)
+        .delete_object(identity.kind, &identity.store_key(), policy)
         .await
 }
 
@@ -627,7 +628,7 @@ async fn get_object_yaml(
     identity: &ObjectReference,
 ) -> anyhow::Result<Option<String>> {
     let yaml = xline_store
-        .get_object_yaml(identity.kind, &identity.name)
+        .get_object_yaml(identity.kind, &identity.store_key())
         .await?;
     Ok(yaml)
 }
@@ -663,7 +664,7 @@ async fn patch_new_owner_references(
     xline_store
         .insert_object_yaml(
             read_guard.identity().kind,
-            &read_guard.identity().name,
+            &read_guard.identity().store_key(),
             &updated_yaml,
         )
         .await?;
@@ -813,7 +814,10 @@ async fn remove_finalizer(
 
     let read_guard = node.read().await;
     let origin_yaml = xline_store
-        .get_object_yaml(read_guard.identity().kind, &read_guard.identity().name)
+        .get_object_yaml(
+            read_guard.identity().kind,
+            &read_guard.identity().store_key(),
+        )
         .await?;
 
     if origin_yaml.is_none() {
@@ -847,7 +851,7 @@ async fn remove_finalizer(
     xline_store
         .insert_object_yaml(
             read_guard.identity().kind,
-            &read_guard.identity().name,
+            &read_guard.identity().store_key(),
             &updated_yaml,
         )
         .await?;
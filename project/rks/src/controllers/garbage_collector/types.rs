@@ -1,3 +1,4 @@
+use crate::api::xlinestore::namespaced_key;
 use common::{ObjectMeta, OwnerReference, ResourceKind};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -47,6 +48,17 @@ impl ObjectReference {
         }
     }
 
+    /// Key of the object in `XlineStore`: `<namespace>/<name>` for namespaced kinds,
+    /// the bare name otherwise.
+    pub fn store_key(&self) -> String {
+        match self.kind {
+            ResourceKind::Pod | ResourceKind::ReplicaSet => {
+                namespaced_key(&self.namespace, &self.name)
+            }
+            _ => self.name.clone(),
+        }
+    }
+
     pub fn from_owner_reference(or: &OwnerReference, namespace: String) -> Self {
         Self {
             kind: or.kind,
@@ -291,6 +291,19 @@ impl JobController {
         Ok(())
     }
 
+    fn is_status_only_update(old_yaml: &str, new_yaml: &str) -> bool {
+        match (
+            serde_yaml::from_str::<Job>(old_yaml),
+            serde_yaml::from_str::<Job>(new_yaml),
+        ) {
+            (Ok(old), Ok(new)) => {
+                old.spec == new.spec
+                    && old.metadata.deletion_timestamp == new.metadata.deletion_timestamp
+            }
+            _ => false,
+        }
+    }
+
     fn is_owned_by_job(&self, pod: &PodTask, job: &Job) -> bool {
         pod.metadata
             .owner_references
@@ -375,7 +388,10 @@ impl JobController {
     async fn terminate_active_pods(&self, owned_pods: &[&PodTask]) -> Result<()> {
         for pod in owned_pods {
             if (pod.status.phase == PodPhase::Running || pod.status.phase == PodPhase::Pending)
-                && let Err(e) = self.store.delete_pod(&pod.metadata.name).await
+                && let Err(e) = self
+                    .store
+                    .delete_namespaced_pod(&pod.metadata.namespace, &pod.metadata.name)
+                    .await
             {
                 warn!(
                     "Failed to delete active pod {} during job termination: {}",
@@ -406,7 +422,9 @@ impl JobController {
 
     /// Create a single Pod for the given Job.
     async fn create_job_pod(&self, job: &Job) -> Result<()> {
-        let pod_name = self.generate_pod_name(&job.metadata.name).await?;
+        let pod_name = self
+            .generate_pod_name(&job.metadata.namespace, &job.metadata.name)
+            .await?;
 
         let mut pod_meta = job.spec.template.metadata.clone();
         pod_meta.name = pod_name.clone();
@@ -437,7 +455,9 @@ impl JobController {
         };
 
         let yaml = serde_yaml::to_string(&pod)?;
-        self.store.insert_pod_yaml(&pod_name, &yaml).await?;
+        self.store
+            .insert_namespaced_pod_yaml(&pod.metadata.namespace, &pod_name, &yaml)
+            .await?;
         info!("Job {} created Pod {}", job.metadata.name, pod_name);
         Ok(())
     }
@@ -452,7 +472,12 @@ impl JobController {
         // Ensure pod name uniqueness in xline.
         let pod_name = loop {
             let name = format!("{}-idx{}-a{}", job_name, idx, a);
-            if self.store.get_pod_yaml(&name).await?.is_none() {
+            if self
+                .store
+                .get_namespaced_pod_yaml(&job.metadata.namespace, &name)
+                .await?
+                .is_none()
+            {
                 break name;
             }
             a += 1;
@@ -507,7 +532,9 @@ impl JobController {
         };
 
         let yaml = serde_yaml::to_string(&pod)?;
-        self.store.insert_pod_yaml(&pod_name, &yaml).await?;
+        self.store
+            .insert_namespaced_pod_yaml(&pod.metadata.namespace, &pod_name, &yaml)
+            .await?;
         info!(
             "Job {} created indexed Pod {} (index={})",
             job.metadata.name, pod_name, idx
@@ -515,11 +542,16 @@ impl JobController {
         Ok(())
     }
 
-    async fn generate_pod_name(&self, job_name: &str) -> Result<String> {
+    async fn generate_pod_name(&self, namespace: &str, job_name: &str) -> Result<String> {
         loop {
             let suffix: u32 = random();
             let name = format!("{}-{:08x}", job_name, suffix);
-            if self.store.get_pod_yaml(&name).await?.is_none() {
+            if self
+                .store
+                .get_namespaced_pod_yaml(namespace, &name)
+                .await?
+                .is_none()
+            {
                 return Ok(name);
             }
             tokio::time::sleep(std::time::Duration::from_millis(10)).await;
@@ -554,8 +586,11 @@ impl Controller for JobController {
 
     async fn handle_watch_response(&mut self, response: &ResourceWatchResponse) -> Result<()> {
         match response.kind {
-            // A Job was added or updated — reconcile it directly.
+            // A Job was added or its spec changed — reconcile it directly. Status-only
+            // updates are our own writes; pod events drive reconciliation from there.
             ResourceKind::Job => match &response.event {
+                WatchEvent::Update { old_yaml, new_yaml }
+                    if Self::is_status_only_update(old_yaml, new_yaml) => {}
                 WatchEvent::Add { yaml } | WatchEvent::Update { new_yaml: yaml, .. } => {
                     match serde_yaml::from_str::<Job>(yaml) {
                         Ok(job) => {
@@ -0,0 +1,151 @@
+//! Leader election between rks instances sharing one xline cluster.
+//!
+//! The leader holds a lock key attached to an xline lease. It renews the lease while
+//! it runs; when it stops or crashes the lease expires, the key disappears, and one of
+//! the standbys polling the lock takes it over.
+
+use crate::api::xlinestore::XlineStore;
+use anyhow::{Result, anyhow};
+use etcd_client::{LeaseKeepAliveStream, LeaseKeeper};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::{Instant, sleep, timeout};
+
+/// Lock key guarding the controller manager.
+pub const CONTROLLER_MANAGER_LOCK: &str = "/registry/leases/controller-manager";
+
+/// Settings of a leader election.
+#[derive(Debug, Clone)]
+pub struct LeaderElectionConfig {
+    /// Key whose holder is the leader.
+    pub lock_key: String,
+    /// Value written to the lock, identifying this instance.
+    pub identity: String,
+    /// TTL of the lease behind the lock; a crashed leader is replaced within it.
+    pub lease_ttl: Duration,
+    /// How often a standby retries taking the lock.
+    pub retry_period: Duration,
+}
+
+impl Default for LeaderElectionConfig {
+    fn default() -> Self {
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "rks".to_string());
+        Self {
+            lock_key: CONTROLLER_MANAGER_LOCK.to_string(),
+            identity: format!("{host}-{}", uuid::Uuid::new_v4()),
+            lease_ttl: Duration::from_secs(15),
+            retry_period: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Leadership held by this instance.
+pub struct LeaderLease {
+    store: Arc<XlineStore>,
+    config: LeaderElectionConfig,
+    lease_id: i64,
+}
+
+/// Polls the lock until this instance takes it.
+pub async fn acquire(store: Arc<XlineStore>, config: LeaderElectionConfig) -> LeaderLease {
+    let ttl_secs = config.lease_ttl.as_secs().max(1) as i64;
+    loop {
+        match store
+            .try_acquire_lock(&config.lock_key, &config.identity, ttl_secs)
+            .await
+        {
+            Ok(Some(lease_id)) => {
+                log::info!(
+                    "{} acquired leadership of {}",
+                    config.identity,
+                    config.lock_key
+                );
+                return LeaderLease {
+                    store,
+                    config,
+                    lease_id,
+                };
+            }
+            Ok(None) => log::debug!("{} is held by another instance", config.lock_key),
+            Err(e) => log::warn!("failed to try lock {}: {:?}", config.lock_key, e),
+        }
+        sleep(config.retry_period).await;
+    }
+}
+
+impl LeaderLease {
+    /// Renews the lease until leadership is lost or `stop` fires.
+    ///
+    /// Returns `Ok(())` after a stop, revoking the lease so a standby takes over right
+    /// away. Returns an error once leadership is lost: the lock changed hands, the
+    /// lease expired, or it could not be renewed for two thirds of its TTL. Giving up
+    /// before the TTL runs out keeps two leaders from running at once.
+    pub async fn hold(self, mut stop: watch::Receiver<bool>) -> Result<()> {
+        let renew_period = self.config.lease_ttl / 3;
+        let renew_deadline = renew_period * 2;
+        let mut last_renewed = Instant::now();
+        let mut keep_alive = None;
+
+        loop {
+            if *stop.borrow() {
+                break;
+            }
+            tokio::select! {
+                changed = stop.changed() => {
+                    if changed.is_err() || *stop.borrow() {
+                        break;
+                    }
+                    continue;
+                }
+                _ = sleep(renew_period) => {}
+            }
+
+            match self.renew(&mut keep_alive, renew_period).await {
+                Ok(()) => last_renewed = Instant::now(),
+                Err(e) if e.is::<LeadershipLost>() => return Err(e),
+                Err(e) => {
+                    keep_alive = None;
+                    if last_renewed.elapsed() >= renew_deadline {
+                        return Err(e.context(LeadershipLost("lease renewal timed out")));
+                    }
+                    log::warn!("failed to renew {}: {:?}", self.config.lock_key, e);
+                }
+            }
+        }
+
+        if let Err(e) = self.store.revoke_lease(self.lease_id).await {
+            log::warn!("failed to release {}: {:?}", self.config.lock_key, e);
+        }
+        Ok(())
+    }
+
+    async fn renew(
+        &self,
+        keep_alive: &mut Option<(LeaseKeeper, LeaseKeepAliveStream)>,
+        wait: Duration,
+    ) -> Result<()> {
+        if keep_alive.is_none() {
+            *keep_alive = Some(self.store.lease_keep_alive(self.lease_id).await?);
+        }
+        let (keeper, responses) = keep_alive.as_mut().unwrap();
+        keeper.keep_alive().await?;
+        match timeout(wait, responses.message()).await {
+            Ok(Ok(Some(resp))) if resp.ttl() > 0 => {}
+            Ok(Ok(_)) => return Err(LeadershipLost("lease expired").into()),
+            Ok(Err(e)) => return Err(e.into()),
+            Err(_) => return Err(anyhow!("no keep-alive response within {:?}", wait)),
+        }
+
+        let holder = self.store.get_lock_holder(&self.config.lock_key).await?;
+        if holder.as_deref() != Some(self.config.identity.as_str()) {
+            return Err(LeadershipLost("lock taken over").into());
+        }
+        Ok(())
+    }
+}
+
+/// Leadership is gone for good; the caller must stop acting as leader.
+#[derive(Debug, thiserror::Error)]
+#[error("leadership lost: {0}")]
+pub struct LeadershipLost(&'static str);
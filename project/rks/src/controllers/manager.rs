@@ -1,26 +1,22 @@
-use crate::api::xlinestore::XlineStore;
+pub use crate::api::xlinestore::WatchEvent;
+use crate::api::xlinestore::{WatchCompacted, XlineStore};
+use crate::controllers::leader_election::{self, LeaderElectionConfig, LeaderLease};
 use anyhow::Result;
 use async_trait::async_trait;
 use common::ResourceKind;
+use futures::StreamExt;
 use once_cell::sync::Lazy;
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::sync::{RwLock, mpsc, watch};
+use tokio::task::JoinHandle;
 use tokio::time::sleep;
 
 pub static CONTROLLER_MANAGER: Lazy<Arc<ControllerManager>> =
     Lazy::new(|| Arc::new(ControllerManager::new()));
 
-/// A watch event.
-/// Contains the resource yaml.
-#[derive(Debug, Clone)]
-pub enum WatchEvent {
-    Add { yaml: String },
-    Update { old_yaml: String, new_yaml: String },
-    Delete { yaml: String },
-}
-
 /// A watch response.
 /// Contains the resource kind, key, and event.
 #[derive(Debug, Clone)]
@@ -185,6 +181,16 @@ pub trait Controller: Send + Sync + 'static {
     async fn handle_watch_response(&mut self, response: &ResourceWatchResponse) -> Result<()> {
         Ok(())
     }
+
+    /// Stops the controller, called once when its manager shuts down.
+    ///
+    /// Override this to stop background tasks started by the controller, so they do not
+    /// keep acting after the manager stopped (for example after losing leadership).
+    ///
+    /// # Default Implementation
+    ///
+    /// The default implementation does nothing.
+    async fn shutdown(&mut self) {}
 }
 
 /// ControllerManager manages the lifecycle and event distribution of multiple controllers.
@@ -200,7 +206,8 @@ pub trait Controller: Send + Sync + 'static {
 ///
 /// 1. **Create manager**: Use the global singleton `CONTROLLER_MANAGER`
 /// 2. **Register controllers**: Call `register` to register each controller
-/// 3. **Start watching**: Call `start_watch` to begin watching resource changes
+/// 3. **Start watching**: Call `start_watch` to begin watching resource changes, or
+///    `run_with_leader_election` when several rks instances share one xline
 /// 4. **Event processing**: The manager automatically distributes events to corresponding controller queues, controllers process asynchronously
 /// 5. **Shutdown**: Call `shutdown` to gracefully shut down all controllers
 ///
@@ -235,6 +242,10 @@ pub struct ControllerManager {
     inflight: RwLock<HashMap<String, HashSet<String>>>,
     // use for stopping the manager.
     stop_tx: watch::Sender<bool>,
+    // informer tasks spawned by `start_watch`, aborted on shutdown.
+    informers: Mutex<Vec<JoinHandle<()>>>,
+    // whether this manager currently holds the controller manager lock.
+    leader: AtomicBool,
 }
 
 impl ControllerManager {
@@ -255,6 +266,8 @@ impl ControllerManager {
             queues: RwLock::new(HashMap::new()),
             inflight: RwLock::new(HashMap::new()),
             stop_tx,
+            informers: Mutex::new(Vec::new()),
+            leader: AtomicBool::new(false),
         }
     }
 
@@ -394,6 +407,7 @@ impl ControllerManager {
                     }
                 }
             }
+            controller_clone.write().await.shutdown().await;
         });
 
         Ok(())
@@ -418,6 +432,10 @@ impl ControllerManager {
     ///
     /// # Auto-reconnect
     ///
+    /// Pod, ReplicaSet and the other prefix informers resume from the revision of the last
+    /// event they delivered, so a reconnect neither drops nor replays events; a snapshot is
+    /// only taken again when that revision has been compacted.
+    ///
     /// If the watch connection is lost or errors occur, it will automatically reconnect using an exponential backoff strategy:
     /// - Initial delay: 100ms
     /// - Maximum delay: 30s
@@ -444,137 +462,16 @@ impl ControllerManager {
     /// # Notes
     ///
     /// - Must be called after registering all controllers
-    /// - This method spawns two background tasks (watching Pods and ReplicaSets respectively) and does not block
+    /// - This method spawns one background task per watched resource kind and does not block
     /// - Watching will continue until the program exits or `shutdown` is called
     pub async fn start_watch(self: Arc<Self>, store: Arc<XlineStore>) -> Result<()> {
-        // pods informer with reconnect loop
-        let mgr_p = self.clone();
-        let store_p = store.clone();
-
-        tokio::spawn(async move {
-            let mut backoff_ms = 100u64;
-            loop {
-                match store_p.pods_snapshot_with_rev().await {
-                    Ok((items, rev)) => {
-                        // broadcast snapshot items to controllers who need to watch pods.
-                        for (name, _yaml) in items.into_iter() {
-                            let senders = mgr_p.get_senders_by_kind(ResourceKind::Pod).await;
-                            for sender in senders {
-                                let _ = sender
-                                    .send(ResourceWatchResponse {
-                                        kind: ResourceKind::Pod,
-                                        key: name.clone(),
-                                        event: WatchEvent::Add {
-                                            yaml: _yaml.clone(),
-                                        },
-                                    })
-                                    .await;
-                            }
-                        }
-
-                        // start watch from snapshot revision and broadcast events to controllers who need to watch pods.
-                        // start watch from rev+1 to avoid re-emitting snapshot items as watch events
-                        match store_p.watch_pods(rev + 1).await {
-                            Ok((_watcher, mut stream)) => {
-                                // reset backoff on successful watch
-                                backoff_ms = 100;
-                                loop {
-                                    match stream.message().await {
-                                        Ok(Some(resp)) => {
-                                            for ev in resp.events() {
-                                                if let Some(kv) = ev.kv() {
-                                                    let key = String::from_utf8_lossy(kv.key())
-                                                        .replace("/registry/pods/", "");
-                                                    let event_opt = match ev.event_type() {
-                                                        etcd_client::EventType::Put => {
-                                                            if let Some(prev_kv) = ev.prev_kv() {
-                                                                Some(WatchEvent::Update {
-                                                                    old_yaml:
-                                                                        String::from_utf8_lossy(
-                                                                            prev_kv.value(),
-                                                                        )
-                                                                        .to_string(),
-                                                                    new_yaml:
-                                                                        String::from_utf8_lossy(
-                                                                            kv.value(),
-                                                                        )
-                                                                        .to_string(),
-                                                                })
-                                                            } else {
-                                                                Some(WatchEvent::Add {
-                                                                    yaml: String::from_utf8_lossy(
-                                                                        kv.value(),
-                                                                    )
-                                                                    .to_string(),
-                                                                })
-                                                            }
-                                                        }
-                                                        etcd_client::EventType::Delete => {
-                                                            if let Some(prev_kv) = ev.prev_kv() {
-                                                                Some(WatchEvent::Delete {
-                                                                    yaml: String::from_utf8_lossy(
-                                                                        prev_kv.value(),
-                                                                    )
-                                                                    .to_string(),
-                                                                })
-                                                            } else {
-                                                                log::warn!(
-                                                                    "watch delete event missing prev_kv for key {}",
-                                                                    key
-                                                                );
-                                                                None
-                                                            }
-                                                        }
-                                                    };
-                                                    let Some(event) = event_opt else {
-                                                        continue;
-                                                    };
-                                                    let senders = mgr_p
-                                                        .get_senders_by_kind(ResourceKind::Pod)
-                                                        .await;
-                                                    for sender in senders {
-                                                        let _ = sender
-                                                            .send(ResourceWatchResponse {
-                                                                kind: ResourceKind::Pod,
-                                                                key: key.clone(),
-                                                                event: event.clone(),
-                                                            })
-                                                            .await;
-                                                    }
-                                                }
-                                            }
-                                        }
-                                        Ok(None) => {
-                                            log::info!("pod watch stream closed, will reconnect");
-                                            break;
-                                        }
-                                        Err(e) => {
-                                            log::error!("pod watch error: {:?}, will reconnect", e);
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                log::error!("failed to start pod watch: {:?}", e);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        log::error!("failed to snapshot pods: {:?}", e);
-                    }
-                }
-
-                // backoff before retry
-                sleep(Duration::from_millis(backoff_ms)).await;
-                backoff_ms = (backoff_ms * 2).min(30_000);
-            }
-        });
+        self.clone()
+            .spawn_prefix_informer(store.clone(), ResourceKind::Pod, "/registry/pods/");
 
         // services informer with reconnect loop
         let mgr_s = self.clone();
         let store_s = store.clone();
-        tokio::spawn(async move {
+        self.track_informer(tokio::spawn(async move {
             let mut backoff_ms = 100u64;
             loop {
                 match store_s.services_snapshot_with_rev().await {
@@ -692,12 +589,12 @@ impl ControllerManager {
                 sleep(Duration::from_millis(backoff_ms)).await;
                 backoff_ms = (backoff_ms * 2).min(30_000);
             }
-        });
+        }));
 
         // endpoints informer with reconnect loop
         let mgr_ep = self.clone();
         let store_ep = store.clone();
-        tokio::spawn(async move {
+        self.track_informer(tokio::spawn(async move {
             let mut backoff_ms = 100u64;
             loop {
                 match store_ep.endpoints_snapshot_with_rev().await {
@@ -812,141 +709,17 @@ impl ControllerManager {
                 sleep(Duration::from_millis(backoff_ms)).await;
                 backoff_ms = (backoff_ms * 2).min(30_000);
             }
-        });
-
-        // replicasets informer with reconnect loop (use snapshot_with_rev to obtain a starting revision)
-        let mgr_rs = self.clone();
-        let store_rs = store.clone();
-        tokio::spawn(async move {
-            let mut backoff_ms = 100u64;
-            loop {
-                match store_rs.replicasets_snapshot_with_rev().await {
-                    Ok((items, rev)) => {
-                        for (name, _yaml) in items.into_iter() {
-                            // broadcast snapshot items to controllers who need to watch replicasets.
-                            let senders =
-                                mgr_rs.get_senders_by_kind(ResourceKind::ReplicaSet).await;
-                            for sender in senders {
-                                let _ = sender
-                                    .send(ResourceWatchResponse {
-                                        kind: ResourceKind::ReplicaSet,
-                                        key: name.clone(),
-                                        event: WatchEvent::Add {
-                                            yaml: _yaml.clone(),
-                                        },
-                                    })
-                                    .await;
-                            }
-                        }
-
-                        // start watch from snapshot revision and broadcast events to controllers who need to watch replicasets.
-                        // rev+1 to skip snapshot duplication
-                        match store_rs.watch_replicasets(rev + 1).await {
-                            Ok((_watcher, mut stream)) => {
-                                backoff_ms = 100;
-                                loop {
-                                    match stream.message().await {
-                                        Ok(Some(resp)) => {
-                                            for ev in resp.events() {
-                                                if let Some(kv) = ev.kv() {
-                                                    let key = String::from_utf8_lossy(kv.key())
-                                                        .replace("/registry/replicasets/", "");
-                                                    let event_opt = match ev.event_type() {
-                                                        etcd_client::EventType::Put => {
-                                                            if let Some(prev_kv) = ev.prev_kv() {
-                                                                Some(WatchEvent::Update {
-                                                                    old_yaml:
-                                                                        String::from_utf8_lossy(
-                                                                            prev_kv.value(),
-                                                                        )
-                                                                        .to_string(),
-                                                                    new_yaml:
-                                                                        String::from_utf8_lossy(
-                                                                            kv.value(),
-                                                                        )
-                                                                        .to_string(),
-                                                                })
-                                                            } else {
-                                                                Some(WatchEvent::Add {
-                                                                    yaml: String::from_utf8_lossy(
-                                                                        kv.value(),
-                                                                    )
-                                                                    .to_string(),
-                                                                })
-                                                            }
-                                                        }
-                                                        etcd_client::EventType::Delete => {
-                                                            if let Some(prev_kv) = ev.prev_kv() {
-                                                                Some(WatchEvent::Delete {
-                                                                    yaml: String::from_utf8_lossy(
-                                                                        prev_kv.value(),
-                                                                    )
-                                                                    .to_string(),
-                                                                })
-                                                            } else {
-                                                                log::warn!(
-                                                                    "watch delete event missing prev_kv for key {}",
-                                                                    key
-                                                                );
-                                                                None
-                                                            }
-                                                        }
-                                                    };
-                                                    let Some(event) = event_opt else {
-                                                        continue;
-                                                    };
-                                                    let senders = mgr_rs
-                                                        .get_senders_by_kind(
-                                                            ResourceKind::ReplicaSet,
-                                                        )
-                                                        .await;
-                                                    for sender in senders {
-                                                        let _ = sender
-                                                            .send(ResourceWatchResponse {
-                                                                kind: ResourceKind::ReplicaSet,
-                                                                key: key.clone(),
-                                                                event: event.clone(),
-                                                            })
-                                                            .await;
-                                                    }
-                                                }
-                                            }
-                                        }
-                                        Ok(None) => {
-                                            log::info!(
-                                                "replicaset watch stream closed, will reconnect"
-                                            );
-                                            break;
-                                        }
-                                        Err(e) => {
-                                            log::error!(
-                                                "replicaset watch error: {:?}, will reconnect",
-                                                e
-                                            );
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                log::error!("failed to start replicaset watch: {:?}", e);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        log::error!("failed to snapshot replicasets: {:?}", e);
-                    }
-                }
+        }));
 
-                // backoff before retry
-                sleep(Duration::from_millis(backoff_ms)).await;
-                backoff_ms = (backoff_ms * 2).min(30_000);
-            }
-        });
+        self.clone().spawn_prefix_informer(
+            store.clone(),
+            ResourceKind::ReplicaSet,
+            "/registry/replicasets/",
+        );
         // deployments informer with reconnect loop
         let mgr_deploy = self.clone();
         let store_deploy = store.clone();
-        tokio::spawn(async move {
+        self.track_informer(tokio::spawn(async move {
             let mut backoff_ms = 100u64;
             loop {
                 match store_deploy.deployments_snapshot_with_rev().await {
@@ -1068,16 +841,125 @@ impl ControllerManager {
                 sleep(Duration::from_millis(backoff_ms)).await;
                 backoff_ms = (backoff_ms * 2).min(30_000);
             }
-        });
+        }));
+
+        self.clone()
+            .spawn_prefix_informer(store.clone(), ResourceKind::Job, "/registry/jobs/");
+        self.clone().spawn_prefix_informer(
+            store.clone(),
+            ResourceKind::DaemonSet,
+            "/registry/daemonsets/",
+        );
+        self.clone()
+            .spawn_prefix_informer(store, ResourceKind::Node, "/registry/nodes/");
         Ok(())
     }
 
+    /// Spawns an informer for every key under `prefix`, broadcasting its events as `kind`.
+    ///
+    /// The initial snapshot is sent as `Add` events and the watch starts at the
+    /// following revision. The revision of the last delivered event is kept as a
+    /// resume token: after a disconnect the watch picks up right after it instead of
+    /// replaying a snapshot. Only a compacted revision forces a new snapshot.
+    /// Reconnects use exponential backoff.
+    fn spawn_prefix_informer(
+        self: Arc<Self>,
+        store: Arc<XlineStore>,
+        kind: ResourceKind,
+        prefix: &'static str,
+    ) {
+        let manager = self.clone();
+        let informer = tokio::spawn(async move {
+            let mut backoff_ms = 100u64;
+            let mut resume_rev: Option<i64> = None;
+            loop {
+                let rev = match resume_rev {
+                    Some(rev) => rev,
+                    None => match store.snapshot_prefix_with_rev(prefix).await {
+                        Ok((items, rev)) => {
+                            for (name, yaml) in items {
+                                self.broadcast(kind, &name, WatchEvent::Add { yaml }).await;
+                            }
+                            resume_rev = Some(rev);
+                            rev
+                        }
+                        Err(e) => {
+                            log::error!("failed to snapshot {} resources: {:?}", kind, e);
+                            sleep(Duration::from_millis(backoff_ms)).await;
+                            backoff_ms = (backoff_ms * 2).min(30_000);
+                            continue;
+                        }
+                    },
+                };
+
+                match store.watch_prefix_events(prefix, rev + 1).await {
+                    Ok(mut events) => {
+                        backoff_ms = 100;
+                        loop {
+                            match events.next().await {
+                                Some(Ok(ev)) => {
+                                    resume_rev = Some(ev.revision);
+                                    self.broadcast(kind, &ev.key, ev.event).await;
+                                }
+                                Some(Err(e)) if e.is::<WatchCompacted>() => {
+                                    log::warn!("{} watch {}, taking a new snapshot", kind, e);
+                                    resume_rev = None;
+                                    break;
+                                }
+                                Some(Err(e)) => {
+                                    log::error!(
+                                        "{} watch error: {:?}, will resume after revision {}",
+                                        kind,
+                                        e,
+                                        resume_rev.unwrap_or(rev)
+                                    );
+                                    break;
+                                }
+                                None => {
+                                    log::info!("{} watch stream closed, will reconnect", kind);
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        log::error!("failed to start {} watch: {:?}", kind, e);
+                    }
+                }
+                sleep(Duration::from_millis(backoff_ms)).await;
+                backoff_ms = (backoff_ms * 2).min(30_000);
+            }
+        });
+        manager.track_informer(informer);
+    }
+
+    fn track_informer(&self, informer: JoinHandle<()>) {
+        if *self.stop_tx.borrow() {
+            informer.abort();
+            return;
+        }
+        self.informers.lock().unwrap().push(informer);
+    }
+
+    async fn broadcast(&self, kind: ResourceKind, key: &str, event: WatchEvent) {
+        for sender in self.get_senders_by_kind(kind).await {
+            let _ = sender
+                .send(ResourceWatchResponse {
+                    kind,
+                    key: key.to_string(),
+                    event: event.clone(),
+                })
+                .await;
+        }
+    }
+
     /// Gracefully shuts down the ControllerManager, stopping all controller processing loops.
     ///
     /// After calling this method:
     /// - All controller dispatcher tasks will receive a stop signal and exit
     /// - Events currently being processed will complete, but new events won't be processed
-    /// - Watch tasks will continue running, but events won't be distributed
+    /// - Informer tasks started by `start_watch` are aborted
+    /// - Leadership taken by `run_with_leader_election` is released
     ///
     /// # Example
     ///
@@ -1091,7 +973,92 @@ impl ControllerManager {
     /// - This method is idempotent and can be safely called multiple times
     /// - This method will also be automatically called if the manager is dropped
     pub fn shutdown(&self) {
-        let _ = self.stop_tx.send(true);
+        self.stop_tx.send_replace(true);
+        for informer in self.informers.lock().unwrap().drain(..) {
+            informer.abort();
+        }
+    }
+
+    /// Waits until this manager holds the controller manager lock in xline, then
+    /// starts watching like `start_watch`.
+    ///
+    /// Use this instead of `start_watch` when several rks instances share one xline:
+    /// only the elected one runs its controllers, the others block here as standbys
+    /// and take over once the leader's lease expires.
+    ///
+    /// The lease is renewed in the background. If leadership is lost mid-run (the
+    /// lease cannot be renewed in time or the lock is taken over), the manager shuts
+    /// down so its controllers stop acting; a new manager is needed to campaign again.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` once this instance is leader and watching has started, or
+    /// right away if `shutdown` is called while standing by.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let manager = CONTROLLER_MANAGER.clone();
+    /// manager.clone().register(my_controller, 10).await?;
+    ///
+    /// // blocks while another rks instance is leader
+    /// manager.run_with_leader_election(store).await?;
+    /// ```
+    pub async fn run_with_leader_election(self: Arc<Self>, store: Arc<XlineStore>) -> Result<()> {
+        self.run_with_leader_election_config(store, LeaderElectionConfig::default())
+            .await
+    }
+
+    /// `run_with_leader_election` with an explicit lock key, identity and lease TTL.
+    pub async fn run_with_leader_election_config(
+        self: Arc<Self>,
+        store: Arc<XlineStore>,
+        config: LeaderElectionConfig,
+    ) -> Result<()> {
+        let mut stop = self.stop_tx.subscribe();
+        if *stop.borrow() {
+            return Ok(());
+        }
+        let lease = tokio::select! {
+            lease = leader_election::acquire(store.clone(), config) => lease,
+            _ = stop.changed() => return Ok(()),
+        };
+        self.run_as_leader(store, lease).await
+    }
+
+    /// Starts watching like `start_watch` on behalf of an already acquired `lease`.
+    ///
+    /// The lease is renewed in the background; once leadership is lost the manager
+    /// shuts down. Use `stopped` to wait for that and campaign again with a new manager.
+    pub async fn run_as_leader(
+        self: Arc<Self>,
+        store: Arc<XlineStore>,
+        lease: LeaderLease,
+    ) -> Result<()> {
+        self.leader.store(true, Ordering::SeqCst);
+
+        let manager = self.clone();
+        let stop = self.stop_tx.subscribe();
+        tokio::spawn(async move {
+            if let Err(e) = lease.hold(stop).await {
+                log::error!("controller manager {:#}, stopping controllers", e);
+                manager.shutdown();
+            }
+            manager.leader.store(false, Ordering::SeqCst);
+        });
+
+        self.start_watch(store).await
+    }
+
+    /// Waits until the manager has been shut down, e.g. after losing leadership.
+    pub async fn stopped(&self) {
+        let mut stop = self.stop_tx.subscribe();
+        let _ = stop.wait_for(|stopped| *stopped).await;
+    }
+
+    /// Returns whether this manager is the elected leader.
+    pub fn is_leader(&self) -> bool {
+        self.leader.load(Ordering::SeqCst)
     }
 
     /// Gets all queue senders for controllers that need to watch the specified resource kind.
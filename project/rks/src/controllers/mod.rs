@@ -1,5 +1,7 @@
+pub mod daemonset;
 pub mod deployment;
 pub mod replicaset;
+pub use daemonset::DaemonSetController;
 pub use deployment::DeploymentController;
 pub use replicaset::ReplicaSetController;
 pub mod manager;
@@ -11,7 +13,10 @@ pub use manager::ControllerManager;
 pub mod endpoint_controller;
 pub mod garbage_collector;
 pub mod job;
+pub mod leader_election;
 pub mod nftrules_controller;
+pub mod node_controller;
 
 pub use job::JobController;
 pub use nftrules_controller::NftablesController;
+pub use node_controller::NodeController;
@@ -0,0 +1,179 @@
+//! Node lifecycle: marks nodes whose heartbeats stop as NotReady and evicts their pods.
+//!
+//! Every Node event re-arms a timer for that node at `lastHeartbeatTime` plus the
+//! grace period. If the timer fires before the next heartbeat lands, the node's Ready
+//! condition becomes Unknown, which also taints it `NoExecute`. Pods bound to a node
+//! that stops being Ready are deleted unless they tolerate that taint, so their owners
+//! recreate them and the scheduler places the replacements on healthy nodes.
+
+use crate::api::xlinestore::{RevisionConflict, XlineStore};
+use crate::controllers::manager::{Controller, ResourceWatchResponse, WatchEvent};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use common::{ConditionStatus, Node, ResourceKind, Taint, TaintEffect, TaintKey};
+use log::{info, warn};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// How long a node may go without a heartbeat before it is marked NotReady.
+pub const DEFAULT_NODE_MONITOR_GRACE_PERIOD: Duration = Duration::from_secs(50);
+
+pub struct NodeController {
+    store: Arc<XlineStore>,
+    node_monitor_grace_period: Duration,
+    // heartbeat deadline of every Ready node, keyed by node name
+    timers: HashMap<String, JoinHandle<()>>,
+    // set on shutdown, so events still in flight do not arm new timers
+    stopped: bool,
+}
+
+impl NodeController {
+    pub fn new(store: Arc<XlineStore>, node_monitor_grace_period: Duration) -> Self {
+        Self {
+            store,
+            node_monitor_grace_period,
+            timers: HashMap::new(),
+            stopped: false,
+        }
+    }
+
+    fn is_ready(node: &Node) -> bool {
+        node.ready_condition()
+            .is_some_and(|cond| matches!(cond.status, ConditionStatus::True))
+    }
+
+    /// Arm the heartbeat deadline of a Ready node, replacing its previous one.
+    fn arm_timer(&mut self, node: &Node) {
+        if self.stopped {
+            return;
+        }
+        let grace = self.node_monitor_grace_period;
+        // a node that never reported a heartbeat is already overdue
+        let delay = node
+            .ready_condition()
+            .and_then(|cond| cond.heartbeat_age(Utc::now()))
+            .map(|age| grace.saturating_sub(age))
+            .unwrap_or_default();
+
+        let store = self.store.clone();
+        let name = node.metadata.name.clone();
+        let timer = tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            if let Err(e) = mark_not_ready_if_expired(&store, &name, grace).await {
+                warn!("failed to check heartbeat of Node {}: {:?}", name, e);
+            }
+        });
+        if let Some(old) = self.timers.insert(node.metadata.name.clone(), timer) {
+            old.abort();
+        }
+    }
+
+    fn cancel_timer(&mut self, node_name: &str) {
+        if let Some(timer) = self.timers.remove(node_name) {
+            timer.abort();
+        }
+    }
+
+    /// Delete the pods bound to `node_name` that do not tolerate the NotReady taint.
+    async fn evict_pods(&self, node_name: &str) -> Result<()> {
+        let taint = Taint::new(TaintKey::NodeNotReady, TaintEffect::NoExecute);
+        for pod in self.store.list_all_pods().await? {
+            if pod.spec.node_name.as_deref() != Some(node_name)
+                || pod.spec.tolerations.iter().any(|tol| tol.tolerate(&taint))
+            {
+                continue;
+            }
+            info!("Evicting pod {} from node {}", pod.metadata.name, node_name);
+            self.store
+                .delete_namespaced_pod(&pod.metadata.namespace, &pod.metadata.name)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Set a node's Ready condition to Unknown if its last heartbeat is older than
+/// `grace`. The write is revision-checked, so a heartbeat landing meanwhile wins and
+/// its update event re-arms the timer.
+async fn mark_not_ready_if_expired(
+    store: &XlineStore,
+    node_name: &str,
+    grace: Duration,
+) -> Result<()> {
+    let Some((yaml, revision)) = store.get_node_yaml_with_revision(node_name).await? else {
+        return Ok(());
+    };
+    let mut node: Node = serde_yaml::from_str(&yaml)?;
+    if !node.update_ready_status_on_timeout(grace) {
+        return Ok(());
+    }
+    node.spec.taints = Node::derive_taints_from_conditions(&node.status.conditions);
+
+    let yaml = serde_yaml::to_string(&node)?;
+    match store
+        .update_node_if_revision(node_name, &yaml, revision)
+        .await
+    {
+        Ok(_) => {
+            warn!("Node {node_name} missed heartbeats for {grace:?}, marked Ready=Unknown");
+            Ok(())
+        }
+        Err(e) if e.is::<RevisionConflict>() => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+#[async_trait]
+impl Controller for NodeController {
+    fn name(&self) -> &'static str {
+        "node-controller"
+    }
+
+    fn watch_resources(&self) -> Vec<ResourceKind> {
+        vec![ResourceKind::Node]
+    }
+
+    async fn handle_watch_response(&mut self, response: &ResourceWatchResponse) -> Result<()> {
+        match &response.event {
+            WatchEvent::Add { yaml } => {
+                let node: Node = serde_yaml::from_str(yaml)?;
+                if Self::is_ready(&node) {
+                    self.arm_timer(&node);
+                } else {
+                    self.cancel_timer(&node.metadata.name);
+                    self.evict_pods(&node.metadata.name).await?;
+                }
+            }
+            WatchEvent::Update { old_yaml, new_yaml } => {
+                let old: Node = serde_yaml::from_str(old_yaml)?;
+                let new: Node = serde_yaml::from_str(new_yaml)?;
+                if Self::is_ready(&new) {
+                    self.arm_timer(&new);
+                } else {
+                    self.cancel_timer(&new.metadata.name);
+                    // heartbeats keep updating NotReady nodes; evict only on the transition
+                    if Self::is_ready(&old) {
+                        info!("Node {} is no longer Ready", new.metadata.name);
+                        self.evict_pods(&new.metadata.name).await?;
+                    }
+                }
+            }
+            WatchEvent::Delete { yaml } => {
+                let node: Node = serde_yaml::from_str(yaml)?;
+                self.cancel_timer(&node.metadata.name);
+                self.evict_pods(&node.metadata.name).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) {
+        self.stopped = true;
+        for (_, timer) in self.timers.drain() {
+            timer.abort();
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::api::xlinestore::XlineStore;
+use crate::api::xlinestore::{RevisionConflict, XlineStore, namespaced_key};
 use crate::controllers::Controller;
 use crate::controllers::manager::{ResourceWatchResponse, WatchEvent};
 use anyhow::Result;
@@ -68,30 +68,118 @@ impl ReplicaSetController {
         true
     }
 
-    fn owns_or_can_adopt_pod(rs: &ReplicaSet, pod: &PodTask) -> bool {
-        match pod.metadata.owner_references.as_ref() {
-            Some(owners) => owners.iter().any(|owner| {
-                if owner.kind != ResourceKind::ReplicaSet {
-                    return false;
-                }
+    fn is_owned_by(rs: &ReplicaSet, pod: &PodTask) -> bool {
+        pod.metadata
+            .owner_references
+            .as_ref()
+            .is_some_and(|owners| owners.iter().any(|owner| Self::is_rs_owner(rs, owner)))
+    }
 
-                if owner.uid == rs.metadata.uid {
-                    return true;
-                }
+    fn is_rs_owner(rs: &ReplicaSet, owner: &OwnerReference) -> bool {
+        owner.kind == ResourceKind::ReplicaSet && owner.uid == rs.metadata.uid
+    }
 
-                owner.controller && owner.name == rs.metadata.name
-            }),
+    /// A pod can be adopted when nothing controls it, or when its controller is an
+    /// earlier ReplicaSet of the same name that has since been replaced.
+    fn can_adopt_pod(rs: &ReplicaSet, pod: &PodTask) -> bool {
+        let owners = pod.metadata.owner_references.as_deref().unwrap_or_default();
+        match owners.iter().find(|owner| owner.controller) {
             None => true,
+            Some(owner) => {
+                owner.kind == ResourceKind::ReplicaSet
+                    && owner.name == rs.metadata.name
+                    && owner.uid != rs.metadata.uid
+            }
+        }
+    }
+
+    fn controller_ref(rs: &ReplicaSet) -> OwnerReference {
+        OwnerReference {
+            api_version: rs.api_version.clone(),
+            kind: ResourceKind::ReplicaSet,
+            name: rs.metadata.name.clone(),
+            uid: rs.metadata.uid,
+            controller: true,
+            block_owner_deletion: Some(true),
         }
     }
 
-    /// Generate a unique pod name based on base name and random suffix.
-    pub async fn generate_unique_name(base: &str, store: &XlineStore) -> Result<String> {
+    /// Re-read `pod` and apply `change` to the fresh copy, writing it back only if no
+    /// one else modified it in between. `change` returns false when the pod no longer
+    /// needs it; the result is the written pod, or `None` if nothing was written.
+    async fn patch_pod(
+        &self,
+        pod: &PodTask,
+        change: impl Fn(&mut PodTask) -> bool,
+    ) -> Result<Option<PodTask>> {
+        let key = namespaced_key(&pod.metadata.namespace, &pod.metadata.name);
+        for _ in 0..5 {
+            let Some((yaml, revision)) = self.store.get_pod_yaml_with_revision(&key).await? else {
+                return Ok(None);
+            };
+            let mut pod: PodTask = serde_yaml::from_str(&yaml)?;
+            if !change(&mut pod) {
+                return Ok(None);
+            }
+            let yaml = serde_yaml::to_string(&pod)?;
+            match self
+                .store
+                .update_pod_if_revision(&key, &yaml, revision)
+                .await
+            {
+                Ok(_) => return Ok(Some(pod)),
+                Err(e) if e.is::<RevisionConflict>() => {}
+                Err(e) => return Err(e),
+            }
+        }
+        log::warn!("gave up updating pod {} after concurrent updates", key);
+        Ok(None)
+    }
+
+    /// Stamp this ReplicaSet as the controller of an orphan pod it selects.
+    async fn adopt_pod(&self, rs: &ReplicaSet, pod: &PodTask) -> Result<Option<PodTask>> {
+        self.patch_pod(pod, |pod| {
+            if !Self::selector_match(rs, pod) || !Self::can_adopt_pod(rs, pod) {
+                return false;
+            }
+            let mut owners = pod.metadata.owner_references.take().unwrap_or_default();
+            owners.retain(|owner| !owner.controller);
+            owners.push(Self::controller_ref(rs));
+            pod.metadata.owner_references = Some(owners);
+            true
+        })
+        .await
+    }
+
+    /// Drop this ReplicaSet's owner reference from a pod it no longer selects.
+    async fn release_pod(&self, rs: &ReplicaSet, pod: &PodTask) -> Result<Option<PodTask>> {
+        self.patch_pod(pod, |pod| {
+            if Self::selector_match(rs, pod) || !Self::is_owned_by(rs, pod) {
+                return false;
+            }
+            let mut owners = pod.metadata.owner_references.take().unwrap_or_default();
+            owners.retain(|owner| !Self::is_rs_owner(rs, owner));
+            pod.metadata.owner_references = (!owners.is_empty()).then_some(owners);
+            true
+        })
+        .await
+    }
+
+    /// Generate a pod name based on base name and random suffix, unique within `namespace`.
+    pub async fn generate_unique_name(
+        base: &str,
+        namespace: &str,
+        store: &XlineStore,
+    ) -> Result<String> {
         loop {
             let rnd: u32 = random();
             let name = format!("{}-{:08x}", base, rnd);
 
-            if store.get_pod_yaml(&name).await?.is_none() {
+            if store
+                .get_namespaced_pod_yaml(namespace, &name)
+                .await?
+                .is_none()
+            {
                 return Ok(name);
             }
             tokio::time::sleep(std::time::Duration::from_millis(10)).await;
@@ -100,65 +188,61 @@ impl ReplicaSetController {
 
     /// Reconcile given ReplicaSet: ensure desired number of pods exist, update status.
     pub async fn reconcile(&self, rs: &mut ReplicaSet) -> Result<()> {
-        let pods = self.store.list_pods().await?;
+        let pods = self
+            .store
+            .list_pods_in_namespace(&rs.metadata.namespace)
+            .await?;
 
-        // Separate owned pods and orphan pods using owns_or_can_adopt_pod
+        // Only pods carrying our owner reference are managed; label matches alone
+        // are not enough, since selectors of different ReplicaSets may overlap.
         let mut owned_pods = Vec::new();
         let mut orphan_pods = Vec::new();
+        let mut released_pods = Vec::new();
 
         for pod in pods {
-            if !Self::selector_match(rs, &pod) {
-                continue;
-            }
-
-            // Check if this pod is already owned by this ReplicaSet
-            let is_owned = pod
-                .metadata
-                .owner_references
-                .as_ref()
-                .is_some_and(|owners| {
-                    owners.iter().any(|owner| {
-                        owner.kind == ResourceKind::ReplicaSet && owner.uid == rs.metadata.uid
-                    })
-                });
-
-            if is_owned {
-                owned_pods.push(pod);
-            } else if Self::owns_or_can_adopt_pod(rs, &pod) {
-                // Can adopt but not yet owned (orphan pod)
+            let matches = Self::selector_match(rs, &pod);
+            if Self::is_owned_by(rs, &pod) {
+                if matches {
+                    owned_pods.push(pod);
+                } else {
+                    released_pods.push(pod);
+                }
+            } else if matches && Self::can_adopt_pod(rs, &pod) {
                 orphan_pods.push(pod);
             }
-            // else: owned by other ReplicaSet, ignore
+            // else: unrelated, or controlled by another owner
         }
 
         let mut matching = owned_pods.clone();
         let desired = rs.spec.replicas;
 
         log::debug!(
-            "ReplicaSet {} reconcile start: owned={} orphans={} desired={}",
+            "ReplicaSet {} reconcile start: owned={} orphans={} released={} desired={}",
             rs.metadata.name,
             owned_pods.len(),
             orphan_pods.len(),
+            released_pods.len(),
             desired
         );
 
-        // Always claim ALL matching orphan pods, regardless of replica count
-        for orphan_pod in orphan_pods {
-            // Add ownerReference to orphan pod
-            let mut pod = orphan_pod.clone();
-            pod.metadata.owner_references = Some(vec![OwnerReference {
-                api_version: rs.api_version.clone(),
-                kind: ResourceKind::ReplicaSet,
-                name: rs.metadata.name.clone(),
-                uid: rs.metadata.uid,
-                controller: true,
-                block_owner_deletion: Some(true),
-            }]);
+        // Let go of pods whose labels no longer match; they are left running as orphans
+        for pod in released_pods {
+            if self.release_pod(rs, &pod).await?.is_some() {
+                log::info!(
+                    "ReplicaSet {} released pod {}",
+                    rs.metadata.name,
+                    pod.metadata.name
+                );
+            }
+        }
 
-            let yaml = serde_yaml::to_string(&pod)?;
-            self.store
-                .insert_pod_yaml(&pod.metadata.name, &yaml)
-                .await?;
+        // Always claim ALL matching orphan pods, regardless of replica count.
+        // Adoption is a revision-checked write, so a pod selected by several
+        // ReplicaSets ends up with exactly one of them.
+        for orphan_pod in orphan_pods {
+            let Some(pod) = self.adopt_pod(rs, &orphan_pod).await? else {
+                continue;
+            };
             log::info!(
                 "ReplicaSet {} adopted orphan pod {}",
                 rs.metadata.name,
@@ -207,25 +291,25 @@ impl ReplicaSetController {
                     status: Default::default(),
                 };
                 // ensure name unique
-                let name =
-                    Self::generate_unique_name(&rs.metadata.name, self.store.as_ref()).await?;
+                let name = Self::generate_unique_name(
+                    &rs.metadata.name,
+                    &rs.metadata.namespace,
+                    self.store.as_ref(),
+                )
+                .await?;
                 pod.metadata.name = name.clone();
+                pod.metadata.namespace = rs.metadata.namespace.clone();
                 // ensure uid unique
                 pod.metadata.uid = Uuid::new_v4();
                 // ensure selector labels present on pod
                 for (k, v) in rs.spec.selector.match_labels.iter() {
                     pod.metadata.labels.insert(k.clone(), v.clone());
                 }
-                pod.metadata.owner_references = Some(vec![OwnerReference {
-                    api_version: rs.api_version.clone(),
-                    kind: ResourceKind::ReplicaSet,
-                    name: rs.metadata.name.clone(),
-                    uid: rs.metadata.uid,
-                    controller: true,
-                    block_owner_deletion: Some(true),
-                }]);
+                pod.metadata.owner_references = Some(vec![Self::controller_ref(rs)]);
                 let yaml = serde_yaml::to_string(&pod)?;
-                self.store.insert_pod_yaml(&name, &yaml).await?;
+                self.store
+                    .insert_namespaced_pod_yaml(&rs.metadata.namespace, &name, &yaml)
+                    .await?;
                 log::debug!(
                     "ReplicaSet {} created pod {} while reconciling",
                     rs.metadata.name,
@@ -251,7 +335,9 @@ impl ReplicaSetController {
             });
             for pod in matching.into_iter().take(to_delete) {
                 let pod_name = pod.metadata.name.clone();
-                self.store.delete_pod(&pod_name).await?;
+                self.store
+                    .delete_namespaced_pod(&pod.metadata.namespace, &pod_name)
+                    .await?;
                 log::info!(
                     "ReplicaSet {} deleted pod {} while reconciling",
                     rs.metadata.name,
@@ -264,6 +350,7 @@ impl ReplicaSetController {
     }
 
     // Implement Controller trait wrapper: load ReplicaSet by name then call reconcile above and persist status.
+    // `key` is `<namespace>/<name>`; a bare name is looked up in the default namespace.
     pub async fn reconcile_by_name(&self, key: &str) -> Result<()> {
         let mut attempts = 0;
         loop {
@@ -273,17 +360,19 @@ impl ReplicaSetController {
             };
 
             let mut rs: ReplicaSet = serde_yaml::from_str(&yaml)?;
-            let name = rs.metadata.name.clone();
+            let rs_key = namespaced_key(&rs.metadata.namespace, &rs.metadata.name);
 
             self.reconcile(&mut rs).await?;
 
             let new_yaml = serde_yaml::to_string(&rs)?;
-            if self
+            match self
                 .store
-                .compare_and_set_replicaset_yaml(&name, revision, &new_yaml)
-                .await?
+                .update_replicaset_if_revision(&rs_key, &new_yaml, revision)
+                .await
             {
-                return Ok(());
+                Ok(_) => return Ok(()),
+                Err(e) if e.is::<RevisionConflict>() => {}
+                Err(e) => return Err(e),
             }
 
             attempts += 1;
@@ -372,13 +461,15 @@ impl Controller for ReplicaSetController {
                             .filter(|o| o.kind == ResourceKind::ReplicaSet)
                         {
                             owner_triggered = true;
-                            if reconciled.insert(owner.name.clone()) {
+                            // owner references never cross namespaces
+                            let rs_key = namespaced_key(&pod.metadata.namespace, &owner.name);
+                            if reconciled.insert(rs_key.clone()) {
                                 log::debug!(
                                     "Pod {} owned by ReplicaSet {}, triggering reconcile",
                                     pod.metadata.name,
-                                    owner.name
+                                    rs_key
                                 );
-                                self.reconcile_by_name(&owner.name).await?;
+                                self.reconcile_by_name(&rs_key).await?;
                             }
                         }
                     }
@@ -390,7 +481,10 @@ impl Controller for ReplicaSetController {
                     let replicasets = match replicasets_cache.as_ref() {
                         Some(rs) => rs,
                         None => {
-                            let rs = self.store.list_replicasets().await?;
+                            let rs = self
+                                .store
+                                .list_replicasets_in_namespace(&pod.metadata.namespace)
+                                .await?;
                             log::debug!(
                                 "ReplicaSetController label-matching Pod event against {} ReplicaSets",
                                 rs.len()
@@ -401,15 +495,14 @@ impl Controller for ReplicaSetController {
                     };
 
                     for rs in replicasets.iter() {
-                        if Self::selector_match(rs, pod)
-                            && reconciled.insert(rs.metadata.name.clone())
-                        {
+                        let rs_key = namespaced_key(&rs.metadata.namespace, &rs.metadata.name);
+                        if Self::selector_match(rs, pod) && reconciled.insert(rs_key.clone()) {
                             log::debug!(
                                 "Pod {} label-matched ReplicaSet {}, triggering reconcile",
                                 pod.metadata.name,
-                                rs.metadata.name
+                                rs_key
                             );
-                            self.reconcile_by_name(&rs.metadata.name).await?;
+                            self.reconcile_by_name(&rs_key).await?;
                         }
                     }
                 }
@@ -1,13 +1,15 @@
 #![allow(dead_code)]
 use anyhow::Result;
-use common::{Endpoint, PodTask, ServiceTask};
+use common::{ConditionStatus, Endpoint, EndpointSubset, PodConditionType, PodTask, ServiceTask};
 use etcd_client::EventType;
 use futures::StreamExt;
 use hickory_proto::op::ResponseCode;
 use hickory_proto::rr::LowerName;
 use hickory_proto::rr::Name;
-use hickory_proto::rr::rdata::SRV;
+use hickory_proto::rr::rdata::{PTR, SRV};
 use hickory_proto::rr::{RData, Record, RecordSet, RecordType};
+use hickory_proto::xfer::Protocol;
+use hickory_resolver::config::{NameServerConfig, NameServerConfigGroup, ResolverOpts};
 use hickory_resolver::name_server::TokioConnectionProvider;
 use hickory_server::ServerFuture;
 use hickory_server::authority::{
@@ -15,7 +17,7 @@ use hickory_server::authority::{
     MessageRequest, ZoneType,
 };
 use hickory_server::server::RequestInfo;
-use hickory_server::store::forwarder::ForwardAuthority;
+use hickory_server::store::forwarder::{ForwardAuthority, ForwardConfig};
 use log::{debug, error, info};
 use nftables::expr::Meta as ExprMeta;
 use nftables::expr::MetaKey;
@@ -29,19 +31,24 @@ use nftables::stmt::{Match as StmtMatch, NAT, Operator, Statement};
 use nftables::types::NfFamily;
 use std::borrow::Cow;
 use std::env;
-use std::net::{Ipv4Addr, SocketAddr};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::UdpSocket;
 use tonic::async_trait;
 
 use crate::api::xlinestore::XlineStore;
+use crate::controllers::endpoint_controller::build_endpoints_from_service_and_pods;
 use crate::dns::object_cache::{DnsObjectCache, EndpointRecord, PodRecord, ServiceRecord};
+use crate::protocol::config::DnsConfig;
 
 pub struct XlineAuthority {
     pub origin: LowerName,
     pub object_cache: Arc<DnsObjectCache>,
     pub xline_store: Arc<XlineStore>,
+    /// TTL in seconds of every record this authority serves.
+    pub ttl: u32,
 }
 
 #[async_trait]
@@ -112,7 +119,7 @@ impl Authority for XlineAuthority {
             }));
         }
 
-        // 2) Service A / headless A handling
+        // 2) Service A/AAAA / headless A/AAAA handling
         if let Some(set) = self
             .build_service_or_headless_a_recordset(name, rtype)
             .await
@@ -131,6 +138,16 @@ impl Authority for XlineAuthority {
             }));
         }
 
+        // 4) Pod PTR handling
+        if rtype == RecordType::PTR
+            && let Some(set) = self.build_pod_ptr_recordset(name).await
+        {
+            return LookupControlFlow::Continue(Ok(LookupRecords::Records {
+                lookup_options,
+                records: Arc::new(set),
+            }));
+        }
+
         LookupControlFlow::Continue(Ok(LookupRecords::Empty))
     }
 }
@@ -139,6 +156,7 @@ impl XlineAuthority {
     pub async fn init_from_store(&self, store: &XlineStore) -> anyhow::Result<()> {
         let pods = store.list_pods().await?;
         let mut pod_cache = self.object_cache.pod_cache.write().await;
+        let mut pod_ip_index = self.object_cache.pod_ip_index.write().await;
         info!("DNS server get pods: {pods:?}");
         for pod in pods {
             let ns = pod.metadata.namespace.clone();
@@ -147,16 +165,18 @@ impl XlineAuthority {
             let ip = ip_only.parse().ok();
             let pod_ip_with_dashes = ip_only.replace('.', "-");
             info!("DNS server insert PodRecord: {pod_ip_with_dashes}, ns: {ns}");
-            pod_cache.insert(
-                (ns.clone(), pod_ip_with_dashes.clone()),
-                PodRecord {
-                    name: pod_ip_with_dashes,
-                    namespace: ns,
-                    pod_ip: ip,
-                },
-            );
+            let record = PodRecord {
+                name: pod_ip_with_dashes.clone(),
+                namespace: ns.clone(),
+                pod_ip: ip,
+            };
+            if let Some(ip) = ip {
+                pod_ip_index.insert(ip, record.clone());
+            }
+            pod_cache.insert((ns, pod_ip_with_dashes), record);
         }
         info!("DNS server init_from_store pod_cache: {pod_cache:?}");
+        drop(pod_ip_index);
         drop(pod_cache);
 
         let services = store.list_services().await?;
@@ -207,10 +227,11 @@ impl XlineAuthority {
     pub async fn start_watch_tasks(self: Arc<Self>, start_rev: i64) {
         // pods
         let pod_cache = Arc::clone(&self.object_cache.pod_cache);
+        let pod_ip_index = Arc::clone(&self.object_cache.pod_ip_index);
         let xline_store = Arc::clone(&self.xline_store);
 
         tokio::spawn(async move {
-            let (mut watcher, mut stream) = xline_store.watch_pods(start_rev).await.unwrap();
+            let (mut watcher, mut stream) = xline_store.watch_pods_raw(start_rev).await.unwrap();
             while let Some(resp) = stream.next().await {
                 match resp {
                     Ok(resp) => {
@@ -229,14 +250,18 @@ impl XlineAuthority {
                                         info!(
                                             "DNS server insert PodRecord: {pod_ip_with_dashes}, ns: {ns}"
                                         );
-                                        pod_cache.write().await.insert(
-                                            (ns.clone(), pod_ip_with_dashes.clone()),
-                                            PodRecord {
-                                                name: pod_ip_with_dashes,
-                                                namespace: ns,
-                                                pod_ip: ip,
-                                            },
-                                        );
+                                        let record = PodRecord {
+                                            name: pod_ip_with_dashes.clone(),
+                                            namespace: ns.clone(),
+                                            pod_ip: ip,
+                                        };
+                                        if let Some(ip) = ip {
+                                            pod_ip_index.write().await.insert(ip, record.clone());
+                                        }
+                                        pod_cache
+                                            .write()
+                                            .await
+                                            .insert((ns, pod_ip_with_dashes), record);
                                     }
                                 }
                                 EventType::Delete => {
@@ -253,6 +278,14 @@ impl XlineAuthority {
                                             info!(
                                                 "DNS server delete PodRecord : {pod_ip_with_dashes}"
                                             );
+                                            if let Ok(ip) = ip_only.parse::<Ipv4Addr>() {
+                                                let mut index = pod_ip_index.write().await;
+                                                // the IP may already belong to a newer pod
+                                                if index.get(&ip).is_some_and(|r| r.namespace == ns)
+                                                {
+                                                    index.remove(&ip);
+                                                }
+                                            }
                                             pod_cache
                                                 .write()
                                                 .await
@@ -357,7 +390,7 @@ impl XlineAuthority {
                                             .spec
                                             .cluster_ip
                                             .as_ref()
-                                            .and_then(|s| s.parse::<Ipv4Addr>().ok());
+                                            .and_then(|s| s.parse::<IpAddr>().ok());
                                         svc_cache.write().await.insert(
                                             (ns.clone(), name.clone()),
                                             ServiceRecord {
@@ -402,12 +435,14 @@ impl XlineAuthority {
     pub async fn start(
         origin: LowerName,
         xline_store: Arc<XlineStore>,
+        ttl: u32,
     ) -> anyhow::Result<Arc<Self>> {
         let object_cache = Arc::new(DnsObjectCache::new());
         let authority = Arc::new(Self {
             origin,
             object_cache: Arc::clone(&object_cache),
             xline_store: Arc::clone(&xline_store),
+            ttl,
         });
         info!("DNS server init_from_store");
         authority.init_from_store(&xline_store).await?;
@@ -422,76 +457,78 @@ impl XlineAuthority {
         Ok(authority)
     }
 
+    /// Returns the ready endpoint subsets backing the Service `ns/svc_name`.
+    ///
+    /// The Endpoints object published by the endpoint controller is authoritative. Until
+    /// it exists, the Service selector is resolved against the ready pods in `XlineStore`
+    /// so that freshly created Services are answerable right away.
+    async fn ready_subsets(&self, ns: &str, svc_name: &str) -> Vec<EndpointSubset> {
+        let key = (ns.to_string(), svc_name.to_string());
+        if let Some(ep) = self.object_cache.endpoints_cache.read().await.get(&key) {
+            return ep.subsets.clone();
+        }
+
+        let svc = match self.xline_store.get_service(svc_name).await {
+            Ok(Some(svc)) if svc.metadata.namespace == ns => svc,
+            Ok(_) => return Vec::new(),
+            Err(e) => {
+                error!("DNS failed to get service {ns}/{svc_name}: {e}");
+                return Vec::new();
+            }
+        };
+        let pods = match self.xline_store.list_pods_in_namespace(ns).await {
+            Ok(pods) => pods,
+            Err(e) => {
+                error!("DNS failed to list pods in namespace {ns}: {e}");
+                return Vec::new();
+            }
+        };
+        let ready: Vec<PodTask> = pods.into_iter().filter(is_pod_ready).collect();
+        build_endpoints_from_service_and_pods(&svc, &ready)
+            .map(|ep| ep.subsets)
+            .unwrap_or_default()
+    }
+
     async fn build_srv_recordset(&self, name: &LowerName) -> Option<RecordSet> {
         // SRV queries have the form: _port._proto.<service>.<ns>.svc.cluster.local.
-        if let Some((port_name, _proto, svc_name, ns)) = parse_srv_query(name, &self.origin) {
-            // prefer service-level SRV pointing to service FQDN
-            let svc_cache = self.object_cache.service_cache.read().await;
-            if let Some(svc) = svc_cache.get(&(ns.clone(), svc_name.clone()))
-                && svc.cluster_ip.is_some()
-            {
-                // find matching port by name in the service ports
-                let matched_port = svc.ports.iter().find_map(|sp| {
-                    sp.name.as_ref().and_then(|n| {
-                        if n == &port_name {
-                            Some(sp.port as u16)
-                        } else {
-                            None
-                        }
-                    })
-                });
-
-                if let Some(port_num) = matched_port {
-                    let mut set = RecordSet::new(name.clone().into(), RecordType::SRV, 30);
-                    let target = format!("{}.{}.svc.{}", svc_name, ns, self.origin);
-                    if let Ok(target_name) = Name::from_str(&target) {
-                        // Kubernetes typically uses priority 0 and weight 100 for service-level SRV records
-                        let rdata = RData::SRV(SRV::new(0, 100, port_num, target_name));
-                        set.insert(Record::from_rdata(name.clone().into(), 30, rdata), 0);
-                    }
-                    if !set.is_empty() {
-                        return Some(set);
-                    }
+        let (port_name, proto, svc_name, ns) = parse_srv_query(name, &self.origin)?;
+
+        // one target per ready endpoint, pointing at the pod host name used by pod A records
+        let mut targets = Vec::new();
+        for subset in self.ready_subsets(&ns, &svc_name).await {
+            let Some(port) = subset.ports.iter().find(|p| {
+                p.name.as_deref() == Some(port_name.as_str())
+                    && p.protocol.eq_ignore_ascii_case(&proto)
+            }) else {
+                continue;
+            };
+            for addr in &subset.addresses {
+                let Some(ip) = endpoint_ip(&addr.ip) else {
+                    continue;
+                };
+                let pod_host = format!(
+                    "{}.{}.pod.{}",
+                    ip.to_string().replace(['.', ':'], "-"),
+                    ns,
+                    self.origin
+                );
+                if let Ok(target_name) = Name::from_str(&pod_host) {
+                    targets.push((port.port as u16, target_name));
                 }
             }
+        }
+        if targets.is_empty() {
+            return None;
+        }
 
-            // fallback to endpoints-based SRV (headless)
-            let ep_cache = self.object_cache.endpoints_cache.read().await;
-            if let Some(ep) = ep_cache.get(&(ns.clone(), svc_name.clone())) {
-                let mut set = RecordSet::new(name.clone().into(), RecordType::SRV, 30);
-                for subset in &ep.subsets {
-                    for port in &subset.ports {
-                        if port.name.as_deref() == Some(&port_name) {
-                            let port_num = port.port as u16;
-                            for addr in &subset.addresses {
-                                if addr.ip.parse::<Ipv4Addr>().is_ok() {
-                                    // target is the pod host name used by pod A records
-                                    let pod_host = format!(
-                                        "{}.pod.{}.{}",
-                                        addr.ip.replace('.', "-"),
-                                        ns,
-                                        self.origin
-                                    );
-                                    if let Ok(target_name) = Name::from_str(&pod_host) {
-                                        let rdata =
-                                            RData::SRV(SRV::new(0, 0, port_num, target_name));
-                                        set.insert(
-                                            Record::from_rdata(name.clone().into(), 30, rdata),
-                                            0,
-                                        );
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-
-                if !set.is_empty() {
-                    return Some(set);
-                }
-            }
+        // all endpoints share priority 0 and split the weight evenly, like CoreDNS does
+        let weight = (100 / targets.len()).max(1) as u16;
+        let mut set = RecordSet::new(name.clone().into(), RecordType::SRV, self.ttl);
+        for (port, target_name) in targets {
+            let rdata = RData::SRV(SRV::new(0, weight, port, target_name));
+            set.insert(Record::from_rdata(name.clone().into(), self.ttl, rdata), 0);
         }
-        None
+        Some(set)
     }
 
     async fn build_service_or_headless_a_recordset(
@@ -499,47 +536,55 @@ impl XlineAuthority {
         name: &LowerName,
         rtype: RecordType,
     ) -> Option<RecordSet> {
-        if let Some((svc_name, ns)) = parse_service_query(name, &self.origin) {
-            let svc_cache = self.object_cache.service_cache.read().await;
-            if let Some(svc) = svc_cache.get(&(ns.clone(), svc_name.clone())) {
-                // ClusterIP service -> return cluster IP
-                if rtype == RecordType::A {
-                    if let Some(ip) = svc.cluster_ip {
-                        let mut set = RecordSet::new(name.clone().into(), RecordType::A, 30);
-                        set.insert(
-                            Record::from_rdata(name.clone().into(), 30, RData::A(ip.into())),
-                            0,
-                        );
-                        return Some(set);
-                    }
-
-                    // headless service: lookup endpoints for backends
-                    let ep_cache = self.object_cache.endpoints_cache.read().await;
-                    if let Some(ep) = ep_cache.get(&(ns.clone(), svc_name.clone())) {
-                        let mut set = RecordSet::new(name.clone().into(), RecordType::A, 30);
-                        for subset in &ep.subsets {
-                            for addr in subset.addresses.iter() {
-                                if let Ok(ipv4) = addr.ip.parse::<Ipv4Addr>() {
-                                    set.insert(
-                                        Record::from_rdata(
-                                            name.clone().into(),
-                                            30,
-                                            RData::A(ipv4.into()),
-                                        ),
-                                        0,
-                                    );
-                                }
-                            }
-                        }
+        if rtype != RecordType::A && rtype != RecordType::AAAA {
+            return None;
+        }
+        let (svc_name, ns) = parse_service_query(name, &self.origin)?;
+        let cluster_ip = self
+            .object_cache
+            .service_cache
+            .read()
+            .await
+            .get(&(ns.clone(), svc_name.clone()))?
+            .cluster_ip;
+
+        let ips: Vec<IpAddr> = match cluster_ip {
+            // ClusterIP service -> return cluster IP
+            Some(ip) => vec![ip],
+            // headless service: return the ready backend pod IPs
+            None => self
+                .ready_subsets(&ns, &svc_name)
+                .await
+                .iter()
+                .flat_map(|subset| &subset.addresses)
+                .filter_map(|addr| endpoint_ip(&addr.ip))
+                .collect(),
+        };
 
-                        if !set.is_empty() {
-                            return Some(set);
-                        }
-                    }
-                }
-            }
+        let mut set = RecordSet::new(name.clone().into(), rtype, self.ttl);
+        for ip in ips {
+            let rdata = match ip {
+                IpAddr::V4(v4) if rtype == RecordType::A => RData::A(v4.into()),
+                IpAddr::V6(v6) if rtype == RecordType::AAAA => RData::AAAA(v6.into()),
+                _ => continue,
+            };
+            set.insert(Record::from_rdata(name.clone().into(), self.ttl, rdata), 0);
         }
-        None
+        (!set.is_empty()).then_some(set)
+    }
+
+    async fn build_pod_ptr_recordset(&self, name: &LowerName) -> Option<RecordSet> {
+        let ip = parse_ptr_query(name)?;
+        let index = self.object_cache.pod_ip_index.read().await;
+        let pod = index.get(&ip)?;
+        let pod_host = format!("{}.{}.pod.{}", pod.name, pod.namespace, self.origin);
+        let target_name = Name::from_str(&pod_host).ok()?;
+        let mut set = RecordSet::new(name.clone().into(), RecordType::PTR, self.ttl);
+        set.insert(
+            Record::from_rdata(name.clone().into(), self.ttl, RData::PTR(PTR(target_name))),
+            0,
+        );
+        Some(set)
     }
 
     async fn build_pod_a_recordset(
@@ -555,9 +600,9 @@ impl XlineAuthority {
                 && let Some(ip) = pod.pod_ip
             {
                 info!("DNS find the Record: {pod:?}");
-                let mut set = RecordSet::new(name.clone().into(), RecordType::A, 30);
+                let mut set = RecordSet::new(name.clone().into(), RecordType::A, self.ttl);
                 set.insert(
-                    Record::from_rdata(name.clone().into(), 30, RData::A(ip.into())),
+                    Record::from_rdata(name.clone().into(), self.ttl, RData::A(ip.into())),
                     0,
                 );
                 return Some(set);
@@ -568,23 +613,31 @@ impl XlineAuthority {
     }
 }
 
-pub async fn run_dns_server(xline_store: Arc<XlineStore>, port: u16) -> anyhow::Result<()> {
+/// Runs the cluster DNS server on `config.port`.
+///
+/// Names under `cluster.local.` are always answered from `XlineStore`. Everything else
+/// goes to the upstream resolvers when `config.forward` is set; reverse lookups fall
+/// through to them only when no pod owns the queried IP.
+pub async fn run_dns_server(xline_store: Arc<XlineStore>, config: DnsConfig) -> anyhow::Result<()> {
     let origin = LowerName::from_str("cluster.local.")?;
-    let xline_authority = XlineAuthority::start(origin.clone(), xline_store).await?;
+    let xline_authority = XlineAuthority::start(origin.clone(), xline_store, config.ttl).await?;
 
     let mut catalog = Catalog::new();
 
     let xline_authority: Arc<dyn AuthorityObject> = xline_authority;
-    catalog.upsert(origin, vec![xline_authority]);
-
-    let forwarder = ForwardAuthority::builder(TokioConnectionProvider::default())
-        .map_err(|e| anyhow::anyhow!(e))?
-        .build()
-        .map_err(|e| anyhow::anyhow!(e))?;
-    catalog.upsert(LowerName::from(Name::root()), vec![Arc::new(forwarder)]);
+    catalog.upsert(origin, vec![Arc::clone(&xline_authority)]);
+
+    // reverse lookups for pod IPs are answered by the same authority
+    let mut reverse_chain = vec![xline_authority];
+    if config.forward {
+        let forwarder: Arc<dyn AuthorityObject> = Arc::new(build_forwarder(&config)?);
+        reverse_chain.push(Arc::clone(&forwarder));
+        catalog.upsert(LowerName::from(Name::root()), vec![forwarder]);
+    }
+    catalog.upsert(LowerName::from_str("in-addr.arpa.")?, reverse_chain);
 
     let mut server = ServerFuture::new(catalog);
-    let addr: SocketAddr = format!("0.0.0.0:{}", port).parse()?;
+    let addr: SocketAddr = format!("0.0.0.0:{}", config.port).parse()?;
     let udp_socket = UdpSocket::bind(addr).await?;
     server.register_socket(udp_socket);
 
@@ -594,6 +647,69 @@ pub async fn run_dns_server(xline_store: Arc<XlineStore>, port: u16) -> anyhow::
     Ok(())
 }
 
+/// Builds the authority forwarding non-cluster names to the configured upstreams.
+///
+/// Each upstream gets a single attempt bounded by `upstream_timeout_ms` before the
+/// resolver falls back to the next one, over UDP first and then TCP.
+fn build_forwarder(
+    config: &DnsConfig,
+) -> anyhow::Result<ForwardAuthority<TokioConnectionProvider>> {
+    let mut options = ResolverOpts::default();
+    options.timeout = Duration::from_millis(config.upstream_timeout_ms);
+    options.attempts = 1;
+
+    let builder = if config.upstreams.is_empty() {
+        ForwardAuthority::builder(TokioConnectionProvider::default())
+            .map_err(|e| anyhow::anyhow!(e))?
+    } else {
+        let mut name_servers = NameServerConfigGroup::new();
+        for upstream in &config.upstreams {
+            let addr = parse_upstream(upstream)?;
+            name_servers.push(NameServerConfig::new(addr, Protocol::Udp));
+            name_servers.push(NameServerConfig::new(addr, Protocol::Tcp));
+        }
+        let forward_config = ForwardConfig {
+            name_servers,
+            options: None,
+        };
+        ForwardAuthority::builder_with_config(forward_config, TokioConnectionProvider::default())
+    };
+
+    info!("DNS server forwarding to upstreams: {:?}", config.upstreams);
+    builder
+        .with_options(options)
+        .build()
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+/// Parses an upstream given as `ip` or `ip:port`, defaulting to port 53.
+fn parse_upstream(upstream: &str) -> anyhow::Result<SocketAddr> {
+    if let Ok(addr) = upstream.parse::<SocketAddr>() {
+        return Ok(addr);
+    }
+    let ip: IpAddr = upstream
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid DNS upstream address: {upstream}"))?;
+    Ok(SocketAddr::new(ip, 53))
+}
+
+/// Parses an endpoint address, dropping any CIDR suffix carried over from the pod IP.
+fn endpoint_ip(ip: &str) -> Option<IpAddr> {
+    ip.split('/').next()?.parse().ok()
+}
+
+fn is_pod_ready(pod: &PodTask) -> bool {
+    pod.status
+        .conditions
+        .as_ref()
+        .and_then(|conds| {
+            conds
+                .iter()
+                .find(|c| matches!(c.condition_type, PodConditionType::PodReady))
+        })
+        .is_some_and(|c| matches!(c.status, ConditionStatus::True))
+}
+
 fn parse_service_query(name: &LowerName, origin: &LowerName) -> Option<(String, String)> {
     let labels: Vec<_> = name
         .iter()
@@ -651,6 +767,19 @@ fn parse_pod_query(name: &LowerName, origin: &LowerName) -> Option<(String, Stri
     Some((pod, ns))
 }
 
+fn parse_ptr_query(name: &LowerName) -> Option<Ipv4Addr> {
+    // "4.3.2.1.in-addr.arpa." -> 1.2.3.4
+    let labels: Vec<_> = name
+        .iter()
+        .map(|l| std::str::from_utf8(l).ok())
+        .collect::<Option<_>>()?;
+
+    let [d, c, b, a, "in-addr", "arpa"] = labels.as_slice() else {
+        return None;
+    };
+    format!("{a}.{b}.{c}.{d}").parse().ok()
+}
+
 fn parse_srv_query(
     name: &LowerName,
     origin: &LowerName,
@@ -1078,4 +1207,26 @@ mod tests {
         assert_eq!(svc, "nginx");
         assert_eq!(ns, "default");
     }
+
+    #[test]
+    fn test_parse_upstream() {
+        assert_eq!(
+            parse_upstream("10.0.0.2").unwrap(),
+            "10.0.0.2:53".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(
+            parse_upstream("10.0.0.2:5353").unwrap(),
+            "10.0.0.2:5353".parse::<SocketAddr>().unwrap()
+        );
+        assert!(parse_upstream("not-an-ip").is_err());
+    }
+
+    #[test]
+    fn test_parse_ptr_query() {
+        let name = LowerName::from_str("5.40.20.10.in-addr.arpa.").unwrap();
+        assert_eq!(parse_ptr_query(&name), Some(Ipv4Addr::new(10, 20, 40, 5)));
+
+        let name = LowerName::from_str("40.20.10.in-addr.arpa.").unwrap();
+        assert_eq!(parse_ptr_query(&name), None);
+    }
 }
@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr};
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::RwLock;
 
@@ -9,7 +9,7 @@ use common::{EndpointSubset, ServicePort};
 pub struct ServiceRecord {
     pub name: String,
     pub namespace: String,
-    pub cluster_ip: Option<Ipv4Addr>,
+    pub cluster_ip: Option<IpAddr>,
     pub ports: Vec<ServicePort>,
 }
 
@@ -32,6 +32,8 @@ pub struct DnsObjectCache {
     pub service_cache: Arc<RwLock<HashMap<(String, String), ServiceRecord>>>, // key: (ns, name)
     pub pod_cache: Arc<RwLock<HashMap<(String, String), PodRecord>>>,
     pub endpoints_cache: Arc<RwLock<HashMap<(String, String), EndpointRecord>>>,
+    /// Reverse index from pod IP to its record, used to answer PTR queries.
+    pub pod_ip_index: Arc<RwLock<HashMap<Ipv4Addr, PodRecord>>>,
 }
 
 impl DnsObjectCache {
@@ -40,6 +42,7 @@ impl DnsObjectCache {
             service_cache: Arc::new(RwLock::new(HashMap::new())),
             pod_cache: Arc::new(RwLock::new(HashMap::new())),
             endpoints_cache: Arc::new(RwLock::new(HashMap::new())),
+            pod_ip_index: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 }
@@ -14,9 +14,11 @@ mod vault;
 
 use crate::controllers::endpoint_controller::EndpointController;
 use crate::controllers::garbage_collector::GarbageCollector;
+use crate::controllers::leader_election::{self, LeaderElectionConfig};
+use crate::controllers::node_controller::DEFAULT_NODE_MONITOR_GRACE_PERIOD;
 use crate::controllers::{
-    CONTROLLER_MANAGER, ControllerManager, DeploymentController, JobController, NftablesController,
-    ReplicaSetController,
+    ControllerManager, DaemonSetController, DeploymentController, JobController,
+    NftablesController, NodeController, ReplicaSetController,
 };
 use crate::dns::authority::{run_dns_server, setup_dns_nftable};
 use crate::network::init;
@@ -107,17 +109,11 @@ async fn handle_start_command() -> anyhow::Result<()> {
     let (network_config, service_ip_allocator) =
         init_service_ip_components(cfg, &local_manager, &xline_options, &xline_store).await?;
 
-    register_controllers(
-        CONTROLLER_MANAGER.clone(),
+    // only the elected rks instance runs the controllers, the others stand by
+    tokio::spawn(run_controller_manager(
         xline_store.clone(),
         node_registry.clone(),
-        4,
-    )
-    .await?;
-    CONTROLLER_MANAGER
-        .clone()
-        .start_watch(xline_store.clone())
-        .await?;
+    ));
 
     let shared = Arc::new(Shared::new(
         xline_store.clone(),
@@ -171,14 +167,22 @@ async fn init_xline_store(cfg: &Config, option: &XlineOptions) -> anyhow::Result
         .insert_network_config(&cfg.xline_config.prefix, &cfg.network_config)
         .await?;
 
+    let migrated = store.migrate_legacy_namespaced_keys().await?;
+    if migrated > 0 {
+        info!(
+            target: "rks::main",
+            "moved {migrated} pods/replicasets to namespaced xline keys"
+        );
+    }
+
     Ok(store)
 }
 
 fn spawn_dns_server(xline_store: Arc<XlineStore>, cfg: &Config) {
     info!(target: "rks::main", "initializing dns server");
-    let port = cfg.dns_config.port;
+    let dns_config = cfg.dns_config.clone();
     tokio::spawn(async move {
-        if let Err(err) = run_dns_server(xline_store, port).await {
+        if let Err(err) = run_dns_server(xline_store, dns_config).await {
             error!(
                 target: "rks::main",
                 "dns server exited with error: {err:?}"
@@ -400,6 +404,44 @@ async fn launch_scheduler(
     Ok(())
 }
 
+/// Campaigns for the controller manager lock and runs a fresh manager for every term
+/// won, tearing it down once leadership is lost and campaigning again. Exits the
+/// process if the controllers cannot be started.
+async fn run_controller_manager(xline_store: Arc<XlineStore>, node_registry: Arc<NodeRegistry>) {
+    let election = LeaderElectionConfig::default();
+    loop {
+        let lease = leader_election::acquire(xline_store.clone(), election.clone()).await;
+        let manager = Arc::new(ControllerManager::new());
+        let started = async {
+            register_controllers(
+                manager.clone(),
+                xline_store.clone(),
+                node_registry.clone(),
+                4,
+            )
+            .await?;
+            manager
+                .clone()
+                .run_as_leader(xline_store.clone(), lease)
+                .await
+        }
+        .await;
+        if let Err(err) = started {
+            error!(
+                target: "rks::main",
+                "controller manager failed to start: {err:?}"
+            );
+            std::process::exit(1);
+        }
+
+        manager.stopped().await;
+        info!(
+            target: "rks::main",
+            "controller manager lost leadership, campaigning again"
+        );
+    }
+}
+
 async fn register_controllers(
     mgr: Arc<ControllerManager>,
     xline_store: Arc<XlineStore>,
@@ -412,6 +454,8 @@ async fn register_controllers(
     let deploy = DeploymentController::new(xline_store.clone());
     let nft = NftablesController::new(xline_store.clone(), node_registry);
     let job = JobController::new(xline_store.clone());
+    let ds = DaemonSetController::new(xline_store.clone());
+    let node = NodeController::new(xline_store.clone(), DEFAULT_NODE_MONITOR_GRACE_PERIOD);
 
     mgr.clone()
         .register(Arc::new(RwLock::new(gc)), workers)
@@ -431,6 +475,12 @@ async fn register_controllers(
     mgr.clone()
         .register(Arc::new(RwLock::new(job)), workers)
         .await?;
+    mgr.clone()
+        .register(Arc::new(RwLock::new(ds)), workers)
+        .await?;
+    mgr.clone()
+        .register(Arc::new(RwLock::new(node)), workers)
+        .await?;
     Ok(())
 }
 
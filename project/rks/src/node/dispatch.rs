@@ -1,4 +1,4 @@
-use crate::api::xlinestore::XlineStore;
+use crate::api::xlinestore::{XlineStore, namespaced_key};
 use crate::commands::{create, delete};
 use crate::network::service_ip::{
     validate_and_allocate_cluster_ip, validate_cluster_ip_immutability,
@@ -7,7 +7,7 @@ use crate::node::Shared;
 use chrono::Utc;
 use common::quic::RksConnection;
 use common::*;
-use common::{AttachControlMessage, Node, NodeStatus, PodTask, RksMessage, ServiceSpec};
+use common::{AttachControlMessage, NodeStatus, PodTask, RksMessage, ServiceSpec};
 use log::{error, info, warn};
 use std::net::Ipv4Addr;
 use std::sync::Arc;
@@ -195,12 +195,8 @@ pub async fn dispatch_user(
             conn.send_msg(&RksMessage::ListPodRes(pods)).await?;
         }
         RksMessage::CreateReplicaSet(mut rs) => {
-            let name = rs.metadata.name.clone();
-            if xline_store
-                .get_replicaset_yaml(&rs.metadata.name)
-                .await?
-                .is_some()
-            {
+            let name = namespaced_key(&rs.metadata.namespace, &rs.metadata.name);
+            if xline_store.get_replicaset_yaml(&name).await?.is_some() {
                 let err_msg = format!("rs \"{}\" already exists", rs.metadata.name);
                 conn.send_msg(&RksMessage::Error(err_msg)).await?;
                 return Ok(());
@@ -219,7 +215,7 @@ pub async fn dispatch_user(
         }
 
         RksMessage::UpdateReplicaSet(incoming_rs) => {
-            let name = incoming_rs.metadata.name.clone();
+            let name = namespaced_key(&incoming_rs.metadata.namespace, &incoming_rs.metadata.name);
             if let Some(existing_yaml) = xline_store.get_replicaset_yaml(&name).await? {
                 let mut final_rs: common::ReplicaSet = serde_yaml::from_str(&existing_yaml)?;
                 if final_rs.spec != incoming_rs.spec {
@@ -807,7 +803,10 @@ pub async fn dispatch_user(
                 "UpdatePodStatus received for Pod {}/{}", pod_namespace, pod_name
             );
             // Update the pod status in xline store
-            if let Some(pod_yaml) = xline_store.get_pod_yaml(&pod_name).await? {
+            if let Some(pod_yaml) = xline_store
+                .get_namespaced_pod_yaml(&pod_namespace, &pod_name)
+                .await?
+            {
                 let mut pod_task: PodTask = serde_yaml::from_str(&pod_yaml)?;
                 // Preserve existing pod_ip if the incoming status does not carry it.
                 // This avoids wiping pod_ip set by SetPodip.
@@ -816,7 +815,9 @@ pub async fn dispatch_user(
                 }
                 pod_task.status = status;
                 let new_yaml = serde_yaml::to_string(&pod_task)?;
-                xline_store.insert_pod_yaml(&pod_name, &new_yaml).await?;
+                xline_store
+                    .insert_namespaced_pod_yaml(&pod_namespace, &pod_name, &new_yaml)
+                    .await?;
                 info!(
                     target: "rks::node::user_dispatch",
                     "updated PodTask {}/{} status", pod_namespace, pod_name
@@ -1042,16 +1043,7 @@ async fn handle_heartbeat(
     node_name: &str,
     status: NodeStatus,
 ) -> anyhow::Result<()> {
-    if let Some(node_yaml) = xline_store.get_node_yaml(node_name).await? {
-        let mut node: Node = serde_yaml::from_str(&node_yaml)?;
-        node.status = status;
-
-        // Use rks clock as heartbeat time.
-        node.set_last_heartbeat_time(Utc::now());
-        node.spec.taints = Node::derive_taints_from_conditions(&node.status.conditions);
-
-        let new_yaml = serde_yaml::to_string(&node)?;
-        xline_store.insert_node_yaml(node_name, &new_yaml).await?;
+    if xline_store.record_node_heartbeat(node_name, status).await? {
         info!(
             target: "rks::node::worker_dispatch",
             "heartbeat updated Node {node_name}"
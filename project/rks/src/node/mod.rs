@@ -14,12 +14,10 @@ use serde_json::json;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
 use tokio::sync::{Mutex, Notify, mpsc};
 
 pub mod cert;
 mod dispatch;
-mod heartbeat;
 mod lease_sync;
 mod local_node;
 mod register;
@@ -171,14 +169,6 @@ impl RksNode {
     }
 
     fn start_background_tasks(&self) {
-        // Check if lastheartbeattime times out
-        heartbeat::watch(
-            self.shared.xline_store.clone(),
-            Duration::from_secs(50), // grace
-            Duration::from_secs(10), // interval
-        );
-        info!("Heartbeat monitor started");
-
         let shared_clone = self.shared.clone();
         let addr_clone = self.addr.clone();
         tokio::spawn(async move {
@@ -1,3 +1,4 @@
+use crate::api::xlinestore::split_namespaced_key;
 use crate::commands::delete::watch_delete;
 use crate::node::Shared;
 use common::quic::RksConnection;
@@ -65,8 +66,8 @@ impl PodsWatcher {
                 }
                 etcd_client::EventType::Delete => {
                     if let Some(kv) = event.prev_kv() {
-                        let pod_name =
-                            String::from_utf8_lossy(kv.key()).replace("/registry/pods/", "");
+                        let key = String::from_utf8_lossy(kv.key()).replace("/registry/pods/", "");
+                        let pod_name = split_namespaced_key(&key).1.to_string();
                         let pod_yaml = String::from_utf8_lossy(kv.value()).to_string();
 
                         // Send DeletePod first
@@ -141,7 +142,7 @@ impl PodsWatcher {
 
     async fn stream_updates(&self, node_id: String, start_rev: i64) -> anyhow::Result<()> {
         // Start watching for changes
-        let (mut watcher, mut stream) = self.shared.xline_store.watch_pods(start_rev).await?;
+        let (mut watcher, mut stream) = self.shared.xline_store.watch_pods_raw(start_rev).await?;
         info!(
             target: "rks::node::watch_pods",
             "start watching pods from revision {start_rev}"
@@ -293,7 +294,11 @@ impl PodsWatcher {
             let updated_yaml = serde_yaml::to_string(&pod)?;
             self.shared
                 .xline_store
-                .insert_pod_yaml(&pod.metadata.name, &updated_yaml)
+                .insert_namespaced_pod_yaml(
+                    &pod.metadata.namespace,
+                    &pod.metadata.name,
+                    &updated_yaml,
+                )
                 .await?;
         }
 
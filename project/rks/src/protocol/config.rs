@@ -80,6 +80,34 @@ fn default_vault_folder() -> PathBuf {
 pub struct DnsConfig {
     #[serde(rename = "Port")]
     pub port: u16,
+    /// TTL in seconds of the records served for the cluster zone.
+    #[serde(rename = "TTL", default = "default_dns_ttl")]
+    pub ttl: u32,
+    /// Whether names outside the cluster zone are forwarded to the upstreams.
+    #[serde(rename = "Forward", default = "default_dns_forward")]
+    pub forward: bool,
+    /// Upstream resolvers as `ip` or `ip:port`, tried in order. Empty means the
+    /// resolvers from `/etc/resolv.conf`.
+    #[serde(rename = "Upstreams", default)]
+    pub upstreams: Vec<String>,
+    /// How long to wait for one upstream before falling back to the next.
+    #[serde(
+        rename = "UpstreamTimeoutMs",
+        default = "default_dns_upstream_timeout_ms"
+    )]
+    pub upstream_timeout_ms: u64,
+}
+
+fn default_dns_ttl() -> u32 {
+    30
+}
+
+fn default_dns_forward() -> bool {
+    true
+}
+
+fn default_dns_upstream_timeout_ms() -> u64 {
+    2000
 }
 
 pub fn load_config(path: &str) -> anyhow::Result<&'static Config> {
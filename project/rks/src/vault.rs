@@ -205,6 +205,7 @@ impl Vault {
             .init(&SealConfig {
                 secret_shares: 5,
                 secret_threshold: 3,
+                ..Default::default()
             })
             .await?;
 
@@ -736,6 +737,7 @@ mod tests {
             .init(&SealConfig {
                 secret_shares: 1,
                 secret_threshold: 1,
+                ..Default::default()
             })
             .await
             .map_err(|e| anyhow::anyhow!("init failed: {e}"))?;
@@ -0,0 +1,291 @@
+use anyhow::Result;
+use libvault::storage::xline::XlineOptions;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::{Instant, sleep};
+
+use common::{
+    ConditionStatus, ContainerSpec, DaemonSet, DaemonSetSpec, LabelSelector, Node, NodeCondition,
+    NodeConditionType, NodeSpec, NodeStatus, ObjectMeta, PodSpec, PodTask, PodTemplateSpec,
+};
+use rks::api::xlinestore::XlineStore;
+use rks::controllers::{ControllerManager, DaemonSetController};
+use serial_test::serial;
+
+#[derive(Deserialize)]
+struct TestCfg {
+    xline_config: XlineCfg,
+}
+
+#[derive(Deserialize)]
+struct XlineCfg {
+    endpoints: Vec<String>,
+}
+
+fn load_test_config() -> Result<TestCfg> {
+    let manifest = env!("CARGO_MANIFEST_DIR");
+    let path = std::path::Path::new(manifest).join("tests/config.yaml");
+    let s = std::fs::read_to_string(path)?;
+    let cfg: TestCfg = serde_yaml::from_str(&s)?;
+    Ok(cfg)
+}
+
+async fn setup_store_and_manager() -> Result<(Arc<XlineStore>, Arc<ControllerManager>)> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let cfg = load_test_config()?;
+    let option = XlineOptions::new(cfg.xline_config.endpoints);
+    let store: Arc<XlineStore> = Arc::new(XlineStore::new(option).await?);
+
+    // clean up anything left behind by previous runs
+    cleanup(&store, "test-ds").await?;
+
+    let mgr = Arc::new(ControllerManager::new());
+    let ds_ctrl = Arc::new(RwLock::new(DaemonSetController::new(store.clone())));
+    mgr.clone().register(ds_ctrl, 2).await?;
+    mgr.clone().start_watch(store.clone()).await?;
+    sleep(Duration::from_secs(1)).await;
+    Ok((store, mgr))
+}
+
+fn make_test_node(name: &str, role: &str) -> Node {
+    Node {
+        api_version: "v1".to_string(),
+        kind: "Node".to_string(),
+        metadata: ObjectMeta {
+            name: name.to_string(),
+            namespace: "".to_string(),
+            labels: HashMap::from([("role".to_string(), role.to_string())]),
+            ..Default::default()
+        },
+        spec: NodeSpec {
+            pod_cidr: "10.244.0.0/24".to_string(),
+            taints: vec![],
+        },
+        status: NodeStatus {
+            capacity: HashMap::new(),
+            allocatable: HashMap::new(),
+            addresses: vec![],
+            conditions: vec![NodeCondition {
+                condition_type: NodeConditionType::Ready,
+                status: ConditionStatus::True,
+                last_heartbeat_time: None,
+            }],
+        },
+    }
+}
+
+fn make_test_daemonset(name: &str, role: &str) -> DaemonSet {
+    let labels = HashMap::from([("app".to_string(), name.to_string())]);
+    DaemonSet {
+        api_version: "v1".to_string(),
+        kind: "DaemonSet".to_string(),
+        metadata: ObjectMeta {
+            name: name.to_string(),
+            namespace: "default".to_string(),
+            ..Default::default()
+        },
+        spec: DaemonSetSpec {
+            selector: LabelSelector {
+                match_labels: labels.clone(),
+                match_expressions: Vec::new(),
+            },
+            template: PodTemplateSpec {
+                metadata: ObjectMeta {
+                    name: format!("{}-pod-template", name),
+                    namespace: "default".to_string(),
+                    labels,
+                    ..Default::default()
+                },
+                spec: PodSpec {
+                    containers: vec![ContainerSpec {
+                        name: "agent".to_string(),
+                        image: "busybox:latest".to_string(),
+                        ports: Vec::new(),
+                        args: Vec::new(),
+                        resources: None,
+                        liveness_probe: None,
+                        readiness_probe: None,
+                        startup_probe: None,
+                        security_context: None,
+                        env: None,
+                        volume_mounts: None,
+                        command: None,
+                        working_dir: None,
+                    }],
+                    ..Default::default()
+                },
+            },
+            node_selector: HashMap::from([("role".to_string(), role.to_string())]),
+        },
+        status: Default::default(),
+    }
+}
+
+async fn cleanup(store: &Arc<XlineStore>, prefix: &str) -> Result<()> {
+    for ds in store.list_daemonsets().await? {
+        if ds.metadata.name.starts_with(prefix) {
+            store.delete_daemonset(&ds.metadata.name).await?;
+        }
+    }
+    for pod in store.list_pods().await? {
+        if pod.metadata.name.starts_with(prefix) {
+            store.delete_pod(&pod.metadata.name).await?;
+        }
+    }
+    for node in store.list_node_names().await? {
+        if node.starts_with(prefix) {
+            store.delete_node(&node).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Waits until the DaemonSet's pods are spread over exactly `expected_nodes`.
+async fn wait_for_daemon_pods(
+    store: &Arc<XlineStore>,
+    ds_name: &str,
+    expected_nodes: &[&str],
+    timeout: Duration,
+) -> Result<Vec<PodTask>> {
+    let start = Instant::now();
+    let mut expected: Vec<String> = expected_nodes.iter().map(|n| n.to_string()).collect();
+    expected.sort();
+    loop {
+        let pods: Vec<PodTask> = store
+            .list_pods()
+            .await?
+            .into_iter()
+            .filter(|p| p.metadata.name.starts_with(ds_name))
+            .collect();
+        let mut nodes: Vec<String> = pods
+            .iter()
+            .filter_map(|p| p.spec.node_name.clone())
+            .collect();
+        nodes.sort();
+
+        if pods.len() == expected.len() && nodes == expected {
+            return Ok(pods);
+        }
+        if Instant::now().duration_since(start) > timeout {
+            return Err(anyhow::anyhow!(
+                "timed out waiting for {} pods on {:?} (found {} on {:?})",
+                ds_name,
+                expected,
+                pods.len(),
+                nodes
+            ));
+        }
+        sleep(Duration::from_millis(500)).await;
+    }
+}
+
+/// Ensures a DaemonSet runs one pod per matching node and follows nodes joining and leaving.
+#[serial]
+#[tokio::test]
+async fn test_daemonset_follows_node_membership() -> Result<()> {
+    let (store, _mgr) = setup_store_and_manager().await?;
+    store
+        .insert_node(&make_test_node("test-ds-node-a", "edge"))
+        .await?;
+    store
+        .insert_node(&make_test_node("test-ds-node-b", "edge"))
+        .await?;
+    store
+        .insert_node(&make_test_node("test-ds-node-other", "core"))
+        .await?;
+
+    let ds = make_test_daemonset("test-ds-agent", "edge");
+    store
+        .insert_daemonset_yaml(&ds.metadata.name, &serde_yaml::to_string(&ds)?)
+        .await?;
+
+    let result = async {
+        wait_for_daemon_pods(
+            &store,
+            "test-ds-agent",
+            &["test-ds-node-a", "test-ds-node-b"],
+            Duration::from_secs(10),
+        )
+        .await?;
+
+        // a new matching node gets its own pod
+        store
+            .insert_node(&make_test_node("test-ds-node-c", "edge"))
+            .await?;
+        wait_for_daemon_pods(
+            &store,
+            "test-ds-agent",
+            &["test-ds-node-a", "test-ds-node-b", "test-ds-node-c"],
+            Duration::from_secs(10),
+        )
+        .await?;
+
+        // removing a node deletes the pod left on it
+        store.delete_node("test-ds-node-b").await?;
+        wait_for_daemon_pods(
+            &store,
+            "test-ds-agent",
+            &["test-ds-node-a", "test-ds-node-c"],
+            Duration::from_secs(10),
+        )
+        .await?;
+
+        let status = store
+            .list_daemonsets()
+            .await?
+            .into_iter()
+            .find(|d| d.metadata.name == "test-ds-agent")
+            .map(|d| d.status)
+            .ok_or_else(|| anyhow::anyhow!("daemonset disappeared"))?;
+        assert_eq!(status.desired_number_scheduled, 2);
+        anyhow::Ok(())
+    }
+    .await;
+
+    cleanup(&store, "test-ds").await?;
+    result
+}
+
+/// Ensures a daemon pod deleted by hand is recreated on the same node.
+#[serial]
+#[tokio::test]
+async fn test_daemonset_recreates_deleted_pod() -> Result<()> {
+    let (store, _mgr) = setup_store_and_manager().await?;
+    store
+        .insert_node(&make_test_node("test-ds-node-a", "edge"))
+        .await?;
+
+    let ds = make_test_daemonset("test-ds-recreate", "edge");
+    store
+        .insert_daemonset_yaml(&ds.metadata.name, &serde_yaml::to_string(&ds)?)
+        .await?;
+
+    let result = async {
+        let pods = wait_for_daemon_pods(
+            &store,
+            "test-ds-recreate",
+            &["test-ds-node-a"],
+            Duration::from_secs(10),
+        )
+        .await?;
+        store.delete_pod(&pods[0].metadata.name).await?;
+
+        let recreated = wait_for_daemon_pods(
+            &store,
+            "test-ds-recreate",
+            &["test-ds-node-a"],
+            Duration::from_secs(10),
+        )
+        .await?;
+        assert_ne!(recreated[0].metadata.name, pods[0].metadata.name);
+        anyhow::Ok(())
+    }
+    .await;
+
+    cleanup(&store, "test-ds").await?;
+    result
+}
@@ -1,8 +1,18 @@
+use hickory_proto::op::{Message, MessageType, Query, ResponseCode};
+use hickory_proto::rr::rdata::{A, PTR, SRV};
+use hickory_proto::rr::{LowerName, Name, RData, Record, RecordType};
+use hickory_server::authority::{Authority, LookupControlFlow, LookupOptions, LookupRecords};
 use libvault::storage::xline::XlineOptions;
 use rks::api::xlinestore::XlineStore;
-use rks::dns::authority::run_dns_server;
-use rks::protocol::config::load_config;
+use rks::dns::authority::{XlineAuthority, run_dns_server};
+use rks::protocol::config::{DnsConfig, load_config};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
 use std::{fs, sync::Arc};
+use tokio::net::UdpSocket;
+use tokio::time::{sleep, timeout};
 
 use log::{LevelFilter, info};
 use once_cell::sync::OnceCell;
@@ -30,6 +40,16 @@ async fn load_store() -> Arc<XlineStore> {
     Arc::new(XlineStore::new(option).await.expect("connect xline failed"))
 }
 
+fn test_dns_config(port: u16) -> DnsConfig {
+    DnsConfig {
+        port,
+        ttl: 30,
+        forward: true,
+        upstreams: Vec::new(),
+        upstream_timeout_ms: 2000,
+    }
+}
+
 #[tokio::test]
 async fn test_run_dns_server_startup() {
     init_logger();
@@ -49,7 +69,7 @@ async fn test_run_dns_server_startup() {
 
     info!("test get pods: {pods:?}");
     let handle = tokio::spawn(async move {
-        let _ = run_dns_server(store, 5300).await;
+        let _ = run_dns_server(store, test_dns_config(5300)).await;
     });
 
     tokio::signal::ctrl_c()
@@ -105,7 +125,7 @@ subsets:
 
     //query name: test-headless.default.svc.cluster.local.
     let handle = tokio::spawn(async move {
-        let _ = run_dns_server(store, 5300).await;
+        let _ = run_dns_server(store, test_dns_config(5300)).await;
     });
 
     tokio::signal::ctrl_c()
@@ -114,3 +134,311 @@ subsets:
 
     handle.abort();
 }
+
+fn labeled_pod_yaml(name: &str, ip: &str, ready: &str) -> String {
+    format!(
+        r#"apiVersion: v1
+kind: Pod
+metadata:
+    name: {name}
+    namespace: default
+    labels:
+        app: test-srv
+spec:
+    containers: []
+status:
+    podIP: {ip}
+    phase: Running
+    conditions:
+      - type: PodReady
+        status: {ready}
+"#
+    )
+}
+
+async fn lookup_records(authority: &XlineAuthority, name: &str, rtype: RecordType) -> Vec<RData> {
+    let name = LowerName::from_str(name).unwrap();
+    match authority
+        .lookup(&name, rtype, LookupOptions::default())
+        .await
+    {
+        LookupControlFlow::Continue(Ok(LookupRecords::Records { records, .. })) => records
+            .records_without_rrsigs()
+            .map(|r| r.data().clone())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn test_srv_records_for_ready_endpoints() {
+    init_logger();
+
+    let store = load_store().await;
+
+    let svc_yaml = r#"apiVersion: v1
+kind: Service
+metadata:
+    name: test-srv
+    namespace: default
+spec:
+    clusterIP: None
+    selector:
+        matchLabels:
+            app: test-srv
+    ports:
+        - port: 8080
+          targetPort: 80
+          name: http
+          protocol: TCP
+"#;
+    store
+        .insert_service_yaml("test-srv", svc_yaml)
+        .await
+        .expect("insert service error");
+    // no Endpoints object yet, so the authority resolves the selector itself
+    let _ = store.delete_endpoint("test-srv").await;
+
+    let pods = [
+        ("test-srv-0", "10.20.40.1", "True"),
+        ("test-srv-1", "10.20.40.2", "True"),
+        ("test-srv-2", "10.20.40.3", "False"),
+    ];
+    for (name, ip, ready) in pods {
+        store
+            .insert_pod_yaml(name, &labeled_pod_yaml(name, ip, ready))
+            .await
+            .expect("insert pod error");
+    }
+
+    let origin = LowerName::from_str("cluster.local.").unwrap();
+    let authority = XlineAuthority::start(origin, store.clone(), 30)
+        .await
+        .expect("start authority error");
+
+    let srv: Vec<SRV> = lookup_records(
+        &authority,
+        "_http._tcp.test-srv.default.svc.cluster.local.",
+        RecordType::SRV,
+    )
+    .await
+    .into_iter()
+    .filter_map(|data| match data {
+        RData::SRV(srv) => Some(srv),
+        _ => None,
+    })
+    .collect();
+    let mut targets: Vec<String> = srv.iter().map(|s| s.target().to_string()).collect();
+    targets.sort();
+    assert_eq!(
+        targets,
+        vec![
+            "10-20-40-1.default.pod.cluster.local.",
+            "10-20-40-2.default.pod.cluster.local.",
+        ]
+    );
+    assert!(
+        srv.iter()
+            .all(|s| s.priority() == 0 && s.weight() == 50 && s.port() == 80)
+    );
+
+    // the SRV targets resolve through the pod A records
+    let target = lookup_records(&authority, &targets[0], RecordType::A).await;
+    assert_eq!(target.len(), 1);
+
+    // headless service names resolve to the ready pod IPs
+    let a = lookup_records(
+        &authority,
+        "test-srv.default.svc.cluster.local.",
+        RecordType::A,
+    )
+    .await;
+    assert_eq!(a.len(), 2);
+
+    for (name, _, _) in pods {
+        store.delete_pod(name).await.expect("delete pod error");
+    }
+    store
+        .delete_service("test-srv")
+        .await
+        .expect("delete service error");
+}
+
+#[tokio::test]
+async fn test_ptr_record_for_pod_ip() {
+    init_logger();
+
+    let store = load_store().await;
+    let _ = store.delete_pod("test-ptr").await;
+
+    let origin = LowerName::from_str("cluster.local.").unwrap();
+    let authority = XlineAuthority::start(origin, store.clone(), 30)
+        .await
+        .expect("start authority error");
+
+    // the pod is created after startup, so the index is filled by the pod watch
+    store
+        .insert_pod_yaml(
+            "test-ptr",
+            &labeled_pod_yaml("test-ptr", "10.20.40.5", "True"),
+        )
+        .await
+        .expect("insert pod error");
+
+    let ptr_name = "5.40.20.10.in-addr.arpa.";
+    let mut names = Vec::new();
+    for _ in 0..50 {
+        names = lookup_records(&authority, ptr_name, RecordType::PTR).await;
+        if !names.is_empty() {
+            break;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(
+        names,
+        vec![RData::PTR(PTR(Name::from_str(
+            "10-20-40-5.default.pod.cluster.local."
+        )
+        .unwrap()))]
+    );
+
+    // deleting the pod drops it from the reverse index
+    store
+        .delete_pod("test-ptr")
+        .await
+        .expect("delete pod error");
+    for _ in 0..50 {
+        names = lookup_records(&authority, ptr_name, RecordType::PTR).await;
+        if names.is_empty() {
+            break;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    assert!(names.is_empty());
+}
+
+/// Answers `example.test.` with 192.0.2.10 and NXDOMAIN for anything else, recording
+/// every name it was asked about.
+async fn spawn_fake_upstream() -> (SocketAddr, Arc<Mutex<Vec<Name>>>) {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let addr = socket.local_addr().unwrap();
+    let asked = Arc::new(Mutex::new(Vec::new()));
+    let asked_clone = Arc::clone(&asked);
+    tokio::spawn(async move {
+        let mut buf = [0u8; 4096];
+        while let Ok((len, peer)) = socket.recv_from(&mut buf).await {
+            let Ok(request) = Message::from_vec(&buf[..len]) else {
+                continue;
+            };
+            let mut response = Message::new();
+            response
+                .set_id(request.id())
+                .set_message_type(MessageType::Response)
+                .set_op_code(request.op_code())
+                .set_recursion_desired(request.recursion_desired())
+                .set_recursion_available(true)
+                .add_queries(request.queries().to_vec());
+            for query in request.queries() {
+                asked_clone.lock().unwrap().push(query.name().clone());
+                if query.name() == &Name::from_str("example.test.").unwrap()
+                    && query.query_type() == RecordType::A
+                {
+                    response.add_answer(Record::from_rdata(
+                        query.name().clone(),
+                        60,
+                        RData::A(A::new(192, 0, 2, 10)),
+                    ));
+                } else {
+                    response.set_response_code(ResponseCode::NXDomain);
+                }
+            }
+            let _ = socket.send_to(&response.to_vec().unwrap(), peer).await;
+        }
+    });
+    (addr, asked)
+}
+
+async fn query_server(server: SocketAddr, name: &str, rtype: RecordType) -> Message {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let mut request = Message::new();
+    request
+        .set_id(7)
+        .set_recursion_desired(true)
+        .add_query(Query::query(Name::from_str(name).unwrap(), rtype));
+    socket
+        .send_to(&request.to_vec().unwrap(), server)
+        .await
+        .unwrap();
+    let mut buf = [0u8; 4096];
+    let (len, _) = timeout(Duration::from_secs(5), socket.recv_from(&mut buf))
+        .await
+        .expect("dns query timed out")
+        .unwrap();
+    Message::from_vec(&buf[..len]).unwrap()
+}
+
+#[tokio::test]
+async fn test_dns_forwarding_local_hit_and_upstream_miss() {
+    init_logger();
+
+    let store = load_store().await;
+    store
+        .insert_pod_yaml(
+            "test-forward",
+            &labeled_pod_yaml("test-forward", "10.20.40.6", "True"),
+        )
+        .await
+        .expect("insert pod error");
+
+    let (upstream, asked) = spawn_fake_upstream().await;
+    let config = DnsConfig {
+        ttl: 15,
+        upstreams: vec![upstream.to_string()],
+        ..test_dns_config(5310)
+    };
+    let server_store = store.clone();
+    let handle = tokio::spawn(async move {
+        let _ = run_dns_server(server_store, config).await;
+    });
+    sleep(Duration::from_secs(2)).await;
+    let server: SocketAddr = "127.0.0.1:5310".parse().unwrap();
+
+    // cluster names are answered locally with the configured TTL
+    let local = query_server(
+        server,
+        "10-20-40-6.default.pod.cluster.local.",
+        RecordType::A,
+    )
+    .await;
+    assert_eq!(local.answers().len(), 1);
+    assert_eq!(local.answers()[0].ttl(), 15);
+    assert_eq!(local.answers()[0].data(), &RData::A(A::new(10, 20, 40, 6)));
+
+    // unknown cluster names are not forwarded either
+    let missing = query_server(
+        server,
+        "10-20-40-99.default.pod.cluster.local.",
+        RecordType::A,
+    )
+    .await;
+    assert!(missing.answers().is_empty());
+
+    // everything else goes to the upstream
+    let forwarded = query_server(server, "example.test.", RecordType::A).await;
+    assert_eq!(forwarded.answers().len(), 1);
+    assert_eq!(
+        forwarded.answers()[0].data(),
+        &RData::A(A::new(192, 0, 2, 10))
+    );
+
+    let cluster_zone = Name::from_str("cluster.local.").unwrap();
+    let asked = asked.lock().unwrap().clone();
+    assert!(asked.contains(&Name::from_str("example.test.").unwrap()));
+    assert!(!asked.iter().any(|name| cluster_zone.zone_of(name)));
+
+    handle.abort();
+    store
+        .delete_pod("test-forward")
+        .await
+        .expect("delete pod error");
+}
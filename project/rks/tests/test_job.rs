@@ -0,0 +1,279 @@
+use anyhow::Result;
+use libvault::storage::xline::XlineOptions;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::{Instant, sleep};
+
+use common::{
+    CompletionMode, ConditionStatus, ContainerSpec, Job, JobConditionType, JobSpec, ObjectMeta,
+    PodPhase, PodSpec, PodTask, PodTemplateSpec,
+};
+use rks::api::xlinestore::XlineStore;
+use rks::controllers::{ControllerManager, JobController};
+use serial_test::serial;
+
+#[derive(Deserialize)]
+struct TestCfg {
+    xline_config: XlineCfg,
+}
+
+#[derive(Deserialize)]
+struct XlineCfg {
+    endpoints: Vec<String>,
+}
+
+fn load_test_config() -> Result<TestCfg> {
+    let manifest = env!("CARGO_MANIFEST_DIR");
+    let path = std::path::Path::new(manifest).join("tests/config.yaml");
+    let s = std::fs::read_to_string(path)?;
+    let cfg: TestCfg = serde_yaml::from_str(&s)?;
+    Ok(cfg)
+}
+
+async fn setup_store_and_manager() -> Result<(Arc<XlineStore>, Arc<ControllerManager>)> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let cfg = load_test_config()?;
+    let option = XlineOptions::new(cfg.xline_config.endpoints);
+    let store: Arc<XlineStore> = Arc::new(XlineStore::new(option).await?);
+
+    // clean up anything left behind by previous runs
+    cleanup(&store, "test-job").await?;
+
+    let mgr = Arc::new(ControllerManager::new());
+    let job_ctrl = Arc::new(RwLock::new(JobController::new(store.clone())));
+    mgr.clone().register(job_ctrl, 2).await?;
+    mgr.clone().start_watch(store.clone()).await?;
+    sleep(Duration::from_secs(1)).await;
+    Ok((store, mgr))
+}
+
+fn make_test_job(name: &str, completions: i32, parallelism: i32, backoff_limit: i32) -> Job {
+    Job {
+        api_version: "v1".to_string(),
+        kind: "Job".to_string(),
+        metadata: ObjectMeta {
+            name: name.to_string(),
+            namespace: "default".to_string(),
+            ..Default::default()
+        },
+        spec: JobSpec {
+            completion_mode: CompletionMode::NonIndexed,
+            completions,
+            parallelism,
+            backoff_limit,
+            active_deadline_seconds: None,
+            ttl_seconds_after_finished: None,
+            template: PodTemplateSpec {
+                metadata: ObjectMeta {
+                    name: format!("{}-pod-template", name),
+                    namespace: "default".to_string(),
+                    labels: HashMap::from([("job".to_string(), name.to_string())]),
+                    ..Default::default()
+                },
+                spec: PodSpec {
+                    containers: vec![ContainerSpec {
+                        name: "work".to_string(),
+                        image: "busybox:latest".to_string(),
+                        ports: Vec::new(),
+                        args: Vec::new(),
+                        resources: None,
+                        liveness_probe: None,
+                        readiness_probe: None,
+                        startup_probe: None,
+                        security_context: None,
+                        env: None,
+                        volume_mounts: None,
+                        command: None,
+                        working_dir: None,
+                    }],
+                    ..Default::default()
+                },
+            },
+        },
+        status: Default::default(),
+    }
+}
+
+async fn cleanup(store: &Arc<XlineStore>, prefix: &str) -> Result<()> {
+    for job in store.list_jobs().await? {
+        if job.metadata.name.starts_with(prefix) {
+            store.delete_job(&job.metadata.name).await?;
+        }
+    }
+    for pod in store.list_pods().await? {
+        if pod.metadata.name.starts_with(prefix) {
+            store.delete_pod(&pod.metadata.name).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Waits until the Job has exactly `expected` pods in `phase`.
+async fn wait_for_job_pods(
+    store: &Arc<XlineStore>,
+    job_name: &str,
+    phase: PodPhase,
+    expected: usize,
+    timeout: Duration,
+) -> Result<Vec<PodTask>> {
+    let start = Instant::now();
+    loop {
+        let pods: Vec<PodTask> = store
+            .list_pods()
+            .await?
+            .into_iter()
+            .filter(|p| p.metadata.name.starts_with(job_name) && p.status.phase == phase)
+            .collect();
+        if pods.len() == expected {
+            return Ok(pods);
+        }
+        if Instant::now().duration_since(start) > timeout {
+            return Err(anyhow::anyhow!(
+                "timed out waiting for {} {:?} pods of job {} (found {})",
+                expected,
+                phase,
+                job_name,
+                pods.len()
+            ));
+        }
+        sleep(Duration::from_millis(500)).await;
+    }
+}
+
+/// Simulates the kubelet reporting a terminal phase for the pod.
+async fn finish_pod(store: &Arc<XlineStore>, pod: &PodTask, phase: PodPhase) -> Result<()> {
+    let mut pod = pod.clone();
+    pod.status.phase = phase;
+    store
+        .insert_pod_yaml(&pod.metadata.name, &serde_yaml::to_string(&pod)?)
+        .await
+}
+
+async fn wait_for_job_condition(
+    store: &Arc<XlineStore>,
+    job_name: &str,
+    condition: JobConditionType,
+    timeout: Duration,
+) -> Result<Job> {
+    let start = Instant::now();
+    loop {
+        if let Some(job) = store.get_job(job_name).await?
+            && job
+                .status
+                .conditions
+                .iter()
+                .any(|c| c.condition_type == condition && matches!(c.status, ConditionStatus::True))
+        {
+            return Ok(job);
+        }
+        if Instant::now().duration_since(start) > timeout {
+            return Err(anyhow::anyhow!(
+                "timed out waiting for job {} to reach {:?}",
+                job_name,
+                condition
+            ));
+        }
+        sleep(Duration::from_millis(500)).await;
+    }
+}
+
+/// Ensures a Job runs pods one at a time (parallelism 1) until two have succeeded.
+#[serial]
+#[tokio::test]
+async fn test_job_completes_after_required_successes() -> Result<()> {
+    let (store, _mgr) = setup_store_and_manager().await?;
+    let job = make_test_job("test-job-complete", 2, 1, 3);
+    store
+        .insert_job_yaml(&job.metadata.name, &serde_yaml::to_string(&job)?)
+        .await?;
+
+    let result = async {
+        for succeeded in 0..2 {
+            let pending = wait_for_job_pods(
+                &store,
+                "test-job-complete",
+                PodPhase::Pending,
+                1,
+                Duration::from_secs(10),
+            )
+            .await?;
+            finish_pod(&store, &pending[0], PodPhase::Succeeded).await?;
+            wait_for_job_pods(
+                &store,
+                "test-job-complete",
+                PodPhase::Succeeded,
+                succeeded + 1,
+                Duration::from_secs(10),
+            )
+            .await?;
+        }
+
+        let job = wait_for_job_condition(
+            &store,
+            "test-job-complete",
+            JobConditionType::Complete,
+            Duration::from_secs(10),
+        )
+        .await?;
+        assert_eq!(job.status.succeeded, 2);
+        assert!(job.status.completion_time.is_some());
+        anyhow::Ok(())
+    }
+    .await;
+
+    cleanup(&store, "test-job").await?;
+    result
+}
+
+/// Ensures failed pods are replaced until the backoff limit is exceeded.
+#[serial]
+#[tokio::test]
+async fn test_job_recreates_failed_pods_until_backoff_limit() -> Result<()> {
+    let (store, _mgr) = setup_store_and_manager().await?;
+    let job = make_test_job("test-job-backoff", 1, 1, 1);
+    store
+        .insert_job_yaml(&job.metadata.name, &serde_yaml::to_string(&job)?)
+        .await?;
+
+    let result = async {
+        // failures up to backoffLimit get a replacement pod
+        let first = wait_for_job_pods(
+            &store,
+            "test-job-backoff",
+            PodPhase::Pending,
+            1,
+            Duration::from_secs(10),
+        )
+        .await?;
+        finish_pod(&store, &first[0], PodPhase::Failed).await?;
+
+        let second = wait_for_job_pods(
+            &store,
+            "test-job-backoff",
+            PodPhase::Pending,
+            1,
+            Duration::from_secs(10),
+        )
+        .await?;
+        assert_ne!(second[0].metadata.name, first[0].metadata.name);
+        finish_pod(&store, &second[0], PodPhase::Failed).await?;
+
+        let job = wait_for_job_condition(
+            &store,
+            "test-job-backoff",
+            JobConditionType::Failed,
+            Duration::from_secs(10),
+        )
+        .await?;
+        assert_eq!(job.status.failed, 2);
+        anyhow::Ok(())
+    }
+    .await;
+
+    cleanup(&store, "test-job").await?;
+    result
+}
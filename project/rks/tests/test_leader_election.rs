@@ -0,0 +1,172 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use common::ResourceKind;
+use libvault::storage::xline::XlineOptions;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::{Instant, sleep};
+
+use rks::api::xlinestore::XlineStore;
+use rks::controllers::leader_election::LeaderElectionConfig;
+use rks::controllers::manager::ResourceWatchResponse;
+use rks::controllers::{Controller, ControllerManager};
+use serial_test::serial;
+
+#[derive(Deserialize)]
+struct TestCfg {
+    xline_config: XlineCfg,
+}
+
+#[derive(Deserialize)]
+struct XlineCfg {
+    endpoints: Vec<String>,
+}
+
+async fn setup_store() -> Result<Arc<XlineStore>> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let manifest = env!("CARGO_MANIFEST_DIR");
+    let path = std::path::Path::new(manifest).join("tests/config.yaml");
+    let cfg: TestCfg = serde_yaml::from_str(&std::fs::read_to_string(path)?)?;
+    let option = XlineOptions::new(cfg.xline_config.endpoints);
+    Ok(Arc::new(XlineStore::new(option).await?))
+}
+
+fn election_config(lock_key: &str, identity: &str) -> LeaderElectionConfig {
+    LeaderElectionConfig {
+        lock_key: lock_key.to_string(),
+        identity: identity.to_string(),
+        lease_ttl: Duration::from_secs(3),
+        retry_period: Duration::from_millis(200),
+    }
+}
+
+async fn delete_lock(store: &XlineStore, lock_key: &str) -> Result<()> {
+    let mut client = store.client().await.clone();
+    client.delete(lock_key, None).await?;
+    Ok(())
+}
+
+/// Counts the Node events it is handed.
+struct CountingController {
+    handled: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl Controller for CountingController {
+    fn name(&self) -> &'static str {
+        "counting-controller"
+    }
+
+    fn watch_resources(&self) -> Vec<ResourceKind> {
+        vec![ResourceKind::Node]
+    }
+
+    async fn handle_watch_response(&mut self, _response: &ResourceWatchResponse) -> Result<()> {
+        self.handled.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+async fn wait_until(timeout: Duration, mut cond: impl FnMut() -> bool) -> bool {
+    let start = Instant::now();
+    while Instant::now().duration_since(start) < timeout {
+        if cond() {
+            return true;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    cond()
+}
+
+/// Ensures only one manager leads and a standby takes over once the leader stops.
+#[serial]
+#[tokio::test]
+async fn test_standby_takes_over_after_leader_stops() -> Result<()> {
+    let store = setup_store().await?;
+    let lock_key = "/registry/leases/test-leader-takeover";
+    delete_lock(&store, lock_key).await?;
+
+    let first = Arc::new(ControllerManager::new());
+    first
+        .clone()
+        .run_with_leader_election_config(store.clone(), election_config(lock_key, "first"))
+        .await?;
+    assert!(first.is_leader());
+    assert_eq!(
+        store.get_lock_holder(lock_key).await?.as_deref(),
+        Some("first")
+    );
+
+    let second = Arc::new(ControllerManager::new());
+    let standby = tokio::spawn(
+        second
+            .clone()
+            .run_with_leader_election_config(store.clone(), election_config(lock_key, "second")),
+    );
+
+    // the leader keeps renewing past its TTL, so the standby keeps waiting
+    sleep(Duration::from_secs(5)).await;
+    assert!(!standby.is_finished());
+    assert!(!second.is_leader());
+    assert!(first.is_leader());
+
+    first.shutdown();
+    assert!(wait_until(Duration::from_secs(5), || standby.is_finished()).await);
+    standby.await??;
+    assert!(second.is_leader());
+    assert!(!first.is_leader());
+    assert_eq!(
+        store.get_lock_holder(lock_key).await?.as_deref(),
+        Some("second")
+    );
+
+    second.shutdown();
+    assert!(wait_until(Duration::from_secs(5), || !second.is_leader()).await);
+    Ok(())
+}
+
+/// Ensures a leader whose lock is taken away stops its controllers.
+#[serial]
+#[tokio::test]
+async fn test_losing_leadership_stops_controllers() -> Result<()> {
+    let store = setup_store().await?;
+    let lock_key = "/registry/leases/test-leader-lost";
+    delete_lock(&store, lock_key).await?;
+
+    let handled = Arc::new(AtomicUsize::new(0));
+    let mgr = Arc::new(ControllerManager::new());
+    let ctrl = CountingController {
+        handled: handled.clone(),
+    };
+    mgr.clone().register(Arc::new(RwLock::new(ctrl)), 1).await?;
+    mgr.clone()
+        .run_with_leader_election_config(store.clone(), election_config(lock_key, "leader"))
+        .await?;
+    assert!(mgr.is_leader());
+
+    let node_yaml = "apiVersion: v1\nkind: Node\nmetadata:\n  name: test-leader-node\n";
+    store
+        .insert_node_yaml("test-leader-node", node_yaml)
+        .await?;
+    let handled_any = || handled.load(Ordering::SeqCst) > 0;
+    assert!(wait_until(Duration::from_secs(5), handled_any).await);
+
+    // someone else now owns the lock
+    delete_lock(&store, lock_key).await?;
+    assert!(wait_until(Duration::from_secs(5), || !mgr.is_leader()).await);
+
+    let before = handled.load(Ordering::SeqCst);
+    let node_yaml = format!("{node_yaml}  labels:\n    v: \"2\"\n");
+    store
+        .insert_node_yaml("test-leader-node", &node_yaml)
+        .await?;
+    sleep(Duration::from_secs(2)).await;
+    assert_eq!(handled.load(Ordering::SeqCst), before);
+
+    store.delete_node("test-leader-node").await?;
+    Ok(())
+}
@@ -0,0 +1,294 @@
+use anyhow::Result;
+use chrono::Utc;
+use libvault::storage::xline::XlineOptions;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::{Instant, sleep};
+
+use common::{
+    ConditionStatus, ContainerSpec, LabelSelector, Node, NodeCondition, NodeConditionType,
+    NodeSpec, NodeStatus, ObjectMeta, PodSpec, PodTask, PodTemplateSpec, ReplicaSet,
+    ReplicaSetSpec, TaintKey,
+};
+use rks::api::xlinestore::XlineStore;
+use rks::controllers::{ControllerManager, NodeController, ReplicaSetController};
+use serial_test::serial;
+
+#[derive(Deserialize)]
+struct TestCfg {
+    xline_config: XlineCfg,
+}
+
+#[derive(Deserialize)]
+struct XlineCfg {
+    endpoints: Vec<String>,
+}
+
+const GRACE: Duration = Duration::from_secs(3);
+
+async fn setup_store_and_manager() -> Result<(Arc<XlineStore>, Arc<ControllerManager>)> {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let manifest = env!("CARGO_MANIFEST_DIR");
+    let path = std::path::Path::new(manifest).join("tests/config.yaml");
+    let cfg: TestCfg = serde_yaml::from_str(&std::fs::read_to_string(path)?)?;
+    let option = XlineOptions::new(cfg.xline_config.endpoints);
+    let store: Arc<XlineStore> = Arc::new(XlineStore::new(option).await?);
+
+    cleanup(&store).await?;
+
+    let mgr = Arc::new(ControllerManager::new());
+    let rs_ctrl = ReplicaSetController::new(store.clone());
+    let node_ctrl = NodeController::new(store.clone(), GRACE);
+    mgr.clone()
+        .register(Arc::new(RwLock::new(rs_ctrl)), 2)
+        .await?;
+    mgr.clone()
+        .register(Arc::new(RwLock::new(node_ctrl)), 1)
+        .await?;
+    mgr.clone().start_watch(store.clone()).await?;
+    sleep(Duration::from_secs(1)).await;
+    Ok((store, mgr))
+}
+
+async fn cleanup(store: &XlineStore) -> Result<()> {
+    for rs in store.list_replicasets().await? {
+        if rs.metadata.name.starts_with("test-nc") {
+            store
+                .delete_namespaced_replicaset(&rs.metadata.namespace, &rs.metadata.name)
+                .await?;
+        }
+    }
+    for pod in store.list_pods().await? {
+        if pod.metadata.name.starts_with("test-nc") {
+            store
+                .delete_namespaced_pod(&pod.metadata.namespace, &pod.metadata.name)
+                .await?;
+        }
+    }
+    for node in store.list_nodes().await? {
+        if node.metadata.name.starts_with("test-nc") {
+            store.delete_node(&node.metadata.name).await?;
+        }
+    }
+    Ok(())
+}
+
+fn node_status() -> NodeStatus {
+    NodeStatus {
+        capacity: HashMap::new(),
+        allocatable: HashMap::new(),
+        addresses: vec![],
+        conditions: vec![NodeCondition {
+            condition_type: NodeConditionType::Ready,
+            status: ConditionStatus::True,
+            last_heartbeat_time: Some(Utc::now().to_rfc3339()),
+        }],
+    }
+}
+
+fn make_test_node(name: &str) -> Node {
+    Node {
+        api_version: "v1".to_string(),
+        kind: "Node".to_string(),
+        metadata: ObjectMeta {
+            name: name.to_string(),
+            namespace: "".to_string(),
+            ..Default::default()
+        },
+        spec: NodeSpec {
+            pod_cidr: "10.244.0.0/24".to_string(),
+            taints: vec![],
+        },
+        status: node_status(),
+    }
+}
+
+fn make_test_replicaset(name: &str, replicas: i32) -> ReplicaSet {
+    let labels = HashMap::from([("app".to_string(), name.to_string())]);
+    ReplicaSet {
+        api_version: "v1".to_string(),
+        kind: "ReplicaSet".to_string(),
+        metadata: ObjectMeta {
+            name: name.to_string(),
+            namespace: "default".to_string(),
+            ..Default::default()
+        },
+        spec: ReplicaSetSpec {
+            replicas,
+            selector: LabelSelector {
+                match_labels: labels.clone(),
+                match_expressions: Vec::new(),
+            },
+            template: PodTemplateSpec {
+                metadata: ObjectMeta {
+                    name: format!("{}-pod-template", name),
+                    namespace: "default".to_string(),
+                    labels,
+                    ..Default::default()
+                },
+                spec: PodSpec {
+                    containers: vec![ContainerSpec {
+                        name: "c".to_string(),
+                        image: "busybox:latest".to_string(),
+                        ports: Vec::new(),
+                        args: Vec::new(),
+                        resources: None,
+                        liveness_probe: None,
+                        readiness_probe: None,
+                        startup_probe: None,
+                        security_context: None,
+                        env: None,
+                        volume_mounts: None,
+                        command: None,
+                        working_dir: None,
+                    }],
+                    ..Default::default()
+                },
+            },
+        },
+        status: Default::default(),
+    }
+}
+
+async fn wait_for_pods(
+    store: &XlineStore,
+    timeout: Duration,
+    cond: impl Fn(&[PodTask]) -> bool,
+) -> Result<Vec<PodTask>> {
+    let start = Instant::now();
+    loop {
+        let pods: Vec<PodTask> = store
+            .list_pods()
+            .await?
+            .into_iter()
+            .filter(|p| p.metadata.name.starts_with("test-nc-rs"))
+            .collect();
+        if cond(&pods) {
+            return Ok(pods);
+        }
+        if start.elapsed() > timeout {
+            let names: Vec<_> = pods
+                .iter()
+                .map(|p| format!("{}@{:?}", p.metadata.name, p.spec.node_name))
+                .collect();
+            anyhow::bail!("timed out waiting for pods, found [{}]", names.join(", "));
+        }
+        sleep(Duration::from_millis(200)).await;
+    }
+}
+
+fn ready_status(node: &Node) -> Option<ConditionStatus> {
+    node.ready_condition().map(|cond| cond.status)
+}
+
+/// Ensures that a node which stops heartbeating is marked NotReady and that its pods
+/// are evicted and recreated by their ReplicaSet, while a heartbeating node stays Ready.
+#[serial]
+#[tokio::test]
+async fn test_dead_node_pods_are_rescheduled() -> Result<()> {
+    let (store, _mgr) = setup_store_and_manager().await?;
+
+    let rs = make_test_replicaset("test-nc-rs", 2);
+    store
+        .insert_replicaset_yaml(&rs.metadata.name, &serde_yaml::to_string(&rs)?)
+        .await?;
+
+    let result = async {
+        // bind the ReplicaSet's pods to the node that is about to die
+        let pods = wait_for_pods(&store, Duration::from_secs(10), |p| p.len() == 2).await?;
+        for mut pod in pods.clone() {
+            pod.spec.node_name = Some("test-nc-dead".to_string());
+            store
+                .insert_namespaced_pod_yaml(
+                    &pod.metadata.namespace,
+                    &pod.metadata.name,
+                    &serde_yaml::to_string(&pod)?,
+                )
+                .await?;
+        }
+
+        store.insert_node(&make_test_node("test-nc-alive")).await?;
+        store.insert_node(&make_test_node("test-nc-dead")).await?;
+        let heartbeat_store = store.clone();
+        let heartbeats = tokio::spawn(async move {
+            loop {
+                let _ = heartbeat_store
+                    .record_node_heartbeat("test-nc-alive", node_status())
+                    .await;
+                sleep(Duration::from_millis(500)).await;
+            }
+        });
+
+        let evicted: Vec<String> = pods.iter().map(|p| p.metadata.name.clone()).collect();
+        let replaced = wait_for_pods(&store, GRACE * 4, |pods| {
+            pods.len() == 2 && pods.iter().all(|p| !evicted.contains(&p.metadata.name))
+        })
+        .await;
+        heartbeats.abort();
+        let replaced = replaced?;
+
+        assert!(
+            replaced
+                .iter()
+                .all(|p| p.spec.node_name.as_deref() != Some("test-nc-dead")),
+            "replacement pods must not be bound to the dead node"
+        );
+
+        let dead = store
+            .get_node("test-nc-dead")
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("dead node disappeared"))?;
+        assert_eq!(ready_status(&dead), Some(ConditionStatus::Unknown));
+        assert!(
+            dead.spec
+                .taints
+                .iter()
+                .any(|t| t.key == TaintKey::NodeNotReady),
+            "dead node should be tainted NotReady"
+        );
+
+        let alive = store
+            .get_node("test-nc-alive")
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("alive node disappeared"))?;
+        assert_eq!(ready_status(&alive), Some(ConditionStatus::True));
+        anyhow::Ok(())
+    }
+    .await;
+
+    cleanup(&store).await?;
+    result
+}
+
+/// Ensures the heartbeat timers stop with the manager, so a node is not marked
+/// NotReady by a controller that is no longer running.
+#[serial]
+#[tokio::test]
+async fn test_shutdown_aborts_heartbeat_timers() -> Result<()> {
+    let (store, mgr) = setup_store_and_manager().await?;
+
+    let result = async {
+        store
+            .insert_node(&make_test_node("test-nc-stopped"))
+            .await?;
+        // let the controller arm the node's timer before stopping it
+        sleep(Duration::from_secs(1)).await;
+        mgr.shutdown();
+
+        sleep(GRACE * 2).await;
+        let node = store
+            .get_node("test-nc-stopped")
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("node disappeared"))?;
+        assert_eq!(ready_status(&node), Some(ConditionStatus::True));
+        anyhow::Ok(())
+    }
+    .await;
+
+    cleanup(&store).await?;
+    result
+}
@@ -116,7 +116,9 @@ async fn cleanup_pods_by_prefix(store: &Arc<XlineStore>, prefix: &str) -> Result
     let pods = store.list_pods().await?;
     for p in pods {
         if p.metadata.name.contains(prefix) {
-            let _ = store.delete_pod(&p.metadata.name).await;
+            let _ = store
+                .delete_namespaced_pod(&p.metadata.namespace, &p.metadata.name)
+                .await;
         }
     }
     Ok(())
@@ -126,7 +128,9 @@ async fn cleanup_replicasets_by_prefix(store: &Arc<XlineStore>, prefix: &str) ->
     let replicasets = store.list_replicasets().await?;
     for rs in replicasets {
         if rs.metadata.name.contains(prefix) {
-            let _ = store.delete_replicaset(&rs.metadata.name).await;
+            let _ = store
+                .delete_namespaced_replicaset(&rs.metadata.namespace, &rs.metadata.name)
+                .await;
         }
     }
     Ok(())
@@ -430,6 +434,52 @@ async fn test_two_replicasets_independent() -> Result<()> {
     Ok(())
 }
 
+/// Ensures that ReplicaSets sharing a name in different namespaces do not collide.
+#[serial]
+#[tokio::test]
+async fn test_same_name_replicasets_in_different_namespaces() -> Result<()> {
+    let (store, _mgr, _rs_ctrl) = setup_store_and_manager().await?;
+
+    for (namespace, replicas) in [("test-rs-ns-a", 2), ("test-rs-ns-b", 1)] {
+        let mut rs = make_test_replicaset("test-rs-shared", replicas);
+        rs.metadata.namespace = namespace.to_string();
+        rs.spec.template.metadata.namespace = namespace.to_string();
+        store
+            .insert_namespaced_replicaset_yaml(
+                namespace,
+                &rs.metadata.name,
+                &serde_yaml::to_string(&rs)?,
+            )
+            .await?;
+    }
+
+    let result = async {
+        wait_for_pod_prefix_count(&store, "test-rs-shared", 3, Duration::from_secs(10)).await?;
+        let pods_a = store.list_pods_in_namespace("test-rs-ns-a").await?;
+        let pods_b = store.list_pods_in_namespace("test-rs-ns-b").await?;
+        assert_eq!(pods_a.len(), 2, "ReplicaSet in ns-a should manage 2 pods");
+        assert_eq!(pods_b.len(), 1, "ReplicaSet in ns-b should manage 1 pod");
+
+        let rs_a = store
+            .get_namespaced_replicaset_yaml("test-rs-ns-a", "test-rs-shared")
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("ReplicaSet in ns-a disappeared"))?;
+        let rs_a: ReplicaSet = serde_yaml::from_str(&rs_a)?;
+        assert!(pods_a.iter().all(|p| {
+            p.metadata
+                .owner_references
+                .as_ref()
+                .is_some_and(|owners| owners.iter().any(|o| o.uid == rs_a.metadata.uid))
+        }));
+        anyhow::Ok(())
+    }
+    .await;
+
+    let _ = cleanup_replicasets_by_prefix(&store, "test-rs-shared").await;
+    let _ = cleanup_pods_by_prefix(&store, "test-rs-shared").await;
+    result
+}
+
 /// Ensures that a ReplicaSet adopts orphan pods matching its selector by adding ownerReferences.
 #[serial]
 #[tokio::test]
@@ -540,3 +590,118 @@ async fn test_replicaset_adopts_orphan_pods() -> Result<()> {
 
     Ok(())
 }
+
+fn owned_by(pod: &PodTask, rs: &ReplicaSet) -> bool {
+    pod.metadata
+        .owner_references
+        .as_ref()
+        .is_some_and(|owners| owners.iter().any(|o| o.uid == rs.metadata.uid))
+}
+
+/// Ensures that ReplicaSets with overlapping selectors never share or steal Pods.
+#[serial]
+#[tokio::test]
+async fn test_overlapping_replicasets_own_disjoint_pods() -> Result<()> {
+    let (store, _mgr, _rs_ctrl) = setup_store_and_manager().await?;
+
+    // both select app=test
+    let rs_a = make_test_replicaset("test-rs-overlap-a", 2);
+    let rs_b = make_test_replicaset("test-rs-overlap-b", 1);
+    for rs in [&rs_a, &rs_b] {
+        store
+            .insert_replicaset_yaml(&rs.metadata.name, &serde_yaml::to_string(rs)?)
+            .await?;
+    }
+
+    let result = async {
+        wait_for_pod_prefix_count(&store, "test-rs-overlap", 3, Duration::from_secs(10)).await?;
+        // give both controllers time to react to each other's pods
+        sleep(Duration::from_secs(2)).await;
+        let pods =
+            wait_for_pod_prefix_count(&store, "test-rs-overlap", 3, Duration::from_secs(5)).await?;
+
+        for pod in &pods {
+            let controllers = pod
+                .metadata
+                .owner_references
+                .as_ref()
+                .map(|owners| owners.iter().filter(|o| o.controller).count())
+                .unwrap_or(0);
+            assert_eq!(
+                controllers, 1,
+                "pod {} should have one controller",
+                pod.metadata.name
+            );
+        }
+        let owned_a = pods.iter().filter(|p| owned_by(p, &rs_a)).count();
+        let owned_b = pods.iter().filter(|p| owned_by(p, &rs_b)).count();
+        assert_eq!(owned_a, 2, "RS A should own 2 pods");
+        assert_eq!(owned_b, 1, "RS B should own 1 pod");
+        anyhow::Ok(())
+    }
+    .await;
+
+    let _ = cleanup_replicasets_by_prefix(&store, "test-rs-overlap").await;
+    let _ = cleanup_pods_by_prefix(&store, "test-rs-overlap").await;
+    result
+}
+
+/// Ensures that a Pod relabeled out of the selector is released and replaced.
+#[serial]
+#[tokio::test]
+async fn test_replicaset_releases_relabeled_pod() -> Result<()> {
+    let (store, _mgr, _rs_ctrl) = setup_store_and_manager().await?;
+
+    let rs = make_test_replicaset("test-rs-release", 2);
+    store
+        .insert_replicaset_yaml(&rs.metadata.name, &serde_yaml::to_string(&rs)?)
+        .await?;
+
+    let result = async {
+        let pods = wait_for_pod_prefix_count(&store, "test-rs-release", 2, Duration::from_secs(10))
+            .await?;
+        let mut relabeled = pods[0].clone();
+        relabeled
+            .metadata
+            .labels
+            .insert("app".to_string(), "test-released".to_string());
+        store
+            .insert_pod_yaml(
+                &relabeled.metadata.name,
+                &serde_yaml::to_string(&relabeled)?,
+            )
+            .await?;
+
+        // the released pod stays, and a replacement is created
+        let pods = wait_for_pod_prefix_count(&store, "test-rs-release", 3, Duration::from_secs(10))
+            .await?;
+        let start = Instant::now();
+        let released = loop {
+            let pod = store
+                .get_pod(&relabeled.metadata.name)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("relabeled pod was deleted"))?;
+            if !owned_by(&pod, &rs) || start.elapsed() > Duration::from_secs(10) {
+                break pod;
+            }
+            sleep(Duration::from_millis(500)).await;
+        };
+        assert!(
+            !owned_by(&released, &rs),
+            "relabeled pod should be released"
+        );
+
+        let owned = pods
+            .iter()
+            .filter(|p| p.metadata.name != relabeled.metadata.name)
+            .filter(|p| owned_by(p, &rs))
+            .count();
+        assert_eq!(owned, 2, "RS should own 2 matching pods");
+        anyhow::Ok(())
+    }
+    .await;
+
+    let _ = store.delete_replicaset(&rs.metadata.name).await;
+    let _ = cleanup_pods_by_prefix(&store, "test-rs-release").await;
+    result
+}
@@ -1,7 +1,7 @@
 use etcd_client::EventType;
 use futures::StreamExt;
 use libvault::storage::xline::XlineOptions;
-use rks::api::xlinestore::XlineStore;
+use rks::api::xlinestore::{WatchEvent, XlineStore};
 use rks::protocol::config::load_config;
 use serial_test::serial;
 use std::sync::Arc;
@@ -25,7 +25,7 @@ async fn test_watch_pods_create_update_delete() {
     let store = load_store().await;
 
     let (_items, rev) = store.pods_snapshot_with_rev().await.unwrap();
-    let (_watcher, mut stream) = store.watch_pods(rev + 1).await.unwrap();
+    let (_watcher, mut stream) = store.watch_pods_raw(rev + 1).await.unwrap();
 
     let pod_name = format!(
         "watch-pod-{}",
@@ -76,7 +76,7 @@ async fn test_watch_pods_create_update_delete() {
 async fn test_watch_pods_multiple_updates_order() {
     let store = load_store().await;
     let (_items, rev) = store.pods_snapshot_with_rev().await.unwrap();
-    let (_watcher, mut stream) = store.watch_pods(rev + 1).await.unwrap();
+    let (_watcher, mut stream) = store.watch_pods_raw(rev + 1).await.unwrap();
 
     let pod_name = format!(
         "multi-update-{}",
@@ -119,7 +119,7 @@ async fn test_watch_pods_multiple_updates_order() {
 #[serial]
 async fn test_watch_from_stale_revision() {
     let store = load_store().await;
-    let result = store.watch_pods(1).await;
+    let result = store.watch_pods_raw(1).await;
     assert!(
         result.is_err(),
         "expected error when watching from stale rev"
@@ -131,7 +131,7 @@ async fn test_watch_from_stale_revision() {
 async fn test_watch_cancel() {
     let store = load_store().await;
     let (_items, rev) = store.pods_snapshot_with_rev().await.unwrap();
-    let (watcher, mut stream) = store.watch_pods(rev + 1).await.unwrap();
+    let (watcher, mut stream) = store.watch_pods_raw(rev + 1).await.unwrap();
 
     drop(watcher);
 
@@ -157,7 +157,7 @@ async fn test_watch_cancel() {
 async fn test_watch_reconnect_manual() {
     let store = load_store().await;
     let (_items, rev) = store.pods_snapshot_with_rev().await.unwrap();
-    let (_watcher, mut stream) = store.watch_pods(rev + 1).await.unwrap();
+    let (_watcher, mut stream) = store.watch_pods_raw(rev + 1).await.unwrap();
 
     let pod_name = format!(
         "reconnect-pod-{}",
@@ -180,7 +180,7 @@ async fn test_watch_reconnect_manual() {
 async fn test_watch_backpressure() {
     let store = load_store().await;
     let (_items, rev) = store.pods_snapshot_with_rev().await.unwrap();
-    let (_watcher, mut stream) = store.watch_pods(rev + 1).await.unwrap();
+    let (_watcher, mut stream) = store.watch_pods_raw(rev + 1).await.unwrap();
 
     let pod_name = format!(
         "backpressure-pod-{}",
@@ -217,8 +217,8 @@ async fn test_watch_multiple_resources() {
     let store = load_store().await;
     let (_items, rev) = store.pods_snapshot_with_rev().await.unwrap();
 
-    let (_watcher1, mut stream1) = store.watch_pods(rev + 1).await.unwrap();
-    let (_watcher2, mut stream2) = store.watch_pods(rev + 1).await.unwrap();
+    let (_watcher1, mut stream1) = store.watch_pods_raw(rev + 1).await.unwrap();
+    let (_watcher2, mut stream2) = store.watch_pods_raw(rev + 1).await.unwrap();
 
     let pod_name = format!(
         "multi-prefix-pod-{}",
@@ -246,3 +246,57 @@ async fn test_watch_multiple_resources() {
     assert_eq!(ev1.event_type(), EventType::Put);
     assert_eq!(ev2.event_type(), EventType::Put);
 }
+
+#[tokio::test]
+#[serial]
+async fn test_watch_pods_events_resume_from_revision() {
+    let store = load_store().await;
+
+    let (_items, rev) = store.pods_snapshot_with_rev().await.unwrap();
+    let mut events = store.watch_pods(rev + 1).await.unwrap();
+
+    let pod_name = format!(
+        "watch-ev-pod-{}",
+        chrono::Utc::now().timestamp_nanos_opt().unwrap()
+    );
+    let yaml_v1 = format!(
+        "apiVersion: v1\nkind: Pod\nmetadata:\n  name: {}\n",
+        pod_name
+    );
+    let yaml_v2 = format!(
+        "apiVersion: v1\nkind: Pod\nmetadata:\n  name: {}\n  labels:\n    v: \"2\"\n",
+        pod_name
+    );
+    store.insert_pod_yaml(&pod_name, &yaml_v1).await.unwrap();
+    store.insert_pod_yaml(&pod_name, &yaml_v2).await.unwrap();
+    store.delete_pod(&pod_name).await.unwrap();
+
+    let mut received = Vec::new();
+    for _ in 0..3 {
+        let ev = timeout(Duration::from_secs(5), events.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(ev.key, format!("default/{pod_name}"));
+        received.push(ev);
+    }
+    assert!(matches!(received[0].event, WatchEvent::Add { .. }));
+    assert!(matches!(
+        &received[1].event,
+        WatchEvent::Update { new_yaml, .. } if *new_yaml == yaml_v2
+    ));
+    assert!(matches!(received[2].event, WatchEvent::Delete { .. }));
+    assert!(received.windows(2).all(|w| w[0].revision < w[1].revision));
+
+    // resuming right after the first event replays only what followed it
+    let mut resumed = store.watch_pods(received[0].revision + 1).await.unwrap();
+    for expected in &received[1..] {
+        let ev = timeout(Duration::from_secs(5), resumed.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(ev.revision, expected.revision);
+    }
+}
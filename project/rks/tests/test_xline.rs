@@ -1,5 +1,6 @@
+use common::{ObjectMeta, PodSpec, PodTask};
 use libvault::storage::xline::XlineOptions;
-use rks::api::xlinestore::XlineStore;
+use rks::api::xlinestore::{RevisionConflict, XlineStore};
 use rks::protocol::config::load_config;
 use std::sync::Arc;
 
@@ -44,7 +45,7 @@ async fn test_xline_rw() {
 
     // List pods and check presence
     let pods = store.list_pod_names().await.expect("List pods failed");
-    assert!(pods.contains(&pod_name));
+    assert!(pods.contains(&format!("default/{pod_name}")));
 
     // Clean up
     store
@@ -52,3 +53,140 @@ async fn test_xline_rw() {
         .await
         .expect("Delete pod failed");
 }
+
+#[tokio::test]
+async fn test_xline_pods_are_namespaced() {
+    let store = load_store().await;
+
+    let pod_name = format!(
+        "pod-ns-test-{}",
+        chrono::Utc::now().timestamp_nanos_opt().unwrap()
+    );
+    let yaml_in = |ns: &str| {
+        let pod = PodTask {
+            api_version: "v1".to_string(),
+            kind: "Pod".to_string(),
+            metadata: ObjectMeta {
+                name: pod_name.clone(),
+                namespace: ns.to_string(),
+                ..Default::default()
+            },
+            spec: PodSpec::default(),
+            status: Default::default(),
+        };
+        serde_yaml::to_string(&pod).unwrap()
+    };
+
+    let pods = ["ns-a", "ns-b"].map(|ns| (ns, yaml_in(ns)));
+
+    // the same name in two namespaces must not collide
+    for (ns, yaml) in &pods {
+        store
+            .insert_namespaced_pod_yaml(ns, &pod_name, yaml)
+            .await
+            .expect("Insert pod yaml failed");
+    }
+    for (ns, yaml) in &pods {
+        let fetched = store
+            .get_namespaced_pod_yaml(ns, &pod_name)
+            .await
+            .expect("Get pod yaml failed");
+        assert_eq!(fetched.as_ref(), Some(yaml));
+    }
+    // bare names resolve to the default namespace
+    assert!(store.get_pod_yaml(&pod_name).await.unwrap().is_none());
+    assert!(
+        store
+            .get_pod_yaml(&format!("ns-a/{pod_name}"))
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    let in_a = store.list_pods_in_namespace("ns-a").await.unwrap();
+    assert!(in_a.iter().all(|p| p.metadata.namespace == "ns-a"));
+    assert!(in_a.iter().any(|p| p.metadata.name == pod_name));
+
+    let all = store.list_all_pods().await.unwrap();
+    assert_eq!(
+        all.iter().filter(|p| p.metadata.name == pod_name).count(),
+        2
+    );
+
+    store
+        .delete_namespaced_pod("ns-a", &pod_name)
+        .await
+        .expect("Delete pod failed");
+    assert!(
+        store
+            .get_namespaced_pod_yaml("ns-a", &pod_name)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        store
+            .get_namespaced_pod_yaml("ns-b", &pod_name)
+            .await
+            .unwrap()
+            .is_some()
+    );
+    store
+        .delete_namespaced_pod("ns-b", &pod_name)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_xline_replicaset_update_if_revision() {
+    let store = load_store().await;
+
+    let rs_name = format!(
+        "rs-cas-test-{}",
+        chrono::Utc::now().timestamp_nanos_opt().unwrap()
+    );
+    let yaml = |replicas: i32| {
+        format!(
+            "apiVersion: v1\nkind: ReplicaSet\nmetadata:\n  name: {rs_name}\n\
+             status:\n  replicas: {replicas}\n"
+        )
+    };
+    store
+        .insert_replicaset_yaml(&rs_name, &yaml(0))
+        .await
+        .expect("Insert replicaset yaml failed");
+
+    let (_, read_rev) = store
+        .get_replicaset_yaml_with_revision(&rs_name)
+        .await
+        .unwrap()
+        .expect("replicaset should exist");
+
+    // the first writer with the revision it read wins
+    let new_rev = store
+        .update_replicaset_if_revision(&rs_name, &yaml(1), read_rev)
+        .await
+        .expect("update with current revision failed");
+    assert!(new_rev > read_rev);
+
+    // a second writer holding the same, now stale, revision gets a conflict
+    let err = store
+        .update_replicaset_if_revision(&rs_name, &yaml(2), read_rev)
+        .await
+        .expect_err("update with stale revision must fail");
+    let conflict = err
+        .downcast_ref::<RevisionConflict>()
+        .expect("expected a revision conflict");
+    assert_eq!(conflict.expected_revision, read_rev);
+    assert_eq!(conflict.current_revision, Some(new_rev));
+
+    let (stored, stored_rev) = store
+        .get_replicaset_yaml_with_revision(&rs_name)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored, yaml(1));
+    assert_eq!(stored_rev, new_rev);
+
+    store.delete_replicaset(&rs_name).await.unwrap();
+}
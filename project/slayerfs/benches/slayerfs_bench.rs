@@ -402,6 +402,7 @@ async fn create_meta_store(cfg: &BenchConfig) -> Result<Arc<dyn MetaStore>> {
                 cache: CacheConfig::default(),
                 client,
                 compact: Default::default(),
+                write: Default::default(),
             };
             let handle = MetaStoreFactory::<DatabaseMetaStore>::create_from_config(config)
                 .await
@@ -420,6 +421,7 @@ async fn create_meta_store(cfg: &BenchConfig) -> Result<Arc<dyn MetaStore>> {
                 cache: CacheConfig::default(),
                 client,
                 compact: Default::default(),
+                write: Default::default(),
             };
             let handle = MetaStoreFactory::<RedisMetaStore>::create_from_config(config)
                 .await
@@ -438,6 +440,7 @@ async fn create_meta_store(cfg: &BenchConfig) -> Result<Arc<dyn MetaStore>> {
                 cache: CacheConfig::default(),
                 client,
                 compact: Default::default(),
+                write: Default::default(),
             };
             let handle = MetaStoreFactory::<EtcdMetaStore>::create_from_config(config)
                 .await
@@ -15,6 +15,7 @@ use slayerfs::{
     CacheConfig, ChunkLayout, ClientOptions, CompactConfig, Config, DatabaseConfig,
     DatabaseMetaStore, DatabaseType, EtcdMetaStore, LocalFsBackend, MetaClient, MetaStore,
     ObjectBlockStore, ObjectClient, SetAttrFlags, SetAttrRequest, VFS, VfsFileAttr, VfsFileType,
+    WriteCacheConfig,
 };
 use tokio::runtime::Builder;
 use tokio::task::JoinSet;
@@ -1235,6 +1236,7 @@ async fn new_local_vfs(root: &Path, meta: &FuzzMetaConfig) -> io::Result<SlayerV
                 cache: CacheConfig::default(),
                 client,
                 compact: CompactConfig::default(),
+                write: WriteCacheConfig::default(),
             };
             let store = DatabaseMetaStore::from_config(cfg)
                 .await
@@ -1249,6 +1251,7 @@ async fn new_local_vfs(root: &Path, meta: &FuzzMetaConfig) -> io::Result<SlayerV
                 cache: CacheConfig::default(),
                 client,
                 compact: CompactConfig::default(),
+                write: WriteCacheConfig::default(),
             };
             let store = EtcdMetaStore::from_config(cfg)
                 .await
@@ -0,0 +1,485 @@
+//! Azure Blob Storage adapter: Blob REST API client with block-blob uploads, retries, and
+//! Shared Key / SAS authentication.
+
+use crate::cadapter::client::ObjectBackend;
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as B64;
+use bytes::Bytes;
+use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE, ETAG, HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, Response, StatusCode, Url};
+use ring::hmac;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use tokio::time::{Duration, sleep};
+
+const API_VERSION: &str = "2021-08-06";
+const CONTENT_MD5: HeaderName = HeaderName::from_static("content-md5");
+
+/// Credentials used to authorize Blob requests.
+#[derive(Clone, Default)]
+pub enum AzureCredential {
+    /// Storage account access key (base64, as shown in the portal); requests are signed with
+    /// Shared Key authorization.
+    SharedKey(String),
+    /// Shared access signature token (the query string, with or without a leading `?`).
+    SasToken(String),
+    /// No authorization, for public containers or local emulators configured to allow it.
+    #[default]
+    Anonymous,
+}
+
+impl fmt::Debug for AzureCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AzureCredential::SharedKey(_) => f.write_str("SharedKey(<redacted>)"),
+            AzureCredential::SasToken(_) => f.write_str("SasToken(<redacted>)"),
+            AzureCredential::Anonymous => f.write_str("Anonymous"),
+        }
+    }
+}
+
+/// Azure backend configuration options
+#[derive(Debug, Clone)]
+pub struct AzureConfig {
+    /// Storage account name
+    pub account: String,
+    /// Blob container name
+    pub container: String,
+    /// Credentials used to authorize requests (default: anonymous)
+    pub credential: AzureCredential,
+    /// Custom blob service endpoint (e.g. `http://127.0.0.1:10000/devstoreaccount1` for Azurite).
+    /// Defaults to `https://{account}.blob.core.windows.net`.
+    pub endpoint: Option<String>,
+    /// Block size for staged block-blob uploads in bytes (default: 8MB)
+    pub part_size: usize,
+    /// Maximum concurrent block uploads (default: 4)
+    pub max_concurrency: usize,
+    /// Maximum retry attempts for failed operations (default: 3)
+    pub max_retries: u32,
+    /// Base delay for exponential backoff in milliseconds (default: 100ms)
+    pub retry_base_delay: u64,
+    /// Enable MD5 checksums for uploads (default: true)
+    pub enable_md5: bool,
+}
+
+impl Default for AzureConfig {
+    fn default() -> Self {
+        Self {
+            account: String::new(),
+            container: String::new(),
+            credential: AzureCredential::default(),
+            endpoint: None,
+            part_size: 8 * 1024 * 1024, // 8MB
+            max_concurrency: 4,
+            max_retries: 3,
+            retry_base_delay: 100,
+            enable_md5: true,
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Clone)]
+pub struct AzureBackend {
+    client: reqwest::Client,
+    config: AzureConfig,
+    /// Container URL without a trailing slash.
+    container_url: String,
+    /// HMAC key for Shared Key authorization.
+    shared_key: Option<Arc<hmac::Key>>,
+}
+
+#[allow(dead_code)]
+impl AzureBackend {
+    /// Create new Azure backend with default configuration
+    pub fn new(
+        account: impl Into<String>,
+        container: impl Into<String>,
+        credential: AzureCredential,
+    ) -> Result<Self> {
+        let config = AzureConfig {
+            account: account.into(),
+            container: container.into(),
+            credential,
+            ..Default::default()
+        };
+        Self::with_config(config)
+    }
+
+    /// Create new Azure backend with custom configuration
+    pub fn with_config(config: AzureConfig) -> Result<Self> {
+        if config.account.is_empty() {
+            return Err(anyhow!("Storage account name cannot be empty"));
+        }
+        if config.container.is_empty() {
+            return Err(anyhow!("Container name cannot be empty"));
+        }
+        if config.part_size == 0 || config.max_concurrency == 0 {
+            return Err(anyhow!(
+                "part_size and max_concurrency must be greater than 0"
+            ));
+        }
+
+        let shared_key = match &config.credential {
+            AzureCredential::SharedKey(key) => {
+                let key = B64
+                    .decode(key.trim())
+                    .context("Azure storage account key is not valid base64")?;
+                Some(Arc::new(hmac::Key::new(hmac::HMAC_SHA256, &key)))
+            }
+            _ => None,
+        };
+
+        let endpoint = config
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| format!("https://{}.blob.core.windows.net", config.account));
+        let container_url = format!(
+            "{}/{}",
+            endpoint.trim_end_matches('/'),
+            encode_uri(&config.container, false)
+        );
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            config,
+            container_url,
+            shared_key,
+        })
+    }
+
+    fn blob_url(&self, key: &str, query: &[(&str, &str)]) -> Result<Url> {
+        let mut url = format!("{}/{}", self.container_url, encode_uri(key, true));
+        let mut sep = '?';
+        for (name, value) in query {
+            url.push(sep);
+            url.push_str(name);
+            url.push('=');
+            url.push_str(&encode_uri(value, false));
+            sep = '&';
+        }
+        if let AzureCredential::SasToken(token) = &self.config.credential {
+            url.push(sep);
+            url.push_str(token.trim_start_matches('?'));
+        }
+        Url::parse(&url).with_context(|| format!("invalid blob url for key {key}"))
+    }
+
+    /// Shared Key signature over the request, see
+    /// https://learn.microsoft.com/rest/api/storageservices/authorize-with-shared-key
+    fn authorization(
+        &self,
+        key: &hmac::Key,
+        method: &Method,
+        url: &Url,
+        headers: &HeaderMap,
+    ) -> String {
+        let header = |name: &HeaderName| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+        };
+        let content_length = match header(&CONTENT_LENGTH) {
+            "0" => "",
+            len => len,
+        };
+
+        let mut ms_headers = headers
+            .iter()
+            .filter(|(name, _)| name.as_str().starts_with("x-ms-"))
+            .map(|(name, value)| (name.as_str(), value.to_str().unwrap_or_default().trim()))
+            .collect::<Vec<_>>();
+        ms_headers.sort();
+
+        let mut params = BTreeMap::<String, Vec<String>>::new();
+        for (name, value) in url.query_pairs() {
+            params
+                .entry(name.to_lowercase())
+                .or_default()
+                .push(value.into_owned());
+        }
+
+        let mut to_sign = format!(
+            "{method}\n\n\n{content_length}\n{}\n{}\n\n\n\n\n\n\n",
+            header(&CONTENT_MD5),
+            header(&CONTENT_TYPE),
+        );
+        for (name, value) in ms_headers {
+            to_sign.push_str(&format!("{name}:{value}\n"));
+        }
+        to_sign.push_str(&format!("/{}{}", self.config.account, url.path()));
+        for (name, mut values) in params {
+            values.sort();
+            to_sign.push_str(&format!("\n{name}:{}", values.join(",")));
+        }
+
+        let signature = B64.encode(hmac::sign(key, to_sign.as_bytes()));
+        format!("SharedKey {}:{signature}", self.config.account)
+    }
+
+    /// Send a request, retrying transport errors, throttling and server errors.
+    ///
+    /// The response is returned as-is for any other status so callers can map expected failures
+    /// (e.g. 404) themselves.
+    async fn send(
+        &self,
+        method: Method,
+        url: Url,
+        extra_headers: &[(HeaderName, String)],
+        body: Option<Bytes>,
+    ) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+
+            let mut headers = HeaderMap::new();
+            for (name, value) in extra_headers {
+                headers.insert(name.clone(), HeaderValue::from_str(value)?);
+            }
+            let date = chrono::Utc::now()
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string();
+            headers.insert("x-ms-date", HeaderValue::from_str(&date)?);
+            headers.insert("x-ms-version", HeaderValue::from_static(API_VERSION));
+            if method == Method::PUT {
+                let len = body.as_ref().map(|b| b.len()).unwrap_or(0);
+                headers.insert(CONTENT_LENGTH, HeaderValue::from(len));
+            }
+            if let Some(key) = &self.shared_key {
+                let auth = self.authorization(key, &method, &url, &headers);
+                headers.insert("authorization", HeaderValue::from_str(&auth)?);
+            }
+
+            let mut request = self
+                .client
+                .request(method.clone(), url.clone())
+                .headers(headers);
+            if let Some(body) = &body {
+                request = request.body(body.clone());
+            }
+
+            let result = request.send().await;
+            let retryable = match &result {
+                Ok(resp) => {
+                    resp.status().is_server_error()
+                        || resp.status() == StatusCode::TOO_MANY_REQUESTS
+                }
+                Err(_) => true,
+            };
+            if retryable && attempt < self.config.max_retries {
+                let delay = self.config.retry_base_delay * (1 << (attempt - 1));
+                sleep(Duration::from_millis(delay)).await;
+                continue;
+            }
+            return Ok(result?);
+        }
+    }
+
+    async fn put_blob(&self, key: &str, data: Bytes) -> Result<()> {
+        let mut headers = vec![(
+            HeaderName::from_static("x-ms-blob-type"),
+            "BlockBlob".to_string(),
+        )];
+        if self.config.enable_md5 && !data.is_empty() {
+            headers.push((CONTENT_MD5, B64.encode(md5::compute(&data).0)));
+        }
+
+        let url = self.blob_url(key, &[])?;
+        let resp = self.send(Method::PUT, url, &headers, Some(data)).await?;
+        check_status(resp, "put blob", key).await?;
+        Ok(())
+    }
+
+    /// Upload large objects as staged blocks and commit them with a block list.
+    ///
+    /// Uncommitted blocks left behind by a failed upload are garbage collected by the service.
+    async fn put_block_list(&self, key: &str, chunks: Vec<Bytes>) -> Result<()> {
+        let mut parts: Vec<Vec<Bytes>> = Vec::new();
+        let mut cur_part: Vec<Bytes> = Vec::new();
+        let mut cur_len: usize = 0;
+
+        for chunk in chunks.into_iter() {
+            let mut offset = 0usize;
+            while offset < chunk.len() {
+                let remaining_part = self.config.part_size - cur_len;
+                let remaining_chunk = chunk.len() - offset;
+                let take = remaining_part.min(remaining_chunk);
+                cur_part.push(chunk.slice(offset..offset + take));
+                cur_len += take;
+                offset += take;
+
+                if cur_len == self.config.part_size {
+                    parts.push(cur_part);
+                    cur_part = Vec::new();
+                    cur_len = 0;
+                }
+            }
+        }
+
+        if cur_len > 0 {
+            parts.push(cur_part);
+        }
+
+        let sem = Arc::new(tokio::sync::Semaphore::new(self.config.max_concurrency));
+        let mut futures = Vec::new();
+
+        for (idx, part_chunks) in parts.into_iter().enumerate() {
+            // Block ids must have the same length within a blob.
+            let block_id = B64.encode(format!("{idx:08}"));
+            let sem_cloned = sem.clone();
+
+            let fut = async move {
+                let _permit = sem_cloned
+                    .acquire_owned()
+                    .await
+                    .with_context(|| "Block upload semaphore closed unexpectedly")?;
+
+                let data = Bytes::from(part_chunks.concat());
+                let mut headers = Vec::new();
+                if self.config.enable_md5 {
+                    headers.push((CONTENT_MD5, B64.encode(md5::compute(&data).0)));
+                }
+
+                let url = self.blob_url(key, &[("comp", "block"), ("blockid", &block_id)])?;
+                let resp = self.send(Method::PUT, url, &headers, Some(data)).await?;
+                check_status(resp, "put block", key).await?;
+                Ok::<_, anyhow::Error>(block_id)
+            };
+            futures.push(fut);
+        }
+
+        let block_ids = futures::future::try_join_all(futures).await?;
+
+        let mut block_list = String::from(r#"<?xml version="1.0" encoding="utf-8"?><BlockList>"#);
+        for block_id in block_ids {
+            block_list.push_str(&format!("<Latest>{block_id}</Latest>"));
+        }
+        block_list.push_str("</BlockList>");
+
+        let url = self.blob_url(key, &[("comp", "blocklist")])?;
+        let resp = self
+            .send(Method::PUT, url, &[], Some(Bytes::from(block_list)))
+            .await?;
+        check_status(resp, "put block list", key).await?;
+        Ok(())
+    }
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters (and `/` when `keep_slash`).
+fn encode_uri(input: &str, keep_slash: bool) -> String {
+    let mut out = String::with_capacity(input.len());
+    for b in input.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            b'/' if keep_slash => out.push('/'),
+            _ => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+async fn check_status(resp: Response, op: &str, key: &str) -> Result<Response> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+    let code = resp
+        .headers()
+        .get("x-ms-error-code")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let body = resp.text().await.unwrap_or_default();
+    Err(anyhow!(
+        "azure {op} failed for {key}: {status} {code} {}",
+        body.trim()
+    ))
+}
+
+#[async_trait]
+impl ObjectBackend for AzureBackend {
+    #[tracing::instrument(level = "trace", skip(self, chunks), fields(key, chunk_count = chunks.len()))]
+    async fn put_object_vectored(&self, key: &str, chunks: Vec<Bytes>) -> Result<()> {
+        let total_size = chunks.iter().map(|e| e.len()).sum::<usize>();
+
+        if total_size <= self.config.part_size {
+            return self.put_blob(key, Bytes::from(chunks.concat())).await;
+        }
+
+        self.put_block_list(key, chunks).await
+    }
+
+    async fn put_object(&self, key: &str, data: &[u8]) -> Result<()> {
+        // Small objects use a single Put Blob; large objects are staged as blocks
+        self.put_object_vectored(key, vec![Bytes::copy_from_slice(data)])
+            .await
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let url = self.blob_url(key, &[])?;
+        let resp = self.send(Method::GET, url, &[], None).await?;
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let resp = check_status(resp, "get blob", key).await?;
+        Ok(Some(resp.bytes().await?.to_vec()))
+    }
+
+    /// Get a range of bytes from an object.
+    /// Used for small range reads in intelligent read strategy.
+    async fn get_object_range(&self, key: &str, offset: u64, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let end = offset + buf.len() as u64 - 1;
+        let headers = [(
+            HeaderName::from_static("x-ms-range"),
+            format!("bytes={offset}-{end}"),
+        )];
+
+        let url = self.blob_url(key, &[])?;
+        let resp = self.send(Method::GET, url, &headers, None).await?;
+        match resp.status() {
+            // Missing blob, or offset beyond the end of the blob.
+            StatusCode::NOT_FOUND | StatusCode::RANGE_NOT_SATISFIABLE => return Ok(0),
+            _ => {}
+        }
+
+        let resp = check_status(resp, "get blob range", key).await?;
+        let data = resp.bytes().await?;
+        let read = data.len().min(buf.len());
+        buf[..read].copy_from_slice(&data[..read]);
+        Ok(read)
+    }
+
+    async fn get_etag(&self, key: &str) -> Result<String> {
+        let url = self.blob_url(key, &[])?;
+        let resp = self.send(Method::HEAD, url, &[], None).await?;
+        let resp = check_status(resp, "get blob properties", key).await?;
+        Ok(resp
+            .headers()
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<()> {
+        let url = self.blob_url(key, &[])?;
+        let resp = self.send(Method::DELETE, url, &[], None).await?;
+        // Deleting a missing blob is not an error, matching S3 semantics.
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        check_status(resp, "delete blob", key).await?;
+        Ok(())
+    }
+}
@@ -16,6 +16,20 @@ pub trait ObjectBackend: Send + Sync {
 
     async fn put_object(&self, key: &str, data: &[u8]) -> Result<()>;
 
+    /// Upload `chunks` under `key` unless the stored object was uploaded with the same content
+    /// `digest`, which is kept alongside the object. Returns `false` when the upload was skipped.
+    ///
+    /// Backends that cannot keep a digest with their objects always upload.
+    async fn put_object_if_changed(
+        &self,
+        key: &str,
+        chunks: Vec<Bytes>,
+        _digest: &str,
+    ) -> Result<bool> {
+        self.put_object_vectored(key, chunks).await?;
+        Ok(true)
+    }
+
     async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>>;
 
     /// Get a range of bytes from an object.
@@ -51,6 +65,17 @@ impl<B: ObjectBackend> ObjectClient<B> {
         self.backend.put_object_vectored(key, chunks).await
     }
 
+    pub async fn put_object_if_changed(
+        &self,
+        key: &str,
+        chunks: Vec<Bytes>,
+        digest: &str,
+    ) -> Result<bool> {
+        self.backend
+            .put_object_if_changed(key, chunks, digest)
+            .await
+    }
+
     pub async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>> {
         self.backend.get_object(key).await
     }
@@ -1,5 +1,7 @@
 //! Local filesystem backend used to mock an object store (implements `ObjectBackend`).
 //!
+//! Objects are written to a temporary file next to their final path and renamed into place, so a
+//! reader sees either the old or the new object, never a torn one.
 
 #[cfg(unix)]
 use std::os::unix::fs::FileExt;
@@ -16,7 +18,7 @@ use std::io::{IoSlice, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
-use tokio::{fs, io::AsyncWriteExt};
+use tokio::fs;
 use tracing::field;
 
 fn can_block_in_place() -> bool {
@@ -30,10 +32,20 @@ fn can_block_in_place() -> bool {
         .unwrap_or(false)
 }
 
+/// Temporary sibling of `path` an object is written to before being renamed over `path`.
+fn temp_path_for(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{name}.tmp.{:016x}", rand::random::<u64>()))
+}
+
 #[derive(Clone)]
 pub struct LocalFsBackend {
     root: PathBuf,
     created_dirs: Arc<DashSet<PathBuf>>,
+    fsync: bool,
 }
 
 impl LocalFsBackend {
@@ -41,8 +53,17 @@ impl LocalFsBackend {
         Self {
             root: root.as_ref().to_path_buf(),
             created_dirs: Arc::new(DashSet::new()),
+            fsync: false,
         }
     }
+
+    /// Fsync every object, and its directory after the rename, before a put returns. Slower, but
+    /// a completed put then survives a crash of the machine, not only of the process.
+    pub fn with_fsync(mut self, fsync: bool) -> Self {
+        self.fsync = fsync;
+        self
+    }
+
     fn path_for(&self, key: &str) -> PathBuf {
         self.root.join(key)
     }
@@ -72,6 +93,7 @@ impl ObjectBackend for LocalFsBackend {
             open_ms = field::Empty,
             write_ms = field::Empty,
             flush_ms = field::Empty,
+            rename_ms = field::Empty,
             write_calls = field::Empty,
             bytes_written = field::Empty
         )
@@ -95,51 +117,81 @@ impl ObjectBackend for LocalFsBackend {
             open_ms: u64,
             write_ms: u64,
             flush_ms: u64,
+            rename_ms: u64,
             write_calls: u64,
             bytes_written: u64,
         }
 
+        let fsync = self.fsync;
         let submit_at = Instant::now();
         let res = tokio::task::spawn_blocking(move || -> std::io::Result<WriteStats> {
             let start = Instant::now();
             let queue_ms = start.duration_since(submit_at).as_millis() as u64;
 
-            let open_start = Instant::now();
-            let mut f = std::fs::File::create(path)?;
-            let open_ms = open_start.elapsed().as_millis() as u64;
-
-            let write_start = Instant::now();
-            let mut write_calls: u64 = 0;
-            let mut bytes_written: u64 = 0;
-            let mut slices = chunks
-                .iter()
-                .map(|e| IoSlice::new(e.as_ref()))
-                .collect::<Vec<_>>();
-
-            let mut slices_ref = slices.as_mut_slice();
-            while !slices_ref.is_empty() {
-                let n = f.write_vectored(slices_ref)?;
-                if n == 0 {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::WriteZero,
-                        "write zero",
-                    ));
+            let temp_path = temp_path_for(&path);
+            let write_temp = || -> std::io::Result<(u64, u64, u64, u64, u64)> {
+                let open_start = Instant::now();
+                let mut f = std::fs::File::create(&temp_path)?;
+                let open_ms = open_start.elapsed().as_millis() as u64;
+
+                let write_start = Instant::now();
+                let mut write_calls: u64 = 0;
+                let mut bytes_written: u64 = 0;
+                let mut slices = chunks
+                    .iter()
+                    .map(|e| IoSlice::new(e.as_ref()))
+                    .collect::<Vec<_>>();
+
+                let mut slices_ref = slices.as_mut_slice();
+                while !slices_ref.is_empty() {
+                    let n = f.write_vectored(slices_ref)?;
+                    if n == 0 {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::WriteZero,
+                            "write zero",
+                        ));
+                    }
+                    write_calls = write_calls.saturating_add(1);
+                    bytes_written = bytes_written.saturating_add(n as u64);
+                    IoSlice::advance_slices(&mut slices_ref, n);
                 }
-                write_calls = write_calls.saturating_add(1);
-                bytes_written = bytes_written.saturating_add(n as u64);
-                IoSlice::advance_slices(&mut slices_ref, n);
-            }
-            let write_ms = write_start.elapsed().as_millis() as u64;
+                let write_ms = write_start.elapsed().as_millis() as u64;
+
+                let flush_start = Instant::now();
+                f.flush()?;
+                if fsync {
+                    f.sync_all()?;
+                }
+                let flush_ms = flush_start.elapsed().as_millis() as u64;
+
+                Ok((open_ms, write_ms, flush_ms, write_calls, bytes_written))
+            };
 
-            let flush_start = Instant::now();
-            f.flush()?;
-            let flush_ms = flush_start.elapsed().as_millis() as u64;
+            let (open_ms, write_ms, flush_ms, write_calls, bytes_written) = match write_temp() {
+                Ok(stats) => stats,
+                Err(e) => {
+                    let _ = std::fs::remove_file(&temp_path);
+                    return Err(e);
+                }
+            };
+
+            let rename_start = Instant::now();
+            if let Err(e) = std::fs::rename(&temp_path, &path) {
+                let _ = std::fs::remove_file(&temp_path);
+                return Err(e);
+            }
+            // The rename is only durable once the directory entry is.
+            if fsync && let Some(dir) = path.parent() {
+                std::fs::File::open(dir)?.sync_all()?;
+            }
+            let rename_ms = rename_start.elapsed().as_millis() as u64;
 
             Ok(WriteStats {
                 queue_ms,
                 open_ms,
                 write_ms,
                 flush_ms,
+                rename_ms,
                 write_calls,
                 bytes_written,
             })
@@ -153,22 +205,15 @@ impl ObjectBackend for LocalFsBackend {
         span.record("open_ms", stats.open_ms);
         span.record("write_ms", stats.write_ms);
         span.record("flush_ms", stats.flush_ms);
+        span.record("rename_ms", stats.rename_ms);
         span.record("write_calls", stats.write_calls);
         span.record("bytes_written", stats.bytes_written);
         Ok(())
     }
 
     async fn put_object(&self, key: &str, data: &[u8]) -> Result<()> {
-        let path = self.path_for(key);
-
-        if let Some(dir) = path.parent() {
-            self.ensure_dir(dir).await?;
-        }
-
-        let mut f = fs::File::create(path).await?;
-        f.write_all(data).await?;
-        f.flush().await?;
-        Ok(())
+        self.put_object_vectored(key, vec![Bytes::copy_from_slice(data)])
+            .await
     }
 
     #[tracing::instrument(name = "LocalFsBackend.get_object", level = "trace", skip(self))]
@@ -4,19 +4,29 @@ use crate::cadapter::client::ObjectBackend;
 use anyhow::{Context, Result, anyhow};
 use async_trait::async_trait;
 use aws_config::BehaviorVersion;
-use aws_sdk_s3::error::SdkError;
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_sdk_s3::config::retry::RetryConfig;
+use aws_sdk_s3::error::{ProvideErrorMetadata, SdkError};
 use aws_sdk_s3::primitives::{ByteStream, SdkBody};
-use aws_sdk_s3::{Client, config::Region};
+use aws_sdk_s3::{
+    Client,
+    config::{Credentials, Region},
+};
 use base64::Engine as _;
 use base64::engine::general_purpose::STANDARD as B64;
 use bytes::Bytes;
 use hyper::Body;
 use md5;
+use std::fmt;
+use std::future::Future;
 use std::sync::Arc;
 use tokio::time::{Duration, sleep};
 
+/// User metadata entry holding the content digest given to `put_object_if_changed`.
+const DIGEST_METADATA_KEY: &str = "slayerfs-digest";
+
 /// S3 backend configuration options
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct S3Config {
     /// S3 bucket name
     pub bucket: String,
@@ -26,9 +36,12 @@ pub struct S3Config {
     pub part_size: usize,
     /// Maximum concurrent multipart upload parts (default: 4)
     pub max_concurrency: usize,
-    /// Maximum retry attempts for failed operations (default: 3)
+    /// Maximum attempts for an operation failing with a transient error (default: 3).
+    /// Throttling, 5xx responses, timeouts and connection errors are retried; missing keys,
+    /// auth and other client errors fail immediately.
     pub max_retries: u32,
-    /// Base delay for exponential backoff in milliseconds (default: 100ms)
+    /// Base delay for exponential backoff in milliseconds, doubled after every attempt
+    /// (default: 100ms)
     pub retry_base_delay: u64,
     /// Enable MD5 checksums for uploads (default: true)
     pub enable_md5: bool,
@@ -36,6 +49,41 @@ pub struct S3Config {
     pub endpoint: Option<String>,
     /// Force path-style access (required for some S3-compatible services)
     pub force_path_style: bool,
+    /// Static access key id; when unset the SDK's default credential chain is used
+    pub access_key: Option<String>,
+    /// Static secret access key, required together with `access_key`
+    pub secret_key: Option<String>,
+    /// Optional session token for temporary static credentials
+    pub session_token: Option<String>,
+}
+
+impl fmt::Debug for S3Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redacted = |v: &Option<String>| v.as_ref().map(|_| "<redacted>");
+        f.debug_struct("S3Config")
+            .field("bucket", &self.bucket)
+            .field("region", &self.region)
+            .field("part_size", &self.part_size)
+            .field("max_concurrency", &self.max_concurrency)
+            .field("max_retries", &self.max_retries)
+            .field("retry_base_delay", &self.retry_base_delay)
+            .field("enable_md5", &self.enable_md5)
+            .field("endpoint", &self.endpoint)
+            .field("force_path_style", &self.force_path_style)
+            .field("access_key", &self.access_key)
+            .field("secret_key", &redacted(&self.secret_key))
+            .field("session_token", &redacted(&self.session_token))
+            .finish()
+    }
+}
+
+impl S3Config {
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_retries: self.max_retries,
+            base_delay: Duration::from_millis(self.retry_base_delay),
+        }
+    }
 }
 
 impl Default for S3Config {
@@ -50,6 +98,102 @@ impl Default for S3Config {
             enable_md5: true,
             endpoint: None,
             force_path_style: false,
+            access_key: None,
+            secret_key: None,
+            session_token: None,
+        }
+    }
+}
+
+/// Exponential backoff for object store operations.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RetryPolicy {
+    /// Total attempts, including the first one.
+    pub max_retries: u32,
+    /// Delay before the second attempt, doubled for every further one.
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Backoff after the given (1-based) failed attempt.
+    fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay * (1 << (attempt - 1).min(16))
+    }
+
+    /// Run `op` until it succeeds, fails with an error `is_retryable` rejects, or the attempts
+    /// are used up. The last error is returned.
+    pub(crate) async fn run<T, E, F, Fut>(
+        &self,
+        mut op: F,
+        is_retryable: impl Fn(&E) -> bool,
+    ) -> std::result::Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = std::result::Result<T, E>>,
+    {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match op().await {
+                Err(e) if attempt < self.max_retries && is_retryable(&e) => {
+                    sleep(self.delay(attempt)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Whether a response status or S3 error code means the request may succeed later.
+fn is_transient(status: u16, code: Option<&str>) -> bool {
+    status == 429
+        || (500..600).contains(&status)
+        || matches!(
+            code,
+            Some(
+                "SlowDown"
+                    | "Throttling"
+                    | "ThrottlingException"
+                    | "RequestTimeout"
+                    | "InternalError"
+                    | "ServiceUnavailable"
+            )
+        )
+}
+
+fn is_retryable<E: ProvideErrorMetadata>(err: &SdkError<E, HttpResponse>) -> bool {
+    match err {
+        SdkError::TimeoutError(_) | SdkError::ResponseError(_) => true,
+        SdkError::DispatchFailure(e) => e.is_io() || e.is_timeout(),
+        SdkError::ServiceError(e) => is_transient(e.raw().status().as_u16(), e.err().code()),
+        _ => false,
+    }
+}
+
+/// A failed attempt of an S3 request, tagged with whether it is worth retrying.
+struct S3Failure {
+    error: anyhow::Error,
+    retryable: bool,
+}
+
+impl<E> From<SdkError<E, HttpResponse>> for S3Failure
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+{
+    fn from(err: SdkError<E, HttpResponse>) -> Self {
+        Self {
+            retryable: is_retryable(&err),
+            error: err.into(),
+        }
+    }
+}
+
+impl From<std::io::Error> for S3Failure {
+    // Reading a response body only fails when the connection drops mid-stream.
+    fn from(err: std::io::Error) -> Self {
+        Self {
+            error: err.into(),
+            retryable: true,
         }
     }
 }
@@ -84,18 +228,40 @@ impl S3Backend {
             aws_config_loader = aws_config_loader.region(Region::new(region.clone()));
         }
 
+        match (&config.access_key, &config.secret_key) {
+            (Some(access_key), Some(secret_key)) => {
+                let credentials = Credentials::new(
+                    access_key,
+                    secret_key,
+                    config.session_token.clone(),
+                    None,
+                    "slayerfs-static",
+                );
+                aws_config_loader = aws_config_loader.credentials_provider(credentials);
+            }
+            (None, None) => {}
+            _ => return Err(anyhow!("access_key and secret_key must be set together")),
+        }
+
         let aws_config = aws_config_loader.load().await;
 
         let mut s3_config_builder = aws_sdk_s3::config::Builder::from(&aws_config);
 
         if let Some(endpoint) = &config.endpoint {
             s3_config_builder = s3_config_builder.endpoint_url(endpoint);
+            // S3-compatible stores generally ignore the region, but SigV4 still needs one.
+            if aws_config.region().is_none() {
+                s3_config_builder = s3_config_builder.region(Region::new("us-east-1"));
+            }
         }
 
         if config.force_path_style {
             s3_config_builder = s3_config_builder.force_path_style(true);
         }
 
+        // Retries are driven by `max_retries`, so the SDK must not retry on its own as well.
+        s3_config_builder = s3_config_builder.retry_config(RetryConfig::disabled());
+
         let client = Client::from_conf(s3_config_builder.build());
 
         Ok(Self { client, config })
@@ -128,74 +294,127 @@ impl S3Backend {
             None
         };
 
-        let mut attempt = 0;
-        loop {
-            attempt += 1;
+        self.config
+            .retry_policy()
+            .run(
+                || {
+                    let mut request = self
+                        .client
+                        .put_object()
+                        .bucket(&self.config.bucket)
+                        .key(key)
+                        .body(Self::stream_from_chunks(&chunks))
+                        .content_length(total_size as i64);
+
+                    if let Some(sum) = checksum.as_ref() {
+                        request = request.content_md5(sum.clone());
+                    }
+                    request.send()
+                },
+                is_retryable,
+            )
+            .await?;
+        Ok(())
+    }
 
-            let body = Self::stream_from_chunks(&chunks);
-            let mut request = self
-                .client
-                .put_object()
-                .bucket(&self.config.bucket)
-                .key(key)
-                .body(body)
-                .content_length(total_size as i64);
+    /// Upload `chunks` tagged with `digest`, only if the object still has the ETag `expected`
+    /// (or still does not exist when `expected` is `None`). Returns `false` when another writer
+    /// changed the object in the meantime.
+    async fn put_object_vectored_conditional(
+        &self,
+        key: &str,
+        chunks: &[Bytes],
+        digest: &str,
+        expected: Option<&str>,
+    ) -> Result<bool> {
+        let total_size = chunks.iter().map(|c| c.len()).sum::<usize>();
+        let checksum = if self.config.enable_md5 && total_size > 0 {
+            Some(Self::md5_base64_chunks(chunks))
+        } else {
+            None
+        };
 
-            if let Some(sum) = checksum.as_ref() {
-                request = request.content_md5(sum.clone());
-            }
+        let result = self
+            .config
+            .retry_policy()
+            .run(
+                || {
+                    let mut request = self
+                        .client
+                        .put_object()
+                        .bucket(&self.config.bucket)
+                        .key(key)
+                        .body(Self::stream_from_chunks(chunks))
+                        .content_length(total_size as i64)
+                        .metadata(DIGEST_METADATA_KEY, digest);
+
+                    request = match expected {
+                        Some(etag) => request.if_match(etag),
+                        None => request.if_none_match("*"),
+                    };
+                    if let Some(sum) = checksum.as_ref() {
+                        request = request.content_md5(sum.clone());
+                    }
+                    request.send()
+                },
+                is_retryable,
+            )
+            .await;
 
-            match request.send().await {
-                Ok(_) => return Ok(()),
-                Err(_e) if attempt < self.config.max_retries => {
-                    let delay = self.config.retry_base_delay * (1 << (attempt - 1));
-                    sleep(Duration::from_millis(delay)).await;
-                    continue;
-                }
-                Err(e) => return Err(e.into()),
+        match result {
+            Ok(_) => Ok(true),
+            // 412: the precondition no longer holds; 409: a concurrent conditional write won.
+            Err(SdkError::ServiceError(err))
+                if matches!(err.raw().status().as_u16(), 409 | 412) =>
+            {
+                Ok(false)
             }
+            Err(e) => Err(e.into()),
         }
     }
 
     /// Put small objects directly (simpler than multipart upload)
     async fn put_object_simple(&self, key: &str, data: &[u8]) -> Result<()> {
-        let mut attempt = 0;
-        loop {
-            attempt += 1;
-
-            let mut request = self
-                .client
-                .put_object()
-                .bucket(&self.config.bucket)
-                .key(key)
-                .body(SdkBody::from(data.to_vec()).into());
-
-            if self.config.enable_md5 {
-                let checksum = Self::md5_base64(data);
-                request = request.content_md5(checksum);
-            }
-
-            match request.send().await {
-                Ok(_) => return Ok(()),
-                Err(_e) if attempt < self.config.max_retries => {
-                    let delay = self.config.retry_base_delay * (1 << (attempt - 1));
-                    sleep(Duration::from_millis(delay)).await;
-                    continue;
-                }
-                Err(e) => return Err(e.into()),
-            }
-        }
+        let checksum = self.config.enable_md5.then(|| Self::md5_base64(data));
+
+        self.config
+            .retry_policy()
+            .run(
+                || {
+                    let mut request = self
+                        .client
+                        .put_object()
+                        .bucket(&self.config.bucket)
+                        .key(key)
+                        .body(SdkBody::from(data.to_vec()).into());
+
+                    if let Some(sum) = checksum.as_ref() {
+                        request = request.content_md5(sum.clone());
+                    }
+                    request.send()
+                },
+                is_retryable,
+            )
+            .await?;
+        Ok(())
     }
 
     /// Handle multipart upload for large objects
     async fn multipart_upload(&self, key: &str, data: &[u8]) -> Result<()> {
         // Create multipart upload
         let create = self
-            .client
-            .create_multipart_upload()
-            .bucket(&self.config.bucket)
-            .key(key)
-            .send()
+            .config
+            .retry_policy()
+            .run(
+                || {
+                    self.client
+                        .create_multipart_upload()
+                        .bucket(&self.config.bucket)
+                        .key(key)
+                        .send()
+                },
+                is_retryable,
+            )
             .await?;
 
         let upload_id = create
@@ -230,8 +449,7 @@ impl S3Backend {
             let pn = part_number;
             let sem_cloned = sem.clone();
             let enable_md5 = self.config.enable_md5;
-            let max_retries = self.config.max_retries;
-            let retry_base_delay = self.config.retry_base_delay;
+            let retry_policy = self.config.retry_policy();
 
             let fut = async move {
                 // Concurrency control
@@ -239,33 +457,28 @@ impl S3Backend {
                     .acquire_owned()
                     .await
                     .with_context(|| "Multipart upload semaphore closed unexpectedly");
-                let mut attempt = 0;
-
-                loop {
-                    attempt += 1;
-                    let mut request = client
-                        .upload_part()
-                        .bucket(&bucket)
-                        .key(&key)
-                        .upload_id(&upload_id_cloned)
-                        .part_number(pn)
-                        .body(SdkBody::from(chunk_vec.clone()).into());
-
-                    if enable_md5 {
-                        let part_md5 = Self::md5_base64(&chunk_vec);
-                        request = request.content_md5(part_md5);
-                    }
-
-                    match request.send().await {
-                        Ok(ok) => break Ok((pn, ok.e_tag().map(|s| s.to_string()))),
-                        Err(_e) if attempt < max_retries => {
-                            let delay = retry_base_delay * (1 << (attempt - 1));
-                            sleep(Duration::from_millis(delay)).await;
-                            continue;
-                        }
-                        Err(e) => break Err(e),
-                    }
-                }
+                let part_md5 = enable_md5.then(|| Self::md5_base64(&chunk_vec));
+
+                retry_policy
+                    .run(
+                        || {
+                            let mut request = client
+                                .upload_part()
+                                .bucket(&bucket)
+                                .key(&key)
+                                .upload_id(&upload_id_cloned)
+                                .part_number(pn)
+                                .body(SdkBody::from(chunk_vec.clone()).into());
+
+                            if let Some(md5) = part_md5.as_ref() {
+                                request = request.content_md5(md5.clone());
+                            }
+                            request.send()
+                        },
+                        is_retryable,
+                    )
+                    .await
+                    .map(|ok| (pn, ok.e_tag().map(|s| s.to_string())))
             };
             parts.push(fut);
 
@@ -295,13 +508,20 @@ impl S3Backend {
             .build();
 
         // Complete multipart upload
-        self.client
-            .complete_multipart_upload()
-            .bucket(&self.config.bucket)
-            .key(key)
-            .upload_id(upload_id)
-            .multipart_upload(completed)
-            .send()
+        self.config
+            .retry_policy()
+            .run(
+                || {
+                    self.client
+                        .complete_multipart_upload()
+                        .bucket(&self.config.bucket)
+                        .key(key)
+                        .upload_id(&upload_id)
+                        .multipart_upload(completed.clone())
+                        .send()
+                },
+                is_retryable,
+            )
             .await?;
 
         // Disarm cleanup guard since upload succeeded
@@ -312,11 +532,18 @@ impl S3Backend {
 
     async fn multipart_upload_vectored(&self, key: &str, chunks: Vec<Bytes>) -> Result<()> {
         let create = self
-            .client
-            .create_multipart_upload()
-            .bucket(&self.config.bucket)
-            .key(key)
-            .send()
+            .config
+            .retry_policy()
+            .run(
+                || {
+                    self.client
+                        .create_multipart_upload()
+                        .bucket(&self.config.bucket)
+                        .key(key)
+                        .send()
+                },
+                is_retryable,
+            )
             .await?;
 
         let upload_id = create
@@ -374,39 +601,32 @@ impl S3Backend {
             let upload_id_cloned = upload_id.clone();
             let pn = (idx + 1) as i32;
             let sem_cloned = sem.clone();
-            let max_retries = self.config.max_retries;
-            let retry_base_delay = self.config.retry_base_delay;
+            let retry_policy = self.config.retry_policy();
 
             let fut = async move {
                 let _permit = sem_cloned.acquire_owned().await;
-                let mut attempt = 0;
-
-                loop {
-                    attempt += 1;
-                    let body = S3Backend::stream_from_chunks(&part_chunks);
-                    let mut request = client
-                        .upload_part()
-                        .bucket(&bucket)
-                        .key(&key)
-                        .upload_id(&upload_id_cloned)
-                        .part_number(pn)
-                        .body(body)
-                        .content_length(part_len as i64);
-
-                    if let Some(md5) = part_md5.as_ref() {
-                        request = request.content_md5(md5.clone());
-                    }
 
-                    match request.send().await {
-                        Ok(ok) => break Ok((pn, ok.e_tag().map(|s| s.to_string()))),
-                        Err(_e) if attempt < max_retries => {
-                            let delay = retry_base_delay * (1 << (attempt - 1));
-                            sleep(Duration::from_millis(delay)).await;
-                            continue;
-                        }
-                        Err(e) => break Err(e),
-                    }
-                }
+                retry_policy
+                    .run(
+                        || {
+                            let mut request = client
+                                .upload_part()
+                                .bucket(&bucket)
+                                .key(&key)
+                                .upload_id(&upload_id_cloned)
+                                .part_number(pn)
+                                .body(S3Backend::stream_from_chunks(&part_chunks))
+                                .content_length(part_len as i64);
+
+                            if let Some(md5) = part_md5.as_ref() {
+                                request = request.content_md5(md5.clone());
+                            }
+                            request.send()
+                        },
+                        is_retryable,
+                    )
+                    .await
+                    .map(|ok| (pn, ok.e_tag().map(|s| s.to_string())))
             };
             futures.push(fut);
         }
@@ -431,13 +651,20 @@ impl S3Backend {
             .set_parts(Some(completed_parts))
             .build();
 
-        self.client
-            .complete_multipart_upload()
-            .bucket(&self.config.bucket)
-            .key(key)
-            .upload_id(upload_id)
-            .multipart_upload(completed)
-            .send()
+        self.config
+            .retry_policy()
+            .run(
+                || {
+                    self.client
+                        .complete_multipart_upload()
+                        .bucket(&self.config.bucket)
+                        .key(key)
+                        .upload_id(&upload_id)
+                        .multipart_upload(completed.clone())
+                        .send()
+                },
+                is_retryable,
+            )
             .await?;
 
         std::mem::forget(cleanup_on_drop);
@@ -498,26 +725,94 @@ impl ObjectBackend for S3Backend {
         self.multipart_upload(key, data).await
     }
 
-    async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>> {
-        let resp = self
-            .client
-            .get_object()
-            .bucket(&self.config.bucket)
-            .key(key)
-            .send()
-            .await;
+    /// Compares the digest recorded in the object's metadata (a HEAD request) and uploads with
+    /// `If-Match`/`If-None-Match` on the ETag that was seen, so an object changed by another
+    /// writer in between is looked at again instead of being overwritten blindly. ETags are not
+    /// compared themselves, as they are not content hashes for multipart or SSE-KMS uploads.
+    async fn put_object_if_changed(
+        &self,
+        key: &str,
+        chunks: Vec<Bytes>,
+        digest: &str,
+    ) -> Result<bool> {
+        let total_size = chunks.iter().map(|c| c.len()).sum::<usize>();
+        if total_size > self.config.part_size {
+            // Multipart uploads are not made conditional; such objects are always uploaded.
+            self.multipart_upload_vectored(key, chunks).await?;
+            return Ok(true);
+        }
+
+        for _ in 0..self.config.max_retries.max(1) {
+            let head = self
+                .config
+                .retry_policy()
+                .run(
+                    || {
+                        self.client
+                            .head_object()
+                            .bucket(&self.config.bucket)
+                            .key(key)
+                            .send()
+                    },
+                    is_retryable,
+                )
+                .await;
+
+            let etag = match head {
+                Ok(o) => {
+                    let stored = o.metadata().and_then(|m| m.get(DIGEST_METADATA_KEY));
+                    if stored.is_some_and(|stored| stored == digest) {
+                        return Ok(false);
+                    }
+                    Some(o.e_tag().unwrap_or_default().to_string())
+                }
+                Err(SdkError::ServiceError(err)) if err.err().is_not_found() => None,
+                Err(e) => return Err(e.into()),
+            };
 
-        match resp {
-            Ok(o) => {
-                use tokio::io::AsyncReadExt;
-                let mut body = o.body.into_async_read();
-                let mut buf = Vec::new();
-                body.read_to_end(&mut buf).await?;
-                Ok(Some(buf))
+            if self
+                .put_object_vectored_conditional(key, &chunks, digest, etag.as_deref())
+                .await?
+            {
+                return Ok(true);
             }
-            Err(SdkError::ServiceError(err)) if err.err().is_no_such_key() => Ok(None),
-            Err(e) => Err(e.into()),
         }
+        Err(anyhow!(
+            "object {key} kept changing during a conditional upload"
+        ))
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        // The body is read inside the retried operation, so a connection dropped mid-download
+        // is retried like a failed request.
+        self.config
+            .retry_policy()
+            .run(
+                || async move {
+                    let resp = self
+                        .client
+                        .get_object()
+                        .bucket(&self.config.bucket)
+                        .key(key)
+                        .send()
+                        .await;
+
+                    match resp {
+                        Ok(o) => {
+                            use tokio::io::AsyncReadExt;
+                            let mut body = o.body.into_async_read();
+                            let mut buf = Vec::new();
+                            body.read_to_end(&mut buf).await?;
+                            Ok(Some(buf))
+                        }
+                        Err(SdkError::ServiceError(err)) if err.err().is_no_such_key() => Ok(None),
+                        Err(e) => Err(S3Failure::from(e)),
+                    }
+                },
+                |failure| failure.retryable,
+            )
+            .await
+            .map_err(|failure| failure.error)
     }
 
     /// Get a range of bytes from an object.
@@ -530,33 +825,47 @@ impl ObjectBackend for S3Backend {
         let end = offset + buf.len() as u64 - 1;
         let range_header = format!("bytes={}-{}", offset, end);
 
-        let resp = self
-            .client
-            .get_object()
-            .bucket(&self.config.bucket)
-            .key(key)
-            .range(range_header)
-            .send()
-            .await;
+        let policy = self.config.retry_policy();
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let resp = self
+                .client
+                .get_object()
+                .bucket(&self.config.bucket)
+                .key(key)
+                .range(&range_header)
+                .send()
+                .await;
 
-        match resp {
-            Ok(o) => {
-                use tokio::io::AsyncReadExt;
-                let mut body = o.body.into_async_read();
-                let mut read = 0;
+            // Written out instead of `RetryPolicy::run` because every attempt refills `buf`.
+            let result = match resp {
+                Ok(o) => {
+                    use tokio::io::AsyncReadExt;
+                    let mut body = o.body.into_async_read();
+                    let mut read = 0;
 
-                while read < buf.len() {
-                    let n = body.read(&mut buf[read..]).await?;
-                    if n == 0 {
-                        break;
+                    loop {
+                        if read == buf.len() {
+                            break Ok(read);
+                        }
+                        match body.read(&mut buf[read..]).await {
+                            Ok(0) => break Ok(read),
+                            Ok(n) => read += n,
+                            Err(e) => break Err(S3Failure::from(e)),
+                        }
                     }
-                    read += n;
                 }
+                Err(SdkError::ServiceError(err)) if err.err().is_no_such_key() => Ok(0),
+                Err(e) => Err(S3Failure::from(e)),
+            };
 
-                Ok(read)
+            match result {
+                Err(failure) if failure.retryable && attempt < policy.max_retries => {
+                    sleep(policy.delay(attempt)).await;
+                }
+                result => return result.map_err(|failure| failure.error),
             }
-            Err(SdkError::ServiceError(err)) if err.err().is_no_such_key() => Ok(0),
-            Err(e) => Err(e.into()),
         }
     }
 
@@ -572,26 +881,94 @@ impl ObjectBackend for S3Backend {
     }
 
     async fn delete_object(&self, key: &str) -> Result<()> {
-        let mut attempt = 0;
+        self.config
+            .retry_policy()
+            .run(
+                || {
+                    self.client
+                        .delete_object()
+                        .bucket(&self.config.bucket)
+                        .key(key)
+                        .send()
+                },
+                is_retryable,
+            )
+            .await?;
+        Ok(())
+    }
+}
 
-        loop {
-            attempt += 1;
-            match self
-                .client
-                .delete_object()
-                .bucket(&self.config.bucket)
-                .key(key)
-                .send()
-                .await
-            {
-                Ok(_) => return Ok(()),
-                Err(_e) if attempt < self.config.max_retries => {
-                    let delay = self.config.retry_base_delay * (1 << (attempt - 1));
-                    sleep(Duration::from_millis(delay)).await;
-                    continue;
-                }
-                Err(e) => return Err(e.into()),
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_s3::operation::get_object::GetObjectError;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_transient_errors_are_retryable() {
+        assert!(is_transient(503, None));
+        assert!(is_transient(500, Some("InternalError")));
+        assert!(is_transient(429, None));
+        assert!(is_transient(400, Some("RequestTimeout")));
+        assert!(is_transient(403, Some("SlowDown")));
+
+        assert!(!is_transient(404, Some("NoSuchKey")));
+        assert!(!is_transient(403, Some("AccessDenied")));
+        assert!(!is_transient(403, Some("InvalidAccessKeyId")));
+        assert!(!is_transient(400, None));
+
+        let timeout = SdkError::<GetObjectError, HttpResponse>::timeout_error("timed out");
+        assert!(is_retryable(&timeout));
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_backs_off_until_success() {
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(1),
+        };
+        let attempts = &AtomicU32::new(0);
+
+        let result = policy
+            .run(
+                || async move {
+                    match attempts.fetch_add(1, Ordering::SeqCst) {
+                        0 | 1 => Err("throttled"),
+                        _ => Ok("data"),
+                    }
+                },
+                |_| true,
+            )
+            .await;
+        assert_eq!(result, Ok("data"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        // Non-retryable errors are returned after the first attempt.
+        attempts.store(0, Ordering::SeqCst);
+        let result: std::result::Result<(), _> = policy
+            .run(
+                || async move {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    Err("access denied")
+                },
+                |_| false,
+            )
+            .await;
+        assert_eq!(result, Err("access denied"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        // The last error surfaces once the attempts are used up.
+        attempts.store(0, Ordering::SeqCst);
+        let result: std::result::Result<(), _> = policy
+            .run(
+                || async move {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    Err("unavailable")
+                },
+                |_| true,
+            )
+            .await;
+        assert_eq!(result, Err("unavailable"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
     }
 }
@@ -0,0 +1,532 @@
+//! Optional compression, encryption and checksums of block payloads stored by `ObjectBlockStore`.
+//!
+//! An encoded block is stored behind a fixed-size header:
+//!
+//! ```text
+//! | "SFBK" (4) | version (1) | codec (1) | flags (1) | reserved (1) | original len, LE (8) |
+//! ```
+//!
+//! When the `checksum` flag is set, the CRC32C of the original payload follows the header (4 bytes,
+//! LE). When the `encrypted` flag is set, a 12-byte AES-GCM nonce comes next and the rest of the
+//! object is the sealed (possibly compressed) payload. Each block is sealed with its own key,
+//! derived from the master key and the object key, and everything before the nonce is
+//! authenticated as AAD.
+//!
+//! Blocks written without compression, encryption or checksum are stored as-is, so their bytes
+//! can still be range-read in place. The only exception is a plain payload that happens to start
+//! with the magic: it gets a header with the `none` codec, which keeps header detection
+//! unambiguous. Reads therefore never depend on the current configuration, and turning
+//! compression or checksums on or off later is safe. Encryption is the exception: once a key is
+//! configured, objects that are not encrypted fail the read, since anyone able to write to the
+//! bucket could otherwise swap in plaintext. `allow_plaintext` lifts this while a bucket written
+//! before encryption was turned on is being migrated.
+
+use anyhow::{Context, anyhow, bail};
+use bytes::Bytes;
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    digest, hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use std::fmt;
+use thiserror::Error;
+
+pub const BLOCK_HEADER_LEN: usize = 16;
+
+const BLOCK_HEADER_MAGIC: &[u8; 4] = b"SFBK";
+const BLOCK_HEADER_VERSION: u8 = 1;
+const CODEC_NONE: u8 = 0;
+const CODEC_ZSTD: u8 = 1;
+const FLAG_ENCRYPTED: u8 = 0x1;
+const FLAG_CHECKSUM: u8 = 0x2;
+const CHECKSUM_LEN: usize = 4;
+const BLOCK_KEY_CONTEXT: &[u8] = b"slayerfs-block-key";
+const BLOCK_DIGEST_CONTEXT: &[u8] = b"slayerfs-block-digest";
+
+/// Codec applied to block payloads before they are uploaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// zstd at the given level (1-22; 3 is zstd's own default).
+    Zstd { level: i32 },
+}
+
+impl Compression {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            Compression::Zstd { level } => {
+                if !zstd::compression_level_range().contains(level) {
+                    bail!("zstd level {level} is out of range");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Client-side AES-256-GCM encryption of block payloads.
+#[derive(Clone)]
+pub struct Encryption {
+    master_key: [u8; 32],
+}
+
+impl Encryption {
+    pub fn new(master_key: [u8; 32]) -> Self {
+        Self { master_key }
+    }
+
+    /// Per-block key: HMAC-SHA256(master key, context || object key).
+    fn block_key(&self, object_key: &str) -> anyhow::Result<LessSafeKey> {
+        let prk = hmac::Key::new(hmac::HMAC_SHA256, &self.master_key);
+        let mut ctx = hmac::Context::with_key(&prk);
+        ctx.update(BLOCK_KEY_CONTEXT);
+        ctx.update(object_key.as_bytes());
+        let tag = ctx.sign();
+
+        let key = UnboundKey::new(&AES_256_GCM, tag.as_ref())
+            .map_err(|_| anyhow!("failed to derive block key for {object_key}"))?;
+        Ok(LessSafeKey::new(key))
+    }
+}
+
+impl fmt::Debug for Encryption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Encryption")
+            .field("master_key", &"<redacted>")
+            .finish()
+    }
+}
+
+/// A block payload did not match the checksum recorded when it was written.
+///
+/// Returned (inside `anyhow::Error`) by block reads; callers can `downcast_ref` it to retry or
+/// fail fast instead of serving corrupt data.
+#[derive(Debug, Clone, Error)]
+#[error("checksum mismatch for block {key}: expected {expected:#010x}, got {actual:#010x}")]
+pub struct BlockChecksumMismatch {
+    pub key: String,
+    pub expected: u32,
+    pub actual: u32,
+}
+
+/// Encoder/decoder for the objects backing blocks.
+#[derive(Debug, Clone, Default)]
+pub struct BlockCodec {
+    pub compression: Option<Compression>,
+    pub encryption: Option<Encryption>,
+    /// Record a CRC32C of the payload on write.
+    pub checksum: bool,
+    /// Verify recorded checksums on read.
+    pub verify_checksum: bool,
+    /// With encryption configured, still accept objects that are not encrypted. They are
+    /// returned unauthenticated.
+    pub allow_plaintext: bool,
+}
+
+impl BlockCodec {
+    /// Whether blocks written by this codec are stored verbatim (and can be range-read in place).
+    pub fn is_passthrough(&self) -> bool {
+        self.compression.is_none() && self.encryption.is_none() && !self.checksum
+    }
+
+    /// Fingerprint of a block payload as this codec would store it under `object_key`, used to
+    /// skip re-uploading unchanged blocks. The codec settings are part of it, so changing them
+    /// rewrites blocks in the new format. With encryption it is an HMAC under the master key and
+    /// reveals nothing about the plaintext.
+    pub fn digest(&self, object_key: &str, parts: &[Bytes]) -> String {
+        let (codec, level) = match self.compression {
+            Some(Compression::Zstd { level }) => (CODEC_ZSTD, level),
+            None => (CODEC_NONE, 0),
+        };
+        let mut flags = 0;
+        if self.encryption.is_some() {
+            flags |= FLAG_ENCRYPTED;
+        }
+        if self.checksum {
+            flags |= FLAG_CHECKSUM;
+        }
+        let mut settings = vec![codec, flags];
+        settings.extend_from_slice(&level.to_le_bytes());
+
+        match &self.encryption {
+            Some(encryption) => {
+                let key = hmac::Key::new(hmac::HMAC_SHA256, &encryption.master_key);
+                let mut ctx = hmac::Context::with_key(&key);
+                ctx.update(BLOCK_DIGEST_CONTEXT);
+                ctx.update(object_key.as_bytes());
+                ctx.update(&settings);
+                for part in parts {
+                    ctx.update(part);
+                }
+                hex::encode(ctx.sign())
+            }
+            None => {
+                let mut ctx = digest::Context::new(&digest::SHA256);
+                ctx.update(&settings);
+                for part in parts {
+                    ctx.update(part);
+                }
+                hex::encode(ctx.finish())
+            }
+        }
+    }
+
+    /// Turn the parts of a block payload into the parts of the object stored under `object_key`.
+    pub fn encode(&self, object_key: &str, parts: Vec<Bytes>) -> anyhow::Result<Vec<Bytes>> {
+        let original_len = parts.iter().map(|p| p.len()).sum::<usize>();
+
+        if self.is_passthrough() {
+            let mut prefix = Vec::with_capacity(BLOCK_HEADER_LEN);
+            for part in &parts {
+                let take = (BLOCK_HEADER_LEN - prefix.len()).min(part.len());
+                prefix.extend_from_slice(&part[..take]);
+                if prefix.len() == BLOCK_HEADER_LEN {
+                    break;
+                }
+            }
+
+            if !has_header(&prefix) {
+                return Ok(parts);
+            }
+            let mut wrapped = vec![Bytes::from(header(CODEC_NONE, 0, original_len))];
+            wrapped.extend(parts);
+            return Ok(wrapped);
+        }
+
+        let mut payload = Vec::with_capacity(original_len);
+        for part in &parts {
+            payload.extend_from_slice(part);
+        }
+        let checksum = self.checksum.then(|| crc32c::crc32c(&payload));
+
+        let codec = match self.compression {
+            Some(Compression::Zstd { level }) => {
+                payload =
+                    zstd::bulk::compress(&payload, level).context("zstd compression failed")?;
+                CODEC_ZSTD
+            }
+            None => CODEC_NONE,
+        };
+
+        let mut flags = 0;
+        if checksum.is_some() {
+            flags |= FLAG_CHECKSUM;
+        }
+        if self.encryption.is_some() {
+            flags |= FLAG_ENCRYPTED;
+        }
+        let mut meta = header(codec, flags, original_len);
+        if let Some(checksum) = checksum {
+            meta.extend_from_slice(&checksum.to_le_bytes());
+        }
+
+        let Some(encryption) = &self.encryption else {
+            return Ok(vec![Bytes::from(meta), Bytes::from(payload)]);
+        };
+
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| anyhow!("failed to generate block nonce"))?;
+        encryption
+            .block_key(object_key)?
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(&meta[..]),
+                &mut payload,
+            )
+            .map_err(|_| anyhow!("failed to encrypt block {object_key}"))?;
+
+        Ok(vec![
+            Bytes::from(meta),
+            Bytes::copy_from_slice(&nonce),
+            Bytes::from(payload),
+        ])
+    }
+
+    /// Recover the block payload from the object stored under `object_key`.
+    pub fn decode(&self, object_key: &str, data: Bytes) -> anyhow::Result<Bytes> {
+        if !has_header(&data) {
+            self.check_plaintext(object_key)?;
+            return Ok(data);
+        }
+
+        let version = data[4];
+        if version != BLOCK_HEADER_VERSION {
+            bail!("block {object_key}: unsupported header version {version}");
+        }
+        let codec = data[5];
+        let flags = data[6];
+        let original_len = u64::from_le_bytes(data[8..BLOCK_HEADER_LEN].try_into()?) as usize;
+        let mut meta_len = BLOCK_HEADER_LEN;
+        let mut checksum = None;
+        if flags & FLAG_CHECKSUM != 0 {
+            let Some(raw) = data.get(meta_len..meta_len + CHECKSUM_LEN) else {
+                bail!("block {object_key} is truncated");
+            };
+            checksum = Some(u32::from_le_bytes(raw.try_into()?));
+            meta_len += CHECKSUM_LEN;
+        }
+        let mut body = data.slice(meta_len..);
+
+        if flags & FLAG_ENCRYPTED != 0 {
+            let Some(encryption) = &self.encryption else {
+                bail!("block {object_key} is encrypted but no encryption key is configured");
+            };
+            if body.len() < NONCE_LEN {
+                bail!("block {object_key} is truncated");
+            }
+            let nonce = Nonce::try_assume_unique_for_key(&body[..NONCE_LEN])
+                .map_err(|_| anyhow!("block {object_key} has an invalid nonce"))?;
+            let mut sealed = body[NONCE_LEN..].to_vec();
+            let plain_len = encryption
+                .block_key(object_key)?
+                .open_in_place(nonce, Aad::from(&data[..meta_len]), &mut sealed)
+                .map_err(|_| {
+                    anyhow!(
+                        "integrity check failed for block {object_key}: \
+                         wrong encryption key or corrupted data"
+                    )
+                })?
+                .len();
+            sealed.truncate(plain_len);
+            body = Bytes::from(sealed);
+        } else {
+            self.check_plaintext(object_key)?;
+        }
+
+        let payload = match codec {
+            CODEC_NONE => body,
+            CODEC_ZSTD => Bytes::from(
+                zstd::bulk::decompress(&body, original_len)
+                    .with_context(|| format!("block {object_key}: zstd decompression failed"))?,
+            ),
+            other => bail!("block {object_key}: unknown codec {other}"),
+        };
+
+        if payload.len() != original_len {
+            bail!(
+                "block {object_key}: length mismatch, header says {original_len}, got {}",
+                payload.len()
+            );
+        }
+
+        if let Some(expected) = checksum
+            && self.verify_checksum
+        {
+            let actual = crc32c::crc32c(&payload);
+            if actual != expected {
+                return Err(BlockChecksumMismatch {
+                    key: object_key.to_string(),
+                    expected,
+                    actual,
+                }
+                .into());
+            }
+        }
+        Ok(payload)
+    }
+
+    /// Refuse an object stored without encryption when a key is configured.
+    fn check_plaintext(&self, object_key: &str) -> anyhow::Result<()> {
+        if self.encryption.is_some() && !self.allow_plaintext {
+            bail!("integrity check failed for block {object_key}: block is not encrypted");
+        }
+        Ok(())
+    }
+}
+
+/// Whether `data` starts with a block header.
+pub fn has_header(data: &[u8]) -> bool {
+    data.len() >= BLOCK_HEADER_LEN && data[..BLOCK_HEADER_MAGIC.len()] == BLOCK_HEADER_MAGIC[..]
+}
+
+fn header(codec: u8, flags: u8, original_len: usize) -> Vec<u8> {
+    let mut header = Vec::with_capacity(BLOCK_HEADER_LEN + CHECKSUM_LEN);
+    header.extend_from_slice(BLOCK_HEADER_MAGIC);
+    header.push(BLOCK_HEADER_VERSION);
+    header.push(codec);
+    header.push(flags);
+    header.push(0);
+    header.extend_from_slice(&(original_len as u64).to_le_bytes());
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "chunks/1/0";
+
+    fn concat(parts: &[Bytes]) -> Bytes {
+        let mut out = Vec::new();
+        for part in parts {
+            out.extend_from_slice(part);
+        }
+        Bytes::from(out)
+    }
+
+    fn zstd() -> BlockCodec {
+        BlockCodec {
+            compression: Some(Compression::Zstd { level: 3 }),
+            ..Default::default()
+        }
+    }
+
+    fn encrypted(master_key: [u8; 32]) -> BlockCodec {
+        BlockCodec {
+            encryption: Some(Encryption::new(master_key)),
+            ..Default::default()
+        }
+    }
+
+    fn checksummed(verify_checksum: bool) -> BlockCodec {
+        BlockCodec {
+            checksum: true,
+            verify_checksum,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn zstd_round_trip() {
+        let payload = Bytes::from(vec![7u8; 64 * 1024]);
+        let encoded = concat(&zstd().encode(KEY, vec![payload.clone()]).unwrap());
+        assert!(encoded.len() < payload.len());
+        assert_eq!(zstd().decode(KEY, encoded).unwrap(), payload);
+    }
+
+    #[test]
+    fn plain_payload_is_stored_as_is() {
+        let codec = BlockCodec::default();
+        let parts = vec![Bytes::from_static(b"hello "), Bytes::from_static(b"world")];
+        let encoded = concat(&codec.encode(KEY, parts.clone()).unwrap());
+        assert_eq!(encoded, concat(&parts));
+        assert_eq!(codec.decode(KEY, encoded).unwrap(), concat(&parts));
+    }
+
+    #[test]
+    fn plain_payload_looking_like_a_header_is_wrapped() {
+        let codec = BlockCodec::default();
+        let mut data = BLOCK_HEADER_MAGIC.to_vec();
+        data.extend_from_slice(&[0xAB; 32]);
+        let parts = vec![
+            Bytes::from(data[..2].to_vec()),
+            Bytes::from(data[2..].to_vec()),
+        ];
+
+        let encoded = concat(&codec.encode(KEY, parts).unwrap());
+        assert_eq!(encoded.len(), data.len() + BLOCK_HEADER_LEN);
+        assert_eq!(codec.decode(KEY, encoded).unwrap(), Bytes::from(data));
+    }
+
+    #[test]
+    fn encrypted_round_trip_and_wrong_key() {
+        let payload = Bytes::from_static(b"secret block payload");
+        let encoded = concat(
+            &encrypted([1; 32])
+                .encode(KEY, vec![payload.clone()])
+                .unwrap(),
+        );
+        assert!(!encoded.windows(payload.len()).any(|w| w == payload));
+        assert_eq!(
+            encrypted([1; 32]).decode(KEY, encoded.clone()).unwrap(),
+            payload
+        );
+
+        let err = encrypted([2; 32]).decode(KEY, encoded.clone()).unwrap_err();
+        assert!(err.to_string().contains("integrity check failed"));
+        // The per-block key is bound to the object key, so blocks cannot be swapped.
+        let err = encrypted([1; 32])
+            .decode("chunks/1/1", encoded.clone())
+            .unwrap_err();
+        assert!(err.to_string().contains("integrity check failed"));
+        let err = BlockCodec::default().decode(KEY, encoded).unwrap_err();
+        assert!(err.to_string().contains("no encryption key"));
+    }
+
+    #[test]
+    fn encryption_rejects_plaintext_blocks() {
+        let payload = Bytes::from_static(b"block payload");
+        let headerless = concat(
+            &BlockCodec::default()
+                .encode(KEY, vec![payload.clone()])
+                .unwrap(),
+        );
+        let checksummed = concat(
+            &checksummed(true)
+                .encode(KEY, vec![payload.clone()])
+                .unwrap(),
+        );
+
+        for encoded in [headerless, checksummed] {
+            let err = encrypted([1; 32]).decode(KEY, encoded.clone()).unwrap_err();
+            assert!(err.to_string().contains("not encrypted"), "{err}");
+
+            let migrating = BlockCodec {
+                allow_plaintext: true,
+                ..encrypted([1; 32])
+            };
+            assert_eq!(migrating.decode(KEY, encoded).unwrap(), payload);
+        }
+    }
+
+    #[test]
+    fn checksum_detects_corruption() {
+        let payload = Bytes::from_static(b"checksummed block payload");
+        let mut encoded = concat(
+            &checksummed(true)
+                .encode(KEY, vec![payload.clone()])
+                .unwrap(),
+        )
+        .to_vec();
+        assert_eq!(
+            checksummed(true)
+                .decode(KEY, Bytes::from(encoded.clone()))
+                .unwrap(),
+            payload
+        );
+
+        *encoded.last_mut().unwrap() ^= 0xFF;
+        let err = checksummed(true)
+            .decode(KEY, Bytes::from(encoded.clone()))
+            .unwrap_err();
+        let mismatch = err.downcast_ref::<BlockChecksumMismatch>().unwrap();
+        assert_eq!(mismatch.key, KEY);
+
+        // Verification disabled: the corrupt payload is returned as stored.
+        let decoded = checksummed(false)
+            .decode(KEY, Bytes::from(encoded))
+            .unwrap();
+        assert_ne!(decoded, payload);
+    }
+
+    #[test]
+    fn digest_tracks_payload_and_settings() {
+        let whole = vec![Bytes::from_static(b"hello world")];
+        let split = vec![Bytes::from_static(b"hello "), Bytes::from_static(b"world")];
+        let plain = BlockCodec::default();
+        assert_eq!(plain.digest(KEY, &whole), plain.digest(KEY, &split));
+        assert_ne!(
+            plain.digest(KEY, &whole),
+            plain.digest(KEY, &[Bytes::from_static(b"hello there")])
+        );
+
+        // Blocks are rewritten when the codec or the encryption key changes.
+        let digests = [
+            plain.digest(KEY, &whole),
+            zstd().digest(KEY, &whole),
+            checksummed(true).digest(KEY, &whole),
+            encrypted([1; 32]).digest(KEY, &whole),
+            encrypted([2; 32]).digest(KEY, &whole),
+        ];
+        for (i, a) in digests.iter().enumerate() {
+            for b in &digests[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+        assert_eq!(
+            encrypted([1; 32]).digest(KEY, &split),
+            encrypted([1; 32]).digest(KEY, &whole)
+        );
+    }
+}
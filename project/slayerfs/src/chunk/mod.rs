@@ -15,6 +15,7 @@
 #![allow(unused_imports)]
 
 pub mod cache;
+pub mod codec;
 pub mod compact;
 pub mod layout;
 pub mod reader;
@@ -35,5 +36,5 @@ pub use layout::{
 pub use singleflight::SingleFlight;
 pub use slice::{BlockSpan, ChunkOffset, SliceDesc, SliceOffset, block_span_iter_slice};
 pub use span::{BlockTag, ChunkTag, PageTag, Span, SpanTag};
-pub use store::{BlockStore, InMemoryBlockStore, ObjectBlockStore, S3BlockStore};
+pub use store::{BlockStore, InMemoryBlockStore, ObjectBlockStore, ReadHint, S3BlockStore};
 pub use util::ChunkSpan;
@@ -1,5 +1,8 @@
 //! Storage backends: asynchronous block-level IO traits and in-memory implementations.
 
+use crate::chunk::codec::{
+    self, BLOCK_HEADER_LEN, BlockChecksumMismatch, BlockCodec, Compression, Encryption,
+};
 use crate::chunk::singleflight::SingleFlight;
 use crate::utils::NumCastExt;
 use crate::utils::zero::make_zero_bytes;
@@ -10,11 +13,22 @@ use crate::{
 use anyhow::{self, Context};
 use async_trait::async_trait;
 use bytes::Bytes;
+use dashmap::DashMap;
 use futures::executor::block_on;
 use hex::encode;
 use moka::{Entry, ops::compute::Op};
 use sha2::{Digest, Sha256};
-use std::{collections::HashMap, fs, io::SeekFrom, path::PathBuf, sync::LazyLock};
+use std::{
+    collections::HashMap,
+    fs,
+    io::SeekFrom,
+    path::PathBuf,
+    sync::{
+        Arc, LazyLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
 use tokio::{
     io::{self, AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
     sync::RwLock,
@@ -58,12 +72,49 @@ pub trait BlockStore {
 
     async fn read_range(&self, key: BlockKey, offset: u64, buf: &mut [u8]) -> anyhow::Result<()>;
 
+    /// Like `read_range`, but lets the caller override how the block is fetched. Stores without
+    /// a choice of read strategy ignore the hint.
+    async fn read_range_with_hint(
+        &self,
+        key: BlockKey,
+        offset: u64,
+        buf: &mut [u8],
+        _hint: ReadHint,
+    ) -> anyhow::Result<()> {
+        self.read_range(key, offset, buf).await
+    }
+
     /// Delete `block_count` blocks starting from `key.1` (block_index) for slice `key.0`.
     async fn delete_range(&self, key: BlockKey, block_count: u64) -> anyhow::Result<()>;
+
+    /// Hit/miss counters of the store's read cache. Stores without a cache report zeros.
+    fn cache_stats(&self) -> BlockCacheStats {
+        BlockCacheStats::default()
+    }
+}
+
+/// Read cache counters of a block store.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockCacheStats {
+    pub hits: u64,
+    pub misses: u64,
 }
 
 pub type BlockKey = (u64 /*slice_id*/, u32 /*block_index*/);
 
+/// Per-call override of the read strategy of `ObjectBlockStore`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReadHint {
+    /// Range read up to `range_read_threshold` of the block, full block read above it.
+    #[default]
+    Auto,
+    /// Always range read, e.g. for latency-sensitive reads. Blocks that carry a codec header
+    /// still need a full block read to be decoded.
+    Range,
+    /// Always read the whole block, e.g. to coalesce with other readers of the same block.
+    Full,
+}
+
 /// Simple in-memory implementation for local development/testing.
 #[derive(Default)]
 #[allow(dead_code)]
@@ -122,16 +173,41 @@ impl BlockStore for InMemoryBlockStore {
     }
 }
 
+/// Upper bound on decoded bytes kept for read-ahead.
+const PREFETCH_CACHE_BYTES: u64 = 256 * 1024 * 1024;
+/// Prefetched blocks that nobody reads within this window are dropped.
+const PREFETCH_CACHE_IDLE: Duration = Duration::from_secs(30);
+/// Number of slices whose last served offset is remembered for sequential detection.
+const READ_CURSOR_CAPACITY: u64 = 4096;
+
 /// BlockStore backed by cadapter::client (key space `chunks/{chunk_id}/{block_index}`).
 pub struct ObjectBlockStore<B: ObjectBackend> {
-    client: ObjectClient<B>,
+    /// Read path state, shared with background prefetch tasks.
+    fetcher: Arc<BlockFetcher<B>>,
     #[allow(dead_code)]
     block_cache: ChunksCache,
+    /// Configuration for read strategy
+    config: BlockStoreConfig,
+    /// End offset of the last read served for each slice, used to detect sequential access.
+    read_cursors: moka::future::Cache<u64, u64>,
+    /// Reads served from prefetched blocks.
+    cache_hits: AtomicU64,
+    /// Reads that had to go to the object store.
+    cache_misses: AtomicU64,
+}
+
+struct BlockFetcher<B: ObjectBackend> {
+    client: ObjectClient<B>,
     /// SingleFlight controller for coalescing concurrent reads to the same block
     /// Thread-safe and shared across the store lifetime so concurrent requests can coalesce.
     read_flight: SingleFlight<BlockKey, Bytes>,
-    /// Configuration for read strategy
-    config: BlockStoreConfig,
+    /// Compression/encryption/checksums applied to block objects, derived from `config`.
+    codec: BlockCodec,
+    /// Decoded blocks fetched ahead of sequential readers. Only filled when prefetch is enabled.
+    prefetched: moka::future::Cache<BlockKey, Arc<Bytes>>,
+    /// Blocks with a prefetch in flight, flagged once a write or delete makes the fetched bytes
+    /// stale so the prefetch does not put them back into `prefetched`.
+    prefetching: DashMap<BlockKey, bool>,
 }
 
 /// Configuration for ObjectBlockStore read strategy
@@ -142,6 +218,30 @@ pub struct BlockStoreConfig {
     /// For ranges smaller than this threshold, use direct range read instead of full block read
     /// Default is 25% of block size (1MB for 4MB blocks)
     pub range_read_threshold: f32,
+    /// Codec applied to block payloads on write (default: none). Reads detect the codec from the
+    /// object header, so blocks written under a different setting stay readable.
+    pub compression: Option<Compression>,
+    /// Client-side AES-256-GCM encryption of block payloads (default: none). Encrypted blocks
+    /// cannot be read without the same master key, and with a key configured blocks that are
+    /// not encrypted fail to read.
+    pub encryption: Option<Encryption>,
+    /// Accept blocks stored without encryption even though `encryption` is set (default:
+    /// false). Only meant for migrating a bucket written before encryption was enabled: such
+    /// blocks are not authenticated, so anyone with write access to the bucket can alter them.
+    pub allow_plaintext_blocks: bool,
+    /// Store a CRC32C of each block payload on write (default: false). Like compression, this
+    /// gives every block a header, so small reads fall back to full block reads.
+    pub checksum: bool,
+    /// Verify recorded checksums on read (default: true). A mismatch fails the read with
+    /// `BlockChecksumMismatch`. Disable only if the storage is already trusted.
+    pub verify_checksum: bool,
+    /// Number of blocks fetched ahead of a sequential reader (default: 0, disabled).
+    /// Prefetches share the SingleFlight of foreground reads, so no block is fetched twice.
+    pub prefetch_count: usize,
+    /// Skip uploading a block whose stored object already holds the same content (default:
+    /// false). Every upload records a digest of the payload with the object and first checks
+    /// the stored one, which costs an extra request per upload on backends that support it.
+    pub skip_unchanged_uploads: bool,
 }
 
 impl Default for BlockStoreConfig {
@@ -149,6 +249,13 @@ impl Default for BlockStoreConfig {
         Self {
             block_size: 4 * 1024 * 1024, // 4MB
             range_read_threshold: 0.25,  // 25% = 1MB for 4MB blocks
+            compression: None,
+            encryption: None,
+            allow_plaintext_blocks: false,
+            checksum: false,
+            verify_checksum: true,
+            prefetch_count: 0,
+            skip_unchanged_uploads: false,
         }
     }
 }
@@ -161,12 +268,25 @@ impl BlockStoreConfig {
         if !(0.0..=1.0).contains(&self.range_read_threshold) {
             anyhow::bail!("range_read_threshold must be between 0.0 and 1.0");
         }
+        if let Some(compression) = &self.compression {
+            compression.validate()?;
+        }
         Ok(())
     }
 
     fn range_size_threshold(&self) -> usize {
         (self.block_size as f32 * self.range_read_threshold) as usize
     }
+
+    fn codec(&self) -> BlockCodec {
+        BlockCodec {
+            compression: self.compression,
+            encryption: self.encryption.clone(),
+            checksum: self.checksum,
+            verify_checksum: self.verify_checksum,
+            allow_plaintext: self.allow_plaintext_blocks,
+        }
+    }
 }
 
 impl<B: ObjectBackend> ObjectBlockStore<B> {
@@ -181,10 +301,12 @@ impl<B: ObjectBackend> ObjectBlockStore<B> {
         let config = BlockStoreConfig::default();
         config.validate().expect("default config must be valid");
         Self {
-            client,
+            fetcher: Arc::new(BlockFetcher::new(client, config.codec())),
             block_cache,
-            read_flight: SingleFlight::new(),
             config,
+            read_cursors: Self::read_cursors(),
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
         }
     }
     /// Creates a new ObjectBlockStore with custom cache configuration
@@ -210,29 +332,171 @@ impl<B: ObjectBackend> ObjectBlockStore<B> {
         let block_cache = block_on(ChunksCache::new_with_config(cache_config))
             .map_err(|e| anyhow::anyhow!("Failed to create cache: {}", e))?;
         Ok(Self {
-            client,
+            fetcher: Arc::new(BlockFetcher::new(client, store_config.codec())),
             block_cache,
-            read_flight: SingleFlight::new(),
             config: store_config,
+            read_cursors: Self::read_cursors(),
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
         })
     }
 
+    fn read_cursors() -> moka::future::Cache<u64, u64> {
+        moka::future::Cache::builder()
+            .max_capacity(READ_CURSOR_CAPACITY)
+            .time_to_idle(PREFETCH_CACHE_IDLE)
+            .build()
+    }
+
     fn key_for(key: BlockKey) -> String {
         let (chunk_id, block_index) = key;
         format!("chunks/{chunk_id}/{block_index}")
     }
+
+    async fn put_block(&self, key: BlockKey, parts: Vec<Bytes>) -> anyhow::Result<()> {
+        let key_str = Self::key_for(key);
+        let digest = self
+            .config
+            .skip_unchanged_uploads
+            .then(|| self.fetcher.codec.digest(&key_str, &parts));
+        let parts = self.fetcher.codec.encode(&key_str, parts)?;
+        let client = &self.fetcher.client;
+        let uploaded = match digest {
+            Some(digest) => client.put_object_if_changed(&key_str, parts, &digest).await,
+            None => client
+                .put_object_vectored(&key_str, parts)
+                .await
+                .map(|_| true),
+        }
+        .map_err(|e| anyhow::anyhow!("object store put failed: {key_str}, {e:?}"))?;
+        if !uploaded {
+            tracing::trace!(key = %key_str, "block unchanged, upload skipped");
+        }
+        self.fetcher.invalidate_prefetched(key).await;
+        Ok(())
+    }
+
+    /// Remember where this read ended and, when it continues the previous read of the slice
+    /// into a new block, fetch the next `prefetch_count` blocks in the background.
+    async fn read_ahead(&self, key: BlockKey, offset: u64, len: usize)
+    where
+        B: 'static,
+    {
+        let (slice_id, block_index) = key;
+        let start = block_index as u64 * self.config.block_size as u64 + offset;
+        let previous = self.read_cursors.get(&slice_id).await;
+        self.read_cursors.insert(slice_id, start + len as u64).await;
+
+        if offset != 0 || previous != Some(start) {
+            return;
+        }
+
+        for ahead in 1..=self.config.prefetch_count as u32 {
+            let Some(next_index) = block_index.checked_add(ahead) else {
+                break;
+            };
+            let next = (slice_id, next_index);
+            if self.fetcher.prefetched.contains_key(&next) {
+                continue;
+            }
+            match self.fetcher.prefetching.entry(next) {
+                dashmap::Entry::Occupied(_) => continue,
+                dashmap::Entry::Vacant(entry) => {
+                    entry.insert(false);
+                }
+            }
+
+            let fetcher = self.fetcher.clone();
+            tokio::spawn(async move {
+                match fetcher.read_full_block(next).await {
+                    // Missing blocks (past the end of the slice) are not worth keeping.
+                    Ok(block) if block.is_empty() => {}
+                    Ok(block) => {
+                        fetcher.prefetched.insert(next, block).await;
+                        // Checked after the insert: a write flagging the block later also
+                        // invalidates after it, so the stale copy is dropped either way.
+                        if fetcher.prefetching.get(&next).is_some_and(|stale| *stale) {
+                            fetcher.prefetched.invalidate(&next).await;
+                        }
+                    }
+                    Err(e) => tracing::debug!(key = ?next, error = ?e, "block prefetch failed"),
+                }
+                fetcher.prefetching.remove(&next);
+            });
+        }
+    }
+}
+
+impl<B: ObjectBackend> BlockFetcher<B> {
+    fn new(client: ObjectClient<B>, codec: BlockCodec) -> Self {
+        Self {
+            client,
+            read_flight: SingleFlight::new(),
+            codec,
+            prefetched: moka::future::Cache::builder()
+                .weigher(|_, block: &Arc<Bytes>| block.len().try_into().unwrap_or(u32::MAX))
+                .max_capacity(PREFETCH_CACHE_BYTES)
+                .time_to_idle(PREFETCH_CACHE_IDLE)
+                .build(),
+            prefetching: DashMap::new(),
+        }
+    }
+
+    /// Drop the prefetched copy of `key`, including one still being fetched.
+    async fn invalidate_prefetched(&self, key: BlockKey) {
+        if let Some(mut stale) = self.prefetching.get_mut(&key) {
+            *stale = true;
+        }
+        self.prefetched.invalidate(&key).await;
+    }
+
+    /// Read and decode a whole block, coalescing concurrent reads of the same key.
+    async fn read_full_block(&self, key: BlockKey) -> anyhow::Result<Arc<Bytes>> {
+        let client = &self.client;
+        let codec = &self.codec;
+
+        self.read_flight
+            .execute(key, || async move {
+                let key_str = Self::key_for(key);
+
+                let data = client
+                    .get_object(&key_str)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("object store get failed: {key_str}, {e:?}"))?;
+
+                // A block that was never written reads as empty (zeros).
+                match data {
+                    Some(data) => codec.decode(&key_str, Bytes::from(data)),
+                    None => Ok(Bytes::new()),
+                }
+            })
+            .await
+            .map_err(|e| match e.downcast_ref::<BlockChecksumMismatch>() {
+                // Keep the mismatch typed so callers can tell corruption from other failures.
+                Some(mismatch) => anyhow::Error::new(mismatch.clone()),
+                None => anyhow::anyhow!("SingleFlight read failed: {e}"),
+            })
+    }
 }
 
 #[async_trait]
-impl<B: ObjectBackend + Send + Sync> BlockStore for ObjectBlockStore<B> {
+impl<B: ObjectBackend + Send + Sync + 'static> BlockStore for ObjectBlockStore<B> {
     async fn write_range(&self, key: BlockKey, offset: u64, data: &[u8]) -> anyhow::Result<u64> {
         let key_str = Self::key_for(key);
-        let mut buf = self
+        let existing = self
+            .fetcher
             .client
             .get_object(&key_str)
             .await
-            .map_err(|e| anyhow::anyhow!("object store get failed: {:?}", e))?
-            .unwrap_or_default();
+            .map_err(|e| anyhow::anyhow!("object store get failed: {:?}", e))?;
+        let mut buf = match existing {
+            Some(existing) => self
+                .fetcher
+                .codec
+                .decode(&key_str, Bytes::from(existing))?
+                .to_vec(),
+            None => Vec::new(),
+        };
 
         let start = offset.as_usize();
         let end = start + data.len();
@@ -240,10 +504,7 @@ impl<B: ObjectBackend + Send + Sync> BlockStore for ObjectBlockStore<B> {
             buf.resize(end, 0);
         }
         buf[start..end].copy_from_slice(data);
-        self.client
-            .put_object(&key_str, &buf)
-            .await
-            .map_err(|e| anyhow::anyhow!("object store put failed: {key_str}, {e:?}"))?;
+        self.put_block(key, vec![Bytes::from(buf)]).await?;
 
         Ok(data.len() as u64)
     }
@@ -255,7 +516,6 @@ impl<B: ObjectBackend + Send + Sync> BlockStore for ObjectBlockStore<B> {
         offset: u64,
         chunks: Vec<Bytes>,
     ) -> anyhow::Result<u64> {
-        let key_str = Self::key_for(key);
         let total_len = chunks.iter().map(|c| c.len()).sum::<usize>();
         if total_len == 0 {
             return Ok(0);
@@ -268,10 +528,7 @@ impl<B: ObjectBackend + Send + Sync> BlockStore for ObjectBlockStore<B> {
         }
         parts.extend(chunks);
 
-        self.client
-            .put_object_vectored(&key_str, parts)
-            .await
-            .map_err(|e| anyhow::anyhow!("object store put failed: {key_str}, {e:?}"))?;
+        self.put_block(key, parts).await?;
 
         Ok(total_len as u64)
     }
@@ -282,7 +539,6 @@ impl<B: ObjectBackend + Send + Sync> BlockStore for ObjectBlockStore<B> {
         offset: u64,
         data: &[u8],
     ) -> anyhow::Result<u64> {
-        let key_str = Self::key_for(key);
         if data.is_empty() {
             return Ok(0);
         }
@@ -294,43 +550,94 @@ impl<B: ObjectBackend + Send + Sync> BlockStore for ObjectBlockStore<B> {
         }
         parts.push(Bytes::copy_from_slice(data));
 
-        self.client
-            .put_object_vectored(&key_str, parts)
-            .await
-            .map_err(|e| anyhow::anyhow!("object store put failed: {key_str}, {e:?}"))?;
+        self.put_block(key, parts).await?;
 
         Ok(data.len() as u64)
     }
 
+    // Caller is responsible for zero-filling buf; this method only overwrites existing bytes.
+    async fn read_range(&self, key: BlockKey, offset: u64, buf: &mut [u8]) -> anyhow::Result<()> {
+        self.read_range_with_hint(key, offset, buf, ReadHint::Auto)
+            .await
+    }
+
     #[tracing::instrument(
         name = "ObjectBlockStore.read_range",
         level = "trace",
         skip(self, buf),
-        fields(key = ?key, offset, len = buf.len(), read_len = tracing::field::Empty, strategy = tracing::field::Empty)
+        fields(key = ?key, offset, len = buf.len(), hint = ?hint, read_len = tracing::field::Empty, strategy = tracing::field::Empty)
     )]
-    // Caller is responsible for zero-filling buf; this method only overwrites existing bytes.
-    async fn read_range(&self, key: BlockKey, offset: u64, buf: &mut [u8]) -> anyhow::Result<()> {
+    async fn read_range_with_hint(
+        &self,
+        key: BlockKey,
+        offset: u64,
+        buf: &mut [u8],
+        hint: ReadHint,
+    ) -> anyhow::Result<()> {
         let len = buf.len();
         let range_size_threshold = self.config.range_size_threshold();
 
+        if self.config.prefetch_count > 0 {
+            self.read_ahead(key, offset, len).await;
+
+            if let Some(block_data) = self.fetcher.prefetched.get(&key).await {
+                self.cache_hits.fetch_add(1, Ordering::Relaxed);
+                tracing::Span::current().record("strategy", "prefetched");
+                let copy_len = copy_block_range(&block_data, offset, buf);
+                tracing::Span::current().record("read_len", copy_len);
+                return Ok(());
+            }
+        }
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+
         // Boundary: len == threshold still uses direct range read; threshold is floor-casted usize.
 
-        // Smart strategy selection:
+        // Smart strategy selection, unless the caller forces one through `hint`:
         // 1. If the requested range is small (< threshold), use direct range read
         // 2. If the range is large, use SingleFlight to potentially coalesce with other requests
-        if len <= range_size_threshold {
+        // Compressed or encrypted objects cannot be range-read in place, so with either enabled
+        // every read goes through the full block path and works on the decoded bytes.
+        let range_read = match hint {
+            ReadHint::Auto => len <= range_size_threshold,
+            ReadHint::Range => true,
+            ReadHint::Full => false,
+        };
+        if range_read && self.fetcher.codec.is_passthrough() {
             // Strategy 1: Direct range read for small ranges (efficient for random access)
             tracing::Span::current().record("strategy", "direct_range");
 
             let key_str = Self::key_for(key);
-            let read_len = self
-                .client
-                .get_object_range(&key_str, offset, buf)
-                .await
+            // The block may still carry a codec header (written under a different config).
+            // A read at offset 0 sees the header in `buf` itself; otherwise probe for it alongside.
+            let covers_header = offset == 0 && len >= BLOCK_HEADER_LEN;
+            let mut probe = [0u8; BLOCK_HEADER_LEN];
+            let (read_len, encoded) = if covers_header {
+                let read_len = self
+                    .fetcher
+                    .client
+                    .get_object_range(&key_str, offset, buf)
+                    .await;
+                (read_len, codec::has_header(buf))
+            } else {
+                let (read_len, probe_len) = tokio::join!(
+                    self.fetcher.client.get_object_range(&key_str, offset, buf),
+                    self.fetcher
+                        .client
+                        .get_object_range(&key_str, 0, &mut probe),
+                );
+                let probe_len = probe_len.map_err(|e| {
+                    anyhow::anyhow!("object store range read failed: {key_str}, {e:?}")
+                })?;
+                (read_len, codec::has_header(&probe[..probe_len]))
+            };
+            let read_len = read_len
                 .map_err(|e| anyhow::anyhow!("object store range read failed: {key_str}, {e:?}"))?;
 
-            tracing::Span::current().record("read_len", read_len);
-            return Ok(());
+            if !encoded {
+                tracing::Span::current().record("read_len", read_len);
+                return Ok(());
+            }
+            buf.fill(0);
         }
 
         // Strategy 2: Full block read with SingleFlight (efficient for large reads and concurrent access)
@@ -338,53 +645,51 @@ impl<B: ObjectBackend + Send + Sync> BlockStore for ObjectBlockStore<B> {
 
         // Use SingleFlight to coalesce concurrent reads to the same block.
         // We read the entire block and then extract the requested range.
-        let client = &self.client;
-
-        let block_data =
-            self.read_flight
-                .execute(key, || async move {
-                    // Read the entire block
-                    let key_str = Self::key_for(key);
-
-                    let data = client.get_object(&key_str).await.map_err(|e| {
-                        anyhow::anyhow!("object store get failed: {key_str}, {e:?}")
-                    })?;
-
-                    Ok::<_, anyhow::Error>(Bytes::from(data.unwrap_or_default()))
-                })
-                .await
-                .map_err(|e| anyhow::anyhow!("SingleFlight read failed: {e}"))?;
+        let block_data = self.fetcher.read_full_block(key).await?;
 
         // Extract the requested range from the block data
-        let offset_usize = offset as usize;
-        let end = offset_usize + len;
-
-        let mut copy_len = 0;
-        if offset_usize < block_data.len() {
-            let copy_end = end.min(block_data.len());
-            copy_len = copy_end - offset_usize;
-            buf[..copy_len].copy_from_slice(&block_data.as_ref()[offset_usize..copy_end]);
-        }
+        let copy_len = copy_block_range(&block_data, offset, buf);
         tracing::Span::current().record("read_len", copy_len);
 
         Ok(())
     }
 
+    fn cache_stats(&self) -> BlockCacheStats {
+        BlockCacheStats {
+            hits: self.cache_hits.load(Ordering::Relaxed),
+            misses: self.cache_misses.load(Ordering::Relaxed),
+        }
+    }
+
     async fn delete_range(&self, key: BlockKey, block_count: u64) -> anyhow::Result<()> {
         let (chunk_id, block_index) = key;
         let start = block_index;
         let end = start + block_count.as_u32();
         for i in start..end {
             let key_str = Self::key_for((chunk_id, i));
-            self.client
+            self.fetcher
+                .client
                 .delete_object(&key_str)
                 .await
                 .map_err(|e| anyhow::anyhow!("object store delete failed: {key_str}, {e:?}"))?;
+            self.fetcher.invalidate_prefetched((chunk_id, i)).await;
         }
         Ok(())
     }
 }
 
+/// Copy `block[offset..]` into `buf`, returning how many bytes the block had to give.
+fn copy_block_range(block: &[u8], offset: u64, buf: &mut [u8]) -> usize {
+    let offset = offset.as_usize();
+    if offset >= block.len() {
+        return 0;
+    }
+
+    let copy_len = buf.len().min(block.len() - offset);
+    buf[..copy_len].copy_from_slice(&block[offset..offset + copy_len]);
+    copy_len
+}
+
 /// Convenience alias: BlockStore backed by the real S3 backend.
 #[allow(dead_code)]
 pub type S3BlockStore = ObjectBlockStore<crate::cadapter::s3::S3Backend>;
@@ -449,6 +754,507 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_compressed_blocks_survive_config_change() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let zstd_config = BlockStoreConfig {
+            compression: Some(Compression::Zstd { level: 3 }),
+            ..Default::default()
+        };
+        let store = ObjectBlockStore::new_with_configs(
+            ObjectClient::new(LocalFsBackend::new(tmp.path())),
+            ChunksCacheConfig::default(),
+            zstd_config,
+        )?;
+
+        let data: Vec<u8> = (0..64 * 1024).map(|i| (i % 7) as u8).collect();
+        store.write_fresh_range((7, 0), 4096, &data).await?;
+        store.write_range((7, 0), 0, b"head").await?;
+
+        let stored = std::fs::read(tmp.path().join("chunks/7/0"))?;
+        assert!(stored.len() < data.len());
+
+        // Compression disabled later: small range reads must still decode the block.
+        let plain = ObjectBlockStore::new_with_configs(
+            ObjectClient::new(LocalFsBackend::new(tmp.path())),
+            ChunksCacheConfig::default(),
+            BlockStoreConfig::default(),
+        )?;
+        for reader in [&store, &plain] {
+            let mut out = vec![0u8; 4];
+            reader.read_range((7, 0), 0, &mut out).await?;
+            assert_eq!(&out, b"head");
+
+            let mut out = vec![0u8; 1024];
+            reader.read_range((7, 0), 4096 + 100, &mut out).await?;
+            assert_eq!(out, data[100..1124]);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_blocks() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let open_store = |master_key: Option<[u8; 32]>| {
+            ObjectBlockStore::new_with_configs(
+                ObjectClient::new(LocalFsBackend::new(tmp.path())),
+                ChunksCacheConfig::default(),
+                BlockStoreConfig {
+                    encryption: master_key.map(Encryption::new),
+                    ..Default::default()
+                },
+            )
+        };
+        let store = open_store(Some([9; 32]))?;
+
+        let data = b"plaintext that must not reach the bucket".to_vec();
+        store.write_fresh_range((8, 0), 128, &data).await?;
+
+        let stored = std::fs::read(tmp.path().join("chunks/8/0"))?;
+        assert!(!stored.windows(data.len()).any(|w| w == data.as_slice()));
+
+        let mut out = vec![0u8; data.len()];
+        store.read_range((8, 0), 128, &mut out).await?;
+        assert_eq!(out, data);
+
+        for master_key in [Some([1; 32]), None] {
+            let mut out = vec![0u8; data.len()];
+            let err = open_store(master_key)?
+                .read_range((8, 0), 128, &mut out)
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("block chunks/8/0"), "{err}");
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_store_rejects_stripped_or_forged_headers() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let open_store = |allow_plaintext_blocks: bool| {
+            ObjectBlockStore::new_with_configs(
+                ObjectClient::new(LocalFsBackend::new(tmp.path())),
+                ChunksCacheConfig::default(),
+                BlockStoreConfig {
+                    encryption: Some(Encryption::new([9; 32])),
+                    allow_plaintext_blocks,
+                    ..Default::default()
+                },
+            )
+        };
+        let data = b"payload an attacker wants to replace".to_vec();
+        let path = tmp.path().join("chunks/10/0");
+
+        // Plaintext dropped in place of the encrypted object.
+        open_store(false)?
+            .write_fresh_range((10, 0), 0, &data)
+            .await?;
+        std::fs::write(&path, b"attacker controlled plaintext")?;
+        let mut out = vec![0u8; 16];
+        let err = open_store(false)?
+            .read_range((10, 0), 0, &mut out)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not encrypted"), "{err}");
+
+        // Header kept but the encrypted flag cleared, the rest forged as a plain payload.
+        open_store(false)?
+            .write_fresh_range((10, 0), 0, &data)
+            .await?;
+        let mut forged = std::fs::read(&path)?[..BLOCK_HEADER_LEN].to_vec();
+        forged[6] = 0;
+        forged[8..BLOCK_HEADER_LEN].copy_from_slice(&(data.len() as u64).to_le_bytes());
+        forged.extend_from_slice(&vec![b'x'; data.len()]);
+        std::fs::write(&path, forged)?;
+        let err = open_store(false)?
+            .read_range((10, 0), 0, &mut out)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not encrypted"), "{err}");
+
+        // Buckets being migrated can opt into reading plaintext blocks.
+        std::fs::write(&path, &data)?;
+        let mut out = vec![0u8; data.len()];
+        open_store(true)?.read_range((10, 0), 0, &mut out).await?;
+        assert_eq!(out, data);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_checksum_mismatch_is_reported() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let store = ObjectBlockStore::new_with_configs(
+            ObjectClient::new(LocalFsBackend::new(tmp.path())),
+            ChunksCacheConfig::default(),
+            BlockStoreConfig {
+                checksum: true,
+                ..Default::default()
+            },
+        )?;
+
+        let data = vec![5u8; 4096];
+        store.write_fresh_range((9, 0), 0, &data).await?;
+
+        let path = tmp.path().join("chunks/9/0");
+        let mut stored = std::fs::read(&path)?;
+        *stored.last_mut().unwrap() ^= 0xFF;
+        std::fs::write(&path, stored)?;
+
+        let mut out = vec![0u8; 16];
+        let err = store.read_range((9, 0), 100, &mut out).await.unwrap_err();
+        assert!(
+            err.downcast_ref::<BlockChecksumMismatch>().is_some(),
+            "{err}"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sequential_reads_prefetch_next_blocks() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let block_size = 64 * 1024;
+        let store = ObjectBlockStore::new_with_configs(
+            ObjectClient::new(LocalFsBackend::new(tmp.path())),
+            ChunksCacheConfig::default(),
+            BlockStoreConfig {
+                block_size,
+                prefetch_count: 2,
+                ..Default::default()
+            },
+        )?;
+
+        for index in 0..4u32 {
+            let data = vec![index as u8 + 1; block_size];
+            store.write_fresh_range((9, index), 0, &data).await?;
+        }
+
+        let mut out = vec![0u8; block_size];
+        store.read_range((9, 0), 0, &mut out).await?;
+        assert!(!store.fetcher.prefetched.contains_key(&(9, 1)));
+        store.read_range((9, 1), 0, &mut out).await?;
+
+        for _ in 0..100 {
+            if store.fetcher.prefetched.contains_key(&(9, 3)) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        // Served from the prefetched copy even though the object is gone.
+        std::fs::remove_file(tmp.path().join("chunks/9/2"))?;
+        store.read_range((9, 2), 0, &mut out).await?;
+        assert_eq!(out, vec![3u8; block_size]);
+
+        // Writes drop the prefetched copy.
+        store.write_fresh_range((9, 3), 0, &[7u8; 16]).await?;
+        let mut out = vec![0u8; 16];
+        store.read_range((9, 3), 0, &mut out).await?;
+        assert_eq!(out, vec![7u8; 16]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_during_prefetch_discards_fetched_block() -> anyhow::Result<()> {
+        use std::sync::atomic::AtomicBool;
+        use tokio::sync::Notify;
+
+        /// Local backend that holds the first fetch of `held` after reading it, until released.
+        struct GatedBackend {
+            inner: LocalFsBackend,
+            held: String,
+            armed: Arc<AtomicBool>,
+            reached: Arc<Notify>,
+            release: Arc<Notify>,
+        }
+
+        #[async_trait]
+        impl ObjectBackend for GatedBackend {
+            async fn put_object(&self, key: &str, data: &[u8]) -> anyhow::Result<()> {
+                self.inner.put_object(key, data).await
+            }
+
+            async fn get_object(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+                let data = self.inner.get_object(key).await?;
+                if key == self.held && self.armed.swap(false, Ordering::SeqCst) {
+                    self.reached.notify_one();
+                    self.release.notified().await;
+                }
+                Ok(data)
+            }
+
+            async fn get_object_range(
+                &self,
+                key: &str,
+                offset: u64,
+                buf: &mut [u8],
+            ) -> anyhow::Result<usize> {
+                self.inner.get_object_range(key, offset, buf).await
+            }
+
+            async fn get_etag(&self, key: &str) -> anyhow::Result<String> {
+                self.inner.get_etag(key).await
+            }
+
+            async fn delete_object(&self, key: &str) -> anyhow::Result<()> {
+                self.inner.delete_object(key).await
+            }
+        }
+
+        let tmp = tempfile::tempdir()?;
+        let block_size = 64 * 1024;
+        let reached = Arc::new(Notify::new());
+        let release = Arc::new(Notify::new());
+        let armed = Arc::new(AtomicBool::new(false));
+        let store = ObjectBlockStore::new_with_configs(
+            ObjectClient::new(GatedBackend {
+                inner: LocalFsBackend::new(tmp.path()),
+                held: "chunks/12/2".to_string(),
+                armed: armed.clone(),
+                reached: reached.clone(),
+                release: release.clone(),
+            }),
+            ChunksCacheConfig::default(),
+            BlockStoreConfig {
+                block_size,
+                prefetch_count: 1,
+                ..Default::default()
+            },
+        )?;
+
+        for index in 0..3u32 {
+            let data = vec![index as u8 + 1; block_size];
+            store.write_fresh_range((12, index), 0, &data).await?;
+        }
+
+        // The prefetch of block 2 reads the old bytes, then a write lands before it caches them.
+        armed.store(true, Ordering::SeqCst);
+        let mut out = vec![0u8; block_size];
+        store.read_range((12, 0), 0, &mut out).await?;
+        store.read_range((12, 1), 0, &mut out).await?;
+        reached.notified().await;
+        store
+            .write_fresh_range((12, 2), 0, &vec![9u8; block_size])
+            .await?;
+        release.notify_one();
+
+        for _ in 0..100 {
+            if store.fetcher.prefetching.is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(store.fetcher.prefetching.is_empty());
+        assert!(!store.fetcher.prefetched.contains_key(&(12, 2)));
+
+        store.read_range((12, 2), 0, &mut out).await?;
+        assert_eq!(out, vec![9u8; block_size]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_blocks_are_not_uploaded_again() -> anyhow::Result<()> {
+        use std::sync::Mutex;
+
+        /// Backend keeping the digest of every object, like S3Backend does in its metadata.
+        #[derive(Clone, Default)]
+        struct DigestBackend {
+            objects: Arc<Mutex<HashMap<String, (Vec<u8>, Option<String>)>>>,
+            uploads: Arc<AtomicU64>,
+        }
+
+        #[async_trait]
+        impl ObjectBackend for DigestBackend {
+            async fn put_object(&self, key: &str, data: &[u8]) -> anyhow::Result<()> {
+                self.uploads.fetch_add(1, Ordering::SeqCst);
+                let mut objects = self.objects.lock().unwrap();
+                objects.insert(key.to_string(), (data.to_vec(), None));
+                Ok(())
+            }
+
+            async fn put_object_if_changed(
+                &self,
+                key: &str,
+                chunks: Vec<Bytes>,
+                digest: &str,
+            ) -> anyhow::Result<bool> {
+                let mut objects = self.objects.lock().unwrap();
+                if objects
+                    .get(key)
+                    .is_some_and(|(_, stored)| stored.as_deref() == Some(digest))
+                {
+                    return Ok(false);
+                }
+                self.uploads.fetch_add(1, Ordering::SeqCst);
+                objects.insert(key.to_string(), (chunks.concat(), Some(digest.to_string())));
+                Ok(true)
+            }
+
+            async fn get_object(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+                let objects = self.objects.lock().unwrap();
+                Ok(objects.get(key).map(|(data, _)| data.clone()))
+            }
+
+            async fn get_object_range(
+                &self,
+                key: &str,
+                offset: u64,
+                buf: &mut [u8],
+            ) -> anyhow::Result<usize> {
+                let objects = self.objects.lock().unwrap();
+                let Some((data, _)) = objects.get(key) else {
+                    return Ok(0);
+                };
+                let start = (offset as usize).min(data.len());
+                let len = buf.len().min(data.len() - start);
+                buf[..len].copy_from_slice(&data[start..start + len]);
+                Ok(len)
+            }
+
+            async fn get_etag(&self, _key: &str) -> anyhow::Result<String> {
+                Ok(String::new())
+            }
+
+            async fn delete_object(&self, key: &str) -> anyhow::Result<()> {
+                self.objects.lock().unwrap().remove(key);
+                Ok(())
+            }
+        }
+
+        let backend = DigestBackend::default();
+        let open_store = |skip_unchanged_uploads: bool| {
+            ObjectBlockStore::new_with_configs(
+                ObjectClient::new(backend.clone()),
+                ChunksCacheConfig::default(),
+                BlockStoreConfig {
+                    skip_unchanged_uploads,
+                    ..Default::default()
+                },
+            )
+        };
+        let store = open_store(true)?;
+        let uploads = || backend.uploads.load(Ordering::SeqCst);
+
+        let data = vec![3u8; 4096];
+        store.write_fresh_range((11, 0), 0, &data).await?;
+        assert_eq!(uploads(), 1);
+
+        // Rewriting identical bytes leaves the object alone.
+        store.write_range((11, 0), 1024, &data[..512]).await?;
+        store.write_fresh_range((11, 0), 0, &data).await?;
+        assert_eq!(uploads(), 1);
+
+        store.write_range((11, 0), 100, b"edit").await?;
+        assert_eq!(uploads(), 2);
+        let mut out = vec![0u8; 4];
+        store.read_range((11, 0), 100, &mut out).await?;
+        assert_eq!(&out, b"edit");
+
+        // Disabled by default: every write is uploaded.
+        let plain = open_store(false)?;
+        plain.write_range((11, 0), 100, b"edit").await?;
+        assert_eq!(uploads(), 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retried_read_is_coalesced() -> anyhow::Result<()> {
+        use crate::cadapter::s3::RetryPolicy;
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use tokio::time::{Duration, sleep};
+
+        /// Backend whose first two fetches fail with a transient error, retried like S3Backend.
+        struct FlakyBackend {
+            data: Vec<u8>,
+            attempts: Arc<AtomicU32>,
+        }
+
+        #[async_trait]
+        impl ObjectBackend for FlakyBackend {
+            async fn put_object(&self, _key: &str, _data: &[u8]) -> anyhow::Result<()> {
+                Ok(())
+            }
+
+            async fn get_object(&self, _key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+                let policy = RetryPolicy {
+                    max_retries: 3,
+                    base_delay: Duration::from_millis(5),
+                };
+                policy
+                    .run(
+                        || async move {
+                            sleep(Duration::from_millis(10)).await;
+                            match self.attempts.fetch_add(1, Ordering::SeqCst) {
+                                0 | 1 => Err(anyhow::anyhow!("503 SlowDown")),
+                                _ => Ok(Some(self.data.clone())),
+                            }
+                        },
+                        |_| true,
+                    )
+                    .await
+            }
+
+            async fn get_object_range(
+                &self,
+                _key: &str,
+                _offset: u64,
+                _buf: &mut [u8],
+            ) -> anyhow::Result<usize> {
+                anyhow::bail!("range reads are not expected")
+            }
+
+            async fn get_etag(&self, _key: &str) -> anyhow::Result<String> {
+                Ok(String::new())
+            }
+
+            async fn delete_object(&self, _key: &str) -> anyhow::Result<()> {
+                Ok(())
+            }
+        }
+
+        let block_size = 64 * 1024;
+        let data: Vec<u8> = (0..block_size).map(|i| (i % 251) as u8).collect();
+        let attempts = Arc::new(AtomicU32::new(0));
+        let store = Arc::new(ObjectBlockStore::new_with_configs(
+            ObjectClient::new(FlakyBackend {
+                data: data.clone(),
+                attempts: attempts.clone(),
+            }),
+            ChunksCacheConfig::default(),
+            BlockStoreConfig {
+                block_size,
+                ..Default::default()
+            },
+        )?);
+
+        let handles: Vec<_> = (0..5)
+            .map(|_| {
+                let store = store.clone();
+                tokio::spawn(async move {
+                    let mut buf = vec![0u8; block_size];
+                    store
+                        .read_range_with_hint((3, 0), 0, &mut buf, ReadHint::Full)
+                        .await
+                        .map(|_| buf)
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.await??, data);
+        }
+        // One flight, retried twice: the waiting readers never started retries of their own.
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_intelligent_read_strategy() -> Result<(), Box<dyn std::error::Error>> {
         use crate::cadapter::client::{ObjectBackend, ObjectClient};
@@ -554,6 +1360,7 @@ mod tests {
         let config = BlockStoreConfig {
             block_size: 4 * 1024 * 1024,
             range_read_threshold: 0.25, // 1MB threshold
+            ..Default::default()
         };
         let store = Arc::new(ObjectBlockStore::new_with_configs(
             client,
@@ -614,6 +1421,42 @@ mod tests {
             "Coalesced path should not fall back to range reads",
         );
 
+        // Hints override the threshold in both directions
+        backend.reset_stats();
+        store
+            .read_range_with_hint((42, 3), 0, &mut large_buf, ReadHint::Range)
+            .await?;
+        let stats = backend.get_stats();
+        assert_eq!(
+            stats.get_object_range_calls, 1,
+            "Range hint forces range read"
+        );
+        assert_eq!(stats.get_object_calls, 0, "Range hint skips full read");
+        assert!(
+            large_buf
+                .iter()
+                .enumerate()
+                .all(|(i, b)| *b == (i % 256) as u8)
+        );
+
+        backend.reset_stats();
+        small_buf.fill(0);
+        store
+            .read_range_with_hint((42, 3), 0, &mut small_buf, ReadHint::Full)
+            .await?;
+        let stats = backend.get_stats();
+        assert_eq!(stats.get_object_calls, 1, "Full hint forces full read");
+        assert_eq!(
+            stats.get_object_range_calls, 0,
+            "Full hint skips range read"
+        );
+        assert!(
+            small_buf
+                .iter()
+                .enumerate()
+                .all(|(i, b)| *b == (i % 256) as u8)
+        );
+
         Ok(())
     }
 }
@@ -2,12 +2,16 @@ use clap::{Args, Parser, Subcommand, ValueEnum};
 use serde::Deserialize;
 use std::path::PathBuf;
 
-use crate::chunk::layout::{DEFAULT_BLOCK_SIZE, DEFAULT_CHUNK_SIZE};
+use crate::chunk::layout::{ChunkLayout, DEFAULT_BLOCK_SIZE, DEFAULT_CHUNK_SIZE};
+use crate::vfs::config::VFSConfig;
 
 pub const DEFAULT_DATA_DIR: &str = "./data";
 pub const DEFAULT_META_URL: &str = "sqlite::memory:";
 pub const DEFAULT_S3_PART_SIZE: usize = 16 * 1024 * 1024;
 pub const DEFAULT_S3_MAX_CONCURRENCY: usize = 8;
+pub const DEFAULT_AZURE_PART_SIZE: usize = 16 * 1024 * 1024;
+pub const DEFAULT_AZURE_MAX_CONCURRENCY: usize = 8;
+pub const DEFAULT_GCS_PART_SIZE: usize = 16 * 1024 * 1024;
 
 #[derive(Parser)]
 #[command(name = "slayerfs", version, about = "SlayerFS FUSE CLI")]
@@ -49,6 +53,10 @@ pub struct MountArgs {
     #[arg(long, value_name = "DIR")]
     pub data_dir: Option<PathBuf>,
 
+    /// Fsync every object and its directory before a write completes (only for localfs backend).
+    #[arg(long)]
+    pub localfs_fsync: Option<bool>,
+
     /// S3 bucket name (only for s3 backend).
     #[arg(long, value_name = "BUCKET")]
     pub s3_bucket: Option<String>,
@@ -73,6 +81,77 @@ pub struct MountArgs {
     #[arg(long)]
     pub s3_force_path_style: Option<bool>,
 
+    /// S3 access key id; defaults to the AWS credential chain (only for s3 backend).
+    #[arg(long, value_name = "KEY")]
+    pub s3_access_key: Option<String>,
+
+    /// S3 secret access key, required with --s3-access-key (only for s3 backend).
+    #[arg(long, value_name = "SECRET")]
+    pub s3_secret_key: Option<String>,
+
+    /// Azure storage account name (only for azure backend).
+    #[arg(long, value_name = "ACCOUNT")]
+    pub azure_account: Option<String>,
+
+    /// Azure blob container name (only for azure backend).
+    #[arg(long, value_name = "CONTAINER")]
+    pub azure_container: Option<String>,
+
+    /// Azure blob service endpoint URL, e.g. for Azurite (only for azure backend).
+    #[arg(long, value_name = "URL")]
+    pub azure_endpoint: Option<String>,
+
+    /// Azure storage account access key (only for azure backend).
+    #[arg(
+        long,
+        value_name = "KEY",
+        env = "AZURE_STORAGE_KEY",
+        hide_env_values = true
+    )]
+    pub azure_access_key: Option<String>,
+
+    /// Azure shared access signature token (only for azure backend).
+    #[arg(
+        long,
+        value_name = "TOKEN",
+        env = "AZURE_STORAGE_SAS_TOKEN",
+        hide_env_values = true
+    )]
+    pub azure_sas_token: Option<String>,
+
+    /// Azure block size in bytes for staged block-blob uploads (only for azure backend).
+    #[arg(long)]
+    pub azure_part_size: Option<usize>,
+
+    /// Azure maximum concurrent block uploads (only for azure backend).
+    #[arg(long)]
+    pub azure_max_concurrency: Option<usize>,
+
+    /// GCS bucket name (only for gcs backend).
+    #[arg(long, value_name = "BUCKET")]
+    pub gcs_bucket: Option<String>,
+
+    /// GCP project billed for requests, e.g. for requester-pays buckets (only for gcs backend).
+    #[arg(long, value_name = "PROJECT")]
+    pub gcs_project: Option<String>,
+
+    /// Service account JSON key file; defaults to Application Default Credentials (only for gcs
+    /// backend).
+    #[arg(long, value_name = "FILE")]
+    pub gcs_credentials: Option<PathBuf>,
+
+    /// Send unauthenticated requests, e.g. to a local emulator (only for gcs backend).
+    #[arg(long)]
+    pub gcs_anonymous: Option<bool>,
+
+    /// GCS endpoint URL (only for gcs backend).
+    #[arg(long, value_name = "URL")]
+    pub gcs_endpoint: Option<String>,
+
+    /// GCS resumable upload chunk size in bytes, a multiple of 256 KiB (only for gcs backend).
+    #[arg(long)]
+    pub gcs_part_size: Option<usize>,
+
     /// Metadata backend (sqlx, etcd or redis).
     #[arg(long, value_enum)]
     pub meta_backend: Option<MetaBackendKind>,
@@ -117,6 +196,8 @@ pub struct InfoArgs {
 pub enum DataBackendKind {
     LocalFs,
     S3,
+    Azure,
+    Gcs,
 }
 
 #[derive(ValueEnum, Deserialize, Clone, Copy, Debug)]
@@ -140,11 +221,14 @@ pub struct DataFileConfig {
     pub backend: Option<DataBackendKind>,
     pub localfs: Option<LocalFsFileConfig>,
     pub s3: Option<S3FileConfig>,
+    pub azure: Option<AzureFileConfig>,
+    pub gcs: Option<GcsFileConfig>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct LocalFsFileConfig {
     pub data_dir: Option<PathBuf>,
+    pub fsync: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -155,6 +239,29 @@ pub struct S3FileConfig {
     pub part_size: Option<usize>,
     pub max_concurrency: Option<usize>,
     pub force_path_style: Option<bool>,
+    pub access_key: Option<String>,
+    pub secret_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct AzureFileConfig {
+    pub account: Option<String>,
+    pub container: Option<String>,
+    pub endpoint: Option<String>,
+    pub access_key: Option<String>,
+    pub sas_token: Option<String>,
+    pub part_size: Option<usize>,
+    pub max_concurrency: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct GcsFileConfig {
+    pub bucket: Option<String>,
+    pub project: Option<String>,
+    pub credentials: Option<PathBuf>,
+    pub anonymous: Option<bool>,
+    pub endpoint: Option<String>,
+    pub part_size: Option<usize>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -186,12 +293,28 @@ pub struct MountConfig {
     pub mount_point: PathBuf,
     pub data_backend: DataBackendKind,
     pub data_dir: PathBuf,
+    pub localfs_fsync: bool,
     pub s3_bucket: Option<String>,
     pub s3_endpoint: Option<String>,
     pub s3_region: Option<String>,
     pub s3_part_size: usize,
     pub s3_max_concurrency: usize,
     pub s3_force_path_style: bool,
+    pub s3_access_key: Option<String>,
+    pub s3_secret_key: Option<String>,
+    pub azure_account: Option<String>,
+    pub azure_container: Option<String>,
+    pub azure_endpoint: Option<String>,
+    pub azure_access_key: Option<String>,
+    pub azure_sas_token: Option<String>,
+    pub azure_part_size: usize,
+    pub azure_max_concurrency: usize,
+    pub gcs_bucket: Option<String>,
+    pub gcs_project: Option<String>,
+    pub gcs_credentials: Option<PathBuf>,
+    pub gcs_anonymous: bool,
+    pub gcs_endpoint: Option<String>,
+    pub gcs_part_size: usize,
     pub meta_backend: MetaBackendKind,
     pub meta_url: String,
     pub meta_etcd_urls: Vec<String>,
@@ -212,6 +335,8 @@ impl MountConfig {
         let data_cfg = file_cfg.data.unwrap_or_default();
         let localfs_cfg = data_cfg.localfs.unwrap_or_default();
         let s3_cfg = data_cfg.s3.unwrap_or_default();
+        let azure_cfg = data_cfg.azure.unwrap_or_default();
+        let gcs_cfg = data_cfg.gcs.unwrap_or_default();
         let meta_cfg = file_cfg.meta.unwrap_or_default();
         let sqlx_cfg = meta_cfg.sqlx.unwrap_or_default();
         let redis_cfg = meta_cfg.redis.unwrap_or_default();
@@ -233,7 +358,7 @@ impl MountConfig {
             MetaBackendKind::Etcd => None,
         };
 
-        Ok(Self {
+        let config = Self {
             mount_point,
             data_backend: args
                 .data_backend
@@ -243,6 +368,7 @@ impl MountConfig {
                 .data_dir
                 .or(localfs_cfg.data_dir)
                 .unwrap_or_else(|| PathBuf::from(DEFAULT_DATA_DIR)),
+            localfs_fsync: args.localfs_fsync.or(localfs_cfg.fsync).unwrap_or(false),
             s3_bucket: args.s3_bucket.or(s3_cfg.bucket),
             s3_endpoint: args.s3_endpoint.or(s3_cfg.endpoint),
             s3_region: args.s3_region.or(s3_cfg.region),
@@ -258,6 +384,30 @@ impl MountConfig {
                 .s3_force_path_style
                 .or(s3_cfg.force_path_style)
                 .unwrap_or(false),
+            s3_access_key: args.s3_access_key.or(s3_cfg.access_key),
+            s3_secret_key: args.s3_secret_key.or(s3_cfg.secret_key),
+            azure_account: args.azure_account.or(azure_cfg.account),
+            azure_container: args.azure_container.or(azure_cfg.container),
+            azure_endpoint: args.azure_endpoint.or(azure_cfg.endpoint),
+            azure_access_key: args.azure_access_key.or(azure_cfg.access_key),
+            azure_sas_token: args.azure_sas_token.or(azure_cfg.sas_token),
+            azure_part_size: args
+                .azure_part_size
+                .or(azure_cfg.part_size)
+                .unwrap_or(DEFAULT_AZURE_PART_SIZE),
+            azure_max_concurrency: args
+                .azure_max_concurrency
+                .or(azure_cfg.max_concurrency)
+                .unwrap_or(DEFAULT_AZURE_MAX_CONCURRENCY),
+            gcs_bucket: args.gcs_bucket.or(gcs_cfg.bucket),
+            gcs_project: args.gcs_project.or(gcs_cfg.project),
+            gcs_credentials: args.gcs_credentials.or(gcs_cfg.credentials),
+            gcs_anonymous: args.gcs_anonymous.or(gcs_cfg.anonymous).unwrap_or(false),
+            gcs_endpoint: args.gcs_endpoint.or(gcs_cfg.endpoint),
+            gcs_part_size: args
+                .gcs_part_size
+                .or(gcs_cfg.part_size)
+                .unwrap_or(DEFAULT_GCS_PART_SIZE),
             meta_backend,
             meta_url: args
                 .meta_url
@@ -272,7 +422,17 @@ impl MountConfig {
                 .block_size
                 .or(layout_cfg.block_size)
                 .unwrap_or(DEFAULT_BLOCK_SIZE),
-        })
+        };
+
+        let layout = ChunkLayout {
+            chunk_size: config.chunk_size,
+            block_size: config.block_size,
+        };
+        VFSConfig::new(layout)
+            .write
+            .validate()
+            .map_err(|e| anyhow::anyhow!("invalid chunk/block layout: {e}"))?;
+        Ok(config)
     }
 }
 
@@ -292,4 +452,29 @@ mod tests {
             other => panic!("expected info command, got {other:?}"),
         }
     }
+
+    fn mount_args(extra: &[&str]) -> MountArgs {
+        let cli = Cli::parse_from(["slayerfs", "mount", "/mnt/slayer"].iter().chain(extra));
+        match cli.cmd {
+            Command::Mount(args) => *args,
+            other => panic!("expected mount command, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn mount_config_rejects_invalid_layout() {
+        assert!(MountConfig::from_sources(mount_args(&[])).is_ok());
+
+        for extra in [
+            ["--block-size", "0"],
+            ["--chunk-size", "0"],
+            ["--chunk-size", "10000000"],
+        ] {
+            let err = MountConfig::from_sources(mount_args(&extra)).unwrap_err();
+            assert!(
+                err.to_string().starts_with("invalid chunk/block layout"),
+                "{extra:?}: {err}"
+            );
+        }
+    }
 }
@@ -13,12 +13,15 @@ use crate::chunk::layout::ChunkLayout;
 use crate::chunk::store::BlockStore;
 use crate::meta::MetaStore;
 use crate::meta::client::MetaClient;
-use crate::meta::config::MetaClientConfig;
+use crate::meta::config::{MetaClientConfig, WriteCacheConfig};
 use crate::meta::layer::MetaLayer;
 use crate::meta::permission::Permission;
 use crate::meta::store::{
-    DirEntry, FileAttr, FileType, MetaError, SetAttrFlags, SetAttrRequest, StatFsSnapshot,
+    DirEntry, DirEntryPlus, FileAttr, FileType, MetaError, SetAttrFlags, SetAttrRequest,
+    StatFsSnapshot,
 };
+use crate::vfs::cache::CacheStats;
+use crate::vfs::error::VfsError;
 use crate::vfs::fs::VFS;
 use libc::{getegid, geteuid, getgroups};
 use std::io;
@@ -44,7 +47,7 @@ impl From<StatFsSnapshot> for StatFs {
         Self {
             total_space: s.total_space,
             avail_space: s.available_space,
-            used_space: s.total_space.saturating_sub(s.available_space),
+            used_space: s.used_space,
             total_inodes: s.used_inodes.saturating_add(s.available_inodes),
             avail_inodes: s.available_inodes,
             used_inodes: s.used_inodes,
@@ -333,6 +336,8 @@ pub struct FileSystemConfig {
     pub enforce_permissions: bool,
     /// Caller identity used for permission checks.
     pub caller: CallerIdentity,
+    /// Write cache tuning applied on top of the layout passed at construction.
+    pub write: WriteCacheConfig,
 }
 
 impl Default for FileSystemConfig {
@@ -342,6 +347,7 @@ impl Default for FileSystemConfig {
             access_log_buffer_size: 1024,
             enforce_permissions: true,
             caller: CallerIdentity::current(),
+            write: WriteCacheConfig::default(),
         }
     }
 }
@@ -356,6 +362,12 @@ impl FileSystemConfig {
         self.enforce_permissions = enforce;
         self
     }
+
+    /// Use the `write` section of a loaded [`Config`](crate::meta::config::Config).
+    pub fn with_write_cache(mut self, write: WriteCacheConfig) -> Self {
+        self.write = write;
+        self
+    }
 }
 
 fn access_log_sender(config: &FileSystemConfig) -> Option<mpsc::Sender<AccessLogEntry>> {
@@ -469,7 +481,11 @@ where
         meta_config: MetaClientConfig,
         config: FileSystemConfig,
     ) -> io::Result<Self> {
-        let vfs = VFS::with_meta_client_config(layout, store, meta, meta_config)
+        let vfs_config = config
+            .write
+            .vfs_config(layout)
+            .map_err(|e| io::Error::from(VfsError::from(e)))?;
+        let vfs = VFS::with_meta_client_config(vfs_config, store, meta, meta_config)
             .await
             .map_err(|e| io::Error::other(format!("vfs init failed: {e}")))?;
 
@@ -491,8 +507,12 @@ where
         config: FileSystemConfig,
     ) -> io::Result<Self> {
         let access_log_tx = access_log_sender(&config);
+        let vfs_config = config
+            .write
+            .vfs_config(layout)
+            .map_err(|e| io::Error::from(VfsError::from(e)))?;
         let vfs = VFS::with_meta_layer_with_default_background(
-            layout,
+            vfs_config,
             Arc::clone(&store),
             Arc::clone(&meta_layer),
         )
@@ -560,6 +580,16 @@ where
         Ok(StatFs::from(snapshot))
     }
 
+    /// Chunk layout used by this filesystem.
+    pub fn layout(&self) -> ChunkLayout {
+        self.vfs.layout()
+    }
+
+    /// Get write cache usage and block store cache counters.
+    pub async fn cache_stats(&self) -> CacheStats {
+        self.vfs.cache_stats().await
+    }
+
     /// Resolve a path to FileStat, following symlinks.
     pub async fn stat(&self, path: &str) -> io::Result<FileStat> {
         let path = Self::normalize_path(path);
@@ -1383,6 +1413,147 @@ where
         result
     }
 
+    /// Make `dst` a copy-on-write clone of `src`, creating `dst` if needed.
+    /// The clone shares the source's blocks instead of copying them.
+    pub async fn clone_file(&self, src: &str, dst: &str) -> io::Result<u64> {
+        let src = Self::normalize_path(src);
+        let dst = Self::normalize_path(dst);
+        let log_ctx = self.log_context();
+        let result = async {
+            let src_fi = self.resolve(&src, true).await?;
+            if src_fi.is_dir() {
+                return Err(io::Error::new(io::ErrorKind::IsADirectory, src.clone()));
+            }
+            if src_fi.file_type() != FileType::File {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, src.clone()));
+            }
+            self.check_access(src_fi.attr(), AccessMask::READ, &src)?;
+
+            let dst_ino = if self.vfs.exists(&dst).await {
+                let dst_fi = self.resolve(&dst, true).await?;
+                if dst_fi.is_dir() {
+                    return Err(io::Error::new(io::ErrorKind::IsADirectory, dst.clone()));
+                }
+                if dst_fi.file_type() != FileType::File {
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, dst.clone()));
+                }
+                self.check_access(dst_fi.attr(), AccessMask::WRITE, &dst)?;
+                dst_fi.inode()
+            } else {
+                self.create_file_in_existing_dir(&dst, false).await?
+            };
+
+            self.vfs
+                .clone_inode(src_fi.inode(), dst_ino)
+                .await
+                .map_err(io::Error::from)
+        }
+        .await;
+        self.log_result(log_ctx.as_ref(), "clone_file", &src, &result);
+        result
+    }
+
+    /// Flush buffered writes of a file to the object store (path-based).
+    pub async fn flush(&self, path: &str) -> io::Result<()> {
+        let path = Self::normalize_path(path);
+        let log_ctx = self.log_context();
+        let result = async {
+            let fi = self.resolve(&path, true).await?;
+            if fi.is_dir() {
+                return Err(io::Error::new(io::ErrorKind::IsADirectory, path.clone()));
+            }
+            self.vfs
+                .flush_inode(fi.inode())
+                .await
+                .map_err(io::Error::from)
+        }
+        .await;
+        self.log_result(log_ctx.as_ref(), "flush", &path, &result);
+        result
+    }
+
+    /// Flush buffered writes of all files before the filesystem is dropped.
+    pub async fn shutdown(&self) -> io::Result<()> {
+        let log_ctx = self.log_context();
+        let result = self.vfs.flush_all().await.map_err(io::Error::from);
+        self.log_result(log_ctx.as_ref(), "shutdown", "/", &result);
+        result
+    }
+
+    /// Set an extended attribute (path-based). `flags` takes `XATTR_CREATE`/`XATTR_REPLACE`.
+    pub async fn set_xattr(
+        &self,
+        path: &str,
+        name: &str,
+        value: &[u8],
+        flags: u32,
+    ) -> io::Result<()> {
+        let path = Self::normalize_path(path);
+        let log_ctx = self.log_context();
+        let result = async {
+            let fi = self.resolve(&path, true).await?;
+            self.check_access(fi.attr(), AccessMask::WRITE, &path)?;
+            self.vfs
+                .set_xattr_ino(fi.inode(), name, value, flags)
+                .await
+                .map_err(io::Error::from)
+        }
+        .await;
+        self.log_result(log_ctx.as_ref(), "set_xattr", &path, &result);
+        result
+    }
+
+    /// Get an extended attribute value; `None` when the attribute is not set.
+    pub async fn get_xattr(&self, path: &str, name: &str) -> io::Result<Option<Vec<u8>>> {
+        let path = Self::normalize_path(path);
+        let log_ctx = self.log_context();
+        let result = async {
+            let fi = self.resolve(&path, true).await?;
+            self.check_access(fi.attr(), AccessMask::READ, &path)?;
+            self.vfs
+                .get_xattr_ino(fi.inode(), name)
+                .await
+                .map_err(io::Error::from)
+        }
+        .await;
+        self.log_result(log_ctx.as_ref(), "get_xattr", &path, &result);
+        result
+    }
+
+    /// List extended attribute names.
+    pub async fn list_xattr(&self, path: &str) -> io::Result<Vec<String>> {
+        let path = Self::normalize_path(path);
+        let log_ctx = self.log_context();
+        let result = async {
+            let fi = self.resolve(&path, true).await?;
+            self.check_access(fi.attr(), AccessMask::READ, &path)?;
+            self.vfs
+                .list_xattr_ino(fi.inode())
+                .await
+                .map_err(io::Error::from)
+        }
+        .await;
+        self.log_result(log_ctx.as_ref(), "list_xattr", &path, &result);
+        result
+    }
+
+    /// Remove an extended attribute; fails with `NotFound` when it is not set.
+    pub async fn remove_xattr(&self, path: &str, name: &str) -> io::Result<()> {
+        let path = Self::normalize_path(path);
+        let log_ctx = self.log_context();
+        let result = async {
+            let fi = self.resolve(&path, true).await?;
+            self.check_access(fi.attr(), AccessMask::WRITE, &path)?;
+            self.vfs
+                .remove_xattr_ino(fi.inode(), name)
+                .await
+                .map_err(io::Error::from)
+        }
+        .await;
+        self.log_result(log_ctx.as_ref(), "remove_xattr", &path, &result);
+        result
+    }
+
     /// Set file attributes.
     pub async fn set_attr(
         &self,
@@ -1430,16 +1601,7 @@ where
         let path = Self::normalize_path(path);
         let log_ctx = self.log_context();
         let result = async {
-            let (ino, attr) = self
-                .meta_layer()
-                .lookup_path_with_attr(&path)
-                .await
-                .map_err(|e| meta_error_to_io(&path, e))?
-                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.clone()))?;
-            if attr.kind != FileType::Dir {
-                return Err(io::Error::new(io::ErrorKind::NotADirectory, path.clone()));
-            }
-            self.check_access(&attr, AccessMask::READ | AccessMask::EXEC, &path)?;
+            let ino = self.listable_dir(&path).await?;
             self.meta_layer()
                 .readdir(ino)
                 .await
@@ -1450,6 +1612,81 @@ where
         result
     }
 
+    /// Read up to `limit` directory entries named after `after`, in name order.
+    pub async fn readdir_page(
+        &self,
+        path: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> io::Result<Vec<DirEntry>> {
+        let path = Self::normalize_path(path);
+        let log_ctx = self.log_context();
+        let result = async {
+            let ino = self.listable_dir(&path).await?;
+            self.meta_layer()
+                .readdir_page(ino, after, limit)
+                .await
+                .map_err(|e| meta_error_to_io(&path, e))
+        }
+        .await;
+        self.log_result(log_ctx.as_ref(), "readdir_page", &path, &result);
+        result
+    }
+
+    /// Read directory entries together with their attributes.
+    pub async fn readdir_plus(&self, path: &str) -> io::Result<Vec<DirEntryPlus>> {
+        let entries = self.readdir(path).await?;
+        self.with_attrs(path, entries).await
+    }
+
+    /// Read one page of directory entries together with their attributes, see `readdir_page`.
+    pub async fn readdir_page_plus(
+        &self,
+        path: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> io::Result<Vec<DirEntryPlus>> {
+        let entries = self.readdir_page(path, after, limit).await?;
+        self.with_attrs(path, entries).await
+    }
+
+    /// Attach attributes to listed entries with a single batched stat.
+    async fn with_attrs(
+        &self,
+        path: &str,
+        entries: Vec<DirEntry>,
+    ) -> io::Result<Vec<DirEntryPlus>> {
+        let inodes: Vec<i64> = entries.iter().map(|entry| entry.ino).collect();
+        let attrs = self
+            .meta_layer()
+            .batch_stat(&inodes)
+            .await
+            .map_err(|e| meta_error_to_io(path, e))?;
+        Ok(entries
+            .into_iter()
+            .zip(attrs)
+            .map(|(entry, attr)| DirEntryPlus { entry, attr })
+            .collect())
+    }
+
+    /// Resolve a directory the caller may list.
+    async fn listable_dir(&self, path: &str) -> io::Result<i64> {
+        let (ino, attr) = self
+            .meta_layer()
+            .lookup_path_with_attr(path)
+            .await
+            .map_err(|e| meta_error_to_io(path, e))?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.to_string()))?;
+        if attr.kind != FileType::Dir {
+            return Err(io::Error::new(
+                io::ErrorKind::NotADirectory,
+                path.to_string(),
+            ));
+        }
+        self.check_access(&attr, AccessMask::READ | AccessMask::EXEC, path)?;
+        Ok(ino)
+    }
+
     /// Read data at a specific offset (path-based).
     pub async fn read_at(&self, path: &str, offset: u64, len: usize) -> io::Result<Vec<u8>> {
         let path = Self::normalize_path(path);
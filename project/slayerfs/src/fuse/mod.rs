@@ -1392,6 +1392,9 @@ where
                 VfsError::AlreadyExists { .. } => Errno::from(libc::EEXIST),
                 VfsError::Unsupported => Errno::from(libc::ENOSYS),
                 VfsError::NotFound { .. } => Errno::from(libc::ENODATA),
+                VfsError::InvalidInput => Errno::from(libc::EINVAL),
+                VfsError::OutOfRange => Errno::from(libc::ERANGE),
+                VfsError::ArgumentListTooLong => Errno::from(libc::E2BIG),
                 _ => Errno::from(libc::EIO),
             })
     }
@@ -1710,6 +1713,7 @@ impl From<VfsError> for Errno {
             VfsError::TooManyLinks => libc::EMLINK,
             VfsError::InvalidFilename => libc::EINVAL,
             VfsError::ArgumentListTooLong => libc::E2BIG,
+            VfsError::OutOfRange => libc::ERANGE,
             VfsError::Interrupted => libc::EINTR,
             VfsError::Unsupported => libc::ENOSYS,
             VfsError::UnexpectedEof => libc::EIO,
@@ -19,8 +19,8 @@ pub(crate) mod utils;
 
 // Public SDK surface for external users.
 pub use crate::sdk_fs::{
-    AccessMode, Client, ClientBackend, DirEntry as SdkDirEntry, File, FileType as SdkFileType,
-    Metadata, OpenOptions, ReadDir,
+    AccessMode, CacheStats, Client, ClientBackend, DirEntry as SdkDirEntry, DirPage, File,
+    FileType as SdkFileType, FsStats, Metadata, OpenOptions, ReadDir,
 };
 pub use crate::vfs::sdk::{LocalClient, VfsClient};
 
@@ -35,6 +35,7 @@ pub use crate::chunk::{CompactResult, Compactor, CompactorError};
 pub use crate::meta::client::MetaClient;
 pub use crate::meta::config::{
     CacheConfig, ClientOptions, CompactConfig, Config, DatabaseConfig, DatabaseType,
+    WriteCacheConfig,
 };
 pub use crate::meta::factory::MetaStoreFactory;
 pub use crate::meta::file_lock::{FileLockInfo, FileLockQuery, FileLockRange, FileLockType};
@@ -44,6 +45,7 @@ pub use crate::meta::store::{
 };
 pub use crate::meta::stores::{DatabaseMetaStore, EtcdMetaStore, RedisMetaStore};
 pub use crate::meta::{
-    MetaHandle, MetaStore, create_meta_store_from_url, create_redis_meta_store_from_url,
+    MetaHandle, MetaStore, create_etcd_meta_store_from_urls, create_meta_store_from_url,
+    create_redis_meta_store_from_url,
 };
 pub use crate::vfs::fs::{RenameFlags, VFS};
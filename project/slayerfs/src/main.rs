@@ -36,7 +36,9 @@ use tracing_subscriber::fmt::format::FmtSpan;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 
+use crate::cadapter::azure::{AzureBackend, AzureConfig, AzureCredential};
 use crate::cadapter::client::ObjectClient;
+use crate::cadapter::gcs::{GcsBackend, GcsConfig, GcsCredential};
 use crate::cadapter::localfs::LocalFsBackend;
 use crate::cadapter::s3::{S3Backend, S3Config};
 use crate::chunk::layout::ChunkLayout;
@@ -50,7 +52,7 @@ use crate::meta::MetaStore;
 use crate::meta::client::MetaClient;
 use crate::meta::config::{
     CacheConfig, ClientOptions, CompactConfig, Config, DatabaseConfig, DatabaseType,
-    MetaClientConfig,
+    MetaClientConfig, WriteCacheConfig,
 };
 use crate::meta::factory::MetaStoreFactory;
 use crate::meta::layer::MetaLayer;
@@ -141,10 +143,6 @@ async fn mount_cmd(args: MountConfig) -> anyhow::Result<()> {
         anyhow::bail!("mount point must be a directory");
     }
 
-    if args.chunk_size < args.block_size as u64 {
-        anyhow::bail!("chunk_size must be >= block_size");
-    }
-
     let layout = ChunkLayout {
         chunk_size: args.chunk_size,
         block_size: args.block_size,
@@ -163,6 +161,16 @@ async fn mount_cmd(args: MountConfig) -> anyhow::Result<()> {
             let store = ObjectBlockStore::new(client);
             mount_with_store(layout, store, meta_store, &args.mount_point).await
         }
+        DataBackendKind::Azure => {
+            let client = create_azure_client(&args)?;
+            let store = ObjectBlockStore::new(client);
+            mount_with_store(layout, store, meta_store, &args.mount_point).await
+        }
+        DataBackendKind::Gcs => {
+            let client = create_gcs_client(&args)?;
+            let store = ObjectBlockStore::new(client);
+            mount_with_store(layout, store, meta_store, &args.mount_point).await
+        }
     }
 }
 
@@ -173,7 +181,9 @@ fn create_localfs_client(args: &MountConfig) -> anyhow::Result<ObjectClient<Loca
     if !args.data_dir.is_dir() {
         anyhow::bail!("data dir must be a directory");
     }
-    Ok(ObjectClient::new(LocalFsBackend::new(&args.data_dir)))
+    Ok(ObjectClient::new(
+        LocalFsBackend::new(&args.data_dir).with_fsync(args.localfs_fsync),
+    ))
 }
 
 async fn create_s3_client(args: &MountConfig) -> anyhow::Result<ObjectClient<S3Backend>> {
@@ -196,6 +206,8 @@ async fn create_s3_client(args: &MountConfig) -> anyhow::Result<ObjectClient<S3B
         max_concurrency: args.s3_max_concurrency,
         endpoint: args.s3_endpoint.clone(),
         force_path_style: args.s3_force_path_style,
+        access_key: args.s3_access_key.clone(),
+        secret_key: args.s3_secret_key.clone(),
         ..Default::default()
     };
 
@@ -203,6 +215,74 @@ async fn create_s3_client(args: &MountConfig) -> anyhow::Result<ObjectClient<S3B
     Ok(ObjectClient::new(backend))
 }
 
+fn create_azure_client(args: &MountConfig) -> anyhow::Result<ObjectClient<AzureBackend>> {
+    let account = args
+        .azure_account
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("azure account must be set when data backend is azure"))?;
+    let container = args
+        .azure_container
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("azure container must be set when data backend is azure"))?;
+
+    if args.azure_part_size == 0 {
+        anyhow::bail!("--azure-part-size must be greater than 0");
+    }
+    if args.azure_max_concurrency == 0 {
+        anyhow::bail!("--azure-max-concurrency must be greater than 0");
+    }
+
+    let credential = match (&args.azure_access_key, &args.azure_sas_token) {
+        (Some(_), Some(_)) => {
+            anyhow::bail!("set only one of --azure-access-key and --azure-sas-token")
+        }
+        (Some(key), None) => AzureCredential::SharedKey(key.clone()),
+        (None, Some(token)) => AzureCredential::SasToken(token.clone()),
+        (None, None) => AzureCredential::Anonymous,
+    };
+
+    let config = AzureConfig {
+        account,
+        container,
+        credential,
+        endpoint: args.azure_endpoint.clone(),
+        part_size: args.azure_part_size,
+        max_concurrency: args.azure_max_concurrency,
+        ..Default::default()
+    };
+
+    let backend = AzureBackend::with_config(config)?;
+    Ok(ObjectClient::new(backend))
+}
+
+fn create_gcs_client(args: &MountConfig) -> anyhow::Result<ObjectClient<GcsBackend>> {
+    let bucket = args
+        .gcs_bucket
+        .clone()
+        .ok_or_else(|| anyhow::anyhow!("gcs bucket must be set when data backend is gcs"))?;
+
+    let credential = match (&args.gcs_credentials, args.gcs_anonymous) {
+        (Some(_), true) => {
+            anyhow::bail!("--gcs-credentials cannot be combined with --gcs-anonymous")
+        }
+        (Some(path), false) => GcsCredential::CredentialsFile(path.clone()),
+        (None, true) => GcsCredential::Anonymous,
+        (None, false) => GcsCredential::ApplicationDefault,
+    };
+
+    let config = GcsConfig {
+        bucket,
+        project: args.gcs_project.clone(),
+        credential,
+        endpoint: args.gcs_endpoint.clone(),
+        part_size: args.gcs_part_size,
+        ..Default::default()
+    };
+
+    let backend = GcsBackend::with_config(config)?;
+    Ok(ObjectClient::new(backend))
+}
+
 async fn mount_with_store<S>(
     layout: ChunkLayout,
     store: S,
@@ -404,6 +484,7 @@ async fn create_meta_store(args: &MountConfig) -> anyhow::Result<Arc<dyn MetaSto
                 cache: CacheConfig::default(),
                 client,
                 compact,
+                write: WriteCacheConfig::default(),
             };
             let handle = MetaStoreFactory::<DatabaseMetaStore>::create_from_config(config).await?;
             Ok(handle.store() as Arc<dyn MetaStore>)
@@ -425,6 +506,7 @@ async fn create_meta_store(args: &MountConfig) -> anyhow::Result<Arc<dyn MetaSto
                 cache: CacheConfig::default(),
                 client,
                 compact,
+                write: WriteCacheConfig::default(),
             };
             let handle = MetaStoreFactory::<EtcdMetaStore>::create_from_config(config).await?;
             Ok(handle.store() as Arc<dyn MetaStore>)
@@ -442,6 +524,7 @@ async fn create_meta_store(args: &MountConfig) -> anyhow::Result<Arc<dyn MetaSto
                 cache: CacheConfig::default(),
                 client,
                 compact,
+                write: WriteCacheConfig::default(),
             };
             let handle = MetaStoreFactory::<RedisMetaStore>::create_from_config(config).await?;
             Ok(handle.store() as Arc<dyn MetaStore>)
@@ -10,6 +10,7 @@ use crate::vfs::fs::FileType;
 use dashmap::{DashMap, Entry};
 use moka::future::Cache;
 use moka::notification::RemovalCause;
+use moka::policy::EvictionPolicy;
 use tokio::sync::RwLock;
 use tracing::info;
 
@@ -97,20 +98,39 @@ impl InodeEntry {
 /// Architecture:
 /// - `entries`: DashMap for lock-free concurrent read/write access to cached data
 /// - `ttl_manager`: Moka cache for automatic expiration and capacity management
+///
+/// Once `capacity` is exceeded the least-recently-used inode is evicted, and
+/// every entry expires `ttl` after insertion so that changes made by other
+/// clients are picked up from the store.
 pub(crate) struct InodeCache {
     entries: Arc<DashMap<i64, Arc<InodeEntry>>>,
     ttl_manager: Cache<i64, Arc<InodeEntry>>,
+    /// When false, inserts are dropped and every lookup misses.
+    enabled: bool,
 }
 
 impl InodeCache {
     /// Creates a new InodeCache with specified capacity and TTL.
     pub(crate) fn new(capacity: u64, ttl: Duration) -> Self {
+        Self::build(capacity, ttl, true)
+    }
+
+    /// Creates a cache that never retains entries (`cache.enabled = false`).
+    pub(crate) fn disabled() -> Self {
+        Self::build(0, Duration::ZERO, false)
+    }
+
+    fn build(capacity: u64, ttl: Duration, enabled: bool) -> Self {
         let entries = Arc::new(DashMap::new());
         let entries_clone = entries.clone();
 
-        let ttl_manager = Cache::builder()
+        let mut builder = Cache::builder()
             .max_capacity(capacity)
-            .time_to_idle(ttl)
+            .eviction_policy(EvictionPolicy::lru());
+        if !ttl.is_zero() {
+            builder = builder.time_to_live(ttl);
+        }
+        let ttl_manager = builder
             .eviction_listener(
                 move |key: Arc<i64>, _value: Arc<InodeEntry>, cause: RemovalCause| {
                     info!("InodeCache: Evicting inode {} (cause: {:?})", key, cause);
@@ -122,10 +142,14 @@ impl InodeCache {
         Self {
             entries,
             ttl_manager,
+            enabled,
         }
     }
 
     pub(crate) async fn insert_node(&self, ino: i64, attr: FileAttr, parent: Option<i64>) {
+        if !self.enabled {
+            return;
+        }
         match self.ttl_manager.get(&ino).await {
             Some(node) => {
                 *node.attr.write().await = attr;
@@ -383,3 +407,101 @@ impl InodeCache {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::config::{
+        CacheConfig, ClientOptions, CompactConfig, Config, DatabaseConfig, DatabaseType,
+        WriteCacheConfig,
+    };
+    use crate::meta::stores::database::DatabaseMetaStore;
+
+    fn file_attr(ino: i64, size: u64) -> FileAttr {
+        FileAttr {
+            ino,
+            size,
+            kind: FileType::File,
+            mode: 0o644,
+            uid: 0,
+            gid: 0,
+            atime: 0,
+            mtime: 0,
+            ctime: 0,
+            nlink: 1,
+        }
+    }
+
+    async fn sqlite_store() -> DatabaseMetaStore {
+        let config = Config {
+            database: DatabaseConfig {
+                db_config: DatabaseType::Sqlite {
+                    url: "sqlite::memory:".to_string(),
+                },
+            },
+            cache: CacheConfig::default(),
+            client: ClientOptions::default(),
+            compact: CompactConfig::default(),
+            write: WriteCacheConfig::default(),
+        };
+        DatabaseMetaStore::from_config(config).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_insert_beyond_capacity_evicts_least_recently_used() {
+        let cache = InodeCache::new(3, Duration::from_secs(60));
+        for ino in 1..=3 {
+            cache.insert_node(ino, file_attr(ino, 0), None).await;
+            cache.ttl_manager.run_pending_tasks().await;
+        }
+
+        // Touch inode 1 so inode 2 becomes the least recently used entry.
+        assert!(cache.get_node(1).await.is_some());
+        cache.ttl_manager.run_pending_tasks().await;
+
+        cache.insert_node(4, file_attr(4, 0), None).await;
+        cache.ttl_manager.run_pending_tasks().await;
+
+        assert!(cache.get_node(2).await.is_none());
+        for ino in [1, 3, 4] {
+            assert!(cache.get_node(ino).await.is_some(), "inode {ino} evicted");
+        }
+        assert_eq!(cache.ttl_manager.entry_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_expired_entry_is_refetched_from_store() {
+        let store = sqlite_store().await;
+        let root = store.root_ino();
+        let fresh = store.stat(root).await.unwrap().unwrap();
+
+        let cache = InodeCache::new(16, Duration::from_millis(50));
+        let stale = FileAttr {
+            size: fresh.size + 4096,
+            ..fresh.clone()
+        };
+        cache.insert_node(root, stale.clone(), None).await;
+        assert_eq!(cache.get_attr(root).await.unwrap().size, stale.size);
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert!(cache.get_attr(root).await.is_none());
+
+        assert!(
+            cache
+                .ensure_node_in_cache(root, &store, None)
+                .await
+                .unwrap()
+        );
+        assert_eq!(cache.get_attr(root).await.unwrap().size, fresh.size);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_cache_never_retains_entries() {
+        let cache = InodeCache::disabled();
+        cache.insert_node(1, file_attr(1, 0), None).await;
+        cache.ttl_manager.run_pending_tasks().await;
+
+        assert!(cache.get_node(1).await.is_none());
+        assert!(cache.entries.is_empty());
+    }
+}
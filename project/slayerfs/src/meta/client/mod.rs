@@ -23,6 +23,7 @@ use dashmap::DashMap;
 use futures::stream;
 use if_addrs::get_if_addrs;
 use moka::future::Cache;
+use moka::policy::EvictionPolicy;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
@@ -64,6 +65,12 @@ pub struct MetaClientOptions {
     pub max_symlinks: usize,
     /// Batch attribute prefetch configuration
     pub batch_prefetch: BatchPrefetchConfig,
+    /// When false, inode and path caches are bypassed and every lookup goes
+    /// to the metadata store (`cache.enabled = false`).
+    pub cache_enabled: bool,
+    /// Volume capacity in bytes reported by `stat_fs`. Object stores are
+    /// effectively unbounded, so `None` reports `DEFAULT_VOLUME_CAPACITY`.
+    pub capacity: Option<u64>,
 }
 
 /// Configuration for batch attribute prefetching during opendir
@@ -137,10 +144,16 @@ impl Default for MetaClientOptions {
             case_insensitive: false,
             max_symlinks: 40,
             batch_prefetch: BatchPrefetchConfig::default(),
+            cache_enabled: true,
+            capacity: None,
         }
     }
 }
 const DEFAULT_SESSION_HEARTBEAT: Duration = Duration::from_secs(30);
+/// Capacity reported by `stat_fs` when no ceiling is configured (1 PiB).
+const DEFAULT_VOLUME_CAPACITY: u64 = 1 << 50;
+/// Inode limit reported by `stat_fs` when the store does not track one.
+const DEFAULT_INODE_LIMIT: u64 = 1 << 32;
 
 /// Metadata client with intelligent caching
 ///
@@ -248,17 +261,28 @@ impl<T: MetaStore + ?Sized + 'static> MetaClient<T> {
 
         let root_ino = store.root_ino();
 
+        let (inode_cache, path_cache) = if options.cache_enabled {
+            (
+                InodeCache::new(capacity.inode as u64, ttl.inode_ttl),
+                Cache::builder()
+                    .max_capacity(capacity.path as u64)
+                    .eviction_policy(EvictionPolicy::lru())
+                    .time_to_live(ttl.path_ttl)
+                    .build(),
+            )
+        } else {
+            debug!("MetaClient: metadata caching disabled");
+            (InodeCache::disabled(), Cache::new(0))
+        };
+
         // Create MetaClient
         let client = Arc::new(Self {
             store: store.clone(),
             options,
             root: AtomicI64::new(root_ino),
             umounting: AtomicBool::new(false),
-            inode_cache: Arc::new(InodeCache::new(capacity.inode as u64, ttl.inode_ttl)),
-            path_cache: Cache::builder()
-                .max_capacity(capacity.path as u64)
-                .time_to_live(ttl.path_ttl)
-                .build(),
+            inode_cache: Arc::new(inode_cache),
+            path_cache,
             path_trie: Arc::new(PathTrie::new()),
             inode_to_paths: Arc::new(DashMap::new()),
             session_manager: Arc::new(SessionManager::new(store.clone())),
@@ -299,6 +323,27 @@ impl<T: MetaStore + ?Sized + 'static> MetaClient<T> {
         &self.options
     }
 
+    /// Fills in total/available figures for a store snapshot, which only
+    /// knows what is used. The configured capacity caps the total; usage
+    /// beyond it reports zero available space rather than growing the volume.
+    fn apply_capacity(&self, snapshot: StatFsSnapshot) -> StatFsSnapshot {
+        let total_space = self
+            .options
+            .capacity
+            .unwrap_or_else(|| DEFAULT_VOLUME_CAPACITY.max(snapshot.used_space));
+        let available_inodes = if snapshot.available_inodes == 0 {
+            DEFAULT_INODE_LIMIT.saturating_sub(snapshot.used_inodes)
+        } else {
+            snapshot.available_inodes
+        };
+        StatFsSnapshot {
+            total_space,
+            available_space: total_space.saturating_sub(snapshot.used_space),
+            available_inodes,
+            ..snapshot
+        }
+    }
+
     /// Returns a clone of the underlying raw `MetaStore` handle.
     #[allow(dead_code)]
     pub fn store(&self) -> Arc<T> {
@@ -855,12 +900,14 @@ impl<T: MetaStore + ?Sized + 'static> MetaClient<T> {
 
             // If no symlink was encountered, we're done
             if !symlink_encountered {
-                self.path_cache.insert(path.to_string(), current_ino).await;
-                self.path_trie.insert(path, current_ino).await;
-                self.inode_to_paths
-                    .entry(current_ino)
-                    .or_default()
-                    .push(path.to_string());
+                if self.options.cache_enabled {
+                    self.path_cache.insert(path.to_string(), current_ino).await;
+                    self.path_trie.insert(path, current_ino).await;
+                    self.inode_to_paths
+                        .entry(current_ino)
+                        .or_default()
+                        .push(path.to_string());
+                }
 
                 return Ok(current_ino);
             }
@@ -1165,7 +1212,8 @@ impl<T: MetaStore + ?Sized + 'static> MetaLayer for MetaClient<T> {
 
     #[tracing::instrument(level = "trace", skip(self))]
     async fn stat_fs(&self) -> Result<StatFsSnapshot, MetaError> {
-        self.store.stat_fs().await
+        let snapshot = self.store.stat_fs().await?;
+        Ok(self.apply_capacity(snapshot))
     }
 
     #[tracing::instrument(level = "trace", skip(self), fields(ino))]
@@ -1185,6 +1233,34 @@ impl<T: MetaStore + ?Sized + 'static> MetaLayer for MetaClient<T> {
         Ok(attr)
     }
 
+    #[tracing::instrument(level = "trace", skip(self, inodes), fields(count = inodes.len()))]
+    async fn batch_stat(&self, inodes: &[i64]) -> Result<Vec<Option<FileAttr>>, MetaError> {
+        let mut attrs = Vec::with_capacity(inodes.len());
+        let mut missing = Vec::new();
+        for &ino in inodes {
+            let inode = self.check_root(ino);
+            let attr = self.inode_cache.get_attr(inode).await;
+            if attr.is_none() {
+                missing.push((attrs.len(), inode));
+            }
+            attrs.push(attr);
+        }
+        if missing.is_empty() {
+            return Ok(attrs);
+        }
+
+        // One store round trip for every inode the cache could not answer.
+        let to_fetch: Vec<i64> = missing.iter().map(|&(_, inode)| inode).collect();
+        let fetched = self.store.batch_stat(&to_fetch).await?;
+        for ((slot, inode), attr) in missing.into_iter().zip(fetched) {
+            if let Some(ref a) = attr {
+                self.inode_cache.insert_node(inode, a.clone(), None).await;
+            }
+            attrs[slot] = attr;
+        }
+        Ok(attrs)
+    }
+
     #[tracing::instrument(level = "trace", skip(self), fields(parent, name))]
     async fn lookup(&self, parent: i64, name: &str) -> Result<Option<i64>, MetaError> {
         self.cached_lookup(parent, name).await
@@ -1285,6 +1361,19 @@ impl<T: MetaStore + ?Sized + 'static> MetaLayer for MetaClient<T> {
         Ok(entries)
     }
 
+    #[tracing::instrument(level = "trace", skip(self), fields(ino, after, limit))]
+    async fn readdir_page(
+        &self,
+        ino: i64,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DirEntry>, MetaError> {
+        let inode = self.check_root(ino);
+        // Paging exists for directories too large to list at once, so pages are neither served
+        // from nor loaded into the children cache.
+        self.store.readdir_page(inode, after, limit).await
+    }
+
     async fn opendir(&self, ino: i64) -> Result<DirHandle, MetaError> {
         let inode = self.check_root(ino);
         let attr = self
@@ -1816,6 +1905,16 @@ impl<T: MetaStore + ?Sized + 'static> MetaLayer for MetaClient<T> {
         Ok(())
     }
 
+    #[tracing::instrument(level = "trace", skip(self), fields(src, dst))]
+    async fn clone_file(&self, src: i64, dst: i64) -> Result<u64, MetaError> {
+        self.ensure_writable()?;
+        let src = self.check_root(src);
+        let dst = self.check_root(dst);
+        let size = self.store.clone_file(src, dst).await?;
+        self.inode_cache.invalidate_inode(dst).await;
+        Ok(size)
+    }
+
     #[tracing::instrument(level = "trace", skip(self), fields(ino))]
     async fn get_names(&self, ino: i64) -> Result<Vec<(Option<i64>, String)>, MetaError> {
         let inode = self.check_root(ino);
@@ -2084,6 +2183,7 @@ mod tests {
     use super::*;
     use crate::meta::config::{
         CacheConfig, ClientOptions, CompactConfig, Config, DatabaseConfig, DatabaseType,
+        WriteCacheConfig,
     };
     use crate::meta::stores::database::DatabaseMetaStore;
     use crate::vfs::chunk_id_for;
@@ -2105,6 +2205,7 @@ mod tests {
             cache: CacheConfig::default(),
             client: ClientOptions::default(),
             compact: CompactConfig::default(),
+            write: WriteCacheConfig::default(),
         };
 
         let store = Arc::new(DatabaseMetaStore::from_config(config).await.unwrap());
@@ -2135,6 +2236,7 @@ mod tests {
             cache: CacheConfig::default(),
             client: ClientOptions::default(),
             compact: CompactConfig::default(),
+            write: WriteCacheConfig::default(),
         };
 
         let store = Arc::new(DatabaseMetaStore::from_config(config).await.unwrap());
@@ -2251,6 +2353,44 @@ mod tests {
         assert_eq!(test_slices, from_cached);
     }
 
+    #[tokio::test]
+    async fn test_stat_fs_applies_capacity_ceiling() {
+        let options = MetaClientOptions {
+            capacity: Some(1000),
+            no_background_jobs: true,
+            ..MetaClientOptions::default()
+        };
+        let client = create_test_client_with_options(options).await;
+
+        let ino = client.create_file(1, "data".to_string()).await.unwrap();
+        let chunk_id = chunk_id_for(ino, 0).unwrap();
+        let slice = crate::chunk::SliceDesc {
+            slice_id: 1,
+            chunk_id,
+            offset: 0,
+            length: 600,
+        };
+        client.write(ino, chunk_id, slice, 600).await.unwrap();
+
+        let snap = client.stat_fs().await.unwrap();
+        assert_eq!(snap.total_space, 1000);
+        assert_eq!(snap.used_space, 600);
+        assert_eq!(snap.available_space, 400);
+        assert!(snap.available_inodes > 0);
+
+        let slice = crate::chunk::SliceDesc {
+            slice_id: 2,
+            offset: 600,
+            ..slice
+        };
+        client.write(ino, chunk_id, slice, 1200).await.unwrap();
+
+        let snap = client.stat_fs().await.unwrap();
+        assert_eq!(snap.total_space, 1000);
+        assert_eq!(snap.used_space, 1200);
+        assert_eq!(snap.available_space, 0);
+    }
+
     #[tokio::test]
     async fn test_control_plane_registers_and_serves_gc_jobs() {
         let runtime_dir = tempfile::tempdir().unwrap();
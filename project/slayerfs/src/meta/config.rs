@@ -2,7 +2,10 @@
 //!
 //! Database connection configuration supporting SQLite, PostgreSQL and Etcd
 
+use crate::chunk::ChunkLayout;
 use crate::meta::client::MetaClientOptions;
+use crate::vfs::config::VFSConfig;
+use crate::vfs::error::LayoutError;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::time::Duration;
@@ -23,6 +26,10 @@ pub struct Config {
 
     #[serde(default)]
     pub compact: CompactConfig,
+
+    /// Write cache tuning (optional, keeps the built-in layout if not specified)
+    #[serde(default)]
+    pub write: WriteCacheConfig,
 }
 
 /// Database configuration
@@ -61,6 +68,7 @@ impl Config {
 
         let config: Config =
             serde_yaml::from_str(&content).map_err(|e| ConfigError::ParseError(e.to_string()))?;
+        config.write.validate().map_err(ConfigError::InvalidWrite)?;
 
         Ok(config)
     }
@@ -121,6 +129,57 @@ pub enum ConfigError {
 
     #[error("Config file not found in default locations")]
     ConfigNotFound,
+
+    #[error("Invalid write cache config: {0}")]
+    InvalidWrite(LayoutError),
+}
+
+/// Write cache configuration
+///
+/// Unset sizes keep what the client is constructed with, so configs without a
+/// `write` section behave as before. Pages must tile blocks and blocks must
+/// tile chunks.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriteCacheConfig {
+    /// Write cache page size in bytes (default: 64KiB, or the block size if
+    /// that is not a multiple of 64KiB)
+    #[serde(default)]
+    pub page_size: Option<u32>,
+    /// Block size override in bytes
+    #[serde(default)]
+    pub block_size: Option<u32>,
+    /// Chunk size override in bytes
+    #[serde(default)]
+    pub chunk_size: Option<u64>,
+    /// Total bytes buffered by the write cache before writes are throttled
+    /// (default: 300MiB, 0 disables throttling)
+    #[serde(default)]
+    pub memory_budget: Option<u64>,
+}
+
+impl WriteCacheConfig {
+    /// Apply the overrides to `layout` and check the size invariants.
+    pub(crate) fn vfs_config(&self, layout: ChunkLayout) -> Result<VFSConfig, LayoutError> {
+        let layout = ChunkLayout {
+            chunk_size: self.chunk_size.unwrap_or(layout.chunk_size),
+            block_size: self.block_size.unwrap_or(layout.block_size),
+        };
+        let config = VFSConfig::new(layout);
+        let mut write = (*config.write).clone();
+        if let Some(page_size) = self.page_size {
+            write.page_size = page_size;
+        }
+        if let Some(memory_budget) = self.memory_budget {
+            write.buffer_size = memory_budget;
+        }
+        write.validate()?;
+        Ok(config.write_config(write))
+    }
+
+    /// Check the overrides against the default layout.
+    pub fn validate(&self) -> Result<(), LayoutError> {
+        self.vfs_config(ChunkLayout::default()).map(|_| ())
+    }
 }
 
 /// Cache configuration
@@ -175,6 +234,9 @@ pub struct ClientOptions {
     /// Maximum symlink follow depth.
     #[serde(default = "default_max_symlinks")]
     pub max_symlinks: usize,
+    /// Volume capacity in bytes reported by statfs (unbounded when unset).
+    #[serde(default)]
+    pub capacity: Option<u64>,
 }
 
 fn default_max_symlinks() -> usize {
@@ -189,6 +251,7 @@ impl Default for ClientOptions {
             case_insensitive: false,
             session_heartbeat: None,
             max_symlinks: default_max_symlinks(),
+            capacity: None,
         }
     }
 }
@@ -311,7 +374,9 @@ impl Default for CacheConfig {
 
 impl CacheConfig {
     /// Validate cache configuration
-    #[allow(dead_code)]
+    ///
+    /// Capacities are only checked when caching is enabled; with
+    /// `enabled = false` the caches are bypassed entirely.
     pub fn validate(&self) -> Result<(), String> {
         if self.enabled {
             if self.capacity.inode == 0 {
@@ -535,3 +600,66 @@ impl Default for CompactConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::config::DEFAULT_WRITE_BUFFER_SIZE;
+
+    fn load(yaml: &str) -> Result<Config, ConfigError> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("slayerfs.yml");
+        std::fs::write(&path, yaml).unwrap();
+        Config::from_file(&path)
+    }
+
+    #[test]
+    fn write_section_defaults_to_builtin_layout() {
+        let config = load("database:\n  type: sqlite\n").unwrap();
+        assert_eq!(config.write, WriteCacheConfig::default());
+
+        let layout = ChunkLayout::default();
+        let vfs = config.write.vfs_config(layout).unwrap();
+        let builtin = VFSConfig::new(layout);
+        assert_eq!(vfs.write.layout, layout);
+        assert_eq!(vfs.write.page_size, builtin.write.page_size);
+        assert_eq!(vfs.write.buffer_size, DEFAULT_WRITE_BUFFER_SIZE);
+    }
+
+    #[test]
+    fn write_section_overrides_layout_and_budget() {
+        let config = load(
+            "database:\n  type: sqlite\nwrite:\n  page_size: 16384\n  block_size: 1048576\n  \
+             chunk_size: 8388608\n  memory_budget: 67108864\n",
+        )
+        .unwrap();
+
+        let vfs = config.write.vfs_config(ChunkLayout::default()).unwrap();
+        assert_eq!(vfs.write.layout.block_size, 1 << 20);
+        assert_eq!(vfs.write.layout.chunk_size, 8 << 20);
+        assert_eq!(vfs.read.layout, vfs.write.layout);
+        assert_eq!(vfs.write.page_size, 16 << 10);
+        assert_eq!(vfs.write.buffer_size, 64 << 20);
+    }
+
+    #[test]
+    fn write_section_rejects_broken_invariants() {
+        let err = load("database:\n  type: sqlite\nwrite:\n  page_size: 3000\n").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::InvalidWrite(LayoutError::BlockNotPageMultiple { .. })
+        ));
+
+        let err = load("database:\n  type: sqlite\nwrite:\n  block_size: 3145728\n").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::InvalidWrite(LayoutError::ChunkNotBlockMultiple { .. })
+        ));
+
+        let err = load("database:\n  type: sqlite\nwrite:\n  page_size: 0\n").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::InvalidWrite(LayoutError::ZeroSize("page size"))
+        ));
+    }
+}
@@ -10,6 +10,7 @@ pub(crate) mod locks_meta;
 pub(crate) mod plock_meta;
 pub(crate) mod session_meta;
 pub(crate) mod slice_meta;
+pub(crate) mod slice_ref;
 pub(crate) mod uncommitted_slice;
 pub(crate) mod xattr_meta;
 
@@ -23,5 +24,6 @@ pub(crate) use locks_meta::Entity as LocksMeta;
 pub(crate) use plock_meta::Entity as PlockMeta;
 #[allow(unused_imports)]
 pub(crate) use slice_meta::{Entity as SliceMeta, Model as SliceMetaModel};
+pub(crate) use slice_ref::Entity as SliceRef;
 pub(crate) use uncommitted_slice::Entity as UncommittedSlice;
 pub(crate) use xattr_meta::Entity as XattrMeta;
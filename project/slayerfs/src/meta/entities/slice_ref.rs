@@ -0,0 +1,23 @@
+//! Reference counts for slices shared between files
+//!
+//! A slice's block objects are keyed by its slice id only, so a copy-on-write
+//! clone can point another chunk at the same slice. Slices without a row here
+//! have exactly one reference; the row is created when a slice gets shared and
+//! removed again once it is back to a single reference.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "slice_ref")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub slice_id: i64,
+    /// Number of `slice_meta` rows (across all chunks) referencing the slice.
+    pub refs: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
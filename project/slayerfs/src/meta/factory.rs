@@ -8,6 +8,7 @@ use std::sync::Arc;
 use crate::meta::client::{MetaClient, MetaClientOptions};
 use crate::meta::config::{
     CacheConfig, CacheTtl, ClientOptions, CompactConfig, Config, DatabaseConfig, DatabaseType,
+    WriteCacheConfig,
 };
 use crate::meta::layer::MetaLayer;
 use crate::meta::store::{MetaError, MetaStore};
@@ -55,6 +56,8 @@ where
 
     /// Create MetaStore from config (with MetaClient caching)
     pub async fn create_from_config(config: Config) -> Result<MetaHandle<M>, MetaError> {
+        config.cache.validate().map_err(MetaError::Config)?;
+
         let store = Arc::new(M::from_config(config.clone()).await?);
 
         let ttl = if config.cache.ttl.is_zero() {
@@ -74,6 +77,8 @@ where
                 .session_heartbeat
                 .unwrap_or(defaults.session_heartbeat),
             max_symlinks: config.client.max_symlinks,
+            cache_enabled: config.cache.enabled,
+            capacity: config.client.capacity,
             ..defaults
         };
 
@@ -99,9 +104,18 @@ pub async fn create_meta_store(
 }
 
 /// Convenience function to create MetaStore from a URL string.
+///
+/// Only SQL URLs are accepted here; etcd clusters are addressed by a list of endpoints, see
+/// [`create_etcd_meta_store_from_urls`].
 pub async fn create_meta_store_from_url(
     url: &str,
 ) -> Result<MetaHandle<DatabaseMetaStore>, MetaError> {
+    if url.to_ascii_lowercase().starts_with("etcd://") {
+        return Err(MetaError::Config(format!(
+            "{url} is an etcd endpoint; use create_etcd_meta_store_from_urls instead"
+        )));
+    }
+
     let config = Config {
         database: DatabaseConfig {
             db_config: DatabaseType::Sqlite {
@@ -111,6 +125,7 @@ pub async fn create_meta_store_from_url(
         cache: CacheConfig::default(),
         client: ClientOptions::default(),
         compact: CompactConfig::default(),
+        write: WriteCacheConfig::default(),
     };
     MetaStoreFactory::<DatabaseMetaStore>::create_from_config(config).await
 }
@@ -129,6 +144,7 @@ pub async fn create_redis_meta_store_from_url(
         cache: CacheConfig::default(),
         client: ClientOptions::default(),
         compact: CompactConfig::default(),
+        write: WriteCacheConfig::default(),
     };
     MetaStoreFactory::<RedisMetaStore>::create_from_config(config).await
 }
@@ -145,6 +161,7 @@ pub async fn create_etcd_meta_store_from_urls(
         cache: CacheConfig::default(),
         client: ClientOptions::default(),
         compact: CompactConfig::default(),
+        write: WriteCacheConfig::default(),
     };
 
     MetaStoreFactory::<EtcdMetaStore>::create_from_config(config).await
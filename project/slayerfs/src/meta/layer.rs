@@ -41,6 +41,9 @@ pub trait MetaLayer: Send + Sync {
     /// Do `stat` but bypass the inode cache.
     async fn stat_fresh(&self, ino: i64) -> Result<Option<FileAttr>, MetaError>;
 
+    /// `stat` several inodes at once, in input order, with `None` for missing ones.
+    async fn batch_stat(&self, inodes: &[i64]) -> Result<Vec<Option<FileAttr>>, MetaError>;
+
     async fn lookup(&self, parent: i64, name: &str) -> Result<Option<i64>, MetaError>;
 
     async fn lookup_path(&self, path: &str) -> Result<Option<(i64, FileType)>, MetaError>;
@@ -59,6 +62,14 @@ pub trait MetaLayer: Send + Sync {
 
     async fn readdir(&self, ino: i64) -> Result<Vec<DirEntry>, MetaError>;
 
+    /// List up to `limit` entries of `ino` named after `after`, in name order.
+    async fn readdir_page(
+        &self,
+        ino: i64,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DirEntry>, MetaError>;
+
     async fn opendir(&self, ino: i64) -> Result<DirHandle, MetaError>;
 
     async fn mkdir(&self, parent: i64, name: String) -> Result<i64, MetaError>;
@@ -181,6 +192,9 @@ pub trait MetaLayer: Send + Sync {
 
     async fn truncate(&self, ino: i64, size: u64, chunk_size: u64) -> Result<(), MetaError>;
 
+    /// Make `dst` a copy-on-write clone of `src`, sharing its slices.
+    async fn clone_file(&self, src: i64, dst: i64) -> Result<u64, MetaError>;
+
     async fn get_names(&self, ino: i64) -> Result<Vec<(Option<i64>, String)>, MetaError>;
 
     async fn get_dentries(&self, ino: i64) -> Result<Vec<(i64, String)>, MetaError>;
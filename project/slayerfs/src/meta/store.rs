@@ -138,6 +138,9 @@ pub struct WriteOutcome {
 pub struct StatFsSnapshot {
     pub total_space: u64,
     pub available_space: u64,
+    /// Bytes held by committed slices, i.e. data actually written to the
+    /// object store. Holes in sparse files are not counted.
+    pub used_space: u64,
     pub used_inodes: u64,
     pub available_inodes: u64,
 }
@@ -150,9 +153,23 @@ pub struct DirEntry {
     pub kind: FileType,
 }
 
+/// Cut one page out of a full directory listing: up to `limit` entries named after `after`,
+/// in name order.
+pub fn page_dir_entries(
+    mut entries: Vec<DirEntry>,
+    after: Option<&str>,
+    limit: usize,
+) -> Vec<DirEntry> {
+    if let Some(after) = after {
+        entries.retain(|entry| entry.name.as_str() > after);
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    entries.truncate(limit);
+    entries
+}
+
 /// Extended directory entry used by readdir+ style operations
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 pub struct DirEntryPlus {
     pub entry: DirEntry,
     pub attr: Option<FileAttr>,
@@ -401,6 +418,22 @@ pub trait MetaStore: Send + Sync {
 
     async fn readdir(&self, ino: i64) -> Result<Vec<DirEntry>, MetaError>;
 
+    /// List up to `limit` entries of `ino` named after `after`, in name order.
+    ///
+    /// The cursor is the last name of the previous page, so entries created or removed between
+    /// pages never shift the listing: every entry present throughout is returned exactly once.
+    /// The default implementation pages a full `readdir`; stores that keep children sorted
+    /// should answer with a range query instead.
+    async fn readdir_page(
+        &self,
+        ino: i64,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DirEntry>, MetaError> {
+        let entries = self.readdir(ino).await?;
+        Ok(page_dir_entries(entries, after, limit))
+    }
+
     /// Batch query attributes for multiple inodes (for optimization)
     /// Returns attributes in the same order as input inodes
     /// Returns None for inodes that don't exist
@@ -934,6 +967,18 @@ pub trait MetaStore: Send + Sync {
         Err(MetaError::NotImplemented)
     }
 
+    /// Make `dst_ino` a copy-on-write clone of `src_ino`: the destination
+    /// chunks reference the source's committed slices (and therefore the same
+    /// block objects) and its size is set to the source size. Slices previously
+    /// referenced by `dst_ino` are released. Shared slices are reference counted
+    /// so their blocks are only deleted once no inode references them.
+    ///
+    /// Returns the size of the clone.
+    async fn clone_file(&self, src_ino: i64, dst_ino: i64) -> Result<u64, MetaError> {
+        let _ = (src_ino, dst_ino);
+        Err(MetaError::NotImplemented)
+    }
+
     async fn record_uncommitted_slice(
         &self,
         slice_id: u64,
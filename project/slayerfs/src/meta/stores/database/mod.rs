@@ -322,6 +322,10 @@ impl DatabaseMetaStore {
                 .create_table_from_entity(UncommittedSlice)
                 .if_not_exists()
                 .to_owned(),
+            schema
+                .create_table_from_entity(SliceRef)
+                .if_not_exists()
+                .to_owned(),
         ];
 
         for (i, stmt) in stmts.iter().enumerate() {
@@ -625,6 +629,105 @@ impl DatabaseMetaStore {
         Utc::now().timestamp_nanos_opt().unwrap_or(0)
     }
 
+    /// Half-open range `[start, end)` of the chunk ids belonging to `ino`.
+    fn chunk_id_range(ino: i64) -> Result<(i64, i64), MetaError> {
+        let to_i64 = |id: u64| {
+            i64::try_from(id).map_err(|_| MetaError::Internal("chunk_id overflow".to_string()))
+        };
+        let start = to_i64(chunk_id_for(ino, 0)?)?;
+        let end = to_i64(chunk_id_for(ino + 1, 0)?)?;
+        Ok((start, end))
+    }
+
+    /// Move every slice of `ino` into the delayed-slice queue so GC can
+    /// release the slice references (and blocks) once they age out.
+    async fn delay_inode_slices<C>(conn: &C, ino: i64, reason: &str) -> Result<(), MetaError>
+    where
+        C: ConnectionTrait,
+    {
+        let (start, end) = Self::chunk_id_range(ino)?;
+        let rows = SliceMeta::find()
+            .filter(slice_meta::Column::ChunkId.gte(start))
+            .filter(slice_meta::Column::ChunkId.lt(end))
+            .all(conn)
+            .await
+            .map_err(MetaError::Database)?;
+
+        let now = Utc::now().timestamp();
+        for row in &rows {
+            delayed_slice::ActiveModel {
+                slice_id: Set(row.slice_id),
+                chunk_id: Set(row.chunk_id),
+                offset: Set(row.offset),
+                size: Set(row.length),
+                created_at: Set(now),
+                reason: Set(reason.to_string()),
+                status: Set("pending".to_string()),
+                ..Default::default()
+            }
+            .insert(conn)
+            .await
+            .map_err(MetaError::Database)?;
+        }
+        Ok(())
+    }
+
+    /// Record `extra` additional chunk references to `slice_id`.
+    async fn add_slice_refs<C>(conn: &C, slice_id: i64, extra: i64) -> Result<(), MetaError>
+    where
+        C: ConnectionTrait,
+    {
+        match SliceRef::find_by_id(slice_id)
+            .one(conn)
+            .await
+            .map_err(MetaError::Database)?
+        {
+            Some(existing) => {
+                let refs = existing.refs;
+                let mut active: slice_ref::ActiveModel = existing.into();
+                active.refs = Set(refs + extra);
+                active.update(conn).await.map_err(MetaError::Database)?;
+            }
+            None => {
+                slice_ref::ActiveModel {
+                    slice_id: Set(slice_id),
+                    refs: Set(1 + extra),
+                }
+                .insert(conn)
+                .await
+                .map_err(MetaError::Database)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop one chunk reference to `slice_id`. Returns `true` when other
+    /// references remain, in which case the slice's blocks must be kept.
+    async fn release_slice_ref<C>(conn: &C, slice_id: i64) -> Result<bool, MetaError>
+    where
+        C: ConnectionTrait,
+    {
+        let Some(existing) = SliceRef::find_by_id(slice_id)
+            .one(conn)
+            .await
+            .map_err(MetaError::Database)?
+        else {
+            return Ok(false);
+        };
+
+        let refs = existing.refs;
+        let active: slice_ref::ActiveModel = existing.into();
+        if refs > 2 {
+            let mut active = active;
+            active.refs = Set(refs - 1);
+            active.update(conn).await.map_err(MetaError::Database)?;
+        } else {
+            // A single remaining reference is the implicit default.
+            active.delete(conn).await.map_err(MetaError::Database)?;
+        }
+        Ok(refs > 1)
+    }
+
     async fn prune_slices_for_truncate<C>(
         &self,
         conn: &C,
@@ -659,8 +762,11 @@ impl DatabaseMetaStore {
                     match trim_action(offset, length, cutoff_offset) {
                         TrimAction::Keep => {}
                         TrimAction::Drop => {
+                            let slice_id = row.slice_id;
                             let active: slice_meta::ActiveModel = row.into();
                             active.delete(conn).await.map_err(MetaError::Database)?;
+                            // A clone may share the slice; it now holds one reference less.
+                            Self::release_slice_ref(conn, slice_id).await?;
                         }
                         TrimAction::Truncate(new_len) => {
                             let mut active: slice_meta::ActiveModel = row.into();
@@ -676,6 +782,15 @@ impl DatabaseMetaStore {
                     .map_err(|_| MetaError::Internal("chunk_id overflow".to_string()))?;
                 let end_chunk_id = i64::try_from(chunk_id_for(ino, end)?)
                     .map_err(|_| MetaError::Internal("chunk_id overflow".to_string()))?;
+                let dropped = SliceMeta::find()
+                    .filter(slice_meta::Column::ChunkId.gte(start_chunk_id))
+                    .filter(slice_meta::Column::ChunkId.lt(end_chunk_id))
+                    .all(conn)
+                    .await
+                    .map_err(MetaError::Database)?;
+                for row in &dropped {
+                    Self::release_slice_ref(conn, row.slice_id).await?;
+                }
                 SliceMeta::delete_many()
                     .filter(slice_meta::Column::ChunkId.gte(start_chunk_id))
                     .filter(slice_meta::Column::ChunkId.lt(end_chunk_id))
@@ -1082,14 +1197,39 @@ impl DatabaseMetaStore {
                 continue;
             }
 
-            // Delete from slice_meta table by slice_id column
+            // Delete the chunk's reference to the slice; clones of the file
+            // may still reference the same slice from other chunks.
             let result = SliceMeta::delete_many()
                 .filter(slice_meta::Column::SliceId.eq(delayed.slice_id))
+                .filter(slice_meta::Column::ChunkId.eq(delayed.chunk_id))
                 .exec(&txn)
                 .await;
 
             match result {
                 Ok(deleted) => {
+                    match Self::release_slice_ref(&txn, delayed.slice_id).await {
+                        Ok(true) => {
+                            // Still shared: drop the record but keep the blocks.
+                            if let Err(e) = DelayedSlice::delete_by_id(delayed.id).exec(&txn).await
+                            {
+                                warn!(
+                                    slice_id = delayed.slice_id,
+                                    error = ?e,
+                                    "Failed to remove delayed record of shared slice"
+                                );
+                            }
+                            continue;
+                        }
+                        Ok(false) => {}
+                        Err(e) => {
+                            warn!(
+                                slice_id = delayed.slice_id,
+                                error = ?e,
+                                "Failed to release slice reference, will retry later"
+                            );
+                            continue;
+                        }
+                    }
                     if deleted.rows_affected > 0 {
                         debug!(
                             slice_id = delayed.slice_id,
@@ -1319,6 +1459,46 @@ impl MetaStore for DatabaseMetaStore {
         Ok(entries)
     }
 
+    #[tracing::instrument(level = "trace", skip(self), fields(ino, after, limit))]
+    async fn readdir_page(
+        &self,
+        ino: i64,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DirEntry>, MetaError> {
+        let access_meta = self
+            .get_access_meta(ino)
+            .await?
+            .ok_or(MetaError::NotFound(ino))?;
+        if !access_meta.permission().is_directory() {
+            return Err(MetaError::NotDirectory(ino));
+        }
+
+        let mut query = ContentMeta::find().filter(content_meta::Column::ParentInode.eq(ino));
+        if let Some(after) = after {
+            query = query.filter(content_meta::Column::EntryName.gt(after));
+        }
+        let contents = query
+            .order_by_asc(content_meta::Column::EntryName)
+            .limit(limit as u64)
+            .all(&self.db)
+            .await
+            .map_err(MetaError::Database)?;
+
+        Ok(contents
+            .into_iter()
+            .map(|content| DirEntry {
+                kind: match content.entry_type {
+                    EntryType::File => FileType::File,
+                    EntryType::Directory => FileType::Dir,
+                    EntryType::Symlink => FileType::Symlink,
+                },
+                name: content.entry_name,
+                ino: content.inode,
+            })
+            .collect())
+    }
+
     #[tracing::instrument(level = "trace", skip(self), fields(parent, name))]
     async fn mkdir(&self, parent: i64, name: String) -> Result<i64, MetaError> {
         self.create_directory(parent, name).await
@@ -1477,6 +1657,12 @@ impl MetaStore for DatabaseMetaStore {
             file_meta.deleted = Set(true);
             file_meta.nlink = Set(0);
             file_meta.parent = Set(0);
+
+            XattrMeta::delete_many()
+                .filter(xattr_meta::Column::Inode.eq(file_id))
+                .exec(&txn)
+                .await
+                .map_err(MetaError::Database)?;
         }
 
         file_meta.modify_time = Set(now);
@@ -2553,7 +2739,18 @@ impl MetaStore for DatabaseMetaStore {
             .await
             .map_err(MetaError::Database)?;
 
-        let used_space: u64 = files.iter().map(|file| file.size.max(0) as u64).sum();
+        // Cloned files share slices, so count each slice's blocks only once.
+        let mut slice_lengths: HashMap<i64, u64> = HashMap::new();
+        for slice in SliceMeta::find()
+            .all(&self.db)
+            .await
+            .map_err(MetaError::Database)?
+        {
+            let length = slice.length.max(0) as u64;
+            let entry = slice_lengths.entry(slice.slice_id).or_default();
+            *entry = (*entry).max(length);
+        }
+        let used_space: u64 = slice_lengths.values().sum();
 
         let file_count = files.len() as u64;
         let dir_count = AccessMeta::find()
@@ -2564,6 +2761,7 @@ impl MetaStore for DatabaseMetaStore {
         Ok(StatFsSnapshot {
             total_space: used_space,
             available_space: 0,
+            used_space,
             used_inodes: file_count + dir_count,
             available_inodes: 0,
         })
@@ -2597,6 +2795,10 @@ impl MetaStore for DatabaseMetaStore {
             ));
         }
 
+        // Hand the file's slices to GC; blocks shared with clones survive
+        // until their last reference is released.
+        Self::delay_inode_slices(&txn, ino, "unlink").await?;
+
         // Delete the file metadata
         let file_meta_active: file_meta::ActiveModel = file_meta.into();
         file_meta_active
@@ -3223,6 +3425,69 @@ impl MetaStore for DatabaseMetaStore {
         Ok(())
     }
 
+    async fn clone_file(&self, src_ino: i64, dst_ino: i64) -> Result<u64, MetaError> {
+        let txn = self.db.begin().await.map_err(MetaError::Database)?;
+
+        let src = FileMeta::find_by_id(src_ino)
+            .one(&txn)
+            .await
+            .map_err(MetaError::Database)?
+            .filter(|f| !f.deleted && f.symlink_target.is_none())
+            .ok_or(MetaError::NotFound(src_ino))?;
+        let dst = FileMeta::find_by_id(dst_ino)
+            .one(&txn)
+            .await
+            .map_err(MetaError::Database)?
+            .filter(|f| !f.deleted && f.symlink_target.is_none())
+            .ok_or(MetaError::NotFound(dst_ino))?;
+
+        // Release whatever the destination referenced before.
+        Self::delay_inode_slices(&txn, dst_ino, "clone").await?;
+        let (dst_start, dst_end) = Self::chunk_id_range(dst_ino)?;
+        SliceMeta::delete_many()
+            .filter(slice_meta::Column::ChunkId.gte(dst_start))
+            .filter(slice_meta::Column::ChunkId.lt(dst_end))
+            .exec(&txn)
+            .await
+            .map_err(MetaError::Database)?;
+
+        let (src_start, src_end) = Self::chunk_id_range(src_ino)?;
+        let rows = SliceMeta::find()
+            .filter(slice_meta::Column::ChunkId.gte(src_start))
+            .filter(slice_meta::Column::ChunkId.lt(src_end))
+            .order_by_asc(slice_meta::Column::Id)
+            .all(&txn)
+            .await
+            .map_err(MetaError::Database)?;
+
+        let mut new_refs: HashMap<i64, i64> = HashMap::new();
+        for row in &rows {
+            slice_meta::ActiveModel {
+                chunk_id: Set(row.chunk_id - src_start + dst_start),
+                slice_id: Set(row.slice_id),
+                offset: Set(row.offset),
+                length: Set(row.length),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await
+            .map_err(MetaError::Database)?;
+            *new_refs.entry(row.slice_id).or_default() += 1;
+        }
+        for (slice_id, extra) in new_refs {
+            Self::add_slice_refs(&txn, slice_id, extra).await?;
+        }
+
+        let size = src.size;
+        let mut active: file_meta::ActiveModel = dst.into();
+        active.size = Set(size);
+        active.modify_time = Set(Self::now_nanos());
+        active.update(&txn).await.map_err(MetaError::Database)?;
+
+        txn.commit().await.map_err(MetaError::Database)?;
+        Ok(size as u64)
+    }
+
     // Track a newly written slice as uncommitted so GC can clean it up if the commit fails.
     async fn record_uncommitted_slice(
         &self,
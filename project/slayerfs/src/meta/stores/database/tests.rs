@@ -1,5 +1,6 @@
 use super::*;
 use crate::CompactConfig;
+use crate::WriteCacheConfig;
 use crate::meta::config::{CacheConfig, ClientOptions, DatabaseConfig};
 use crate::meta::file_lock::{FileLockQuery, FileLockRange, FileLockType};
 use tokio::time;
@@ -14,6 +15,7 @@ fn test_config() -> Config {
         cache: CacheConfig::default(),
         client: ClientOptions::default(),
         compact: CompactConfig::default(),
+        write: WriteCacheConfig::default(),
     }
 }
 
@@ -27,6 +29,7 @@ fn file_db_config(path: &std::path::Path) -> Config {
         cache: CacheConfig::default(),
         client: ClientOptions::default(),
         compact: CompactConfig::default(),
+        write: WriteCacheConfig::default(),
     }
 }
 
@@ -41,6 +44,7 @@ fn shared_db_config() -> Config {
         cache: CacheConfig::default(),
         client: ClientOptions::default(),
         compact: CompactConfig::default(),
+        write: WriteCacheConfig::default(),
     }
 }
 
@@ -384,6 +388,43 @@ async fn test_hardlink_last_unlink_cleanup() {
     );
 }
 
+#[tokio::test]
+async fn test_last_unlink_removes_xattrs() {
+    let store = new_test_store().await;
+    let parent = store.root_ino();
+
+    let file_ino = store
+        .create_file(parent, "xattr.txt".to_string())
+        .await
+        .unwrap();
+    store
+        .link(file_ino, parent, "xattr-link.txt")
+        .await
+        .unwrap();
+    store
+        .set_xattr(file_ino, "user.checksum", b"abc", 0)
+        .await
+        .unwrap();
+
+    async fn xattr_rows(store: &DatabaseMetaStore, ino: i64) -> Vec<xattr_meta::Model> {
+        XattrMeta::find()
+            .filter(xattr_meta::Column::Inode.eq(ino))
+            .all(&store.db)
+            .await
+            .unwrap()
+    }
+
+    // Another link still refers to the inode, so its xattrs stay.
+    store.unlink(parent, "xattr-link.txt").await.unwrap();
+    assert_eq!(xattr_rows(&store, file_ino).await.len(), 1);
+
+    store.unlink(parent, "xattr.txt").await.unwrap();
+    assert!(
+        xattr_rows(&store, file_ino).await.is_empty(),
+        "xattrs should be removed with the last link"
+    );
+}
+
 #[tokio::test]
 async fn test_hardlink_dentry_binding_cross_dir_rename_unlink() {
     let store = new_test_store().await;
@@ -1191,3 +1232,88 @@ async fn test_chown_directory() {
     assert_eq!(stat.uid, 500);
     assert_eq!(stat.gid, 500);
 }
+
+#[tokio::test]
+async fn test_cloned_slices_are_released_by_last_reference() {
+    let store = new_test_store().await;
+    let parent = store.root_ino();
+    let src = store.create_file(parent, "src".to_string()).await.unwrap();
+    let dst = store.create_file(parent, "dst".to_string()).await.unwrap();
+
+    let src_chunk = chunk_id_for(src, 0).unwrap();
+    let slice = SliceDesc {
+        slice_id: 7,
+        chunk_id: src_chunk,
+        offset: 0,
+        length: 100,
+    };
+    store.write(src, src_chunk, slice, 100).await.unwrap();
+
+    assert_eq!(store.clone_file(src, dst).await.unwrap(), 100);
+    assert_eq!(store.stat(dst).await.unwrap().unwrap().size, 100);
+    let dst_slices = store
+        .get_slices(chunk_id_for(dst, 0).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(dst_slices.len(), 1);
+    assert_eq!(dst_slices[0].slice_id, 7);
+    assert_eq!(store.stat_fs().await.unwrap().used_space, 100);
+
+    // Removing the source releases one reference; the blocks stay for the clone.
+    store.unlink(parent, "src").await.unwrap();
+    store.remove_file_metadata(src).await.unwrap();
+    let ready = store.process_delayed_slices(100, -1).await.unwrap();
+    assert!(ready.is_empty(), "shared slice must not be deleted");
+    assert_eq!(
+        store
+            .get_slices(chunk_id_for(dst, 0).unwrap())
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // The last reference hands the slice to block deletion.
+    store.unlink(parent, "dst").await.unwrap();
+    store.remove_file_metadata(dst).await.unwrap();
+    let ready = store.process_delayed_slices(100, -1).await.unwrap();
+    assert_eq!(ready.len(), 1);
+    assert_eq!(ready[0].0, 7);
+    store.confirm_delayed_deleted(&[ready[0].3]).await.unwrap();
+
+    // Truncating the shared slices away from the source releases its references too, so
+    // deleting the clone afterwards frees the blocks.
+    let src = store.create_file(parent, "src2".to_string()).await.unwrap();
+    let dst = store.create_file(parent, "dst2".to_string()).await.unwrap();
+    let chunk_size = 64 * 1024 * 1024;
+    for (index, slice_id) in [(0, 8), (1, 9)] {
+        let chunk_id = chunk_id_for(src, index).unwrap();
+        let slice = SliceDesc {
+            slice_id,
+            chunk_id,
+            offset: 0,
+            length: 100,
+        };
+        store
+            .write(src, chunk_id, slice, index * chunk_size + 100)
+            .await
+            .unwrap();
+    }
+    store.clone_file(src, dst).await.unwrap();
+
+    store.truncate(src, 0, chunk_size).await.unwrap();
+    assert!(
+        store
+            .get_slices(chunk_id_for(src, 0).unwrap())
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    store.unlink(parent, "dst2").await.unwrap();
+    store.remove_file_metadata(dst).await.unwrap();
+    let ready = store.process_delayed_slices(100, -1).await.unwrap();
+    let mut released: Vec<u64> = ready.iter().map(|r| r.0).collect();
+    released.sort_unstable();
+    assert_eq!(released, vec![8, 9]);
+}
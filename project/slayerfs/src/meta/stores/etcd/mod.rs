@@ -1667,6 +1667,60 @@ impl MetaStore for EtcdMetaStore {
         Ok(entries)
     }
 
+    #[tracing::instrument(level = "trace", skip(self), fields(ino, after, limit))]
+    async fn readdir_page(
+        &self,
+        ino: i64,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DirEntry>, MetaError> {
+        let access_meta = self
+            .get_access_meta(ino)
+            .await?
+            .ok_or(MetaError::NotFound(ino))?;
+        if !access_meta.permission().is_directory() {
+            return Err(MetaError::NotDirectory(ino));
+        }
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Forward keys sort by name under the parent's prefix, so a page is one range read.
+        let prefix = format!("f:{}:", ino);
+        let start_key = match after {
+            Some(after) => Self::next_scan_key(&Self::etcd_forward_key(ino, after)),
+            None => prefix.clone(),
+        };
+        let limit = i64::try_from(limit)
+            .map_err(|_| MetaError::Internal("etcd scan limit overflow".to_string()))?;
+        let options = GetOptions::new()
+            .with_range(Self::prefix_range_end(&prefix))
+            .with_limit(limit);
+        let mut client = self.client.clone();
+        let resp = client
+            .get(start_key, Some(options))
+            .await
+            .map_err(|e| MetaError::Internal(format!("Failed to list directory {ino}: {e}")))?;
+
+        let mut entries = Vec::with_capacity(resp.kvs().len());
+        for kv in resp.kvs() {
+            let Ok(entry) = serde_json::from_slice::<EtcdForwardEntry>(kv.value()) else {
+                continue;
+            };
+            let kind = match entry.resolved_entry_type() {
+                EntryType::File => FileType::File,
+                EntryType::Directory => FileType::Dir,
+                EntryType::Symlink => FileType::Symlink,
+            };
+            entries.push(DirEntry {
+                name: entry.name,
+                ino: entry.inode,
+                kind,
+            });
+        }
+        Ok(entries)
+    }
+
     #[tracing::instrument(level = "trace", skip(self), fields(parent, name))]
     async fn mkdir(&self, parent: i64, name: String) -> Result<i64, MetaError> {
         self.create_directory(parent, name).await
@@ -2,7 +2,7 @@ use crate::chunk::SliceDesc;
 use crate::meta::MetaStore;
 use crate::meta::config::Config;
 use crate::meta::config::{
-    CacheConfig, ClientOptions, CompactConfig, DatabaseConfig, DatabaseType,
+    CacheConfig, ClientOptions, CompactConfig, DatabaseConfig, DatabaseType, WriteCacheConfig,
 };
 use crate::meta::file_lock::{FileLockQuery, FileLockRange, FileLockType};
 use crate::meta::store::{LockName, MetaError, SetAttrFlags, SetAttrRequest};
@@ -51,6 +51,7 @@ fn test_config() -> Config {
         cache: CacheConfig::default(),
         client: ClientOptions::default(),
         compact: CompactConfig::default(),
+        write: WriteCacheConfig::default(),
     }
 }
 
@@ -65,6 +66,7 @@ fn shared_db_config() -> Config {
         cache: CacheConfig::default(),
         client: ClientOptions::default(),
         compact: CompactConfig::default(),
+        write: WriteCacheConfig::default(),
     }
 }
 
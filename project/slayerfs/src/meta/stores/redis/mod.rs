@@ -2,9 +2,10 @@
 //!
 //! This store focuses on the core interfaces needed by the VFS layer so that
 //! the filesystem can persist metadata in Redis. It purposely keeps the key
-//! layout simple (one key per inode plus a hash per directory) and uses JSON
-//! serialization for file attributes. Advanced features (sessions, quota, etc.)
-//! can be layered on later by extending the schema.
+//! layout simple (one key per inode plus a hash and a sorted name index per
+//! directory) and uses JSON serialization for file attributes. Advanced
+//! features (sessions, quota, etc.) can be layered on later by extending the
+//! schema.
 
 use super::{apply_truncate_plan, build_paths_from_names, trim_slices_in_place};
 use crate::chunk::SliceDesc;
@@ -41,6 +42,7 @@ const COUNTER_INODE_KEY: &str = "nextinode";
 const COUNTER_SLICE_KEY: &str = "nextchunk";
 const NODE_KEY_PREFIX: &str = "i";
 const DIR_KEY_PREFIX: &str = "d";
+const DIR_NAMES_KEY_PREFIX: &str = "dn";
 const CHUNK_KEY_PREFIX: &str = "c";
 const DELETED_SET_KEY: &str = "delslices";
 const ALL_SESSIONS_KEY: &str = "allsessions";
@@ -92,6 +94,7 @@ const LINK_LUA: &str = r#"
     local node_key = KEYS[1]
     local lp_key = KEYS[2]
     local dir_key = KEYS[3]
+    local dir_names_key = KEYS[4]
     local parent_ino = ARGV[1]
     local name = ARGV[2]
     local timestamp = tonumber(ARGV[3])
@@ -141,6 +144,7 @@ const LINK_LUA: &str = r#"
 
     -- Add to directory
     redis.call('HSET', dir_key, name, node.ino)
+    redis.call('ZADD', dir_names_key, 0, name)
 
     -- Save node
     redis.call('SET', node_key, cjson.encode(node))
@@ -153,6 +157,7 @@ const UNLINK_LUA: &str = r#"
     local node_key = KEYS[1]
     local lp_key = KEYS[2]
     local dir_key = KEYS[3]
+    local dir_names_key = KEYS[4]
     local parent_ino = ARGV[1]
     local name = ARGV[2]
     local timestamp = tonumber(ARGV[3])
@@ -176,6 +181,7 @@ const UNLINK_LUA: &str = r#"
 
     -- Remove from directory
     redis.call('HDEL', dir_key, name)
+    redis.call('ZREM', dir_names_key, name)
 
     -- Remove from link_parents (idempotent)
     local member = parent_ino .. ":" .. name
@@ -219,6 +225,8 @@ const RMDIR_LUA: &str = r#"
     local child_node_key = KEYS[2]
     local parent_node_key = KEYS[3]
     local child_dir_key = KEYS[4]
+    local parent_dir_names_key = KEYS[5]
+    local child_dir_names_key = KEYS[6]
     local name = ARGV[1]
     local child_ino = tonumber(ARGV[2])
     local parent_ino = tonumber(ARGV[3])
@@ -270,8 +278,10 @@ const RMDIR_LUA: &str = r#"
 
     -- Atomic delete
     redis.call('HDEL', parent_dir_key, name)
+    redis.call('ZREM', parent_dir_names_key, name)
     redis.call('DEL', child_node_key)
     redis.call('DEL', child_dir_key)
+    redis.call('DEL', child_dir_names_key)
 
     return cjson.encode({ok=true})
 "#;
@@ -283,6 +293,7 @@ const CREATE_ENTRY_LUA: &str = r#"
     local parent_dir_key = KEYS[1]
     local parent_node_key = KEYS[2]
     local counter_key = KEYS[3]
+    local parent_dir_names_key = KEYS[4]
     local name = ARGV[1]
     local kind = ARGV[2]
     local timestamp = tonumber(ARGV[3])
@@ -355,6 +366,7 @@ const CREATE_ENTRY_LUA: &str = r#"
 
     -- Add directory entry
     redis.call('HSET', parent_dir_key, name, new_ino)
+    redis.call('ZADD', parent_dir_names_key, 0, name)
 
     -- Update parent if creating directory (nlink++)
     if kind == "Dir" then
@@ -379,6 +391,8 @@ const RENAME_LUA: &str = r#"
     local old_parent_node_key = KEYS[4]
     local new_parent_node_key = KEYS[5]
     local link_parents_key = KEYS[6]
+    local old_parent_dir_names_key = KEYS[7]
+    local new_parent_dir_names_key = KEYS[8]
     local old_name = ARGV[1]
     local new_name = ARGV[2]
     local old_parent_ino = tonumber(ARGV[3])
@@ -475,7 +489,9 @@ const RENAME_LUA: &str = r#"
 
     -- Remove old dentry and add new dentry
     redis.call('HDEL', old_parent_dir_key, old_name)
+    redis.call('ZREM', old_parent_dir_names_key, old_name)
     redis.call('HSET', new_parent_dir_key, new_name, dentry_ino)
+    redis.call('ZADD', new_parent_dir_names_key, 0, new_name)
 
     -- Save updated child node
     redis.call('SET', child_node_key, cjson.encode(child_node))
@@ -691,6 +707,22 @@ const RENAME_EXCHANGE_LUA: &str = r#"
     return cjson.encode({ok=true})
 "#;
 
+// Lua script for indexing directory names created before the sorted-set index existed
+const DIR_NAMES_BACKFILL_LUA: &str = r#"
+    local dir_key = KEYS[1]
+    local dir_names_key = KEYS[2]
+
+    if redis.call('ZCARD', dir_names_key) == redis.call('HLEN', dir_key) then
+        return 0
+    end
+    redis.call('DEL', dir_names_key)
+    local names = redis.call('HKEYS', dir_key)
+    for _, name in ipairs(names) do
+        redis.call('ZADD', dir_names_key, 0, name)
+    end
+    return #names
+"#;
+
 /// Response structure for Lua script results
 #[derive(Debug, Deserialize)]
 struct LuaResponse {
@@ -862,6 +894,12 @@ impl RedisMetaStore {
         format!("{DIR_KEY_PREFIX}{ino}")
     }
 
+    /// Sorted set (all scores 0) mirroring the names in `dir_key`, so pages
+    /// can be read in name order with `ZRANGEBYLEX`.
+    fn dir_names_key(&self, ino: i64) -> String {
+        format!("{DIR_NAMES_KEY_PREFIX}{ino}")
+    }
+
     fn chunk_key(&self, chunk_id: u64) -> String {
         let inode = chunk_id / CHUNK_ID_BASE;
         let chunk_index = chunk_id % CHUNK_ID_BASE;
@@ -1087,6 +1125,7 @@ impl RedisMetaStore {
         let parent_dir_key = self.dir_key(parent);
         let parent_node_key = self.node_key(parent);
         let counter_key = COUNTER_INODE_KEY;
+        let parent_dir_names_key = self.dir_names_key(parent);
 
         // Step 3: Prepare ARGV parameters
         let kind_str = match kind {
@@ -1115,6 +1154,7 @@ impl RedisMetaStore {
             .key(&parent_dir_key) // KEYS[1]
             .key(&parent_node_key) // KEYS[2]
             .key(counter_key) // KEYS[3]
+            .key(&parent_dir_names_key) // KEYS[4]
             .arg(&name) // ARGV[1]
             .arg(kind_str) // ARGV[2]
             .arg(now) // ARGV[3]
@@ -1565,6 +1605,90 @@ impl MetaStore for RedisMetaStore {
         Ok(result)
     }
 
+    #[tracing::instrument(level = "trace", skip(self), fields(ino, limit))]
+    async fn readdir_page(
+        &self,
+        ino: i64,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DirEntry>, MetaError> {
+        let node = self.get_node(ino).await?.ok_or(MetaError::NotFound(ino))?;
+        if node.kind != NodeKind::Dir {
+            return Err(MetaError::NotDirectory(ino));
+        }
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut conn = self.conn.clone();
+        let dir_key = self.dir_key(ino);
+        let dir_names_key = self.dir_names_key(ino);
+        if after.is_none() {
+            let _: i64 = redis::Script::new(DIR_NAMES_BACKFILL_LUA)
+                .key(&dir_key)
+                .key(&dir_names_key)
+                .invoke_async(&mut conn)
+                .await
+                .map_err(redis_err)?;
+        }
+
+        // The name index has every score at 0, so a lex range is a name-ordered cursor.
+        let min = after.map_or_else(|| "-".to_string(), |name| format!("({name}"));
+        let names: Vec<String> = redis::cmd("ZRANGEBYLEX")
+            .arg(&dir_names_key)
+            .arg(min)
+            .arg("+")
+            .arg("LIMIT")
+            .arg(0)
+            .arg(limit)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let inos: Vec<Option<i64>> = redis::cmd("HMGET")
+            .arg(&dir_key)
+            .arg(&names)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+        let present: Vec<(String, i64)> = names
+            .into_iter()
+            .zip(inos)
+            .filter_map(|(name, child)| child.map(|child| (name, child)))
+            .collect();
+        if present.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let node_keys: Vec<String> = present
+            .iter()
+            .map(|(_, child)| self.node_key(*child))
+            .collect();
+        let nodes: Vec<Option<Vec<u8>>> = redis::cmd("MGET")
+            .arg(&node_keys)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+
+        let mut result = Vec::with_capacity(present.len());
+        for ((name, child), data) in present.into_iter().zip(nodes) {
+            let Some(bytes) = data else {
+                continue;
+            };
+            let node: StoredNode =
+                serde_json::from_slice(&bytes).map_err(|e| MetaError::Internal(e.to_string()))?;
+            result.push(DirEntry {
+                name,
+                ino: child,
+                kind: node.kind.into(),
+            });
+        }
+        Ok(result)
+    }
+
     #[tracing::instrument(level = "trace", skip(self), fields(parent, name))]
     async fn mkdir(&self, parent: i64, name: String) -> Result<i64, MetaError> {
         self.create_entry(parent, name, FileType::Dir).await
@@ -1582,6 +1706,8 @@ impl MetaStore for RedisMetaStore {
         let child_node_key = self.node_key(child);
         let parent_node_key = self.node_key(parent);
         let child_dir_key = self.dir_key(child);
+        let parent_dir_names_key = self.dir_names_key(parent);
+        let child_dir_names_key = self.dir_names_key(child);
         let now = current_time();
 
         // Step 3: Invoke Lua script atomically
@@ -1591,6 +1717,8 @@ impl MetaStore for RedisMetaStore {
             .key(&child_node_key)
             .key(&parent_node_key)
             .key(&child_dir_key)
+            .key(&parent_dir_names_key)
+            .key(&child_dir_names_key)
             .arg(name)
             .arg(child)
             .arg(parent)
@@ -1649,6 +1777,7 @@ impl MetaStore for RedisMetaStore {
         let node_key = self.node_key(ino);
         let lp_key = Self::link_parent_key(ino);
         let dir_key = self.dir_key(parent);
+        let dir_names_key = self.dir_names_key(parent);
         let now = current_time();
 
         let script = redis::Script::new(LINK_LUA);
@@ -1656,6 +1785,7 @@ impl MetaStore for RedisMetaStore {
             .key(&node_key)
             .key(&lp_key)
             .key(&dir_key)
+            .key(&dir_names_key)
             .arg(parent)
             .arg(name)
             .arg(now)
@@ -1751,6 +1881,7 @@ impl MetaStore for RedisMetaStore {
         let node_key = self.node_key(child);
         let lp_key = Self::link_parent_key(child);
         let dir_key = self.dir_key(parent);
+        let dir_names_key = self.dir_names_key(parent);
         let now = current_time();
 
         let script = redis::Script::new(UNLINK_LUA);
@@ -1758,6 +1889,7 @@ impl MetaStore for RedisMetaStore {
             .key(&node_key)
             .key(&lp_key)
             .key(&dir_key)
+            .key(&dir_names_key)
             .arg(parent)
             .arg(name)
             .arg(now)
@@ -1846,6 +1978,8 @@ impl MetaStore for RedisMetaStore {
         let old_parent_node_key = self.node_key(old_parent);
         let new_parent_node_key = self.node_key(new_parent);
         let link_parents_key = Self::link_parent_key(child);
+        let old_parent_dir_names_key = self.dir_names_key(old_parent);
+        let new_parent_dir_names_key = self.dir_names_key(new_parent);
         let now = current_time();
 
         // Step 3: Invoke Lua script atomically
@@ -1857,6 +1991,8 @@ impl MetaStore for RedisMetaStore {
             .key(&old_parent_node_key) // KEYS[4]
             .key(&new_parent_node_key) // KEYS[5]
             .key(&link_parents_key) // KEYS[6]
+            .key(&old_parent_dir_names_key) // KEYS[7]
+            .key(&new_parent_dir_names_key) // KEYS[8]
             .arg(old_name) // ARGV[1]
             .arg(&new_name) // ARGV[2]
             .arg(old_parent) // ARGV[3]
@@ -2174,13 +2310,36 @@ impl MetaStore for RedisMetaStore {
                 continue;
             }
 
-            used_space = used_space.saturating_add(node.attr.size);
             used_inodes = used_inodes.saturating_add(1);
         }
 
+        let chunk_keys: Vec<String> = redis::cmd("KEYS")
+            .arg(format!("{CHUNK_KEY_PREFIX}*"))
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_err)?;
+
+        for key in chunk_keys {
+            if Self::parse_chunk_id_from_chunk_key(&key).is_none() {
+                continue;
+            }
+            let raw: Vec<Vec<u8>> = redis::cmd("LRANGE")
+                .arg(&key)
+                .arg(0)
+                .arg(-1)
+                .query_async(&mut conn)
+                .await
+                .map_err(redis_err)?;
+            for bytes in raw {
+                let slice: SliceDesc = crate::meta::serialization::deserialize_meta(&bytes)?;
+                used_space = used_space.saturating_add(slice.length);
+            }
+        }
+
         Ok(StatFsSnapshot {
             total_space: used_space,
             available_space: 0,
+            used_space,
             used_inodes,
             available_inodes: 0,
         })
@@ -1,7 +1,7 @@
 use crate::meta::MetaStore;
 use crate::meta::config::Config;
 use crate::meta::config::{
-    CacheConfig, ClientOptions, CompactConfig, DatabaseConfig, DatabaseType,
+    CacheConfig, ClientOptions, CompactConfig, DatabaseConfig, DatabaseType, WriteCacheConfig,
 };
 use crate::meta::file_lock::{FileLockQuery, FileLockRange, FileLockType};
 use crate::meta::store::{LockName, MetaError, SetAttrFlags, SetAttrRequest};
@@ -43,6 +43,7 @@ fn test_config() -> Config {
         cache: CacheConfig::default(),
         client: ClientOptions::default(),
         compact: CompactConfig::default(),
+        write: WriteCacheConfig::default(),
     }
 }
 
@@ -57,6 +58,7 @@ fn shared_db_config() -> Config {
         cache: CacheConfig::default(),
         client: ClientOptions::default(),
         compact: CompactConfig::default(),
+        write: WriteCacheConfig::default(),
     }
 }
 
@@ -2041,6 +2043,63 @@ async fn test_readdir_basic_flow() {
     )));
 }
 
+#[serial]
+#[tokio::test]
+#[ignore]
+async fn test_readdir_page_walks_names_in_order() {
+    let store = new_test_store().await;
+    let root = store.root_ino();
+
+    let dir = store.mkdir(root, "paged".to_string()).await.unwrap();
+    for name in ["d", "b", "e", "a", "c"] {
+        store.create_file(dir, name.to_string()).await.unwrap();
+    }
+    store.mkdir(dir, "f".to_string()).await.unwrap();
+    store.unlink(dir, "e").await.unwrap();
+    store.rename(dir, "a", dir, "g".to_string()).await.unwrap();
+
+    let mut names = Vec::new();
+    let mut after: Option<String> = None;
+    loop {
+        let page = store.readdir_page(dir, after.as_deref(), 2).await.unwrap();
+        if page.is_empty() {
+            break;
+        }
+        assert!(page.len() <= 2);
+        after = page.last().map(|e| e.name.clone());
+        names.extend(page.into_iter().map(|e| (e.name, e.kind)));
+    }
+    assert_eq!(
+        names,
+        vec![
+            ("b".to_string(), crate::meta::store::FileType::File),
+            ("c".to_string(), crate::meta::store::FileType::File),
+            ("d".to_string(), crate::meta::store::FileType::File),
+            ("f".to_string(), crate::meta::store::FileType::Dir),
+            ("g".to_string(), crate::meta::store::FileType::File),
+        ]
+    );
+
+    // Directories written before the name index existed are indexed on the first page.
+    let mut conn = store.conn.clone();
+    let _: () = redis::cmd("DEL")
+        .arg(store.dir_names_key(dir))
+        .query_async(&mut conn)
+        .await
+        .unwrap();
+    let page = store.readdir_page(dir, None, 10).await.unwrap();
+    assert_eq!(page.len(), 5);
+    assert_eq!(page[0].name, "b");
+    let page = store.readdir_page(dir, Some("d"), 10).await.unwrap();
+    let rest: Vec<_> = page.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(rest, ["f", "g"]);
+
+    store.rmdir(dir, "f").await.unwrap();
+    let page = store.readdir_page(dir, Some("d"), 10).await.unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].name, "g");
+}
+
 // --- State machine tests ---
 
 #[serial]
@@ -2614,10 +2673,21 @@ async fn test_stat_fs_accounting_fallback() {
         "should count 4 non-deleted inodes (including root)"
     );
     assert_eq!(
-        snap.total_space,
-        1000 + 6,
-        "should count file and symlink size"
+        snap.used_space, 0,
+        "logical sizes without slices should not count as used space"
     );
+
+    let chunk_id = crate::vfs::chunk_id_for(f1, 0).unwrap();
+    let slice = crate::chunk::SliceDesc {
+        slice_id: 1,
+        chunk_id,
+        offset: 0,
+        length: 512,
+    };
+    store.write(f1, chunk_id, slice, 1000).await.unwrap();
+
+    let snap = store.stat_fs().await.unwrap();
+    assert_eq!(snap.used_space, 512, "should count allocated slice bytes");
 }
 
 #[serial]
@@ -11,11 +11,13 @@ use tokio::io::{AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
 use tokio::sync::Mutex;
 
 use crate::meta::store::{
-    DirEntry as MetaDirEntry, FileAttr as MetaFileAttr, FileType as MetaFileType,
+    DirEntry as MetaDirEntry, DirEntryPlus as MetaDirEntryPlus, FileAttr as MetaFileAttr,
+    FileType as MetaFileType, page_dir_entries,
 };
 
 // Re-export useful types from meta store
 pub use crate::meta::store::{SetAttrFlags, SetAttrRequest, StatFsSnapshot};
+pub use crate::vfs::cache::CacheStats;
 
 /// Backend trait for filesystem operations used by the std-like Client.
 ///
@@ -41,6 +43,36 @@ pub trait ClientBackend: Send + Sync + 'static {
     /// Read directory entries.
     async fn readdir(&self, path: &str) -> io::Result<Vec<MetaDirEntry>>;
 
+    /// Read up to `limit` directory entries named after `after`, in name order. Backends
+    /// without range queries page a full `readdir`.
+    async fn readdir_page(
+        &self,
+        path: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> io::Result<Vec<MetaDirEntry>> {
+        let entries = self.readdir(path).await?;
+        Ok(page_dir_entries(entries, after, limit))
+    }
+
+    /// Read directory entries with their attributes (readdir-plus). Backends without a
+    /// batched stat list entries without attributes, which are then looked up on demand.
+    async fn readdir_plus(&self, path: &str) -> io::Result<Vec<MetaDirEntryPlus>> {
+        let entries = self.readdir(path).await?;
+        Ok(without_attrs(entries))
+    }
+
+    /// `readdir_page` with the attributes of each entry, see `readdir_plus`.
+    async fn readdir_page_plus(
+        &self,
+        path: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> io::Result<Vec<MetaDirEntryPlus>> {
+        let entries = self.readdir_page(path, after, limit).await?;
+        Ok(without_attrs(entries))
+    }
+
     /// Get file/directory attributes.
     async fn stat(&self, path: &str) -> io::Result<MetaFileAttr>;
 
@@ -56,6 +88,56 @@ pub trait ClientBackend: Send + Sync + 'static {
     /// Truncate a file to the specified size.
     async fn truncate(&self, path: &str, size: u64) -> io::Result<()>;
 
+    /// Set an extended attribute (`flags`: `XATTR_CREATE` / `XATTR_REPLACE`).
+    async fn set_xattr(
+        &self,
+        _path: &str,
+        _name: &str,
+        _value: &[u8],
+        _flags: u32,
+    ) -> io::Result<()> {
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
+
+    /// Get an extended attribute value, `None` if unset.
+    async fn get_xattr(&self, _path: &str, _name: &str) -> io::Result<Option<Vec<u8>>> {
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
+
+    /// List extended attribute names.
+    async fn list_xattr(&self, _path: &str) -> io::Result<Vec<String>> {
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
+
+    /// Remove an extended attribute.
+    async fn remove_xattr(&self, _path: &str, _name: &str) -> io::Result<()> {
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
+
+    /// Persist buffered writes of a file. Backends that do not buffer have nothing to do.
+    async fn flush(&self, _path: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Persist buffered writes and metadata of a file.
+    async fn fsync(&self, path: &str) -> io::Result<()> {
+        self.flush(path).await
+    }
+
+    /// Persist buffered writes of every file before the client goes away.
+    async fn shutdown(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Make `dst` share the blocks of `src` copy-on-write. Backends without
+    /// block sharing report `Unsupported` and callers fall back to copying bytes.
+    async fn clone_file(&self, _src: &str, _dst: &str) -> io::Result<u64> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "clone_file is not supported by this backend",
+        ))
+    }
+
     /// Check whether a path exists.
     async fn exists(&self, path: &str) -> bool;
 
@@ -76,6 +158,22 @@ pub trait ClientBackend: Send + Sync + 'static {
     /// Get file system statistics (total/available space and inodes).
     async fn stat_fs(&self) -> io::Result<StatFsSnapshot>;
 
+    /// Get `statvfs`-style usage figures, including the block size.
+    async fn statfs(&self) -> io::Result<FsStats> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "statfs is not supported by this backend",
+        ))
+    }
+
+    /// Get write cache usage and block store cache counters.
+    async fn cache_stats(&self) -> io::Result<CacheStats> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "cache stats are not supported by this backend",
+        ))
+    }
+
     /// Create a hard link.
     async fn link(&self, existing: &str, link_path: &str) -> io::Result<MetaFileAttr>;
 
@@ -139,6 +237,70 @@ impl Client {
         self.client.rename(&old, &new).await
     }
 
+    /// Upload pending writes of `path` and return once the object store acknowledged them.
+    /// Cheap when the file has no dirty pages.
+    pub async fn flush(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path_to_str(path)?;
+        self.client.flush(&path).await
+    }
+
+    /// Make data and metadata of `path` durable.
+    pub async fn fsync(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path_to_str(path)?;
+        self.client.fsync(&path).await
+    }
+
+    /// Flush all pending writes so no buffered data is lost when the client is dropped.
+    ///
+    /// Background flushing persists data of slow writers eventually; call this before
+    /// exiting to make sure everything written so far is committed. The client stays
+    /// usable afterwards.
+    pub async fn shutdown(&self) -> io::Result<()> {
+        self.client.shutdown().await
+    }
+
+    /// Set extended attribute `name` to `value`, creating or replacing it.
+    pub async fn setxattr(
+        &self,
+        path: impl AsRef<Path>,
+        name: &str,
+        value: impl AsRef<[u8]>,
+    ) -> io::Result<()> {
+        let path = path_to_str(path)?;
+        self.client.set_xattr(&path, name, value.as_ref(), 0).await
+    }
+
+    /// Get extended attribute `name`, `None` if it is not set.
+    pub async fn getxattr(
+        &self,
+        path: impl AsRef<Path>,
+        name: &str,
+    ) -> io::Result<Option<Vec<u8>>> {
+        let path = path_to_str(path)?;
+        self.client.get_xattr(&path, name).await
+    }
+
+    /// List the extended attribute names of `path`.
+    pub async fn listxattr(&self, path: impl AsRef<Path>) -> io::Result<Vec<String>> {
+        let path = path_to_str(path)?;
+        self.client.list_xattr(&path).await
+    }
+
+    /// Remove extended attribute `name`.
+    pub async fn removexattr(&self, path: impl AsRef<Path>, name: &str) -> io::Result<()> {
+        let path = path_to_str(path)?;
+        self.client.remove_xattr(&path, name).await
+    }
+
+    /// Shrink or extend a file to `new_size` bytes.
+    ///
+    /// Data past `new_size` is dropped when shrinking. Extending is sparse: the new range reads
+    /// back as zeros without allocating blocks.
+    pub async fn truncate(&self, path: impl AsRef<Path>, new_size: u64) -> io::Result<()> {
+        let path = path_to_str(path)?;
+        self.client.truncate(&path, new_size).await
+    }
+
     pub async fn read(&self, path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
         let mut opts = OpenOptions::new();
         opts.read(true);
@@ -162,7 +324,7 @@ impl Client {
 
     pub async fn read_dir(&self, path: impl AsRef<Path>) -> io::Result<ReadDir> {
         let path = path_to_str(path)?;
-        let entries = self.client.readdir(&path).await?;
+        let entries = self.client.readdir_plus(&path).await?;
         Ok(ReadDir {
             client: Arc::clone(&self.client),
             parent: path,
@@ -170,10 +332,62 @@ impl Client {
         })
     }
 
+    /// Read one page of a directory: up to `limit` entries named after `after`, in name order.
+    ///
+    /// Pass the returned `next` cursor as `after` to get the following page. Entries are keyed
+    /// by name, so creating or removing entries between pages never skips or repeats the others.
+    pub async fn readdir_page(
+        &self,
+        path: impl AsRef<Path>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> io::Result<DirPage> {
+        if limit == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "readdir page limit must be greater than 0",
+            ));
+        }
+        let path = path_to_str(path)?;
+        // One extra entry tells whether another page follows.
+        let mut entries = self
+            .client
+            .readdir_page_plus(&path, after, limit.saturating_add(1))
+            .await?;
+        let next = if entries.len() > limit {
+            entries.truncate(limit);
+            entries.last().map(|plus| plus.entry.name.clone())
+        } else {
+            None
+        };
+        Ok(DirPage {
+            entries: entries
+                .into_iter()
+                .map(|plus| DirEntry {
+                    client: Arc::clone(&self.client),
+                    parent: path.clone(),
+                    inner: plus.entry,
+                    attr: plus.attr,
+                })
+                .collect(),
+            next,
+        })
+    }
+
+    /// Copy `src` to `dst`, creating or truncating `dst`.
+    ///
+    /// When the backend supports it the copy is copy-on-write: `dst` references the
+    /// same block objects as `src` and the two only diverge once one side is
+    /// modified. Otherwise the bytes are copied.
     pub async fn copy(&self, src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<u64> {
         let src = path_to_str(src)?;
         let dst = path_to_str(dst)?;
 
+        match self.client.clone_file(&src, &dst).await {
+            Err(e) if e.kind() == io::ErrorKind::Unsupported => {}
+            other => return other,
+        }
+
         let mut src_opts = OpenOptions::new();
         src_opts.read(true);
         let src_f = self.open(&src_opts, &src).await?;
@@ -223,6 +437,11 @@ impl Client {
         self.client.remove_dir_all(&path).await
     }
 
+    /// Change the permission bits of a file or directory, like chmod(2).
+    pub async fn chmod(&self, path: impl AsRef<Path>, mode: u32) -> io::Result<()> {
+        self.set_permissions(path, mode).await
+    }
+
     /// Change the permission mode of a file or directory.
     pub async fn set_permissions(&self, path: impl AsRef<Path>, mode: u32) -> io::Result<()> {
         let path = path_to_str(path)?;
@@ -287,6 +506,27 @@ impl Client {
         self.client.stat_fs().await
     }
 
+    /// Get filesystem usage in the spirit of `statvfs(3)`.
+    ///
+    /// Used bytes count blocks actually written to the object store, so holes
+    /// in sparse files do not count towards usage.
+    pub async fn statfs(&self) -> io::Result<FsStats> {
+        self.client.statfs().await
+    }
+
+    /// Get cache usage across all active write slices plus block store cache hits/misses.
+    pub async fn cache_stats(&self) -> io::Result<CacheStats> {
+        self.client.cache_stats().await
+    }
+
+    /// Create a hard link `new` to the file at `existing`, like link(2).
+    ///
+    /// Both names refer to the same inode, whose `nlink` counts its names. Removing one name
+    /// leaves the data reachable through the others; it is only freed once the last is gone.
+    pub async fn link(&self, existing: impl AsRef<Path>, new: impl AsRef<Path>) -> io::Result<()> {
+        self.hard_link(existing, new).await
+    }
+
     /// Create a hard link.
     pub async fn hard_link(
         &self,
@@ -387,6 +627,28 @@ where
         self.readdir(path).await
     }
 
+    async fn readdir_page(
+        &self,
+        path: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> io::Result<Vec<MetaDirEntry>> {
+        self.readdir_page(path, after, limit).await
+    }
+
+    async fn readdir_plus(&self, path: &str) -> io::Result<Vec<MetaDirEntryPlus>> {
+        self.readdir_plus(path).await
+    }
+
+    async fn readdir_page_plus(
+        &self,
+        path: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> io::Result<Vec<MetaDirEntryPlus>> {
+        self.readdir_page_plus(path, after, limit).await
+    }
+
     async fn stat(&self, path: &str) -> io::Result<MetaFileAttr> {
         self.stat(path).await
     }
@@ -407,6 +669,38 @@ where
         self.truncate(path, size).await
     }
 
+    async fn flush(&self, path: &str) -> io::Result<()> {
+        crate::vfs::sdk::VfsClient::flush(self, path).await
+    }
+
+    async fn set_xattr(&self, path: &str, name: &str, value: &[u8], flags: u32) -> io::Result<()> {
+        crate::vfs::sdk::VfsClient::set_xattr(self, path, name, value, flags).await
+    }
+
+    async fn get_xattr(&self, path: &str, name: &str) -> io::Result<Option<Vec<u8>>> {
+        crate::vfs::sdk::VfsClient::get_xattr(self, path, name).await
+    }
+
+    async fn list_xattr(&self, path: &str) -> io::Result<Vec<String>> {
+        crate::vfs::sdk::VfsClient::list_xattr(self, path).await
+    }
+
+    async fn remove_xattr(&self, path: &str, name: &str) -> io::Result<()> {
+        crate::vfs::sdk::VfsClient::remove_xattr(self, path, name).await
+    }
+
+    async fn fsync(&self, path: &str) -> io::Result<()> {
+        crate::vfs::sdk::VfsClient::fsync(self, path).await
+    }
+
+    async fn shutdown(&self) -> io::Result<()> {
+        crate::vfs::sdk::VfsClient::shutdown(self).await
+    }
+
+    async fn clone_file(&self, src: &str, dst: &str) -> io::Result<u64> {
+        crate::vfs::sdk::VfsClient::clone_file(self, src, dst).await
+    }
+
     async fn exists(&self, path: &str) -> bool {
         crate::vfs::sdk::VfsClient::exists(self, path).await
     }
@@ -432,6 +726,14 @@ where
         self.stat_fs().await
     }
 
+    async fn statfs(&self) -> io::Result<FsStats> {
+        crate::vfs::sdk::VfsClient::statfs(self).await
+    }
+
+    async fn cache_stats(&self) -> io::Result<CacheStats> {
+        Ok(crate::vfs::sdk::VfsClient::cache_stats(self).await)
+    }
+
     async fn link(&self, existing: &str, link_path: &str) -> io::Result<MetaFileAttr> {
         self.link(existing, link_path).await
     }
@@ -461,6 +763,27 @@ fn path_to_str(path: impl AsRef<Path>) -> io::Result<String> {
     Ok(s)
 }
 
+fn without_attrs(entries: Vec<MetaDirEntry>) -> Vec<MetaDirEntryPlus> {
+    entries
+        .into_iter()
+        .map(|entry| MetaDirEntryPlus { entry, attr: None })
+        .collect()
+}
+
+/// Filesystem usage returned by [`Client::statfs`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FsStats {
+    /// Block size of the chunk layout, in bytes.
+    pub block_size: u64,
+    /// Configured capacity, or a large nominal value for unbounded stores.
+    pub total_bytes: u64,
+    /// Bytes held by blocks written to the object store.
+    pub used_bytes: u64,
+    pub available_bytes: u64,
+    pub total_inodes: u64,
+    pub used_inodes: u64,
+}
+
 #[derive(Debug, Clone)]
 pub struct FileType(MetaFileType);
 
@@ -921,30 +1244,18 @@ impl File {
     }
 
     /// Synchronize all file data and metadata to storage.
-    ///
-    /// In SlayerFS, writes are persisted immediately to object storage,
-    /// so this is a no-op but provided for API compatibility.
     pub async fn sync_all(&self) -> io::Result<()> {
-        // SlayerFS writes directly to object storage, so data is already persisted.
-        // This method exists for API compatibility with std::fs::File.
-        Ok(())
+        self.client.fsync(&self.path).await
     }
 
     /// Synchronize file data to storage (without metadata).
-    ///
-    /// In SlayerFS, writes are persisted immediately to object storage,
-    /// so this is a no-op but provided for API compatibility.
     pub async fn sync_data(&self) -> io::Result<()> {
-        // SlayerFS writes directly to object storage, so data is already persisted.
-        Ok(())
+        self.client.flush(&self.path).await
     }
 
-    /// Flush internal buffers.
-    ///
-    /// In SlayerFS, there are no internal buffers to flush,
-    /// so this is a no-op but provided for API compatibility.
+    /// Flush buffered writes to the object store.
     pub async fn flush(&self) -> io::Result<()> {
-        Ok(())
+        self.client.flush(&self.path).await
     }
 }
 
@@ -1172,10 +1483,22 @@ impl AsyncSeek for File {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct DirEntry {
+    client: DynClient,
     parent: String,
     inner: MetaDirEntry,
+    /// Attributes returned with the listing, if the backend supports readdir-plus.
+    attr: Option<MetaFileAttr>,
+}
+
+impl std::fmt::Debug for DirEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DirEntry")
+            .field("parent", &self.parent)
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
 }
 
 impl DirEntry {
@@ -1194,20 +1517,43 @@ impl DirEntry {
             format!("{}/{}", self.parent, self.inner.name)
         }
     }
+
+    pub fn ino(&self) -> i64 {
+        self.inner.ino
+    }
+
+    /// Mode, ownership and times of the entry as of the listing. Symlinks are not followed.
+    pub async fn metadata(&self) -> io::Result<Metadata> {
+        if let Some(attr) = &self.attr {
+            return Ok(Metadata(attr.clone()));
+        }
+        let attr = self.client.lstat(&self.path()).await?;
+        Ok(Metadata(attr))
+    }
+}
+
+/// One page of a directory listing, see `Client::readdir_page`.
+#[derive(Debug, Clone)]
+pub struct DirPage {
+    pub entries: Vec<DirEntry>,
+    /// Cursor of the next page, `None` once the listing is complete.
+    pub next: Option<String>,
 }
 
 pub struct ReadDir {
     client: DynClient,
     parent: String,
-    entries: VecDeque<MetaDirEntry>,
+    entries: VecDeque<MetaDirEntryPlus>,
 }
 
 impl ReadDir {
     pub async fn next_entry(&mut self) -> io::Result<Option<DirEntry>> {
         match self.entries.pop_front() {
-            Some(inner) => Ok(Some(DirEntry {
+            Some(plus) => Ok(Some(DirEntry {
+                client: Arc::clone(&self.client),
                 parent: self.parent.clone(),
-                inner,
+                inner: plus.entry,
+                attr: plus.attr,
             })),
             None => Ok(None),
         }
@@ -1402,6 +1748,269 @@ mod tests {
         assert_eq!(read_buf3.filled(), b"efgh");
     }
 
+    #[tokio::test]
+    async fn rename_moves_file_and_keeps_contents() {
+        let (_tmp, fs) = local_client().await;
+        fs.create_dir_all("/src").await.unwrap();
+        fs.create_dir_all("/dst/full").await.unwrap();
+        fs.write("/src/a.txt", b"payload").await.unwrap();
+        fs.write("/dst/b.txt", b"old").await.unwrap();
+        fs.write("/dst/full/c.txt", b"keep").await.unwrap();
+
+        // Across directories, replacing an existing file.
+        fs.rename("/src/a.txt", "/dst/b.txt").await.unwrap();
+        assert!(!fs.exists("/src/a.txt").await);
+        assert_eq!(fs.read("/dst/b.txt").await.unwrap(), b"payload");
+
+        let names = |dir: &'static str| {
+            let fs = fs.clone();
+            async move {
+                let mut names = Vec::new();
+                let mut entries = fs.read_dir(dir).await.unwrap();
+                while let Some(entry) = entries.next_entry().await.unwrap() {
+                    names.push(entry.file_name().to_string());
+                }
+                names
+            }
+        };
+        assert!(names("/src").await.is_empty());
+        assert!(names("/dst").await.contains(&"b.txt".to_string()));
+
+        // A non-empty directory is never replaced.
+        fs.create_dir_all("/src/d").await.unwrap();
+        assert!(fs.rename("/src/d", "/dst/full").await.is_err());
+        assert_eq!(fs.read("/dst/full/c.txt").await.unwrap(), b"keep");
+        assert!(fs.exists("/src/d").await);
+    }
+
+    #[tokio::test]
+    async fn truncate_shrinks_and_extends_sparsely() {
+        let (_tmp, fs) = local_client().await;
+        fs.write("/t.bin", b"0123456789").await.unwrap();
+
+        fs.truncate("/t.bin", 4).await.unwrap();
+        assert_eq!(fs.metadata("/t.bin").await.unwrap().len(), 4);
+        assert_eq!(fs.read("/t.bin").await.unwrap(), b"0123");
+
+        fs.truncate("/t.bin", 4096).await.unwrap();
+        assert_eq!(fs.metadata("/t.bin").await.unwrap().len(), 4096);
+        let data = fs.read("/t.bin").await.unwrap();
+        assert_eq!(data.len(), 4096);
+        assert_eq!(&data[..4], b"0123");
+        assert!(data[4..].iter().all(|b| *b == 0));
+
+        // Nothing is readable past the new end.
+        let past_end = fs.client().read_at("/t.bin", 4096, 16).await;
+        assert!(past_end.map(|d| d.is_empty()).unwrap_or(true));
+    }
+
+    #[tokio::test]
+    async fn chmod_chown_and_set_times_round_trip() {
+        let (_tmp, fs) = local_client().await;
+        fs.create_dir_all("/perm").await.unwrap();
+        fs.write("/perm/f", b"x").await.unwrap();
+
+        let meta = fs.metadata("/perm/f").await.unwrap();
+        assert_eq!(meta.mode() & 0o7777, 0o644);
+        assert_eq!(fs.metadata("/perm").await.unwrap().mode() & 0o7777, 0o755);
+
+        fs.chmod("/perm/f", 0o600).await.unwrap();
+        fs.chown("/perm/f", Some(1000), Some(1001)).await.unwrap();
+        fs.set_times("/perm/f", Some(1_000), Some(2_000))
+            .await
+            .unwrap();
+
+        let meta = fs.metadata("/perm/f").await.unwrap();
+        assert_eq!(meta.mode() & 0o7777, 0o600);
+        assert_eq!((meta.uid(), meta.gid()), (1000, 1001));
+        assert_eq!((meta.atime(), meta.mtime()), (1_000, 2_000));
+
+        // Directory listings expose the same attributes.
+        let mut entries = fs.read_dir("/perm").await.unwrap();
+        let entry = entries.next_entry().await.unwrap().unwrap();
+        assert_eq!(entry.ino(), meta.ino());
+        let listed = entry.metadata().await.unwrap();
+        assert_eq!(listed.mode(), meta.mode());
+        assert_eq!((listed.uid(), listed.gid()), (1000, 1001));
+    }
+
+    #[tokio::test]
+    async fn readdir_page_walks_directory_in_name_order() {
+        let (_tmp, fs) = local_client().await;
+        fs.create_dir("/big").await.unwrap();
+        for name in ["e", "a", "d", "b", "c"] {
+            fs.write(format!("/big/{name}"), b"x").await.unwrap();
+        }
+
+        let page = fs.readdir_page("/big", None, 2).await.unwrap();
+        let names: Vec<_> = page.entries.iter().map(|e| e.file_name()).collect();
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(page.entries[0].path(), "/big/a");
+        assert_eq!(page.next.as_deref(), Some("b"));
+
+        // Changes behind the cursor do not shift the following pages.
+        fs.remove_file("/big/a").await.unwrap();
+        fs.write("/big/aa", b"x").await.unwrap();
+        fs.write("/big/cc", b"x").await.unwrap();
+
+        let mut names = Vec::new();
+        let mut after = page.next;
+        while let Some(cursor) = after {
+            let page = fs
+                .readdir_page("/big", Some(cursor.as_str()), 2)
+                .await
+                .unwrap();
+            names.extend(page.entries.iter().map(|e| e.file_name().to_string()));
+            after = page.next;
+        }
+        assert_eq!(names, ["c", "cc", "d", "e"]);
+
+        let err = fs.readdir_page("/big", None, 0).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = fs.readdir_page("/big/e", None, 2).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotADirectory);
+    }
+
+    #[tokio::test]
+    async fn dir_entry_metadata_comes_with_the_listing() {
+        let (_tmp, fs) = local_client().await;
+        fs.create_dir("/plus").await.unwrap();
+        fs.write("/plus/a", b"abc").await.unwrap();
+        fs.symlink("/plus/a", "/plus/l").await.unwrap();
+
+        let page = fs.readdir_page("/plus", None, 10).await.unwrap();
+        let mut entries = fs.read_dir("/plus").await.unwrap();
+        let listed = entries.next_entry().await.unwrap().unwrap();
+
+        // Attributes were fetched with the listing, so no per-entry lookup is needed.
+        fs.remove_file("/plus/a").await.unwrap();
+        let meta = listed.metadata().await.unwrap();
+        assert_eq!(listed.file_name(), "a");
+        assert_eq!(meta.len(), 3);
+        assert_eq!(page.entries[0].metadata().await.unwrap().ino(), meta.ino());
+        let link = page.entries[1].metadata().await.unwrap();
+        assert!(link.file_type().is_symlink());
+    }
+
+    #[tokio::test]
+    async fn statfs_counts_allocated_blocks_not_logical_size() {
+        let (_tmp, fs) = local_client().await;
+        fs.write("/sparse.bin", vec![7u8; 4096]).await.unwrap();
+        fs.fsync("/sparse.bin").await.unwrap();
+        fs.truncate("/sparse.bin", 64 * 1024 * 1024).await.unwrap();
+
+        let stats = fs.statfs().await.unwrap();
+        assert_eq!(stats.block_size, ChunkLayout::default().block_size as u64);
+        assert_eq!(stats.used_bytes, 4096);
+        assert!(stats.total_bytes >= stats.used_bytes);
+        assert_eq!(stats.available_bytes, stats.total_bytes - stats.used_bytes);
+        // Root directory plus the file.
+        assert!(stats.used_inodes >= 2);
+        assert!(stats.total_inodes > stats.used_inodes);
+    }
+
+    #[tokio::test]
+    async fn flush_and_fsync_persist_writes() {
+        let (_tmp, fs) = local_client().await;
+
+        let mut opts = OpenOptions::new();
+        opts.write(true).create(true);
+        let f = fs.open(&opts, "/db.log").await.unwrap();
+        f.write_all(b"record-1").await.unwrap();
+        f.sync_all().await.unwrap();
+
+        fs.flush("/db.log").await.unwrap();
+        fs.fsync("/db.log").await.unwrap();
+        assert_eq!(fs.read("/db.log").await.unwrap(), b"record-1");
+
+        fs.create_dir("/dir").await.unwrap();
+        let err = fs.flush("/dir").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::IsADirectory);
+    }
+
+    #[tokio::test]
+    async fn shutdown_commits_pending_writes() {
+        let (_tmp, fs) = local_client().await;
+
+        let mut opts = OpenOptions::new();
+        opts.write(true).create(true);
+        for name in ["/a.log", "/b.log"] {
+            let f = fs.open(&opts, name).await.unwrap();
+            f.write_all(&[1u8; 3000]).await.unwrap();
+        }
+
+        fs.shutdown().await.unwrap();
+        assert_eq!(fs.statfs().await.unwrap().used_bytes, 6000);
+        assert_eq!(fs.read("/b.log").await.unwrap(), vec![1u8; 3000]);
+    }
+
+    #[tokio::test]
+    async fn copy_shares_blocks_until_modified() {
+        let (_tmp, fs) = local_client().await;
+        let data: Vec<u8> = (0..3 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+        fs.write("/src.bin", &data).await.unwrap();
+        fs.fsync("/src.bin").await.unwrap();
+        let used = fs.statfs().await.unwrap().used_bytes;
+
+        assert_eq!(
+            fs.copy("/src.bin", "/dst.bin").await.unwrap(),
+            data.len() as u64
+        );
+        assert_eq!(fs.read("/dst.bin").await.unwrap(), data);
+        assert_eq!(fs.statfs().await.unwrap().used_bytes, used);
+
+        let mut opts = OpenOptions::new();
+        opts.write(true);
+        let f = fs.open(&opts, "/dst.bin").await.unwrap();
+        f.seek(io::SeekFrom::Start(1024)).await.unwrap();
+        f.write_all(b"diverged").await.unwrap();
+        f.sync_all().await.unwrap();
+        drop(f);
+
+        let mut expected = data.clone();
+        expected[1024..1032].copy_from_slice(b"diverged");
+        assert_eq!(fs.read("/dst.bin").await.unwrap(), expected);
+        assert_eq!(fs.read("/src.bin").await.unwrap(), data);
+
+        fs.remove_file("/src.bin").await.unwrap();
+        assert_eq!(fs.read("/dst.bin").await.unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn xattr_roundtrip() {
+        let (_tmp, fs) = local_client().await;
+        fs.write("/obj", b"data").await.unwrap();
+
+        fs.setxattr("/obj", "user.content-type", b"text/plain")
+            .await
+            .unwrap();
+        fs.setxattr("/obj", "user.crc", [0u8, 1, 2, 255])
+            .await
+            .unwrap();
+        assert_eq!(
+            fs.getxattr("/obj", "user.crc").await.unwrap(),
+            Some(vec![0u8, 1, 2, 255])
+        );
+        assert_eq!(fs.getxattr("/obj", "user.missing").await.unwrap(), None);
+
+        let mut names = fs.listxattr("/obj").await.unwrap();
+        names.sort();
+        assert_eq!(names, vec!["user.content-type", "user.crc"]);
+
+        let too_big = vec![0u8; crate::vfs::fs::XATTR_SIZE_MAX + 1];
+        let err = fs.setxattr("/obj", "user.big", &too_big).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ArgumentListTooLong);
+
+        fs.removexattr("/obj", "user.crc").await.unwrap();
+        assert_eq!(
+            fs.listxattr("/obj").await.unwrap(),
+            vec!["user.content-type"]
+        );
+
+        fs.remove_file("/obj").await.unwrap();
+        assert!(fs.getxattr("/obj", "user.content-type").await.is_err());
+    }
+
     #[tokio::test]
     async fn open_create_new_fails_if_exists() {
         let (_tmp, fs) = local_client().await;
@@ -1588,14 +2197,35 @@ mod tests {
 
         fs.write("/orig.txt", b"original").await.unwrap();
 
-        let result = fs.symlink("/orig.txt", "/link.txt").await;
-        if result.is_ok() {
-            let target = fs.read_link("/link.txt").await.unwrap();
-            assert_eq!(target, "/orig.txt");
+        fs.symlink("/orig.txt", "/link.txt").await.unwrap();
+        let target = fs.read_link("/link.txt").await.unwrap();
+        assert_eq!(target, "/orig.txt");
+
+        let link_meta = fs.symlink_metadata("/link.txt").await.unwrap();
+        assert!(link_meta.is_symlink());
+        assert!(!fs.metadata("/link.txt").await.unwrap().is_symlink());
+        assert_eq!(fs.read("/link.txt").await.unwrap(), b"original");
 
-            let link_meta = fs.symlink_metadata("/link.txt").await.unwrap();
-            assert!(link_meta.is_symlink());
+        let mut entries = fs.read_dir("/").await.unwrap();
+        let mut link_type = None;
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            if entry.file_name() == "link.txt" {
+                link_type = Some(entry.file_type());
+            }
         }
+        assert!(link_type.expect("link listed").is_symlink());
+    }
+
+    #[tokio::test]
+    async fn test_symlink_cycle_is_rejected() {
+        let (_tmp, fs) = local_client().await;
+
+        fs.symlink("/b", "/a").await.unwrap();
+        fs.symlink("/a", "/b").await.unwrap();
+
+        assert_eq!(fs.read_link("/a").await.unwrap(), "/b");
+        assert!(fs.metadata("/a").await.is_err());
+        assert!(fs.read("/a").await.is_err());
     }
 
     #[tokio::test]
@@ -1613,4 +2243,35 @@ mod tests {
             assert!(link_meta.nlink() >= 2);
         }
     }
+
+    #[tokio::test]
+    async fn link_shares_data_and_survives_unlink() {
+        let (_tmp, fs) = local_client().await;
+
+        fs.write("/a.txt", b"content").await.unwrap();
+        fs.create_dir("/dir").await.unwrap();
+        fs.link("/a.txt", "/dir/b.txt").await.unwrap();
+
+        let a = fs.metadata("/a.txt").await.unwrap();
+        let b = fs.metadata("/dir/b.txt").await.unwrap();
+        assert_eq!(a.ino(), b.ino());
+        assert_eq!(a.nlink(), 2);
+        assert_eq!(b.nlink(), 2);
+
+        let mut opts = OpenOptions::new();
+        opts.write(true);
+        let f = fs.open(&opts, "/dir/b.txt").await.unwrap();
+        f.write_all(b"CON").await.unwrap();
+        assert_eq!(fs.read("/a.txt").await.unwrap(), b"CONtent");
+
+        fs.remove_file("/a.txt").await.unwrap();
+        assert!(!fs.exists("/a.txt").await);
+        assert_eq!(fs.read("/dir/b.txt").await.unwrap(), b"CONtent");
+        assert_eq!(fs.metadata("/dir/b.txt").await.unwrap().nlink(), 1);
+
+        let err = fs.link("/dir", "/dir2").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::IsADirectory);
+        let err = fs.link("/missing", "/c.txt").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
 }
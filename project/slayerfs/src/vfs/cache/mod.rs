@@ -1 +1,41 @@
 pub(crate) mod page;
+
+use crate::chunk::store::BlockCacheStats;
+use page::CacheSliceStats;
+use serde::Serialize;
+
+/// Filesystem-wide cache usage, summed over every slice buffered by the file writers
+/// plus the block store read cache counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    /// Number of active (not yet released) write slices.
+    pub slices: usize,
+    /// Bytes written into the slices.
+    pub len: u64,
+    /// Bytes allocated for pages; compare against `len` to tune `page_size`.
+    pub alloc_bytes: u64,
+    pub pages_total: usize,
+    pub pages_used: usize,
+    /// Block reads answered by the block store cache.
+    pub block_cache_hits: u64,
+    /// Block reads that went to the object store.
+    pub block_cache_misses: u64,
+}
+
+impl CacheStats {
+    pub(crate) fn add_slice(&mut self, stats: CacheSliceStats) {
+        self.slices += 1;
+        self.len += stats.len;
+        self.alloc_bytes += stats.alloc_bytes;
+        self.pages_total += stats.pages_total;
+        self.pages_used += stats.pages_used;
+    }
+
+    pub(crate) fn with_block_cache(self, stats: BlockCacheStats) -> Self {
+        Self {
+            block_cache_hits: stats.hits,
+            block_cache_misses: stats.misses,
+            ..self
+        }
+    }
+}
@@ -1,12 +1,14 @@
 use std::io::{Cursor, Read};
 use std::mem::take;
 use std::sync::Arc;
+use std::time::Instant;
 
 use crate::chunk::{BlockTag, ChunkSpan, PageTag};
 use crate::utils::NumCastExt;
 use crate::utils::zero::make_zero_bytes;
 use crate::vfs::config::WriteConfig;
 use bytes::{Bytes, BytesMut};
+use thiserror::Error;
 
 #[derive(Debug)]
 pub(crate) enum WriteAction {
@@ -14,11 +16,42 @@ pub(crate) enum WriteAction {
     Append,
 }
 
+/// Errors returned by [`CacheSlice::write`], [`CacheSlice::write_at`] and
+/// [`CacheSlice::append`].
+///
+/// `Frozen` means the target range touches pages sealed by `freeze` or
+/// `freeze_blocks`. The check happens before any byte is copied, so the slice
+/// is left untouched and the caller can retry the whole buffer elsewhere:
+///
+/// ```text
+/// loop {
+///     let slice = find_writable_slice_or_create(offset, len);
+///     match slice.write(offset, buf, action) {
+///         Ok(()) => break,
+///         Err(WriteError::Frozen { .. }) => continue, // raced with a freeze
+///         Err(e) => return Err(e),
+///     }
+/// }
+/// ```
+#[derive(Debug, Error)]
+pub(crate) enum WriteError {
+    #[error("slice was frozen (freeze version {version})")]
+    Frozen { version: u64 },
+    #[error("append exceeds chunk size")]
+    ExceedsChunk,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
 pub(crate) struct CacheSlice {
     config: Arc<WriteConfig>,
     len: u64,
     alloc_bytes: u64,
     pages: Vec<Option<Page>>,
+    /// Last time each block was written, used to pick eviction victims.
+    last_write: Vec<Option<Instant>>,
+    /// Bumped on every `freeze`/`freeze_blocks`, reported in `WriteError::Frozen`.
+    version: u64,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -31,11 +64,8 @@ pub(crate) struct CacheSliceStats {
 
 impl CacheSlice {
     pub(crate) fn new(config: Arc<WriteConfig>) -> Self {
-        assert_eq!(
-            config.layout.block_size % config.page_size,
-            0,
-            "The block size must be a multiple of the page size"
-        );
+        // The VFS validates its config once when it is built.
+        debug_assert!(config.validate().is_ok());
 
         let (chunk_size, block_size, page_size) = (
             config.layout.chunk_size,
@@ -55,6 +85,8 @@ impl CacheSlice {
             len: 0,
             alloc_bytes: 0,
             pages,
+            last_write: vec![None; blocks],
+            version: 0,
         }
     }
 
@@ -75,7 +107,7 @@ impl CacheSlice {
         offset: u64,
         buf: &[u8],
         action: WriteAction,
-    ) -> anyhow::Result<()> {
+    ) -> Result<(), WriteError> {
         match action {
             WriteAction::Append => self.append(buf),
             WriteAction::Overlap => self.write_at(offset, buf),
@@ -89,7 +121,8 @@ impl CacheSlice {
 
     /// Use buffer to overlap specific range.
     #[tracing::instrument(level = "trace", skip(self, buf), fields(len = buf.len()))]
-    pub(crate) fn write_at(&mut self, offset: u64, buf: &[u8]) -> anyhow::Result<()> {
+    pub(crate) fn write_at(&mut self, offset: u64, buf: &[u8]) -> Result<(), WriteError> {
+        self.ensure_unfrozen(offset, buf.len() as u64)?;
         let mut cursor = Cursor::new(buf);
 
         let (chunk_size, block_size, page_size, pages_per_block) = (
@@ -99,9 +132,10 @@ impl CacheSlice {
             self.pages_per_block(),
         );
 
-        let span = ChunkSpan::new(0, offset, buf.len() as u64);
+        let (span, now) = (ChunkSpan::new(0, offset, buf.len() as u64), Instant::now());
 
         for block_span in span.split_into::<BlockTag>(chunk_size, block_size, true) {
+            self.last_write[block_span.index.as_usize()] = Some(now);
             let page_spans = block_span.split_into::<PageTag>(block_size, page_size, true);
 
             for page_span in page_spans {
@@ -113,9 +147,11 @@ impl CacheSlice {
                     (page_span.offset + page_span.len).as_usize(),
                 );
 
+                let version = self.version;
                 let slice = {
                     let page = self.ensure_page_mut(flat_idx, page_size as usize);
-                    page.write_slice(page_span.offset.as_usize(), end)?
+                    page.write_slice(page_span.offset.as_usize(), end)
+                        .ok_or(WriteError::Frozen { version })?
                 };
 
                 cursor.read_exact(slice)?;
@@ -127,20 +163,29 @@ impl CacheSlice {
 
     /// Append a buffer behind the latest page. The buffer length must not exceed the remaining length.
     #[tracing::instrument(level = "trace", skip(self, buf), fields(len = buf.len()))]
-    pub(crate) fn append(&mut self, buf: &[u8]) -> anyhow::Result<()> {
+    pub(crate) fn append(&mut self, buf: &[u8]) -> Result<(), WriteError> {
         let (max_len, next_len) = (self.config.layout.chunk_size, self.len + buf.len() as u64);
 
-        anyhow::ensure!(next_len <= max_len, "append exceeds chunk size");
+        if next_len > max_len {
+            return Err(WriteError::ExceedsChunk);
+        }
+        self.ensure_unfrozen(self.len, buf.len() as u64)?;
 
         let (page_size, mut position, mut cursor) =
             (self.config.page_size as usize, 0usize, Cursor::new(buf));
+        let (pages_per_block, now) = (self.pages_per_block(), Instant::now());
 
         while position < buf.len() {
             let (flat_idx, within_page) = self.next_write_slot_flat();
+            self.last_write[flat_idx / pages_per_block] = Some(now);
 
+            let version = self.version;
             let read = {
                 let page = self.ensure_page_mut(flat_idx, page_size);
-                cursor.read(page.write_slice(within_page, page_size)?)?
+                let slice = page
+                    .write_slice(within_page, page_size)
+                    .ok_or(WriteError::Frozen { version })?;
+                cursor.read(slice)?
             };
 
             if read == 0 {
@@ -176,13 +221,20 @@ impl CacheSlice {
         }
     }
 
+    /// Current freeze version; changes whenever pages are frozen.
+    pub(crate) fn version(&self) -> u64 {
+        self.version
+    }
+
     pub(crate) fn freeze(&mut self) {
+        self.version += 1;
         for page in self.pages.iter_mut().flatten() {
             page.freeze();
         }
     }
 
     pub(crate) fn freeze_blocks(&mut self, start: usize, end: usize) {
+        self.version += 1;
         let pages_per_block = self.pages_per_block();
 
         let (start_idx, end_idx) = (start * pages_per_block, end * pages_per_block);
@@ -198,6 +250,7 @@ impl CacheSlice {
 
         for block_idx in idx {
             let range = self.block_page_range(block_idx, pages_per_block);
+            self.last_write[block_idx] = None;
 
             for page in self.pages[range].iter_mut() {
                 if page.is_some() {
@@ -216,10 +269,31 @@ impl CacheSlice {
         for page in &mut self.pages {
             *page = None;
         }
+        self.last_write.fill(None);
         self.alloc_bytes = 0;
         freed
     }
 
+    /// Shorten the slice to `len` bytes, releasing every block that lies wholly past it.
+    pub(crate) fn truncate(&mut self, len: u64) -> u64 {
+        if len >= self.len {
+            return 0;
+        }
+
+        let block_size = self.config.layout.block_size as u64;
+        let (first, last) = (
+            len.div_ceil(block_size) as usize,
+            self.len.div_ceil(block_size) as usize,
+        );
+        self.len = len;
+        self.release_block((first..last).collect())
+    }
+
+    /// Last time block `idx` was written, or `None` if it holds no cached data.
+    pub(crate) fn block_last_write(&self, idx: usize) -> Option<Instant> {
+        self.last_write.get(idx).copied().flatten()
+    }
+
     pub(crate) fn collect_pages(
         &mut self,
         start: usize,
@@ -269,6 +343,28 @@ impl CacheSlice {
         Ok(out)
     }
 
+    /// Fails with `WriteError::Frozen` if any existing page in `[offset, offset + len)`
+    /// is frozen, so a write is either applied in full or not at all.
+    fn ensure_unfrozen(&self, offset: u64, len: u64) -> Result<(), WriteError> {
+        if len == 0 {
+            return Ok(());
+        }
+        // Pages are laid out block-major and blocks are a whole number of pages,
+        // so the flat page index is simply the offset divided by the page size.
+        let page_size = self.config.page_size as u64;
+        let (first, last) = (
+            (offset / page_size) as usize,
+            ((offset + len - 1) / page_size) as usize,
+        );
+        let mut pages = self.pages.iter().take(last + 1).skip(first).flatten();
+        if pages.any(Page::is_frozen) {
+            return Err(WriteError::Frozen {
+                version: self.version,
+            });
+        }
+        Ok(())
+    }
+
     fn next_write_slot_flat(&mut self) -> (usize, usize) {
         let (page_size, block_size, pages_per_block, total) = (
             self.config.page_size as usize,
@@ -337,13 +433,11 @@ impl Page {
         }
     }
 
-    fn write_slice(&mut self, start: usize, end: usize) -> anyhow::Result<&mut [u8]> {
+    /// Mutable view of `[start, end)`, or `None` if the page has been frozen.
+    fn write_slice(&mut self, start: usize, end: usize) -> Option<&mut [u8]> {
         match &mut self.data {
-            PageBuf::Mutable(buf) => Ok(&mut buf[start..end]),
-            // Return an error: a frozen page means the caller raced with a freeze.
-            PageBuf::Frozen(_) => {
-                anyhow::bail!("attempt to write to frozen page");
-            }
+            PageBuf::Mutable(buf) => Some(&mut buf[start..end]),
+            PageBuf::Frozen(_) => None,
         }
     }
 
@@ -354,6 +448,10 @@ impl Page {
         }
     }
 
+    fn is_frozen(&self) -> bool {
+        matches!(self.data, PageBuf::Frozen(_))
+    }
+
     fn bytes(&self) -> anyhow::Result<Bytes> {
         match &self.data {
             PageBuf::Frozen(buf) => Ok(buf.clone()),
@@ -372,7 +470,7 @@ mod tests {
     use std::sync::Arc;
 
     use crate::chunk::ChunkLayout;
-    use crate::vfs::cache::page::CacheSlice;
+    use crate::vfs::cache::page::{CacheSlice, WriteError};
     use crate::vfs::config::WriteConfig;
     use bytes::Bytes;
 
@@ -574,4 +672,44 @@ mod tests {
         assert_eq!(slice.len, total as u64);
         assert_eq!(collect_all(&mut slice), overwrite);
     }
+
+    #[test]
+    fn test_block_last_write_tracks_writes_and_releases() {
+        let (mut slice, data) = (CacheSlice::new(config()), patterned(10 * 1024, 3));
+
+        slice.append(&data[..4 * 1024]).unwrap();
+        let first = slice.block_last_write(0).unwrap();
+        assert!(slice.block_last_write(1).is_none());
+
+        slice.append(&data[4 * 1024..]).unwrap();
+        let second = slice.block_last_write(1).unwrap();
+        assert!(first <= second);
+        assert_eq!(slice.block_last_write(0), Some(first));
+
+        slice.freeze_blocks(0, 2);
+        slice.release_block(vec![0]);
+        assert!(slice.block_last_write(0).is_none());
+        assert_eq!(slice.block_last_write(1), Some(second));
+        assert_eq!(slice.alloc_bytes(), 6 * 1024);
+    }
+
+    #[test]
+    fn test_write_into_frozen_pages_is_rejected_without_partial_write() {
+        let (mut slice, data) = (CacheSlice::new(config()), patterned(6 * 1024, 5));
+        slice.append(&data).unwrap();
+
+        slice.freeze_blocks(0, 1);
+        assert_eq!(slice.version(), 1);
+
+        // Spans the frozen block 0 and the still-mutable block 1.
+        let patch = patterned(2 * 1024, 9);
+        let err = slice.write_at(3 * 1024, &patch).unwrap_err();
+        assert!(matches!(err, WriteError::Frozen { version: 1 }));
+
+        slice.freeze();
+        let err = slice.append(b"tail").unwrap_err();
+        assert!(matches!(err, WriteError::Frozen { version: 2 }));
+        assert_eq!(slice.len(), data.len() as u64);
+        assert_eq!(collect_all(&mut slice), data);
+    }
 }
@@ -1,4 +1,5 @@
 use crate::chunk::ChunkLayout;
+use crate::vfs::error::LayoutError;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -7,6 +8,10 @@ pub const DEFAULT_MAX_AHEAD: u64 = 32 * 1024 * 1024; // 32MB
 pub const DEFAULT_BUFFER_SIZE: u64 = 1024 * 1024 * 300; // 300MB
 pub const DEFAULT_WRITE_BUFFER_SIZE: u64 = 1024 * 1024 * 300; // 300MB
 pub const DEFAULT_FLUSH_ALL_INTERVAL: Duration = Duration::from_secs(5);
+pub const DEFAULT_WRITE_CACHE_BUDGET: u64 = 0; // disabled
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+pub const DEFAULT_FLUSH_MAX_AGE: Duration = Duration::from_secs(5);
+pub const DEFAULT_FLUSH_DIRTY_THRESHOLD: u64 = 0; // disabled
 
 #[derive(Clone)]
 pub struct ReadConfig {
@@ -64,6 +69,21 @@ pub struct WriteConfig {
     /// Default: 300MB. Set to 0 to disable throttling.
     pub buffer_size: u64,
     pub flush_all_interval: Duration,
+    /// Budget for the pages cached by all file writers together.
+    /// A writer over it uploads its least-recently-written full blocks first
+    /// and waits for its uploads to release them. Mutable pages are never evicted.
+    /// Default: 0 (disabled).
+    pub cache_budget: u64,
+    /// How often the per-file background flusher scans for slices to upload.
+    /// Default: 100ms.
+    pub flush_interval: Duration,
+    /// Slices older than this are frozen and uploaded by the background
+    /// flusher even if they are still being written. Default: 5s.
+    pub flush_max_age: Duration,
+    /// Slices holding at least this many bytes that are not yet uploaded are
+    /// frozen and uploaded by the background flusher.
+    /// Default: 0 (disabled).
+    pub flush_dirty_threshold: u64,
 }
 
 impl Default for WriteConfig {
@@ -73,6 +93,10 @@ impl Default for WriteConfig {
             page_size: DEFAULT_PAGE_SIZE,
             buffer_size: DEFAULT_WRITE_BUFFER_SIZE,
             flush_all_interval: DEFAULT_FLUSH_ALL_INTERVAL,
+            cache_budget: DEFAULT_WRITE_CACHE_BUDGET,
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            flush_max_age: DEFAULT_FLUSH_MAX_AGE,
+            flush_dirty_threshold: DEFAULT_FLUSH_DIRTY_THRESHOLD,
         }
     }
 }
@@ -103,6 +127,67 @@ impl WriteConfig {
             ..self
         }
     }
+
+    pub fn cache_budget(self, cache_budget: u64) -> Self {
+        Self {
+            cache_budget,
+            ..self
+        }
+    }
+
+    pub fn flush_interval(self, flush_interval: Duration) -> Self {
+        Self {
+            flush_interval,
+            ..self
+        }
+    }
+
+    pub fn flush_max_age(self, flush_max_age: Duration) -> Self {
+        Self {
+            flush_max_age,
+            ..self
+        }
+    }
+
+    pub fn flush_dirty_threshold(self, flush_dirty_threshold: u64) -> Self {
+        Self {
+            flush_dirty_threshold,
+            ..self
+        }
+    }
+
+    /// Check the size invariants the write cache relies on: all sizes are
+    /// non-zero, a block is made of whole pages and a chunk of whole blocks.
+    pub fn validate(&self) -> Result<(), LayoutError> {
+        let ChunkLayout {
+            chunk_size,
+            block_size,
+        } = self.layout;
+        let page_size = self.page_size;
+
+        if chunk_size == 0 {
+            return Err(LayoutError::ZeroSize("chunk size"));
+        }
+        if block_size == 0 {
+            return Err(LayoutError::ZeroSize("block size"));
+        }
+        if page_size == 0 {
+            return Err(LayoutError::ZeroSize("page size"));
+        }
+        if !block_size.is_multiple_of(page_size) {
+            return Err(LayoutError::BlockNotPageMultiple {
+                block_size,
+                page_size,
+            });
+        }
+        if !chunk_size.is_multiple_of(block_size as u64) {
+            return Err(LayoutError::ChunkNotBlockMultiple {
+                chunk_size,
+                block_size,
+            });
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone, Default)]
@@ -139,3 +224,66 @@ impl VFSConfig {
         Self { read, write }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(chunk_size: u64, block_size: u32) -> ChunkLayout {
+        ChunkLayout {
+            chunk_size,
+            block_size,
+        }
+    }
+
+    #[test]
+    fn default_write_config_is_valid() {
+        assert_eq!(WriteConfig::default().validate(), Ok(()));
+        assert_eq!(
+            VFSConfig::new(layout(1 << 20, 4096)).write.validate(),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn zero_sizes_are_rejected() {
+        assert_eq!(
+            WriteConfig::new(layout(0, 4096)).validate(),
+            Err(LayoutError::ZeroSize("chunk size"))
+        );
+        assert_eq!(
+            WriteConfig::new(layout(1 << 20, 0)).validate(),
+            Err(LayoutError::ZeroSize("block size"))
+        );
+        assert_eq!(
+            WriteConfig::new(layout(1 << 20, 4096))
+                .page_size(0)
+                .validate(),
+            Err(LayoutError::ZeroSize("page size"))
+        );
+    }
+
+    #[test]
+    fn block_must_be_a_multiple_of_page() {
+        let config = WriteConfig::new(layout(1 << 20, 6 * 1024)).page_size(4096);
+        assert_eq!(
+            config.validate(),
+            Err(LayoutError::BlockNotPageMultiple {
+                block_size: 6 * 1024,
+                page_size: 4096,
+            })
+        );
+    }
+
+    #[test]
+    fn chunk_must_be_a_multiple_of_block() {
+        let config = WriteConfig::new(layout(10 * 1024, 4096)).page_size(4096);
+        assert_eq!(
+            config.validate(),
+            Err(LayoutError::ChunkNotBlockMultiple {
+                chunk_size: 10 * 1024,
+                block_size: 4096,
+            })
+        );
+    }
+}
@@ -40,6 +40,19 @@ impl From<&str> for PathHint {
     }
 }
 
+/// A chunk/block/page size combination the write path cannot work with.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    #[error("{0} must be greater than zero")]
+    ZeroSize(&'static str),
+
+    #[error("block size {block_size} is not a multiple of page size {page_size}")]
+    BlockNotPageMultiple { block_size: u32, page_size: u32 },
+
+    #[error("chunk size {chunk_size} is not a multiple of block size {block_size}")]
+    ChunkNotBlockMultiple { chunk_size: u64, block_size: u32 },
+}
+
 #[derive(Error, Debug)]
 pub enum VfsError {
     // Filesystem/path-related errors (often used with a path hint).
@@ -152,6 +165,9 @@ pub enum VfsError {
     #[error("argument list too long")]
     ArgumentListTooLong,
 
+    #[error("result out of range")]
+    OutOfRange,
+
     #[error("interrupted")]
     Interrupted,
 
@@ -173,6 +189,9 @@ pub enum VfsError {
     #[error("{0}")]
     Meta(#[from] MetaError),
 
+    #[error("invalid layout: {0}")]
+    InvalidLayout(#[from] LayoutError),
+
     #[error("other error")]
     Other,
 }
@@ -302,11 +321,13 @@ impl From<VfsError> for std::io::Error {
             VfsError::InvalidFilename => ErrorKind::InvalidFilename,
             VfsError::FilenameTooLong { .. } => ErrorKind::InvalidFilename,
             VfsError::ArgumentListTooLong => ErrorKind::ArgumentListTooLong,
+            VfsError::OutOfRange => ErrorKind::InvalidInput,
             VfsError::Interrupted => ErrorKind::Interrupted,
             VfsError::Unsupported => ErrorKind::Unsupported,
             VfsError::UnexpectedEof => ErrorKind::UnexpectedEof,
             VfsError::OutOfMemory => ErrorKind::OutOfMemory,
             VfsError::StaleNetworkFileHandle => ErrorKind::StaleNetworkFileHandle,
+            VfsError::InvalidLayout(_) => ErrorKind::InvalidInput,
             VfsError::Anyhow(_) | VfsError::Meta(_) | VfsError::Other => ErrorKind::Other,
         };
         std::io::Error::new(kind, value.to_string())
@@ -18,6 +18,11 @@ use tokio::sync::Mutex;
 // Re-export types from meta::store for convenience
 pub use crate::meta::store::{DirEntry, FileAttr, FileType};
 
+/// Longest accepted xattr name, as Linux `XATTR_NAME_MAX`.
+pub const XATTR_NAME_MAX: usize = 255;
+/// Largest accepted xattr value, as Linux `XATTR_SIZE_MAX`.
+pub const XATTR_SIZE_MAX: usize = 64 * 1024;
+
 /// Rename operation flags (similar to Linux renameat2 flags)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct RenameFlags {
@@ -83,6 +88,7 @@ struct VfsBackgroundTasks {
 
 use crate::vfs::Inode;
 use crate::vfs::backend::Backend;
+use crate::vfs::cache::CacheStats;
 use crate::vfs::config::VFSConfig;
 use crate::vfs::error::{PathHint, VfsError};
 use crate::vfs::handles::{DirHandle, FileHandle, HandleFlags};
@@ -332,11 +338,17 @@ where
     R: MetaStore + Send + Sync + 'static,
 {
     pub async fn new(layout: ChunkLayout, store: S, meta: R) -> Result<Self, VfsError> {
-        Self::with_meta_client_config(layout, store, meta, MetaClientConfig::default()).await
+        Self::with_meta_client_config(
+            VFSConfig::new(layout),
+            store,
+            meta,
+            MetaClientConfig::default(),
+        )
+        .await
     }
 
     pub(crate) async fn with_meta_client_config(
-        layout: ChunkLayout,
+        vfs_config: VFSConfig,
         store: S,
         meta: R,
         config: MetaClientConfig,
@@ -355,7 +367,7 @@ where
 
         meta_client.initialize().await.map_err(VfsError::from)?;
 
-        Self::with_meta_layer_with_compact_config(layout, store, meta_client, config.compact)
+        Self::with_meta_layer_with_configs(vfs_config, store, meta_client, config.compact)
     }
 }
 
@@ -370,30 +382,37 @@ where
         meta_layer: Arc<MetaClient<R>>,
         compact_config: CompactConfig,
     ) -> Result<Self, VfsError> {
+        Self::with_meta_layer_with_configs(
+            VFSConfig::new(layout),
+            store,
+            meta_layer,
+            compact_config,
+        )
+    }
+
+    pub(crate) fn with_meta_layer_with_configs(
+        vfs_config: VFSConfig,
+        store: Arc<S>,
+        meta_layer: Arc<MetaClient<R>>,
+        compact_config: CompactConfig,
+    ) -> Result<Self, VfsError> {
+        // reject a bad layout before the background tasks start using it
+        vfs_config.write.validate()?;
+        let layout = vfs_config.write.layout;
         let enabled = !meta_layer.options().no_background_jobs;
         let bg_config = VfsBackgroundConfig::from_compact_config(&layout, compact_config, enabled);
         let background_tasks =
             Self::start_background_tasks(&meta_layer, Arc::clone(&store), layout, bg_config);
 
-        Self::from_components_with_background(
-            VFSConfig::new(layout),
-            store,
-            meta_layer,
-            background_tasks,
-        )
+        Self::from_components_with_background(vfs_config, store, meta_layer, background_tasks)
     }
 
     pub(crate) fn with_meta_layer_with_default_background(
-        layout: ChunkLayout,
+        vfs_config: VFSConfig,
         store: Arc<S>,
         meta_layer: Arc<MetaClient<R>>,
     ) -> Result<Self, VfsError> {
-        Self::with_meta_layer_with_compact_config(
-            layout,
-            store,
-            meta_layer,
-            CompactConfig::default(),
-        )
+        Self::with_meta_layer_with_configs(vfs_config, store, meta_layer, CompactConfig::default())
     }
 
     /// Start background compaction and gc tasks
@@ -448,6 +467,7 @@ where
         meta_layer: Arc<M>,
         background_tasks: Option<VfsBackgroundTasks>,
     ) -> Result<Self, VfsError> {
+        config.write.validate()?;
         let layout = config.write.layout;
         let root_ino = meta_layer.root_ino();
         let backend = Arc::new(Backend::new(store.clone(), meta_layer.clone()));
@@ -1518,6 +1538,20 @@ where
         self.truncate_inode(ino, size).await
     }
 
+    /// Upload and commit any buffered writes of `ino`, returning once the object store and
+    /// meta store have acknowledged them. A file without pending writes returns immediately.
+    pub async fn flush_inode(&self, ino: i64) -> Result<(), VfsError> {
+        self.state.writer.flush_required(ino as u64).await?;
+        Ok(())
+    }
+
+    /// Upload and commit buffered writes of every open file. Called on shutdown so that
+    /// nothing left in the write cache is lost.
+    pub async fn flush_all(&self) -> Result<(), VfsError> {
+        self.state.writer.flush_all().await?;
+        Ok(())
+    }
+
     /// Truncate/extend file size by inode (metadata only; holes are read as zeros).
     /// Shrinking does not eagerly reclaim block data.
     pub async fn truncate_inode(&self, ino: i64, size: u64) -> Result<(), VfsError> {
@@ -1527,6 +1561,7 @@ where
             guards.push(handle.lock_write().await);
         }
 
+        self.state.writer.truncate(ino as u64, size).await;
         self.state
             .writer
             .flush_required(ino as u64)
@@ -1576,6 +1611,7 @@ where
                 guards.push(handle.lock_write().await);
             }
 
+            self.state.writer.truncate(ino as u64, size).await;
             self.state
                 .writer
                 .flush_required(ino as u64)
@@ -1845,6 +1881,56 @@ where
             .await
     }
 
+    /// Replace the contents of `dst_ino` with a copy-on-write clone of `src_ino`.
+    ///
+    /// Both files are flushed first so the clone sees every committed slice; the
+    /// destination then references the source's block objects and only diverges
+    /// once either side is written. Returns the size of the clone.
+    pub async fn clone_inode(&self, src_ino: i64, dst_ino: i64) -> Result<u64, VfsError> {
+        if src_ino == dst_ino {
+            return Err(VfsError::InvalidInput);
+        }
+
+        let mut locked = Vec::new();
+        let mut unique = BTreeMap::new();
+        for handle in self.file_handles_for_inode(src_ino) {
+            unique.insert(handle.fh, handle);
+        }
+        for handle in self.file_handles_for_inode(dst_ino) {
+            unique.insert(handle.fh, handle);
+        }
+        for handle in unique.into_values() {
+            locked.push(handle.lock_write().await);
+        }
+
+        for ino in [src_ino, dst_ino] {
+            self.state
+                .writer
+                .flush_required(ino as u64)
+                .await
+                .map_err(|_| VfsError::Other)?;
+        }
+
+        let size = self.meta_clone_file(src_ino, dst_ino).await?;
+
+        self.state.reader.invalidate_all(dst_ino as u64).await;
+        self.state.writer.clear(dst_ino as u64).await;
+
+        let guard = self
+            .lock_inode(dst_ino)
+            .or_insert_with(|| Inode::new(dst_ino, size));
+        guard.update_size(size);
+
+        if let Some(mut attr) = self.state.handles.attr_for_inode(dst_ino) {
+            attr.size = size;
+            self.state.handles.update_attr_for_inode(dst_ino, &attr);
+        }
+
+        self.state.modified.touch(dst_ino).await;
+        drop(locked);
+        Ok(size)
+    }
+
     /// Allocate a per-file handle, returning the opaque fh id.
     #[tracing::instrument(level = "trace", skip(self), fields(ino, read, write))]
     pub async fn open(
@@ -2087,6 +2173,7 @@ where
     }
 
     /// Set xattr for a given inode.
+    /// Names must be 1..=`XATTR_NAME_MAX` bytes and values at most `XATTR_SIZE_MAX` bytes.
     pub async fn set_xattr_ino(
         &self,
         inode: i64,
@@ -2094,6 +2181,15 @@ where
         value: &[u8],
         flags: u32,
     ) -> Result<(), VfsError> {
+        if name.is_empty() {
+            return Err(VfsError::InvalidInput);
+        }
+        if name.len() > XATTR_NAME_MAX {
+            return Err(VfsError::OutOfRange);
+        }
+        if value.len() > XATTR_SIZE_MAX {
+            return Err(VfsError::ArgumentListTooLong);
+        }
         self.meta_set_xattr(inode, name, value, flags).await
     }
 
@@ -2180,6 +2276,17 @@ where
         self.meta_stat_fs().await
     }
 
+    /// Chunk layout shared by the reader, writer and compaction paths.
+    pub fn layout(&self) -> ChunkLayout {
+        self.core.layout
+    }
+
+    /// Get write cache usage across all open files and the block store cache counters.
+    pub async fn cache_stats(&self) -> CacheStats {
+        let stats = self.state.writer.cache_stats().await;
+        stats.with_block_cache(self.core.backend.store().cache_stats())
+    }
+
     async fn ensure_inode_registered(&self, ino: i64) -> Result<Arc<Inode>, VfsError> {
         // Fast path to check whether there is an existing inode.
         if let Some(inode) = self.state.inodes.get(&ino) {
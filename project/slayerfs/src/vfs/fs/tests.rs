@@ -1188,4 +1188,17 @@ mod permission_tests {
         assert_eq!(stat.uid, 1234);
         assert_eq!(stat.gid, 5678);
     }
+
+    #[tokio::test]
+    async fn test_set_xattr_rejects_bad_names() {
+        let fs = new_test_vfs().await;
+        let ino = fs.create_file("/xattr.txt").await.unwrap();
+
+        let err = fs.set_xattr_ino(ino, "", b"v", 0).await.unwrap_err();
+        assert!(matches!(err, crate::vfs::error::VfsError::InvalidInput));
+
+        let long = "u".repeat(crate::vfs::fs::XATTR_NAME_MAX + 1);
+        let err = fs.set_xattr_ino(ino, &long, b"v", 0).await.unwrap_err();
+        assert!(matches!(err, crate::vfs::error::VfsError::OutOfRange));
+    }
 }
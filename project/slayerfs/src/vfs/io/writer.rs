@@ -18,8 +18,9 @@ use crate::meta::{MetaLayer, SLICE_ID_KEY};
 use crate::utils::{NumCastExt, UsageGuard};
 use crate::vfs::Inode;
 use crate::vfs::backend::Backend;
-use crate::vfs::cache::page::CacheSlice;
+use crate::vfs::cache::CacheStats;
 use crate::vfs::cache::page::WriteAction as PageWriteAction;
+use crate::vfs::cache::page::{CacheSlice, CacheSliceStats, WriteError};
 use crate::vfs::chunk_id_for;
 use crate::vfs::config::WriteConfig;
 use crate::vfs::extract_ino_and_chunk_index;
@@ -36,7 +37,6 @@ use tokio::sync::{Mutex, Notify};
 use tokio::time::{interval, timeout};
 use tracing::{Instrument, warn};
 
-const FLUSH_DURATION: Duration = Duration::from_secs(5);
 const COMMIT_WAIT_SLICE: Duration = Duration::from_millis(100);
 const FLUSH_WAIT: Duration = Duration::from_secs(3);
 const FLUSH_DEADLINE: Duration = Duration::from_secs(300);
@@ -47,6 +47,7 @@ const COMMIT_RETRY_MAX_MS: u64 = 2000;
 const MAX_UNFLUSHED_SLICES: usize = 3;
 const MAX_SLICES_THRESHOLD: usize = 800;
 const WRITE_MAX_WAIT: Duration = Duration::from_secs(30);
+const CACHE_BUDGET_WAIT: Duration = Duration::from_millis(100);
 
 fn commit_retry_backoff(failures: u32) -> Duration {
     let exp = failures.saturating_sub(1).min(16);
@@ -153,7 +154,7 @@ impl SliceState {
         offset: u64,
         buf: &[u8],
         action: PageWriteAction,
-    ) -> anyhow::Result<()> {
+    ) -> Result<(), WriteError> {
         self.data.write(offset - self.offset, buf, action)?;
         self.last_mod = Instant::now();
         Ok(())
@@ -176,6 +177,33 @@ impl SliceState {
         }
     }
 
+    /// Drop cached data past `chunk_end` (relative to the chunk start). Blocks already
+    /// uploaded or being uploaded are kept; the metadata truncate trims them.
+    fn truncate(&mut self, chunk_end: u64) {
+        let size = self.data.block_size() as u64;
+        let pending_end = self
+            .uploading
+            .map(|(_, end)| end as u64 * size)
+            .unwrap_or(self.uploaded)
+            .max(self.uploaded);
+
+        let keep = chunk_end.saturating_sub(self.offset).max(pending_end);
+        if keep >= self.data.len() {
+            return;
+        }
+        self.data.truncate(keep);
+        self.usage.update_bytes(self.data.alloc_bytes());
+
+        if matches!(self.state, SliceStatus::Readonly)
+            && self.uploading.is_none()
+            && !self.has_idle_block()
+        {
+            self.state = SliceStatus::Uploaded;
+            self.err = None;
+            self.notify.notify_waiters();
+        }
+    }
+
     pub fn idx_need_upload(&self) -> (usize, usize) {
         let size = self.data.block_size() as u64;
         let start = (self.uploaded / size) as usize;
@@ -240,11 +268,15 @@ where
 
     fn try_write(&self, offset: u64, buf: &[u8]) -> anyhow::Result<bool> {
         let wrote = self.with_mut(|s| match s.can_write(offset, buf.len()) {
-            Some(action) => {
-                s.write(offset, buf, action)?;
-                s.usage.update_bytes(s.data.alloc_bytes());
-                Ok::<bool, anyhow::Error>(true)
-            }
+            Some(action) => match s.write(offset, buf, action) {
+                Ok(()) => {
+                    s.usage.update_bytes(s.data.alloc_bytes());
+                    Ok::<bool, anyhow::Error>(true)
+                }
+                // Nothing was written; the caller retries with another slice.
+                Err(WriteError::Frozen { .. }) => Ok(false),
+                Err(err) => Err(err.into()),
+            },
             None => Ok::<bool, anyhow::Error>(false),
         })?;
 
@@ -510,8 +542,9 @@ where
         // There is a potential race condition in the time window between `find_slice_or_create` and `try_append`.
         // `find_slice_or_create` checks and returns a slice that can be appended, but after it selects the slice,
         // it releases the lock. `auto_flush` and `commit_chunk` can freeze a slice without holding the lock,
-        // so when handle trying appending buf, the slice may have become readonly. This is highly unlikely to happen,
-        // therefore, it is ok to retry until success.
+        // so when handle trying appending buf, the slice may have become readonly, or some of its pages may have been
+        // frozen for upload (`WriteError::Frozen`). Either way nothing has been written yet and `try_write` returns
+        // false. This is highly unlikely to happen, therefore, it is ok to retry until success.
         let mut failed_cnt = 0;
 
         loop {
@@ -689,6 +722,7 @@ where
     #[tracing::instrument(level = "trace", skip(self, buf), fields(offset, len = buf.len()))]
     pub(crate) async fn write_at(&self, offset: u64, buf: &[u8]) -> anyhow::Result<usize> {
         self.back_pressure().await?;
+        self.wait_cache_budget().await?;
         let mut guard = self.shared.inner.lock().await;
 
         // Wait for any ongoing flush to finish. This serializes writes with flush().
@@ -737,9 +771,74 @@ where
         if new_len > self.shared.inode.file_size() {
             self.shared.inode.update_size(new_len);
         }
+
         Ok(buf.len())
     }
 
+    /// Keep the pages cached by all writers under `cache_budget`. While the shared usage is over
+    /// it, this writer uploads its full blocks, least-recently-written first, and waits for its
+    /// uploads to release them. Mutable pages are never evicted, so a writer with nothing to
+    /// upload goes ahead rather than waiting on the others.
+    async fn wait_cache_budget(&self) -> anyhow::Result<()> {
+        let budget = self.shared.config.cache_budget;
+        if budget == 0 {
+            return Ok(());
+        }
+
+        let started = Instant::now();
+        loop {
+            let usage = self.shared.buffer_usage.load(Ordering::Relaxed);
+            if usage <= budget {
+                return Ok(());
+            }
+            if started.elapsed() >= WRITE_MAX_WAIT {
+                return Err(anyhow::anyhow!(
+                    "Timeout waiting for write cache after {:?}. Current usage: {usage} bytes, budget: {budget} bytes",
+                    started.elapsed(),
+                ));
+            }
+
+            let slices: Vec<Arc<ParkingMutex<SliceState>>> = {
+                let guard = self.shared.inner.lock().await;
+                guard
+                    .chunks
+                    .values()
+                    .flat_map(|chunk| chunk.slices.iter().cloned())
+                    .collect()
+            };
+
+            let (mut in_flight, mut candidates) = (None, Vec::new());
+            for slice in slices {
+                let s = slice.lock();
+                if s.uploading.is_some() {
+                    in_flight.get_or_insert_with(|| s.notify.clone());
+                } else if s.has_idle_block() {
+                    // Blocks are uploaded in order, so the slice's next block decides its turn.
+                    let next = (s.uploaded / s.data.block_size() as u64) as usize;
+                    let last_write = s.data.block_last_write(next);
+                    candidates.push((last_write, s.data.alloc_bytes(), slice.clone()));
+                }
+            }
+
+            candidates.sort_by_key(|(last_write, _, _)| *last_write);
+            let mut excess = usage - budget;
+            for (_, bytes, slice) in candidates {
+                if excess == 0 {
+                    break;
+                }
+                excess = excess.saturating_sub(bytes);
+                in_flight.get_or_insert_with(|| slice.lock().notify.clone());
+                Self::spawn_flush_slice(self.shared.clone(), slice);
+            }
+
+            let Some(notify) = in_flight else {
+                return Ok(());
+            };
+            // Uploads wake the slice's waiters without queueing, so recheck on a timer as well.
+            let _ = timeout(CACHE_BUDGET_WAIT, notify.notified()).await;
+        }
+    }
+
     // Flush: freeze all slices, upload them, and wait for commit threads to drain.
     // This blocks new writes until flushing completes (flush_waiting gate).
     #[tracing::instrument(level = "trace", skip(self))]
@@ -831,11 +930,40 @@ where
         }
     }
 
+    /// Release cached blocks past `size` so a shrinking truncate does not upload them.
+    pub(crate) async fn truncate(&self, size: u64) {
+        let chunk_size = self.shared.config.layout.chunk_size;
+        let guard = self.shared.inner.lock().await;
+        for (cid, chunk) in &guard.chunks {
+            let (_, index) = extract_ino_and_chunk_index(*cid);
+            let end = size.saturating_sub(index * chunk_size);
+            for slice in &chunk.slices {
+                slice.lock().truncate(end);
+            }
+        }
+    }
+
     pub(crate) async fn has_pending(&self) -> bool {
         let guard = self.shared.inner.lock().await;
         guard.has_chunks()
     }
 
+    pub(crate) async fn slice_stats(&self) -> Vec<CacheSliceStats> {
+        let slices: Vec<Arc<ParkingMutex<SliceState>>> = {
+            let guard = self.shared.inner.lock().await;
+            guard
+                .chunks
+                .values()
+                .flat_map(|chunk| chunk.slices.iter().cloned())
+                .collect()
+        };
+
+        slices
+            .iter()
+            .map(|slice| slice.lock().data.stats())
+            .collect()
+    }
+
     /// Spawn a background task to upload a frozen slice's data.
     /// Metadata commit is handled separately by commit_chunk.
     fn spawn_flush_slice(shared: Arc<Shared<B, M>>, slice: Arc<ParkingMutex<SliceState>>) {
@@ -1000,7 +1128,7 @@ where
                 }
 
                 // If the slice is too old, it will be frozen and flushed.
-                if !runtime.frozen && runtime.started.elapsed() > FLUSH_DURATION * 2 {
+                if !runtime.frozen && runtime.started.elapsed() > shared.config.flush_max_age * 2 {
                     let _span = tracing::trace_span!("commit_chunk.freeze").entered();
                     let froze = SliceHandle {
                         slice: &slice,
@@ -1129,8 +1257,10 @@ where
         }
     }
 
-    /// The automatic flush loop: periodically freezes older/idle slices to reduce memory
-    /// usage and ensure progress. It does not commit metadata directly.
+    /// The automatic flush loop: every `flush_interval` it freezes slices that are older than
+    /// `flush_max_age`, idle, or hold at least `flush_dirty_threshold` bytes not yet uploaded,
+    /// so slow writers get persisted without an explicit flush. It does not commit metadata
+    /// directly; `commit_chunk` does that once the upload finishes.
     /// Use `Weak` to stop it when the `FileWriter` was dropped.
     async fn auto_flush(shared: Weak<Shared<B, M>>) {
         let idle = Duration::from_secs(1);
@@ -1139,6 +1269,11 @@ where
             let Some(shared) = shared.upgrade() else {
                 return;
             };
+            let (interval, max_age, dirty_threshold) = (
+                shared.config.flush_interval,
+                shared.config.flush_max_age,
+                shared.config.flush_dirty_threshold,
+            );
 
             let mut to_flush = Vec::new();
             {
@@ -1168,10 +1303,11 @@ where
                             shared: &shared,
                         };
 
-                        let (age, idle_time, writeable) = handle.with_ref(|s| {
+                        let (age, idle_time, dirty, writeable) = handle.with_ref(|s| {
                             (
                                 now.duration_since(s.started),
                                 now.duration_since(s.last_mod),
+                                s.data.len().saturating_sub(s.uploaded),
                                 matches!(s.state, SliceStatus::Writable),
                             )
                         });
@@ -1180,9 +1316,11 @@ where
                             continue;
                         }
 
-                        // age > FLUSH_DURATION means the slices are too old and (idle_time > idle && age > idle)
+                        // age > max_age means the slices are too old and (idle_time > idle && age > idle)
                         // represents it's been too long since last flushed.
-                        let mut should = age > FLUSH_DURATION || (idle_time > idle && age > idle);
+                        let mut should = age > max_age
+                            || (idle_time > idle && age > idle)
+                            || (dirty_threshold > 0 && dirty >= dirty_threshold);
                         if !should && too_many {
                             // idx <= half represents older slices.
                             if chunk_idx % 2 == pick_bit && idx <= half {
@@ -1201,7 +1339,7 @@ where
                 Self::spawn_flush_slice(shared.clone(), slice);
             }
 
-            tokio::time::sleep(Duration::from_millis(100)).await;
+            tokio::time::sleep(interval).await;
         }
     }
 }
@@ -1273,6 +1411,44 @@ where
         }
     }
 
+    /// Sum the page cache usage of every file writer.
+    pub(crate) async fn cache_stats(&self) -> CacheStats {
+        let writers: Vec<Arc<FileWriter<B, M>>> = self
+            .files
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect();
+
+        let mut stats = CacheStats::default();
+        for writer in writers {
+            for slice in writer.slice_stats().await {
+                stats.add_slice(slice);
+            }
+        }
+        stats
+    }
+
+    /// Flush every file writer and wait for their data to be committed.
+    /// Unlike the periodic `flush_once`, every writer is attempted and the first
+    /// error is returned, so callers shutting down know whether data was lost.
+    pub(crate) async fn flush_all(&self) -> anyhow::Result<()> {
+        let writers: Vec<Arc<FileWriter<B, M>>> = self
+            .files
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect();
+
+        let mut first_err = None;
+        for writer in writers {
+            if writer.has_pending().await
+                && let Err(err) = writer.flush().await
+            {
+                first_err.get_or_insert(err);
+            }
+        }
+        first_err.map_or(Ok(()), Err)
+    }
+
     /// Like `flush_if_exists` but propagates errors.  Used in truncate paths
     /// where a failed flush means data would be silently lost.
     pub(crate) async fn flush_required(&self, ino: u64) -> anyhow::Result<()> {
@@ -1285,6 +1461,13 @@ where
         Ok(())
     }
 
+    pub(crate) async fn truncate(&self, ino: u64, size: u64) {
+        let writer = self.files.get(&ino).map(|entry| entry.value().clone());
+        if let Some(writer) = writer {
+            writer.truncate(size).await;
+        }
+    }
+
     pub(crate) async fn clear(&self, ino: u64) {
         let writer = self.files.get(&ino).map(|entry| entry.value().clone());
         if let Some(writer) = writer {
@@ -1395,6 +1578,38 @@ mod tests {
         }
     }
 
+    /// Delays every upload, so writes outpace them.
+    struct SlowStore {
+        inner: InMemoryBlockStore,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl BlockStore for SlowStore {
+        async fn write_range(
+            &self,
+            key: BlockKey,
+            offset: u64,
+            data: &[u8],
+        ) -> anyhow::Result<u64> {
+            sleep(self.delay).await;
+            self.inner.write_range(key, offset, data).await
+        }
+
+        async fn read_range(
+            &self,
+            key: BlockKey,
+            offset: u64,
+            buf: &mut [u8],
+        ) -> anyhow::Result<()> {
+            self.inner.read_range(key, offset, buf).await
+        }
+
+        async fn delete_range(&self, key: BlockKey, block_count: u64) -> anyhow::Result<()> {
+            self.inner.delete_range(key, block_count).await
+        }
+    }
+
     #[test]
     fn test_idx_need_upload_writable_only_full_blocks() {
         let layout = ChunkLayout {
@@ -1537,6 +1752,66 @@ mod tests {
         assert_eq!(out, second);
     }
 
+    #[tokio::test]
+    async fn test_truncate_releases_blocks_past_new_end() {
+        let layout = ChunkLayout {
+            chunk_size: 64 * 1024,
+            block_size: 4 * 1024,
+        };
+        let store = Arc::new(BlockingStore::new(true));
+        let meta_handle = create_meta_store_from_url("sqlite::memory:").await.unwrap();
+        let meta_store = meta_handle.store();
+        let meta = meta_handle.layer();
+        let backend = Arc::new(Backend::new(store.clone(), meta.clone()));
+        let ino = meta
+            .create_file(1, "truncate.txt".to_string())
+            .await
+            .unwrap();
+        let inode = Inode::new(ino, 0);
+        let reader = Arc::new(DataReader::new(
+            Arc::new(ReadConfig::new(layout)),
+            backend.clone(),
+        ));
+        let usage = Arc::new(AtomicU64::new(0));
+        let writer = FileWriter::new(
+            inode.clone(),
+            test_config(layout),
+            backend.clone(),
+            reader,
+            usage.clone(),
+        );
+
+        // The first block starts uploading (and stays blocked); the next ones stay cached.
+        let block = layout.block_size as usize;
+        writer.write_at(0, &vec![7u8; block]).await.unwrap();
+        let slice = {
+            let guard = writer.shared.inner.lock().await;
+            guard.chunks.values().next().unwrap().slices[0].clone()
+        };
+        while slice.lock().uploading.is_none() {
+            tokio::task::yield_now().await;
+        }
+        writer
+            .write_at(block as u64, &vec![8u8; block + block / 2])
+            .await
+            .unwrap();
+        let before = writer.slice_stats().await;
+        assert_eq!(before[0].pages_used, 3);
+
+        writer.truncate(block as u64).await;
+        let after = writer.slice_stats().await;
+        assert_eq!(after[0].pages_used, 1);
+        assert_eq!(after[0].len, block as u64);
+        assert_eq!(usage.load(Ordering::Relaxed), after[0].alloc_bytes);
+
+        store.unblock();
+        writer.flush().await.unwrap();
+        let cid = chunk_id_for(inode.ino(), 0).unwrap();
+        let slices = meta_store.get_slices(cid).await.unwrap();
+        assert_eq!(slices.len(), 1);
+        assert_eq!(slices[0].length, layout.block_size as u64);
+    }
+
     #[tokio::test]
     async fn test_file_writer_cross_chunks() {
         let layout = ChunkLayout {
@@ -1639,6 +1914,112 @@ mod tests {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_concurrent_writes_and_freezes_lose_no_data() {
+        let layout = ChunkLayout {
+            chunk_size: 64 * 1024,
+            block_size: 4 * 1024,
+        };
+        let store = Arc::new(InMemoryBlockStore::new());
+        let meta_handle = create_meta_store_from_url("sqlite::memory:").await.unwrap();
+        let meta = meta_handle.layer();
+        let backend = Arc::new(Backend::new(store.clone(), meta.clone()));
+        let ino = meta
+            .create_file(1, "write_freeze_race.txt".to_string())
+            .await
+            .unwrap();
+        let inode = Inode::new(ino, 0);
+        let reader = Arc::new(DataReader::new(
+            Arc::new(ReadConfig::new(layout)),
+            backend.clone(),
+        ));
+        let writer = Arc::new(FileWriter::new(
+            inode.clone(),
+            test_config(layout),
+            backend.clone(),
+            reader,
+            Arc::new(AtomicU64::new(0)),
+        ));
+
+        let piece = 1000usize;
+        let pieces = layout.chunk_size as usize / piece;
+        let expected: Vec<u8> = (0..pieces * piece).map(|i| (i / piece) as u8).collect();
+
+        let done = Arc::new(AtomicBool::new(false));
+        let freezer = {
+            let (w, done) = (writer.clone(), done.clone());
+            tokio::spawn(async move {
+                while !done.load(Ordering::Acquire) {
+                    w.flush().await.unwrap();
+                    tokio::task::yield_now().await;
+                }
+            })
+        };
+
+        for (idx, chunk) in expected.chunks(piece).enumerate() {
+            writer.write_at((idx * piece) as u64, chunk).await.unwrap();
+            tokio::task::yield_now().await;
+        }
+        done.store(true, Ordering::Release);
+        freezer.await.unwrap();
+        writer.flush().await.unwrap();
+
+        let cid = chunk_id_for(inode.ino(), 0).unwrap();
+        let mut fetcher = DataFetcher::new(layout, cid, backend.as_ref());
+        fetcher.prepare_slices().await.unwrap();
+        let out = fetcher.read_at(0u64.into(), expected.len()).await.unwrap();
+        assert_eq!(out, expected);
+    }
+
+    #[tokio::test]
+    async fn test_auto_flush_commits_slices_over_dirty_threshold() {
+        let layout = ChunkLayout {
+            chunk_size: 16 * 1024,
+            block_size: 4 * 1024,
+        };
+        let store = Arc::new(InMemoryBlockStore::new());
+        let meta_handle = create_meta_store_from_url("sqlite::memory:").await.unwrap();
+        let meta_store = meta_handle.store();
+        let meta = meta_handle.layer();
+        let backend = Arc::new(Backend::new(store.clone(), meta.clone()));
+        let ino = meta
+            .create_file(1, "dirty_threshold.txt".to_string())
+            .await
+            .unwrap();
+        let inode = Inode::new(ino, 0);
+        let reader = Arc::new(DataReader::new(
+            Arc::new(ReadConfig::new(layout)),
+            backend.clone(),
+        ));
+        let config = Arc::new(
+            WriteConfig::new(layout)
+                .page_size(4 * 1024)
+                .flush_interval(Duration::from_millis(10))
+                .flush_max_age(Duration::from_secs(60))
+                .flush_dirty_threshold(1024),
+        );
+        let writer = FileWriter::new(
+            inode.clone(),
+            config,
+            backend.clone(),
+            reader,
+            Arc::new(AtomicU64::new(0)),
+        );
+
+        // Less than a block, so nothing is uploaded eagerly, and well below the idle timeout.
+        writer.write_at(0, &[5u8; 2048]).await.unwrap();
+
+        let cid = chunk_id_for(inode.ino(), 0).unwrap();
+        timeout(Duration::from_millis(800), async {
+            while meta_store.get_slices(cid).await.unwrap().is_empty() {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("dirty slice should be committed without an explicit flush");
+        assert!(inode.file_size() >= 2048);
+    }
+
     #[tokio::test]
     async fn test_background_flush_all_commits() {
         let layout = ChunkLayout {
@@ -1684,4 +2065,64 @@ mod tests {
         .await
         .expect("flush-all should commit");
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_cache_budget_bounds_usage_across_writers() {
+        const WRITERS: usize = 8;
+        const BLOCK: usize = 4 * 1024;
+        const BUDGET: u64 = 64 * 1024;
+
+        let layout = ChunkLayout {
+            chunk_size: 16 * 1024,
+            block_size: BLOCK as u32,
+        };
+        let store = Arc::new(SlowStore {
+            inner: InMemoryBlockStore::new(),
+            delay: Duration::from_millis(2),
+        });
+        let meta_handle = create_meta_store_from_url("sqlite::memory:").await.unwrap();
+        let meta = meta_handle.layer();
+        let backend = Arc::new(Backend::new(store, meta.clone()));
+        let reader = Arc::new(DataReader::new(
+            Arc::new(ReadConfig::new(layout)),
+            backend.clone(),
+        ));
+        let config = Arc::new(
+            WriteConfig::new(layout)
+                .page_size(4 * 1024)
+                .cache_budget(BUDGET),
+        );
+        let pool = Arc::new(DataWriter::new(config, backend.clone(), reader));
+
+        let peak = Arc::new(AtomicU64::new(0));
+        let mut tasks = Vec::new();
+        for i in 0..WRITERS {
+            let ino = meta
+                .create_file(1, format!("budget_{i}.txt"))
+                .await
+                .unwrap();
+            let writer = pool.ensure_file(Inode::new(ino, 0));
+            let (usage, peak) = (pool.buffer_usage.clone(), peak.clone());
+            tasks.push(tokio::spawn(async move {
+                for n in 0..64u64 {
+                    writer
+                        .write_at(n * BLOCK as u64, &[i as u8; BLOCK])
+                        .await
+                        .unwrap();
+                    peak.fetch_max(usage.load(Ordering::Relaxed), Ordering::Relaxed);
+                }
+                writer.flush().await.unwrap();
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        // A writer may land a block past the budget before it sees the usage, and keeps the
+        // block it is filling mutable.
+        let limit = BUDGET + (2 * WRITERS * BLOCK) as u64;
+        let peak = peak.load(Ordering::Relaxed);
+        assert!(peak <= limit, "peak usage {peak} exceeds {limit}");
+        assert_eq!(pool.buffer_usage.load(Ordering::Relaxed), 0);
+    }
 }
@@ -256,6 +256,13 @@ where
             .map_err(meta_err_to_vfs)
     }
 
+    pub(super) async fn meta_clone_file(&self, src: i64, dst: i64) -> Result<u64, VfsError> {
+        self.meta_layer()
+            .clone_file(src, dst)
+            .await
+            .map_err(meta_err_to_vfs)
+    }
+
     // ------------------------------------------------------------------
     // Symlink content
     // ------------------------------------------------------------------
@@ -14,9 +14,11 @@ use crate::meta::MetaStore;
 use crate::meta::factory::create_meta_store_from_url;
 use crate::meta::file_lock::{FileLockInfo, FileLockQuery, FileLockRange, FileLockType};
 use crate::meta::store::{
-    DirEntry, FileAttr, FileType, SetAttrFlags, SetAttrRequest, StatFsSnapshot,
+    DirEntry, DirEntryPlus, FileAttr, FileType, SetAttrFlags, SetAttrRequest, StatFsSnapshot,
 };
 use crate::meta::stores::DatabaseMetaStore;
+use crate::sdk_fs::FsStats;
+use crate::vfs::cache::CacheStats;
 use std::future::Future;
 use std::io;
 use std::path::Path;
@@ -93,6 +95,31 @@ impl<S: BlockStore + Send + Sync + 'static, M: MetaStore + 'static> VfsClient<S,
         self.fs.readdir(path).await
     }
 
+    /// Read up to `limit` directory entries named after `after`, in name order.
+    pub async fn readdir_page(
+        &self,
+        path: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> io::Result<Vec<DirEntry>> {
+        self.fs.readdir_page(path, after, limit).await
+    }
+
+    /// Read directory entries together with their attributes.
+    pub async fn readdir_plus(&self, path: &str) -> io::Result<Vec<DirEntryPlus>> {
+        self.fs.readdir_plus(path).await
+    }
+
+    /// Read one page of directory entries together with their attributes.
+    pub async fn readdir_page_plus(
+        &self,
+        path: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> io::Result<Vec<DirEntryPlus>> {
+        self.fs.readdir_page_plus(path, after, limit).await
+    }
+
     /// Get file/directory attributes.
     pub async fn stat(&self, path: &str) -> io::Result<FileAttr> {
         self.fs.stat(path).await.map(|fi| fi.attr().clone())
@@ -119,6 +146,55 @@ impl<S: BlockStore + Send + Sync + 'static, M: MetaStore + 'static> VfsClient<S,
             .await
     }
 
+    /// Clone `src` into `dst`, sharing blocks copy-on-write.
+    pub async fn clone_file(&self, src: &str, dst: &str) -> io::Result<u64> {
+        self.retry_on_deadlock(|| self.fs.clone_file(src, dst))
+            .await
+    }
+
+    /// Upload buffered writes of a file and wait for the object store to acknowledge them.
+    pub async fn flush(&self, path: &str) -> io::Result<()> {
+        self.fs.flush(path).await
+    }
+
+    /// Like `flush`; metadata is committed together with the data, so nothing else is pending.
+    pub async fn fsync(&self, path: &str) -> io::Result<()> {
+        self.fs.flush(path).await
+    }
+
+    /// Upload and commit buffered writes of every file.
+    pub async fn shutdown(&self) -> io::Result<()> {
+        self.fs.shutdown().await
+    }
+
+    /// Set an extended attribute.
+    pub async fn set_xattr(
+        &self,
+        path: &str,
+        name: &str,
+        value: &[u8],
+        flags: u32,
+    ) -> io::Result<()> {
+        self.retry_on_deadlock(|| self.fs.set_xattr(path, name, value, flags))
+            .await
+    }
+
+    /// Get an extended attribute value.
+    pub async fn get_xattr(&self, path: &str, name: &str) -> io::Result<Option<Vec<u8>>> {
+        self.fs.get_xattr(path, name).await
+    }
+
+    /// List extended attribute names.
+    pub async fn list_xattr(&self, path: &str) -> io::Result<Vec<String>> {
+        self.fs.list_xattr(path).await
+    }
+
+    /// Remove an extended attribute.
+    pub async fn remove_xattr(&self, path: &str, name: &str) -> io::Result<()> {
+        self.retry_on_deadlock(|| self.fs.remove_xattr(path, name))
+            .await
+    }
+
     /// Check whether a path exists.
     pub async fn exists(&self, path: &str) -> bool {
         self.fs.exists(path).await
@@ -192,11 +268,30 @@ impl<S: BlockStore + Send + Sync + 'static, M: MetaStore + 'static> VfsClient<S,
         Ok(StatFsSnapshot {
             total_space: snapshot.total_space,
             available_space: snapshot.avail_space,
+            used_space: snapshot.used_space,
             used_inodes: snapshot.used_inodes,
             available_inodes: snapshot.avail_inodes,
         })
     }
 
+    /// Get `statvfs`-style usage figures with the layout's block size.
+    pub async fn statfs(&self) -> io::Result<FsStats> {
+        let snapshot = self.fs.stat_fs().await?;
+        Ok(FsStats {
+            block_size: self.fs.layout().block_size as u64,
+            total_bytes: snapshot.total_space,
+            used_bytes: snapshot.used_space,
+            available_bytes: snapshot.avail_space,
+            total_inodes: snapshot.total_inodes,
+            used_inodes: snapshot.used_inodes,
+        })
+    }
+
+    /// Get write cache usage and block store cache counters.
+    pub async fn cache_stats(&self) -> CacheStats {
+        self.fs.cache_stats().await
+    }
+
     /// Create a hard link.
     pub async fn link(&self, existing: &str, link_path: &str) -> io::Result<FileAttr> {
         self.fs.link(existing, link_path).await?;
@@ -284,6 +379,7 @@ impl LocalClient {
 mod tests {
     use super::*;
     use crate::fs::CallerIdentity;
+    use crate::meta::config::WriteCacheConfig;
     use crate::vfs::fs::FileType;
     use tempfile::tempdir;
 
@@ -322,6 +418,66 @@ mod tests {
         assert!(st.size >= len as u64);
     }
 
+    #[tokio::test]
+    async fn test_sdk_write_cache_config() {
+        let tmp = tempdir().unwrap();
+        let write = WriteCacheConfig {
+            page_size: Some(16 * 1024),
+            block_size: Some(256 * 1024),
+            chunk_size: Some(4 * 1024 * 1024),
+            memory_budget: Some(32 * 1024 * 1024),
+        };
+        let config = FileSystemConfig::default()
+            .with_caller(CallerIdentity::root())
+            .with_write_cache(write.clone());
+        let cli = LocalClient::new_local_with_config(tmp.path(), ChunkLayout::default(), config)
+            .await
+            .expect("init LocalClient");
+        assert_eq!(cli.fs.layout().block_size, 256 * 1024);
+        assert_eq!(cli.fs.layout().chunk_size, 4 * 1024 * 1024);
+
+        cli.create_file("/tuned.bin", false).await.unwrap();
+        let data: Vec<u8> = (0..600 * 1024).map(|i| (i % 251) as u8).collect();
+        cli.write_at("/tuned.bin", 0, &data).await.unwrap();
+        let out = cli.read_at("/tuned.bin", 0, data.len()).await.unwrap();
+        assert_eq!(out, data);
+
+        let broken = FileSystemConfig::default().with_write_cache(WriteCacheConfig {
+            page_size: Some(3000),
+            ..write
+        });
+        let err = LocalClient::new_local_with_config(tmp.path(), ChunkLayout::default(), broken)
+            .await
+            .err()
+            .expect("page size must tile the block size");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[tokio::test]
+    async fn test_sdk_cache_stats() {
+        let layout = ChunkLayout::default();
+        let tmp = tempdir().unwrap();
+        let config = FileSystemConfig::default().with_caller(CallerIdentity::root());
+        let cli = LocalClient::new_local_with_config(tmp.path(), layout, config)
+            .await
+            .expect("init LocalClient");
+
+        cli.create_file("/stats.bin", false).await.unwrap();
+        let data = vec![9u8; layout.block_size as usize];
+        cli.write_at("/stats.bin", 0, &data).await.unwrap();
+
+        // Closing the file flushed its slices, so nothing stays in the write cache.
+        let stats = cli.cache_stats().await;
+        assert_eq!((stats.slices, stats.alloc_bytes), (0, 0));
+
+        let out = cli.read_at("/stats.bin", 0, data.len()).await.unwrap();
+        assert_eq!(out, data);
+
+        let stats = cli.cache_stats().await;
+        assert!(stats.block_cache_misses > 0);
+        assert_eq!(stats.block_cache_hits, 0);
+    }
+
     #[tokio::test]
     async fn test_sdk_local_ops_extras() {
         let layout = ChunkLayout::default();
@@ -26,6 +26,7 @@ pub async fn setup_test_fs() -> (TempDir, Arc<DatabaseMetaStore>, Arc<InMemoryBl
         cache: Default::default(),
         client: Default::default(),
         compact: Default::default(),
+        write: Default::default(),
     };
 
     let meta_store = Arc::new(DatabaseMetaStore::from_config(config).await.unwrap());
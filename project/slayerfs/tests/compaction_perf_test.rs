@@ -250,6 +250,7 @@ async fn setup_test_fs() -> (
                 cache: Default::default(),
                 client: Default::default(),
                 compact: Default::default(),
+                write: Default::default(),
             };
             let meta_store = Arc::new(DatabaseMetaStore::from_config(config).await.unwrap());
             meta_store.initialize().await.unwrap();
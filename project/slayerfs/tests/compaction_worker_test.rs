@@ -32,6 +32,7 @@ mod tests {
             cache: Default::default(),
             client: Default::default(),
             compact: Default::default(),
+            write: Default::default(),
         };
 
         let meta_store: Arc<DatabaseMetaStore> =
@@ -29,6 +29,7 @@ mod tests {
             cache: Default::default(),
             client: Default::default(),
             compact: Default::default(),
+            write: Default::default(),
         };
 
         let meta_store = Arc::new(DatabaseMetaStore::from_config(config).await.unwrap());